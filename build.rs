@@ -0,0 +1,113 @@
+//! Build script embedding build information into the binary.
+//!
+//! Sets the following variables for `env!`:
+//!
+//! - `COMMIT_WIZARD_GIT_SHA`: commit the binary was built from (`unknown`
+//!   outside a git checkout; packagers can set it explicitly)
+//! - `COMMIT_WIZARD_FEATURES`: comma-separated list of enabled cargo features
+//! - `COMMIT_WIZARD_TARGET`: target triple
+//! - `COMMIT_WIZARD_PROFILE`: build profile (`debug` or `release`)
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=COMMIT_WIZARD_GIT_SHA");
+    // Sources and the manifest keep the `-dirty` marker current; the index
+    // is not watched, since that would rebuild on every `git add`
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    for path in git_watch_paths() {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+
+    let git_sha = env::var("COMMIT_WIZARD_GIT_SHA")
+        .ok()
+        .filter(|sha| !sha.trim().is_empty())
+        .or_else(git_sha)
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=COMMIT_WIZARD_GIT_SHA={}", git_sha);
+
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(feature_name))
+        .filter(|feature| feature != "default")
+        .collect();
+    features.sort();
+    println!(
+        "cargo:rustc-env=COMMIT_WIZARD_FEATURES={}",
+        features.join(",")
+    );
+
+    let target = env::var("TARGET").unwrap_or_else(|_| "unknown".to_string());
+    println!("cargo:rustc-env=COMMIT_WIZARD_TARGET={}", target);
+    let profile = env::var("PROFILE").unwrap_or_else(|_| "unknown".to_string());
+    println!("cargo:rustc-env=COMMIT_WIZARD_PROFILE={}", profile);
+}
+
+/// Returns the short hash of `HEAD`, marked `-dirty` with local changes.
+fn git_sha() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let sha = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if sha.is_empty() {
+        return None;
+    }
+
+    let dirty = Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .is_some_and(|output| !output.stdout.is_empty());
+    Some(if dirty { format!("{}-dirty", sha) } else { sha })
+}
+
+/// Returns the git files that change with new commits or checkouts: `HEAD`
+/// of the git dir, and the branch refs of the common dir (they differ in
+/// worktrees, where `.git` is a file).
+///
+/// Returns nothing outside a git checkout, e.g. when building from a crate
+/// tarball, so Cargo does not rerun the script for paths that never exist.
+fn git_watch_paths() -> Vec<PathBuf> {
+    let Some(git_dir) = git_path("--git-dir") else {
+        return Vec::new();
+    };
+    let common_dir = git_path("--git-common-dir").unwrap_or_else(|| git_dir.clone());
+    [
+        git_dir.join("HEAD"),
+        common_dir.join("refs").join("heads"),
+        common_dir.join("packed-refs"),
+    ]
+    .into_iter()
+    .filter(|path| path.exists())
+    .collect()
+}
+
+/// Returns a directory printed by `git rev-parse <flag>`, made absolute.
+fn git_path(flag: &str) -> Option<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", flag])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let path = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if path.is_empty() {
+        return None;
+    }
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        Some(path)
+    } else {
+        env::current_dir().ok().map(|dir| dir.join(path))
+    }
+}
+
+/// Converts `CARGO_FEATURE_VENDORED_OPENSSL` style names back to
+/// `vendored-openssl`.
+fn feature_name(var: &str) -> String {
+    var.to_lowercase().replace('_', "-")
+}
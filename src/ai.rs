@@ -1,61 +1,25 @@
-//! AI-powered commit message generation using GitHub Copilot API.
+//! AI-powered commit message generation using GitHub Models or OpenAI.
 //!
-//! This module integrates with GitHub's Copilot API to generate conventional
-//! commit messages based on file changes and diffs.
+//! This module generates conventional commit messages based on file changes
+//! and diffs. Requests go through [`HttpChatProvider`], the same client the
+//! provider abstraction uses.
 
 use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
-use std::env;
-use std::time::Duration;
 
+use crate::provider::{AiProvider, HttpChatProvider};
+use crate::retry::{RetryPolicy, RetryingProvider};
 use crate::types::{ChangeGroup, ChangedFile};
 
-/// GitHub Models API endpoint for chat completions
-/// See: <https://docs.github.com/en/github-models>
-const GITHUB_MODELS_API_URL: &str = "https://models.github.com/chat/completions";
-
-/// OpenAI API endpoint (fallback)
-const OPENAI_API_URL: &str = "https://api.openai.com/v1/chat/completions";
-
-/// Timeout for API requests (30 seconds)
-const API_TIMEOUT: Duration = Duration::from_secs(30);
-
-/// Request structure for GitHub Copilot API
-#[derive(Debug, Serialize)]
-struct CopilotRequest {
-    messages: Vec<Message>,
-    model: String,
-    temperature: f32,
-    max_tokens: u32,
-}
-
-/// Request structure for OpenAI API
-#[derive(Debug, Serialize)]
-struct OpenAIRequest {
-    model: String,
-    messages: Vec<Message>,
-    temperature: f32,
-    max_completion_tokens: u32,
-}
-
-/// Message in the chat conversation
-#[derive(Debug, Serialize, Deserialize)]
-struct Message {
-    role: String,
-    content: String,
-}
-
-/// Response from GitHub Copilot API
-#[derive(Debug, Deserialize)]
-struct CopilotResponse {
-    choices: Vec<Choice>,
-}
-
-/// Individual choice from the API response
-#[derive(Debug, Deserialize)]
-struct Choice {
-    message: Message,
-}
+/// Instructions sent ahead of the change description
+const SYSTEM_PROMPT: &str = "You are a commit message generator. Follow these rules: \
+                             - Use imperative mood: 'add feature' NOT 'added feature' \
+                             - Keep description concise and factual \
+                             - Do NOT include type/scope prefix (feat:, fix:, etc.) \
+                             - Start with a lowercase verb \
+                             - No period at the end of description \
+                             - If providing a body, separate it with a blank line \
+                             - Body should use bullet points starting with '-' \
+                             - Mention breaking changes if applicable";
 
 /// Generates a commit message using GitHub Copilot API.
 ///
@@ -111,242 +75,35 @@ pub fn generate_commit_message(
     diff: Option<&str>,
 ) -> Result<(String, Option<String>)> {
     // Try GitHub token first, then OpenAI
-    let (token, api_url, api, model) = if let Some(gh_token) = get_github_token() {
-        (
-            gh_token,
-            GITHUB_MODELS_API_URL,
-            "GitHub Models",
-            env::var("GITHUB_COPILOT_MODEL").unwrap_or_else(|_| "gpt-4".to_string()),
-        )
-    } else if let Some(openai_token) = get_openai_token() {
-        (
-            openai_token,
-            OPENAI_API_URL,
-            "OpenAI",
-            env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4.1-2025-04-14".to_string()),
-        )
-    } else {
-        anyhow::bail!(
-            "No API token found. Set one of:\n\
-             - GITHUB_TOKEN or GH_TOKEN (for GitHub Models API)\n\
-             - OPENAI_API_KEY (for OpenAI API)\n\n\
-             Create GitHub token at: https://github.com/settings/tokens\n\
-             Create OpenAI key at: https://platform.openai.com/api-keys"
-        );
-    };
-
-    let content_message = "You are a commit message generator. Follow these rules: \
-                          - Use imperative mood: 'add feature' NOT 'added feature' \
-                          - Keep description concise and factual \
-                          - Do NOT include type/scope prefix (feat:, fix:, etc.) \
-                          - Start with a lowercase verb \
-                          - No period at the end of description \
-                          - If providing a body, separate it with a blank line \
-                          - Body should use bullet points starting with '-' \
-                          - Mention breaking changes if applicable"
-        .to_string();
-
-    if api == "GitHub Models" {
-        github_models_api_call(group, files, diff, content_message, token, api_url, model)
-    } else if api == "OpenAI" {
-        openai_api_call(group, files, diff, content_message, token, api_url, model)
-    } else {
-        anyhow::bail!("Unsupported API: {}", api);
-    }
-}
-
-/// API configuration parameters
-pub struct ApiConfig {
-    pub token: String,
-    pub api_url: String,
-    pub model: String,
-    pub system_prompt: String,
-}
-
-/// Makes an API call to OpenAI to generate commit message.
-fn openai_api_call(
-    group: &ChangeGroup,
-    files: &[ChangedFile],
-    diff: Option<&str>,
-    content_string: String,
-    token: String,
-    api_url: &str,
-    openai_model: String,
-) -> Result<(String, Option<String>)> {
-    let client = reqwest::blocking::Client::builder()
-        .timeout(API_TIMEOUT)
-        .build()
-        .context("Failed to create HTTP client")?;
-
-    let config = ApiConfig {
-        token,
-        api_url: api_url.to_string(),
-        model: openai_model,
-        system_prompt: content_string,
-    };
-
-    openai_api_call_with_client(&client, group, files, diff, &config)
+    let provider = [
+        HttpChatProvider::github_models(None),
+        HttpChatProvider::openai(None),
+    ]
+    .into_iter()
+    .find(|provider| provider.is_available())
+    .context(
+        "No API token found. Set one of:\n\
+         - GITHUB_TOKEN or GH_TOKEN (for GitHub Models API)\n\
+         - OPENAI_API_KEY (for OpenAI API)\n\n\
+         Create GitHub token at: https://github.com/settings/tokens\n\
+         Create OpenAI key at: https://platform.openai.com/api-keys",
+    )?;
+
+    // Rate limits and server errors are usually transient
+    let provider = RetryingProvider::new(Box::new(provider), RetryPolicy::default());
+    generate_with_provider(&provider, group, files, diff)
 }
 
-/// Makes an API call to OpenAI with a provided HTTP client (for testing).
+/// Generates a commit message with `provider` (for testing).
 #[doc(hidden)]
-pub fn openai_api_call_with_client(
-    client: &reqwest::blocking::Client,
+pub fn generate_with_provider(
+    provider: &dyn AiProvider,
     group: &ChangeGroup,
     files: &[ChangedFile],
     diff: Option<&str>,
-    config: &ApiConfig,
 ) -> Result<(String, Option<String>)> {
-    let prompt = build_prompt(group, files, diff);
-    let request = OpenAIRequest {
-        model: config.model.clone(),
-        messages: vec![
-            Message {
-                role: "system".to_string(),
-                content: config.system_prompt.clone(),
-            },
-            Message {
-                role: "user".to_string(),
-                content: prompt,
-            },
-        ],
-        temperature: 0.3,
-        max_completion_tokens: 200,
-    };
-
-    let response = client
-        .post(&config.api_url)
-        .header("Authorization", format!("Bearer {}", config.token))
-        .header("Content-Type", "application/json")
-        .json(&request)
-        .send()
-        .context("Failed to send request to OpenAI API")?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().unwrap_or_default();
-        anyhow::bail!("OpenAI API returned error {}: {}", status, body);
-    }
-
-    let openai_response: CopilotResponse = response
-        .json()
-        .context("Failed to parse OpenAI API response")?;
-
-    parse_commit_message(
-        openai_response
-            .choices
-            .first()
-            .context("No response from OpenAI API")?
-            .message
-            .content
-            .as_str(),
-    )
-}
-/// Makes an API call to GitHub Models API to generate commit message.
-fn github_models_api_call(
-    group: &ChangeGroup,
-    files: &[ChangedFile],
-    diff: Option<&str>,
-    content_string: String,
-    token: String,
-    api_url: &str,
-    github_copilot_model: String,
-) -> Result<(String, Option<String>)> {
-    let client = reqwest::blocking::Client::builder()
-        .timeout(API_TIMEOUT)
-        .build()
-        .context("Failed to create HTTP client")?;
-
-    let config = ApiConfig {
-        token,
-        api_url: api_url.to_string(),
-        model: github_copilot_model,
-        system_prompt: content_string,
-    };
-
-    github_models_api_call_with_client(&client, group, files, diff, &config)
-}
-
-/// Makes an API call to GitHub Models with a provided HTTP client (for testing).
-#[doc(hidden)]
-pub fn github_models_api_call_with_client(
-    client: &reqwest::blocking::Client,
-    group: &ChangeGroup,
-    files: &[ChangedFile],
-    diff: Option<&str>,
-    config: &ApiConfig,
-) -> Result<(String, Option<String>)> {
-    let prompt = build_prompt(group, files, diff);
-    let request = CopilotRequest {
-        messages: vec![
-            Message {
-                role: "system".to_string(),
-                content: config.system_prompt.clone(),
-            },
-            Message {
-                role: "user".to_string(),
-                content: prompt,
-            },
-        ],
-        model: config.model.clone(),
-        temperature: 0.3,
-        max_tokens: 200,
-    };
-
-    let response = client
-        .post(&config.api_url)
-        .header("Authorization", format!("Bearer {}", config.token))
-        .header("Content-Type", "application/json")
-        .json(&request)
-        .send()
-        .context("Failed to send request to GitHub Copilot API")?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().unwrap_or_default();
-        anyhow::bail!("GitHub Copilot API returned error {}: {}", status, body);
-    }
-
-    let copilot_response: CopilotResponse = response
-        .json()
-        .context("Failed to parse GitHub Copilot API response")?;
-
-    parse_commit_message(
-        copilot_response
-            .choices
-            .first()
-            .context("No response from GitHub Copilot API")?
-            .message
-            .content
-            .as_str(),
-    )
-}
-
-/// Retrieves the GitHub token from environment variables.
-///
-/// Checks `GITHUB_TOKEN` first, then falls back to `GH_TOKEN`.
-fn get_github_token() -> Option<String> {
-    // Example logic
-    let token = std::env::var("GITHUB_TOKEN")
-        .ok()
-        .filter(|t| !t.trim().is_empty())
-        .or_else(|| {
-            std::env::var("GH_TOKEN")
-                .ok()
-                .filter(|t| !t.trim().is_empty())
-        });
-
-    token
-}
-
-/// Retrieves the OpenAI API key from environment variables.
-fn get_openai_token() -> Option<String> {
-    // Example logic
-    let token = std::env::var("OPENAI_API_KEY")
-        .ok()
-        .filter(|t| !t.trim().is_empty());
-
-    token
+    let prompt = format!("{}\n\n{}", SYSTEM_PROMPT, build_prompt(group, files, diff));
+    parse_commit_message(&provider.complete(&prompt)?)
 }
 
 /// Builds the prompt for the AI based on change context.
@@ -377,13 +134,9 @@ pub fn build_prompt(group: &ChangeGroup, files: &[ChangedFile], diff: Option<&st
 
     if let Some(diff_content) = diff {
         prompt.push_str("\nDiff (first 1000 chars):\n");
-        let truncated = if diff_content.len() > 1000 {
-            &diff_content[..1000]
-        } else {
-            diff_content
-        };
+        let truncated = crate::prompt::truncate_chars(diff_content, 1000);
         prompt.push_str(truncated);
-        if diff_content.len() > 1000 {
+        if truncated.len() < diff_content.len() {
             prompt.push_str("\n... (truncated)");
         }
     }
@@ -0,0 +1,514 @@
+//! Reference graph between changed files for dependency-aware grouping.
+//!
+//! Path heuristics put `src/lib.rs` and `src/parser.rs` into different groups
+//! when they get different scopes, even if `lib.rs` just gained
+//! `mod parser;`. This module reads the changed files and links those that
+//! reference each other:
+//!
+//! - Rust: `mod name;` and `use crate::`, `self::`, `super::` paths;
+//!   integration tests also link through `use my_crate::...` paths
+//! - JavaScript/TypeScript: relative `import`, `export ... from`, and
+//!   `require()` specifiers
+//! - Python: `import a.b` and `from a.b import c`, absolute or relative
+//! - tests and the module they test by file name (`tests/parser_tests.rs`,
+//!   `parser.test.ts`, `test_parser.py`, `parser_test.go`)
+//!
+//! The same scanning finds untracked files that tracked changes start to
+//! reference (see [`untracked_references`]), so they are not left out of
+//! the commit that needs them.
+//!
+//! Linked files form components; [`crate::inference::build_groups`] commits
+//! each component as a whole. [`crate::history`] adds links between files
+//! that past commits changed together. Only references between changed files matter,
+//! so the graph never leaves the change set.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::types::ChangedFile;
+
+/// File extensions tried for extensionless JavaScript/TypeScript imports.
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// Compiled reference patterns.
+#[derive(Debug)]
+pub struct ReferenceScanner {
+    rust_mod: Regex,
+    rust_use: Regex,
+    js_import: Regex,
+    py_from: Regex,
+    py_import: Regex,
+}
+
+impl Default for ReferenceScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReferenceScanner {
+    /// Compiles the reference patterns.
+    pub fn new() -> Self {
+        let compile = |pattern: &str| Regex::new(pattern).expect("valid reference pattern");
+        Self {
+            rust_mod: compile(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)\s*;"),
+            rust_use: compile(r"\buse\s+((?:\w+::)+)"),
+            js_import: compile(
+                r#"(?:\bfrom\s*|\bimport\s*\(?\s*|\brequire\s*\(\s*)['"](\.{1,2}/[^'"]*)['"]"#,
+            ),
+            py_from: compile(r"(?m)^\s*from\s+(\.*)([\w.]*)\s+import\s+([\w, ]+)"),
+            py_import: compile(r"(?m)^\s*import\s+([\w.]+(?:\s*,\s*[\w.]+)*)"),
+        }
+    }
+
+    /// Returns the files of `changed` that `content`, the content of `path`,
+    /// references.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use commit_wizard::analysis::ReferenceScanner;
+    ///
+    /// let changed: HashSet<String> = ["src/lib.rs", "src/parser.rs", "web/api.ts"]
+    ///     .iter()
+    ///     .map(|p| p.to_string())
+    ///     .collect();
+    /// let scanner = ReferenceScanner::new();
+    ///
+    /// assert_eq!(
+    ///     scanner.references("src/lib.rs", "pub mod parser;\n", &changed),
+    ///     ["src/parser.rs"]
+    /// );
+    /// assert_eq!(
+    ///     scanner.references("web/app.ts", "import { get } from './api';\n", &changed),
+    ///     ["web/api.ts"]
+    /// );
+    /// ```
+    pub fn references(&self, path: &str, content: &str, changed: &HashSet<String>) -> Vec<String> {
+        if !Self::scans(path) {
+            return Vec::new();
+        }
+        let candidates = match extension(path) {
+            "rs" => self.rust_candidates(path, content),
+            "py" => self.python_candidates(path, content),
+            _ => self.js_candidates(path, content),
+        };
+
+        let mut found: Vec<String> = candidates
+            .into_iter()
+            .filter(|candidate| candidate != path && changed.contains(candidate))
+            .collect();
+        found.sort();
+        found.dedup();
+        found
+    }
+
+    /// Returns whether references are looked for in files like `path`.
+    pub fn scans(path: &str) -> bool {
+        matches!(extension(path), "rs" | "py") || JS_EXTENSIONS.contains(&extension(path))
+    }
+
+    fn rust_candidates(&self, path: &str, content: &str) -> Vec<String> {
+        let (dir, stem) = split_path(path);
+        // Directory of the modules this file declares
+        let module_dir = if matches!(stem, "lib" | "main" | "mod") {
+            dir.to_string()
+        } else {
+            join(dir, stem)
+        };
+
+        let mut candidates = Vec::new();
+        for caps in self.rust_mod.captures_iter(content) {
+            candidates.extend(rust_module_files(&module_dir, &[&caps[1]]));
+        }
+
+        let crate_root = rust_crate_root(path);
+        let in_tests = path.starts_with("tests/") || path.contains("/tests/");
+        for caps in self.rust_use.captures_iter(content) {
+            let segments: Vec<&str> = caps[1].split("::").filter(|s| !s.is_empty()).collect();
+            let Some((first, rest)) = segments.split_first() else {
+                continue;
+            };
+            let (base, rest) = match *first {
+                "crate" => (crate_root.clone(), rest),
+                "self" => (Some(module_dir.clone()), rest),
+                "super" if stem == "mod" => (parent(dir).map(str::to_string), rest),
+                "super" => (Some(dir.to_string()), rest),
+                "std" | "core" | "alloc" => continue,
+                // Integration tests reach the crate under its own name
+                _ if in_tests => (crate_root.clone(), rest),
+                _ => continue,
+            };
+            let Some(base) = base else {
+                continue;
+            };
+            if *first == "super" {
+                // The parent module itself
+                candidates.extend(rust_module_files(&base, &[]));
+                candidates.extend(["lib", "main"].map(|root| join(&base, &format!("{}.rs", root))));
+            }
+            for len in 1..=rest.len() {
+                candidates.extend(rust_module_files(&base, &rest[..len]));
+            }
+        }
+        candidates
+    }
+
+    fn js_candidates(&self, path: &str, content: &str) -> Vec<String> {
+        let (dir, _) = split_path(path);
+        let mut candidates = Vec::new();
+        for caps in self.js_import.captures_iter(content) {
+            let Some(target) = normalize(&join(dir, &caps[1])) else {
+                continue;
+            };
+            candidates.push(target.clone());
+            for ext in JS_EXTENSIONS {
+                candidates.push(format!("{}.{}", target, ext));
+                candidates.push(format!("{}/index.{}", target, ext));
+            }
+        }
+        candidates
+    }
+
+    fn python_candidates(&self, path: &str, content: &str) -> Vec<String> {
+        let (dir, _) = split_path(path);
+        let mut candidates = Vec::new();
+
+        for caps in self.py_from.captures_iter(content) {
+            let dots = caps[1].len();
+            let module = &caps[2];
+            let bases = if dots == 0 {
+                python_roots()
+            } else {
+                let mut base = Some(dir.to_string());
+                for _ in 1..dots {
+                    base = base.and_then(|b| parent(&b).map(str::to_string));
+                }
+                base.into_iter().collect()
+            };
+            for base in bases {
+                let module_dir = join(&base, &module.replace('.', "/"));
+                if !module.is_empty() {
+                    candidates.extend(python_module_files(&module_dir));
+                }
+                // `from pkg import module` may name a submodule
+                for name in caps[3].split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    let name = name.split_whitespace().next().unwrap_or(name);
+                    candidates.extend(python_module_files(&join(&module_dir, name)));
+                }
+            }
+        }
+
+        for caps in self.py_import.captures_iter(content) {
+            for module in caps[1].split(',').map(str::trim) {
+                for base in python_roots() {
+                    candidates.extend(python_module_files(&join(&base, &module.replace('.', "/"))));
+                }
+            }
+        }
+        candidates
+    }
+}
+
+/// Returns the changed file a test file tests, found by file name.
+///
+/// The module must be the only changed non-test file with the tested name;
+/// one in the same directory (or the parent of a `__tests__` directory) is
+/// preferred.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use commit_wizard::analysis::tested_module;
+///
+/// let changed: HashSet<String> = ["src/parser.rs", "tests/parser_tests.rs", "web/cart.test.ts", "web/cart.ts"]
+///     .iter()
+///     .map(|p| p.to_string())
+///     .collect();
+///
+/// assert_eq!(tested_module("tests/parser_tests.rs", &changed), Some("src/parser.rs".to_string()));
+/// assert_eq!(tested_module("web/cart.test.ts", &changed), Some("web/cart.ts".to_string()));
+/// assert_eq!(tested_module("src/parser.rs", &changed), None);
+/// ```
+pub fn tested_module(path: &str, changed: &HashSet<String>) -> Option<String> {
+    let name = tested_name(path)?;
+    let (dir, _) = split_path(path);
+    let preferred_dir = dir.strip_suffix("/__tests__").unwrap_or(dir);
+
+    let matches: Vec<&String> = changed
+        .iter()
+        .filter(|candidate| candidate.as_str() != path && tested_name(candidate).is_none())
+        .filter(|candidate| {
+            let (candidate_dir, stem) = split_path(candidate);
+            extension(candidate) == extension(path)
+                && (stem == name
+                    || (stem == "mod" && candidate_dir.rsplit('/').next() == Some(name)))
+        })
+        .collect();
+
+    if let Some(local) = matches
+        .iter()
+        .find(|candidate| split_path(candidate).0 == preferred_dir)
+    {
+        return Some(local.to_string());
+    }
+    match matches.as_slice() {
+        [only] => Some(only.to_string()),
+        _ => None,
+    }
+}
+
+/// Links changed files that reference each other.
+///
+/// Contents are read from the working tree below `root`; deleted and
+/// unreadable files only take part as reference targets. See
+/// [`link_related`] for how links are recorded.
+pub fn link_files(root: &Path, files: &mut [ChangedFile]) {
+    let edges = reference_edges(root, files);
+    link_related(files, edges);
+}
+
+/// Returns the pairs of changed files where the first references the second.
+///
+/// Submodules and files with a commit type from `[rules]` are left out.
+pub fn reference_edges(root: &Path, files: &[ChangedFile]) -> Vec<(String, String)> {
+    let changed = linkable_paths(files);
+    let scanner = ReferenceScanner::new();
+
+    let mut edges = Vec::new();
+    for path in &changed {
+        let mut targets: Vec<String> = tested_module(path, &changed).into_iter().collect();
+        if let Ok(content) = fs::read_to_string(root.join(path)) {
+            targets.extend(scanner.references(path, &content, &changed));
+        }
+        for target in targets {
+            log::debug!("{} references {}", path, target);
+            edges.push((path.clone(), target));
+        }
+    }
+    edges
+}
+
+/// Finds the untracked files that lines added by tracked changes reference,
+/// e.g. the `src/parser.rs` of a new `mod parser;`. Committing the change
+/// without them would leave a broken commit.
+///
+/// # Arguments
+///
+/// * `patches` - Unified diffs of the tracked changes, by path
+/// * `untracked` - Paths of the untracked files
+///
+/// # Returns
+///
+/// The referenced untracked paths, each with the sorted paths of the
+/// tracked files that reference it.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use commit_wizard::analysis::untracked_references;
+///
+/// let untracked: HashSet<String> = ["src/parser.rs".to_string()].into();
+/// let patch = "@@ -1 +1,2 @@\n mod cli;\n+mod parser;\n";
+/// let references = untracked_references([("src/lib.rs", patch)], &untracked);
+/// assert_eq!(references["src/parser.rs"], ["src/lib.rs"]);
+/// ```
+pub fn untracked_references<'a>(
+    patches: impl IntoIterator<Item = (&'a str, &'a str)>,
+    untracked: &HashSet<String>,
+) -> BTreeMap<String, Vec<String>> {
+    let scanner = ReferenceScanner::new();
+    let mut references: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (path, patch) in patches {
+        if !ReferenceScanner::scans(path) {
+            continue;
+        }
+        let added: Vec<&str> = patch
+            .lines()
+            .filter(|line| !line.starts_with("+++"))
+            .filter_map(|line| line.strip_prefix('+'))
+            .collect();
+        for target in scanner.references(path, &added.join("\n"), untracked) {
+            log::debug!("{} references untracked {}", path, target);
+            references.entry(target).or_default().push(path.to_string());
+        }
+    }
+    for sources in references.values_mut() {
+        sources.sort();
+        sources.dedup();
+    }
+    references
+}
+
+/// Links the changed files connected by `edges` into components.
+///
+/// Every file of a component of two or more linked files gets the smallest
+/// path of the component as [`ChangedFile::related_to`]. Submodules, files
+/// with a commit type from `[rules]`, and paths that are not changed are
+/// left out.
+pub fn link_related(files: &mut [ChangedFile], edges: impl IntoIterator<Item = (String, String)>) {
+    let changed = linkable_paths(files);
+
+    // Union-find over paths; the root of a set is its smallest path
+    let mut parents: BTreeMap<String, String> = changed
+        .iter()
+        .map(|path| (path.clone(), path.clone()))
+        .collect();
+    for (from, to) in edges {
+        if !changed.contains(&from) || !changed.contains(&to) {
+            continue;
+        }
+        let a = find(&mut parents, &from);
+        let b = find(&mut parents, &to);
+        if a != b {
+            let (small, large) = if a < b { (a, b) } else { (b, a) };
+            parents.insert(large, small);
+        }
+    }
+
+    let mut sizes: BTreeMap<String, usize> = BTreeMap::new();
+    let roots: Vec<(String, String)> = changed
+        .iter()
+        .map(|path| (path.clone(), find(&mut parents, path)))
+        .collect();
+    for (_, root) in &roots {
+        *sizes.entry(root.clone()).or_default() += 1;
+    }
+    let roots: BTreeMap<String, String> = roots.into_iter().collect();
+    for file in files.iter_mut() {
+        file.related_to = roots
+            .get(&file.path)
+            .filter(|root| sizes[*root] > 1)
+            .cloned();
+    }
+}
+
+/// Returns the paths of the files that can be linked.
+fn linkable_paths(files: &[ChangedFile]) -> HashSet<String> {
+    files
+        .iter()
+        .filter(|file| !file.is_submodule() && file.commit_type.is_none())
+        .map(|file| file.path.clone())
+        .collect()
+}
+
+/// Returns the representative of `path`'s set.
+fn find(parents: &mut BTreeMap<String, String>, path: &str) -> String {
+    let mut current = path.to_string();
+    while parents[&current] != current {
+        current = parents[&current].clone();
+    }
+    parents.insert(path.to_string(), current.clone());
+    current
+}
+
+/// Returns the name of the module a test file tests, if `path` is one.
+fn tested_name(path: &str) -> Option<&str> {
+    let (dir, stem) = split_path(path);
+    let in_tests_dir = dir == "tests" || dir.ends_with("/tests");
+    match extension(path) {
+        "rs" if in_tests_dir => Some(
+            stem.strip_suffix("_tests")
+                .or_else(|| stem.strip_suffix("_test"))
+                .unwrap_or(stem),
+        ),
+        "rs" => None,
+        "py" => stem
+            .strip_prefix("test_")
+            .or_else(|| stem.strip_suffix("_test")),
+        "go" => stem.strip_suffix("_test"),
+        ext if JS_EXTENSIONS.contains(&ext) => stem
+            .strip_suffix(".test")
+            .or_else(|| stem.strip_suffix(".spec"))
+            .or_else(|| dir.ends_with("__tests__").then_some(stem)),
+        _ => None,
+    }
+    .filter(|name| !name.is_empty())
+}
+
+/// Returns the candidate files of the Rust module `segments` below `base`.
+fn rust_module_files(base: &str, segments: &[&str]) -> [String; 2] {
+    let module = join(base, &segments.join("/"));
+    [format!("{}.rs", module), join(&module, "mod.rs")]
+}
+
+/// Returns the `src` directory of the crate containing `path`.
+fn rust_crate_root(path: &str) -> Option<String> {
+    if path.starts_with("src/") {
+        return Some("src".to_string());
+    }
+    if let Some(idx) = path.find("/src/") {
+        return Some(path[..idx + 4].to_string());
+    }
+    // Integration tests: `<crate>/tests/x.rs` → `<crate>/src`
+    if path.starts_with("tests/") {
+        return Some("src".to_string());
+    }
+    path.find("/tests/")
+        .map(|idx| format!("{}/src", &path[..idx]))
+}
+
+/// Directories absolute Python imports are resolved against.
+fn python_roots() -> Vec<String> {
+    vec![String::new(), "src".to_string()]
+}
+
+/// Returns the candidate files of the Python module at `module`.
+fn python_module_files(module: &str) -> [String; 2] {
+    [format!("{}.py", module), format!("{}/__init__.py", module)]
+}
+
+/// Splits a path into its directory and its file name without the last
+/// extension.
+fn split_path(path: &str) -> (&str, &str) {
+    let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    (dir, stem)
+}
+
+/// Returns the extension of `path`, or `""`.
+fn extension(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name.rsplit_once('.').map_or("", |(_, ext)| ext)
+}
+
+/// Returns the parent directory of `dir`, or [`None`] at the root.
+fn parent(dir: &str) -> Option<&str> {
+    if dir.is_empty() {
+        return None;
+    }
+    Some(dir.rsplit_once('/').map_or("", |(parent, _)| parent))
+}
+
+/// Joins two relative paths.
+fn join(base: &str, path: &str) -> String {
+    match (base.is_empty(), path.is_empty()) {
+        (true, _) => path.to_string(),
+        (_, true) => base.to_string(),
+        _ => format!("{}/{}", base, path),
+    }
+}
+
+/// Resolves `.` and `..` segments; [`None`] if the path leaves the root.
+fn normalize(path: &str) -> Option<String> {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
@@ -0,0 +1,168 @@
+//! Per-session AI budget.
+//!
+//! Working through dozens of groups can add up to many AI requests. An
+//! [`AiBudget`] caps the number of requests and the total time spent waiting
+//! for responses in one session. Once the budget is used up, requests fail
+//! with [`BudgetExceeded`] and callers fall back to heuristics.
+//!
+//! A request that is already running is never interrupted; the time limit
+//! only stops further requests.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::config::AiConfig;
+use crate::provider::{AiProvider, ChatMessage, ProviderLayer};
+
+/// Error returned for requests made after the budget is used up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    /// Requests made in this session
+    pub requests: usize,
+    /// Time spent waiting for AI responses in this session
+    pub time: Duration,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "AI budget for this session is used up ({} request(s), {}s)",
+            self.requests,
+            self.time.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Limits on AI usage in one session, shared by all requests.
+#[derive(Debug, Default)]
+pub struct AiBudget {
+    max_requests: Option<usize>,
+    max_time: Option<Duration>,
+    usage: Mutex<Usage>,
+}
+
+#[derive(Debug, Default)]
+struct Usage {
+    requests: usize,
+    time: Duration,
+}
+
+impl AiBudget {
+    /// Creates a budget; [`None`] means no limit.
+    pub fn new(max_requests: Option<usize>, max_time: Option<Duration>) -> Self {
+        Self {
+            max_requests,
+            max_time,
+            usage: Mutex::new(Usage::default()),
+        }
+    }
+
+    /// Creates the budget configured in `[ai]`.
+    ///
+    /// # Returns
+    ///
+    /// The budget, or [`None`] if neither limit is configured.
+    pub fn from_config(config: &AiConfig) -> Option<Self> {
+        if config.max_requests.is_none() && config.max_seconds.is_none() {
+            return None;
+        }
+        Some(Self::new(
+            config.max_requests,
+            config.max_seconds.map(Duration::from_secs),
+        ))
+    }
+
+    /// Reserves a request.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BudgetExceeded`] if the request or time limit is reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::budget::AiBudget;
+    ///
+    /// let budget = AiBudget::new(Some(1), None);
+    /// assert!(budget.start_request().is_ok());
+    /// assert!(budget.start_request().is_err());
+    /// ```
+    pub fn start_request(&self) -> Result<(), BudgetExceeded> {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        let over_requests = self.max_requests.is_some_and(|max| usage.requests >= max);
+        let over_time = self.max_time.is_some_and(|max| usage.time >= max);
+        if over_requests || over_time {
+            return Err(BudgetExceeded {
+                requests: usage.requests,
+                time: usage.time,
+            });
+        }
+        usage.requests += 1;
+        Ok(())
+    }
+
+    /// Adds the duration of a finished request.
+    pub fn record_time(&self, elapsed: Duration) {
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        usage.time += elapsed;
+    }
+
+    /// Returns the requests made and the time spent so far.
+    pub fn usage(&self) -> (usize, Duration) {
+        let usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        (usage.requests, usage.time)
+    }
+}
+
+/// Provider that enforces an [`AiBudget`] on every request.
+pub struct BudgetedProvider {
+    inner: Box<dyn AiProvider>,
+    budget: Arc<AiBudget>,
+}
+
+impl BudgetedProvider {
+    /// Wraps `inner` so its requests count against `budget`.
+    pub fn new(inner: Box<dyn AiProvider>, budget: Arc<AiBudget>) -> Self {
+        Self { inner, budget }
+    }
+
+    /// Runs one request against the budget.
+    fn metered<T>(&self, request: impl FnOnce(&dyn AiProvider) -> Result<T>) -> Result<T> {
+        self.budget.start_request()?;
+        let started = Instant::now();
+        let result = request(self.inner.as_ref());
+        self.budget.record_time(started.elapsed());
+
+        let (requests, time) = self.budget.usage();
+        log::debug!("AI budget used: {} request(s), {:?}", requests, time);
+        result
+    }
+}
+
+impl ProviderLayer for BudgetedProvider {
+    fn inner(&self) -> &dyn AiProvider {
+        self.inner.as_ref()
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.metered(|inner| inner.complete(prompt))
+    }
+
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        self.metered(|inner| inner.chat(messages))
+    }
+
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        self.metered(|inner| inner.complete_json(prompt))
+    }
+
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        self.metered(|inner| inner.complete_n(prompt, n))
+    }
+}
@@ -0,0 +1,101 @@
+//! Build information for bug reports.
+//!
+//! The build script records the commit, enabled features, target, and
+//! profile of the build. Together with the crate version and the linked
+//! libgit2 version this is shown by `--version --verbose` and
+//! `--version-json`.
+
+use std::fmt;
+
+use serde::Serialize;
+
+/// Details about how this binary was built.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    /// Crate version
+    pub version: String,
+    /// Commit the binary was built from, or `unknown`
+    pub git_sha: String,
+    /// Enabled cargo features
+    pub features: Vec<String>,
+    /// Version of the linked libgit2
+    pub libgit2_version: String,
+    /// Whether libgit2 is vendored or provided by the system
+    pub libgit2_vendored: bool,
+    /// Target triple
+    pub target: String,
+    /// Build profile (`debug` or `release`)
+    pub profile: String,
+}
+
+impl BuildInfo {
+    /// Returns the information of the running binary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::build_info::BuildInfo;
+    ///
+    /// let info = BuildInfo::current();
+    /// assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    /// ```
+    pub fn current() -> Self {
+        let libgit2 = git2::Version::get();
+        let (major, minor, patch) = libgit2.libgit2_version();
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_sha: env!("COMMIT_WIZARD_GIT_SHA").to_string(),
+            features: env!("COMMIT_WIZARD_FEATURES")
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .map(str::to_string)
+                .collect(),
+            libgit2_version: format!("{}.{}.{}", major, minor, patch),
+            libgit2_vendored: libgit2.vendored(),
+            target: env!("COMMIT_WIZARD_TARGET").to_string(),
+            profile: env!("COMMIT_WIZARD_PROFILE").to_string(),
+        }
+    }
+
+    /// Returns the one-line version, e.g. `commit-wizard 0.1.0`.
+    pub fn short(&self) -> String {
+        format!("commit-wizard {}", self.version)
+    }
+
+    /// Serializes the information as pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails.
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.short())?;
+        writeln!(f, "commit:   {}", self.git_sha)?;
+        writeln!(
+            f,
+            "features: {}",
+            if self.features.is_empty() {
+                "(none)".to_string()
+            } else {
+                self.features.join(", ")
+            }
+        )?;
+        writeln!(
+            f,
+            "libgit2:  {} ({})",
+            self.libgit2_version,
+            if self.libgit2_vendored {
+                "vendored"
+            } else {
+                "system"
+            }
+        )?;
+        writeln!(f, "target:   {}", self.target)?;
+        write!(f, "profile:  {}", self.profile)
+    }
+}
@@ -0,0 +1,566 @@
+//! Fix-likelihood scoring for changed files.
+//!
+//! Heuristic grouping has no way to tell a bug fix from a feature by path
+//! alone. This module scores each modified file from three signals:
+//!
+//! - bug-related keywords (`panic`, `null`, `off-by-one`, ...) in changed lines
+//! - the shape of the change (small edits that replace lines rather than
+//!   adding new code)
+//! - blame data: how much of the replaced code was written recently
+//!
+//! Blame is the expensive signal: it only covers the replaced line range
+//! and the last [`RECENT_DAYS`] days of history, and a [`BlameBudget`]
+//! bounds how many files are blamed and for how long.
+//!
+//! The score turns `feat` into `fix` in heuristic grouping and is passed to
+//! the AI in the grouping prompt. Changed test assertions count as keywords.
+//!
+//! [`classify_files`] then suggests a commit type per file, also using the
+//! branch prefix (`fix/`, `hotfix/`, `perf/`, `refactor/`), performance
+//! keywords, and whether the diff adds declarations or only edits existing
+//! ones: a change that adds no function is more likely a fix, refactoring,
+//! or optimization than a feature.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use git2::{BlameOptions, Oid, Repository, Sort};
+
+use crate::semantic::SymbolAction;
+use crate::types::{ChangedFile, CommitType, FileDiff};
+
+/// Score from which a change is treated as a fix.
+pub const FIX_THRESHOLD: f32 = 0.5;
+
+/// Lines introduced within this many days count as recently written.
+pub const RECENT_DAYS: i64 = 30;
+
+/// Files blamed in one analysis at most; further files are scored without
+/// blame data.
+pub const MAX_BLAMED_FILES: usize = 200;
+
+/// Time the blame lookups of one analysis may take.
+pub const BLAME_TIME_BUDGET: Duration = Duration::from_secs(2);
+
+/// Changes with at most this many changed lines count as small edits.
+const SMALL_CHANGE_LINES: usize = 40;
+
+/// Edits that add no declarations count as fixes from this much below
+/// [`FIX_THRESHOLD`].
+const EDIT_ONLY_MARGIN: f32 = 0.1;
+
+/// Performance keywords needed to suggest `perf` without a `perf/` branch.
+const PERF_MIN_HITS: usize = 2;
+
+/// Weight of the keyword signal.
+const KEYWORD_WEIGHT: f32 = 0.55;
+
+/// Weight of the change shape signal.
+const SHAPE_WEIGHT: f32 = 0.2;
+
+/// Weight of the blame signal.
+const BLAME_WEIGHT: f32 = 0.25;
+
+/// Words and phrases that hint at a bug fix, matched case-insensitively.
+const FIX_KEYWORDS: &[&str] = &[
+    "bug",
+    "crash",
+    "deadlock",
+    "error",
+    "fix",
+    "fixes",
+    "fixed",
+    "invalid",
+    "leak",
+    "nan",
+    "nil",
+    "null",
+    "off by one",
+    "off-by-one",
+    "out of bounds",
+    "overflow",
+    "panic",
+    "race",
+    "regression",
+    "segfault",
+    "typo",
+    "underflow",
+    "workaround",
+];
+
+/// Words and phrases that hint at a performance change, matched
+/// case-insensitively.
+const PERF_KEYWORDS: &[&str] = &[
+    "allocation",
+    "allocations",
+    "cache",
+    "cached",
+    "faster",
+    "fast path",
+    "hot path",
+    "memoize",
+    "optimization",
+    "optimize",
+    "perf",
+    "performance",
+    "speed up",
+    "with_capacity",
+    "zero-copy",
+];
+
+/// Counts fix keywords in the added and removed lines of a patch.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::classify::keyword_hits;
+///
+/// let patch = "@@ -1 +1 @@\n-    let x = v[i + 1];\n+    // off-by-one: avoid panic\n+    let x = v[i];\n";
+/// assert_eq!(keyword_hits(patch), 2);
+/// ```
+pub fn keyword_hits(patch: &str) -> usize {
+    count_keywords(patch, FIX_KEYWORDS)
+}
+
+/// Counts performance keywords in the added and removed lines of a patch.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::classify::perf_hits;
+///
+/// let patch = "@@ -1 +1,2 @@\n-let mut out = Vec::new();\n+// Avoid allocations on the hot path\n+let mut out = Vec::with_capacity(n);\n";
+/// assert_eq!(perf_hits(patch), 3);
+/// ```
+pub fn perf_hits(patch: &str) -> usize {
+    count_keywords(patch, PERF_KEYWORDS)
+}
+
+/// Counts the test assertions a patch changes: assertion lines that are
+/// both removed and added, as when an expected value is corrected.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::classify::changed_assertions;
+///
+/// let patch = "@@ -4,2 +4,2 @@\n-    assert_eq!(parse(\"1.5\"), 1);\n+    assert_eq!(parse(\"1.5\"), 2);\n     assert!(ok);\n";
+/// assert_eq!(changed_assertions(patch), 1);
+/// assert_eq!(changed_assertions("@@ -0,0 +1 @@\n+    assert!(ok);\n"), 0);
+/// ```
+pub fn changed_assertions(patch: &str) -> usize {
+    let is_assertion = |line: &str| {
+        let line = line.trim_start();
+        line.starts_with("assert") || line.contains(".assert") || line.starts_with("expect(")
+    };
+    let count = |prefix: char| {
+        patch
+            .lines()
+            .filter(|line| !line.starts_with("+++") && !line.starts_with("---"))
+            .filter_map(|line| line.strip_prefix(prefix))
+            .filter(|line| is_assertion(line))
+            .count()
+    };
+    count('-').min(count('+'))
+}
+
+/// Returns the commit type a branch name prefix stands for.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::classify::branch_commit_type;
+/// use commit_wizard::types::CommitType;
+///
+/// assert_eq!(branch_commit_type("hotfix/PROJ-12-login"), Some(CommitType::Fix));
+/// assert_eq!(branch_commit_type("Refactor/parser"), Some(CommitType::Refactor));
+/// assert_eq!(branch_commit_type("feature/export"), None);
+/// assert_eq!(branch_commit_type("main"), None);
+/// ```
+pub fn branch_commit_type(branch: &str) -> Option<CommitType> {
+    let (prefix, _) = branch.split_once('/')?;
+    match prefix.to_lowercase().as_str() {
+        "fix" | "bugfix" | "hotfix" => Some(CommitType::Fix),
+        "perf" | "performance" => Some(CommitType::Perf),
+        "refactor" | "refactoring" => Some(CommitType::Refactor),
+        _ => None,
+    }
+}
+
+/// Suggests a commit type for a modified file from its diff and branch.
+///
+/// In order:
+///
+/// 1. likely fixes and every modified file on a fix branch are `fix`
+/// 2. edits that add no declarations and score close to [`FIX_THRESHOLD`]
+///    are `fix`
+/// 3. without added declarations, a perf branch or several performance
+///    keywords give `perf`, a refactor branch or renamed declarations give
+///    `refactor`
+///
+/// New files and submodules get no suggestion, so they stay features.
+///
+/// # Arguments
+///
+/// * `file` - The file, scored and with its declarations analyzed
+/// * `patch` - The file's patch
+/// * `branch_type` - The type of the branch prefix, see [`branch_commit_type`]
+///
+/// # Returns
+///
+/// The suggested type, or [`None`] to keep the path-based type.
+pub fn suggest_commit_type(
+    file: &ChangedFile,
+    patch: &str,
+    branch_type: Option<CommitType>,
+) -> Option<CommitType> {
+    if file.is_new() || file.status.is_wt_new() || file.is_submodule() {
+        return None;
+    }
+    if file.is_likely_fix() || branch_type == Some(CommitType::Fix) {
+        return Some(CommitType::Fix);
+    }
+
+    let adds_declarations = file
+        .symbol_changes
+        .iter()
+        .any(|change| change.action == SymbolAction::Add);
+    if adds_declarations {
+        return None;
+    }
+
+    let edits_only = file.lines_removed.unwrap_or(0) > 0;
+    if edits_only
+        && file
+            .fix_likelihood
+            .is_some_and(|score| score >= FIX_THRESHOLD - EDIT_ONLY_MARGIN)
+    {
+        return Some(CommitType::Fix);
+    }
+    if branch_type == Some(CommitType::Perf) || perf_hits(patch) >= PERF_MIN_HITS {
+        return Some(CommitType::Perf);
+    }
+    let renames = file
+        .symbol_changes
+        .iter()
+        .any(|change| matches!(change.action, SymbolAction::Rename { .. }));
+    if branch_type == Some(CommitType::Refactor) || renames {
+        return Some(CommitType::Refactor);
+    }
+    None
+}
+
+/// Suggests a commit type for every file with a diff, see
+/// [`suggest_commit_type`].
+///
+/// Runs after [`score_files`] and [`crate::semantic::describe_files`].
+/// `diffs` may hold the diffs themselves or shared references to them.
+pub fn classify_files<D: Borrow<FileDiff>>(
+    files: &mut [ChangedFile],
+    diffs: &HashMap<String, D>,
+    branch: &str,
+) {
+    let branch_type = branch_commit_type(branch);
+    for file in files.iter_mut() {
+        let Some(diff) = diffs
+            .get(&file.path)
+            .map(Borrow::borrow)
+            .filter(|d| !d.is_binary)
+        else {
+            continue;
+        };
+        file.suggested_type = suggest_commit_type(file, &diff.patch, branch_type);
+        if let Some(commit_type) = file.suggested_type {
+            log::debug!("Suggested type for {}: {}", file.path, commit_type.as_str());
+        }
+    }
+}
+
+/// Counts `keywords` in the added and removed lines of a patch.
+fn count_keywords(patch: &str, keywords: &[&str]) -> usize {
+    changed_lines(patch)
+        .map(|line| {
+            let lower = line.to_lowercase();
+            let words: Vec<&str> = lower
+                .split(|c: char| !(c.is_alphanumeric() || c == '-'))
+                .filter(|w| !w.is_empty())
+                .collect();
+            keywords
+                .iter()
+                .filter(|keyword| {
+                    if keyword.contains([' ', '_']) {
+                        lower.contains(*keyword)
+                    } else {
+                        words.contains(keyword)
+                    }
+                })
+                .count()
+        })
+        .sum()
+}
+
+/// Returns the old-side line numbers of the lines removed by a patch.
+pub fn removed_line_numbers(patch: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut old_line = 0;
+
+    for line in patch.lines() {
+        if let Some(header) = line.strip_prefix("@@ -") {
+            // "@@ -12,3 +12,4 @@": the old side starts at line 12
+            old_line = header
+                .split([',', ' '])
+                .next()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0);
+        } else if line.starts_with("---") || line.starts_with("+++") {
+            continue;
+        } else if line.starts_with('-') {
+            lines.push(old_line);
+            old_line += 1;
+        } else if line.starts_with(' ') {
+            old_line += 1;
+        }
+    }
+
+    lines
+}
+
+/// Combines the signals into a fix likelihood between 0.0 and 1.0.
+///
+/// The weights are chosen so that a small edit of recently written code is
+/// not a fix on its own; at least one keyword is needed to reach
+/// [`FIX_THRESHOLD`].
+///
+/// # Arguments
+///
+/// * `diff` - The file's diff
+/// * `recent_ratio` - Share of the removed lines that were written recently,
+///   if blame data is available
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::classify::{fix_likelihood, FIX_THRESHOLD};
+/// use commit_wizard::types::FileDiff;
+///
+/// let diff = FileDiff {
+///     patch: "@@ -3 +3 @@\n-    if i <= len {\n+    if i < len { // off-by-one panic\n".into(),
+///     lines_added: 1,
+///     lines_removed: 1,
+///     is_binary: false,
+/// };
+/// assert!(fix_likelihood(&diff, Some(1.0)) >= FIX_THRESHOLD);
+/// ```
+pub fn fix_likelihood(diff: &FileDiff, recent_ratio: Option<f32>) -> f32 {
+    // Saturates: one hit 0.3, two 0.51, three 0.66
+    let hits = keyword_hits(&diff.patch) + changed_assertions(&diff.patch);
+    let keyword_score = 1.0 - 0.7_f32.powi(hits as i32);
+
+    let changed = diff.lines_added + diff.lines_removed;
+    let shape_score = match (diff.lines_removed, changed) {
+        (0, _) => 0.0,
+        (_, changed) if changed <= SMALL_CHANGE_LINES => 1.0,
+        _ => 0.5,
+    };
+
+    let blame_score = recent_ratio.unwrap_or(0.0).clamp(0.0, 1.0);
+
+    (KEYWORD_WEIGHT * keyword_score + SHAPE_WEIGHT * shape_score + BLAME_WEIGHT * blame_score)
+        .clamp(0.0, 1.0)
+}
+
+/// Returns the share of `lines` in `path` at HEAD that were committed within
+/// the last [`RECENT_DAYS`] days.
+///
+/// # Returns
+///
+/// The ratio, or [`None`] if `lines` is empty.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be blamed (e.g. it does not exist at
+/// HEAD).
+pub fn recent_line_ratio(repo: &Repository, path: &str, lines: &[usize]) -> Result<Option<f32>> {
+    let cutoff = recent_cutoff();
+    line_ratio_since(repo, path, lines, cutoff, last_commit_before(repo, cutoff))
+}
+
+/// Returns the Unix time from which commits count as recent.
+fn recent_cutoff() -> i64 {
+    chrono::Utc::now().timestamp() - RECENT_DAYS * 24 * 60 * 60
+}
+
+/// Returns the newest commit reachable from HEAD that is older than
+/// `cutoff`; blame does not need to look past it.
+fn last_commit_before(repo: &Repository, cutoff: i64) -> Option<Oid> {
+    let mut walk = repo.revwalk().ok()?;
+    walk.set_sorting(Sort::TIME).ok()?;
+    walk.push_head().ok()?;
+    walk.filter_map(std::result::Result::ok).find(|&id| {
+        repo.find_commit(id)
+            .is_ok_and(|commit| commit.time().seconds() < cutoff)
+    })
+}
+
+/// Returns the share of `lines` in `path` at HEAD committed at or after
+/// `cutoff`, blaming only their line range and the history after `oldest`.
+fn line_ratio_since(
+    repo: &Repository,
+    path: &str,
+    lines: &[usize],
+    cutoff: i64,
+    oldest: Option<Oid>,
+) -> Result<Option<f32>> {
+    let (Some(&first), Some(&last)) = (lines.iter().min(), lines.iter().max()) else {
+        return Ok(None);
+    };
+
+    let mut opts = BlameOptions::new();
+    opts.min_line(first).max_line(last);
+    if let Some(oldest) = oldest {
+        // Lines older than this commit are attributed to it, and it is not
+        // recent either
+        opts.oldest_commit(oldest);
+    }
+    let blame = repo
+        .blame_file(Path::new(path), Some(&mut opts))
+        .with_context(|| format!("Failed to blame {}", path))?;
+
+    let mut commit_times: HashMap<Oid, i64> = HashMap::new();
+    let mut recent = 0;
+    for &line in lines {
+        let Some(hunk) = blame.get_line(line) else {
+            continue;
+        };
+        let id = hunk.final_commit_id();
+        let time = match commit_times.get(&id) {
+            Some(time) => *time,
+            None => {
+                let time = repo
+                    .find_commit(id)
+                    .map(|c| c.time().seconds())
+                    .unwrap_or(i64::MIN);
+                commit_times.insert(id, time);
+                time
+            }
+        };
+        if time >= cutoff {
+            recent += 1;
+        }
+    }
+
+    Ok(Some(recent as f32 / lines.len() as f32))
+}
+
+/// Limits of the blame lookups of [`score_files`].
+///
+/// Blame dominates the analysis of large change sets, so once `max_files`
+/// were blamed, `max_time` has passed, or `cancel` is set, the remaining
+/// files are scored without blame data. One budget covers all batches of
+/// an analysis.
+pub struct BlameBudget<'a> {
+    files_left: usize,
+    deadline: Instant,
+    cancel: &'a AtomicBool,
+    cutoff: i64,
+    oldest: Option<Option<Oid>>,
+}
+
+impl<'a> BlameBudget<'a> {
+    /// Creates a budget of `max_files` files and `max_time`, ending early
+    /// when `cancel` is set.
+    pub fn new(max_files: usize, max_time: Duration, cancel: &'a AtomicBool) -> Self {
+        Self {
+            files_left: max_files,
+            deadline: Instant::now() + max_time,
+            cancel,
+            cutoff: recent_cutoff(),
+            oldest: None,
+        }
+    }
+
+    /// Takes one file from the budget, returning `false` if it is spent.
+    fn take(&mut self) -> bool {
+        if self.files_left == 0
+            || Instant::now() >= self.deadline
+            || self.cancel.load(Ordering::Relaxed)
+        {
+            return false;
+        }
+        self.files_left -= 1;
+        true
+    }
+
+    /// Blames `lines` of `path` like [`recent_line_ratio`], walking the
+    /// history for the oldest commit to consider only once.
+    fn recent_line_ratio(
+        &mut self,
+        repo: &Repository,
+        path: &str,
+        lines: &[usize],
+    ) -> Result<Option<f32>> {
+        let cutoff = self.cutoff;
+        let oldest = *self
+            .oldest
+            .get_or_insert_with(|| last_commit_before(repo, cutoff));
+        line_ratio_since(repo, path, lines, cutoff, oldest)
+    }
+}
+
+/// Scores the fix likelihood of every modified file that has a diff.
+///
+/// New, binary, and submodule entries are left unscored. Blame failures are
+/// logged and the file is scored without blame data, as are the files
+/// beyond `budget`.
+pub fn score_files<D: Borrow<FileDiff>>(
+    repo: &Repository,
+    files: &mut [ChangedFile],
+    diffs: &HashMap<String, D>,
+    budget: &mut BlameBudget,
+) {
+    for file in files.iter_mut() {
+        if file.is_new() || file.status.is_wt_new() || file.is_submodule() {
+            continue;
+        }
+        let Some(diff) = diffs
+            .get(&file.path)
+            .map(Borrow::borrow)
+            .filter(|d| !d.is_binary)
+        else {
+            continue;
+        };
+
+        let removed = removed_line_numbers(&diff.patch);
+        let blame_path = file.old_path.as_deref().unwrap_or(&file.path);
+        let recent_ratio = if removed.is_empty() {
+            None
+        } else if !budget.take() {
+            log::debug!("Blame budget spent; scoring {} without blame", blame_path);
+            None
+        } else {
+            match budget.recent_line_ratio(repo, blame_path, &removed) {
+                Ok(ratio) => ratio,
+                Err(e) => {
+                    log::debug!("No blame data for {}: {:#}", blame_path, e);
+                    None
+                }
+            }
+        };
+
+        let score = fix_likelihood(diff, recent_ratio);
+        log::debug!("Fix likelihood for {}: {:.2}", file.path, score);
+        file.fix_likelihood = Some(score);
+    }
+}
+
+/// Yields the added and removed lines of a patch, without file headers.
+fn changed_lines(patch: &str) -> impl Iterator<Item = &str> {
+    patch
+        .lines()
+        .filter(|line| !line.starts_with("+++") && !line.starts_with("---"))
+        .filter_map(|line| line.strip_prefix('+').or_else(|| line.strip_prefix('-')))
+}
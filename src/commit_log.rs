@@ -0,0 +1,356 @@
+//! The recent history, annotated by commit type.
+//!
+//! `commit-wizard log` lists the latest commits of the current branch with
+//! an icon and the color of their commit type, whether or not they were
+//! made with the wizard. Headers that do not follow the Conventional
+//! Commits format stand out with `❔`, merges with `🔀`, and a summary line
+//! counts the commits per type, so the structure of a branch can be audited
+//! at a glance before opening a pull request.
+//!
+//! In the TUI, `L` lists the same commits with the files each changed (see
+//! [`recent_commits`]), to check what was just committed.
+
+use std::fmt::Write as _;
+
+use anyhow::{Context, Result};
+use git2::{Repository, Sort};
+use ratatui::crossterm::style::{Color, Stylize};
+use regex::Regex;
+use serde::{Serialize, Serializer};
+
+use crate::palette::Palette;
+use crate::types::CommitType;
+
+/// Icon of commits whose header is not conventional.
+pub const UNKNOWN_ICON: &str = "❔";
+
+/// Icon of merge commits whose header is not conventional.
+pub const MERGE_ICON: &str = "🔀";
+
+/// Format of the printed history.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// A line per commit and a summary
+    #[default]
+    Text,
+    /// Pretty-printed JSON object
+    Json,
+}
+
+/// A commit of the history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogEntry {
+    /// Full commit id
+    pub id: String,
+    /// Abbreviated commit id
+    pub short_id: String,
+    /// Commit type, if the header is conventional
+    #[serde(rename = "type", serialize_with = "serialize_type")]
+    pub commit_type: Option<CommitType>,
+    /// Scope, if the header is conventional and has one
+    pub scope: Option<String>,
+    /// Whether the commit is marked as breaking, by `!` or a
+    /// `BREAKING CHANGE` footer
+    pub breaking: bool,
+    /// First line of the message
+    pub header: String,
+    /// Author name
+    pub author: String,
+    /// Commit date, `YYYY-MM-DD` in local time
+    pub date: String,
+    /// Whether the commit has more than one parent
+    pub merge: bool,
+}
+
+impl LogEntry {
+    /// Builds an entry from the parts of a commit, parsing its header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::commit_log::LogEntry;
+    /// use commit_wizard::types::CommitType;
+    ///
+    /// let entry = LogEntry::new("0123456789", "feat(ui)!: add themes\n\nBody", "Jane", "2026-10-01", false);
+    /// assert_eq!(entry.commit_type, Some(CommitType::Feat));
+    /// assert_eq!(entry.scope.as_deref(), Some("ui"));
+    /// assert!(entry.breaking);
+    ///
+    /// let entry = LogEntry::new("0123456789", "Update stuff", "Jane", "2026-10-01", false);
+    /// assert_eq!(entry.commit_type, None);
+    /// assert_eq!(entry.icon(), "❔");
+    /// ```
+    pub fn new(id: &str, message: &str, author: &str, date: &str, merge: bool) -> Self {
+        let header = message
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        let pattern =
+            Regex::new(r"^(?P<type>[a-z]+)(?:\((?P<scope>[^()]+)\))?(?P<breaking>!)?: \S")
+                .expect("valid header pattern");
+        let captures = pattern.captures(&header);
+        let commit_type = captures.as_ref().and_then(|captures| {
+            CommitType::all()
+                .iter()
+                .copied()
+                .find(|t| t.as_str() == &captures["type"])
+        });
+        let (scope, bang) = match (&captures, commit_type) {
+            (Some(captures), Some(_)) => (
+                captures.name("scope").map(|s| s.as_str().to_string()),
+                captures.name("breaking").is_some(),
+            ),
+            _ => (None, false),
+        };
+        let footer = message.lines().skip(1).any(|line| {
+            line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+        });
+        Self {
+            id: id.to_string(),
+            short_id: id.chars().take(7).collect(),
+            commit_type,
+            scope,
+            breaking: commit_type.is_some() && (bang || footer),
+            header,
+            author: author.to_string(),
+            date: date.to_string(),
+            merge,
+        }
+    }
+
+    /// Returns the icon of the entry's commit type.
+    pub fn icon(&self) -> &'static str {
+        match self.commit_type {
+            Some(commit_type) => commit_type.icon(),
+            None if self.merge => MERGE_ICON,
+            None => UNKNOWN_ICON,
+        }
+    }
+
+    /// Renders the entry as one line, with the type and scope in the
+    /// color of the commit type if `palette` is given.
+    pub fn render(&self, palette: Option<&Palette>) -> String {
+        let meta = format!("— {}, {}", self.author, self.date);
+        let Some(palette) = palette else {
+            return format!(
+                "{} {} {}  {}",
+                self.icon(),
+                self.short_id,
+                self.header,
+                meta
+            );
+        };
+
+        let id = self.short_id.as_str().with(Color::DarkGrey);
+        let meta = meta.with(Color::DarkGrey);
+        let header = match self.commit_type {
+            // The prefix up to the ':' takes the type's color
+            Some(commit_type) => {
+                let (prefix, rest) = self.header.split_at(self.header.find(':').unwrap_or(0));
+                let (prefix, bang) = match prefix.strip_suffix('!') {
+                    Some(prefix) => (prefix, "!".red().bold().to_string()),
+                    None => (prefix, String::new()),
+                };
+                format!(
+                    "{}{}{}",
+                    prefix.with(palette.color(commit_type).into()).bold(),
+                    bang,
+                    rest
+                )
+            }
+            None => self.header.as_str().with(Color::DarkGrey).to_string(),
+        };
+        format!("{} {} {}  {}", self.icon(), id, header, meta)
+    }
+}
+
+/// A commit of the history with the files it changed, as listed by the
+/// recent commits popup of the TUI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentCommit {
+    /// The commit
+    pub entry: LogEntry,
+    /// Paths changed compared to the first parent, or to an empty tree
+    /// for the root commit
+    pub files: Vec<String>,
+}
+
+/// Reads up to `max_count` commits reachable from `HEAD` with the files
+/// each of them changed, newest first.
+///
+/// # Errors
+///
+/// Returns an error if the history or a commit's diff cannot be read.
+pub fn recent_commits(repo: &Repository, max_count: usize) -> Result<Vec<RecentCommit>> {
+    CommitLog::read(repo, max_count)?
+        .commits
+        .into_iter()
+        .map(|entry| {
+            let files = changed_files(repo, &entry.id)
+                .with_context(|| format!("Failed to diff commit {}", entry.short_id))?;
+            Ok(RecentCommit { entry, files })
+        })
+        .collect()
+}
+
+/// Returns the paths the commit `id` changed compared to its first parent.
+fn changed_files(repo: &Repository, id: &str) -> Result<Vec<String>> {
+    let commit = repo.find_commit(git2::Oid::from_str(id)?)?;
+    let tree = commit.tree()?;
+    let parent = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&tree), None)?;
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
+
+fn serialize_type<S: Serializer>(
+    commit_type: &Option<CommitType>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match commit_type {
+        Some(commit_type) => serializer.serialize_some(commit_type.as_str()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// The latest commits of the current branch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CommitLog {
+    /// The commits, newest first
+    pub commits: Vec<LogEntry>,
+}
+
+impl CommitLog {
+    /// Reads up to `max_count` commits reachable from `HEAD`, newest first.
+    ///
+    /// A repository without commits yields an empty log.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the history cannot be walked.
+    pub fn read(repo: &Repository, max_count: usize) -> Result<Self> {
+        let mut log = Self::default();
+        let Ok(head) = repo.head().and_then(|head| head.peel_to_commit()) else {
+            return Ok(log);
+        };
+
+        let mut walk = repo.revwalk().context("Failed to walk history")?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        walk.push(head.id())?;
+        for oid in walk.take(max_count) {
+            let commit = repo.find_commit(oid?)?;
+            let date = chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+                .map(|time| {
+                    time.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            log.commits.push(LogEntry::new(
+                &commit.id().to_string(),
+                &String::from_utf8_lossy(commit.message_bytes()),
+                &String::from_utf8_lossy(commit.author().name_bytes()),
+                &date,
+                commit.parent_count() > 1,
+            ));
+        }
+        Ok(log)
+    }
+
+    /// Returns the number of commits with a conventional header.
+    pub fn conventional(&self) -> usize {
+        self.commits
+            .iter()
+            .filter(|entry| entry.commit_type.is_some())
+            .count()
+    }
+
+    /// Returns the number of commits per type, in the order of
+    /// [`CommitType::all`], leaving out types without commits.
+    pub fn counts(&self) -> Vec<(CommitType, usize)> {
+        CommitType::all()
+            .iter()
+            .map(|&commit_type| {
+                let count = self
+                    .commits
+                    .iter()
+                    .filter(|entry| entry.commit_type == Some(commit_type))
+                    .count();
+                (commit_type, count)
+            })
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
+    /// Renders the log in `format`; text is colored if `palette` is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if JSON serialization fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::commit_log::{CommitLog, LogEntry, LogFormat};
+    ///
+    /// let log = CommitLog {
+    ///     commits: vec![
+    ///         LogEntry::new("1111111111", "fix(git): handle detached HEAD", "Jane", "2026-10-02", false),
+    ///         LogEntry::new("2222222222", "WIP", "Joe", "2026-10-01", false),
+    ///     ],
+    /// };
+    /// let text = log.render(LogFormat::Text, None).unwrap();
+    /// assert!(text.starts_with("🐛 1111111 fix(git): handle detached HEAD  — Jane, 2026-10-02\n❔ 2222222 WIP"));
+    /// assert!(text.ends_with("2 commits, 1 conventional (50%): fix 1"));
+    /// ```
+    pub fn render(&self, format: LogFormat, palette: Option<&Palette>) -> Result<String> {
+        Ok(match format {
+            LogFormat::Text => {
+                if self.commits.is_empty() {
+                    return Ok("ℹ No commits yet".to_string());
+                }
+                let mut out = String::new();
+                for entry in &self.commits {
+                    let _ = writeln!(out, "{}", entry.render(palette));
+                }
+                let total = self.commits.len();
+                let conventional = self.conventional();
+                let _ = write!(
+                    out,
+                    "\n{} commit{}, {} conventional ({}%)",
+                    total,
+                    if total == 1 { "" } else { "s" },
+                    conventional,
+                    conventional * 100 / total
+                );
+                let counts: Vec<String> = self
+                    .counts()
+                    .into_iter()
+                    .map(|(commit_type, count)| {
+                        let name = match palette {
+                            Some(palette) => commit_type
+                                .as_str()
+                                .with(palette.color(commit_type).into())
+                                .to_string(),
+                            None => commit_type.as_str().to_string(),
+                        };
+                        format!("{} {}", name, count)
+                    })
+                    .collect();
+                if !counts.is_empty() {
+                    let _ = write!(out, ": {}", counts.join(" · "));
+                }
+                out
+            }
+            LogFormat::Json => serde_json::to_string_pretty(self)?,
+        })
+    }
+}
@@ -0,0 +1,90 @@
+//! Line diff between a commit message and its regenerated version.
+//!
+//! When the AI regenerates or refines a message, the TUI shows what changed
+//! before the new text replaces the old one. Messages are short, so a plain
+//! longest-common-subsequence diff over lines is enough.
+
+/// A line of a message diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    /// Line present in both messages
+    Same(String),
+    /// Line only in the previous message
+    Removed(String),
+    /// Line only in the new message
+    Added(String),
+}
+
+impl DiffLine {
+    /// Returns the text of the line.
+    pub fn text(&self) -> &str {
+        match self {
+            DiffLine::Same(text) | DiffLine::Removed(text) | DiffLine::Added(text) => text,
+        }
+    }
+}
+
+/// Diffs two messages line by line.
+///
+/// Removed lines come before the added lines that replace them.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::compare::{diff_lines, DiffLine};
+///
+/// let diff = diff_lines("add login\n\n- form", "add login page\n\n- form");
+/// assert_eq!(
+///     diff,
+///     [
+///         DiffLine::Removed("add login".into()),
+///         DiffLine::Added("add login page".into()),
+///         DiffLine::Same("".into()),
+///         DiffLine::Same("- form".into()),
+///     ]
+/// );
+/// ```
+pub fn diff_lines(previous: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = previous.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j]: length of the longest common subsequence of old[i..]
+    // and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines.extend(
+        old[i..]
+            .iter()
+            .map(|line| DiffLine::Removed(line.to_string())),
+    );
+    lines.extend(
+        new[j..]
+            .iter()
+            .map(|line| DiffLine::Added(line.to_string())),
+    );
+    lines
+}
@@ -0,0 +1,191 @@
+//! Merging of small groups into related larger ones.
+//!
+//! AI grouping in particular sometimes splits a change set into a handful
+//! of one-line commits that reviewers would rather see as one. With
+//! `[grouping] min_files` or `min_lines` set, groups below every set
+//! threshold are merged into the most related larger group: the one whose
+//! files they reference, else the one with the same scope, else the one
+//! sharing the deepest directory. Small groups related to no larger group
+//! are collected in one group of their own.
+//!
+//! ```toml
+//! [grouping]
+//! min_files = 2   # default: 0 (off)
+//! min_lines = 10  # default: 0 (off)
+//! ```
+
+use std::cmp::Reverse;
+use std::path::Path;
+
+use crate::config::GroupingConfig;
+use crate::types::{ChangeGroup, CommitType};
+
+/// Description of the group collecting unrelated small groups.
+pub const COLLECTED_DESCRIPTION: &str = "apply small changes";
+
+/// Returns whether `group` is below every threshold that is set.
+///
+/// Committed groups, submodule updates, and groups whose line count is
+/// unknown (binary files) while `min_lines` is set are never small.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::config::GroupingConfig;
+/// use commit_wizard::consolidate::is_small;
+/// use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+/// use git2::Status;
+///
+/// let mut file = ChangedFile::new("README.md".into(), Status::WT_MODIFIED);
+/// file.lines_added = Some(1);
+/// file.lines_removed = Some(1);
+/// let group = ChangeGroup::new(CommitType::Docs, None, vec![file], None, "fix typo".into(), vec![]);
+///
+/// assert!(!is_small(&group, &GroupingConfig::default()));
+/// assert!(is_small(&group, &GroupingConfig { min_files: 2, min_lines: 10 }));
+/// assert!(!is_small(&group, &GroupingConfig { min_files: 2, min_lines: 2 }));
+/// ```
+pub fn is_small(group: &ChangeGroup, config: &GroupingConfig) -> bool {
+    if !config.is_enabled()
+        || group.is_committed()
+        || group.files.iter().any(|file| file.is_submodule())
+    {
+        return false;
+    }
+    if config.min_files > 0 && group.files.len() >= config.min_files {
+        return false;
+    }
+    if config.min_lines > 0 {
+        match group.diff_stats() {
+            Some((added, removed)) if added + removed < config.min_lines => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Merges the small groups (see [`is_small`]) into the most related larger
+/// group, or into one collecting group if none is related.
+///
+/// Merged groups add their description to the body of the group they are
+/// merged into, so the message still mentions them. The collecting group
+/// takes the commit type and scope the merged groups have in common,
+/// `chore` without a scope otherwise. A single small group without a
+/// related larger group stays as it is.
+///
+/// # Returns
+///
+/// The groups and the number of groups that were merged away.
+pub fn merge_small_groups(
+    groups: Vec<ChangeGroup>,
+    config: &GroupingConfig,
+) -> (Vec<ChangeGroup>, usize) {
+    let (small, mut large): (Vec<_>, Vec<_>) = groups
+        .into_iter()
+        .partition(|group| is_small(group, config));
+    if small.is_empty() {
+        return (large, 0);
+    }
+
+    let candidates = large.len();
+    let mut unrelated = Vec::new();
+    let mut merged = 0;
+    for group in small {
+        let target = (0..candidates)
+            .filter(|&idx| !large[idx].is_committed())
+            .map(|idx| (relatedness(&group, &large[idx]), Reverse(idx)))
+            .filter(|((links, same_scope, depth), _)| *links > 0 || *same_scope || *depth > 0)
+            .max()
+            .map(|(_, Reverse(idx))| idx);
+        match target {
+            Some(idx) => {
+                absorb(&mut large[idx], group);
+                merged += 1;
+            }
+            None => unrelated.push(group),
+        }
+    }
+
+    if unrelated.len() > 1 {
+        merged += unrelated.len() - 1;
+        large.push(collect(unrelated));
+    } else {
+        large.extend(unrelated);
+    }
+    (large, merged)
+}
+
+/// Returns how related `small` is to `target`: the number of reference
+/// links between their files, whether they have the same scope, and the
+/// number of leading directories their files share at most.
+fn relatedness(small: &ChangeGroup, target: &ChangeGroup) -> (usize, bool, usize) {
+    let mut links = 0;
+    let mut depth = 0;
+    for file in &small.files {
+        for other in &target.files {
+            let linked = file.related_to.as_deref() == Some(other.path.as_str())
+                || other.related_to.as_deref() == Some(file.path.as_str())
+                || (file.related_to.is_some() && file.related_to == other.related_to);
+            if linked {
+                links += 1;
+            }
+            depth = depth.max(shared_directories(&file.path, &other.path));
+        }
+    }
+    let same_scope = small.scope.is_some() && small.scope == target.scope;
+    (links, same_scope, depth)
+}
+
+/// Returns the number of leading directories two paths share.
+fn shared_directories(a: &str, b: &str) -> usize {
+    let dirs = |path| {
+        Path::new(path)
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+    };
+    dirs(a).zip(dirs(b)).take_while(|(a, b)| a == b).count()
+}
+
+/// Moves the files, description, and notes of `small` into `target`.
+fn absorb(target: &mut ChangeGroup, small: ChangeGroup) {
+    target.ensure_body();
+    if !small.description.trim().is_empty() {
+        target.body_lines.push(small.description.clone());
+    }
+    target.notes = match (target.notes.take(), small.notes) {
+        (Some(a), Some(b)) => Some(format!("{}\n\n{}", a, b)),
+        (a, b) => a.or(b),
+    };
+    target.stale |= small.stale;
+    target.files.extend(small.files);
+}
+
+/// Builds the group collecting small groups that no larger group is
+/// related to.
+fn collect(groups: Vec<ChangeGroup>) -> ChangeGroup {
+    let first = &groups[0];
+    let commit_type = if groups.iter().all(|g| g.commit_type == first.commit_type) {
+        first.commit_type
+    } else {
+        CommitType::Chore
+    };
+    let scope = first
+        .scope
+        .clone()
+        .filter(|scope| groups.iter().all(|g| g.scope.as_ref() == Some(scope)));
+    let mut collected = ChangeGroup::new(
+        commit_type,
+        scope,
+        Vec::new(),
+        first.ticket.clone(),
+        COLLECTED_DESCRIPTION.to_string(),
+        Vec::new(),
+    );
+    collected.ticket_placement = first.ticket_placement;
+    collected.footers = first.footers.clone();
+    for group in groups {
+        absorb(&mut collected, group);
+    }
+    collected
+}
@@ -0,0 +1,197 @@
+//! Pre-generation context command.
+//!
+//! A project can configure a shell command (e.g. `cargo test --quiet` or
+//! `npm run lint`) that runs before grouping. The tail of its output is added
+//! to every AI prompt so commit messages can reference test or lint results,
+//! and a failing command triggers a warning before committing.
+//!
+//! The command runs with the user's shell in the repository root. Cloning a
+//! repository must not make it run anything, so it is only read from the
+//! user configuration and `--config`, never from `.commit-wizard.toml` (see
+//! [`crate::config::USER_ONLY_KEYS`]).
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+
+use crate::config::ContextConfig;
+use crate::provider::{AiProvider, ChatMessage, ProviderLayer};
+
+/// Output of the context command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandContext {
+    /// The command as configured
+    pub command: String,
+    /// Trailing lines of stdout followed by stderr
+    pub output: String,
+    /// Exit code, or [`None`] if the command was terminated by a signal
+    pub exit_code: Option<i32>,
+    /// Whether leading output lines were dropped
+    pub truncated: bool,
+}
+
+impl CommandContext {
+    /// Creates a context from raw command output, keeping the last
+    /// `max_lines` lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::context::CommandContext;
+    ///
+    /// let ctx = CommandContext::new("make test", "a\nb\nc\n", Some(0), 2);
+    /// assert_eq!(ctx.output, "b\nc");
+    /// assert!(ctx.truncated);
+    /// assert!(ctx.success());
+    /// ```
+    pub fn new(command: &str, output: &str, exit_code: Option<i32>, max_lines: usize) -> Self {
+        let lines: Vec<&str> = output.trim_end().lines().collect();
+        let skip = lines.len().saturating_sub(max_lines);
+        Self {
+            command: command.to_string(),
+            output: lines[skip..].join("\n"),
+            exit_code,
+            truncated: skip > 0,
+        }
+    }
+
+    /// Returns whether the command exited with status 0.
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// Describes the exit status, e.g. `exit code 101`.
+    pub fn status_text(&self) -> String {
+        match self.exit_code {
+            Some(0) => "succeeded".to_string(),
+            Some(code) => format!("exit code {}", code),
+            None => "terminated by signal".to_string(),
+        }
+    }
+
+    /// Formats the output as a prompt section.
+    pub fn prompt_section(&self) -> String {
+        let mut section = format!(
+            "PROJECT CHECK OUTPUT (`{}`, {}):\n",
+            self.command,
+            self.status_text()
+        );
+        if self.truncated {
+            section.push_str("... (earlier lines omitted)\n");
+        }
+        section.push_str(&self.output);
+        section.push_str(
+            "\n\nUse this output as background: mention test or lint results only if they relate to the changes.\n",
+        );
+        section
+    }
+}
+
+/// Runs the configured context command in `workdir`.
+///
+/// # Returns
+///
+/// The command output, or [`None`] if no command is configured.
+///
+/// # Errors
+///
+/// Returns an error if the shell cannot be started, e.g. with
+/// `--no-subprocess`. A command that runs but fails is not an error; see
+/// [`CommandContext::success`].
+pub fn run_context_command(
+    config: &ContextConfig,
+    workdir: &Path,
+) -> Result<Option<CommandContext>> {
+    let Some(command) = config.command.as_deref().filter(|c| !c.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    crate::subprocess::ensure_allowed(command)?;
+    log::info!("Running context command: {}", command);
+    let output = shell_command(command)
+        .current_dir(workdir)
+        .output()
+        .with_context(|| format!("Failed to run context command `{}`", command))?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.trim().is_empty() {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&stderr);
+    }
+
+    let ctx = CommandContext::new(command, &text, output.status.code(), config.max_lines);
+    log::debug!("Context command {}", ctx.status_text());
+    Ok(Some(ctx))
+}
+
+/// Builds a command running `command` with the platform shell.
+pub(crate) fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// Provider that prepends the context command output to every prompt.
+pub struct ContextProvider {
+    inner: Box<dyn AiProvider>,
+    section: String,
+}
+
+impl ContextProvider {
+    /// Wraps `inner` so its prompts include `context`.
+    pub fn new(inner: Box<dyn AiProvider>, context: &CommandContext) -> Self {
+        Self {
+            inner,
+            section: context.prompt_section(),
+        }
+    }
+
+    /// Wraps `inner` so its prompts start with `section`, e.g. the issue
+    /// the changes work on.
+    pub fn with_section(inner: Box<dyn AiProvider>, section: String) -> Self {
+        Self { inner, section }
+    }
+}
+
+impl ProviderLayer for ContextProvider {
+    fn inner(&self) -> &dyn AiProvider {
+        self.inner.as_ref()
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.inner
+            .complete(&format!("{}\n{}", self.section, prompt))
+    }
+
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        self.inner
+            .complete_json(&format!("{}\n{}", self.section, prompt))
+    }
+
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        self.inner
+            .complete_n(&format!("{}\n{}", self.section, prompt), n)
+    }
+
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        // The context belongs to the first prompt of the conversation
+        let mut messages = messages.to_vec();
+        if let Some(first) = messages.first_mut() {
+            first.content = format!("{}\n{}", self.section, first.content);
+        }
+        self.inner.chat(&messages)
+    }
+}
@@ -3,22 +3,30 @@
 //! This module provides AI-driven grouping of changed files and generation
 //! of conventional commit messages. It uses the GitHub Copilot CLI to analyze
 //! file changes and produce high-quality commit messages.
+//!
+//! The prompts and response parsing defined here are shared by every
+//! [`AiProvider`]; the Copilot CLI itself is exposed as [`CopilotCliProvider`].
 
 use anyhow::{bail, Context, Result};
+use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
 
 use std::process::{Command, Stdio};
+use std::time::Duration;
 
+use crate::fallback::UnparsableResponse;
+use crate::prompt::{condense_diff, fit_diffs};
+use crate::provider::{AiProvider, ChatMessage};
 use crate::types::{ChangeGroup, ChangedFile, CommitType};
 use log::{debug, error, warn};
 
-/// Maximum diff size to send to Copilot (1000 characters)
-const MAX_DIFF_SIZE: usize = 1000;
-
 /// Markers for extracting commit messages from Copilot response
 const START_MARKER: &str = "**START COMMIT MESSAGE**";
 const END_MARKER: &str = "**END COMMIT MESSAGE**";
 
+/// Prompt requirement added when files are deleted.
+const DELETED_FILES_REQUIREMENT: &str = "- Deleted files are removed entirely; describe what the removal drops, using their former purpose where given (e.g. 'remove legacy INI config parser')\n";
+
 /// Trait for abstracting command execution (enables testing).
 ///
 /// This trait allows dependency injection of command execution logic,
@@ -165,14 +173,7 @@ pub fn build_groups_with_ai(
         return Ok(crate::inference::build_groups(files, ticket));
     }
 
-    // Build prompt for file grouping
-    let grouping_prompt = build_grouping_prompt(&files, ticket.as_deref(), &diffs);
-
-    // Call Copilot CLI
-    let response = call_copilot_cli(&grouping_prompt)?;
-
-    // Parse response into groups
-    parse_groups_from_response(&response, files, ticket, &diffs)
+    CopilotCliProvider::default().group_files(files, ticket, &diffs)
 }
 
 /// Generates a commit message for a specific group using AI.
@@ -195,16 +196,239 @@ pub fn generate_commit_message_with_ai(
         anyhow::bail!("GitHub Copilot CLI is not available");
     }
 
-    let prompt = build_commit_message_prompt(group, files, diff);
-    let response = call_copilot_cli(&prompt)?;
-    parse_commit_message(&response)
+    CopilotCliProvider::default().generate_message(group, files, diff)
+}
+
+/// [`AiProvider`] backed by the GitHub Copilot CLI.
+///
+/// Without a `timeout` the CLI may run as long as it needs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CopilotCliProvider {
+    /// Time after which the CLI is stopped and the request fails
+    pub timeout: Option<Duration>,
+}
+
+impl AiProvider for CopilotCliProvider {
+    fn name(&self) -> &str {
+        "GitHub Copilot CLI"
+    }
+
+    fn is_available(&self) -> bool {
+        !crate::subprocess::is_disabled() && is_copilot_cli_available()
+    }
+
+    fn unavailable_reason(&self) -> String {
+        if crate::subprocess::is_disabled() {
+            "GitHub Copilot CLI is disabled by --no-subprocess".to_string()
+        } else if RealCommandExecutor.check_version() {
+            "GitHub Copilot CLI is not authenticated (run `copilot`, then `/login`)".to_string()
+        } else {
+            "GitHub Copilot CLI is not installed (npm install -g @github/copilot)".to_string()
+        }
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        call_copilot_cli(prompt, self.timeout)
+    }
+}
+
+/// Groups files with any provider using the shared grouping prompt.
+///
+/// Backs [`AiProvider::group_files`].
+pub(crate) fn group_files_with_provider<P: AiProvider + ?Sized>(
+    provider: &P,
+    files: Vec<ChangedFile>,
+    ticket: Option<String>,
+    diffs: &HashMap<String, String>,
+) -> Result<Vec<ChangeGroup>> {
+    // Files excluded from AI are grouped heuristically and never mentioned
+    // in the prompt
+    let (excluded, files): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|file| is_excluded(provider, file));
+    let mut local_groups = crate::inference::build_groups(excluded, ticket.clone());
+
+    // Submodule pointer updates always get their own commit
+    let (submodules, files): (Vec<_>, Vec<_>) =
+        files.into_iter().partition(ChangedFile::is_submodule);
+    local_groups.extend(crate::inference::build_submodule_group(
+        submodules,
+        ticket.clone(),
+    ));
+    if files.is_empty() {
+        return Ok(local_groups);
+    }
+
+    let diffs: HashMap<String, String> = diffs
+        .iter()
+        .filter(|(path, _)| !provider.excludes(path))
+        .map(|(path, diff)| (path.clone(), diff.clone()))
+        .collect();
+
+    // Build prompt for file grouping
+    let grouping_prompt = build_grouping_prompt(
+        &files,
+        ticket.as_deref(),
+        &diffs,
+        provider.diff_token_budget(),
+    );
+
+    let response = strip_response(&provider.complete_json(&grouping_prompt)?);
+    debug!("AI grouping response: {}", response);
+
+    // Parse response into groups
+    let ai_groups =
+        parse_grouping_response(&response).with_context(|| UnparsableResponse::new(&response))?;
+    if ai_groups.is_empty() {
+        bail!("AI grouping response contains no groups");
+    }
+    let mut groups = groups_from_ai(ai_groups, &files, ticket)?;
+    groups.extend(local_groups);
+    Ok(groups)
+}
+
+/// Returns whether a file, under its current or previous path, must not be
+/// sent to `provider`.
+fn is_excluded<P: AiProvider + ?Sized>(provider: &P, file: &ChangedFile) -> bool {
+    provider.excludes(&file.path)
+        || file
+            .old_path
+            .as_deref()
+            .is_some_and(|old_path| provider.excludes(old_path))
+}
+
+/// Drops the files excluded from AI, and their diffs, from a group's input
+/// for message prompts.
+///
+/// # Arguments
+///
+/// * `provider` - The provider the prompt is for
+/// * `files` - The files of the group
+/// * `diff` - Optional combined diff of the files
+///
+/// # Returns
+///
+/// The files and diff that may be sent to the provider.
+///
+/// # Errors
+///
+/// Returns an error if all files are excluded, since there is nothing left
+/// to describe.
+pub fn visible_to_ai<P: AiProvider + ?Sized>(
+    provider: &P,
+    files: &[ChangedFile],
+    diff: Option<&str>,
+) -> Result<(Vec<ChangedFile>, Option<String>)> {
+    let visible: Vec<ChangedFile> = files
+        .iter()
+        .filter(|file| !is_excluded(provider, file))
+        .cloned()
+        .collect();
+    if visible.is_empty() && !files.is_empty() {
+        bail!("All files of this group are excluded from AI by [ai] exclude");
+    }
+    let diff =
+        diff.map(|diff| crate::exclude::strip_excluded_diffs(diff, |path| provider.excludes(path)));
+    Ok((visible, diff))
+}
+
+/// Generates a commit message with any provider using the shared prompt.
+///
+/// Backs [`AiProvider::generate_message`].
+pub(crate) fn generate_message_with_provider<P: AiProvider + ?Sized>(
+    provider: &P,
+    group: &ChangeGroup,
+    files: &[ChangedFile],
+    diff: Option<&str>,
+) -> Result<(String, Option<String>)> {
+    let (files, diff) = visible_to_ai(provider, files, diff)?;
+    let prompt =
+        build_commit_message_prompt(group, &files, diff.as_deref(), provider.diff_token_budget());
+    let response = provider.complete(&prompt)?;
+    parse_commit_message(&strip_response(&response))
+        .with_context(|| UnparsableResponse::new(&response))
+}
+
+/// Generates several alternative commit messages with any provider.
+///
+/// Backs [`AiProvider::generate_messages`].
+pub(crate) fn generate_messages_with_provider<P: AiProvider + ?Sized>(
+    provider: &P,
+    group: &ChangeGroup,
+    files: &[ChangedFile],
+    diff: Option<&str>,
+    n: usize,
+) -> Result<Vec<(String, Option<String>)>> {
+    let (files, diff) = visible_to_ai(provider, files, diff)?;
+    let prompt =
+        build_commit_message_prompt(group, &files, diff.as_deref(), provider.diff_token_budget());
+    let mut candidates: Vec<(String, Option<String>)> = Vec::new();
+    let mut unparsable = None;
+    for response in provider.complete_n(&prompt, n)? {
+        match parse_commit_message(&strip_response(&response)) {
+            Ok(candidate) if !candidates.contains(&candidate) => candidates.push(candidate),
+            Ok(_) => log::debug!("Dropping duplicate message candidate"),
+            Err(e) => {
+                log::warn!("Dropping unparsable message candidate: {}", e);
+                unparsable.get_or_insert(response);
+            }
+        }
+    }
+    if candidates.is_empty() {
+        let error = anyhow::anyhow!("{} returned no usable commit message", provider.name());
+        return Err(match unparsable {
+            Some(response) => error.context(UnparsableResponse::new(&response)),
+            None => error,
+        });
+    }
+    Ok(candidates)
+}
+
+/// Refines a commit message with any provider.
+///
+/// Backs [`AiProvider::refine_message`].
+pub(crate) fn refine_message_with_provider<P: AiProvider + ?Sized>(
+    provider: &P,
+    conversation: &[ChatMessage],
+) -> Result<(String, Option<String>)> {
+    let response = provider.chat(conversation)?;
+    parse_commit_message(&strip_response(&response))
+        .with_context(|| UnparsableResponse::new(&response))
+}
+
+/// Reduces a raw provider response to the text between the markers.
+///
+/// Responses without markers (e.g. already extracted by the Copilot CLI
+/// provider) are used as-is, minus surrounding markdown code fences.
+fn strip_response(response: &str) -> String {
+    let text = if response.contains(START_MARKER) {
+        extract_response_between_markers(response).unwrap_or_else(|_| response.to_string())
+    } else {
+        response.to_string()
+    };
+
+    let text = text.trim();
+    match text.strip_prefix("```") {
+        Some(fenced) => fenced
+            .split_once('\n')
+            .map_or("", |(_, rest)| rest)
+            .trim_end()
+            .trim_end_matches("```")
+            .trim()
+            .to_string(),
+        None => text.to_string(),
+    }
 }
 
 /// Builds the prompt for AI-based file grouping.
+///
+/// The diffs share a budget of `max_diff_tokens` tokens; see
+/// [`crate::prompt`].
 pub fn build_grouping_prompt(
     files: &[ChangedFile],
     ticket: Option<&str>,
     diffs: &HashMap<String, String>,
+    max_diff_tokens: usize,
 ) -> String {
     let mut prompt = String::new();
 
@@ -218,7 +442,19 @@ pub fn build_grouping_prompt(
     prompt.push_str("- Assign appropriate conventional commit type (feat, fix, docs, style, refactor, perf, test, chore, ci, build)\n");
     prompt.push_str("- Determine scope from file paths (e.g., 'api', 'ui', 'auth')\n");
     prompt.push_str("- Generate concise, imperative descriptions\n");
-    prompt.push_str("- Keep descriptions under 72 characters\n\n");
+    prompt.push_str("- Keep descriptions under 72 characters\n");
+    prompt.push_str("- List the groups in the order they should be committed, so that every commit builds: build configuration and dependencies before the code using them, shared types and APIs before their callers\n");
+    prompt.push_str("- If a group must be committed after other groups, give their numbers (1 for the first group listed) in \"depends_on\" and explain why in a short \"order_note\"\n");
+    if files.iter().any(|f| f.fix_likelihood.is_some()) {
+        prompt.push_str("- A fix likelihood is given for modified files, based on bug-related keywords and on how recently the changed code was written; prefer 'fix' over 'feat' when it is high\n");
+    }
+    if files.iter().any(|f| f.suggested_type.is_some()) {
+        prompt.push_str("- A suggested type is given for modified files, based on the branch name, bug and performance keywords, and whether the diff adds declarations or only edits existing ones\n");
+    }
+    if files.iter().any(ChangedFile::is_deleted) {
+        prompt.push_str(DELETED_FILES_REQUIREMENT);
+    }
+    prompt.push('\n');
 
     if let Some(ticket_num) = ticket {
         prompt.push_str(&format!("Ticket/Issue: {}\n\n", ticket_num));
@@ -237,50 +473,86 @@ pub fn build_grouping_prompt(
         } else {
             "changed"
         };
-        prompt.push_str(&format!("  {} - {}\n", status, file.path));
+        let mut hints = Vec::new();
+        if let Some(score) = file.fix_likelihood {
+            hints.push(format!("fix likelihood: {:.0}%", score * 100.0));
+        }
+        if let Some(commit_type) = file.suggested_type {
+            hints.push(format!("suggested type: {}", commit_type.as_str()));
+        }
+        if let Some(purpose) = &file.former_purpose {
+            hints.push(format!("former purpose: {}", purpose));
+        }
+        if hints.is_empty() {
+            prompt.push_str(&format!("  {} - {}\n", status, file.path));
+        } else {
+            prompt.push_str(&format!(
+                "  {} - {} ({})\n",
+                status,
+                file.path,
+                hints.join(", ")
+            ));
+        }
     }
 
-    // Add diffs for context (truncated)
+    // Add diffs for context (condensed to the token budget)
     if !diffs.is_empty() {
         prompt.push_str("\nDIFF PREVIEW:\n");
-        for (path, diff) in diffs.iter().take(5) {
+        let (fitted, omitted) = fit_diffs(diffs, max_diff_tokens);
+        for (path, diff) in fitted {
             prompt.push_str(&format!("\n{}:\n", path));
-            let truncated = if diff.len() > MAX_DIFF_SIZE {
-                format!("{}... (truncated)", &diff[..MAX_DIFF_SIZE])
-            } else {
-                diff.clone()
-            };
-            prompt.push_str(&truncated);
+            prompt.push_str(&diff);
+        }
+        if omitted > 0 {
+            prompt.push_str(&format!(
+                "\n... (diffs of {} more files omitted)\n",
+                omitted
+            ));
         }
     }
 
+    prompt.push_str("\n\nOUTPUT FORMAT:\n");
+    prompt.push_str(
+        "- Respond with strict JSON: double-quoted strings, no comments, no trailing commas\n",
+    );
+    prompt.push_str("- Every changed file must appear in exactly one group's \"files\", spelled exactly as listed\n");
+    prompt.push_str(
+        "- \"type\" is one of: feat, fix, docs, style, refactor, perf, test, chore, ci, build\n",
+    );
+    prompt.push_str("- \"scope\" is a string or null\n");
+    prompt.push_str("- \"depends_on\" lists group numbers and may be empty; \"order_note\" is a string or null\n");
+    prompt.push_str("- body_lines should NOT start with '- ', it will be added automatically\n");
     prompt.push_str(&format!(
-        "\n\nProvide the grouping in JSON format between these markers:\n{}\n",
+        "\nProvide the grouping as a JSON object between these markers:\n{}\n",
         START_MARKER
     ));
-    prompt.push_str("[\n");
-    prompt.push_str("  {\n");
-    prompt.push_str("    \"type\": \"feat\",\n");
-    prompt.push_str("    \"scope\": \"api\",\n");
-    prompt.push_str("    \"description\": \"add user endpoint\",\n");
-    prompt.push_str("    \"files\": [\"src/api/users.rs\"],\n");
-    prompt.push_str("    \"body_lines\": [\"implement GET /users\", \"add user model\"]\n");
-    prompt.push_str(
-        "    # NOTE: body_lines should NOT start with '- ', it will be added automatically\n",
-    );
-    prompt.push_str("  }\n");
-    prompt.push_str("]\n");
+    prompt.push_str("{\n");
+    prompt.push_str("  \"groups\": [\n");
+    prompt.push_str("    {\n");
+    prompt.push_str("      \"type\": \"feat\",\n");
+    prompt.push_str("      \"scope\": \"api\",\n");
+    prompt.push_str("      \"description\": \"add user endpoint\",\n");
+    prompt.push_str("      \"files\": [\"src/api/users.rs\"],\n");
+    prompt.push_str("      \"body_lines\": [\"implement GET /users\", \"add user model\"],\n");
+    prompt.push_str("      \"depends_on\": [],\n");
+    prompt.push_str("      \"order_note\": null\n");
+    prompt.push_str("    }\n");
+    prompt.push_str("  ]\n");
+    prompt.push_str("}\n");
     prompt.push_str(&format!("{}\n", END_MARKER));
 
     prompt
 }
 
 /// Builds the prompt for commit message generation.
+///
+/// The diff is condensed to `max_diff_tokens` tokens.
 #[doc(hidden)] // Internal use and testing only
 pub fn build_commit_message_prompt(
     group: &ChangeGroup,
     files: &[ChangedFile],
     diff: Option<&str>,
+    max_diff_tokens: usize,
 ) -> String {
     let mut prompt = String::new();
 
@@ -300,29 +572,40 @@ pub fn build_commit_message_prompt(
     prompt
         .push_str("- If providing a body, provide plain text lines WITHOUT bullet point prefix\n");
     prompt.push_str("- The tool will automatically add '- ' prefix to each body line\n");
-    prompt.push_str("- Mention breaking changes if applicable\n\n");
+    prompt.push_str("- Mention breaking changes if applicable\n");
+    if files.iter().any(ChangedFile::is_deleted) {
+        prompt.push_str(DELETED_FILES_REQUIREMENT);
+    }
+    prompt.push('\n');
 
     prompt.push_str(&format!("Type: {}\n", group.commit_type.as_str()));
     if let Some(scope) = &group.scope {
         prompt.push_str(&format!("Scope: {}\n", scope));
     }
 
+    if let Some(notes) = &group.notes {
+        prompt.push_str(
+            "\nNOTES FROM THE AUTHOR (explain why the change was made; use them, do not quote them):\n",
+        );
+        prompt.push_str(notes);
+        prompt.push('\n');
+    }
+
     prompt.push_str("\nCHANGED FILES:\n");
     for file in files {
-        prompt.push_str(&format!("  - {}\n", file.path));
+        match (file.is_deleted(), &file.former_purpose) {
+            (true, Some(purpose)) => prompt.push_str(&format!(
+                "  - {} (deleted; former purpose: {})\n",
+                file.path, purpose
+            )),
+            (true, None) => prompt.push_str(&format!("  - {} (deleted)\n", file.path)),
+            (false, _) => prompt.push_str(&format!("  - {}\n", file.path)),
+        }
     }
 
     if let Some(diff_content) = diff {
         prompt.push_str("\nDIFF:\n");
-        let truncated = if diff_content.len() > MAX_DIFF_SIZE {
-            &diff_content[..MAX_DIFF_SIZE]
-        } else {
-            diff_content
-        };
-        prompt.push_str(truncated);
-        if diff_content.len() > MAX_DIFF_SIZE {
-            prompt.push_str("\n... (truncated)");
-        }
+        prompt.push_str(&condense_diff(diff_content, max_diff_tokens));
     }
 
     prompt.push_str(&format!(
@@ -336,6 +619,28 @@ pub fn build_commit_message_prompt(
     prompt
 }
 
+/// Builds the follow-up prompt asking for a refined commit message.
+///
+/// # Arguments
+///
+/// * `feedback` - What the user wants changed, e.g. "shorter"
+pub fn build_refine_prompt(feedback: &str) -> String {
+    let mut prompt = String::new();
+
+    prompt.push_str("Revise your last commit message based on this feedback:\n");
+    prompt.push_str(feedback.trim());
+    prompt.push_str("\n\nKeep following the REQUIREMENTS from the first message.");
+    prompt.push_str(&format!(
+        "\n\nGenerate ONLY the revised commit message between these markers:\n{}\n",
+        START_MARKER
+    ));
+    prompt.push_str("<description>\n\n");
+    prompt.push_str("<optional body with bullet points>\n");
+    prompt.push_str(&format!("{}\n", END_MARKER));
+
+    prompt
+}
+
 /// Calls the GitHub Copilot CLI with the given prompt.
 ///
 /// Executes `copilot -p <prompt>` as a subprocess and extracts the response
@@ -351,7 +656,8 @@ pub fn build_commit_message_prompt(
 /// # Returns
 ///
 /// The extracted response text between markers.
-fn call_copilot_cli(prompt: &str) -> Result<String> {
+fn call_copilot_cli(prompt: &str, timeout: Option<Duration>) -> Result<String> {
+    crate::subprocess::ensure_allowed("copilot")?;
     debug!(
         "Calling GitHub Copilot CLI with prompt length: {}",
         prompt.len()
@@ -401,18 +707,14 @@ fn call_copilot_cli(prompt: &str) -> Result<String> {
     //
     // If the Copilot CLI adds stdin support in the future, we should migrate
     // to that approach to eliminate process listing exposure.
-    let child = Command::new("copilot")
-        .arg("-p")
-        .arg(prompt)
-        .stdin(Stdio::null())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .context("Failed to spawn GitHub Copilot CLI")?;
-
-    let output = child
-        .wait_with_output()
-        .context("Failed to wait for GitHub Copilot CLI")?;
+    let mut cmd = Command::new("copilot");
+    cmd.arg("-p").arg(prompt);
+    // Always spawned through execute_with_timeout, so cancelling the request
+    // kills the CLI and everything it started
+    let cancel = crate::provider::cancel_flag();
+    let output =
+        crate::git::execute_with_timeout(&mut cmd, timeout.unwrap_or(Duration::MAX), &cancel)
+            .context("GitHub Copilot CLI did not finish")?;
 
     // Check exit status
     if !output.status.success() {
@@ -425,7 +727,7 @@ fn call_copilot_cli(prompt: &str) -> Result<String> {
         };
         error!(
             "GitHub Copilot CLI failed with status {}: {}",
-            output.status, &error_msg
+            output.status, error_msg
         );
         crate::logging::log_api_response("Copilot CLI", false, None);
         anyhow::bail!("GitHub Copilot CLI failed: {}", error_msg);
@@ -497,141 +799,367 @@ pub fn extract_response_between_markers(output: &str) -> Result<String> {
     Ok(result)
 }
 
-/// Parses AI response into commit groups.
-fn parse_groups_from_response(
-    response: &str,
-    files: Vec<ChangedFile>,
-    ticket: Option<String>,
-    diffs: &HashMap<String, String>,
-) -> Result<Vec<ChangeGroup>> {
-    // Try to parse JSON response
-    let groups_result: Result<Vec<serde_json::Value>, _> = serde_json::from_str(response);
-
-    match groups_result {
-        Ok(json_groups) => {
-            let mut groups = Vec::new();
-
-            for json_group in json_groups {
-                let type_str = json_group["type"].as_str().unwrap_or("feat");
-                let commit_type = parse_commit_type(type_str);
-
-                let scope = json_group["scope"].as_str().map(|s| s.to_string());
-
-                let description = json_group["description"]
-                    .as_str()
-                    .unwrap_or("update files")
-                    .to_string();
-
-                let file_paths: Vec<String> = json_group["files"]
-                    .as_array()
-                    .unwrap_or(&vec![])
-                    .iter()
-                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                    .collect();
+/// One commit group as returned by the AI, following the schema in the
+/// grouping prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AiGroup {
+    /// Conventional commit type, e.g. `feat`
+    #[serde(rename = "type")]
+    pub commit_type: String,
+    /// Optional scope; `null` or empty for none
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// Commit description
+    pub description: String,
+    /// Paths of the files in this group
+    pub files: Vec<String>,
+    /// Body lines without bullet prefix
+    #[serde(default)]
+    pub body_lines: Vec<String>,
+    /// Numbers (1-based) of the groups to commit before this one; entries
+    /// that are not numbers are ignored
+    #[serde(default, deserialize_with = "deserialize_group_numbers")]
+    pub depends_on: Vec<usize>,
+    /// Why the group is committed at its place, e.g. `after the build
+    /// change that adds the regex crate`
+    #[serde(default)]
+    pub order_note: Option<String>,
+}
 
-                let body_lines: Vec<String> = json_group["body_lines"]
-                    .as_array()
-                    .unwrap_or(&vec![])
-                    .iter()
-                    .filter_map(|v| {
-                        v.as_str().map(|s| {
-                            // Remove '- ' prefix if present (defensive programming)
-                            s.strip_prefix("- ").unwrap_or(s).to_string()
-                        })
-                    })
-                    .collect();
-
-                // Filter files that match this group
-                let group_files: Vec<ChangedFile> = files
-                    .iter()
-                    .filter(|f| file_paths.contains(&f.path))
-                    .cloned()
-                    .collect();
-
-                if !group_files.is_empty() {
-                    groups.push(ChangeGroup::new(
-                        commit_type,
-                        scope,
-                        group_files,
-                        ticket.clone(),
-                        description,
-                        body_lines,
-                    ));
-                }
-            }
+/// Reads group numbers leniently: numbers and numeric strings are kept,
+/// anything else is dropped, so a malformed hint never fails the grouping.
+fn deserialize_group_numbers<'de, D>(deserializer: D) -> std::result::Result<Vec<usize>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    let items = match value {
+        Some(serde_json::Value::Array(items)) => items,
+        Some(item) => vec![item],
+        None => Vec::new(),
+    };
+    Ok(items
+        .iter()
+        .filter_map(|item| match item {
+            serde_json::Value::Number(number) => number.as_u64(),
+            serde_json::Value::String(text) => text.trim().trim_start_matches('#').parse().ok(),
+            _ => None,
+        })
+        .filter_map(|number| usize::try_from(number).ok())
+        .collect())
+}
 
-            if groups.is_empty() {
-                // Fallback to single group if parsing failed
-                fallback_single_group(files, ticket, diffs)
-            } else {
-                // Validate no duplicate files across groups
-                validate_no_duplicate_files(&groups)?;
-                Ok(groups)
-            }
-        }
-        Err(_) => {
-            // JSON parsing failed, create single group with AI-generated message
-            fallback_single_group(files, ticket, diffs)
+/// Returns the commit order of AI groups as indices into `groups`.
+///
+/// Groups keep the order they were listed in, except that a group moves
+/// behind the groups its `depends_on` names. Numbers that name no other
+/// group are ignored, and dependency cycles are broken in listed order.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::copilot::{commit_order, parse_grouping_response};
+///
+/// let groups = parse_grouping_response(
+///     r#"[
+///         {"type": "feat", "description": "use regex", "files": ["src/lib.rs"], "depends_on": [2]},
+///         {"type": "build", "description": "add regex", "files": ["Cargo.toml"]},
+///         {"type": "docs", "description": "document", "files": ["README.md"], "depends_on": ["9"]}
+///     ]"#,
+/// )
+/// .unwrap();
+/// assert_eq!(commit_order(&groups), vec![1, 0, 2]);
+/// ```
+pub fn commit_order(groups: &[AiGroup]) -> Vec<usize> {
+    let dependencies: Vec<Vec<usize>> = groups
+        .iter()
+        .enumerate()
+        .map(|(idx, group)| {
+            group
+                .depends_on
+                .iter()
+                .filter_map(|number| number.checked_sub(1))
+                .filter(|&dep| dep != idx && dep < groups.len())
+                .collect()
+        })
+        .collect();
+
+    let mut order = Vec::with_capacity(groups.len());
+    let mut placed = vec![false; groups.len()];
+    while order.len() < groups.len() {
+        let ready = (0..groups.len())
+            .find(|&idx| !placed[idx] && dependencies[idx].iter().all(|&dep| placed[dep]));
+        // In a cycle nothing is ready; the first remaining group goes next
+        let next = ready.unwrap_or_else(|| {
+            (0..groups.len())
+                .find(|&idx| !placed[idx])
+                .expect("a group is left")
+        });
+        if ready.is_none() {
+            warn!(
+                "AI grouping: dependency cycle at group #{}, keeping the listed order",
+                next + 1
+            );
         }
+        placed[next] = true;
+        order.push(next);
     }
+    order
 }
 
-/// Creates a single fallback group when AI grouping fails.
-fn fallback_single_group(
-    files: Vec<ChangedFile>,
+/// Parses a grouping response into its groups.
+///
+/// Accepts a JSON array of groups or an object with a `groups` array (the
+/// shape JSON output modes produce).
+///
+/// # Errors
+///
+/// Returns an error naming the position of invalid JSON, or the group
+/// number and field that does not match the schema.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::copilot::parse_grouping_response;
+///
+/// let groups = parse_grouping_response(
+///     r#"{"groups": [{"type": "fix", "description": "handle empty input", "files": ["src/lib.rs"]}]}"#,
+/// )
+/// .unwrap();
+/// assert_eq!(groups[0].files, vec!["src/lib.rs"]);
+///
+/// let err = parse_grouping_response(r#"[{"type": "fix", "files": []}]"#).unwrap_err();
+/// assert!(format!("{:#}", err).contains("group #1"));
+/// ```
+pub fn parse_grouping_response(response: &str) -> Result<Vec<AiGroup>> {
+    let value: serde_json::Value =
+        serde_json::from_str(response).context("AI grouping response is not valid JSON")?;
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(mut object) => match object.remove("groups") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => bail!("AI grouping response has no \"groups\" array"),
+        },
+        _ => bail!("AI grouping response must be a JSON array or object"),
+    };
+
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(idx, item)| {
+            serde_json::from_value(item)
+                .with_context(|| format!("Invalid group #{} in AI grouping response", idx + 1))
+        })
+        .collect()
+}
+
+/// Turns parsed AI groups into commit groups.
+///
+/// Files assigned to several groups and files the AI forgot are repaired:
+/// duplicates stay in their first group, and forgotten files are placed by
+/// [`reconcile_groups`]. The groups are returned in their [`commit_order`],
+/// with the AI's explanation as [`ChangeGroup::order_note`].
+///
+/// # Errors
+///
+/// Returns an error for unknown commit types.
+fn groups_from_ai(
+    ai_groups: Vec<AiGroup>,
+    files: &[ChangedFile],
     ticket: Option<String>,
-    diffs: &HashMap<String, String>,
 ) -> Result<Vec<ChangeGroup>> {
-    // Determine primary commit type from files
-    let commit_type =
-        crate::inference::infer_commit_type(files.first().map(|f| f.path.as_str()).unwrap_or(""));
+    let order = commit_order(&ai_groups);
+    let mut groups = Vec::new();
+    let mut listed = Vec::new();
+    for (idx, ai_group) in ai_groups.into_iter().enumerate() {
+        let commit_type = CommitType::all()
+            .iter()
+            .copied()
+            .find(|t| t.as_str() == ai_group.commit_type.trim().to_lowercase())
+            .with_context(|| {
+                format!(
+                    "Invalid group #{} in AI grouping response: unknown commit type '{}'",
+                    idx + 1,
+                    ai_group.commit_type
+                )
+            })?;
+
+        for path in &ai_group.files {
+            if !files.iter().any(|f| &f.path == path) {
+                warn!("AI grouping lists unknown file '{}', ignoring it", path);
+            }
+        }
+        let group_files: Vec<ChangedFile> = files
+            .iter()
+            .filter(|f| ai_group.files.contains(&f.path))
+            .filter(|f| {
+                let assigned = groups
+                    .iter()
+                    .any(|g: &ChangeGroup| g.files.iter().any(|gf| gf.path == f.path));
+                if assigned {
+                    warn!(
+                        "AI grouping repair: '{}' is in several groups, keeping the first",
+                        f.path
+                    );
+                }
+                !assigned
+            })
+            .cloned()
+            .collect();
+        if group_files.is_empty() {
+            warn!(
+                "AI group #{} ('{}') contains no changed files, dropping it",
+                idx + 1,
+                ai_group.description
+            );
+            continue;
+        }
 
-    // Get scope from first file
-    let scope = crate::inference::infer_scope(files.first().map(|f| f.path.as_str()).unwrap_or(""));
+        let body_lines = ai_group
+            .body_lines
+            .iter()
+            // Remove '- ' prefix if present (defensive programming)
+            .map(|line| line.strip_prefix("- ").unwrap_or(line).to_string())
+            .collect();
+        let mut group = ChangeGroup::new(
+            commit_type,
+            ai_group.scope.filter(|s| !s.trim().is_empty()),
+            group_files,
+            ticket.clone(),
+            ai_group.description,
+            body_lines,
+        );
+        group.order_note = ai_group
+            .order_note
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty());
+        groups.push(group);
+        listed.push(idx);
+    }
 
-    // Try to generate a good description with AI
-    let description =
-        if let Some(first_file) = files.first() {
-            let mut prompt =
-                format!(
-            "Generate a short commit description (max 50 chars) for:\nType: {}\nFiles: {}\n",
-            commit_type.as_str(),
-            files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>().join(", ")
+    // Duplicates were resolved in listed order; now put the groups in
+    // commit order
+    let mut ranked: Vec<(usize, ChangeGroup)> = listed
+        .into_iter()
+        .map(|idx| order.iter().position(|&o| o == idx).unwrap_or(idx))
+        .zip(groups)
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+    let mut groups: Vec<ChangeGroup> = ranked.into_iter().map(|(_, group)| group).collect();
+
+    let repaired = reconcile_groups(&mut groups, files, ticket);
+    if repaired > 0 {
+        warn!(
+            "AI grouping repair: assigned {} forgotten file(s)",
+            repaired
         );
+    }
+    Ok(groups)
+}
 
-            // Add diff context if available
-            if let Some(diff) = diffs.get(&first_file.path) {
-                prompt.push_str("\nDIFF PREVIEW:\n");
-                let truncated = if diff.len() > MAX_DIFF_SIZE {
-                    format!("{}... (truncated)", &diff[..MAX_DIFF_SIZE])
-                } else {
-                    diff.clone()
-                };
-                prompt.push_str(&truncated);
+/// Assigns changed files missing from `groups` to the best-matching group.
+///
+/// A group matches a file if it contains files from the same directory
+/// (strongest), has the scope inferred for the file, or the inferred commit
+/// type. Files matching no group get new heuristic groups. Every assignment
+/// is logged.
+///
+/// # Returns
+///
+/// The number of files that were missing.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::copilot::reconcile_groups;
+/// use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+/// use git2::Status;
+///
+/// let users = ChangedFile::new("src/api/users.rs".to_string(), Status::INDEX_NEW);
+/// let orders = ChangedFile::new("src/api/orders.rs".to_string(), Status::INDEX_NEW);
+/// let mut groups = vec![ChangeGroup::new(
+///     CommitType::Feat,
+///     Some("api".to_string()),
+///     vec![users.clone()],
+///     None,
+///     "add endpoints".to_string(),
+///     vec![],
+/// )];
+///
+/// assert_eq!(reconcile_groups(&mut groups, &[users, orders], None), 1);
+/// assert_eq!(groups[0].files.len(), 2);
+/// ```
+pub fn reconcile_groups(
+    groups: &mut Vec<ChangeGroup>,
+    files: &[ChangedFile],
+    ticket: Option<String>,
+) -> usize {
+    let missing: Vec<ChangedFile> = files
+        .iter()
+        .filter(|f| {
+            !groups
+                .iter()
+                .any(|g| g.files.iter().any(|gf| gf.path == f.path))
+        })
+        .cloned()
+        .collect();
+    let count = missing.len();
+
+    let mut unmatched = Vec::new();
+    for file in missing {
+        let best = groups
+            .iter()
+            .enumerate()
+            .map(|(idx, group)| (idx, group_match_score(group, &file)))
+            .filter(|&(_, score)| score > 0)
+            // The first group wins ties
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)));
+        match best {
+            Some((idx, _)) => {
+                warn!(
+                    "AI grouping repair: assigned forgotten '{}' to group '{}'",
+                    file.path, groups[idx].description
+                );
+                groups[idx].files.push(file);
             }
+            None => unmatched.push(file),
+        }
+    }
 
-            prompt.push_str(&format!(
-                "\n\nRespond with:\n{}\n<your description here>\n{}\n",
-                START_MARKER, END_MARKER
-            ));
-            call_copilot_cli(&prompt)
-                .ok()
-                .and_then(|s| s.lines().next().map(|l| l.to_string()))
-                .unwrap_or_else(|| crate::inference::infer_description(&files, commit_type, &scope))
-        } else {
-            "update files".to_string()
-        };
-
-    let body_lines = crate::inference::infer_body_lines(&files);
+    if !unmatched.is_empty() {
+        let new_groups = crate::inference::build_groups(unmatched, ticket);
+        for group in &new_groups {
+            let paths: Vec<&str> = group.files.iter().map(|f| f.path.as_str()).collect();
+            warn!(
+                "AI grouping repair: new group '{}' for forgotten file(s): {}",
+                group.description,
+                paths.join(", ")
+            );
+        }
+        groups.extend(new_groups);
+    }
+    count
+}
 
-    Ok(vec![ChangeGroup::new(
-        commit_type,
-        scope,
-        files,
-        ticket,
-        description,
-        body_lines,
-    )])
+/// Scores how well a file fits into a group; 0 means no match.
+fn group_match_score(group: &ChangeGroup, file: &ChangedFile) -> u32 {
+    let parent = |path: &str| std::path::Path::new(path).parent().map(|p| p.to_path_buf());
+    let mut score = 0;
+    if group
+        .files
+        .iter()
+        .any(|f| parent(&f.path) == parent(&file.path))
+    {
+        score += 3;
+    }
+    let scope = crate::inference::file_scope(file);
+    if scope.is_some() && scope == group.scope {
+        score += 2;
+    }
+    if crate::inference::file_commit_type(file) == group.commit_type {
+        score += 1;
+    }
+    score
 }
 
 /// Parses a commit type string into CommitType enum.
@@ -0,0 +1,407 @@
+//! Background daemon that keeps the status of a repository warm.
+//!
+//! Collecting the changed files of a large monorepo takes seconds, most of
+//! it spent by libgit2 walking the working tree. `commit-wizard daemon`
+//! keeps the repository open and remembers the last status; with the
+//! `watch` feature it watches the working tree (like `--watch`) and only
+//! collects the status again after files changed, or when `HEAD` or the
+//! index moved. Interactive runs ask the daemon over a unix socket in the
+//! git directory (`commit-wizard.sock`) and collect the status themselves
+//! when no daemon answers.
+//!
+//! The protocol is one JSON request per connection, answered by one JSON
+//! line. Answers of another [`PROTOCOL_VERSION`] are ignored, so a daemon
+//! left running across an upgrade is harmless.
+//!
+//! Unix sockets are not available on Windows, so this module is compiled
+//! on unix platforms only.
+
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context, Result};
+use git2::{Oid, Repository, Status};
+use serde::{Deserialize, Serialize};
+
+use crate::git::{collect_changed_files, collect_untracked_files, RepoContext};
+use crate::types::{ChangedFile, FileKind};
+
+/// Name of the daemon's socket in the git directory.
+pub const SOCKET_FILE: &str = "commit-wizard.sock";
+
+/// Version of the protocol; answers of other versions are ignored.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// How long a client waits for an answer, which may take as long as
+/// collecting the status directly.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long the daemon waits for a client to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Returns the daemon socket of the worktree with git directory `git_dir`.
+pub fn socket_path(git_dir: &Path) -> PathBuf {
+    git_dir.join(SOCKET_FILE)
+}
+
+/// Changed files of a repository, as collected at startup.
+#[derive(Debug, Clone, Default)]
+pub struct RepoStatus {
+    /// Changed tracked files (see [`collect_changed_files`])
+    pub tracked: Vec<ChangedFile>,
+    /// Untracked files (see [`collect_untracked_files`])
+    pub untracked: Vec<ChangedFile>,
+}
+
+impl RepoStatus {
+    /// Collects the status of `repo` directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the status cannot be read.
+    pub fn collect(repo: &Repository) -> Result<Self> {
+        Ok(Self {
+            tracked: collect_changed_files(repo, false)?,
+            untracked: collect_untracked_files(repo)?,
+        })
+    }
+}
+
+/// A request sent to the daemon.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "kebab-case")]
+enum Request {
+    /// Asks for the changed files
+    Status,
+    /// Asks the daemon to exit
+    Stop,
+}
+
+/// A changed file on the wire.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WireFile {
+    path: String,
+    status: u32,
+    old_path: Option<String>,
+    submodule: bool,
+}
+
+impl WireFile {
+    fn from_file(file: &ChangedFile) -> Self {
+        Self {
+            path: file.path.clone(),
+            status: file.status.bits(),
+            old_path: file.old_path.clone(),
+            submodule: file.is_submodule(),
+        }
+    }
+
+    fn into_file(self) -> ChangedFile {
+        let kind = if self.submodule {
+            FileKind::Submodule
+        } else {
+            FileKind::Regular
+        };
+        ChangedFile::new(self.path, Status::from_bits_truncate(self.status))
+            .with_old_path(self.old_path)
+            .with_kind(kind)
+    }
+}
+
+/// The daemon's answer.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Response {
+    version: u32,
+    #[serde(default)]
+    tracked: Vec<WireFile>,
+    #[serde(default)]
+    untracked: Vec<WireFile>,
+    /// Why the request failed, if it did
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// What the cached status was collected from: `HEAD` and the index file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RepoState {
+    head: Option<Oid>,
+    index: Option<(SystemTime, u64)>,
+}
+
+impl RepoState {
+    fn read(repo: &Repository, git_dir: &Path) -> Self {
+        let index = fs::metadata(git_dir.join("index"))
+            .ok()
+            .and_then(|meta| Some((meta.modified().ok()?, meta.len())));
+        Self {
+            head: repo.head().ok().and_then(|head| head.target()),
+            index,
+        }
+    }
+}
+
+/// A daemon serving the status of one worktree.
+///
+/// The socket is removed when the daemon is dropped.
+pub struct Daemon {
+    ctx: RepoContext,
+    repo: Repository,
+    listener: UnixListener,
+    socket: PathBuf,
+    /// Raised when files in the working tree change; [`None`] collects the
+    /// status for every request
+    changes: Option<Arc<AtomicBool>>,
+    cached: Option<(RepoState, Response)>,
+    // Kept alive for its notifications
+    #[cfg(feature = "watch")]
+    _watcher: Option<crate::watch::WorktreeWatcher>,
+}
+
+impl Daemon {
+    /// Opens the repository of `ctx` and listens on its socket.
+    ///
+    /// A socket left behind by a daemon that no longer runs is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a daemon already serves the worktree, or the
+    /// repository or socket cannot be opened (e.g. when the path of the
+    /// socket is too long for a unix socket).
+    pub fn bind(ctx: &RepoContext) -> Result<Self> {
+        let socket = socket_path(&ctx.git_dir);
+        if socket.exists() {
+            if UnixStream::connect(&socket).is_ok() {
+                bail!(
+                    "A daemon already serves {}\n\
+                     Hint: stop it with `commit-wizard daemon --stop`",
+                    ctx.workdir.display()
+                );
+            }
+            fs::remove_file(&socket)
+                .with_context(|| format!("Failed to remove stale socket {}", socket.display()))?;
+        }
+        let repo = ctx.open()?;
+        let listener = UnixListener::bind(&socket)
+            .with_context(|| format!("Failed to listen on {}", socket.display()))?;
+
+        #[cfg(feature = "watch")]
+        let (watcher, changes) = match crate::watch::WorktreeWatcher::start(&ctx.workdir) {
+            Ok(watcher) => {
+                let changes = watcher.events();
+                (Some(watcher), Some(changes))
+            }
+            Err(e) => {
+                log::warn!("Collecting the status for every request: {:#}", e);
+                (None, None)
+            }
+        };
+        #[cfg(not(feature = "watch"))]
+        let changes = None;
+
+        Ok(Self {
+            ctx: ctx.clone(),
+            repo,
+            listener,
+            socket,
+            changes,
+            cached: None,
+            #[cfg(feature = "watch")]
+            _watcher: watcher,
+        })
+    }
+
+    /// Returns the path of the socket the daemon listens on.
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+
+    /// Answers requests one at a time until a client asks the daemon to
+    /// stop.
+    ///
+    /// Failures of single connections are logged and do not stop the
+    /// daemon.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket stops accepting connections.
+    pub fn serve(mut self) -> Result<()> {
+        log::info!("Daemon serving {}", self.ctx.workdir.display());
+        loop {
+            let (stream, _) = self
+                .listener
+                .accept()
+                .context("Failed to accept a connection")?;
+            match self.handle(stream) {
+                Ok(true) => {
+                    log::info!("Daemon stopped");
+                    return Ok(());
+                }
+                Ok(false) => {}
+                Err(e) => log::warn!("Daemon request failed: {:#}", e),
+            }
+        }
+    }
+
+    /// Answers one connection; returns whether the daemon should stop.
+    fn handle(&mut self, stream: UnixStream) -> Result<bool> {
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        let request: Request = serde_json::from_str(&line).context("Invalid request")?;
+        log::debug!("Daemon request: {:?}", request);
+
+        let stop = matches!(request, Request::Stop);
+        let response = match request {
+            Request::Status => self.status(),
+            Request::Stop => Response {
+                version: PROTOCOL_VERSION,
+                ..Response::default()
+            },
+        };
+        let mut stream = stream;
+        serde_json::to_writer(&mut stream, &response)?;
+        stream.write_all(b"\n")?;
+        Ok(stop)
+    }
+
+    /// Returns the status, collecting it again if anything changed since
+    /// it was cached.
+    fn status(&mut self) -> Response {
+        let state = RepoState::read(&self.repo, &self.ctx.git_dir);
+        // Clear the flag first, so changes made while collecting count
+        let files_changed = self
+            .changes
+            .as_ref()
+            .map_or(true, |changes| changes.swap(false, Ordering::SeqCst));
+        if let Some((cached_state, response)) = &self.cached {
+            if !files_changed && *cached_state == state {
+                return response.clone();
+            }
+        }
+
+        match RepoStatus::collect(&self.repo) {
+            Ok(status) => {
+                let response = Response {
+                    version: PROTOCOL_VERSION,
+                    tracked: status.tracked.iter().map(WireFile::from_file).collect(),
+                    untracked: status.untracked.iter().map(WireFile::from_file).collect(),
+                    error: None,
+                };
+                self.cached = Some((state, response.clone()));
+                response
+            }
+            Err(e) => {
+                self.cached = None;
+                Response {
+                    version: PROTOCOL_VERSION,
+                    error: Some(format!("{:#}", e)),
+                    ..Response::default()
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.socket);
+    }
+}
+
+/// Sends `request` to the daemon of `git_dir`.
+///
+/// Returns [`None`] if no daemon listens on the socket.
+fn send(git_dir: &Path, request: &Request) -> Result<Option<Response>> {
+    let socket = socket_path(git_dir);
+    let mut stream = match UnixStream::connect(&socket) {
+        Ok(stream) => stream,
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => {
+            return Ok(None);
+        }
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to connect to {}", socket.display()))
+        }
+    };
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    serde_json::to_writer(&mut stream, request)?;
+    stream.write_all(b"\n")?;
+
+    let mut line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut line)
+        .context("The daemon did not answer")?;
+    let response: Response = serde_json::from_str(&line).context("Invalid daemon answer")?;
+    if response.version != PROTOCOL_VERSION {
+        bail!(
+            "The daemon speaks protocol version {} instead of {}; restart it",
+            response.version,
+            PROTOCOL_VERSION
+        );
+    }
+    if let Some(error) = response.error {
+        bail!("The daemon failed: {}", error);
+    }
+    Ok(Some(response))
+}
+
+/// Asks the daemon of the worktree for its status.
+///
+/// # Returns
+///
+/// The changed files, or [`None`] if no daemon runs for the worktree.
+///
+/// # Errors
+///
+/// Returns an error if the daemon does not answer in time, answers with
+/// another protocol version, or failed to collect the status. Callers
+/// collect the status directly then.
+///
+/// # Examples
+///
+/// ```no_run
+/// use commit_wizard::daemon::{query_status, RepoStatus};
+/// use commit_wizard::git::discover_repo;
+/// use std::path::Path;
+///
+/// let ctx = discover_repo(Path::new(".")).unwrap();
+/// let status = match query_status(&ctx) {
+///     Ok(Some(status)) => status,
+///     _ => RepoStatus::collect(&ctx.open().unwrap()).unwrap(),
+/// };
+/// println!("{} changed file(s)", status.tracked.len());
+/// ```
+pub fn query_status(ctx: &RepoContext) -> Result<Option<RepoStatus>> {
+    let Some(response) = send(&ctx.git_dir, &Request::Status)? else {
+        return Ok(None);
+    };
+    Ok(Some(RepoStatus {
+        tracked: response
+            .tracked
+            .into_iter()
+            .map(WireFile::into_file)
+            .collect(),
+        untracked: response
+            .untracked
+            .into_iter()
+            .map(WireFile::into_file)
+            .collect(),
+    }))
+}
+
+/// Asks the daemon of the worktree to exit.
+///
+/// # Returns
+///
+/// Whether a daemon was running.
+///
+/// # Errors
+///
+/// Returns an error if the daemon does not answer.
+pub fn stop(ctx: &RepoContext) -> Result<bool> {
+    Ok(send(&ctx.git_dir, &Request::Stop)?.is_some())
+}
@@ -0,0 +1,333 @@
+//! Diagnostic reports for bug reports.
+//!
+//! When the wizard fails unexpectedly, it offers to write a report the user
+//! can attach to an issue. The report contains the error chain, build and
+//! environment information, a snapshot of the effective configuration, the
+//! end of the log file (with `--log`), and, only if `[diagnostics]
+//! include_prompts` is enabled, the last AI prompt and response.
+//!
+//! Collected text is passed through [`redact`], which removes API tokens and
+//! credentials with the prompt redaction rules of [`crate::sanitize`], as
+//! well as e-mail addresses and the home directory. Prompts contain
+//! source code diffs, which is why they are opt-in.
+
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::build_info::BuildInfo;
+use crate::provider::{flatten_conversation, AiProvider, ChatMessage, ProviderLayer};
+use crate::sanitize::{Redactor, MASK};
+
+/// Number of log lines included in a report.
+const LOG_EXCERPT_LINES: usize = 200;
+
+/// Environment variables holding credentials; their values are redacted
+/// and only their presence is reported.
+const SECRET_VARS: &[&str] = &["GITHUB_TOKEN", "GH_TOKEN", "GITLAB_TOKEN", "OPENAI_API_KEY"];
+
+/// Last prompt sent to the AI provider and its response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exchange {
+    /// Prompt as sent (conversations are flattened)
+    pub prompt: String,
+    /// Response text, or the error message of a failed request
+    pub response: String,
+}
+
+/// Shared slot holding the last AI exchange.
+#[derive(Debug, Clone, Default)]
+pub struct ExchangeRecorder {
+    last: Arc<Mutex<Option<Exchange>>>,
+}
+
+impl ExchangeRecorder {
+    /// Creates an empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores an exchange, replacing the previous one.
+    pub fn record(&self, prompt: &str, response: &str) {
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        *last = Some(Exchange {
+            prompt: prompt.to_string(),
+            response: response.to_string(),
+        });
+    }
+
+    /// Returns the last exchange, if any.
+    pub fn last(&self) -> Option<Exchange> {
+        self.last.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Provider that records the last exchange for diagnostic reports.
+pub struct RecordingProvider {
+    inner: Box<dyn AiProvider>,
+    recorder: ExchangeRecorder,
+}
+
+impl RecordingProvider {
+    /// Wraps `inner` so its requests are stored in `recorder`.
+    pub fn new(inner: Box<dyn AiProvider>, recorder: ExchangeRecorder) -> Self {
+        Self { inner, recorder }
+    }
+
+    fn recorded(&self, prompt: &str, result: Result<String>) -> Result<String> {
+        match &result {
+            Ok(response) => self.recorder.record(prompt, response),
+            Err(e) => self.recorder.record(prompt, &format!("error: {:#}", e)),
+        }
+        result
+    }
+}
+
+impl ProviderLayer for RecordingProvider {
+    fn inner(&self) -> &dyn AiProvider {
+        self.inner.as_ref()
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.recorded(prompt, self.inner.complete(prompt))
+    }
+
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        self.recorded(prompt, self.inner.complete_json(prompt))
+    }
+
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        let result = self.inner.complete_n(prompt, n);
+        let response = match &result {
+            Ok(responses) => responses.join("\n\n---\n\n"),
+            Err(e) => format!("error: {:#}", e),
+        };
+        self.recorder.record(prompt, &response);
+        result
+    }
+
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        self.recorded(&flatten_conversation(messages), self.inner.chat(messages))
+    }
+}
+
+/// Information collected while the wizard runs, used if it fails.
+#[derive(Debug, Default)]
+pub struct DiagnosticContext {
+    /// Log file written with `--log`
+    pub log_path: Option<PathBuf>,
+    /// Snapshot of the effective configuration
+    pub config: Option<String>,
+    /// Last AI exchange, if prompts may be included
+    pub exchange: Option<ExchangeRecorder>,
+}
+
+/// Removes credentials and personal data from text.
+///
+/// Redacts values of the token environment variables and everything
+/// [`Redactor::for_reports`] masks, replaces e-mail addresses with
+/// `[EMAIL]`, and replaces the home directory with `~`.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::diagnostics::redact;
+///
+/// let text = redact("Authorization: Bearer abc123 from dev@example.com");
+/// assert_eq!(text, "Authorization: Bearer [REDACTED] from [EMAIL]");
+/// ```
+pub fn redact(text: &str) -> String {
+    let mut text = text.to_string();
+
+    for var in SECRET_VARS {
+        if let Ok(value) = env::var(var) {
+            if value.trim().len() >= 8 {
+                text = text.replace(value.trim(), MASK);
+            }
+        }
+    }
+
+    text = Redactor::for_reports().redact(&text).text;
+    let email = Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}")
+        .expect("valid e-mail pattern");
+    text = email.replace_all(&text, "[EMAIL]").into_owned();
+
+    if let Some(home) = dirs::home_dir() {
+        let home = home.to_string_lossy();
+        if home.len() > 1 {
+            text = text.replace(home.as_ref(), "~");
+        }
+    }
+    text
+}
+
+/// Summarizes the environment without revealing secrets.
+///
+/// Values are already redacted; credential variables are only reported as
+/// `set` or `unset`.
+pub fn environment_summary() -> Vec<(String, String)> {
+    let mut summary = vec![
+        (
+            "os".to_string(),
+            format!("{} ({})", env::consts::OS, env::consts::ARCH),
+        ),
+        ("git".to_string(), command_version("git")),
+        ("copilot".to_string(), command_version("copilot")),
+    ];
+    for var in [
+        "TERM",
+        "COLORTERM",
+        "LANG",
+        "EDITOR",
+        "VISUAL",
+        "OLLAMA_HOST",
+    ] {
+        let value = env::var(var).map_or_else(|_| "(unset)".to_string(), |v| redact(&v));
+        summary.push((var.to_string(), value));
+    }
+    for var in SECRET_VARS {
+        let state = match env::var(var) {
+            Ok(value) if !value.trim().is_empty() => "set",
+            _ => "unset",
+        };
+        summary.push((var.to_string(), state.to_string()));
+    }
+    summary
+}
+
+/// Returns the first line of `<command> --version`, or `not found`.
+///
+/// Nothing is run with `--no-subprocess`.
+fn command_version(command: &str) -> String {
+    if crate::subprocess::is_disabled() {
+        return "not checked (--no-subprocess)".to_string();
+    }
+    Command::new(command)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .map(|line| line.trim().to_string())
+        })
+        .unwrap_or_else(|| "not found".to_string())
+}
+
+/// Returns the last `max_lines` lines of a log file.
+fn log_excerpt(path: &Path, max_lines: usize) -> Result<String> {
+    let file = fs::File::open(path)
+        .with_context(|| format!("Failed to open log file {}", path.display()))?;
+    let lines: Vec<String> = BufReader::new(file).lines().collect::<Result<_, _>>()?;
+    let skip = lines.len().saturating_sub(max_lines);
+    Ok(lines[skip..].join("\n"))
+}
+
+/// Assembles a redacted report for `error`.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::anyhow;
+/// use commit_wizard::diagnostics::{build_report, DiagnosticContext};
+///
+/// let error = anyhow!("token=abc123").context("Failed to commit");
+/// let report = build_report(&error, &DiagnosticContext::default());
+/// assert!(report.contains("Failed to commit"));
+/// assert!(report.contains("token=[REDACTED]"));
+/// ```
+pub fn build_report(error: &anyhow::Error, ctx: &DiagnosticContext) -> String {
+    let mut report = String::from("# Commit Wizard diagnostic report\n\n");
+    report.push_str(&format!(
+        "Created: {}\n\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S %z")
+    ));
+
+    report.push_str("## Error\n\n");
+    for (idx, cause) in error.chain().enumerate() {
+        report.push_str(&format!("{}. {}\n", idx + 1, redact(&cause.to_string())));
+    }
+
+    report.push_str("\n## Build\n\n");
+    report.push_str(&BuildInfo::current().to_string());
+    report.push('\n');
+
+    report.push_str("\n## Environment\n\n");
+    for (key, value) in environment_summary() {
+        report.push_str(&format!("{}: {}\n", key, value));
+    }
+
+    report.push_str("\n## Configuration\n\n");
+    match &ctx.config {
+        Some(config) => report.push_str(&redact(config)),
+        None => report.push_str("(not loaded)"),
+    }
+    report.push('\n');
+
+    report.push_str("\n## Log\n\n");
+    match &ctx.log_path {
+        Some(path) => match log_excerpt(path, LOG_EXCERPT_LINES) {
+            Ok(excerpt) => report.push_str(&redact(&excerpt)),
+            Err(e) => report.push_str(&format!("(unavailable: {:#})", e)),
+        },
+        None => report.push_str("(logging disabled; run with --log to include it)"),
+    }
+    report.push('\n');
+
+    report.push_str("\n## Last AI exchange\n\n");
+    match ctx.exchange.as_ref().map(ExchangeRecorder::last) {
+        Some(Some(exchange)) => {
+            report.push_str("### Prompt\n\n");
+            report.push_str(&redact(&exchange.prompt));
+            report.push_str("\n\n### Response\n\n");
+            report.push_str(&redact(&exchange.response));
+            report.push('\n');
+        }
+        Some(None) => report.push_str("(no AI request made)\n"),
+        None => report.push_str("(not included; set `include_prompts = true` in [diagnostics])\n"),
+    }
+
+    report
+}
+
+/// Writes a report to a new file in `dir`.
+///
+/// # Returns
+///
+/// The path of the written file.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be created or the file cannot
+/// be written.
+pub fn write_report(report: &str, dir: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    let name = format!(
+        "commit-wizard-report-{}.md",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = dir.join(name);
+    fs::write(&path, report)
+        .with_context(|| format!("Failed to write report {}", path.display()))?;
+    Ok(path)
+}
+
+/// Returns the directory reports are written to.
+///
+/// Reports go to the data directory rather than the repository so they do
+/// not show up as untracked files.
+pub fn default_report_dir() -> PathBuf {
+    match dirs::data_dir() {
+        Some(dir) => dir.join("commit-wizard").join("reports"),
+        None => env::temp_dir(),
+    }
+}
@@ -0,0 +1,245 @@
+//! Diffs of the changed files, loaded when needed.
+//!
+//! Keeping the diff of every changed file in memory for the whole session
+//! costs hundreds of megabytes in large trees. A [`DiffStore`] instead loads
+//! diffs on request, in one libgit2 pass per batch, and keeps the most
+//! recently used ones up to a size budget (`[diff] cache_mb`). The analysis
+//! passes, the AI prompts, and the diff viewer share one store, so a diff
+//! loaded for one of them is usually still cached for the next.
+//!
+//! Diffs compare `HEAD` with the working tree, including staged changes,
+//! like [`crate::git::collect_diffs`]. Files changed on disk must be
+//! [invalidated](DiffStore::invalidate) before their diff is read again.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use anyhow::Result;
+
+use crate::git::{collect_path_diffs, RepoContext};
+use crate::prompt::condense_diff;
+use crate::types::FileDiff;
+
+/// Number of files whose diffs are loaded in one pass.
+pub const BATCH_FILES: usize = 256;
+
+/// Bytes counted per cached entry besides the path and patch.
+const ENTRY_OVERHEAD: usize = 64;
+
+/// A cached diff; [`None`] for files without textual changes.
+#[derive(Debug)]
+struct Entry {
+    diff: Option<Arc<FileDiff>>,
+    size: usize,
+    used: u64,
+}
+
+/// Cached diffs in order of use.
+#[derive(Debug, Default)]
+struct Cache {
+    entries: HashMap<String, Entry>,
+    /// Paths by last use, least recently used first
+    order: BTreeMap<u64, String>,
+    bytes: usize,
+    clock: u64,
+}
+
+impl Cache {
+    /// Returns the cached diff of `path` and marks it as used, or [`None`]
+    /// if it is not cached.
+    fn get(&mut self, path: &str) -> Option<Option<Arc<FileDiff>>> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(path)?;
+        self.order.remove(&entry.used);
+        entry.used = self.clock;
+        self.order.insert(self.clock, path.to_string());
+        Some(entry.diff.clone())
+    }
+
+    /// Caches the diff of `path`, evicting the least recently used diffs
+    /// beyond `budget` bytes. Diffs larger than the budget are not cached.
+    fn insert(&mut self, path: String, diff: Option<Arc<FileDiff>>, budget: usize) {
+        self.remove(&path);
+        let size = path.len() + ENTRY_OVERHEAD + diff.as_ref().map_or(0, |d| d.patch.len());
+        if size > budget {
+            return;
+        }
+        while self.bytes + size > budget {
+            let Some((_, oldest)) = self.order.pop_first() else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                self.bytes -= entry.size;
+            }
+        }
+        self.clock += 1;
+        self.order.insert(self.clock, path.clone());
+        self.entries.insert(
+            path,
+            Entry {
+                diff,
+                size,
+                used: self.clock,
+            },
+        );
+        self.bytes += size;
+    }
+
+    fn remove(&mut self, path: &str) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.order.remove(&entry.used);
+            self.bytes -= entry.size;
+        }
+    }
+}
+
+/// Loads and caches the diffs of changed files.
+///
+/// The store can be shared between threads; loading a diff opens the
+/// repository for that call.
+#[derive(Debug)]
+pub struct DiffStore {
+    ctx: RepoContext,
+    budget: usize,
+    cache: Mutex<Cache>,
+}
+
+impl DiffStore {
+    /// Creates a store for the repository of `ctx` that caches up to
+    /// `budget` bytes of diffs.
+    pub fn new(ctx: RepoContext, budget: usize) -> Self {
+        Self {
+            ctx,
+            budget,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    fn cache(&self) -> MutexGuard<'_, Cache> {
+        // A panic while holding the lock leaves the cache consistent
+        self.cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Returns the diff of `path`, loading it if it is not cached.
+    ///
+    /// # Returns
+    ///
+    /// The diff, or [`None`] if the file has no textual changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository cannot be opened or the diff
+    /// cannot be computed.
+    pub fn get(&self, path: &str) -> Result<Option<Arc<FileDiff>>> {
+        Ok(self.get_many([path])?.remove(path))
+    }
+
+    /// Returns the diffs of `paths`, loading the ones that are not cached
+    /// in a single pass.
+    ///
+    /// Files without textual changes are left out. The returned diffs stay
+    /// valid even if the cache evicts them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository cannot be opened or the diffs
+    /// cannot be computed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use commit_wizard::diff_store::DiffStore;
+    /// use commit_wizard::git::discover_repo;
+    /// use std::path::Path;
+    ///
+    /// let store = DiffStore::new(discover_repo(Path::new(".")).unwrap(), 64 << 20);
+    /// let diffs = store.get_many(["src/lib.rs", "README.md"]).unwrap();
+    /// for (path, diff) in &diffs {
+    ///     println!("{}: +{} -{}", path, diff.lines_added, diff.lines_removed);
+    /// }
+    /// ```
+    pub fn get_many<'a>(
+        &self,
+        paths: impl IntoIterator<Item = &'a str>,
+    ) -> Result<HashMap<String, Arc<FileDiff>>> {
+        let mut found = HashMap::new();
+        let mut missing = Vec::new();
+        {
+            let mut cache = self.cache();
+            for path in paths {
+                match cache.get(path) {
+                    Some(Some(diff)) => {
+                        found.insert(path.to_string(), diff);
+                    }
+                    Some(None) => {}
+                    None => missing.push(path),
+                }
+            }
+        }
+        if missing.is_empty() {
+            return Ok(found);
+        }
+
+        let repo = self.ctx.open()?;
+        let mut loaded = collect_path_diffs(&repo, &missing)?;
+        log::debug!("Loaded diffs of {} file(s)", missing.len());
+        let mut cache = self.cache();
+        for path in missing {
+            let diff = loaded.remove(path).map(Arc::new);
+            cache.insert(path.to_string(), diff.clone(), self.budget);
+            if let Some(diff) = diff {
+                found.insert(path.to_string(), diff);
+            }
+        }
+        Ok(found)
+    }
+
+    /// Returns the patches of `paths`, each condensed to about
+    /// `max_tokens` tokens (see [`condense_diff`]), loading them in batches
+    /// of [`BATCH_FILES`].
+    ///
+    /// Used for AI grouping, whose prompt never holds more than
+    /// `max_tokens` of diffs, so the full patches need not be kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the diffs cannot be loaded.
+    pub fn condensed_patches(
+        &self,
+        paths: &[&str],
+        max_tokens: usize,
+    ) -> Result<HashMap<String, String>> {
+        let mut patches = HashMap::new();
+        for batch in paths.chunks(BATCH_FILES) {
+            for (path, diff) in self.get_many(batch.iter().copied())? {
+                patches.insert(path, condense_diff(&diff.patch, max_tokens));
+            }
+        }
+        Ok(patches)
+    }
+
+    /// Forgets the cached diffs of `paths`, e.g. after they were edited.
+    pub fn invalidate<'a>(&self, paths: impl IntoIterator<Item = &'a str>) {
+        let mut cache = self.cache();
+        for path in paths {
+            cache.remove(path);
+        }
+    }
+
+    /// Forgets all cached diffs.
+    pub fn clear(&self) {
+        *self.cache() = Cache::default();
+    }
+
+    /// Returns the number of bytes the cached diffs take.
+    pub fn cached_bytes(&self) -> usize {
+        self.cache().bytes
+    }
+
+    /// Returns whether the diff of `path` is cached.
+    pub fn is_cached(&self, path: &str) -> bool {
+        self.cache().entries.contains_key(path)
+    }
+}
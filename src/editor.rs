@@ -1,11 +1,21 @@
 //! Integrated text editor for commit message editing.
 //!
 //! This module provides an embedded text editor using the edtui widget,
-//! eliminating the need for external editor processes.
+//! eliminating the need for external editor processes. Users who prefer
+//! their own editor open the message in it with [`edit_externally`]; the
+//! editor comes from [`get_editor`] and is validated before it is started.
 
-use anyhow::Result;
-use edtui::{EditorEventHandler, EditorState, Lines};
-use ratatui::crossterm::event::{Event as CrosstermEvent, KeyCode, KeyModifiers};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command as Process;
+
+use anyhow::{bail, Context, Result};
+use edtui::{EditorEventHandler, EditorState, Index2, Lines};
+use ratatui::crossterm::event::{Event as CrosstermEvent, KeyModifiers};
+
+use crate::keymap::{Command, EDITOR};
+use crate::scope::ScopeVocabulary;
 
 /// Editor for commit messages with vim-style keybindings.
 ///
@@ -21,6 +31,10 @@ pub struct CommitMessageEditor {
     original_text: String,
     /// Whether the editor is currently active
     active: bool,
+    /// Scopes offered when completing the header's scope
+    vocabulary: ScopeVocabulary,
+    /// Typed prefix and index of the last completion, while cycling
+    completion: Option<(String, usize)>,
 }
 
 impl CommitMessageEditor {
@@ -34,7 +48,71 @@ impl CommitMessageEditor {
             event_handler,
             original_text: initial_text,
             active: false,
+            vocabulary: ScopeVocabulary::default(),
+            completion: None,
+        }
+    }
+
+    /// Sets the scopes offered by [`Self::complete_scope`].
+    pub fn set_scope_vocabulary(&mut self, vocabulary: ScopeVocabulary) {
+        self.vocabulary = vocabulary;
+    }
+
+    /// Completes the scope of the header from the scope vocabulary.
+    ///
+    /// Works while the cursor is on the header between the parentheses of
+    /// `type(scope):`, completing the text before the cursor; repeated
+    /// calls cycle through the matching scopes, most used first.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the cursor is elsewhere or no scope matches.
+    pub fn complete_scope(&mut self) -> bool {
+        let cycling = self.completion.take();
+        let cursor = self.state.cursor;
+        if cursor.row != 0 {
+            return false;
+        }
+        let text = self.text();
+        let header: Vec<char> = text.lines().next().unwrap_or_default().chars().collect();
+        let Some(open) = header.iter().position(|&c| c == '(') else {
+            return false;
+        };
+        let close = header[open..]
+            .iter()
+            .position(|&c| c == ')')
+            .map_or(header.len(), |offset| open + offset);
+        if cursor.col <= open || cursor.col > close {
+            return false;
         }
+
+        let (prefix, index) = match cycling {
+            Some((prefix, index)) => (prefix, index + 1),
+            None => (header[open + 1..cursor.col].iter().collect(), 0),
+        };
+        let matches = self.vocabulary.complete(&prefix);
+        if matches.is_empty() {
+            return false;
+        }
+        let index = index % matches.len();
+        let scope = &matches[index];
+
+        let mut completed: String = header[..=open].iter().collect();
+        completed.push_str(scope);
+        if close < header.len() {
+            completed.extend(&header[close..]);
+        } else {
+            completed.push(')');
+        }
+        let rest = text.split_once('\n').map(|(_, rest)| rest);
+        let text = match rest {
+            Some(rest) => format!("{}\n{}", completed, rest),
+            None => completed,
+        };
+        self.state.lines = Lines::from(text.as_str());
+        self.state.cursor = Index2::new(0, open + 1 + scope.chars().count());
+        self.completion = Some((prefix, index));
+        true
     }
 
     /// Creates an empty editor.
@@ -105,16 +183,22 @@ impl CommitMessageEditor {
     /// - Esc: Cancel without saving
     /// - Ctrl+C: Cancel without saving
     /// - Ctrl+S: Save and close
+    /// - Tab: Complete the scope of the header (see [`Self::complete_scope`])
     pub fn handle_event(&mut self, event: CrosstermEvent) -> Result<bool> {
         // Check for exit keys first
         if let CrosstermEvent::Key(key) = event {
-            match (key.code, key.modifiers) {
-                (KeyCode::Char('s'), KeyModifiers::CONTROL) => {
+            match EDITOR.command(&key) {
+                Some(Command::CompleteScope)
+                    if key.modifiers == KeyModifiers::NONE && self.complete_scope() =>
+                {
+                    return Ok(true);
+                }
+                Some(Command::Save) => {
                     // Save and close
                     self.save();
                     return Ok(false);
                 }
-                (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                Some(Command::Cancel) => {
                     // Cancel without saving
                     self.cancel();
                     return Ok(false);
@@ -123,8 +207,145 @@ impl CommitMessageEditor {
             }
         }
 
+        // Any other key ends cycling through completions
+        self.completion = None;
+
         // Forward event to edtui handler
         self.event_handler.on_event(event, &mut self.state);
         Ok(true)
     }
 }
+
+/// Characters that only make sense to a shell; editors are started without
+/// one, so a command containing them is rejected rather than misread.
+const SHELL_METACHARACTERS: &[char] = &[
+    ';', '|', '&', '$', '`', '<', '>', '(', ')', '{', '}', '*', '?', '!', '\n', '\r',
+];
+
+/// Returns the external editor command: `configured` (the `[ui] editor`
+/// setting, which only the user configuration and `--config` can set) if
+/// given, then `$VISUAL`, then `$EDITOR`.
+///
+/// # Errors
+///
+/// Returns an error if no editor is set or the command fails
+/// [`validate_editor_command`].
+pub fn get_editor(configured: Option<&str>) -> Result<String> {
+    let command = configured
+        .map(str::to_string)
+        .into_iter()
+        .chain(env::var("VISUAL").ok())
+        .chain(env::var("EDITOR").ok())
+        .find(|command| !command.trim().is_empty());
+    let Some(command) = command else {
+        bail!("No external editor set: configure `editor` under [ui], or set $VISUAL or $EDITOR");
+    };
+    let command = command.trim().to_string();
+    validate_editor_command(&command)?;
+    Ok(command)
+}
+
+/// Checks that an editor command is a program, optionally with arguments
+/// (e.g. `code --wait`), that can be found.
+///
+/// # Errors
+///
+/// Returns an error if the command contains shell metacharacters, or its
+/// program is neither an existing path nor found in `PATH`.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::editor::validate_editor_command;
+///
+/// assert!(validate_editor_command("vim; rm -rf ~").is_err());
+/// assert!(validate_editor_command("").is_err());
+/// ```
+pub fn validate_editor_command(command: &str) -> Result<()> {
+    if let Some(c) = command.chars().find(|c| SHELL_METACHARACTERS.contains(c)) {
+        bail!(
+            "Editor command '{}' contains '{}'; give a program and its arguments without shell syntax",
+            command,
+            c.escape_default()
+        );
+    }
+    let Some(program) = command.split_whitespace().next() else {
+        bail!("The editor command is empty");
+    };
+    if find_program(program).is_none() {
+        bail!("Editor '{}' not found", program);
+    }
+    Ok(())
+}
+
+/// Returns the path of `program`: itself if it names a path, otherwise the
+/// first match in `PATH`.
+fn find_program(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let extensions: &[&str] = if cfg!(windows) {
+        &["", "exe", "cmd", "bat"]
+    } else {
+        &[""]
+    };
+    env::split_paths(&env::var_os("PATH")?).find_map(|dir| {
+        extensions.iter().find_map(|ext| {
+            let candidate = dir.join(program).with_extension(ext);
+            candidate.is_file().then_some(candidate)
+        })
+    })
+}
+
+/// Hint appended to the message in the external editor; lines starting
+/// with `#` are removed again, as git does.
+const EXTERNAL_EDITOR_HINT: &str = "\n\
+# Edit the commit message. Lines starting with '#' are ignored, and an
+# empty message keeps the previous one.
+";
+
+/// Opens `text` in the external `editor` command and returns the edited
+/// text, without comment lines and surrounding blank lines.
+///
+/// The terminal must be handed over to the editor by the caller, e.g. by
+/// leaving the alternate screen. The file is named `COMMIT_EDITMSG`, so
+/// editors pick their git commit mode.
+///
+/// # Errors
+///
+/// Returns an error if external programs are disabled, the editor cannot
+/// be started or exits with a failure, or the file cannot be read back.
+pub fn edit_externally(editor: &str, text: &str) -> Result<String> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().context("The editor command is empty")?;
+    crate::subprocess::ensure_allowed(program)?;
+
+    let dir = tempfile::tempdir().context("Failed to create a temporary directory")?;
+    let path = dir.path().join("COMMIT_EDITMSG");
+    fs::write(
+        &path,
+        format!("{}\n{}", text.trim_end(), EXTERNAL_EDITOR_HINT),
+    )
+    .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    let status = Process::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Failed to start the editor '{}'", program))?;
+    if !status.success() {
+        bail!(
+            "The editor exited with {}; the message was not changed",
+            status
+        );
+    }
+
+    let edited =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let lines: Vec<&str> = edited
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    Ok(lines.join("\n").trim().to_string())
+}
@@ -0,0 +1,570 @@
+//! Event bus between the TUI and background git operations.
+//!
+//! The TUI does not commit, push, or tag on its own thread. It sends an
+//! [`Action`] to the [`EventBus`], whose worker thread performs the git work
+//! and reports back with [`Event`]s that the event loop drains on every tick.
+//! The interface keeps redrawing while hooks or network operations are slow.
+//!
+//! Actions run one at a time in the order they were sent, so commits never
+//! race each other. AI requests have their own workers (see
+//! [`crate::worker`]) and never delay a commit.
+//!
+//! Cancelling kills the running git process together with the hooks or
+//! helpers it started and restores the index of an interrupted commit (see
+//! [`crate::git::commit_group_cancellable`]). A batch of commits keeps the
+//! groups committed before the cancellation. Cherry-picking onto several
+//! branches stops before the next branch.
+//!
+//! Messages are rewritten by the configured post-processors (see
+//! [`crate::postprocess`]) right before their group is committed.
+//!
+//! Every git command is stopped after the limit configured in
+//! `[timeouts]`; [`EventBus::remaining`] tells the TUI how much time the
+//! running action has left.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::config::TimeoutConfig;
+use crate::diff_store::DiffStore;
+use crate::git::{
+    cherry_pick_onto, commit_group_with_message, create_tag_cancellable, get_file_diff_cancellable,
+    pull_rebase_cancellable, push_branch_cancellable, Cancelled, CherryPick,
+};
+use crate::postprocess::PostProcessors;
+use crate::types::ChangeGroup;
+
+/// Kind of a git operation, used for progress display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    /// Committing one or more groups
+    Commit,
+    /// Pushing a branch
+    Push,
+    /// Fetching the upstream and rebasing onto it
+    Pull,
+    /// Creating a tag
+    Tag,
+    /// Loading the diff of a file
+    Diff,
+    /// Cherry-picking commits onto other branches
+    CherryPick,
+}
+
+impl ActionKind {
+    /// Returns the progress label, e.g. `Committing`.
+    pub fn label(self) -> &'static str {
+        match self {
+            ActionKind::Commit => "Committing",
+            ActionKind::Push => "Pushing",
+            ActionKind::Pull => "Fetching and rebasing",
+            ActionKind::Tag => "Tagging",
+            ActionKind::Diff => "Loading diff",
+            ActionKind::CherryPick => "Cherry-picking",
+        }
+    }
+}
+
+/// Git operation requested by the TUI.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Commit groups in order, each with its index in the TUI
+    Commit(Vec<(usize, ChangeGroup)>),
+    /// Push `branch` to `remote`
+    Push {
+        /// Remote to push to
+        remote: String,
+        /// Branch to push
+        branch: String,
+        /// Remote pull requests are opened against, if known
+        pr_base: Option<String>,
+    },
+    /// Fetch the upstream and rebase the current branch onto it
+    Pull,
+    /// Create a tag at HEAD
+    Tag {
+        /// Tag name
+        name: String,
+        /// Tag message
+        notes: String,
+        /// Whether to sign the tag
+        sign: bool,
+    },
+    /// Load the staged diff of a file
+    Diff {
+        /// Path of the file
+        path: String,
+    },
+    /// Load the diffs of several files as one
+    CombinedDiff {
+        /// Paths of the files, in the order they are shown
+        paths: Vec<String>,
+    },
+    /// Cherry-pick commits onto other branches
+    CherryPick {
+        /// Commits to pick, oldest first
+        commits: Vec<git2::Oid>,
+        /// Local branches to pick onto
+        branches: Vec<String>,
+    },
+}
+
+impl Action {
+    /// Returns the kind of the action.
+    pub fn kind(&self) -> ActionKind {
+        match self {
+            Action::Commit(_) => ActionKind::Commit,
+            Action::Push { .. } => ActionKind::Push,
+            Action::Pull => ActionKind::Pull,
+            Action::Tag { .. } => ActionKind::Tag,
+            Action::Diff { .. } | Action::CombinedDiff { .. } => ActionKind::Diff,
+            Action::CherryPick { .. } => ActionKind::CherryPick,
+        }
+    }
+}
+
+/// A group committed by the worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommittedGroup {
+    /// Index of the group in the TUI
+    pub index: usize,
+    /// Header of the commit message
+    pub header: String,
+    /// Output of `git commit`
+    pub output: String,
+    /// The created commit, if it could be read back
+    pub commit: Option<git2::Oid>,
+}
+
+/// Result reported by the worker.
+#[derive(Debug)]
+pub enum Event {
+    /// A group of a commit batch was committed; more may follow
+    Committed(CommittedGroup),
+    /// A commit batch ended
+    CommitFinished {
+        /// Groups committed, in order
+        committed: Vec<CommittedGroup>,
+        /// Error that stopped the batch
+        error: Option<anyhow::Error>,
+        /// Whether the batch was cancelled before all groups were committed
+        cancelled: bool,
+    },
+    /// A push ended
+    Pushed {
+        /// Remote pushed to
+        remote: String,
+        /// Branch pushed
+        branch: String,
+        /// Remote pull requests are opened against, if known
+        pr_base: Option<String>,
+        /// Output of `git push`
+        result: Result<String>,
+    },
+    /// Fetching and rebasing ended
+    Pulled {
+        /// Output of `git pull`
+        result: Result<String>,
+    },
+    /// Creating a tag ended
+    Tagged {
+        /// Tag name
+        name: String,
+        /// Tag message
+        notes: String,
+        /// Output of `git tag`
+        result: Result<String>,
+    },
+    /// Loading a diff ended
+    Diff {
+        /// Path of the file
+        path: String,
+        /// The diff
+        result: Result<String>,
+    },
+    /// Loading the diffs of several files ended
+    CombinedDiff {
+        /// Paths of the files
+        paths: Vec<String>,
+        /// The diffs, one after the other
+        result: Result<String>,
+    },
+    /// Cherry-picking ended
+    CherryPicked {
+        /// Result per branch, in the order requested; branches after a
+        /// cancellation are missing
+        results: Vec<(String, Result<CherryPick>)>,
+    },
+}
+
+impl Event {
+    /// Returns whether this is the last event of its action.
+    pub fn is_final(&self) -> bool {
+        !matches!(self, Event::Committed(_))
+    }
+}
+
+/// Channels to the worker thread running git operations.
+#[derive(Debug)]
+pub struct EventBus {
+    actions: Sender<Action>,
+    events: Receiver<Event>,
+    cancelled: Arc<AtomicBool>,
+    running: Option<(ActionKind, Instant)>,
+    timeouts: TimeoutConfig,
+}
+
+impl EventBus {
+    /// Starts the worker thread for the repository at `repo_path`, stopping
+    /// git commands after the given `timeouts`.
+    ///
+    /// The thread ends when the bus is dropped, after finishing the running
+    /// action.
+    pub fn start(repo_path: PathBuf, timeouts: TimeoutConfig) -> Self {
+        Self::start_with_diff_store(repo_path, timeouts, None)
+    }
+
+    /// Starts the worker thread like [`Self::start`], loading diffs for the
+    /// diff viewer from `diff_store` if set.
+    pub fn start_with_diff_store(
+        repo_path: PathBuf,
+        timeouts: TimeoutConfig,
+        diff_store: Option<Arc<DiffStore>>,
+    ) -> Self {
+        Self::start_with_post_processors(repo_path, timeouts, diff_store, PostProcessors::default())
+    }
+
+    /// Starts the worker thread like [`Self::start_with_diff_store`],
+    /// rewriting commit messages with `post_processors`.
+    pub fn start_with_post_processors(
+        repo_path: PathBuf,
+        timeouts: TimeoutConfig,
+        diff_store: Option<Arc<DiffStore>>,
+        post_processors: PostProcessors,
+    ) -> Self {
+        let (actions, action_receiver) = mpsc::channel::<Action>();
+        let (event_sender, events) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let cancelled_clone = cancelled.clone();
+        thread::spawn(move || {
+            for action in action_receiver {
+                let event = perform(
+                    &repo_path,
+                    action,
+                    &timeouts,
+                    diff_store.as_deref(),
+                    &post_processors,
+                    &event_sender,
+                    &cancelled_clone,
+                );
+                // The receiver is gone if the TUI quit meanwhile
+                if event_sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            actions,
+            events,
+            cancelled,
+            running: None,
+            timeouts,
+        }
+    }
+
+    /// Sends an action to the worker.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another action is still running or the worker
+    /// has stopped.
+    pub fn dispatch(&mut self, action: Action) -> Result<()> {
+        if let Some((kind, _)) = self.running {
+            bail!("{} is still in progress", kind.label());
+        }
+        let kind = action.kind();
+        self.cancelled.store(false, Ordering::Relaxed);
+        self.actions
+            .send(action)
+            .map_err(|_| anyhow!("Background worker has stopped"))?;
+        self.running = Some((kind, Instant::now()));
+        Ok(())
+    }
+
+    /// Returns the next event without blocking.
+    ///
+    /// A worker that stopped without finishing the running action (e.g.
+    /// after a panic) is reported as a failure of that action.
+    pub fn try_event(&mut self) -> Option<Event> {
+        let event = match self.events.try_recv() {
+            Ok(event) => event,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => {
+                let (kind, _) = self.running?;
+                stopped_event(kind)
+            }
+        };
+        if event.is_final() {
+            self.running = None;
+        } else if let Some((_, started)) = self.running.as_mut() {
+            // Each group of a batch gets the full commit timeout
+            *started = Instant::now();
+        }
+        Some(event)
+    }
+
+    /// Returns the running action and how long it has been running.
+    pub fn running(&self) -> Option<(ActionKind, Duration)> {
+        self.running
+            .map(|(kind, started)| (kind, started.elapsed()))
+    }
+
+    /// Returns how long the running action may still take before it times
+    /// out.
+    ///
+    /// For a commit batch this is the limit of the group being committed,
+    /// i.e. staging plus the commit itself.
+    pub fn remaining(&self) -> Option<Duration> {
+        let (kind, started) = self.running?;
+        let limit = match kind {
+            ActionKind::Commit => self.timeouts.stage() + self.timeouts.commit(),
+            ActionKind::Push | ActionKind::Pull => self.timeouts.push(),
+            ActionKind::Tag => self.timeouts.tag(),
+            ActionKind::Diff => self.timeouts.diff(),
+            ActionKind::CherryPick => self.timeouts.commit(),
+        };
+        Some(limit.saturating_sub(started.elapsed()))
+    }
+
+    /// Returns whether an action is running.
+    pub fn is_busy(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Cancels the running action, killing its git process.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Performs `action` and returns its final event.
+fn perform(
+    repo_path: &Path,
+    action: Action,
+    timeouts: &TimeoutConfig,
+    diff_store: Option<&DiffStore>,
+    post_processors: &PostProcessors,
+    events: &Sender<Event>,
+    cancelled: &AtomicBool,
+) -> Event {
+    match action {
+        Action::Commit(groups) => {
+            let mut committed = Vec::new();
+            let total = groups.len();
+            for (index, group) in groups {
+                if cancelled.load(Ordering::Relaxed) {
+                    log::info!(
+                        "Commit batch cancelled after {} of {} group(s)",
+                        committed.len(),
+                        total
+                    );
+                    return Event::CommitFinished {
+                        committed,
+                        error: None,
+                        cancelled: true,
+                    };
+                }
+                match commit_one(repo_path, &group, post_processors, timeouts, cancelled) {
+                    Ok(output) => {
+                        let done = CommittedGroup {
+                            index,
+                            header: group.header(),
+                            output,
+                            commit: head_commit(repo_path),
+                        };
+                        let _ = events.send(Event::Committed(done.clone()));
+                        committed.push(done);
+                    }
+                    Err(error) if error.is::<Cancelled>() => {
+                        log::info!("Commit of group {} cancelled", index);
+                        return Event::CommitFinished {
+                            committed,
+                            error: None,
+                            cancelled: true,
+                        };
+                    }
+                    Err(error) => {
+                        return Event::CommitFinished {
+                            committed,
+                            error: Some(error),
+                            cancelled: false,
+                        }
+                    }
+                }
+            }
+            Event::CommitFinished {
+                committed,
+                error: None,
+                cancelled: false,
+            }
+        }
+        Action::Push {
+            remote,
+            branch,
+            pr_base,
+        } => {
+            let result = push_branch_cancellable(repo_path, &remote, &branch, timeouts, cancelled);
+            Event::Pushed {
+                remote,
+                branch,
+                pr_base,
+                result,
+            }
+        }
+        Action::Pull => Event::Pulled {
+            result: pull_rebase_cancellable(repo_path, timeouts, cancelled),
+        },
+        Action::Tag { name, notes, sign } => {
+            let result =
+                create_tag_cancellable(repo_path, &name, &notes, sign, timeouts, cancelled);
+            Event::Tagged {
+                name,
+                notes,
+                result,
+            }
+        }
+        Action::Diff { path } => {
+            let result = load_diff(repo_path, &path, timeouts, diff_store, cancelled);
+            Event::Diff { path, result }
+        }
+        Action::CombinedDiff { paths } => {
+            let mut combined = String::new();
+            for path in &paths {
+                if cancelled.load(Ordering::Relaxed) {
+                    return Event::CombinedDiff {
+                        paths,
+                        result: Err(Cancelled.into()),
+                    };
+                }
+                match load_diff(repo_path, path, timeouts, diff_store, cancelled) {
+                    Ok(diff) => {
+                        combined.push_str(&diff);
+                        if !diff.is_empty() && !diff.ends_with('\n') {
+                            combined.push('\n');
+                        }
+                    }
+                    Err(error) => {
+                        return Event::CombinedDiff {
+                            paths,
+                            result: Err(error),
+                        }
+                    }
+                }
+            }
+            Event::CombinedDiff {
+                paths,
+                result: Ok(combined),
+            }
+        }
+        Action::CherryPick { commits, branches } => {
+            let mut results = Vec::new();
+            for branch in branches {
+                if cancelled.load(Ordering::Relaxed) {
+                    log::info!("Cherry-picking cancelled before {}", branch);
+                    break;
+                }
+                let result = cherry_pick_onto(repo_path, &commits, &branch);
+                results.push((branch, result));
+            }
+            Event::CherryPicked { results }
+        }
+    }
+}
+
+/// Commits `group` with its message as rewritten by `post_processors`.
+///
+/// A post-processor failure that keeps the message is reported at the top
+/// of the commit output.
+fn commit_one(
+    repo_path: &Path,
+    group: &ChangeGroup,
+    post_processors: &PostProcessors,
+    timeouts: &TimeoutConfig,
+    cancelled: &AtomicBool,
+) -> Result<String> {
+    let processed = post_processors.apply(
+        &group.full_message(),
+        repo_path,
+        timeouts.commit(),
+        cancelled,
+    )?;
+    let output =
+        commit_group_with_message(repo_path, group, &processed.message, timeouts, cancelled)?;
+    Ok(match processed.warning {
+        Some(warning) => format!("⚠ {}\n{}", warning, output),
+        None => output,
+    })
+}
+
+/// Loads the diff of the file at `path`, from `diff_store` if set.
+fn load_diff(
+    repo_path: &Path,
+    path: &str,
+    timeouts: &TimeoutConfig,
+    diff_store: Option<&DiffStore>,
+    cancelled: &AtomicBool,
+) -> Result<String> {
+    if let Some(store) = diff_store {
+        return store
+            .get(path)
+            .map(|diff| diff.map(|d| d.patch.clone()).unwrap_or_default());
+    }
+    let repo = git2::Repository::discover(repo_path)?;
+    get_file_diff_cancellable(&repo, path, timeouts, cancelled)
+}
+
+/// Returns the commit HEAD points to.
+fn head_commit(repo_path: &Path) -> Option<git2::Oid> {
+    let repo = git2::Repository::discover(repo_path).ok()?;
+    let head = repo.head().ok()?;
+    head.target()
+}
+
+/// Returns the failure event for an action whose worker stopped.
+fn stopped_event(kind: ActionKind) -> Event {
+    let error = anyhow!("Background worker stopped unexpectedly");
+    match kind {
+        ActionKind::Commit => Event::CommitFinished {
+            committed: Vec::new(),
+            error: Some(error),
+            cancelled: false,
+        },
+        ActionKind::Push => Event::Pushed {
+            remote: String::new(),
+            branch: String::new(),
+            pr_base: None,
+            result: Err(error),
+        },
+        ActionKind::Pull => Event::Pulled { result: Err(error) },
+        ActionKind::Tag => Event::Tagged {
+            name: String::new(),
+            notes: String::new(),
+            result: Err(error),
+        },
+        ActionKind::Diff => Event::Diff {
+            path: String::new(),
+            result: Err(error),
+        },
+        ActionKind::CherryPick => Event::CherryPicked {
+            results: vec![(String::new(), Err(error))],
+        },
+    }
+}
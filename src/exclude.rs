@@ -0,0 +1,162 @@
+//! Files that are never sent to AI providers.
+//!
+//! `[ai] exclude` lists glob patterns such as `secrets/**` or `*.pem`.
+//! Matching files are grouped heuristically; neither their paths nor their
+//! diffs appear in grouping or message prompts. [`ExcludingProvider`] makes
+//! the patterns known to the prompt builders through
+//! [`AiProvider::excludes`].
+//!
+//! Patterns follow `.gitignore` conventions: a pattern without `/` matches
+//! a file or directory name at any depth, other patterns are relative to the
+//! repository root. `*` and `?` do not cross directories, `**` does, and a
+//! matched directory excludes everything below it.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::provider::{AiProvider, ProviderLayer};
+
+/// Compiled `[ai] exclude` patterns.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    patterns: Vec<Regex>,
+}
+
+impl PathFilter {
+    /// Compiles glob patterns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern cannot be compiled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::exclude::PathFilter;
+    ///
+    /// let filter = PathFilter::new(&["secrets/**", "*.pem", "vendor"]).unwrap();
+    /// assert!(filter.matches("secrets/prod/db.env"));
+    /// assert!(filter.matches("deploy/certs/server.pem"));
+    /// assert!(filter.matches("third_party/vendor/lib.go"));
+    /// assert!(!filter.matches("src/secrets.rs"));
+    /// ```
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                let pattern = pattern.as_ref();
+                Regex::new(&glob_to_regex(pattern))
+                    .with_context(|| format!("Invalid exclude pattern '{}'", pattern))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Returns whether no patterns are configured.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Returns whether `path` (relative to the repository root) is excluded.
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.trim_start_matches("./");
+        self.patterns.iter().any(|pattern| pattern.is_match(path))
+    }
+}
+
+/// Translates a glob pattern into an anchored regular expression.
+fn glob_to_regex(pattern: &str) -> String {
+    let pattern = pattern.trim().trim_start_matches("./");
+    // A trailing slash only says the pattern names a directory
+    let pattern = pattern.trim_end_matches('/');
+    let (anchored, pattern) = match pattern.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (pattern.contains('/'), pattern),
+    };
+
+    let mut regex = String::from(if anchored { "^" } else { "(?:^|/)" });
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    // Matching a directory excludes its contents
+    regex.push_str("(?:/|$)");
+    regex
+}
+
+/// Removes the sections of excluded files from a multi-file diff.
+///
+/// Sections start with a `diff --git a/<path> b/<path>` header; text before
+/// the first header is kept.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::exclude::strip_excluded_diffs;
+///
+/// let diff = "diff --git a/key.pem b/key.pem\n+secret\ndiff --git a/src/lib.rs b/src/lib.rs\n+pub mod x;\n";
+/// let kept = strip_excluded_diffs(diff, |path| path.ends_with(".pem"));
+/// assert_eq!(kept, "diff --git a/src/lib.rs b/src/lib.rs\n+pub mod x;\n");
+/// ```
+pub fn strip_excluded_diffs(diff: &str, is_excluded: impl Fn(&str) -> bool) -> String {
+    let mut kept = String::new();
+    let mut keep = true;
+    for line in diff.split_inclusive('\n') {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            keep = !diff_header_paths(header.trim_end())
+                .iter()
+                .any(|p| is_excluded(p));
+        }
+        if keep {
+            kept.push_str(line);
+        }
+    }
+    kept
+}
+
+/// Returns the old and new path of a `diff --git` header.
+fn diff_header_paths(header: &str) -> Vec<&str> {
+    match header.rfind(" b/") {
+        Some(idx) => vec![
+            header[..idx].trim_start_matches("a/"),
+            &header[idx + " b/".len()..],
+        ],
+        None => vec![header],
+    }
+}
+
+/// Provider that keeps the files matching `[ai] exclude` out of prompts.
+pub struct ExcludingProvider {
+    inner: Box<dyn AiProvider>,
+    filter: PathFilter,
+}
+
+impl ExcludingProvider {
+    /// Wraps `inner` so files matching `filter` are never sent to it.
+    pub fn new(inner: Box<dyn AiProvider>, filter: PathFilter) -> Self {
+        Self { inner, filter }
+    }
+}
+
+impl ProviderLayer for ExcludingProvider {
+    fn inner(&self) -> &dyn AiProvider {
+        self.inner.as_ref()
+    }
+
+    fn excludes(&self, path: &str) -> bool {
+        self.filter.matches(path) || self.inner.excludes(path)
+    }
+}
@@ -0,0 +1,223 @@
+//! Extension points for applications embedding the TUI.
+//!
+//! An embedding application can add its own actions (e.g. a company-internal
+//! "create Jira subtask") and a side panel without forking:
+//!
+//! ```no_run
+//! use commit_wizard::extension::ratatui::widgets::{Block, Borders, Paragraph};
+//! use commit_wizard::extension::Extensions;
+//! use commit_wizard::types::AppState;
+//!
+//! let mut extensions = Extensions::new();
+//! extensions
+//!     .add_action('J', "Create Jira subtask", |app, _repo_path| {
+//!         let header = app.selected_group().map(|g| g.header()).unwrap_or_default();
+//!         app.set_status(format!("✓ Created subtask for {}", header));
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! extensions.set_side_panel("Jira", 25, |f, area, app| {
+//!     let text = format!("{} group(s)", app.groups.len());
+//!     let block = Block::default().title(" Jira ").borders(Borders::ALL);
+//!     f.render_widget(Paragraph::new(text).block(block), area);
+//! });
+//!
+//! # let groups = Vec::new();
+//! let app = AppState::new(groups).with_extensions(extensions);
+//! ```
+//!
+//! Actions run on the UI thread with the whole [`AppState`]; long-running
+//! work should be moved to a thread by the action itself. Errors returned by
+//! an action are shown in the status popup. Panels are drawn with the
+//! re-exported [`ratatui`], so the embedding application uses the same
+//! version.
+
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use ratatui::layout::Rect;
+
+use crate::types::AppState;
+
+pub use ratatui;
+
+/// Keys used by the main interface, which actions cannot take.
+pub const RESERVED_KEYS: &[char] = &[
+    'a', 'A', 'B', 'c', 'C', 'd', 'e', 'E', 'f', 'F', 'H', 'i', 'j', 'k', 'L', 'm', 'n', 'N', 'o',
+    'O', 'P', 'q', 'r', 'R', 's', 'S', 't', 'U', 'W', 'x', 'Z', '?', '/', ' ',
+];
+
+/// Handler of an action.
+pub type ActionHandler = Box<dyn FnMut(&mut AppState, &Path) -> Result<()>>;
+
+/// Renders the side panel into the given area.
+pub type PanelRenderer = Box<dyn Fn(&mut ratatui::Frame, Rect, &AppState)>;
+
+/// An action bound to a key of the main interface.
+pub struct ExtensionAction {
+    /// Key that runs the action
+    pub key: char,
+    /// Description shown in the help popup
+    pub label: String,
+    handler: ActionHandler,
+}
+
+impl fmt::Debug for ExtensionAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtensionAction")
+            .field("key", &self.key)
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A panel drawn to the right of the built-in panels.
+pub struct SidePanel {
+    /// Name of the panel, for logs
+    pub title: String,
+    /// Share of the content width, in percent (10 to 60)
+    pub width: u16,
+    render: PanelRenderer,
+}
+
+impl fmt::Debug for SidePanel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SidePanel")
+            .field("title", &self.title)
+            .field("width", &self.width)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Actions and side panel registered by the embedding application.
+#[derive(Debug, Default)]
+pub struct Extensions {
+    actions: Vec<ExtensionAction>,
+    side_panel: Option<SidePanel>,
+}
+
+impl Extensions {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds `handler` to `key` in the main interface.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is one of [`RESERVED_KEYS`] or already
+    /// bound to another action.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::extension::Extensions;
+    ///
+    /// let mut extensions = Extensions::new();
+    /// assert!(extensions.add_action('J', "Create Jira subtask", |_, _| Ok(())).is_ok());
+    /// assert!(extensions.add_action('J', "Again", |_, _| Ok(())).is_err());
+    /// assert!(extensions.add_action('c', "Commit", |_, _| Ok(())).is_err());
+    /// ```
+    pub fn add_action(
+        &mut self,
+        key: char,
+        label: impl Into<String>,
+        handler: impl FnMut(&mut AppState, &Path) -> Result<()> + 'static,
+    ) -> Result<&mut Self> {
+        let label = label.into();
+        if RESERVED_KEYS.contains(&key) {
+            bail!(
+                "Key '{}' of action '{}' is used by commit-wizard",
+                key,
+                label
+            );
+        }
+        if let Some(existing) = self.actions.iter().find(|action| action.key == key) {
+            bail!(
+                "Key '{}' of action '{}' is already bound to '{}'",
+                key,
+                label,
+                existing.label
+            );
+        }
+        self.actions.push(ExtensionAction {
+            key,
+            label,
+            handler: Box::new(handler),
+        });
+        Ok(self)
+    }
+
+    /// Sets the side panel, replacing a previous one.
+    ///
+    /// `width` is the share of the content width in percent, clamped to 10
+    /// to 60.
+    pub fn set_side_panel(
+        &mut self,
+        title: impl Into<String>,
+        width: u16,
+        render: impl Fn(&mut ratatui::Frame, Rect, &AppState) + 'static,
+    ) -> &mut Self {
+        self.side_panel = Some(SidePanel {
+            title: title.into(),
+            width: width.clamp(10, 60),
+            render: Box::new(render),
+        });
+        self
+    }
+
+    /// Returns the registered actions in registration order.
+    pub fn actions(&self) -> &[ExtensionAction] {
+        &self.actions
+    }
+
+    /// Returns the side panel, if one is set.
+    pub fn side_panel(&self) -> Option<&SidePanel> {
+        self.side_panel.as_ref()
+    }
+
+    /// Returns whether no action or panel is registered.
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty() && self.side_panel.is_none()
+    }
+}
+
+impl SidePanel {
+    /// Draws the panel.
+    pub fn render(&self, f: &mut ratatui::Frame, area: Rect, app: &AppState) {
+        (self.render)(f, area, app);
+    }
+}
+
+/// Runs the action bound to `key`, if any.
+///
+/// The registry is moved out of `app` while the action runs, so the action
+/// sees an empty [`AppState::extensions`].
+///
+/// # Returns
+///
+/// `false` if no action is bound to `key`.
+///
+/// # Errors
+///
+/// Returns the error of the action, with its label as context.
+pub fn run_action(app: &mut AppState, repo_path: &Path, key: char) -> Result<bool> {
+    let mut extensions = std::mem::take(&mut app.extensions);
+    let result = match extensions
+        .actions
+        .iter_mut()
+        .find(|action| action.key == key)
+    {
+        Some(action) => {
+            log::info!("Running extension action '{}'", action.label);
+            (action.handler)(app, repo_path)
+                .with_context(|| format!("Action '{}' failed", action.label))
+                .map(|()| true)
+        }
+        None => Ok(false),
+    };
+    app.extensions = extensions;
+    result
+}
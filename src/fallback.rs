@@ -0,0 +1,248 @@
+//! Why the wizard fell back from AI to heuristics.
+//!
+//! When no provider is available or an AI request fails, the session keeps
+//! going with heuristic groups and messages. Each such fallback is recorded
+//! as an [`AiFallback`] with the precise cause (CLI missing, authentication,
+//! timeout, unparsable response with a snippet of it, ...). The TUI lists
+//! them in a details popup (`i`) and prints a [`session_report`] on exit,
+//! so the cause no longer has to be dug out of the log file.
+
+use std::fmt;
+
+use crate::budget::BudgetExceeded;
+use crate::git::TimedOut;
+use crate::network::UntrustedCertificate;
+use crate::retry::ApiError;
+
+/// Longest response snippet kept for unparsable responses.
+const SNIPPET_CHARS: usize = 120;
+
+/// Operation of fallbacks recorded when no provider is available at startup.
+const PROVIDER_SELECTION: &str = "provider selection";
+
+/// Context attached to errors parsing an AI response, carrying the start of
+/// the response.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use commit_wizard::fallback::{FallbackCause, UnparsableResponse};
+///
+/// let error = serde_json::from_str::<Vec<u8>>("Sure! Here are\nthe groups")
+///     .context("AI grouping response is not valid JSON")
+///     .with_context(|| UnparsableResponse::new("Sure! Here are\nthe groups"))
+///     .unwrap_err();
+/// assert_eq!(FallbackCause::of(&error), FallbackCause::Parse);
+/// assert!(error.to_string().contains("\"Sure! Here are the groups\""));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnparsableResponse {
+    /// Start of the response, whitespace collapsed
+    pub snippet: String,
+}
+
+impl UnparsableResponse {
+    /// Keeps the first [`SNIPPET_CHARS`] characters of `response`.
+    pub fn new(response: &str) -> Self {
+        let collapsed = response.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut snippet: String = collapsed.chars().take(SNIPPET_CHARS).collect();
+        if collapsed.chars().count() > SNIPPET_CHARS {
+            snippet.push('…');
+        }
+        Self { snippet }
+    }
+}
+
+impl fmt::Display for UnparsableResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unparsable AI response \"{}\"", self.snippet)
+    }
+}
+
+/// Cause of a fallback to heuristics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FallbackCause {
+    /// No provider is installed, configured, or reachable
+    Unavailable,
+    /// The provider rejected the credentials
+    Authentication,
+    /// The request took longer than its timeout
+    Timeout,
+    /// The provider's rate limit was hit, even after retrying
+    RateLimited,
+    /// The provider could not be reached over the network
+    Network,
+    /// The server certificate is not trusted, e.g. behind a proxy that
+    /// intercepts TLS
+    Certificate,
+    /// The session's `[ai]` budget is used up
+    Budget,
+    /// The response did not have the expected format
+    Parse,
+    /// The API answered with another error status
+    Api(u16),
+    /// Any other failure
+    Other,
+}
+
+impl FallbackCause {
+    /// Classifies the error of a failed AI request.
+    ///
+    /// Typed errors in the chain are checked first; errors of the Copilot
+    /// CLI are only available as text and are recognized by their wording.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::fallback::FallbackCause;
+    /// use commit_wizard::retry::ApiError;
+    ///
+    /// let error = anyhow::Error::new(ApiError::new(401, None, "Bad credentials"));
+    /// assert_eq!(FallbackCause::of(&error), FallbackCause::Authentication);
+    /// let error = anyhow::anyhow!("Error: No authentication information found.");
+    /// assert_eq!(FallbackCause::of(&error), FallbackCause::Authentication);
+    /// ```
+    pub fn of(error: &anyhow::Error) -> Self {
+        if error.downcast_ref::<UnparsableResponse>().is_some() {
+            return FallbackCause::Parse;
+        }
+        for cause in error.chain() {
+            if cause.is::<BudgetExceeded>() {
+                return FallbackCause::Budget;
+            }
+            if cause.is::<TimedOut>() {
+                return FallbackCause::Timeout;
+            }
+            if cause.is::<UntrustedCertificate>() {
+                return FallbackCause::Certificate;
+            }
+            if let Some(api) = cause.downcast_ref::<ApiError>() {
+                return match api.status {
+                    401 | 403 => FallbackCause::Authentication,
+                    408 | 504 => FallbackCause::Timeout,
+                    429 => FallbackCause::RateLimited,
+                    status => FallbackCause::Api(status),
+                };
+            }
+            if let Some(http) = cause.downcast_ref::<reqwest::Error>() {
+                if http.is_timeout() {
+                    return FallbackCause::Timeout;
+                }
+                if http.is_connect() || http.is_request() {
+                    return FallbackCause::Network;
+                }
+            }
+        }
+
+        let text = format!("{:#}", error).to_lowercase();
+        if text.contains("authenticat") || text.contains("/login") {
+            FallbackCause::Authentication
+        } else if text.contains("timed out") {
+            FallbackCause::Timeout
+        } else if text.contains("not available") || text.contains("not found") {
+            FallbackCause::Unavailable
+        } else {
+            FallbackCause::Other
+        }
+    }
+
+    /// Short description, e.g. `timed out`.
+    pub fn label(self) -> String {
+        match self {
+            FallbackCause::Unavailable => "provider unavailable".to_string(),
+            FallbackCause::Authentication => "authentication failed".to_string(),
+            FallbackCause::Timeout => "timed out".to_string(),
+            FallbackCause::RateLimited => "rate limited".to_string(),
+            FallbackCause::Network => "network error".to_string(),
+            FallbackCause::Certificate => "certificate not trusted".to_string(),
+            FallbackCause::Budget => "AI budget used up".to_string(),
+            FallbackCause::Parse => "unparsable response".to_string(),
+            FallbackCause::Api(status) => format!("API error {}", status),
+            FallbackCause::Other => "request failed".to_string(),
+        }
+    }
+}
+
+/// A fallback from AI to heuristics during the session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AiFallback {
+    /// What the AI was asked to do, e.g. `grouping`
+    pub operation: &'static str,
+    /// Classified cause
+    pub cause: FallbackCause,
+    /// Full error text
+    pub detail: String,
+    /// Local time of the fallback, `HH:MM:SS`
+    pub time: String,
+}
+
+impl AiFallback {
+    /// Records a failed AI request.
+    pub fn from_error(operation: &'static str, error: &anyhow::Error) -> Self {
+        Self::new(operation, FallbackCause::of(error), format!("{:#}", error))
+    }
+
+    /// Records that no provider was available at startup.
+    ///
+    /// # Arguments
+    ///
+    /// * `reasons` - Why each candidate provider is unavailable
+    pub fn unavailable(reasons: &[String]) -> Self {
+        Self::new(
+            PROVIDER_SELECTION,
+            FallbackCause::Unavailable,
+            reasons.join("; "),
+        )
+    }
+
+    fn new(operation: &'static str, cause: FallbackCause, detail: String) -> Self {
+        Self {
+            operation,
+            cause,
+            detail,
+            time: chrono::Local::now().format("%H:%M:%S").to_string(),
+        }
+    }
+
+    /// One-line summary, e.g. `AI grouping failed (timed out)`.
+    pub fn summary(&self) -> String {
+        if self.operation == PROVIDER_SELECTION {
+            "No AI provider available".to_string()
+        } else {
+            format!("AI {} failed ({})", self.operation, self.cause.label())
+        }
+    }
+}
+
+/// Lists the fallbacks of a session for printing after the TUI closes.
+///
+/// # Returns
+///
+/// The report, or an empty string without fallbacks.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::fallback::{session_report, AiFallback};
+///
+/// let fallback = AiFallback::from_error("grouping", &anyhow::anyhow!("Command timed out after 60s"));
+/// let report = session_report(&[fallback]);
+/// assert!(report.starts_with("AI fell back to heuristics 1 time(s):"));
+/// assert!(report.contains("AI grouping failed (timed out): Command timed out after 60s"));
+/// ```
+pub fn session_report(fallbacks: &[AiFallback]) -> String {
+    if fallbacks.is_empty() {
+        return String::new();
+    }
+    let mut report = format!("AI fell back to heuristics {} time(s):", fallbacks.len());
+    for fallback in fallbacks {
+        report.push_str(&format!(
+            "\n  {} {}: {}",
+            fallback.time,
+            fallback.summary(),
+            fallback.detail
+        ));
+    }
+    report
+}
@@ -0,0 +1,138 @@
+//! Fingerprints of the files a plan was made from.
+//!
+//! Groups and their messages describe the working tree as it was when the
+//! diffs were collected. If a file is edited afterwards, committing its
+//! group would record changes the message does not mention. A [`Snapshot`]
+//! keeps the content hash of every planned file; before committing, the
+//! files of a group are hashed again and a difference marks the group as
+//! stale until its diff and message are refreshed.
+//!
+//! Hashes are git blob ids of the working tree content, so they match what
+//! staging the file would record.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use git2::{ObjectType, Oid};
+
+/// Returns the blob id of a working tree file, or [`None`] if the file does
+/// not exist.
+///
+/// Directories, such as submodules, are not hashed and also give [`None`].
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn fingerprint_file(workdir: &Path, path: &str) -> Result<Option<Oid>> {
+    let full_path = workdir.join(path);
+    let metadata = match fs::symlink_metadata(&full_path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path)),
+    };
+    if metadata.is_dir() {
+        return Ok(None);
+    }
+    let content = if metadata.file_type().is_symlink() {
+        // git stores the link target, not the file it points to
+        fs::read_link(&full_path)
+            .with_context(|| format!("Failed to read link {}", path))?
+            .to_string_lossy()
+            .into_owned()
+            .into_bytes()
+    } else {
+        fs::read(&full_path).with_context(|| format!("Failed to read {}", path))?
+    };
+    let id = Oid::hash_object(ObjectType::Blob, &content)
+        .with_context(|| format!("Failed to hash {}", path))?;
+    Ok(Some(id))
+}
+
+/// Content hashes of the planned files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    files: HashMap<String, Option<Oid>>,
+}
+
+impl Snapshot {
+    /// Hashes `paths` in `workdir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read.
+    pub fn capture<'a>(workdir: &Path, paths: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut snapshot = Self::default();
+        snapshot.refresh(workdir, paths)?;
+        Ok(snapshot)
+    }
+
+    /// Returns whether no file has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns the recorded hash of `path`: [`None`] if the path was never
+    /// recorded, `Some(None)` if the file did not exist.
+    pub fn recorded(&self, path: &str) -> Option<Option<Oid>> {
+        self.files.get(path).copied()
+    }
+
+    /// Returns the recorded paths of `paths` whose content changed since
+    /// they were recorded.
+    ///
+    /// Paths that were never recorded are not reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::fingerprint::Snapshot;
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// std::fs::write(dir.path().join("a.txt"), "one").unwrap();
+    /// let snapshot = Snapshot::capture(dir.path(), ["a.txt"]).unwrap();
+    ///
+    /// std::fs::write(dir.path().join("a.txt"), "two").unwrap();
+    /// assert_eq!(snapshot.changed(dir.path(), ["a.txt"]).unwrap(), ["a.txt"]);
+    /// ```
+    pub fn changed<'a>(
+        &self,
+        workdir: &Path,
+        paths: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<String>> {
+        let mut changed = Vec::new();
+        for path in paths {
+            let Some(recorded) = self.files.get(path) else {
+                continue;
+            };
+            if fingerprint_file(workdir, path)? != *recorded {
+                changed.push(path.to_string());
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Records the current content of `paths`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read; files hashed before the
+    /// error keep their new hash.
+    pub fn refresh<'a>(
+        &mut self,
+        workdir: &Path,
+        paths: impl IntoIterator<Item = &'a str>,
+    ) -> Result<()> {
+        for path in paths {
+            let id = fingerprint_file(workdir, path)?;
+            self.files.insert(path.to_string(), id);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+//! Issue lookup on GitHub and GitLab.
+//!
+//! When the branch names an issue such as `#123` and an API token is
+//! available, the issue title and description are fetched from the forge
+//! hosting the repository. They are added to AI prompts so messages can
+//! reflect the intent of the change, and optionally end up in a
+//! `Closes #123: <title>` footer.
+//!
+//! Only numeric references are looked up; Jira-style keys such as `PROJ-42`
+//! belong to other trackers and are left alone. The forge, API URL, and
+//! project are derived from the remote URL unless `[forge]` sets them.
+
+use std::env;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::config::{ForgeConfig, NetworkSettings};
+use crate::network;
+use crate::retry::ApiError;
+
+/// Longest issue description added to prompts, in characters.
+const MAX_DESCRIPTION_CHARS: usize = 2000;
+
+/// Kind of forge hosting the repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    /// GitHub or GitHub Enterprise
+    GitHub,
+    /// GitLab, hosted or self-managed
+    GitLab,
+}
+
+impl ForgeKind {
+    /// Returns the environment variables holding an API token, in order of
+    /// preference.
+    pub fn token_vars(self) -> &'static [&'static str] {
+        match self {
+            ForgeKind::GitHub => &["GITHUB_TOKEN", "GH_TOKEN"],
+            ForgeKind::GitLab => &["GITLAB_TOKEN"],
+        }
+    }
+
+    /// Returns the REST API base URL for a host, e.g.
+    /// `https://api.github.com` for `github.com`.
+    pub fn api_url(self, host: &str) -> String {
+        match self {
+            ForgeKind::GitHub if host == "github.com" => "https://api.github.com".to_string(),
+            ForgeKind::GitHub => format!("https://{}/api/v3", host),
+            ForgeKind::GitLab => format!("https://{}/api/v4", host),
+        }
+    }
+}
+
+/// Repository location parsed from a remote URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteRepository {
+    /// Host name, e.g. `github.com`
+    pub host: String,
+    /// Project path, e.g. `owner/repo` or `group/subgroup/repo`
+    pub project: String,
+}
+
+impl RemoteRepository {
+    /// Guesses the forge from the host name.
+    ///
+    /// Returns [`None`] for hosts that name neither forge; `[forge] kind`
+    /// must be set for those.
+    pub fn kind(&self) -> Option<ForgeKind> {
+        if self.host.contains("github") {
+            Some(ForgeKind::GitHub)
+        } else if self.host.contains("gitlab") {
+            Some(ForgeKind::GitLab)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parses an HTTPS or SSH remote URL.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::forge::parse_remote_url;
+///
+/// let remote = parse_remote_url("git@github.com:owner/repo.git").unwrap();
+/// assert_eq!(remote.host, "github.com");
+/// assert_eq!(remote.project, "owner/repo");
+///
+/// let remote = parse_remote_url("https://gitlab.com/group/sub/repo").unwrap();
+/// assert_eq!(remote.project, "group/sub/repo");
+///
+/// assert!(parse_remote_url("/srv/git/repo.git").is_none());
+/// ```
+pub fn parse_remote_url(url: &str) -> Option<RemoteRepository> {
+    let url = url.trim();
+    let (host, path) = if let Some((_, rest)) = url.split_once("://") {
+        // https://host/path, ssh://git@host:22/path
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority.rsplit('@').next()?;
+        let host = host.split(':').next()?;
+        (host, path)
+    } else {
+        // scp-like: git@host:path
+        let (authority, path) = url.split_once(':')?;
+        if authority.contains('/') {
+            return None;
+        }
+        (authority.rsplit('@').next()?, path)
+    };
+
+    let project = path.trim_matches('/');
+    let project = project.strip_suffix(".git").unwrap_or(project);
+    if host.is_empty() || !project.contains('/') {
+        return None;
+    }
+    Some(RemoteRepository {
+        host: host.to_string(),
+        project: project.to_string(),
+    })
+}
+
+/// Returns the issue numbers of `#123` references in a ticket string such
+/// as `#12, LU-5`.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::forge::issue_numbers;
+///
+/// assert_eq!(issue_numbers("#12, LU-5, #7"), [12, 7]);
+/// assert!(issue_numbers("PROJ-42").is_empty());
+/// ```
+pub fn issue_numbers(ticket: &str) -> Vec<u64> {
+    ticket
+        .split(',')
+        .filter_map(|part| part.trim().strip_prefix('#')?.parse().ok())
+        .collect()
+}
+
+/// An issue fetched from a forge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// Issue number
+    pub number: u64,
+    /// Issue title
+    pub title: String,
+    /// Issue description, empty if there is none
+    pub description: String,
+    /// Web URL of the issue
+    pub url: String,
+}
+
+impl Issue {
+    /// Returns the reference to the issue, e.g. `#123`.
+    pub fn reference(&self) -> String {
+        format!("#{}", self.number)
+    }
+
+    /// Returns the footer closing the issue, e.g. `Closes #123: Login fails`.
+    pub fn footer(&self) -> String {
+        format!("Closes {}: {}", self.reference(), self.title.trim())
+    }
+
+    /// Formats the issue as a prompt section.
+    ///
+    /// Long descriptions are cut after [`MAX_DESCRIPTION_CHARS`] characters.
+    pub fn prompt_section(&self) -> String {
+        let mut section = format!("ISSUE {}: {}\n", self.reference(), self.title.trim());
+        let description = self.description.trim();
+        if !description.is_empty() {
+            let mut chars = description.chars();
+            let shortened: String = chars.by_ref().take(MAX_DESCRIPTION_CHARS).collect();
+            section.push_str(&shortened);
+            if chars.next().is_some() {
+                section.push_str("\n... (description shortened)");
+            }
+            section.push('\n');
+        }
+        section.push_str(
+            "\nThe changes work on this issue; use it to explain why they were made, without quoting it.\n",
+        );
+        section
+    }
+}
+
+/// Client of a forge's issue API.
+pub trait IssueTracker {
+    /// Returns the name of the forge, e.g. `GitHub`.
+    fn name(&self) -> &str;
+
+    /// Fetches an issue by number.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API cannot be reached, rejects the token, or
+    /// does not know the issue.
+    fn issue(&self, number: u64) -> Result<Issue>;
+}
+
+/// Issue response of the GitHub API
+#[derive(Debug, Deserialize)]
+struct GitHubIssue {
+    title: String,
+    body: Option<String>,
+    html_url: String,
+}
+
+/// Issue client for the GitHub REST API.
+#[derive(Debug, Clone)]
+pub struct GitHubClient {
+    api_url: String,
+    project: String,
+    token: String,
+    timeout: Duration,
+    network: NetworkSettings,
+}
+
+impl GitHubClient {
+    /// Creates a client for the `owner/repo` project at `api_url`.
+    pub fn new(api_url: &str, project: &str, token: &str, timeout: Duration) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            project: project.to_string(),
+            token: token.to_string(),
+            timeout,
+            network: NetworkSettings::default(),
+        }
+    }
+
+    /// Sends requests through the proxy and with the certificate
+    /// authorities of `network`.
+    pub fn with_network(mut self, network: NetworkSettings) -> Self {
+        self.network = network;
+        self
+    }
+}
+
+impl IssueTracker for GitHubClient {
+    fn name(&self) -> &str {
+        "GitHub"
+    }
+
+    fn issue(&self, number: u64) -> Result<Issue> {
+        let url = format!("{}/repos/{}/issues/{}", self.api_url, self.project, number);
+        let response = http_client(self.timeout, &self.network)?
+            .get(&url)
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .map_err(|e| network::explain_send_error(e, &self.network))
+            .context("Failed to send request to GitHub API")?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("GitHub API", response).into());
+        }
+        let issue: GitHubIssue = response
+            .json()
+            .context("Failed to parse GitHub API response")?;
+        Ok(Issue {
+            number,
+            title: issue.title,
+            description: issue.body.unwrap_or_default(),
+            url: issue.html_url,
+        })
+    }
+}
+
+/// Issue response of the GitLab API
+#[derive(Debug, Deserialize)]
+struct GitLabIssue {
+    title: String,
+    description: Option<String>,
+    web_url: String,
+}
+
+/// Issue client for the GitLab REST API.
+#[derive(Debug, Clone)]
+pub struct GitLabClient {
+    api_url: String,
+    project: String,
+    token: String,
+    timeout: Duration,
+    network: NetworkSettings,
+}
+
+impl GitLabClient {
+    /// Creates a client for the `group/repo` project at `api_url`.
+    pub fn new(api_url: &str, project: &str, token: &str, timeout: Duration) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            project: project.to_string(),
+            token: token.to_string(),
+            timeout,
+            network: NetworkSettings::default(),
+        }
+    }
+
+    /// Sends requests through the proxy and with the certificate
+    /// authorities of `network`.
+    pub fn with_network(mut self, network: NetworkSettings) -> Self {
+        self.network = network;
+        self
+    }
+}
+
+impl IssueTracker for GitLabClient {
+    fn name(&self) -> &str {
+        "GitLab"
+    }
+
+    fn issue(&self, number: u64) -> Result<Issue> {
+        // Project paths are passed URL-encoded in place of the numeric id
+        let url = format!(
+            "{}/projects/{}/issues/{}",
+            self.api_url,
+            self.project.replace('/', "%2F"),
+            number
+        );
+        let response = http_client(self.timeout, &self.network)?
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .map_err(|e| network::explain_send_error(e, &self.network))
+            .context("Failed to send request to GitLab API")?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response("GitLab API", response).into());
+        }
+        let issue: GitLabIssue = response
+            .json()
+            .context("Failed to parse GitLab API response")?;
+        Ok(Issue {
+            number,
+            title: issue.title,
+            description: issue.description.unwrap_or_default(),
+            url: issue.web_url,
+        })
+    }
+}
+
+/// Builds an HTTP client; GitHub rejects requests without a user agent.
+fn http_client(timeout: Duration, network: &NetworkSettings) -> Result<reqwest::blocking::Client> {
+    network::client_builder(network)?
+        .timeout(timeout)
+        .user_agent(concat!("commit-wizard/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+/// Creates the issue client for a repository.
+///
+/// `remote_url` is the URL of the remote pull requests target; `[forge]`
+/// settings take precedence over what it implies. Requests use the proxy
+/// and certificate authorities of `network`.
+///
+/// # Returns
+///
+/// The client, or [`None`] if the lookup is disabled, the forge cannot be
+/// determined, or no token is set.
+pub fn tracker_from_config(
+    config: &ForgeConfig,
+    remote_url: Option<&str>,
+    network: &NetworkSettings,
+) -> Option<Box<dyn IssueTracker>> {
+    if !config.enabled {
+        return None;
+    }
+    let remote = remote_url.and_then(parse_remote_url);
+    let kind = config
+        .kind
+        .or_else(|| remote.as_ref().and_then(RemoteRepository::kind))?;
+    let project = config
+        .project
+        .clone()
+        .or_else(|| remote.as_ref().map(|r| r.project.clone()))?;
+    let api_url = config
+        .api_url
+        .clone()
+        .or_else(|| remote.as_ref().map(|r| kind.api_url(&r.host)))?;
+
+    let token = match &config.token_env {
+        Some(var) => env_token(var),
+        None => kind.token_vars().iter().find_map(|var| env_token(var)),
+    };
+    let Some(token) = token else {
+        log::debug!("No {:?} token set; skipping issue lookup", kind);
+        return None;
+    };
+
+    let timeout = Duration::from_secs(config.timeout);
+    Some(match kind {
+        ForgeKind::GitHub => Box::new(
+            GitHubClient::new(&api_url, &project, &token, timeout).with_network(network.clone()),
+        ),
+        ForgeKind::GitLab => Box::new(
+            GitLabClient::new(&api_url, &project, &token, timeout).with_network(network.clone()),
+        ),
+    })
+}
+
+/// Reads a non-empty token from an environment variable.
+fn env_token(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|t| !t.trim().is_empty())
+}
@@ -0,0 +1,307 @@
+//! Generated files that should be marked in `.gitattributes`.
+//!
+//! Lock files, minified bundles and generated code are committed again and
+//! again, and every time their diffs bloat reviews and AI prompts. Marking
+//! them `linguist-generated -diff` in `.gitattributes` collapses them on
+//! GitHub and GitLab and makes git treat their diffs as binary.
+//!
+//! Before grouping, the changed files are checked for ones that look
+//! generated (by name, or by a marker such as `@generated` or
+//! `DO NOT EDIT` near the top), are large, were changed by several recent
+//! commits, and are not yet marked. The wizard offers to add entries for
+//! them; the change is committed as a `chore` group of its own.
+//!
+//! ```toml
+//! [generated]
+//! suggest = true     # default
+//! min_size_kb = 32   # default
+//! min_commits = 3    # default
+//! commits = 200      # default
+//! ```
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use git2::{AttrCheckFlags, AttrValue, Repository, Status};
+
+use crate::config::GeneratedConfig;
+use crate::history::mine;
+use crate::types::{ChangeGroup, ChangedFile, CommitType};
+
+/// Path of the attributes file the entries are added to.
+pub const GITATTRIBUTES: &str = ".gitattributes";
+
+/// Attributes added for each generated file.
+pub const ATTRIBUTES: &str = "linguist-generated -diff";
+
+/// File names of lock files and other generated manifests.
+const GENERATED_NAMES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "composer.lock",
+    "Gemfile.lock",
+    "go.sum",
+    "flake.lock",
+    "packages.lock.json",
+];
+
+/// Suffixes of generated code and build output.
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".min.js",
+    ".min.css",
+    ".js.map",
+    ".css.map",
+    ".pb.go",
+    "_pb2.py",
+    "_pb2_grpc.py",
+    ".g.dart",
+    ".freezed.dart",
+    ".generated.ts",
+    ".generated.cs",
+    ".designer.cs",
+];
+
+/// Markers generators put near the top of their output, lowercase.
+const MARKERS: &[&str] = &[
+    "@generated",
+    "do not edit",
+    "auto-generated",
+    "autogenerated",
+    "code generated by",
+];
+
+/// Number of leading lines searched for a marker.
+const MARKER_LINES: usize = 5;
+
+/// Number of leading bytes read to find a marker.
+const MARKER_BYTES: u64 = 1024;
+
+/// A generated file worth marking in `.gitattributes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedFile {
+    /// Path relative to the repository root
+    pub path: String,
+    /// Size in the working tree in bytes
+    pub size: u64,
+    /// Number of recent commits that changed the file
+    pub commits: usize,
+}
+
+/// Returns whether a file looks generated, by its name or by a marker in
+/// the first lines of `head`.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::generated::looks_generated;
+///
+/// assert!(looks_generated("web/package-lock.json", ""));
+/// assert!(looks_generated("api/client.rs", "// @generated by openapi\nuse serde;"));
+/// assert!(!looks_generated("src/lib.rs", "//! The library.\n"));
+/// ```
+pub fn looks_generated(path: &str, head: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if GENERATED_NAMES.contains(&name)
+        || GENERATED_SUFFIXES
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+    {
+        return true;
+    }
+    head.lines().take(MARKER_LINES).any(|line| {
+        let line = line.to_lowercase();
+        MARKERS.iter().any(|marker| line.contains(marker))
+    })
+}
+
+/// Finds the changed files that look generated, are large, were changed
+/// by several recent commits and are not marked in `.gitattributes` yet.
+///
+/// # Arguments
+///
+/// * `repo` - The repository
+/// * `files` - The changed files
+/// * `config` - Thresholds from `[generated]`
+///
+/// # Returns
+///
+/// The files to suggest, sorted by path; empty if suggestions are off.
+///
+/// # Errors
+///
+/// Returns an error if the repository has no working tree or the
+/// attributes or history cannot be read.
+pub fn unmarked_generated_files(
+    repo: &Repository,
+    files: &[ChangedFile],
+    config: &GeneratedConfig,
+) -> Result<Vec<GeneratedFile>> {
+    if !config.suggest {
+        return Ok(Vec::new());
+    }
+    let workdir = repo.workdir().context("Repository has no working tree")?;
+
+    let mut candidates = Vec::new();
+    for file in files {
+        if file.is_deleted() || file.is_submodule() {
+            continue;
+        }
+        let Ok(metadata) = fs::metadata(workdir.join(&file.path)) else {
+            continue;
+        };
+        if !metadata.is_file() || metadata.len() < config.min_size_kb * 1024 {
+            continue;
+        }
+        if !looks_generated(&file.path, &read_head(&workdir.join(&file.path))) {
+            continue;
+        }
+        if is_marked(repo, &file.path)? {
+            log::debug!("{} is already marked in .gitattributes", file.path);
+            continue;
+        }
+        candidates.push(GeneratedFile {
+            path: file.path.clone(),
+            size: metadata.len(),
+            commits: 0,
+        });
+    }
+    if candidates.is_empty() {
+        return Ok(candidates);
+    }
+
+    let paths: HashSet<String> = candidates.iter().map(|file| file.path.clone()).collect();
+    let stats = mine(repo, &paths, config.commits)?;
+    for file in &mut candidates {
+        file.commits = stats.commits(&file.path);
+    }
+    candidates.retain(|file| file.commits >= config.min_commits.max(1));
+    candidates.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(candidates)
+}
+
+/// Returns the `.gitattributes` lines marking `files`.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::generated::{gitattributes_entries, GeneratedFile};
+///
+/// let files = [GeneratedFile { path: "Cargo.lock".into(), size: 40_000, commits: 5 }];
+/// assert_eq!(gitattributes_entries(&files), "/Cargo.lock linguist-generated -diff\n");
+/// ```
+pub fn gitattributes_entries(files: &[GeneratedFile]) -> String {
+    files
+        .iter()
+        .map(|file| format!("{} {}\n", pattern(&file.path), ATTRIBUTES))
+        .collect()
+}
+
+/// Adds the entries marking `files` to the `.gitattributes` at the root of
+/// `workdir`, creating the file if needed.
+///
+/// # Returns
+///
+/// The changed `.gitattributes`, to be committed.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or written.
+pub fn add_to_gitattributes(workdir: &Path, files: &[GeneratedFile]) -> Result<ChangedFile> {
+    let path = workdir.join(GITATTRIBUTES);
+    let (mut content, status) = match fs::read_to_string(&path) {
+        Ok(content) => (content, Status::WT_MODIFIED),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (String::new(), Status::WT_NEW),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+        }
+    };
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&gitattributes_entries(files));
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(ChangedFile::new(GITATTRIBUTES.to_string(), status))
+}
+
+/// Builds the `chore` group committing the `.gitattributes` entries.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::generated::{build_gitattributes_group, GeneratedFile};
+/// use commit_wizard::types::{ChangedFile, CommitType};
+/// use git2::Status;
+///
+/// let attributes = ChangedFile::new(".gitattributes".into(), Status::WT_NEW);
+/// let files = [GeneratedFile { path: "Cargo.lock".into(), size: 40_000, commits: 5 }];
+/// let group = build_gitattributes_group(attributes, &files, None);
+/// assert_eq!(group.commit_type, CommitType::Chore);
+/// assert_eq!(group.description, "mark generated files in .gitattributes");
+/// assert_eq!(group.body_lines, ["mark Cargo.lock as generated"]);
+/// ```
+pub fn build_gitattributes_group(
+    attributes: ChangedFile,
+    files: &[GeneratedFile],
+    ticket: Option<String>,
+) -> ChangeGroup {
+    ChangeGroup::new(
+        CommitType::Chore,
+        None,
+        vec![attributes],
+        ticket,
+        format!("mark generated files in {}", GITATTRIBUTES),
+        files
+            .iter()
+            .map(|file| format!("mark {} as generated", file.path))
+            .collect(),
+    )
+}
+
+/// Returns whether the attributes already say how to treat `path`: any
+/// `linguist-generated` value, or `-diff`.
+fn is_marked(repo: &Repository, path: &str) -> Result<bool> {
+    let attr = |name: &str| {
+        repo.get_attr(Path::new(path), name, AttrCheckFlags::default())
+            .map(AttrValue::from_string)
+            .with_context(|| format!("Failed to read the attributes of {}", path))
+    };
+    Ok(
+        !matches!(attr("linguist-generated")?, AttrValue::Unspecified)
+            || matches!(attr("diff")?, AttrValue::False),
+    )
+}
+
+/// Reads the first bytes of a file, lossily decoded.
+fn read_head(path: &Path) -> String {
+    let mut head = Vec::new();
+    if let Ok(file) = fs::File::open(path) {
+        let _ = file.take(MARKER_BYTES).read_to_end(&mut head);
+    }
+    String::from_utf8_lossy(&head).into_owned()
+}
+
+/// Returns the pattern matching exactly `path`: anchored at the root, with
+/// glob characters escaped, and quoted if it contains whitespace.
+fn pattern(path: &str) -> String {
+    let mut escaped = String::from("/");
+    for c in path.chars() {
+        if matches!(c, '*' | '?' | '[' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    if escaped.contains(char::is_whitespace) || escaped.contains('"') {
+        format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        escaped
+    }
+}
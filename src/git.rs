@@ -4,19 +4,216 @@
 //! including collecting staged files, extracting branch information, and
 //! executing commits.
 
-use std::io::Write;
-use std::path::Path;
-use std::process::Command;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::{bail, Context, Result};
-use git2::{Repository, Status, StatusOptions};
+use git2::{DiffFormat, DiffOptions, Oid, Repository, ResetType, Status, StatusOptions};
 use regex::Regex;
 use tempfile::NamedTempFile;
 
-use crate::types::{ChangeGroup, ChangedFile};
+use crate::config::TimeoutConfig;
+use crate::types::{ChangeGroup, ChangedFile, FileDiff, FileKind};
 use log::{debug, error};
 
+/// How often a running git command is checked for completion or
+/// cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Error returned for git operations cancelled by the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cancelled by the user")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Error returned for git commands stopped after their timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedOut(pub Duration);
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Command execution timed out after {}s (raise the limit in [timeouts] or with --timeout)",
+            self.0.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// A resolved repository location.
+///
+/// Produced by [`discover_repo`] so that the CLI, the TUI, and the commit
+/// path all agree on which repository and working tree they operate on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoContext {
+    /// Root of the working tree
+    pub workdir: PathBuf,
+    /// Git directory of this working tree (for linked worktrees this is
+    /// `.git/worktrees/<name>` in the main repository)
+    pub git_dir: PathBuf,
+    /// Git directory shared by all worktrees
+    pub common_dir: PathBuf,
+    /// Whether the working tree is a linked worktree
+    pub is_worktree: bool,
+}
+
+impl RepoContext {
+    /// Opens the repository described by this context.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository cannot be opened.
+    pub fn open(&self) -> Result<Repository> {
+        let repo = Repository::open(&self.git_dir)
+            .with_context(|| format!("Failed to open repository at {}", self.git_dir.display()))?;
+        if repo.workdir() != Some(self.workdir.as_path()) {
+            repo.set_workdir(&self.workdir, false)
+                .context("Failed to set working directory")?;
+        }
+        Ok(repo)
+    }
+}
+
+/// Discovers the repository containing `path`.
+///
+/// Handles repositories found via parent directories, linked worktrees, and
+/// the `GIT_DIR`/`GIT_WORK_TREE` environment variables (which take
+/// precedence over `path` when `GIT_DIR` is set).
+///
+/// # Errors
+///
+/// Returns an error if no repository is found or the repository is bare.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use commit_wizard::git::discover_repo;
+///
+/// let ctx = discover_repo(Path::new(".")).unwrap();
+/// let repo = ctx.open().unwrap();
+/// println!("Working tree: {}", ctx.workdir.display());
+/// ```
+pub fn discover_repo(path: &Path) -> Result<RepoContext> {
+    let repo = if std::env::var_os("GIT_DIR").is_some() {
+        Repository::open_from_env().context("Failed to open the repository given by GIT_DIR")?
+    } else {
+        Repository::discover(path).with_context(|| {
+            format!(
+                "Not a git repository (or any parent directory): {}",
+                path.display()
+            )
+        })?
+    };
+
+    let Some(workdir) = repo.workdir() else {
+        bail!(
+            "{} is a bare repository without a working tree.\n\
+             Hint: create a worktree with `git worktree add <path>` and run commit-wizard there",
+            repo.path().display()
+        );
+    };
+
+    Ok(RepoContext {
+        workdir: normalize_dir(workdir),
+        git_dir: normalize_dir(repo.path()),
+        common_dir: normalize_dir(repo.commondir()),
+        is_worktree: repo.is_worktree(),
+    })
+}
+
+/// A working tree of a repository with its pending changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorktreeInfo {
+    /// Worktree name, or [`None`] for the main working tree
+    pub name: Option<String>,
+    /// Root of the working tree
+    pub path: PathBuf,
+    /// Checked-out branch, or [`None`] if HEAD is detached or unborn
+    pub branch: Option<String>,
+    /// Number of uncommitted changes, including untracked files
+    pub changes: usize,
+    /// Whether this is the working tree described by the context
+    pub is_current: bool,
+}
+
+impl WorktreeInfo {
+    /// Returns a short label such as `main` or `feature-x (worktree)`.
+    pub fn label(&self) -> String {
+        let branch = self.branch.as_deref().unwrap_or("detached");
+        match &self.name {
+            Some(_) => format!("{} (worktree)", branch),
+            None => branch.to_string(),
+        }
+    }
+}
+
+/// Lists the main working tree and all linked worktrees of a repository.
+///
+/// Worktrees whose directory no longer exists (prunable worktrees) and the
+/// main working tree of a bare repository are left out.
+///
+/// # Errors
+///
+/// Returns an error if the repository or one of its worktrees cannot be
+/// opened or its status cannot be read.
+pub fn list_worktrees(ctx: &RepoContext) -> Result<Vec<WorktreeInfo>> {
+    let main = Repository::open(&ctx.common_dir)
+        .with_context(|| format!("Failed to open repository at {}", ctx.common_dir.display()))?;
+
+    let mut repos = Vec::new();
+    if let Some(workdir) = main.workdir() {
+        repos.push((None, normalize_dir(workdir)));
+    }
+    let names = main.worktrees().context("Failed to list worktrees")?;
+    for name in names.iter().flatten() {
+        let worktree = main
+            .find_worktree(name)
+            .with_context(|| format!("Failed to open worktree '{}'", name))?;
+        if worktree.validate().is_err() {
+            debug!("Skipping prunable worktree '{}'", name);
+            continue;
+        }
+        repos.push((Some(name.to_string()), normalize_dir(worktree.path())));
+    }
+
+    repos
+        .into_iter()
+        .map(|(name, path)| {
+            let repo = Repository::open(&path)
+                .with_context(|| format!("Failed to open worktree at {}", path.display()))?;
+            let changes = collect_changed_files(&repo, true)?.len();
+            Ok(WorktreeInfo {
+                branch: get_current_branch(&repo).ok(),
+                is_current: path == ctx.workdir,
+                name,
+                path,
+                changes,
+            })
+        })
+        .collect()
+}
+
+/// Strips the trailing separator libgit2 adds to directory paths.
+fn normalize_dir(path: &Path) -> PathBuf {
+    path.components().collect()
+}
+
 /// Collects all changed files from the git repository (staged and unstaged).
 ///
 /// This function collects:
@@ -62,11 +259,26 @@ pub fn collect_changed_files(
         .statuses(Some(&mut opts))
         .context("Failed to get git status")?;
 
+    let submodules = submodule_pointer_changes(repo);
     let mut result = Vec::new();
 
     for entry in statuses.iter() {
         let status = entry.status();
 
+        // Submodules only count if their recorded commit changed; changes
+        // inside a submodule belong to the submodule's own history
+        let mut kind = FileKind::Regular;
+        if let Some(&pointer_changed) = entry.path().and_then(|p| submodules.get(p)) {
+            if !pointer_changed {
+                debug!(
+                    "Skipping submodule with local changes only: {}",
+                    entry.path().unwrap_or_default()
+                );
+                continue;
+            }
+            kind = FileKind::Submodule;
+        }
+
         // Process both staged and unstaged changes, including untracked if requested
         let relevant_flags = if include_untracked {
             Status::INDEX_NEW
@@ -116,12 +328,31 @@ pub fn collect_changed_files(
             })
             .or_else(|| entry.path().map(Path::new));
 
+        // For renames, remember where the file came from
+        let old_path = entry
+            .head_to_index()
+            .filter(|_| status.is_index_renamed())
+            .and_then(|diff| diff.old_file().path())
+            .or_else(|| {
+                entry
+                    .index_to_workdir()
+                    .filter(|_| status.is_wt_renamed())
+                    .and_then(|diff| diff.old_file().path())
+            })
+            .map(|p| p.to_string_lossy().to_string())
+            .filter(|p| is_valid_path(p));
+
         if let Some(path) = path {
             let path_str = path.to_string_lossy().to_string();
 
             // Validate path (security: prevent directory traversal)
             if is_valid_path(&path_str) {
-                result.push(ChangedFile::new(path_str, status));
+                let old_path = old_path.filter(|old| *old != path_str);
+                result.push(
+                    ChangedFile::new(path_str, status)
+                        .with_old_path(old_path)
+                        .with_kind(kind),
+                );
             }
         }
     }
@@ -129,6 +360,33 @@ pub fn collect_changed_files(
     Ok(result)
 }
 
+/// Maps each submodule path to whether its recorded commit changed.
+///
+/// A pointer change is a difference between the commit recorded in HEAD,
+/// the index, and the commit checked out in the submodule. Failures to read
+/// submodules are logged and treated as "no submodules".
+fn submodule_pointer_changes(repo: &Repository) -> HashMap<String, bool> {
+    let submodules = match repo.submodules() {
+        Ok(submodules) => submodules,
+        Err(e) => {
+            debug!("Failed to list submodules: {}", e);
+            return HashMap::new();
+        }
+    };
+
+    submodules
+        .iter()
+        .filter_map(|sm| {
+            let path = sm.path().to_str()?.to_string();
+            let head = sm.head_id();
+            let index = sm.index_id();
+            let workdir = sm.workdir_id();
+            let changed = head != index || (workdir.is_some() && workdir != index);
+            Some((path, changed))
+        })
+        .collect()
+}
+
 /// Collects only untracked files that are not ignored by gitignore.
 ///
 /// # Arguments
@@ -219,14 +477,39 @@ fn is_valid_path(path: &str) -> bool {
 ///
 /// Returns an error if the diff operation fails.
 pub fn get_file_diff(repo: &Repository, file_path: &str) -> Result<String> {
+    get_file_diff_cancellable(
+        repo,
+        file_path,
+        &TimeoutConfig::default(),
+        &AtomicBool::new(false),
+    )
+}
+
+/// Gets the git diff for a specific file within the configured timeout,
+/// stopping when `cancel` is set.
+///
+/// # Errors
+///
+/// Returns [`Cancelled`] if `cancel` was set before the diff finished, and
+/// an error if the diff operation fails.
+pub fn get_file_diff_cancellable(
+    repo: &Repository,
+    file_path: &str,
+    timeouts: &TimeoutConfig,
+    cancel: &AtomicBool,
+) -> Result<String> {
+    if crate::subprocess::is_disabled() {
+        return staged_diff_in_process(repo, file_path);
+    }
+
     let workdir = repo
         .workdir()
         .context("Repository has no working directory")?;
 
-    let output = Command::new("git")
-        .args(["diff", "--cached", "--", file_path])
-        .current_dir(workdir)
-        .output()
+    let mut cmd = Command::new("git");
+    cmd.args(["diff", "--cached", "--", file_path])
+        .current_dir(workdir);
+    let output = execute_with_timeout(&mut cmd, timeouts.diff(), cancel)
         .context("Failed to execute git diff")?;
 
     if !output.status.success() {
@@ -239,6 +522,224 @@ pub fn get_file_diff(repo: &Repository, file_path: &str) -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// Gets the staged diff of a file with libgit2, like `git diff --cached`.
+fn staged_diff_in_process(repo: &Repository, file_path: &str) -> Result<String> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let mut opts = DiffOptions::new();
+    opts.pathspec(file_path).disable_pathspec_match(true);
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))
+        .context("Failed to compute diff")?;
+
+    let mut patch = String::new();
+    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            patch.push(line.origin());
+        }
+        patch.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })
+    .context("Failed to format diff")?;
+    Ok(patch)
+}
+
+/// Collects diffs for many files in a single libgit2 pass.
+///
+/// Compares `HEAD` against the working directory (including the index), so
+/// both staged and unstaged changes are reported. Untracked files are included
+/// with their full content as added lines.
+///
+/// # Arguments
+///
+/// * `repo` - A reference to the git repository
+/// * `files` - The changed files to collect diffs for
+///
+/// # Returns
+///
+/// A map from file path to its [`FileDiff`]. Files without textual changes
+/// are omitted.
+///
+/// # Errors
+///
+/// Returns an error if the diff cannot be computed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use git2::Repository;
+/// use commit_wizard::git::{collect_changed_files, collect_diffs};
+///
+/// let repo = Repository::open(".").unwrap();
+/// let files = collect_changed_files(&repo, false).unwrap();
+/// let diffs = collect_diffs(&repo, &files).unwrap();
+/// for (path, diff) in &diffs {
+///     println!("{}: +{} -{}", path, diff.lines_added, diff.lines_removed);
+/// }
+/// ```
+pub fn collect_diffs(
+    repo: &Repository,
+    files: &[ChangedFile],
+) -> Result<HashMap<String, FileDiff>> {
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    collect_path_diffs(repo, &paths)
+}
+
+/// Collects diffs for the files at `paths` in a single libgit2 pass, like
+/// [`collect_diffs`].
+///
+/// # Errors
+///
+/// Returns an error if the diff cannot be computed.
+pub fn collect_path_diffs(repo: &Repository, paths: &[&str]) -> Result<HashMap<String, FileDiff>> {
+    let mut result: HashMap<String, FileDiff> = HashMap::new();
+
+    if paths.is_empty() {
+        return Ok(result);
+    }
+
+    // An unborn HEAD (no commits yet) diffs against the empty tree
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+    let mut opts = DiffOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true)
+        .disable_pathspec_match(true);
+    let mut has_pathspec = false;
+    for path in paths {
+        if is_valid_path(path) {
+            opts.pathspec(path);
+            has_pathspec = true;
+        }
+    }
+
+    // Without a pathspec libgit2 would diff the whole tree
+    if !has_pathspec {
+        return Ok(result);
+    }
+
+    let diff = repo
+        .diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut opts))
+        .context("Failed to compute diff")?;
+
+    diff.print(DiffFormat::Patch, |delta, _hunk, line| {
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|p| p.to_string_lossy().to_string());
+
+        let Some(path) = path else {
+            return true;
+        };
+
+        let entry = result.entry(path).or_default();
+        if delta.flags().is_binary() {
+            entry.is_binary = true;
+        }
+
+        let content = String::from_utf8_lossy(line.content());
+        match line.origin() {
+            '+' => {
+                entry.lines_added += 1;
+                entry.patch.push('+');
+                entry.patch.push_str(&content);
+            }
+            '-' => {
+                entry.lines_removed += 1;
+                entry.patch.push('-');
+                entry.patch.push_str(&content);
+            }
+            ' ' => {
+                entry.patch.push(' ');
+                entry.patch.push_str(&content);
+            }
+            _ => entry.patch.push_str(&content),
+        }
+
+        true
+    })
+    .context("Failed to format diff")?;
+
+    debug!("Collected diffs for {} file(s) in one pass", result.len());
+
+    Ok(result)
+}
+
+/// Reads the content of a file as last committed.
+///
+/// Deleted files no longer exist in the working tree; this is how their
+/// former content is found.
+///
+/// # Returns
+///
+/// The content, or [`None`] if HEAD is unborn, the file is not in the HEAD
+/// tree, or its content is binary.
+///
+/// # Errors
+///
+/// Returns an error if the HEAD tree or the blob cannot be read.
+pub fn head_content(repo: &Repository, path: &str) -> Result<Option<String>> {
+    let Some(tree) = repo.head().ok().and_then(|head| head.peel_to_tree().ok()) else {
+        return Ok(None);
+    };
+    let Ok(entry) = tree.get_path(Path::new(path)) else {
+        return Ok(None);
+    };
+    let object = entry
+        .to_object(repo)
+        .with_context(|| format!("Failed to read {} from HEAD", path))?;
+    let Ok(blob) = object.into_blob() else {
+        // Directories and submodules have no content
+        return Ok(None);
+    };
+    if blob.is_binary() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+}
+
+/// Longest part of a deleted file sent as context, in lines.
+const MAX_DELETED_LINES: usize = 200;
+
+/// Builds a patch removing a file's committed content, as context for
+/// describing its deletion.
+///
+/// `git diff --cached` shows nothing for a deletion that is not staged, so
+/// the AI would only see the file name. The patch lists the first
+/// [`MAX_DELETED_LINES`] lines of the content in HEAD as removed lines and
+/// notes how many more were left out.
+///
+/// # Returns
+///
+/// The patch, or [`None`] if the file has no text content in HEAD.
+///
+/// # Errors
+///
+/// Returns an error if the HEAD tree or the blob cannot be read.
+pub fn deleted_file_patch(repo: &Repository, path: &str) -> Result<Option<String>> {
+    let Some(content) = head_content(repo, path)? else {
+        return Ok(None);
+    };
+    let total = content.lines().count();
+    let mut patch = format!(
+        "diff --git a/{0} b/{0}\ndeleted file\n--- a/{0}\n+++ /dev/null\n@@ -1,{1} +0,0 @@\n",
+        path, total
+    );
+    for line in content.lines().take(MAX_DELETED_LINES) {
+        patch.push('-');
+        patch.push_str(line);
+        patch.push('\n');
+    }
+    if total > MAX_DELETED_LINES {
+        patch.push_str(&format!(
+            "... ({} more lines of the deleted file omitted)\n",
+            total - MAX_DELETED_LINES
+        ));
+    }
+    Ok(Some(patch))
+}
+
 /// Gets the current branch name from the repository.
 ///
 /// # Arguments
@@ -262,6 +763,103 @@ pub fn get_current_branch(repo: &Repository) -> Result<String> {
     Ok(shorthand.to_string())
 }
 
+/// Longest description part of a suggested branch name, in characters.
+const BRANCH_SLUG_LENGTH: usize = 40;
+
+/// Suggests a branch name for changes, e.g. `feat/PROJ-123-add-auth`.
+///
+/// The first ticket and the description are reduced to characters that are
+/// safe in branch names; a leading `#` of GitHub issues is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::git::suggest_branch_name;
+/// use commit_wizard::types::CommitType;
+///
+/// assert_eq!(
+///     suggest_branch_name(CommitType::Feat, Some("PROJ-123"), "Add auth"),
+///     "feat/PROJ-123-add-auth"
+/// );
+/// assert_eq!(
+///     suggest_branch_name(CommitType::Fix, Some("#42, LU-7"), "handle `None` values"),
+///     "fix/42-handle-none-values"
+/// );
+/// assert_eq!(suggest_branch_name(CommitType::Docs, None, ""), "docs/update");
+/// ```
+pub fn suggest_branch_name(
+    commit_type: crate::types::CommitType,
+    ticket: Option<&str>,
+    description: &str,
+) -> String {
+    let slug = |text: &str, lowercase: bool| {
+        let mut slug = String::new();
+        for c in text.chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(if lowercase { c.to_ascii_lowercase() } else { c });
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        slug.trim_end_matches('-').to_string()
+    };
+
+    let ticket = ticket
+        .and_then(|tickets| tickets.split(',').next())
+        .map(|ticket| slug(ticket, false))
+        .filter(|ticket| !ticket.is_empty());
+    let mut description = slug(description, true);
+    if description.len() > BRANCH_SLUG_LENGTH {
+        // Cut at a word boundary where possible
+        let cut = description[..BRANCH_SLUG_LENGTH]
+            .rfind('-')
+            .unwrap_or(BRANCH_SLUG_LENGTH);
+        description.truncate(cut);
+    }
+
+    let name = match (ticket, description.is_empty()) {
+        (Some(ticket), true) => ticket,
+        (Some(ticket), false) => format!("{}-{}", ticket, description),
+        (None, true) => "update".to_string(),
+        (None, false) => description,
+    };
+    format!("{}/{}", commit_type.as_str(), name)
+}
+
+/// Creates a branch at HEAD and switches to it.
+///
+/// The index and working tree are left as they are, so uncommitted changes
+/// move to the new branch. In a repository without commits, HEAD is pointed
+/// at the branch, which is created by the first commit.
+///
+/// # Errors
+///
+/// Returns an error if the name is invalid, the branch already exists, or
+/// HEAD cannot be updated.
+pub fn create_branch(repo: &Repository, name: &str) -> Result<()> {
+    if name.starts_with('-') || !git2::Branch::name_is_valid(name)? {
+        bail!("Invalid branch name: {}", name);
+    }
+    if repo.find_branch(name, git2::BranchType::Local).is_ok() {
+        bail!("Branch '{}' already exists", name);
+    }
+
+    let refname = format!("refs/heads/{}", name);
+    match repo.head() {
+        Ok(head) => {
+            let commit = head.peel_to_commit().context("Failed to read HEAD")?;
+            repo.branch(name, &commit, false)
+                .with_context(|| format!("Failed to create branch '{}'", name))?;
+        }
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {}
+        Err(e) => return Err(e).context("Failed to read HEAD"),
+    }
+    repo.set_head(&refname)
+        .with_context(|| format!("Failed to switch to branch '{}'", name))?;
+    debug!("Created and switched to branch {}", name);
+    Ok(())
+}
+
 /// Extracts a ticket reference from a branch name.
 ///
 /// # Arguments
@@ -274,7 +872,9 @@ pub fn get_current_branch(repo: &Repository) -> Result<String> {
 ///
 /// # Pattern
 ///
-/// Matches uppercase letters followed by a dash and digits: `[A-Z]+-\d+`
+/// Matches uppercase letters followed by a dash and digits: `[A-Z]+-\d+`.
+/// Other trackers and several tickets per branch are supported by
+/// [`crate::ticket::TicketExtractor`].
 ///
 /// # Examples
 ///
@@ -326,11 +926,228 @@ pub fn extract_ticket_from_branch(branch: &str) -> Option<String> {
 /// - Uses temporary files for commit messages
 /// - Sets a timeout to prevent hanging
 pub fn commit_group(repo_path: &Path, group: &ChangeGroup) -> Result<String> {
+    commit_group_cancellable(
+        repo_path,
+        group,
+        &TimeoutConfig::default(),
+        &AtomicBool::new(false),
+    )
+}
+
+/// Commits a single change group within the configured timeouts, stopping
+/// when `cancel` is set.
+///
+/// Works like [`commit_group`]. When cancelled or timed out, the running git
+/// process and the hooks it started are killed, a lock file left behind is
+/// removed, and the index is restored to its state before the commit unless
+/// the commit was already created.
+///
+/// # Errors
+///
+/// Returns [`Cancelled`] if the commit was cancelled, [`TimedOut`] if a
+/// command took longer than its timeout, and the errors of [`commit_group`]
+/// otherwise.
+pub fn commit_group_cancellable(
+    repo_path: &Path,
+    group: &ChangeGroup,
+    timeouts: &TimeoutConfig,
+    cancel: &AtomicBool,
+) -> Result<String> {
+    commit_group_with_message(repo_path, group, &group.full_message(), timeouts, cancel)
+}
+
+/// Commits a single change group with `message` instead of the group's
+/// own, e.g. as rewritten by the message post-processors (see
+/// [`crate::postprocess`]).
+///
+/// Works like [`commit_group_cancellable`].
+///
+/// # Errors
+///
+/// Returns the errors of [`commit_group_cancellable`].
+pub fn commit_group_with_message(
+    repo_path: &Path,
+    group: &ChangeGroup,
+    message: &str,
+    timeouts: &TimeoutConfig,
+    cancel: &AtomicBool,
+) -> Result<String> {
+    let repo = Repository::discover(repo_path).context("Failed to open repository")?;
+    let index_path = repo.path().join("index");
+    let head_before = repo.head().ok().and_then(|head| head.target());
+    let index_before = fs::read(&index_path).ok();
+    let started = SystemTime::now();
+
+    if crate::subprocess::is_disabled() {
+        return commit_in_process(&repo, group, message);
+    }
+    let result = stage_and_commit(repo_path, group, message, timeouts, cancel);
+    if !matches!(&result, Err(e) if e.is::<Cancelled>() || e.is::<TimedOut>()) {
+        return result;
+    }
+
+    remove_stale_lock(&repo.path().join("index.lock"), started);
+    let head_after = repo.head().ok().and_then(|head| head.target());
+    if head_after != head_before {
+        log::warn!("Commit was created before it could be stopped");
+        return Ok("Commit was created before it could be stopped\n".to_string());
+    }
+    if let Some(index) = index_before {
+        fs::write(&index_path, index).context("Failed to restore the index")?;
+        debug!("Restored the index after stopping a commit");
+    }
+    result
+}
+
+/// Stages the files of `group` and commits them with libgit2.
+///
+/// Mirrors `git commit -- <paths>`: only the group's paths are committed,
+/// other staged changes stay staged. Hooks do not run and the commit is not
+/// signed.
+fn commit_in_process(repo: &Repository, group: &ChangeGroup, message: &str) -> Result<String> {
+    let workdir = repo
+        .workdir()
+        .context("Repository has no working directory")?;
+    let mut paths: Vec<&str> = Vec::new();
+    for file in &group.files {
+        if !is_valid_path(&file.path) {
+            bail!("Invalid file path: {}", file.path);
+        }
+        paths.push(&file.path);
+        if let Some(old_path) = &file.old_path {
+            if !is_valid_path(old_path) {
+                bail!("Invalid file path: {}", old_path);
+            }
+            paths.push(old_path);
+        }
+    }
+
+    // Stage the group's paths, including deletions
+    let mut index = repo.index().context("Failed to read the index")?;
+    for path in &paths {
+        if fs::symlink_metadata(workdir.join(path)).is_ok() {
+            index
+                .add_path(Path::new(path))
+                .with_context(|| format!("Failed to stage {}", path))?;
+        } else {
+            index
+                .remove_path(Path::new(path))
+                .with_context(|| format!("Failed to stage {}", path))?;
+        }
+    }
+    index.write().context("Failed to write the index")?;
+
+    // Commit HEAD's tree with only the group's paths taken from the index
+    let head = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let mut tree_index = git2::Index::new().context("Failed to create an index")?;
+    if let Some(head) = &head {
+        tree_index
+            .read_tree(&head.tree().context("Failed to read HEAD")?)
+            .context("Failed to read HEAD")?;
+    }
+    for path in &paths {
+        match index.get_path(Path::new(path), 0) {
+            Some(entry) => tree_index
+                .add(&entry)
+                .with_context(|| format!("Failed to commit {}", path))?,
+            None => {
+                // Not in HEAD either if removing fails
+                let _ = tree_index.remove(Path::new(path), 0);
+            }
+        }
+    }
+    let tree_id = tree_index
+        .write_tree_to(repo)
+        .context("Failed to write the commit tree")?;
+    if head.as_ref().map(|head| head.tree_id()) == Some(tree_id) {
+        bail!("git commit failed: nothing to commit");
+    }
+
+    let tree = repo
+        .find_tree(tree_id)
+        .context("Failed to read the commit tree")?;
+    let signature = repo
+        .signature()
+        .context("Failed to read user.name and user.email")?;
+    let message = git2::message_prettify(message, Some(b'#'))
+        .context("Failed to clean up the commit message")?;
+    let parents: Vec<&git2::Commit> = head.iter().collect();
+    let id = repo
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &parents,
+        )
+        .context("git commit failed")?;
+
+    let branch = get_current_branch(repo).unwrap_or_else(|_| "HEAD".to_string());
+    Ok(format!(
+        "[{} {}] {}\n {} file(s) changed (libgit2: hooks and signing skipped)\n",
+        branch,
+        short_id(id),
+        message.lines().next().unwrap_or_default(),
+        paths.len()
+    ))
+}
+
+/// Removes a lock file created after `since` by a killed git process.
+fn remove_stale_lock(lock: &Path, since: SystemTime) {
+    let created_by_us = fs::metadata(lock)
+        .and_then(|meta| meta.modified())
+        .is_ok_and(|modified| modified >= since);
+    if created_by_us {
+        match fs::remove_file(lock) {
+            Ok(()) => debug!("Removed {} left by a stopped command", lock.display()),
+            Err(e) => log::warn!("Failed to remove {}: {}", lock.display(), e),
+        }
+    }
+}
+
+/// Stages the files of `group` and commits them with `message`.
+fn stage_and_commit(
+    repo_path: &Path,
+    group: &ChangeGroup,
+    message: &str,
+    timeouts: &TimeoutConfig,
+    cancel: &AtomicBool,
+) -> Result<String> {
     // Validate all file paths first
     for file in &group.files {
         if !is_valid_path(&file.path) {
             bail!("Invalid file path: {}", file.path);
         }
+        if let Some(old_path) = &file.old_path {
+            if !is_valid_path(old_path) {
+                bail!("Invalid file path: {}", old_path);
+            }
+        }
+    }
+
+    // Stage the deletion side of renames; the old path may already be gone
+    // from the index, so unmatched paths are not an error
+    let old_paths: Vec<&str> = group
+        .files
+        .iter()
+        .filter_map(|f| f.old_path.as_deref())
+        .collect();
+    if !old_paths.is_empty() {
+        let mut rm_cmd = Command::new("git");
+        rm_cmd
+            .arg("-C")
+            .arg(repo_path)
+            .args(["rm", "--cached", "--ignore-unmatch", "-q", "--"])
+            .args(&old_paths);
+
+        let rm_output = execute_with_timeout(&mut rm_cmd, timeouts.stage(), cancel)
+            .context("Failed to stage renamed files")?;
+        if !rm_output.status.success() {
+            let stderr = String::from_utf8_lossy(&rm_output.stderr);
+            error!("git rm failed: {}", stderr);
+            bail!("Failed to stage renamed files: {}", stderr);
+        }
     }
 
     // Stage the files in this group
@@ -343,7 +1160,7 @@ pub fn commit_group(repo_path: &Path, group: &ChangeGroup) -> Result<String> {
         stage_cmd.arg(&file.path);
     }
 
-    let stage_output = execute_with_timeout(&mut stage_cmd, Duration::from_secs(10))
+    let stage_output = execute_with_timeout(&mut stage_cmd, timeouts.stage(), cancel)
         .context("Failed to stage files")?;
 
     if !stage_output.status.success() {
@@ -356,10 +1173,9 @@ pub fn commit_group(repo_path: &Path, group: &ChangeGroup) -> Result<String> {
     // even if they were previously unstaged. This is intentional behavior.
 
     // Create commit message
-    let msg = group.full_message();
     let mut tmp = NamedTempFile::new().context("Failed to create temporary file")?;
 
-    std::io::Write::write_all(&mut tmp, msg.as_bytes())
+    std::io::Write::write_all(&mut tmp, message.as_bytes())
         .context("Failed to write commit message")?;
     tmp.flush().context("Failed to flush commit message")?;
 
@@ -372,10 +1188,11 @@ pub fn commit_group(repo_path: &Path, group: &ChangeGroup) -> Result<String> {
         .arg(tmp.path())
         .arg("--");
 
-    // Add specific files to this commit
+    // Add specific files to this commit, including both sides of renames
     for file in &group.files {
         cmd.arg(&file.path);
     }
+    cmd.args(&old_paths);
 
     // Execute with timeout for robustness
     debug!(
@@ -385,7 +1202,7 @@ pub fn commit_group(repo_path: &Path, group: &ChangeGroup) -> Result<String> {
             .collect::<Vec<_>>()
             .join(" ")
     );
-    let output = execute_with_timeout(&mut cmd, Duration::from_secs(30))
+    let output = execute_with_timeout(&mut cmd, timeouts.commit(), cancel)
         .context("Failed to execute git commit")?;
 
     // Capture both stdout and stderr for display
@@ -420,7 +1237,718 @@ pub fn commit_all_groups(repo_path: &Path, groups: &[ChangeGroup]) -> Result<()>
     Ok(())
 }
 
-/// Executes a command with a timeout.
+/// Undoes a commit like `git reset --soft HEAD~1`, keeping its changes
+/// staged.
+///
+/// Only `commit` is undone: it must still be HEAD, have exactly one parent,
+/// and not be on the branch's upstream yet, so commits made outside the
+/// session and commits others may have fetched are never rewritten.
+///
+/// # Errors
+///
+/// Returns an error if HEAD is not `commit`, `commit` is a root or merge
+/// commit, it has been pushed to the upstream, or the reset fails.
+pub fn undo_commit(repo: &Repository, commit: Oid) -> Result<()> {
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .context("Failed to read HEAD")?;
+    if head.id() != commit {
+        bail!(
+            "HEAD is {}, not the commit {} created by commit-wizard",
+            short_id(head.id()),
+            short_id(commit)
+        );
+    }
+    if head.parent_count() != 1 {
+        bail!(
+            "Commit {} has {} parents; only commits with one parent can be undone",
+            short_id(commit),
+            head.parent_count()
+        );
+    }
+
+    // A pushed commit would come back on the next pull
+    let upstream = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .map(git2::Branch::wrap)
+        .and_then(|branch| branch.upstream().ok())
+        .and_then(|upstream| upstream.get().target());
+    if let Some(upstream) = upstream {
+        if upstream == commit || repo.graph_descendant_of(upstream, commit).unwrap_or(false) {
+            bail!("Commit {} has already been pushed", short_id(commit));
+        }
+    }
+
+    let parent = head.parent(0).context("Failed to read the parent commit")?;
+    repo.reset(parent.as_object(), ResetType::Soft, None)
+        .context("Failed to reset to the parent commit")?;
+    debug!("Undid commit {}", commit);
+    Ok(())
+}
+
+/// Creates an annotated tag on HEAD.
+///
+/// The tag is created with the git CLI so that signing honors the user's
+/// git and GPG configuration.
+///
+/// # Arguments
+///
+/// * `repo_path` - Path to the git repository
+/// * `name` - Name of the tag
+/// * `message` - Tag message (e.g. release notes)
+/// * `sign` - Whether to create a GPG-signed tag
+///
+/// # Returns
+///
+/// The combined output of `git tag`.
+///
+/// # Errors
+///
+/// Returns an error if the tag name is invalid, the tag already exists, or
+/// git fails.
+pub fn create_tag(repo_path: &Path, name: &str, message: &str, sign: bool) -> Result<String> {
+    create_tag_cancellable(
+        repo_path,
+        name,
+        message,
+        sign,
+        &TimeoutConfig::default(),
+        &AtomicBool::new(false),
+    )
+}
+
+/// Creates an annotated tag on HEAD within the configured timeout, stopping
+/// when `cancel` is set.
+///
+/// # Errors
+///
+/// Returns [`Cancelled`] if tagging was cancelled, and the errors of
+/// [`create_tag`] otherwise.
+pub fn create_tag_cancellable(
+    repo_path: &Path,
+    name: &str,
+    message: &str,
+    sign: bool,
+    timeouts: &TimeoutConfig,
+    cancel: &AtomicBool,
+) -> Result<String> {
+    if name.starts_with('-') || !git2::Reference::is_valid_name(&format!("refs/tags/{}", name)) {
+        bail!("Invalid tag name: {}", name);
+    }
+
+    if crate::subprocess::is_disabled() {
+        if sign {
+            bail!("Signing tags needs git, but external programs are disabled by --no-subprocess");
+        }
+        return tag_in_process(repo_path, name, message);
+    }
+
+    let mut tmp = NamedTempFile::new().context("Failed to create temporary file")?;
+    std::io::Write::write_all(&mut tmp, message.as_bytes())
+        .context("Failed to write tag message")?;
+    tmp.flush().context("Failed to flush tag message")?;
+
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(repo_path)
+        .arg("tag")
+        .arg(if sign { "-s" } else { "-a" })
+        .arg("-F")
+        .arg(tmp.path())
+        .arg(name);
+
+    // Signing may wait for a passphrase prompt, so the default is generous
+    let output = execute_with_timeout(&mut cmd, timeouts.tag(), cancel)
+        .context("Failed to execute git tag")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if !output.status.success() {
+        error!("git tag failed: {}", stderr);
+        bail!("git tag failed: {}", stderr);
+    }
+
+    Ok(format!("{}{}", stdout, stderr))
+}
+
+/// Creates an annotated tag at HEAD with libgit2.
+fn tag_in_process(repo_path: &Path, name: &str, message: &str) -> Result<String> {
+    let repo = Repository::discover(repo_path).context("Failed to open repository")?;
+    let head = repo
+        .head()
+        .and_then(|head| head.peel(git2::ObjectType::Commit))
+        .context("Failed to read HEAD")?;
+    let signature = repo
+        .signature()
+        .context("Failed to read user.name and user.email")?;
+    let message = git2::message_prettify(message, Some(b'#'))
+        .context("Failed to clean up the tag message")?;
+    repo.tag(name, &head, &signature, &message, false)
+        .with_context(|| format!("git tag failed: cannot create tag {}", name))?;
+    Ok(String::new())
+}
+
+/// Pushes the current branch to a remote.
+///
+/// # Arguments
+///
+/// * `repo_path` - Path to the git repository
+/// * `remote` - Name of the remote to push to
+/// * `branch` - Name of the local branch
+///
+/// # Returns
+///
+/// The combined output of `git push`.
+///
+/// # Errors
+///
+/// Returns an error if the names are invalid or the push fails.
+pub fn push_branch(repo_path: &Path, remote: &str, branch: &str) -> Result<String> {
+    push_branch_cancellable(
+        repo_path,
+        remote,
+        branch,
+        &TimeoutConfig::default(),
+        &AtomicBool::new(false),
+    )
+}
+
+/// Pushes the current branch to a remote within the configured timeout,
+/// stopping when `cancel` is set.
+///
+/// Credential prompts are disabled: the push runs without a terminal, so
+/// it fails instead of waiting for input that cannot be given.
+///
+/// # Errors
+///
+/// Returns [`Cancelled`] if the push was cancelled, and the errors of
+/// [`push_branch`] otherwise.
+pub fn push_branch_cancellable(
+    repo_path: &Path,
+    remote: &str,
+    branch: &str,
+    timeouts: &TimeoutConfig,
+    cancel: &AtomicBool,
+) -> Result<String> {
+    if remote.starts_with('-') || !git2::Remote::is_valid_name(remote) {
+        bail!("Invalid remote name: {}", remote);
+    }
+    if branch.starts_with('-') || !git2::Branch::name_is_valid(branch)? {
+        bail!("Invalid branch name: {}", branch);
+    }
+
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(repo_path)
+        .arg("push")
+        .arg(remote)
+        .arg(format!("refs/heads/{0}:refs/heads/{0}", branch))
+        .env("GIT_TERMINAL_PROMPT", "0");
+
+    // Network operations may be slow; the default allows more time than
+    // for local commands
+    let output = execute_with_timeout(&mut cmd, timeouts.push(), cancel)
+        .context("Failed to execute git push")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if !output.status.success() {
+        error!("git push failed: {}", stderr);
+        bail!("git push failed: {}", stderr);
+    }
+
+    Ok(format!("{}{}", stdout, stderr))
+}
+
+/// Where the checked out branch stands: its last commit and how far it
+/// is from its upstream.
+///
+/// The counts compare with the upstream as of the last fetch, so a branch
+/// can be behind without knowing it until [`pull_rebase_cancellable`]
+/// fetches.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BranchStatus {
+    /// Short id of the HEAD commit; `None` before the first commit
+    pub head_id: Option<String>,
+    /// Summary line of the HEAD commit
+    pub head_summary: String,
+    /// Upstream branch, e.g. `origin/main`
+    pub upstream: Option<String>,
+    /// Commits on the branch the upstream does not have
+    pub ahead: usize,
+    /// Commits on the upstream the branch does not have
+    pub behind: usize,
+}
+
+impl BranchStatus {
+    /// Reads the HEAD commit and compares the branch with its upstream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the commits cannot be compared.
+    pub fn collect(repo: &Repository) -> Result<Self> {
+        let mut status = Self::default();
+        let Ok(head) = repo.head() else {
+            // No commit yet
+            return Ok(status);
+        };
+        let Some(local) = head.target() else {
+            return Ok(status);
+        };
+        if let Ok(commit) = repo.find_commit(local) {
+            status.head_id = Some(short_id(local));
+            status.head_summary = commit.summary().unwrap_or_default().to_string();
+        }
+
+        if !head.is_branch() {
+            return Ok(status);
+        }
+        let branch = git2::Branch::wrap(head);
+        let Ok(upstream) = branch.upstream() else {
+            return Ok(status);
+        };
+        status.upstream = upstream.name().ok().flatten().map(str::to_string);
+        if let Some(remote) = upstream.get().target() {
+            let (ahead, behind) = repo
+                .graph_ahead_behind(local, remote)
+                .context("Failed to compare the branch with its upstream")?;
+            status.ahead = ahead;
+            status.behind = behind;
+        }
+        Ok(status)
+    }
+
+    /// Returns whether the upstream has commits the branch lacks.
+    pub fn is_behind(&self) -> bool {
+        self.behind > 0
+    }
+
+    /// Describes the distance to the upstream, e.g. `origin/main ↑2 ↓1`.
+    ///
+    /// Returns `None` without an upstream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::git::BranchStatus;
+    ///
+    /// let status = BranchStatus {
+    ///     upstream: Some("origin/main".to_string()),
+    ///     ahead: 2,
+    ///     behind: 1,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(status.tracking_label().as_deref(), Some("origin/main ↑2 ↓1"));
+    ///
+    /// let status = BranchStatus {
+    ///     upstream: Some("origin/main".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(status.tracking_label().as_deref(), Some("origin/main ✓"));
+    /// assert_eq!(BranchStatus::default().tracking_label(), None);
+    /// ```
+    pub fn tracking_label(&self) -> Option<String> {
+        let upstream = self.upstream.as_ref()?;
+        let mut label = upstream.clone();
+        if self.ahead > 0 {
+            label.push_str(&format!(" ↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            label.push_str(&format!(" ↓{}", self.behind));
+        }
+        if self.ahead == 0 && self.behind == 0 {
+            label.push_str(" ✓");
+        }
+        Some(label)
+    }
+}
+
+/// A submodule with changes that are not recorded in the superproject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirtySubmodule {
+    /// Path of the submodule
+    pub path: String,
+    /// What changed, e.g. `new commits` or `modified files`
+    pub changes: Vec<&'static str>,
+}
+
+/// Repository context for the info popup: where the repository is, where
+/// the branch stands, and what else is pending besides the changed files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoInfo {
+    /// Working directory
+    pub workdir: PathBuf,
+    /// Checked out branch; `None` on a detached HEAD
+    pub branch: Option<String>,
+    /// HEAD commit and distance to the upstream
+    pub status: BranchStatus,
+    /// Author of the HEAD commit
+    pub author: Option<String>,
+    /// When the HEAD commit was made, as `YYYY-MM-DD HH:MM` local time
+    pub committed_at: Option<String>,
+    /// Number of stash entries
+    pub stashes: usize,
+    /// Submodules with changes, by path
+    pub dirty_submodules: Vec<DirtySubmodule>,
+}
+
+impl RepoInfo {
+    /// Collects the information, reading each part from the repository.
+    ///
+    /// Submodules that cannot be read are logged and left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository has no working directory or the
+    /// branch cannot be compared with its upstream.
+    pub fn collect(repo: &Repository) -> Result<Self> {
+        let workdir = repo
+            .workdir()
+            .context("Repository has no working directory")?
+            .to_path_buf();
+        let head = repo.head().ok();
+        let branch = head
+            .as_ref()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand())
+            .map(str::to_string);
+        let commit = head.and_then(|head| head.peel_to_commit().ok());
+        let author = commit
+            .as_ref()
+            .and_then(|c| c.author().name().map(str::to_string));
+        let committed_at = commit.as_ref().and_then(|c| {
+            chrono::DateTime::from_timestamp(c.time().seconds(), 0).map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+        });
+        // Each stash entry is a reflog entry of refs/stash
+        let stashes = repo
+            .reflog("refs/stash")
+            .map(|reflog| reflog.len())
+            .unwrap_or(0);
+
+        Ok(Self {
+            workdir,
+            branch,
+            status: BranchStatus::collect(repo)?,
+            author,
+            committed_at,
+            stashes,
+            dirty_submodules: dirty_submodules(repo),
+        })
+    }
+}
+
+/// Lists the submodules with new commits, uncommitted changes, or
+/// untracked files.
+fn dirty_submodules(repo: &Repository) -> Vec<DirtySubmodule> {
+    let submodules = match repo.submodules() {
+        Ok(submodules) => submodules,
+        Err(e) => {
+            debug!("Failed to list submodules: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut dirty = Vec::new();
+    for submodule in submodules {
+        let Some(name) = submodule.name() else {
+            continue;
+        };
+        let status = match repo.submodule_status(name, git2::SubmoduleIgnore::None) {
+            Ok(status) => status,
+            Err(e) => {
+                debug!("Failed to read the status of submodule {}: {}", name, e);
+                continue;
+            }
+        };
+        let mut changes = Vec::new();
+        if status.is_wd_modified() {
+            changes.push("new commits");
+        }
+        if status.intersects(
+            git2::SubmoduleStatus::WD_INDEX_MODIFIED | git2::SubmoduleStatus::WD_WD_MODIFIED,
+        ) {
+            changes.push("modified files");
+        }
+        if status.is_wd_untracked() {
+            changes.push("untracked files");
+        }
+        if !changes.is_empty() {
+            dirty.push(DirtySubmodule {
+                path: submodule.path().to_string_lossy().into_owned(),
+                changes,
+            });
+        }
+    }
+    dirty
+}
+
+/// Fetches the upstream and rebases the current branch onto it within the
+/// push timeout, stopping when `cancel` is set.
+///
+/// Uncommitted changes are stashed for the rebase and restored afterwards
+/// (`git pull --rebase --autostash`). A rebase that stops on conflicts is
+/// aborted, leaving the branch and the working tree as they were.
+///
+/// # Errors
+///
+/// Returns [`Cancelled`] if the pull was cancelled, and an error if the
+/// fetch or the rebase fails.
+pub fn pull_rebase_cancellable(
+    repo_path: &Path,
+    timeouts: &TimeoutConfig,
+    cancel: &AtomicBool,
+) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(repo_path)
+        .args(["pull", "--rebase", "--autostash"])
+        .env("GIT_TERMINAL_PROMPT", "0");
+
+    let output = execute_with_timeout(&mut cmd, timeouts.push(), cancel);
+    let rebasing = Repository::discover(repo_path)
+        .map(|repo| {
+            matches!(
+                repo.state(),
+                git2::RepositoryState::Rebase
+                    | git2::RepositoryState::RebaseInteractive
+                    | git2::RepositoryState::RebaseMerge
+            )
+        })
+        .unwrap_or(false);
+    if rebasing {
+        let aborted = Command::new("git")
+            .arg("-C")
+            .arg(repo_path)
+            .args(["rebase", "--abort"])
+            .output();
+        if !aborted.is_ok_and(|o| o.status.success()) {
+            bail!("The rebase stopped and could not be aborted; run `git rebase --abort`");
+        }
+    }
+    let output = output.context("Failed to execute git pull")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        error!("git pull failed: {}", stderr);
+        if rebasing {
+            bail!("The rebase had conflicts and was aborted: {}", stderr);
+        }
+        bail!("git pull failed: {}", stderr);
+    }
+
+    Ok(format!("{}{}", stdout, stderr))
+}
+
+/// Result of cherry-picking commits onto a branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CherryPick {
+    /// Branch the commits were picked onto
+    pub branch: String,
+    /// Commits created on the branch, in order
+    pub created: Vec<git2::Oid>,
+    /// Source commits whose changes the branch already contained
+    pub skipped: Vec<git2::Oid>,
+    /// Source commit that conflicted and the conflicting paths; the branch
+    /// is left unchanged
+    pub conflict: Option<(git2::Oid, Vec<String>)>,
+}
+
+impl CherryPick {
+    /// Returns whether the commits were picked without conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflict.is_none()
+    }
+}
+
+impl fmt::Display for CherryPick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((commit, paths)) = &self.conflict {
+            return write!(
+                f,
+                "✗ {}: {} conflicts in {}, branch left unchanged",
+                self.branch,
+                short_id(*commit),
+                paths.join(", ")
+            );
+        }
+        write!(
+            f,
+            "✓ {}: {} commit(s) picked",
+            self.branch,
+            self.created.len()
+        )?;
+        if !self.created.is_empty() {
+            let ids: Vec<String> = self.created.iter().map(|id| short_id(*id)).collect();
+            write!(f, " ({})", ids.join(", "))?;
+        }
+        if !self.skipped.is_empty() {
+            write!(f, ", {} already on the branch", self.skipped.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the abbreviated form of a commit id.
+fn short_id(id: git2::Oid) -> String {
+    id.to_string().chars().take(7).collect()
+}
+
+/// Lists the local branches that commits can be cherry-picked onto.
+///
+/// Branches checked out in any working tree are left out, since moving them
+/// would leave that working tree out of sync.
+///
+/// # Errors
+///
+/// Returns an error if the branches or worktrees cannot be read.
+pub fn cherry_pick_targets(repo: &Repository) -> Result<Vec<String>> {
+    let checked_out = checked_out_branches(repo)?;
+    let mut names = Vec::new();
+    for branch in repo
+        .branches(Some(git2::BranchType::Local))
+        .context("Failed to list branches")?
+    {
+        let (branch, _) = branch?;
+        if let Some(name) = branch.name()? {
+            if !checked_out.iter().any(|b| b == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Returns the branches checked out in the main working tree and all
+/// linked worktrees.
+fn checked_out_branches(repo: &Repository) -> Result<Vec<String>> {
+    let main = Repository::open(repo.commondir()).context("Failed to open repository")?;
+    let mut repos = vec![main];
+    let names = repos[0].worktrees().context("Failed to list worktrees")?;
+    for name in names.iter().flatten() {
+        let worktree = repos[0].find_worktree(name)?;
+        if worktree.validate().is_ok() {
+            repos.push(Repository::open_from_worktree(&worktree)?);
+        }
+    }
+
+    let mut branches = Vec::new();
+    for repo in &repos {
+        if let Ok(head) = repo.find_reference("HEAD") {
+            if let Some(target) = head.symbolic_target() {
+                if let Some(name) = target.strip_prefix("refs/heads/") {
+                    branches.push(name.to_string());
+                }
+            }
+        }
+    }
+    Ok(branches)
+}
+
+/// Cherry-picks `commits` in order onto `branch`.
+///
+/// The commits are applied in memory on top of the branch tip, keeping
+/// their author and message, so the working tree and the index are not
+/// touched. The branch is only moved if all commits apply; on the first
+/// conflict it is left unchanged and the conflict is reported. Hooks do
+/// not run and commits are not signed.
+///
+/// # Arguments
+///
+/// * `repo_path` - Path to the git repository
+/// * `commits` - Commits to pick, oldest first
+/// * `branch` - Name of a local branch that is not checked out
+///
+/// # Errors
+///
+/// Returns an error if the branch does not exist or is checked out, a
+/// commit cannot be found, or git fails.
+pub fn cherry_pick_onto(
+    repo_path: &Path,
+    commits: &[git2::Oid],
+    branch: &str,
+) -> Result<CherryPick> {
+    let repo = Repository::discover(repo_path).context("Failed to open repository")?;
+    if checked_out_branches(&repo)?.iter().any(|b| b == branch) {
+        bail!("Branch '{}' is checked out and cannot be updated", branch);
+    }
+    let mut reference = repo
+        .find_branch(branch, git2::BranchType::Local)
+        .with_context(|| format!("Branch '{}' not found", branch))?
+        .into_reference();
+    let mut tip = reference
+        .peel_to_commit()
+        .with_context(|| format!("Branch '{}' has no commit", branch))?;
+
+    let mut result = CherryPick {
+        branch: branch.to_string(),
+        created: Vec::new(),
+        skipped: Vec::new(),
+        conflict: None,
+    };
+    for &id in commits {
+        let commit = repo
+            .find_commit(id)
+            .with_context(|| format!("Commit {} not found", id))?;
+        let mut index = repo
+            .cherrypick_commit(&commit, &tip, 0, None)
+            .with_context(|| format!("Failed to cherry-pick {} onto {}", id, branch))?;
+
+        if index.has_conflicts() {
+            let mut paths: Vec<String> = index
+                .conflicts()?
+                .filter_map(|conflict| conflict.ok())
+                .filter_map(|conflict| conflict.our.or(conflict.their).or(conflict.ancestor))
+                .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+                .collect();
+            paths.dedup();
+            result.created.clear();
+            result.skipped.clear();
+            result.conflict = Some((id, paths));
+            return Ok(result);
+        }
+
+        let tree_id = index.write_tree_to(&repo)?;
+        if tree_id == tip.tree_id() {
+            debug!("{} is already on {}", id, branch);
+            result.skipped.push(id);
+            continue;
+        }
+        let tree = repo.find_tree(tree_id)?;
+        let committer = repo
+            .signature()
+            .unwrap_or_else(|_| commit.committer().to_owned());
+        let message = commit.message_raw().unwrap_or_default();
+        let new_id = repo.commit(None, &commit.author(), &committer, message, &tree, &[&tip])?;
+        tip = repo.find_commit(new_id)?;
+        result.created.push(new_id);
+    }
+
+    if !result.created.is_empty() {
+        reference.set_target(
+            tip.id(),
+            &format!(
+                "commit-wizard: cherry-pick {} commit(s)",
+                result.created.len()
+            ),
+        )?;
+    }
+    Ok(result)
+}
+
+/// Executes a command with a timeout, stopping it when `cancel` is set.
+///
+/// On Unix the command runs in its own process group, so hooks and helpers
+/// it starts (e.g. `ssh` during a push) are killed together with it.
 ///
 /// # Security & Robustness
 ///
@@ -428,25 +1956,105 @@ pub fn commit_all_groups(repo_path: &Path, groups: &[ChangeGroup]) -> Result<()>
 /// - Freeze the UI
 /// - Consume system resources
 /// - Enable DoS attacks
-fn execute_with_timeout(cmd: &mut Command, timeout: Duration) -> Result<std::process::Output> {
-    use std::sync::mpsc;
-    use std::thread;
+///
+/// # Errors
+///
+/// Returns [`Cancelled`] if `cancel` was set, [`TimedOut`] if the command
+/// took longer than `timeout`, and an error if it cannot be started.
+pub(crate) fn execute_with_timeout(
+    cmd: &mut Command,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Result<Output> {
+    execute_with_input(cmd, None, timeout, cancel)
+}
 
-    let (tx, rx) = mpsc::channel();
+/// Executes a command like [`execute_with_timeout`], writing `input` to its
+/// standard input if given.
+///
+/// # Errors
+///
+/// Returns the errors of [`execute_with_timeout`].
+pub(crate) fn execute_with_input(
+    cmd: &mut Command,
+    input: Option<&[u8]>,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Result<Output> {
+    crate::subprocess::ensure_allowed(&cmd.get_program().to_string_lossy())?;
+    cmd.stdin(if input.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    })
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
 
-    // Clone command for execution in thread
-    let mut cmd_clone = Command::new(cmd.get_program());
-    cmd_clone.args(cmd.get_args());
-    if let Some(dir) = cmd.get_current_dir() {
-        cmd_clone.current_dir(dir);
+    let mut child = cmd.spawn().context("Command execution failed")?;
+    // Written on a thread like the output is read, so neither side blocks;
+    // a command that does not read its input just closes the pipe
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        let input = input.to_vec();
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
     }
+    // Drain the pipes on threads so a chatty command cannot block on a full
+    // pipe while it is polled
+    let stdout = child.stdout.take().map(read_pipe);
+    let stderr = child.stderr.take().map(read_pipe);
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().context("Command execution failed")? {
+            break status;
+        }
+        if cancel.load(Ordering::Relaxed) {
+            kill_process_tree(&mut child);
+            return Err(Cancelled.into());
+        }
+        if started.elapsed() >= timeout {
+            kill_process_tree(&mut child);
+            return Err(TimedOut(timeout).into());
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let collect = |pipe: Option<thread::JoinHandle<Vec<u8>>>| {
+        pipe.and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    };
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
 
+/// Reads a pipe to the end on a background thread.
+fn read_pipe<R: Read + Send + 'static>(mut pipe: R) -> thread::JoinHandle<Vec<u8>> {
     thread::spawn(move || {
-        let result = cmd_clone.output();
-        let _ = tx.send(result);
-    });
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
 
-    rx.recv_timeout(timeout)
-        .context("Command execution timed out")?
-        .context("Command execution failed")
+/// Kills a child process and, on Unix, its process group.
+fn kill_process_tree(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let _ = Command::new("kill")
+            .args(["-KILL", "--", &format!("-{}", child.id())])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    let _ = child.kill();
+    let _ = child.wait();
 }
@@ -0,0 +1,303 @@
+//! Lightweight syntax highlighting for the file preview.
+//!
+//! This is a line-based tokenizer, not a parser: it recognizes line comments,
+//! string literals, numbers, and keywords for common languages, which is
+//! enough to make a file readable in the terminal. Block comments and
+//! multi-line strings are not tracked across lines.
+
+use std::path::Path;
+
+/// The kind of a highlighted token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Anything that is not one of the other kinds
+    Plain,
+    /// A language keyword
+    Keyword,
+    /// A string or character literal
+    String,
+    /// A numeric literal
+    Number,
+    /// A comment up to the end of the line
+    Comment,
+}
+
+/// Highlighting rules for one language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Syntax {
+    /// Language name
+    pub name: &'static str,
+    keywords: &'static [&'static str],
+    line_comment: Option<&'static str>,
+    quotes: &'static [char],
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "False", "finally", "for", "from", "global", "if", "import", "in", "is",
+    "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True", "try", "while",
+    "with", "yield",
+];
+
+const JS_KEYWORDS: &[&str] = &[
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "from",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "of",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "yield",
+];
+
+const GO_KEYWORDS: &[&str] = &[
+    "break",
+    "case",
+    "chan",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "else",
+    "false",
+    "for",
+    "func",
+    "go",
+    "goto",
+    "if",
+    "import",
+    "interface",
+    "map",
+    "nil",
+    "package",
+    "range",
+    "return",
+    "select",
+    "struct",
+    "switch",
+    "true",
+    "type",
+    "var",
+];
+
+const C_KEYWORDS: &[&str] = &[
+    "auto",
+    "bool",
+    "break",
+    "case",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "if",
+    "int",
+    "long",
+    "namespace",
+    "new",
+    "null",
+    "nullptr",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "this",
+    "true",
+    "typedef",
+    "union",
+    "unsigned",
+    "void",
+    "volatile",
+    "while",
+];
+
+const SHELL_KEYWORDS: &[&str] = &[
+    "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in",
+    "local", "return", "then", "until", "while",
+];
+
+const CONFIG_KEYWORDS: &[&str] = &["true", "false", "null", "yes", "no"];
+
+/// Returns the highlighting rules for a file based on its extension or name.
+///
+/// # Returns
+///
+/// The syntax, or [`None`] if the language is not recognized.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::highlight::syntax_for_path;
+///
+/// assert_eq!(syntax_for_path("src/main.rs").unwrap().name, "Rust");
+/// assert!(syntax_for_path("notes.txt").is_none());
+/// ```
+pub fn syntax_for_path(path: &str) -> Option<Syntax> {
+    let path = Path::new(path);
+    let file_name = path.file_name()?.to_str()?;
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+
+    let syntax = |name, keywords, line_comment, quotes| Syntax {
+        name,
+        keywords,
+        line_comment,
+        quotes,
+    };
+
+    match (file_name, extension) {
+        (_, "rs") => Some(syntax("Rust", RUST_KEYWORDS, Some("//"), &['"'])),
+        (_, "py") => Some(syntax("Python", PYTHON_KEYWORDS, Some("#"), &['"', '\''])),
+        (_, "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs") => Some(syntax(
+            "JavaScript",
+            JS_KEYWORDS,
+            Some("//"),
+            &['"', '\'', '`'],
+        )),
+        (_, "go") => Some(syntax("Go", GO_KEYWORDS, Some("//"), &['"', '`'])),
+        (_, "c" | "h" | "cc" | "cpp" | "hpp" | "java" | "cs") => {
+            Some(syntax("C-family", C_KEYWORDS, Some("//"), &['"', '\'']))
+        }
+        (_, "sh" | "bash" | "zsh") | ("Makefile" | "Dockerfile", _) => {
+            Some(syntax("Shell", SHELL_KEYWORDS, Some("#"), &['"', '\'']))
+        }
+        (_, "toml" | "yaml" | "yml") => {
+            Some(syntax("Config", CONFIG_KEYWORDS, Some("#"), &['"', '\'']))
+        }
+        (_, "json") => Some(syntax("JSON", CONFIG_KEYWORDS, None, &['"'])),
+        _ => None,
+    }
+}
+
+/// Splits a line into highlighted tokens.
+///
+/// Concatenating the token texts yields the original line.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::highlight::{highlight_line, syntax_for_path, TokenKind};
+///
+/// let syntax = syntax_for_path("main.rs").unwrap();
+/// let tokens = highlight_line("let x = 1; // one", &syntax);
+/// assert_eq!(tokens[0], (TokenKind::Keyword, "let"));
+/// assert_eq!(tokens.last(), Some(&(TokenKind::Comment, "// one")));
+/// ```
+pub fn highlight_line<'a>(line: &'a str, syntax: &Syntax) -> Vec<(TokenKind, &'a str)> {
+    let mut tokens = Vec::new();
+    // Start of the pending plain text, merged so callers get fewer spans
+    let mut plain_start = 0;
+    let mut pos = 0;
+
+    while pos < line.len() {
+        let rest = &line[pos..];
+        let c = rest.chars().next().unwrap_or_default();
+
+        let (kind, len) = if syntax.line_comment.is_some_and(|m| rest.starts_with(m)) {
+            (TokenKind::Comment, rest.len())
+        } else if syntax.quotes.contains(&c) {
+            (TokenKind::String, string_len(rest, c))
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '.' || ch == '_'))
+                .unwrap_or(rest.len());
+            (TokenKind::Number, len)
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_'))
+                .unwrap_or(rest.len());
+            let kind = if syntax.keywords.contains(&&rest[..len]) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Plain
+            };
+            (kind, len)
+        } else {
+            (TokenKind::Plain, c.len_utf8())
+        };
+
+        if kind != TokenKind::Plain {
+            if plain_start < pos {
+                tokens.push((TokenKind::Plain, &line[plain_start..pos]));
+            }
+            tokens.push((kind, &rest[..len]));
+            plain_start = pos + len;
+        }
+        pos += len;
+    }
+    if plain_start < line.len() {
+        tokens.push((TokenKind::Plain, &line[plain_start..]));
+    }
+
+    tokens
+}
+
+/// Returns the byte length of the string literal at the start of `text`,
+/// including both quotes, or the rest of the line if it is unterminated.
+fn string_len(text: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (idx, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return idx + c.len_utf8(),
+            _ => {}
+        }
+    }
+    text.len()
+}
@@ -0,0 +1,179 @@
+//! Grouping by co-change history.
+//!
+//! On a mature repository, files that were usually committed together
+//! belong together: a handler and its template, a schema and its
+//! migration. With `[history] enabled = true`, the recent history is mined
+//! for how often each pair of currently changed files was changed by the
+//! same commit. Pairs above the configured thresholds are linked like files
+//! that reference each other (see [`crate::analysis`]), so heuristic grouping
+//! commits them together.
+//!
+//! The affinity of two files is the Jaccard index of the commits changing
+//! them: commits changing both divided by commits changing either. Merge
+//! commits and commits touching more than [`MAX_FILES_PER_COMMIT`] files
+//! (mass renames, formatting runs) are skipped, as they say little about
+//! which files belong together.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use git2::{Repository, Sort};
+
+use crate::config::HistoryConfig;
+use crate::types::ChangedFile;
+
+/// Commits touching more files than this are not learned from.
+pub const MAX_FILES_PER_COMMIT: usize = 50;
+
+/// How often files, and pairs of files, were committed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoChangeStats {
+    /// Number of commits changing each file
+    commits: HashMap<String, usize>,
+    /// Number of commits changing both files, keyed by the ordered pair
+    pairs: BTreeMap<(String, String), usize>,
+}
+
+impl CoChangeStats {
+    /// Records a commit that changed `paths`; only `tracked` paths are
+    /// counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use commit_wizard::history::CoChangeStats;
+    ///
+    /// let tracked: HashSet<String> = ["a.rs", "b.rs"].iter().map(|p| p.to_string()).collect();
+    /// let mut stats = CoChangeStats::default();
+    /// stats.record(&["a.rs", "b.rs"], &tracked);
+    /// stats.record(&["a.rs", "README.md"], &tracked);
+    ///
+    /// assert_eq!(stats.together("a.rs", "b.rs"), 1);
+    /// assert_eq!(stats.affinity("b.rs", "a.rs"), 0.5);
+    /// ```
+    pub fn record<S: AsRef<str>>(&mut self, paths: &[S], tracked: &HashSet<String>) {
+        let mut paths: Vec<&str> = paths
+            .iter()
+            .map(AsRef::as_ref)
+            .filter(|path| tracked.contains(*path))
+            .collect();
+        paths.sort_unstable();
+        paths.dedup();
+
+        for (idx, path) in paths.iter().enumerate() {
+            *self.commits.entry(path.to_string()).or_default() += 1;
+            for other in &paths[idx + 1..] {
+                *self
+                    .pairs
+                    .entry((path.to_string(), other.to_string()))
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    /// Returns the number of commits that changed a file.
+    pub fn commits(&self, path: &str) -> usize {
+        self.commits.get(path).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of commits that changed both files.
+    pub fn together(&self, a: &str, b: &str) -> usize {
+        let key = if a <= b {
+            (a.to_string(), b.to_string())
+        } else {
+            (b.to_string(), a.to_string())
+        };
+        self.pairs.get(&key).copied().unwrap_or(0)
+    }
+
+    /// Returns the share of the commits changing either file that changed
+    /// both, between 0.0 and 1.0.
+    pub fn affinity(&self, a: &str, b: &str) -> f32 {
+        let together = self.together(a, b);
+        if together == 0 {
+            return 0.0;
+        }
+        together as f32 / (self.commits(a) + self.commits(b) - together) as f32
+    }
+
+    /// Returns the pairs that pass the thresholds of `config`, with their
+    /// affinity.
+    pub fn affine_pairs(&self, config: &HistoryConfig) -> Vec<(String, String, f32)> {
+        self.pairs
+            .iter()
+            .filter(|(_, &together)| together >= config.min_commits.max(1))
+            .map(|((a, b), _)| (a.clone(), b.clone(), self.affinity(a, b)))
+            .filter(|(_, _, affinity)| *affinity >= config.affinity)
+            .collect()
+    }
+}
+
+/// Counts how often the `paths` were changed together by the last `limit`
+/// commits reachable from HEAD.
+///
+/// # Errors
+///
+/// Returns an error if the history cannot be walked or a commit cannot be
+/// diffed. A repository without commits has no history and yields empty
+/// statistics.
+pub fn mine(repo: &Repository, paths: &HashSet<String>, limit: usize) -> Result<CoChangeStats> {
+    let mut stats = CoChangeStats::default();
+    let Ok(head) = repo.head().and_then(|head| head.peel_to_commit()) else {
+        return Ok(stats);
+    };
+
+    let mut walk = repo.revwalk().context("Failed to walk history")?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    walk.push(head.id())?;
+
+    for oid in walk.take(limit) {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .with_context(|| format!("Failed to diff commit {}", commit.id()))?;
+        if diff.deltas().len() > MAX_FILES_PER_COMMIT {
+            continue;
+        }
+        let changed: Vec<String> = diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        stats.record(&changed, paths);
+    }
+    Ok(stats)
+}
+
+/// Returns links between the changed files that past commits changed
+/// together, for [`crate::analysis::link_related`].
+///
+/// # Errors
+///
+/// Returns an error if the history cannot be mined.
+pub fn co_change_edges(
+    repo: &Repository,
+    files: &[ChangedFile],
+    config: &HistoryConfig,
+) -> Result<Vec<(String, String)>> {
+    let paths: HashSet<String> = files.iter().map(|file| file.path.clone()).collect();
+    let stats = mine(repo, &paths, config.commits)?;
+
+    Ok(stats
+        .affine_pairs(config)
+        .into_iter()
+        .map(|(a, b, affinity)| {
+            log::debug!("{} and {} changed together ({:.2})", a, b, affinity);
+            (a, b)
+        })
+        .collect())
+}
@@ -3,7 +3,8 @@
 //! This module analyzes file paths and content to automatically determine
 //! appropriate commit types, scopes, and generate helpful descriptions.
 
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::types::{ChangeGroup, ChangedFile, CommitType};
 
@@ -129,38 +130,106 @@ fn is_style_file(path: &str) -> bool {
         || path.contains("/css/")
 }
 
+/// Generic directories that make meaningless scopes, the default of
+/// `[scope] ignore`.
+pub const DEFAULT_IGNORED_SCOPES: &[&str] =
+    &["src", "lib", "app", "source", "pkg", "internal", "cmd"];
+
 /// Extracts a scope from a file path.
 ///
+/// Generic directories ([`DEFAULT_IGNORED_SCOPES`]) such as `src/` or Go's
+/// `cmd/` are skipped, so the scope is the module below them, or omitted if
+/// there is none. Maven/Gradle sources (`src/main/java/...`) use their
+/// package's last segment. Package manifests and `[scope]` settings are
+/// taken into account by [`crate::scope::ScopeResolver`].
+///
 /// # Arguments
 ///
 /// * `path` - The file path to analyze
 ///
 /// # Returns
 ///
-/// The first directory segment as the scope, or [`None`] if not applicable.
+/// The first meaningful directory segment as the scope, or [`None`] if not
+/// applicable.
 ///
 /// # Examples
 ///
 /// ```
 /// use commit_wizard::inference::infer_scope;
 ///
-/// assert_eq!(infer_scope("src/main.rs"), Some("src".to_string()));
+/// assert_eq!(infer_scope("src/api/users.rs"), Some("api".to_string()));
 /// assert_eq!(infer_scope("backend/api/users.rs"), Some("backend".to_string()));
+/// assert_eq!(infer_scope("cmd/server/main.go"), Some("server".to_string()));
+/// assert_eq!(infer_scope("src/main.rs"), None);
 /// assert_eq!(infer_scope("README.md"), None);
 /// ```
 pub fn infer_scope(path: &str) -> Option<String> {
-    let first_segment = path.split('/').next()?;
-
-    // Filter out non-meaningful scopes
-    if first_segment.is_empty()
-        || first_segment == "."
-        || first_segment.starts_with('.')
-        || first_segment.to_lowercase().ends_with(".md")
-    {
+    infer_scope_with(path, DEFAULT_IGNORED_SCOPES)
+}
+
+/// Extracts a scope from a file path like [`infer_scope`], skipping the
+/// given generic directories instead of the defaults.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::inference::infer_scope_with;
+///
+/// assert_eq!(infer_scope_with("web/views/home.tsx", &["web"]), Some("views".to_string()));
+/// assert_eq!(infer_scope_with("src/main.rs", &[] as &[&str]), Some("src".to_string()));
+/// ```
+pub fn infer_scope_with<S: AsRef<str>>(path: &str, ignored: &[S]) -> Option<String> {
+    let is_ignored = |segment: &str| ignored.iter().any(|i| i.as_ref() == segment);
+    let segments: Vec<&str> = path.split('/').collect();
+    let (first_segment, directories) = segments.split_first()?;
+
+    if !is_ignored(first_segment) || directories.is_empty() {
+        // Filter out non-meaningful scopes
+        if first_segment.is_empty()
+            || *first_segment == "."
+            || first_segment.starts_with('.')
+            || first_segment.to_lowercase().ends_with(".md")
+        {
+            return None;
+        }
+        return Some(first_segment.to_string());
+    }
+
+    // Only directories below the ignored one qualify, not the file itself
+    let directories = &directories[..directories.len() - 1];
+    let scope = match directories {
+        ["main" | "test", "java" | "kotlin" | "scala", package @ ..] => package.last(),
+        _ => directories.iter().find(|segment| !is_ignored(segment)),
+    }?;
+    if scope.starts_with('.') {
         return None;
     }
+    Some(scope.to_string())
+}
+
+/// Returns the scope of a file: the one assigned by
+/// [`crate::scope::ScopeResolver`] or [`crate::rules::PathRules`],
+/// otherwise [`infer_scope`].
+pub fn file_scope(file: &ChangedFile) -> Option<String> {
+    match &file.scope {
+        Some(scope) => scope.clone(),
+        None => infer_scope(&file.path),
+    }
+}
 
-    Some(first_segment.to_string())
+/// Returns the commit type of a file: the one assigned by
+/// [`crate::rules::PathRules`], otherwise [`infer_commit_type`]. Features
+/// take the type suggested by [`crate::classify::classify_files`], or are
+/// [`CommitType::Fix`] if likely fixes.
+pub fn file_commit_type(file: &ChangedFile) -> CommitType {
+    if let Some(commit_type) = file.commit_type {
+        return commit_type;
+    }
+    match (infer_commit_type(&file.path), file.suggested_type) {
+        (CommitType::Feat, Some(suggested)) => suggested,
+        (CommitType::Feat, None) if file.is_likely_fix() => CommitType::Fix,
+        (commit_type, _) => commit_type,
+    }
 }
 
 /// Generates a descriptive commit message based on the files and context.
@@ -206,6 +275,10 @@ pub fn infer_description(
 
 /// Generates bullet points for the commit body based on the files.
 ///
+/// Files with analyzed declaration changes (see [`crate::semantic`]) get
+/// one bullet per change, e.g. "add function `parse_config`"; other files
+/// get one bullet naming the file.
+///
 /// # Arguments
 ///
 /// * `files` - The files in this commit group
@@ -216,36 +289,201 @@ pub fn infer_description(
 pub fn infer_body_lines(files: &[ChangedFile]) -> Vec<String> {
     const MAX_BODY_LINES: usize = 20;
 
-    let mut lines: Vec<String> = files
-        .iter()
-        .take(MAX_BODY_LINES)
-        .map(|f| {
-            let action = if f.is_new() {
-                "add"
-            } else if f.is_deleted() {
-                "remove"
-            } else if f.is_modified() {
-                "modify"
-            } else if f.is_renamed() {
-                "rename"
-            } else {
-                "update"
-            };
-            format!("{} {}", action, f.path)
-        })
-        .collect();
+    let mut lines: Vec<String> = Vec::new();
+    let mut shown = 0;
+    for file in files {
+        if lines.len() >= MAX_BODY_LINES {
+            break;
+        }
+        let room = MAX_BODY_LINES - lines.len();
+        lines.extend(file_body_lines(file).into_iter().take(room));
+        shown += 1;
+    }
 
     // Add note if there are more files than shown
-    if files.len() > MAX_BODY_LINES {
-        lines.push(format!(
-            "... and {} more files",
-            files.len() - MAX_BODY_LINES
-        ));
+    if files.len() > shown {
+        lines.push(format!("... and {} more files", files.len() - shown));
     }
 
     lines
 }
 
+/// Returns the body lines describing one file.
+fn file_body_lines(file: &ChangedFile) -> Vec<String> {
+    if !file.symbol_changes.is_empty() {
+        let mut lines: Vec<String> = Vec::new();
+        for change in &file.symbol_changes {
+            let line = change.to_string();
+            if !lines.contains(&line) {
+                lines.push(line);
+            }
+        }
+        return lines;
+    }
+
+    let action = if file.is_new() {
+        "add"
+    } else if file.is_deleted() {
+        "remove"
+    } else if file.is_modified() {
+        "modify"
+    } else if file.is_renamed() {
+        "rename"
+    } else {
+        "update"
+    };
+    vec![format!("{} {}", action, file.path)]
+}
+
+/// Builds a `chore` group for submodule pointer updates.
+///
+/// # Returns
+///
+/// The group, or [`None`] if `files` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::inference::build_submodule_group;
+/// use commit_wizard::types::{ChangedFile, CommitType, FileKind};
+/// use git2::Status;
+///
+/// let file = ChangedFile::new("vendor/lib".into(), Status::WT_MODIFIED).with_kind(FileKind::Submodule);
+/// let group = build_submodule_group(vec![file], None).unwrap();
+/// assert_eq!(group.commit_type, CommitType::Chore);
+/// assert_eq!(group.description, "update vendor/lib submodule");
+/// ```
+pub fn build_submodule_group(
+    files: Vec<ChangedFile>,
+    ticket: Option<String>,
+) -> Option<ChangeGroup> {
+    let description = match files.as_slice() {
+        [] => return None,
+        [file] => format!("update {} submodule", file.path),
+        _ => format!("update {} submodules", files.len()),
+    };
+    let body_lines = files
+        .iter()
+        .map(|f| format!("bump {} to its current commit", f.path))
+        .collect();
+
+    Some(ChangeGroup::new(
+        CommitType::Chore,
+        None,
+        files,
+        ticket,
+        description,
+        body_lines,
+    ))
+}
+
+/// Builds the group for a change set that only deletes files, e.g. the
+/// removal of a deprecated module.
+///
+/// Removing source code is a `refactor`, removing anything else a `chore`.
+/// The description names what is removed: the module of a single source
+/// file, or the directory all files share.
+///
+/// # Returns
+///
+/// The group, or [`None`] if `files` is empty or not all files are deleted.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::inference::build_removal_group;
+/// use commit_wizard::types::{ChangedFile, CommitType};
+/// use git2::Status;
+///
+/// let files = vec![
+///     ChangedFile::new("src/legacy/mod.rs".into(), Status::INDEX_DELETED),
+///     ChangedFile::new("src/legacy/parser.rs".into(), Status::INDEX_DELETED),
+/// ];
+/// let group = build_removal_group(files, None).unwrap();
+/// assert_eq!(group.commit_type, CommitType::Refactor);
+/// assert_eq!(group.description, "remove legacy module");
+/// ```
+pub fn build_removal_group(files: Vec<ChangedFile>, ticket: Option<String>) -> Option<ChangeGroup> {
+    if files.is_empty() || !files.iter().all(ChangedFile::is_deleted) {
+        return None;
+    }
+
+    let is_code = files
+        .iter()
+        .any(|f| crate::semantic::module_name(&f.path).is_some());
+    let commit_type = if is_code {
+        CommitType::Refactor
+    } else {
+        CommitType::Chore
+    };
+
+    let mut scopes = files.iter().map(file_scope);
+    let first_scope = scopes.next().flatten();
+    let scope = if scopes.all(|scope| scope == first_scope) {
+        first_scope
+    } else {
+        None
+    };
+
+    let description = match files.as_slice() {
+        [file] => match crate::semantic::module_name(&file.path) {
+            Some(module) => format!("remove {} module", module),
+            None => format!(
+                "remove {}",
+                file.path.rsplit('/').next().unwrap_or(&file.path)
+            ),
+        },
+        _ => match common_directory(&files) {
+            Some(dir) if is_code => {
+                format!("remove {} module", dir.rsplit('/').next().unwrap_or(&dir))
+            }
+            Some(dir) => format!("remove {}", dir),
+            None => format!("remove {} files", files.len()),
+        },
+    };
+    let body_lines = infer_body_lines(&files);
+
+    Some(ChangeGroup::new(
+        commit_type,
+        scope,
+        files,
+        ticket,
+        description,
+        body_lines,
+    ))
+}
+
+/// Returns the deepest directory containing all files, unless it is a
+/// generic one such as `src` ([`DEFAULT_IGNORED_SCOPES`]).
+fn common_directory(files: &[ChangedFile]) -> Option<String> {
+    let mut common: Vec<&str> = files.first()?.path.split('/').collect();
+    common.pop();
+    for file in &files[1..] {
+        let directories: Vec<&str> = file.path.split('/').collect();
+        let directories = &directories[..directories.len() - 1];
+        let shared = common
+            .iter()
+            .zip(directories)
+            .take_while(|(a, b)| a == b)
+            .count();
+        common.truncate(shared);
+    }
+    let last = common.last()?;
+    if DEFAULT_IGNORED_SCOPES.contains(last) {
+        return None;
+    }
+    Some(common.join("/"))
+}
+
+/// Groups with more files than this get their body lines when first shown
+/// or committed (see [`ChangeGroup::ensure_body`]) instead of while
+/// grouping.
+pub const LAZY_BODY_FILES: usize = 200;
+
+/// Number of files between progress reports of
+/// [`build_groups_with_progress`].
+const PROGRESS_INTERVAL: usize = 500;
+
 /// Groups changed files into logical commit groups.
 ///
 /// # Arguments
@@ -259,11 +497,29 @@ pub fn infer_body_lines(files: &[ChangedFile]) -> Vec<String> {
 ///
 /// # Algorithm
 ///
+/// Change sets that only delete files become one removal group (see
+/// [`build_removal_group`]).
+///
 /// 1. Infer commit type and scope for each file
-/// 2. Group files with identical type and scope
-/// 3. Generate descriptions and body lines for each group
+/// 2. Group files with identical type and scope, keeping files linked by
+///    [`crate::analysis::link_files`] together
+/// 3. Generate descriptions and body lines for each group; groups with more
+///    than [`LAZY_BODY_FILES`] files defer their body
 /// 4. Sort groups deterministically
 pub fn build_groups(files: Vec<ChangedFile>, ticket: Option<String>) -> Vec<ChangeGroup> {
+    build_groups_with_progress(files, ticket, |_, _| {})
+}
+
+/// Groups changed files like [`build_groups`], reporting progress.
+///
+/// `progress` is called with the number of files grouped so far and the
+/// total, every few hundred files and once at the end, so large change
+/// sets such as vendored dependency updates can show how far grouping got.
+pub fn build_groups_with_progress(
+    files: Vec<ChangedFile>,
+    ticket: Option<String>,
+    mut progress: impl FnMut(usize, usize),
+) -> Vec<ChangeGroup> {
     #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
     struct GroupKey {
         commit_type: CommitType,
@@ -272,11 +528,66 @@ pub fn build_groups(files: Vec<ChangedFile>, ticket: Option<String>) -> Vec<Chan
 
     let mut map: BTreeMap<GroupKey, Vec<ChangedFile>> = BTreeMap::new();
 
-    // Group files by type and scope
-    for file in files {
-        let commit_type = infer_commit_type(&file.path);
-        let scope = infer_scope(&file.path);
-        let key = GroupKey { commit_type, scope };
+    // Submodule pointer updates always get their own commit
+    let (submodules, files): (Vec<_>, Vec<_>) =
+        files.into_iter().partition(ChangedFile::is_submodule);
+
+    // Deleting a module is one change, however many types its files have
+    if !files.is_empty() && files.iter().all(ChangedFile::is_deleted) {
+        let mut groups: Vec<ChangeGroup> = build_removal_group(files, ticket.clone())
+            .into_iter()
+            .collect();
+        groups.extend(build_submodule_group(submodules, ticket));
+        groups.sort_by_key(|g| g.commit_type);
+        return groups;
+    }
+
+    // Group files by type and scope; changes scored as likely fixes are
+    // fixes rather than features
+    let total = files.len();
+    let keyed: Vec<(GroupKey, ChangedFile)> = files
+        .into_iter()
+        .enumerate()
+        .map(|(idx, file)| {
+            if idx > 0 && idx % PROGRESS_INTERVAL == 0 {
+                progress(idx, total);
+            }
+            let commit_type = file_commit_type(&file);
+            let scope = file_scope(&file);
+            (GroupKey { commit_type, scope }, file)
+        })
+        .collect();
+
+    // Files referencing each other stay together under the most common key
+    // of their component; ties go to the first type (feat before test),
+    // then to keys with a scope
+    let mut component_keys: HashMap<&str, BTreeMap<&GroupKey, usize>> = HashMap::new();
+    for (key, file) in &keyed {
+        if let Some(component) = &file.related_to {
+            *component_keys
+                .entry(component.as_str())
+                .or_default()
+                .entry(key)
+                .or_default() += 1;
+        }
+    }
+    let component_keys: HashMap<String, GroupKey> = component_keys
+        .into_iter()
+        .filter_map(|(component, counts)| {
+            let (key, _) = counts.into_iter().min_by_key(|(key, count)| {
+                (Reverse(*count), key.commit_type, key.scope.is_none())
+            })?;
+            Some((component.to_string(), key.clone()))
+        })
+        .collect();
+
+    for (key, file) in keyed {
+        let key = file
+            .related_to
+            .as_ref()
+            .and_then(|component| component_keys.get(component))
+            .cloned()
+            .unwrap_or(key);
         map.entry(key).or_default().push(file);
     }
 
@@ -285,18 +596,25 @@ pub fn build_groups(files: Vec<ChangedFile>, ticket: Option<String>) -> Vec<Chan
         .into_iter()
         .map(|(key, group_files)| {
             let description = infer_description(&group_files, key.commit_type, &key.scope);
-            let body_lines = infer_body_lines(&group_files);
-
-            ChangeGroup::new(
+            let mut group = ChangeGroup::new(
                 key.commit_type,
-                key.scope.clone(),
+                key.scope,
                 group_files,
                 ticket.clone(),
                 description,
-                body_lines,
-            )
+                Vec::new(),
+            );
+            if group.files.len() > LAZY_BODY_FILES {
+                group.body_pending = true;
+            } else {
+                group.body_lines = infer_body_lines(&group.files);
+            }
+            group
         })
         .collect();
+    progress(total, total);
+
+    groups.extend(build_submodule_group(submodules, ticket));
 
     // Sort by commit type for consistent ordering
     groups.sort_by_key(|g| g.commit_type);
@@ -0,0 +1,833 @@
+//! Key bindings of the terminal interface.
+//!
+//! Every view and popup has a [`Section`] of bindings. The key handlers of
+//! [`crate::ui`] look keys up here, and the help popup (`?`) lists the same
+//! sections, so the help always shows what the keys do.
+//!
+//! ```
+//! use commit_wizard::keymap::{Command, MAIN};
+//! use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+//!
+//! let key = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE);
+//! assert_eq!(MAIN.command(&key), Some(Command::Commit));
+//! ```
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// What a key does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+    /// Select the next entry or scroll down
+    Down,
+    /// Select the previous entry or scroll up
+    Up,
+    /// Scroll down a page
+    PageDown,
+    /// Scroll up a page
+    PageUp,
+    /// Go to the next step or candidate
+    Next,
+    /// Go to the previous step or candidate
+    Previous,
+    /// Focus the next panel
+    NextPanel,
+    /// Focus the previous panel
+    PreviousPanel,
+    /// Edit the commit message
+    Edit,
+    /// Edit the commit message in the external editor
+    ExternalEdit,
+    /// Generate the commit message with AI
+    GenerateMessage,
+    /// Refine the commit message with feedback
+    Refine,
+    /// Attach notes for the AI to the selected group
+    Notes,
+    /// Show the diff of the selected file
+    Diff,
+    /// Commit the selected group
+    Commit,
+    /// Commit all groups
+    CommitAll,
+    /// Skip the selected group, or leave out the selected file
+    Skip,
+    /// Leave out the selected file
+    LeaveOut,
+    /// Mark the selected entry
+    ToggleMark,
+    /// Move the marked files into the selected group
+    MoveMarked,
+    /// Start searching
+    Search,
+    /// Go to the next search match
+    NextMatch,
+    /// Go to the previous search match
+    PreviousMatch,
+    /// Fix whitespace problems
+    FixWhitespace,
+    /// Insert missing license headers
+    FixLicense,
+    /// Apply the suggested scope fix
+    FixScope,
+    /// Rename the selected group's scope in all groups
+    RenameScope,
+    /// Cycle the group sort order
+    CycleSort,
+    /// Cycle the group filter
+    CycleFilter,
+    /// Show the repository info
+    RepoInfo,
+    /// Push the current branch
+    Push,
+    /// Fetch and rebase onto the upstream
+    Pull,
+    /// Create and switch to a new branch
+    NewBranch,
+    /// Undo the last commit of the session
+    Undo,
+    /// Show the commit output of the selected group
+    CommitLog,
+    /// Show the latest commits of the branch
+    RecentCommits,
+    /// Move the selected group down in the commit order
+    MoveGroupDown,
+    /// Move the selected group up in the commit order
+    MoveGroupUp,
+    /// Stash the changes outside the groups, or restore them
+    Stash,
+    /// Re-scan the repository and group the changes again
+    Refresh,
+    /// Clear the status message
+    ClearStatus,
+    /// Toggle the help
+    Help,
+    /// Quit the wizard
+    Quit,
+    /// Switch between the diff and the whole file
+    ToggleView,
+    /// Create the offered release tag
+    Tag,
+    /// Cherry-pick the new commits onto other branches
+    CherryPick,
+    /// Accept the popup
+    Confirm,
+    /// Close the popup
+    Close,
+    /// Delete the last typed character
+    DeleteChar,
+    /// Complete the scope of the message header
+    CompleteScope,
+    /// Save the edited message
+    Save,
+    /// Discard the edited message
+    Cancel,
+}
+
+impl Command {
+    /// Returns whether the command waits while a git operation runs,
+    /// because it changes the groups or the repository.
+    pub fn waits_for_git(self) -> bool {
+        self.waits_for_ai()
+            || matches!(
+                self,
+                Command::Quit
+                    | Command::Push
+                    | Command::Pull
+                    | Command::NewBranch
+                    | Command::Undo
+                    | Command::Diff
+            )
+    }
+
+    /// Returns whether the command waits while an AI request runs, because
+    /// it changes the groups.
+    pub fn waits_for_ai(self) -> bool {
+        matches!(
+            self,
+            Command::Edit
+                | Command::ExternalEdit
+                | Command::GenerateMessage
+                | Command::Refine
+                | Command::Notes
+                | Command::Commit
+                | Command::CommitAll
+                | Command::Skip
+                | Command::LeaveOut
+                | Command::MoveMarked
+                | Command::FixWhitespace
+                | Command::FixLicense
+                | Command::FixScope
+                | Command::RenameScope
+                | Command::MoveGroupDown
+                | Command::MoveGroupUp
+                | Command::Stash
+                | Command::Refresh
+        )
+    }
+}
+
+/// A key, or a key with a modifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// A character typed without Ctrl or Alt
+    Char(char),
+    /// A character typed with Ctrl
+    Ctrl(char),
+    /// A special key, with any modifiers
+    Code(KeyCode),
+    /// A special key typed with Shift
+    Shift(KeyCode),
+}
+
+impl Key {
+    /// Returns whether `event` is this key.
+    pub fn matches(self, event: &KeyEvent) -> bool {
+        let control = event.modifiers.contains(KeyModifiers::CONTROL);
+        match self {
+            Key::Char(c) => {
+                event.code == KeyCode::Char(c)
+                    && !control
+                    && !event.modifiers.contains(KeyModifiers::ALT)
+            }
+            Key::Ctrl(c) => event.code == KeyCode::Char(c) && control,
+            Key::Code(code) => event.code == code,
+            Key::Shift(code) => event.code == code && event.modifiers.contains(KeyModifiers::SHIFT),
+        }
+    }
+
+    /// Returns the key as shown in the help, e.g. `Ctrl+L` or `↓`.
+    pub fn label(self) -> String {
+        match self {
+            Key::Char(' ') => "Space".to_string(),
+            Key::Char(c) => c.to_string(),
+            Key::Ctrl(c) => format!("Ctrl+{}", c.to_ascii_uppercase()),
+            Key::Code(KeyCode::BackTab) => "Shift+Tab".to_string(),
+            Key::Code(code) => code_label(code),
+            Key::Shift(code) => format!("Shift+{}", code_label(code)),
+        }
+    }
+}
+
+/// Returns the name of a special key.
+fn code_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Keys bound to a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    /// Keys that run the command
+    pub keys: &'static [Key],
+    /// The command
+    pub command: Command,
+    /// What the command does, for the help
+    pub description: &'static str,
+}
+
+impl Binding {
+    /// Returns the keys as shown in the help, e.g. `↓ j`.
+    pub fn label(&self) -> String {
+        let mut labels: Vec<String> = Vec::new();
+        for key in self.keys {
+            let label = key.label();
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        labels.join(" ")
+    }
+}
+
+/// The bindings of a view or popup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section {
+    /// Name of the view, for the help
+    pub title: &'static str,
+    /// Bindings, looked up in order
+    pub bindings: &'static [Binding],
+}
+
+impl Section {
+    /// Returns the command `event` runs in this view, if any.
+    pub fn command(&self, event: &KeyEvent) -> Option<Command> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.keys.iter().any(|key| key.matches(event)))
+            .map(|binding| binding.command)
+    }
+
+    /// Returns the binding of `command`.
+    pub fn binding(&self, command: Command) -> Option<&Binding> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.command == command)
+    }
+}
+
+const fn bind(keys: &'static [Key], command: Command, description: &'static str) -> Binding {
+    Binding {
+        keys,
+        command,
+        description,
+    }
+}
+
+const DOWN: &[Key] = &[Key::Code(KeyCode::Down), Key::Char('j')];
+const UP: &[Key] = &[Key::Code(KeyCode::Up), Key::Char('k')];
+const ESC: &[Key] = &[Key::Code(KeyCode::Esc)];
+const ENTER: &[Key] = &[Key::Code(KeyCode::Enter)];
+const BACKSPACE: &[Key] = &[Key::Code(KeyCode::Backspace)];
+
+/// The main view with the groups, message and files panels.
+pub const MAIN: Section = Section {
+    title: "Main view",
+    bindings: &[
+        // Before ↓ and ↑, which match Shift+↓ and Shift+↑ too
+        bind(
+            &[Key::Shift(KeyCode::Down)],
+            Command::MoveGroupDown,
+            "Move the selected group down in the commit order",
+        ),
+        bind(
+            &[Key::Shift(KeyCode::Up)],
+            Command::MoveGroupUp,
+            "Move the selected group up in the commit order",
+        ),
+        bind(
+            DOWN,
+            Command::Down,
+            "Next group, file, or scroll the message down",
+        ),
+        bind(
+            UP,
+            Command::Up,
+            "Previous group, file, or scroll the message up",
+        ),
+        // Before Tab, which matches Shift+Tab too
+        bind(
+            &[Key::Code(KeyCode::BackTab), Key::Shift(KeyCode::Tab)],
+            Command::PreviousPanel,
+            "Switch to the previous panel",
+        ),
+        bind(
+            &[Key::Code(KeyCode::Tab)],
+            Command::NextPanel,
+            "Switch to the next panel",
+        ),
+        bind(&[Key::Char('e')], Command::Edit, "Edit the commit message"),
+        bind(
+            &[Key::Char('E')],
+            Command::ExternalEdit,
+            "Edit the commit message in $VISUAL / $EDITOR",
+        ),
+        bind(
+            &[Key::Char('a')],
+            Command::GenerateMessage,
+            "Generate the commit message with AI",
+        ),
+        bind(
+            &[Key::Char('r')],
+            Command::Refine,
+            "Refine the commit message with feedback",
+        ),
+        bind(
+            &[Key::Char('s')],
+            Command::Notes,
+            "Notes for the AI on why the change was made (paste text or @file)",
+        ),
+        bind(
+            &[Key::Char('d')],
+            Command::Diff,
+            "View the diff of the selected file",
+        ),
+        bind(&[Key::Char('c')], Command::Commit, "Commit the selected group"),
+        bind(&[Key::Char('C')], Command::CommitAll, "Commit all groups"),
+        bind(
+            &[Key::Char('x')],
+            Command::Skip,
+            "Skip the selected group, or include it again; in the Files panel: leave the selected file out",
+        ),
+        bind(
+            &[Key::Code(KeyCode::Delete)],
+            Command::LeaveOut,
+            "In the Files panel: leave the selected file out",
+        ),
+        bind(
+            &[Key::Char(' ')],
+            Command::ToggleMark,
+            "In the Files panel: mark the file (x, d act on marks)",
+        ),
+        bind(
+            &[Key::Char('m')],
+            Command::MoveMarked,
+            "Move the marked files into the selected group",
+        ),
+        bind(&[Key::Char('/')], Command::Search, "Search groups and files"),
+        bind(&[Key::Char('n')], Command::NextMatch, "Next search match"),
+        bind(
+            &[Key::Char('N')],
+            Command::PreviousMatch,
+            "Previous search match",
+        ),
+        bind(
+            &[Key::Char('W')],
+            Command::FixWhitespace,
+            "Fix whitespace problems",
+        ),
+        bind(
+            &[Key::Char('H')],
+            Command::FixLicense,
+            "Insert missing license headers",
+        ),
+        bind(
+            &[Key::Char('S')],
+            Command::FixScope,
+            "Apply the suggested scope fix",
+        ),
+        bind(
+            &[Key::Char('A')],
+            Command::RenameScope,
+            "Rename the selected group's scope in all groups",
+        ),
+        bind(
+            &[Key::Char('o')],
+            Command::CycleSort,
+            "Cycle the group sort order",
+        ),
+        bind(&[Key::Char('f')], Command::CycleFilter, "Cycle the group filter"),
+        bind(
+            &[Key::Char('i')],
+            Command::RepoInfo,
+            "Show repository info and AI fallbacks",
+        ),
+        bind(&[Key::Char('P')], Command::Push, "Push the current branch"),
+        bind(
+            &[Key::Char('F')],
+            Command::Pull,
+            "Fetch the upstream and rebase onto it",
+        ),
+        bind(
+            &[Key::Char('B')],
+            Command::NewBranch,
+            "Create and switch to a new branch",
+        ),
+        bind(
+            &[Key::Char('U')],
+            Command::Undo,
+            "Undo the last commit of this session",
+        ),
+        bind(
+            &[Key::Char('O')],
+            Command::CommitLog,
+            "Show the git output of the selected group's commits",
+        ),
+        bind(
+            &[Key::Char('L')],
+            Command::RecentCommits,
+            "Show the latest commits of the branch and their files",
+        ),
+        bind(
+            &[Key::Char('Z')],
+            Command::Stash,
+            "Stash changes outside the groups / restore them",
+        ),
+        bind(
+            &[Key::Char('R')],
+            Command::Refresh,
+            "Re-scan the repository and regroup the changes",
+        ),
+        bind(
+            &[Key::Ctrl('l')],
+            Command::ClearStatus,
+            "Clear the status message",
+        ),
+        bind(&[Key::Char('?')], Command::Help, "Toggle this help"),
+        bind(
+            &[Key::Char('q'), Key::Code(KeyCode::Esc)],
+            Command::Quit,
+            "Quit; Esc first clears the search and the marks",
+        ),
+    ],
+};
+
+/// The commit message editor.
+pub const EDITOR: Section = Section {
+    title: "Commit message editor",
+    bindings: &[
+        bind(
+            &[Key::Ctrl('s')],
+            Command::Save,
+            "Save the message and close the editor",
+        ),
+        bind(
+            &[Key::Ctrl('c')],
+            Command::Cancel,
+            "Discard the changes and close the editor",
+        ),
+        bind(
+            &[Key::Code(KeyCode::Tab)],
+            Command::CompleteScope,
+            "Complete the scope of the header",
+        ),
+        bind(
+            &[Key::Char('?')],
+            Command::Help,
+            "Show the editor shortcuts",
+        ),
+    ],
+};
+
+/// The search query being typed after `/`.
+pub const SEARCH: Section = Section {
+    title: "Search",
+    bindings: &[
+        bind(
+            &[Key::Code(KeyCode::Down), Key::Code(KeyCode::Tab)],
+            Command::NextMatch,
+            "Next match",
+        ),
+        bind(
+            &[Key::Code(KeyCode::Up), Key::Code(KeyCode::BackTab)],
+            Command::PreviousMatch,
+            "Previous match",
+        ),
+        bind(
+            ENTER,
+            Command::Confirm,
+            "Keep the search and use the panels (n / N: next / previous match)",
+        ),
+        bind(BACKSPACE, Command::DeleteChar, "Delete the last character"),
+        bind(ESC, Command::Close, "Clear the search"),
+    ],
+};
+
+/// The diff viewer opened with `d`.
+pub const DIFF_VIEWER: Section = Section {
+    title: "Diff viewer",
+    bindings: &[
+        bind(DOWN, Command::Down, "Scroll down"),
+        bind(UP, Command::Up, "Scroll up"),
+        bind(
+            &[Key::Char('p')],
+            Command::ToggleView,
+            "Switch between the diff and the whole file",
+        ),
+        bind(ESC, Command::Close, "Close"),
+    ],
+};
+
+/// The output of a commit or push.
+pub const COMMIT_OUTPUT: Section = Section {
+    title: "Commit output",
+    bindings: &[
+        bind(DOWN, Command::Down, "Scroll down"),
+        bind(UP, Command::Up, "Scroll up"),
+        bind(
+            &[Key::Code(KeyCode::PageDown)],
+            Command::PageDown,
+            "Scroll down a page",
+        ),
+        bind(
+            &[Key::Code(KeyCode::PageUp)],
+            Command::PageUp,
+            "Scroll up a page",
+        ),
+        bind(
+            &[Key::Char('t')],
+            Command::Tag,
+            "Create the offered release tag",
+        ),
+        bind(
+            &[Key::Char('b')],
+            Command::CherryPick,
+            "Cherry-pick the new commits onto other branches",
+        ),
+        bind(ESC, Command::Close, "Close"),
+    ],
+};
+
+/// The status popup, once focused.
+pub const STATUS: Section = Section {
+    title: "Status popup",
+    bindings: &[
+        bind(DOWN, Command::Down, "Scroll down"),
+        bind(UP, Command::Up, "Scroll up"),
+        bind(
+            &[Key::Code(KeyCode::Esc), Key::Code(KeyCode::Enter)],
+            Command::Close,
+            "Close",
+        ),
+    ],
+};
+
+/// The branches to cherry-pick onto, opened with `b` in the commit output.
+pub const BRANCH_PICKER: Section = Section {
+    title: "Branch picker",
+    bindings: &[
+        bind(DOWN, Command::Down, "Next branch"),
+        bind(UP, Command::Up, "Previous branch"),
+        bind(
+            &[Key::Char(' ')],
+            Command::ToggleMark,
+            "Choose the branch, or not",
+        ),
+        bind(
+            ENTER,
+            Command::Confirm,
+            "Cherry-pick onto the chosen branches",
+        ),
+        bind(ESC, Command::Close, "Close"),
+    ],
+};
+
+/// The remotes to push to, when there are several.
+pub const REMOTE_PICKER: Section = Section {
+    title: "Remote picker",
+    bindings: &[
+        bind(DOWN, Command::Down, "Next remote"),
+        bind(UP, Command::Up, "Previous remote"),
+        bind(ENTER, Command::Confirm, "Push to the selected remote"),
+        bind(ESC, Command::Close, "Close"),
+    ],
+};
+
+/// The generated message candidates.
+pub const CANDIDATE_PICKER: Section = Section {
+    title: "Message candidates",
+    bindings: &[
+        bind(
+            &[
+                Key::Code(KeyCode::Right),
+                Key::Code(KeyCode::Down),
+                Key::Char('l'),
+                Key::Char('j'),
+                Key::Char('n'),
+                Key::Code(KeyCode::Tab),
+            ],
+            Command::Next,
+            "Next candidate",
+        ),
+        bind(
+            &[
+                Key::Code(KeyCode::Left),
+                Key::Code(KeyCode::Up),
+                Key::Char('h'),
+                Key::Char('k'),
+                Key::Char('p'),
+                Key::Code(KeyCode::BackTab),
+            ],
+            Command::Previous,
+            "Previous candidate",
+        ),
+        bind(ENTER, Command::Confirm, "Use the chosen message"),
+        bind(ESC, Command::Close, "Keep the current message"),
+    ],
+};
+
+/// A regenerated message compared with the current one.
+pub const MESSAGE_COMPARISON: Section = Section {
+    title: "Message comparison",
+    bindings: &[
+        bind(DOWN, Command::Down, "Scroll down"),
+        bind(UP, Command::Up, "Scroll up"),
+        bind(
+            &[Key::Code(KeyCode::Enter), Key::Char('n')],
+            Command::Confirm,
+            "Use the new message",
+        ),
+        bind(
+            &[Key::Code(KeyCode::Esc), Key::Char('o')],
+            Command::Close,
+            "Keep the current message",
+        ),
+    ],
+};
+
+/// Groups whose files changed since they were planned.
+pub const STALE_GROUPS: Section = Section {
+    title: "Stale groups",
+    bindings: &[
+        bind(
+            &[Key::Code(KeyCode::Enter), Key::Char('r')],
+            Command::Confirm,
+            "Refresh the groups",
+        ),
+        bind(ESC, Command::Close, "Cancel the commit"),
+    ],
+};
+
+/// The question before committing all groups or quitting; the answer is
+/// no unless `y` is pressed.
+pub const CONFIRMATION: Section = Section {
+    title: "Confirmation",
+    bindings: &[
+        bind(&[Key::Char('y'), Key::Char('Y')], Command::Confirm, "Yes"),
+        bind(
+            &[
+                Key::Char('n'),
+                Key::Char('N'),
+                Key::Code(KeyCode::Enter),
+                Key::Code(KeyCode::Esc),
+            ],
+            Command::Close,
+            "No",
+        ),
+    ],
+};
+
+/// The progress of committing all groups; after a failure the batch
+/// continues or stops here.
+pub const COMMIT_PROGRESS: Section = Section {
+    title: "Commit progress",
+    bindings: &[
+        bind(DOWN, Command::Down, "Scroll down"),
+        bind(UP, Command::Up, "Scroll up"),
+        bind(
+            &[Key::Code(KeyCode::Enter), Key::Char('c')],
+            Command::Confirm,
+            "Continue after a failure, or close when done",
+        ),
+        bind(
+            &[Key::Code(KeyCode::Esc), Key::Char('a')],
+            Command::Close,
+            "Abort after a failure (Esc cancels while committing)",
+        ),
+    ],
+};
+
+/// Text being typed: feedback for `r`, a branch name for `B`.
+pub const TEXT_INPUT: Section = Section {
+    title: "Feedback and branch name input",
+    bindings: &[
+        bind(ENTER, Command::Confirm, "Submit"),
+        bind(BACKSPACE, Command::DeleteChar, "Delete the last character"),
+        bind(ESC, Command::Close, "Cancel"),
+    ],
+};
+
+/// The new name of a scope being typed after `A`.
+pub const SCOPE_RENAME: Section = Section {
+    title: "Scope rename",
+    bindings: &[
+        bind(ENTER, Command::Confirm, "Rename the scope in all groups"),
+        bind(
+            &[Key::Code(KeyCode::Tab)],
+            Command::ToggleMark,
+            "Also record the rename as a scope alias",
+        ),
+        bind(BACKSPACE, Command::DeleteChar, "Delete the last character"),
+        bind(ESC, Command::Close, "Cancel"),
+    ],
+};
+
+/// The first-run tour.
+pub const TUTORIAL: Section = Section {
+    title: "Tour",
+    bindings: &[
+        bind(
+            &[
+                Key::Code(KeyCode::Enter),
+                Key::Code(KeyCode::Right),
+                Key::Char(' '),
+                Key::Char('n'),
+            ],
+            Command::Next,
+            "Next step",
+        ),
+        bind(
+            &[
+                Key::Code(KeyCode::Left),
+                Key::Code(KeyCode::Backspace),
+                Key::Char('p'),
+            ],
+            Command::Previous,
+            "Previous step",
+        ),
+        bind(
+            &[Key::Code(KeyCode::Esc), Key::Char('q')],
+            Command::Close,
+            "Close the tour",
+        ),
+    ],
+};
+
+/// This help.
+pub const HELP: Section = Section {
+    title: "Help",
+    bindings: &[
+        bind(DOWN, Command::Down, "Scroll down"),
+        bind(UP, Command::Up, "Scroll up"),
+        bind(
+            &[Key::Char('?'), Key::Code(KeyCode::Esc), Key::Char('q')],
+            Command::Close,
+            "Close",
+        ),
+    ],
+};
+
+/// The repository info opened with `i`.
+pub const REPO_INFO: Section = Section {
+    title: "Repository info",
+    bindings: &[bind(
+        &[Key::Char('i'), Key::Code(KeyCode::Esc), Key::Char('q')],
+        Command::Close,
+        "Close",
+    )],
+};
+
+/// The latest commits of the branch, listed with `L`.
+pub const RECENT_COMMITS: Section = Section {
+    title: "Recent commits",
+    bindings: &[
+        bind(DOWN, Command::Down, "Scroll down"),
+        bind(UP, Command::Up, "Scroll up"),
+        bind(
+            &[Key::Char('L'), Key::Code(KeyCode::Esc), Key::Char('q')],
+            Command::Close,
+            "Close",
+        ),
+    ],
+};
+
+/// The editor shortcuts opened with `?` in the editor.
+pub const EDITOR_HELP: Section = Section {
+    title: "Editor shortcuts",
+    bindings: &[bind(
+        &[Key::Code(KeyCode::Esc), Key::Char('?')],
+        Command::Close,
+        "Close",
+    )],
+};
+
+/// All sections, in the order the help lists them.
+pub const SECTIONS: &[&Section] = &[
+    &MAIN,
+    &SEARCH,
+    &EDITOR,
+    &EDITOR_HELP,
+    &DIFF_VIEWER,
+    &COMMIT_OUTPUT,
+    &BRANCH_PICKER,
+    &REMOTE_PICKER,
+    &CANDIDATE_PICKER,
+    &MESSAGE_COMPARISON,
+    &STALE_GROUPS,
+    &CONFIRMATION,
+    &COMMIT_PROGRESS,
+    &TEXT_INPUT,
+    &SCOPE_RENAME,
+    &STATUS,
+    &REPO_INFO,
+    &RECENT_COMMITS,
+    &TUTORIAL,
+    &HELP,
+];
@@ -9,15 +9,72 @@
     note = "Legacy HTTP API module - use `copilot` module with GitHub Copilot CLI instead"
 )]
 pub mod ai;
+pub mod analysis;
+pub mod budget;
+pub mod build_info;
+pub mod classify;
+pub mod commit_log;
+pub mod compare;
+pub mod config;
+pub mod consolidate;
+pub mod context;
 pub mod copilot;
+#[cfg(unix)]
+pub mod daemon;
+pub mod diagnostics;
+pub mod diff_store;
 pub mod editor;
+pub mod events;
+pub mod exclude;
+pub mod extension;
+pub mod fallback;
+pub mod fingerprint;
+pub mod forge;
+pub mod generated;
 pub mod git;
+pub mod highlight;
+pub mod history;
 pub mod inference;
+pub mod keymap;
+pub mod license;
+pub mod lint;
 pub mod logging;
+pub mod network;
+pub mod notes;
+pub mod outcome;
 pub mod output;
+pub mod palette;
+pub mod plugin;
+pub mod postprocess;
 pub mod progress;
+pub mod prompt;
+pub mod provider;
+pub mod release;
+pub mod remote;
+pub mod replan;
+pub mod replay;
+pub mod retry;
+pub mod rules;
+pub mod sanitize;
+pub mod scope;
+pub mod semantic;
+pub mod session;
+pub mod stash;
+pub mod subprocess;
+pub mod summary;
+pub mod theme;
+pub mod ticket;
+pub mod title;
+pub mod tutorial;
 pub mod types;
 pub mod ui;
+#[cfg(feature = "update-check")]
+pub mod update;
+pub mod validation;
+#[cfg(feature = "watch")]
+pub mod watch;
+pub mod whitespace;
+pub mod worker;
 
 // Re-export commonly used types
 pub use types::{AppState, ChangeGroup, ChangedFile, CommitType};
@@ -0,0 +1,206 @@
+//! License header checks for newly added files.
+//!
+//! When enabled in the configuration, every newly added file with a
+//! configured extension must start with the project's license header.
+//! Offending files are flagged in the TUI and the header can be inserted
+//! automatically before committing.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::Datelike;
+use git2::Repository;
+use regex::Regex;
+
+use crate::config::LicenseConfig;
+use crate::types::ChangedFile;
+
+/// Number of bytes at the start of a file searched for the header.
+const HEADER_SEARCH_WINDOW: usize = 4096;
+
+/// Compiled license header check.
+#[derive(Debug, Clone)]
+pub struct LicenseChecker {
+    template: String,
+    pattern: Regex,
+    extensions: Vec<String>,
+}
+
+impl LicenseChecker {
+    /// Creates a checker for the given header template.
+    ///
+    /// The placeholder `{year}` in the template matches any four-digit year
+    /// or year range (e.g. `2021-2025`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template produces an invalid pattern.
+    pub fn new(template: &str, extensions: Vec<String>) -> Result<Self> {
+        let template = template.trim_end().to_string();
+        let pattern = template
+            .lines()
+            .map(|line| {
+                regex::escape(line.trim_end()).replace(r"\{year\}", r"\d{4}(?:\s*-\s*\d{4})?")
+            })
+            .collect::<Vec<_>>()
+            .join(r"[ \t]*\r?\n");
+        let pattern = Regex::new(&pattern).context("Invalid license header template")?;
+
+        Ok(Self {
+            template,
+            pattern,
+            extensions,
+        })
+    }
+
+    /// Creates a checker from configuration, if the check is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template file cannot be read or is invalid.
+    pub fn from_config(config: &LicenseConfig, repo_root: &Path) -> Result<Option<Self>> {
+        match config.resolve_template(repo_root)? {
+            Some(template) => Ok(Some(Self::new(&template, config.extensions.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns whether the file at `path` must carry the header.
+    pub fn applies_to(&self, path: &str) -> bool {
+        let Some(ext) = Path::new(path).extension().and_then(|e| e.to_str()) else {
+            return false;
+        };
+        self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext))
+    }
+
+    /// Returns whether `content` contains the header near the top.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::license::LicenseChecker;
+    ///
+    /// let checker = LicenseChecker::new("// Copyright {year} ACME", vec!["rs".into()]).unwrap();
+    /// assert!(checker.has_header("// Copyright 2024 ACME\nfn main() {}\n"));
+    /// assert!(!checker.has_header("fn main() {}\n"));
+    /// ```
+    pub fn has_header(&self, content: &str) -> bool {
+        let mut end = content.len().min(HEADER_SEARCH_WINDOW);
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.pattern.is_match(&content[..end])
+    }
+
+    /// Renders the header for the current year.
+    pub fn render_header(&self) -> String {
+        let year = chrono::Local::now().year();
+        self.template.replace("{year}", &year.to_string())
+    }
+
+    /// Returns `content` with the header inserted at the top.
+    ///
+    /// A leading shebang line is kept as the first line.
+    pub fn insert_header(&self, content: &str) -> String {
+        let header = self.render_header();
+        let (shebang, rest) = match content.strip_prefix("#!") {
+            Some(_) => match content.find('\n') {
+                Some(idx) => content.split_at(idx + 1),
+                None => (content, ""),
+            },
+            None => ("", content),
+        };
+
+        let mut result = String::with_capacity(content.len() + header.len() + 2);
+        result.push_str(shebang);
+        if !shebang.is_empty() && !shebang.ends_with('\n') {
+            result.push('\n');
+        }
+        result.push_str(&header);
+        result.push('\n');
+        if !rest.is_empty() {
+            result.push('\n');
+            result.push_str(rest);
+        }
+        result
+    }
+}
+
+/// Returns whether the file is newly added (staged or untracked).
+fn is_added(file: &ChangedFile) -> bool {
+    file.status.is_index_new() || file.status.is_wt_new()
+}
+
+/// Finds newly added files that are missing the license header.
+///
+/// # Arguments
+///
+/// * `checker` - The configured license checker
+/// * `workdir` - Working directory of the repository
+/// * `files` - Changed files to inspect; only added files are checked
+///
+/// # Returns
+///
+/// Paths of the offending files. Unreadable files are skipped.
+pub fn find_missing_headers(
+    checker: &LicenseChecker,
+    workdir: &Path,
+    files: &[ChangedFile],
+) -> Vec<String> {
+    files
+        .iter()
+        .filter(|f| is_added(f) && checker.applies_to(&f.path))
+        .filter(|f| match fs::read_to_string(workdir.join(&f.path)) {
+            Ok(content) => !checker.has_header(&content),
+            Err(e) => {
+                log::debug!("Skipping license check for {}: {}", f.path, e);
+                false
+            }
+        })
+        .map(|f| f.path.clone())
+        .collect()
+}
+
+/// Inserts the license header into the given files and re-stages them.
+///
+/// Files are only re-staged if they were already present in the index, so
+/// untracked files stay untracked until the group is committed.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read or written, or the index
+/// cannot be updated.
+pub fn insert_headers(
+    checker: &LicenseChecker,
+    repo: &Repository,
+    paths: &[String],
+) -> Result<usize> {
+    let workdir = repo
+        .workdir()
+        .context("Repository has no working directory")?;
+    let mut index = repo.index().context("Failed to open index")?;
+    let mut updated = 0;
+
+    for path in paths {
+        let full_path = workdir.join(path);
+        let content = fs::read_to_string(&full_path)
+            .with_context(|| format!("Failed to read {}", full_path.display()))?;
+        if checker.has_header(&content) {
+            continue;
+        }
+
+        fs::write(&full_path, checker.insert_header(&content))
+            .with_context(|| format!("Failed to write {}", full_path.display()))?;
+        updated += 1;
+
+        if index.get_path(Path::new(path), 0).is_some() {
+            index
+                .add_path(Path::new(path))
+                .with_context(|| format!("Failed to re-stage {}", path))?;
+        }
+    }
+
+    index.write().context("Failed to write index")?;
+    Ok(updated)
+}
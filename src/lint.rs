@@ -0,0 +1,201 @@
+//! Live checks of the commit message being edited.
+//!
+//! The integrated editor shows a status line below the text with the
+//! length of the header and of the longest body line, and the problems
+//! the message has in the style of commitlint rules (`header-max-length`,
+//! `type-enum`, `scope-enum`, ...). The header is checked with the rules of
+//! `commit-wizard check-title` (see [`crate::title`]), so problems show
+//! while typing instead of after a rejected commit.
+
+use std::fmt;
+
+use crate::title::{TitleCheck, TitleRule};
+use crate::types::{ChangeGroup, CommitType};
+use crate::validation::ScopePolicy;
+
+/// Maximum recommended length of a body line.
+pub const MAX_BODY_LINE_LENGTH: usize = 72;
+
+/// How serious a problem is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The message works, but breaks a convention
+    Warning,
+    /// The commit would be rejected or come out differently
+    Error,
+}
+
+/// A problem of the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    /// Name of the broken rule, e.g. `header-max-length`
+    pub rule: &'static str,
+    /// How serious the problem is
+    pub severity: Severity,
+    /// Line of the problem, starting at 1
+    pub line: usize,
+    /// What is wrong
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "L{} {}: {}", self.line, self.rule, self.message)
+    }
+}
+
+/// The result of checking a commit message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageLint {
+    /// Length of the header in characters
+    pub header_length: usize,
+    /// Line and length of the longest body line, if there is a body
+    pub longest_body_line: Option<(usize, usize)>,
+    /// The problems, errors first, then by line
+    pub issues: Vec<LintIssue>,
+}
+
+impl MessageLint {
+    /// Checks the message `text` of a group of `commit_type`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::lint::{MessageLint, Severity};
+    /// use commit_wizard::types::CommitType;
+    /// use commit_wizard::validation::ScopePolicy;
+    ///
+    /// let policy = ScopePolicy::default();
+    /// let lint = MessageLint::check("feat(ui): add themes\n\n- Add a light theme", CommitType::Feat, &policy);
+    /// assert!(lint.issues.is_empty());
+    /// assert_eq!(lint.header_length, 20);
+    ///
+    /// let lint = MessageLint::check("feat(ui):", CommitType::Feat, &policy);
+    /// assert_eq!(lint.issues[0].rule, "subject-empty");
+    /// assert_eq!(lint.issues[0].severity, Severity::Error);
+    /// ```
+    pub fn check(text: &str, commit_type: CommitType, policy: &ScopePolicy) -> Self {
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or_default().trim();
+        let mut lint = Self {
+            header_length: header.chars().count(),
+            ..Self::default()
+        };
+
+        let title = TitleCheck::run(header, policy);
+        for problem in &title.problems {
+            // A header without a type is taken as the description
+            let (rule, severity) = match problem.rule {
+                TitleRule::Format => ("header-format", Severity::Warning),
+                TitleRule::Type => ("type-enum", Severity::Error),
+                TitleRule::Description => ("subject-empty", Severity::Error),
+                TitleRule::Scope => ("scope-enum", Severity::Error),
+            };
+            lint.issue(rule, severity, 1, problem.message.clone());
+        }
+        if let Some(type_name) = title
+            .commit_type
+            .as_deref()
+            .filter(|name| !name.eq_ignore_ascii_case(commit_type.as_str()))
+        {
+            lint.issue(
+                "type-fixed",
+                Severity::Warning,
+                1,
+                format!(
+                    "the type stays '{}': '{}' cannot be set in the editor",
+                    commit_type.as_str(),
+                    type_name
+                ),
+            );
+        }
+        if lint.header_length > ChangeGroup::MAX_HEADER_LENGTH {
+            lint.issue(
+                "header-max-length",
+                Severity::Warning,
+                1,
+                format!(
+                    "the header has {} characters and is cut at {}",
+                    lint.header_length,
+                    ChangeGroup::MAX_HEADER_LENGTH
+                ),
+            );
+        }
+
+        for (idx, line) in lines.enumerate() {
+            let number = idx + 2;
+            if number == 2 && !line.trim().is_empty() {
+                lint.issue(
+                    "body-leading-blank",
+                    Severity::Warning,
+                    number,
+                    "leave a blank line after the header".to_string(),
+                );
+            }
+            let length = line.trim_end().chars().count();
+            if length > lint.longest_body_line.map_or(0, |(_, longest)| longest) {
+                lint.longest_body_line = Some((number, length));
+            }
+            if length > MAX_BODY_LINE_LENGTH {
+                lint.issue(
+                    "body-max-line-length",
+                    Severity::Warning,
+                    number,
+                    format!(
+                        "the line has {} characters, more than {}",
+                        length, MAX_BODY_LINE_LENGTH
+                    ),
+                );
+            }
+        }
+
+        lint.issues
+            .sort_by(|a, b| b.severity.cmp(&a.severity).then(a.line.cmp(&b.line)));
+        lint
+    }
+
+    fn issue(&mut self, rule: &'static str, severity: Severity, line: usize, message: String) {
+        self.issues.push(LintIssue {
+            rule,
+            severity,
+            line,
+            message,
+        });
+    }
+
+    /// Returns the number of errors.
+    pub fn errors(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+            .count()
+    }
+
+    /// Returns the lengths for the status line, e.g.
+    /// `Header 45/72 · Body 80/72 (L4)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::lint::MessageLint;
+    /// use commit_wizard::types::CommitType;
+    /// use commit_wizard::validation::ScopePolicy;
+    ///
+    /// let lint = MessageLint::check("fix: typo\n\n- Fix it", CommitType::Fix, &ScopePolicy::default());
+    /// assert_eq!(lint.ruler(), "Header 9/72 · Body 8/72 (L3)");
+    /// ```
+    pub fn ruler(&self) -> String {
+        let mut ruler = format!(
+            "Header {}/{}",
+            self.header_length,
+            ChangeGroup::MAX_HEADER_LENGTH
+        );
+        if let Some((line, length)) = self.longest_body_line.filter(|&(_, length)| length > 0) {
+            ruler.push_str(&format!(
+                " · Body {}/{} (L{})",
+                length, MAX_BODY_LINE_LENGTH, line
+            ));
+        }
+        ruler
+    }
+}
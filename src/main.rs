@@ -25,26 +25,83 @@
 //! commit-wizard --repo /path/to/repo
 //! ```
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Instant;
 
-use anyhow::{bail, Context, Result};
+use anyhow::{Context, Result};
 use clap::Parser;
 use git2::Repository;
 
 // Use the library modules
-use commit_wizard::copilot::{build_groups_with_ai, is_ai_available};
+use commit_wizard::analysis::{
+    link_related, reference_edges, untracked_references, ReferenceScanner,
+};
+use commit_wizard::budget::{AiBudget, BudgetedProvider};
+use commit_wizard::build_info::BuildInfo;
+use commit_wizard::classify::{
+    classify_files, score_files, BlameBudget, BLAME_TIME_BUDGET, MAX_BLAMED_FILES,
+};
+use commit_wizard::commit_log::{CommitLog, LogFormat};
+use commit_wizard::config::{Config, PluginConfig};
+use commit_wizard::consolidate::merge_small_groups;
+use commit_wizard::context::{run_context_command, ContextProvider};
+use commit_wizard::diagnostics::{
+    build_report, default_report_dir, write_report, DiagnosticContext, ExchangeRecorder,
+    RecordingProvider,
+};
+use commit_wizard::diff_store::{DiffStore, BATCH_FILES};
+use commit_wizard::exclude::{ExcludingProvider, PathFilter};
+use commit_wizard::fallback::AiFallback;
+use commit_wizard::fingerprint::Snapshot;
+use commit_wizard::forge::{issue_numbers, tracker_from_config, Issue};
+use commit_wizard::generated::{
+    add_to_gitattributes, build_gitattributes_group, unmarked_generated_files, GeneratedFile,
+    ATTRIBUTES, GITATTRIBUTES,
+};
 use commit_wizard::git::{
-    collect_changed_files, collect_untracked_files, extract_ticket_from_branch, get_current_branch,
-    get_file_diff,
+    collect_changed_files, collect_diffs, collect_untracked_files, discover_repo,
+    get_current_branch, list_worktrees, BranchStatus, RepoContext, WorktreeInfo,
 };
-use commit_wizard::inference::build_groups;
+use commit_wizard::history::co_change_edges;
+use commit_wizard::inference::build_groups_with_progress;
 use commit_wizard::logging;
+use commit_wizard::network::Service;
+use commit_wizard::outcome::{Outcome, OutcomeError};
 use commit_wizard::output::print_ai_status;
+use commit_wizard::palette::Palette;
+use commit_wizard::plugin::{truncated_diffs, PluginSet, WasmPlugin};
+use commit_wizard::postprocess::PostProcessFailure;
 use commit_wizard::progress::ProgressSpinner;
-use commit_wizard::types::AppState;
+use commit_wizard::prompt::{DiffBudgetProvider, DEFAULT_DIFF_TOKENS};
+use commit_wizard::provider::{select_provider_explained, AiProvider, ProviderKind};
+use commit_wizard::remote::resolve_remotes;
+use commit_wizard::replan::{Replan, Replanner};
+use commit_wizard::replay::{CapturingProvider, Recorder};
+use commit_wizard::retry::{RetryPolicy, RetryingProvider};
+use commit_wizard::rules::PathRules;
+use commit_wizard::sanitize::{RedactingProvider, Redactor};
+use commit_wizard::scope::{ScopeResolver, ScopeVocabulary};
+use commit_wizard::semantic::{describe_deleted_files, describe_files};
+use commit_wizard::session::{session_path, SavedSession};
+use commit_wizard::stash::restore_leftover;
+use commit_wizard::subprocess;
+use commit_wizard::summary::SummaryFormat;
+use commit_wizard::theme::Theme;
+use commit_wizard::ticket::TicketExtractor;
+use commit_wizard::title::{TitleCheck, TitleFormat, TITLE_ENV};
+use commit_wizard::tutorial::Tutorial;
+use commit_wizard::types::{AppState, ChangeGroup, ChangedFile, SessionInfo};
 use commit_wizard::ui::run_tui;
+use commit_wizard::validation::ScopePolicy;
+#[cfg(feature = "watch")]
+use commit_wizard::watch::WorktreeWatcher;
+use commit_wizard::worker::AiWorker;
 
 /// Command-line interface options.
 #[derive(Parser, Debug)]
@@ -52,6 +109,7 @@ use commit_wizard::ui::run_tui;
     name = "commit-wizard",
     author,
     version,
+    disable_version_flag = true,
     about = "Interactive tool for creating conventional commits",
     long_about = "Commit Wizard helps you create well-structured commits following \
                   the Conventional Commits specification. It automatically groups \
@@ -66,10 +124,61 @@ struct Cli {
     #[arg(short, long, value_name = "PATH")]
     repo: Option<PathBuf>,
 
+    /// Additional configuration file (overrides user and repository config)
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
     /// Disable AI and use heuristic grouping (AI is enabled by default if token is available)
     #[arg(long)]
     no_ai: bool,
 
+    /// AI provider to use (overrides the `[ai]` configuration)
+    #[arg(long, value_enum, value_name = "PROVIDER")]
+    provider: Option<ProviderKind>,
+
+    /// Fail with exit code 4 instead of using heuristics when no AI provider is available or
+    /// AI grouping fails
+    #[arg(long, conflicts_with = "no_ai")]
+    require_ai: bool,
+
+    /// Send prompts to the AI without masking secrets (overrides `[redact]`)
+    #[arg(long)]
+    no_redact: bool,
+
+    /// Never start external programs: git operations use libgit2 (no hooks, signing, or
+    /// pushing), AI is limited to HTTP providers, and the context command is skipped
+    #[arg(long)]
+    no_subprocess: bool,
+
+    /// Format of the report of committed and skipped groups printed to stdout on exit
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = SummaryFormat::Text)]
+    summary: SummaryFormat,
+
+    /// Commit scopes that are not listed in `[scope] allowed` (for exceptional cases)
+    #[arg(long)]
+    allow_any_scope: bool,
+
+    /// Timeout for an operation, e.g. `commit=120` (stage, commit, diff, push, tag, ai; repeatable)
+    #[arg(long = "timeout", value_name = "OPERATION=SECONDS")]
+    timeouts: Vec<String>,
+
+    /// List uncommitted changes in all worktrees and choose which one to work in
+    #[arg(long)]
+    worktrees: bool,
+
+    /// Store every AI request and response as JSON in a new session directory under DIR,
+    /// e.g. to replay them in tests
+    #[arg(long, value_name = "DIR")]
+    record: Option<PathBuf>,
+
+    /// Watch the working tree and offer to refresh the groups when files change
+    #[arg(long)]
+    watch: bool,
+
+    /// Show the first-run tour of the panels and core keys again
+    #[arg(long)]
+    tutorial: bool,
+
     /// Enable logging to file
     #[arg(long)]
     log: bool,
@@ -81,16 +190,87 @@ struct Cli {
     /// Verbose output for debugging (also enables DEBUG log level)
     #[arg(short, long)]
     verbose: bool,
+
+    /// Print version (with --verbose: commit, features, and libgit2 version)
+    #[arg(short = 'V', long)]
+    version: bool,
+
+    /// Print build information as JSON
+    #[arg(long)]
+    version_json: bool,
 }
 
 #[derive(clap::Subcommand, Debug)]
 enum Commands {
-    // No commands currently defined
+    /// Keep the status of the repository warm so interactive runs start instantly (unix only)
+    Daemon {
+        /// Stop the daemon of the repository instead of starting one
+        #[arg(long)]
+        stop: bool,
+    },
+    /// Check a pull request title against the commit conventions and `[scope]` policy (for CI)
+    CheckTitle {
+        /// Title to check (defaults to the PR_TITLE environment variable)
+        #[arg(long)]
+        title: Option<String>,
+        /// Format of the result printed to stdout
+        #[arg(long, value_enum, value_name = "FORMAT", default_value_t = TitleFormat::Text)]
+        format: TitleFormat,
+    },
+    /// List the latest commits with the icon and color of their commit type
+    Log {
+        /// Number of commits to list
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 30)]
+        max_count: usize,
+        /// Format of the history printed to stdout
+        #[arg(long, value_enum, value_name = "FORMAT", default_value_t = LogFormat::Text)]
+        format: LogFormat,
+        /// Print without colors (also when NO_COLOR is set or stdout is not a terminal)
+        #[arg(long)]
+        no_color: bool,
+    },
 }
 
 /// Application entry point.
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+///
+/// The exit code reports the [`Outcome`] of the run, so scripts can tell
+/// e.g. "nothing to commit" from a failed commit.
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            // --help is not an error
+            return if e.use_stderr() {
+                Outcome::Usage
+            } else {
+                Outcome::Success
+            }
+            .into();
+        }
+    };
+    match run(cli) {
+        Ok(outcome) => outcome.into(),
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            Outcome::of(&e).into()
+        }
+    }
+}
+
+/// Handles the command line and runs the wizard.
+fn run(cli: Cli) -> Result<Outcome> {
+    if cli.version || cli.version_json {
+        let info = BuildInfo::current();
+        if cli.version_json {
+            println!("{}", info.to_json()?);
+        } else if cli.verbose {
+            println!("{}", info);
+        } else {
+            println!("{}", info.short());
+        }
+        return Ok(Outcome::Success);
+    }
 
     // Initialize logging
     let log_path = logging::init_logging(cli.log, cli.log_local, cli.verbose)?;
@@ -106,20 +286,256 @@ fn main() -> Result<()> {
         eprintln!("🔍 Verbose mode enabled");
     }
 
+    if cli.no_subprocess {
+        subprocess::disable();
+        if cli.verbose {
+            eprintln!("🔒 External programs disabled; using libgit2 and HTTP AI providers only");
+        }
+    }
+
     // Handle subcommands
-    if let Some(_command) = &cli.command {
-        // No commands currently defined
-        bail!("No subcommands are currently available");
+    match &cli.command {
+        Some(Commands::Daemon { stop }) => return run_daemon(cli.repo.as_deref(), *stop),
+        Some(Commands::CheckTitle { title, format }) => {
+            return run_check_title(&cli, title.clone(), *format)
+        }
+        Some(Commands::Log {
+            max_count,
+            format,
+            no_color,
+        }) => return run_log(&cli, *max_count, *format, *no_color),
+        None => {}
+    }
+
+    let mut diagnostics = DiagnosticContext {
+        log_path,
+        ..DiagnosticContext::default()
+    };
+    run_application(cli, &mut diagnostics).map_err(|e| {
+        // Expected outcomes such as a clean working tree are not bugs
+        if Outcome::of(&e) == Outcome::Error {
+            log::error!("Unexpected failure: {:#}", e);
+            offer_diagnostic_report(&e, &diagnostics);
+        }
+        e
+    })
+}
+
+/// Offers to write a diagnostic report after a failure.
+///
+/// Only asks when running interactively; any problem writing the report is
+/// printed and otherwise ignored, so the original error is still shown.
+fn offer_diagnostic_report(error: &anyhow::Error, diagnostics: &DiagnosticContext) {
+    use std::io::{stderr, stdin, IsTerminal};
+
+    if !stdin().is_terminal() || !stderr().is_terminal() {
+        return;
+    }
+
+    eprint!("\n❗ Write a diagnostic report to attach to a bug report? [y/N]: ");
+    let _ = stderr().flush();
+    let mut input = String::new();
+    if stdin().read_line(&mut input).is_err() {
+        return;
+    }
+    if !matches!(input.trim().to_lowercase().as_str(), "y" | "yes") {
+        return;
+    }
+
+    let report = build_report(error, diagnostics);
+    match write_report(&report, &default_report_dir()) {
+        Ok(path) => eprintln!(
+            "📄 Report written to {} (secrets are redacted; please review it before sharing)",
+            path.display()
+        ),
+        Err(e) => eprintln!("⚠️  {:#}", e),
+    }
+}
+
+/// Asks whether submodule pointer updates should be committed.
+///
+/// Included updates end up in their own `chore` group.
+fn prompt_submodule_inclusion(submodules: &[commit_wizard::types::ChangedFile]) -> Result<bool> {
+    use std::io::{stdin, stdout};
+
+    println!(
+        "\n◈ Found {} submodule(s) pointing to a new commit:",
+        submodules.len()
+    );
+    for file in submodules {
+        println!("  • {}", file.path);
     }
+    print!("\nCommit the submodule update(s) as a separate chore commit? [Y/n]: ");
+    stdout().flush()?;
+
+    let mut input = String::new();
+    stdin().read_line(&mut input)?;
 
-    run_application(cli)
+    match input.trim().to_lowercase().as_str() {
+        "" | "y" | "yes" => {
+            println!("✓ Including submodule update(s)");
+            Ok(true)
+        }
+        _ => {
+            println!("✓ Skipping submodule update(s)");
+            Ok(false)
+        }
+    }
+}
+
+/// Asks whether the generated files should be marked in `.gitattributes`.
+///
+/// The entries end up in their own `chore` group. Nothing is written
+/// unless the user agrees.
+fn prompt_generated_attributes(generated: &[GeneratedFile], commits: usize) -> Result<bool> {
+    use std::io::{stdin, stdout};
+
+    println!(
+        "\n🧾 Found {} generated file(s) committed again and again:",
+        generated.len()
+    );
+    for file in generated {
+        println!(
+            "  • {} ({} KiB, changed by {} of the last {} commits)",
+            file.path,
+            file.size / 1024,
+            file.commits,
+            commits
+        );
+    }
+    println!(
+        "\nMarking them `{}` in {} collapses them in reviews and keeps their diffs out of AI prompts.",
+        ATTRIBUTES, GITATTRIBUTES
+    );
+    print!("Add the entries as a separate chore commit? [y/N]: ");
+    stdout().flush()?;
+
+    let mut input = String::new();
+    stdin().read_line(&mut input)?;
+
+    match input.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(true),
+        _ => {
+            println!("✓ Leaving {} unchanged", GITATTRIBUTES);
+            Ok(false)
+        }
+    }
+}
+
+/// Asks whether the saved session should be resumed instead of grouping
+/// the changes again.
+fn prompt_session_resume(saved: &SavedSession) -> Result<bool> {
+    use std::io::{stdin, stdout};
+
+    let (groups, committed) = saved.progress();
+    println!(
+        "\n↺ Found an unfinished session on '{}': {} group(s), {} committed",
+        saved.branch, groups, committed
+    );
+    print!("Resume it with your edits? [Y/n]: ");
+    stdout().flush()?;
+
+    let mut input = String::new();
+    stdin().read_line(&mut input)?;
+
+    match input.trim().to_lowercase().as_str() {
+        "" | "y" | "yes" => {
+            println!("✓ Resuming the saved session");
+            Ok(true)
+        }
+        _ => {
+            println!("✓ Starting over");
+            Ok(false)
+        }
+    }
+}
+
+/// Lists all worktrees with their pending changes and asks which one to use.
+///
+/// Returns the path of the chosen worktree, or `None` to stay in the current
+/// one.
+fn prompt_worktree_selection(worktrees: &[WorktreeInfo]) -> Result<Option<PathBuf>> {
+    use std::io::{stdin, stdout};
+
+    println!("\n🌳 Worktrees:");
+    for (idx, worktree) in worktrees.iter().enumerate() {
+        println!(
+            "  {}. {} {} — {} ({} change(s))",
+            idx + 1,
+            if worktree.is_current { "*" } else { " " },
+            worktree.label(),
+            worktree.path.display(),
+            worktree.changes
+        );
+    }
+    print!("\nWork in which worktree? [current]: ");
+    stdout().flush()?;
+
+    let mut input = String::new();
+    stdin().read_line(&mut input)?;
+
+    let selected = input
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|idx| worktrees.get(idx));
+    match selected {
+        Some(worktree) if !worktree.is_current => {
+            println!("✓ Switching to {}", worktree.path.display());
+            Ok(Some(worktree.path.clone()))
+        }
+        _ => {
+            println!("✓ Staying in the current worktree");
+            Ok(None)
+        }
+    }
+}
+
+/// Finds the untracked files the tracked changes start to reference, e.g.
+/// the module file of a new `mod` declaration.
+///
+/// Returns the referenced untracked paths with the tracked files
+/// referencing them; empty if the diffs cannot be read.
+fn referenced_untracked(
+    repo: &Repository,
+    changed_files: &[commit_wizard::types::ChangedFile],
+    untracked: &[commit_wizard::types::ChangedFile],
+) -> BTreeMap<String, Vec<String>> {
+    let scanned: Vec<_> = changed_files
+        .iter()
+        .filter(|f| !f.is_submodule() && ReferenceScanner::scans(&f.path))
+        .cloned()
+        .collect();
+    if scanned.is_empty() {
+        return BTreeMap::new();
+    }
+    let diffs = match collect_diffs(repo, &scanned) {
+        Ok(diffs) => diffs,
+        Err(e) => {
+            log::warn!("Failed to read diffs for untracked references: {:#}", e);
+            return BTreeMap::new();
+        }
+    };
+    let untracked: HashSet<String> = untracked.iter().map(|f| f.path.clone()).collect();
+    untracked_references(
+        diffs
+            .iter()
+            .map(|(path, diff)| (path.as_str(), diff.patch.as_str())),
+        &untracked,
+    )
 }
 
 /// Prompts user to select which untracked files to include.
 ///
+/// Untracked files in `references` are referenced by the tracked changes;
+/// if the user leaves any of them out, they are asked again whether to
+/// include them, since the commit would not work without them.
+///
 /// Returns the list of selected untracked files.
 fn prompt_untracked_files_selection(
     untracked: Vec<commit_wizard::types::ChangedFile>,
+    references: &BTreeMap<String, Vec<String>>,
 ) -> Result<Vec<commit_wizard::types::ChangedFile>> {
     use std::io::{stdin, stdout};
 
@@ -132,7 +548,15 @@ fn prompt_untracked_files_selection(
         untracked.len()
     );
     for (idx, file) in untracked.iter().enumerate() {
-        println!("  {}. {}", idx + 1, file.path);
+        match references.get(&file.path) {
+            Some(sources) => println!(
+                "  {}. {} ← referenced by {}",
+                idx + 1,
+                file.path,
+                sources.join(", ")
+            ),
+            None => println!("  {}. {}", idx + 1, file.path),
+        }
     }
 
     println!("\nOptions:");
@@ -146,14 +570,14 @@ fn prompt_untracked_files_selection(
     stdin().read_line(&mut input)?;
     let choice = input.trim().to_lowercase();
 
-    match choice.as_str() {
+    let mut selected = match choice.as_str() {
         "" | "a" | "all" => {
             println!("✓ Including all {} untracked files", untracked.len());
-            Ok(untracked)
+            return Ok(untracked);
         }
         "n" | "none" => {
             println!("✓ Excluding all untracked files");
-            Ok(vec![])
+            vec![]
         }
         "s" | "select" => {
             println!("\nEnter file numbers to include (comma-separated, e.g., 1,3,5):");
@@ -173,7 +597,7 @@ fn prompt_untracked_files_selection(
 
             if selected_indices.is_empty() {
                 println!("⚠ No valid selections, including all files");
-                Ok(untracked)
+                return Ok(untracked);
             } else {
                 let selected: Vec<_> = selected_indices
                     .into_iter()
@@ -185,18 +609,167 @@ fn prompt_untracked_files_selection(
                     println!("  • {}", file.path);
                 }
 
-                Ok(selected)
+                selected
             }
         }
         _ => {
             println!("⚠ Invalid choice, defaulting to include all");
-            Ok(untracked)
+            return Ok(untracked);
         }
+    };
+
+    let declined: Vec<_> = untracked
+        .iter()
+        .filter(|f| references.contains_key(&f.path) && !selected.iter().any(|s| s.path == f.path))
+        .collect();
+    if declined.is_empty() {
+        return Ok(selected);
+    }
+
+    println!("\n⚠ The changes reference untracked files you left out:");
+    for file in &declined {
+        println!("  • {} ← {}", file.path, references[&file.path].join(", "));
+    }
+    print!("Include them too? [Y/n]: ");
+    stdout().flush()?;
+
+    let mut answer = String::new();
+    stdin().read_line(&mut answer)?;
+    if answer.trim().eq_ignore_ascii_case("n") {
+        println!("✓ Leaving them out");
+    } else {
+        println!("✓ Including {} referenced file(s)", declined.len());
+        selected.extend(declined.into_iter().cloned());
+    }
+    Ok(selected)
+}
+
+/// Runs `commit-wizard check-title`: checks a pull request title with the
+/// `[scope]` policy of the repository and prints the result.
+///
+/// Exits with [`Outcome::ValidationFailed`] if the title breaks a rule.
+fn run_check_title(cli: &Cli, title: Option<String>, format: TitleFormat) -> Result<Outcome> {
+    let Some(title) = title.or_else(|| env::var(TITLE_ENV).ok()) else {
+        return Err(OutcomeError::new(
+            Outcome::Usage,
+            format!("No title to check: pass --title or set {}", TITLE_ENV),
+        )
+        .into());
+    };
+
+    // Outside a repository only the user configuration applies
+    let path = match &cli.repo {
+        Some(path) => path.clone(),
+        None => env::current_dir().context("Failed to get current directory")?,
+    };
+    let root = discover_repo(&path).map_or(path, |ctx| ctx.workdir);
+    let mut config = Config::load(&root, cli.config.as_deref())?;
+    if cli.allow_any_scope {
+        config.scope.allowed.clear();
     }
+    let policy = ScopePolicy::from_config(&config.scope)?;
+
+    let check = TitleCheck::run(&title, &policy);
+    println!("{}", check.render(format)?);
+    Ok(if check.valid {
+        Outcome::Success
+    } else {
+        Outcome::ValidationFailed
+    })
+}
+
+/// Runs `commit-wizard log`: prints the latest commits annotated by type.
+fn run_log(cli: &Cli, max_count: usize, format: LogFormat, no_color: bool) -> Result<Outcome> {
+    use std::io::{stdout, IsTerminal};
+
+    let path = match &cli.repo {
+        Some(path) => path.clone(),
+        None => env::current_dir().context("Failed to get current directory")?,
+    };
+    let ctx = discover_repo(&path)?;
+    let config = Config::load(&ctx.workdir, cli.config.as_deref())?;
+
+    let colored = !no_color && env::var_os("NO_COLOR").is_none() && stdout().is_terminal();
+    let palette = if colored {
+        Some(Palette::for_theme(config.ui.theme, &config.colors)?)
+    } else {
+        None
+    };
+    let log = CommitLog::read(&ctx.open()?, max_count)?;
+    println!("{}", log.render(format, palette.as_ref())?);
+    Ok(Outcome::Success)
+}
+
+/// Runs `commit-wizard daemon` in the foreground, or stops the running
+/// daemon with `--stop`.
+#[cfg(unix)]
+fn run_daemon(repo: Option<&Path>, stop: bool) -> Result<Outcome> {
+    use commit_wizard::daemon::{self, Daemon};
+
+    let repo_path = match repo {
+        Some(path) => path.to_path_buf(),
+        None => env::current_dir().context("Failed to get current directory")?,
+    };
+    let ctx = discover_repo(&repo_path)?;
+    if stop {
+        if daemon::stop(&ctx)? {
+            println!("✓ Daemon stopped");
+        } else {
+            println!("ℹ No daemon runs for {}", ctx.workdir.display());
+        }
+        return Ok(Outcome::Success);
+    }
+
+    let daemon = Daemon::bind(&ctx)?;
+    println!(
+        "✓ Serving the status of {} on {} (Ctrl+C or `commit-wizard daemon --stop` ends it)",
+        ctx.workdir.display(),
+        daemon.socket().display()
+    );
+    daemon.serve()?;
+    Ok(Outcome::Success)
+}
+
+#[cfg(not(unix))]
+fn run_daemon(_repo: Option<&Path>, _stop: bool) -> Result<Outcome> {
+    anyhow::bail!("The daemon needs unix sockets, which this platform does not provide")
+}
+
+/// Collects the changed tracked and the untracked files, from the daemon
+/// if one runs for the worktree.
+#[cfg(unix)]
+fn collect_status(
+    ctx: &RepoContext,
+    repo: &Repository,
+) -> Result<(Vec<ChangedFile>, Vec<ChangedFile>)> {
+    match commit_wizard::daemon::query_status(ctx) {
+        Ok(Some(status)) => {
+            log::info!("Collected the status from the daemon");
+            return Ok((status.tracked, status.untracked));
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Collecting the status without the daemon: {:#}", e),
+    }
+    Ok((
+        collect_changed_files(repo, false)?,
+        collect_untracked_files(repo)?,
+    ))
+}
+
+#[cfg(not(unix))]
+fn collect_status(
+    _ctx: &RepoContext,
+    repo: &Repository,
+) -> Result<(Vec<ChangedFile>, Vec<ChangedFile>)> {
+    Ok((
+        collect_changed_files(repo, false)?,
+        collect_untracked_files(repo)?,
+    ))
 }
 
 /// Runs the main application logic.
-fn run_application(cli: Cli) -> Result<()> {
+fn run_application(cli: Cli, diagnostics: &mut DiagnosticContext) -> Result<Outcome> {
+    let session_started = Instant::now();
     // Determine repository path
     let repo_path = cli
         .repo
@@ -206,17 +779,90 @@ fn run_application(cli: Cli) -> Result<()> {
         eprintln!("📂 Repository path: {}", repo_path.display());
     }
 
-    // Open repository
-    let repo = Repository::open(&repo_path).with_context(|| {
+    // Discover repository (parent directories, worktrees, GIT_DIR/GIT_WORK_TREE)
+    let mut ctx = discover_repo(&repo_path).with_context(|| {
         log::error!("Failed to open repository: {}", repo_path.display());
         format!(
-            "Not a git repository: {}\n\
+            "No usable git repository at {}\n\
              Hint: Run this command from inside a git repository or use --repo <path>",
             repo_path.display()
         )
     })?;
 
-    log::info!("Opened repository: {}", repo_path.display());
+    if cli.worktrees {
+        let worktrees = list_worktrees(&ctx)?;
+        if let Some(path) = prompt_worktree_selection(&worktrees)? {
+            // An explicit choice replaces a location given by the environment
+            env::remove_var("GIT_DIR");
+            env::remove_var("GIT_WORK_TREE");
+            ctx = discover_repo(&path)?;
+        }
+    }
+    let repo = ctx.open()?;
+
+    // Git subprocesses run in the working tree, so pass the resolved
+    // locations as absolute paths in case they came from the environment
+    if env::var_os("GIT_DIR").is_some() {
+        env::set_var("GIT_DIR", &ctx.git_dir);
+        env::set_var("GIT_WORK_TREE", &ctx.workdir);
+    }
+    let repo_path = ctx.workdir.clone();
+
+    log::info!(
+        "Opened repository: {} (git dir: {}{})",
+        repo_path.display(),
+        ctx.git_dir.display(),
+        if ctx.is_worktree {
+            ", linked worktree"
+        } else {
+            ""
+        }
+    );
+    if cli.verbose && ctx.is_worktree {
+        eprintln!("🌳 Linked worktree of {}", ctx.common_dir.display());
+    }
+
+    // Load layered configuration (user, repository, --config)
+    let mut config = Config::load(&repo_path, cli.config.as_deref())?;
+    for spec in &cli.timeouts {
+        config.apply_timeout_override(spec)?;
+    }
+    if cli.allow_any_scope && !config.scope.allowed.is_empty() {
+        log::info!("--allow-any-scope: ignoring [scope] allowed");
+        config.scope.allowed.clear();
+    }
+    log::debug!("Loaded configuration: {:?}", config);
+
+    // A session that was killed may have left its stash behind
+    match restore_leftover(&repo_path, &config.timeouts) {
+        Ok(true) => eprintln!("ℹ️  Restored the changes stashed by an interrupted session"),
+        Ok(false) => {}
+        Err(e) => {
+            logging::log_error("Failed to restore the stash of an interrupted session", &e);
+            eprintln!(
+                "⚠️  Could not restore the changes stashed by an interrupted session: {:#}\n   They are kept in the stash; restore them with `git stash pop`",
+                e
+            );
+        }
+    }
+    diagnostics.config = Some(format!("{:#?}", config));
+    let palette = Palette::for_theme(config.ui.theme, &config.colors)?;
+    let theme = Theme::from_config(config.ui.theme, &config.ui.colors)?;
+
+    // Opt-in; answers are cached, so GitHub is asked at most once a week
+    #[cfg(feature = "update-check")]
+    let update_hint = commit_wizard::update::check_for_update(
+        &config.update,
+        &config.network.for_service(Service::Update),
+    );
+    #[cfg(not(feature = "update-check"))]
+    let update_hint: Option<String> = None;
+    if let Some(hint) = &update_hint {
+        log::info!("{}", hint);
+        if cli.verbose {
+            eprintln!("⬆ {}", hint);
+        }
+    }
 
     // Get branch and extract ticket
     let branch = get_current_branch(&repo)?;
@@ -226,7 +872,11 @@ fn run_application(cli: Cli) -> Result<()> {
         eprintln!("🌿 Current branch: {}", branch);
     }
 
-    let ticket = extract_ticket_from_branch(&branch);
+    // Show which checkout is active when the repository has several worktrees
+    let has_worktrees = ctx.is_worktree || repo.worktrees().is_ok_and(|w| !w.is_empty());
+    let worktree_label = has_worktrees.then(|| format!("{} @ {}", branch, repo_path.display()));
+
+    let ticket = TicketExtractor::from_config(&config.ticket)?.ticket_for(&branch);
     if let Some(ref t) = ticket {
         log::info!("Detected ticket: {}", t);
         if cli.verbose {
@@ -241,38 +891,181 @@ fn run_application(cli: Cli) -> Result<()> {
 
     // Step 1: Collect changed files (staged and unstaged, excluding untracked)
     let spinner = ProgressSpinner::new("Collecting changed files...", 1, 4);
-    let mut changed_files = collect_changed_files(&repo, false)?;
+    let (mut changed_files, untracked_files) = collect_status(&ctx, &repo)?;
     log::info!("Collected {} changed files (tracked)", changed_files.len());
     spinner.stop();
 
-    // Step 1a: Check for untracked files and prompt user
-    let untracked_files = collect_untracked_files(&repo)?;
+    // Step 1a: Check for untracked files and prompt user; refreshing the
+    // groups later leaves out the ones declined here
+    let mut declined_untracked: HashSet<String> =
+        untracked_files.iter().map(|f| f.path.clone()).collect();
     if !untracked_files.is_empty() {
         log::info!("Found {} untracked files", untracked_files.len());
 
-        // Interactive selection for untracked files
-        let selected_untracked = prompt_untracked_files_selection(untracked_files)?;
+        // Interactive selection for untracked files; the ones the tracked
+        // changes reference are pointed out
+        let references = referenced_untracked(&repo, &changed_files, &untracked_files);
+        let selected_untracked = prompt_untracked_files_selection(untracked_files, &references)?;
 
         if !selected_untracked.is_empty() {
             log::info!("User selected {} untracked files", selected_untracked.len());
+            for file in &selected_untracked {
+                declined_untracked.remove(&file.path);
+            }
             changed_files.extend(selected_untracked);
         } else {
             log::info!("User excluded all untracked files");
         }
     }
 
+    // Step 1b: Offer to commit submodule pointer updates separately
+    let mut include_submodules = true;
+    if changed_files.iter().any(|f| f.is_submodule()) {
+        let (submodules, files): (Vec<_>, Vec<_>) =
+            changed_files.into_iter().partition(|f| f.is_submodule());
+        changed_files = files;
+        if prompt_submodule_inclusion(&submodules)? {
+            log::info!("Including {} submodule update(s)", submodules.len());
+            changed_files.extend(submodules);
+        } else {
+            log::info!("User excluded submodule updates");
+            include_submodules = false;
+        }
+    }
+
     if cli.verbose {
         eprintln!("📋 Found {} changed file(s)", changed_files.len());
     }
 
     // Prevent continuing when there are no changed files to process
     if changed_files.is_empty() {
-        bail!("No changed files detected. Stage or modify files before running commit-wizard.");
+        return Err(OutcomeError::new(
+            Outcome::NothingToCommit,
+            "No changed files detected. Stage or modify files before running commit-wizard.",
+        )
+        .into());
     }
+    // Prefer the scopes earlier commits used
+    let vocabulary = if config.scope.history_commits > 0 {
+        ScopeVocabulary::mine(&repo, config.scope.history_commits, config.scope.min_uses)
+            .unwrap_or_else(|e| {
+                logging::log_error("Failed to learn scopes from the commit history", &e);
+                ScopeVocabulary::default()
+            })
+    } else {
+        ScopeVocabulary::default()
+    };
+    // Diffs are loaded when needed and cached up to `[diff] cache_mb`; the
+    // analysis, prompts and the diff viewer share them
+    let diff_store = Arc::new(DiffStore::new(ctx.clone(), config.diff.cache_bytes()));
+    let snapshot = analyze_changes(
+        &repo,
+        &repo_path,
+        &config,
+        &branch,
+        &vocabulary,
+        &diff_store,
+        &mut changed_files,
+    )?;
+
+    // Offer to continue an unfinished session whose plan still matches
+    let mut orphaned_files = Vec::new();
+    let resumed = match SavedSession::load(&session_path(&ctx.git_dir)) {
+        Ok(Some(saved)) => {
+            let head = repo.head().ok().and_then(|head| head.target());
+            match saved.matches(&ctx.workdir, &branch, head, &changed_files) {
+                Ok(true) if prompt_session_resume(&saved)? => {
+                    orphaned_files = saved.restore_orphaned(&changed_files);
+                    Some(saved.restore(std::mem::take(&mut changed_files)))
+                }
+                Ok(true) => None,
+                Ok(false) => {
+                    log::info!("The saved session no longer matches the working tree");
+                    None
+                }
+                Err(e) => {
+                    logging::log_error("Failed to check the saved session", &e);
+                    None
+                }
+            }
+        }
+        Ok(None) => None,
+        Err(e) => {
+            logging::log_error("Failed to read the saved session", &e);
+            None
+        }
+    };
+
+    // Offer to mark generated files that keep being committed; the entries
+    // are committed as a chore group of their own
+    let mut attributes_group = None;
+    if resumed.is_none() && changed_files.iter().all(|f| f.path != GITATTRIBUTES) {
+        let generated = unmarked_generated_files(&repo, &changed_files, &config.generated)
+            .unwrap_or_else(|e| {
+                logging::log_error("Failed to look for generated files", &e);
+                Vec::new()
+            });
+        if !generated.is_empty()
+            && prompt_generated_attributes(&generated, config.generated.commits)?
+        {
+            let attributes = add_to_gitattributes(&ctx.workdir, &generated)?;
+            println!(
+                "✓ Marked {} file(s) as generated in {}",
+                generated.len(),
+                GITATTRIBUTES
+            );
+            attributes_group = Some(build_gitattributes_group(
+                attributes,
+                &generated,
+                ticket.clone(),
+            ));
+        }
+    }
+
     // Step 2: Determine if AI should be used
     let spinner = ProgressSpinner::new("Checking AI availability...", 2, 4);
-    let ai_available = is_ai_available();
-    let use_ai = !cli.no_ai && ai_available;
+    let provider_kind = cli.provider.unwrap_or(config.ai.provider);
+    let mut ai_fallbacks = Vec::new();
+    let provider = if cli.no_ai {
+        None
+    } else {
+        match select_provider_explained(provider_kind, &config.ai, &config.network) {
+            Ok(provider) => Some(provider),
+            Err(reasons) => {
+                for reason in &reasons {
+                    log::info!("AI provider unavailable: {}", reason);
+                }
+                ai_fallbacks.push(AiFallback::unavailable(&reasons));
+                None
+            }
+        }
+    };
+    // Retry rate limits and server errors before any other wrapper sees
+    // the failure, so budgets and diagnostics count one request
+    let provider = match provider {
+        Some(provider) if config.ai.retries > 0 => Some(Box::new(RetryingProvider::new(
+            provider,
+            RetryPolicy::from_config(&config.ai),
+        )) as Box<dyn AiProvider>),
+        provider => provider,
+    };
+    let ai_available = provider.is_some();
+    if cli.require_ai && !ai_available {
+        spinner.stop();
+        let reasons = ai_fallbacks
+            .first()
+            .map(|fallback| format!(": {}", fallback.detail))
+            .unwrap_or_default();
+        return Err(OutcomeError::new(
+            Outcome::AiUnavailable,
+            format!(
+                "No AI provider is available and --require-ai was given{}",
+                reasons
+            ),
+        )
+        .into());
+    }
+    let use_ai = ai_available;
     spinner.stop();
 
     log::info!(
@@ -282,64 +1075,515 @@ fn run_application(cli: Cli) -> Result<()> {
         cli.no_ai
     );
     print_ai_status(cli.verbose, use_ai, cli.no_ai, ai_available);
+    if let Some(provider) = &provider {
+        log::info!("AI provider: {}", provider.name());
+        if cli.verbose {
+            eprintln!("🔌 AI provider: {}", provider.name());
+        }
+    }
 
-    // Step 3: Build commit groups (AI-first approach)
-    let spinner = ProgressSpinner::new("Creating commit groups...", 3, 4);
-    let groups = if use_ai {
-        // Collect diffs for AI context
-        let mut diffs = std::collections::HashMap::new();
-        for file in &changed_files {
-            if let Ok(diff) = get_file_diff(&repo, &file.path) {
-                diffs.insert(file.path.clone(), diff);
+    // Keep the last exchange for diagnostic reports if the user allows it
+    let provider = match provider {
+        Some(provider) if config.diagnostics.include_prompts => {
+            let recorder = ExchangeRecorder::new();
+            diagnostics.exchange = Some(recorder.clone());
+            Some(Box::new(RecordingProvider::new(provider, recorder)) as Box<dyn AiProvider>)
+        }
+        provider => provider,
+    };
+
+    // Write every exchange to the recording; wrapped inside the redactor
+    // like the diagnostics recorder, so recordings never contain secrets
+    let provider = match (provider, &cli.record) {
+        (Some(provider), Some(root)) => {
+            let recorder = Recorder::create(root)?;
+            log::info!("Recording AI exchanges to {}", recorder.dir().display());
+            if cli.verbose {
+                eprintln!("⏺ Recording AI exchanges to {}", recorder.dir().display());
             }
+            Some(Box::new(CapturingProvider::new(provider, recorder)) as Box<dyn AiProvider>)
+        }
+        (provider, _) => provider,
+    };
+
+    // Mask secrets in diffs and context output before prompts leave the
+    // machine; wrapped outside the recorder so reports never contain them
+    let provider = match provider {
+        Some(provider) if config.redact.enabled && !cli.no_redact => {
+            let redactor = Redactor::new(&config.redact)?;
+            Some(Box::new(RedactingProvider::new(provider, redactor)) as Box<dyn AiProvider>)
+        }
+        Some(provider) => {
+            log::warn!("Secret redaction is disabled; prompts are sent unmodified");
+            Some(provider)
         }
+        None => None,
+    };
 
-        match build_groups_with_ai(changed_files.clone(), ticket.clone(), diffs) {
-            Ok(ai_groups) => {
-                log::info!("AI grouping successful: {} groups created", ai_groups.len());
-                logging::log_grouping_result(changed_files.len(), ai_groups.len(), true);
-                spinner.stop();
-                if cli.verbose {
-                    eprintln!("✨ AI created {} commit group(s)", ai_groups.len());
-                }
-                ai_groups
+    if !config.message.post_process.is_empty() && subprocess::is_disabled() {
+        log::warn!("Message post-processors cannot run: external programs are disabled");
+        eprintln!(
+            "⚠️  Message post-processors cannot run with --no-subprocess (post_process_failure = \"{}\")",
+            match config.message.post_process_failure {
+                PostProcessFailure::Block => "block: commits fail",
+                PostProcessFailure::Keep => "keep: messages are committed unchanged",
             }
+        );
+    }
+
+    let plugins = if config.plugins.wasm.is_empty() {
+        PluginSet::default()
+    } else {
+        PluginSet::load(&config.plugins, &ctx.workdir).unwrap_or_else(|e| {
+            logging::log_error("Failed to load plugins", &e);
+            eprintln!("⚠️  {:#}\n   Continuing without plugins", e);
+            PluginSet::default()
+        })
+    };
+
+    // Run the configured context command; its output feeds the AI prompts
+    // and failures are shown as a warning before committing
+    let command_context = if config.context.command.is_some() && subprocess::is_disabled() {
+        log::warn!("Context command skipped: external programs are disabled");
+        if cli.verbose {
+            eprintln!("🧪 Context command skipped (--no-subprocess)");
+        }
+        None
+    } else if config.context.command.is_some() {
+        let spinner = ProgressSpinner::new("Running context command...", 2, 4);
+        let result = run_context_command(&config.context, &ctx.workdir);
+        spinner.stop();
+        match result {
+            Ok(command_context) => command_context,
             Err(e) => {
-                logging::log_error("AI grouping failed", &e);
-                log::warn!("Falling back to heuristic grouping");
-                spinner.stop();
-                if cli.verbose {
-                    eprintln!("⚠️  AI grouping failed: {}", e);
-                    eprintln!("🔄 Falling back to heuristic grouping");
+                logging::log_error("Context command failed to start", &e);
+                eprintln!("⚠️  {:#}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    if let Some(command_context) = &command_context {
+        if cli.verbose {
+            eprintln!(
+                "🧪 Context command `{}` {}",
+                command_context.command,
+                command_context.status_text()
+            );
+        }
+    }
+    let provider = match (provider, &command_context) {
+        (Some(provider), Some(command_context)) => {
+            Some(Box::new(ContextProvider::new(provider, command_context)) as Box<dyn AiProvider>)
+        }
+        (provider, _) => provider,
+    };
+
+    // Look up issues named by the branch; their titles and descriptions
+    // feed the AI prompts and optionally become `Closes` footers
+    let issues = match &ticket {
+        Some(ticket) if !issue_numbers(ticket).is_empty() => {
+            let remote_url = resolve_remotes(&repo, &branch, &config.remote)
+                .ok()
+                .and_then(|targets| targets.pr_base)
+                .and_then(|name| {
+                    repo.find_remote(&name)
+                        .ok()
+                        .and_then(|remote| remote.url().map(str::to_string))
+                });
+            let network = config.network.for_service(Service::Forge);
+            match tracker_from_config(&config.forge, remote_url.as_deref(), &network) {
+                Some(tracker) => {
+                    let spinner = ProgressSpinner::new("Looking up issues...", 2, 4);
+                    let mut issues = Vec::new();
+                    for number in issue_numbers(ticket) {
+                        match tracker.issue(number) {
+                            Ok(issue) => issues.push(issue),
+                            Err(e) => {
+                                let context = format!(
+                                    "Failed to look up issue #{} on {}",
+                                    number,
+                                    tracker.name()
+                                );
+                                logging::log_error(&context, &e);
+                            }
+                        }
+                    }
+                    spinner.stop();
+                    issues
                 }
-                let heuristic_groups = build_groups(changed_files, ticket);
-                logging::log_grouping_result(
-                    heuristic_groups.iter().map(|g| g.files.len()).sum(),
-                    heuristic_groups.len(),
-                    false,
-                );
-                heuristic_groups
+                None => Vec::new(),
             }
         }
+        _ => Vec::new(),
+    };
+    for issue in &issues {
+        log::info!("Found issue {}: {}", issue.reference(), issue.title);
+        if cli.verbose {
+            eprintln!("🔗 Issue {}: {}", issue.reference(), issue.title);
+        }
+    }
+    let provider = match provider {
+        Some(provider) if !issues.is_empty() => {
+            let section = issues
+                .iter()
+                .map(Issue::prompt_section)
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some(Box::new(ContextProvider::with_section(provider, section)) as Box<dyn AiProvider>)
+        }
+        provider => provider,
+    };
+    let issue_footers: Vec<String> = if config.forge.closes_footer {
+        issues.iter().map(Issue::footer).collect()
     } else {
-        let heuristic_groups = build_groups(changed_files, ticket);
-        logging::log_grouping_result(
-            heuristic_groups.iter().map(|g| g.files.len()).sum(),
-            heuristic_groups.len(),
-            false,
+        Vec::new()
+    };
+
+    let provider = match (provider, AiBudget::from_config(&config.ai)) {
+        (Some(provider), Some(budget)) => {
+            log::info!("AI budget: {:?}", budget);
+            Some(Box::new(BudgetedProvider::new(provider, Arc::new(budget))) as Box<dyn AiProvider>)
+        }
+        (provider, _) => provider,
+    };
+    let provider = match provider {
+        Some(provider) if config.ai.diff_tokens != DEFAULT_DIFF_TOKENS => Some(Box::new(
+            DiffBudgetProvider::new(provider, config.ai.diff_tokens),
+        )
+            as Box<dyn AiProvider>),
+        provider => provider,
+    };
+    let provider = match provider {
+        Some(provider) if !config.ai.exclude.is_empty() => {
+            let filter = PathFilter::new(&config.ai.exclude)?;
+            Some(Box::new(ExcludingProvider::new(provider, filter)) as Box<dyn AiProvider>)
+        }
+        provider => provider,
+    };
+
+    // Step 3: Build heuristic commit groups; AI grouping replaces them in the
+    // background once it finishes. With --require-ai there is no heuristic
+    // fallback: the AI groups are awaited and a failure ends the run.
+    let provider: Option<Arc<dyn AiProvider>> = provider.map(Arc::from);
+    // The grouping prompt holds at most the provider's diff budget, so
+    // larger patches are condensed before they are handed over
+    let grouping_patches = |provider: &Arc<dyn AiProvider>, files: &[ChangedFile]| {
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        diff_store
+            .condensed_patches(&paths, provider.diff_token_budget())
+            .unwrap_or_else(|e| {
+                logging::log_error("Failed to collect diffs", &e);
+                HashMap::new()
+            })
+    };
+    // Groups below the configured minimum size are merged into related ones
+    let consolidate = |groups: Vec<ChangeGroup>| {
+        let (groups, merged) = merge_small_groups(groups, &config.grouping);
+        if merged > 0 {
+            log::info!("Merged {} small commit group(s)", merged);
+            if cli.verbose {
+                eprintln!("🧩 Merged {} small commit group(s)", merged);
+            }
+        }
+        groups
+    };
+    let (mut groups, worker) = match (resumed, &provider) {
+        (Some(groups), _) => {
+            log::info!("Resumed session: {} commit groups", groups.len());
+            (groups, None)
+        }
+        (None, Some(provider)) if cli.require_ai => {
+            let spinner = ProgressSpinner::new("Creating commit groups with AI...", 3, 4);
+            let patches = grouping_patches(provider, &changed_files);
+            let result = provider.group_files(changed_files, ticket.clone(), &patches);
+            spinner.stop();
+            let groups = result.map_err(|e| {
+                logging::log_error("AI grouping failed", &e);
+                OutcomeError::new(
+                    Outcome::AiUnavailable,
+                    format!("AI grouping failed and --require-ai was given: {:#}", e),
+                )
+            })?;
+            let groups = consolidate(groups);
+            logging::log_grouping_result(
+                groups.iter().map(|g| g.files.len()).sum(),
+                groups.len(),
+                true,
+            );
+            log::info!("AI result: {} commit groups", groups.len());
+            if cli.verbose {
+                eprintln!("✨ AI: {} commit group(s)", groups.len());
+            }
+            (groups, None)
+        }
+        (None, provider) => {
+            let spinner = ProgressSpinner::new("Creating commit groups...", 3, 4);
+            let worker = provider.as_ref().map(|provider| {
+                AiWorker::spawn_grouping(
+                    provider.clone(),
+                    changed_files.clone(),
+                    ticket.clone(),
+                    grouping_patches(provider, &changed_files),
+                )
+            });
+            let plugin_groups = group_with_plugins(
+                &plugins,
+                &changed_files,
+                &diff_store,
+                &config.plugins,
+                &branch,
+                ticket.clone(),
+            );
+            let groups = plugin_groups.unwrap_or_else(|| {
+                build_groups_with_progress(changed_files, ticket.clone(), |done, total| {
+                    spinner.set_message(format!(
+                        "Creating commit groups... ({}/{} files)",
+                        done, total
+                    ));
+                })
+            });
+            let groups = consolidate(groups);
+            logging::log_grouping_result(
+                groups.iter().map(|g| g.files.len()).sum(),
+                groups.len(),
+                false,
+            );
+            spinner.stop();
+
+            log::info!("Heuristic result: {} commit groups", groups.len());
+            if cli.verbose {
+                eprintln!("📦 Heuristic: {} commit group(s)", groups.len());
+                if worker.is_some() {
+                    eprintln!("✨ AI grouping continues in the background");
+                }
+            }
+            (groups, worker)
+        }
+    };
+    groups.extend(attributes_group);
+
+    // Let the TUI collect and analyze the changes again; untracked files
+    // and submodule updates declined above stay left out
+    let replanner: Replanner = {
+        let (ctx, repo_path, config, branch) = (
+            ctx.clone(),
+            repo_path.clone(),
+            config.clone(),
+            branch.clone(),
         );
-        spinner.stop();
-        heuristic_groups
+        let (vocabulary, diff_store) = (vocabulary.clone(), Arc::clone(&diff_store));
+        Box::new(move || {
+            // Files may have changed since their diffs were loaded
+            diff_store.clear();
+            let repo = ctx.open()?;
+            let (mut files, untracked) = collect_status(&ctx, &repo)?;
+            files.extend(
+                untracked
+                    .into_iter()
+                    .filter(|f| !declined_untracked.contains(&f.path)),
+            );
+            if !include_submodules {
+                files.retain(|f| !f.is_submodule());
+            }
+            let snapshot = analyze_changes(
+                &repo,
+                &repo_path,
+                &config,
+                &branch,
+                &vocabulary,
+                &diff_store,
+                &mut files,
+            )?;
+            Ok(Replan { files, snapshot })
+        })
     };
 
-    log::info!("Final result: {} commit groups", groups.len());
-    if cli.verbose {
-        eprintln!("📦 Final: {} commit group(s)", groups.len());
+    // Watch the working tree while the TUI runs; the watcher stops when
+    // it is dropped at the end of the run
+    #[cfg(feature = "watch")]
+    let watcher = if cli.watch {
+        match WorktreeWatcher::start(&ctx.workdir) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                logging::log_error("Failed to watch the working tree", &e);
+                eprintln!("⚠️  --watch is unavailable: {:#}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    #[cfg(feature = "watch")]
+    let worktree_events = watcher.as_ref().map(WorktreeWatcher::events);
+    #[cfg(not(feature = "watch"))]
+    let worktree_events = None;
+    #[cfg(not(feature = "watch"))]
+    if cli.watch {
+        eprintln!("⚠️  --watch is unavailable: built without the `watch` feature");
+    }
+
+    // Run TUI (AI results are applied as they arrive)
+    let branch_status = BranchStatus::collect(&repo).unwrap_or_else(|e| {
+        logging::log_error("Failed to compare the branch with its upstream", &e);
+        BranchStatus::default()
+    });
+    let session = SessionInfo {
+        repo_name: repo_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| repo_path.display().to_string()),
+        branch,
+        ticket,
+        branch_status,
+        started: session_started,
+    };
+    let app = AppState::new(groups)
+        .with_session(session)
+        .with_palette(palette)
+        .with_theme(theme)
+        .with_config(config)
+        .with_worktree_label(worktree_label)
+        .with_ai(provider, worker)
+        .with_ai_fallbacks(ai_fallbacks)
+        .with_command_context(command_context)
+        .with_issue_footers(issue_footers)
+        .with_snapshot(snapshot)
+        .with_update_hint(update_hint)
+        .with_scope_vocabulary(vocabulary)
+        .with_summary_format(cli.summary)
+        .with_replanner(Some(replanner))
+        .with_diff_store(Some(diff_store))
+        .with_plugins(plugins)
+        .with_orphaned_files(orphaned_files)
+        .with_worktree_events(worktree_events)
+        .with_tutorial(if cli.tutorial {
+            Some(Tutorial::replay())
+        } else {
+            Tutorial::first_run()
+        });
+    run_tui(app, &repo_path)
+}
+
+/// Groups `files` with the first plugin that groups files, handing it
+/// their diffs shortened to `[plugins] max_diff_bytes`.
+///
+/// # Returns
+///
+/// The groups, or `None` if no plugin groups files or the plugin failed;
+/// heuristic grouping is used then.
+fn group_with_plugins(
+    plugins: &PluginSet,
+    files: &[ChangedFile],
+    diff_store: &DiffStore,
+    config: &PluginConfig,
+    branch: &str,
+    ticket: Option<String>,
+) -> Option<Vec<ChangeGroup>> {
+    if !plugins.plugins().iter().any(WasmPlugin::can_group) {
+        return None;
+    }
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    let patches = paths
+        .chunks(BATCH_FILES)
+        .map(|batch| diff_store.get_many(batch.iter().copied()))
+        .collect::<Result<Vec<_>>>()
+        .unwrap_or_else(|e| {
+            logging::log_error("Failed to collect diffs for plugins", &e);
+            Vec::new()
+        })
+        .into_iter()
+        .flatten()
+        .map(|(path, diff)| (path, diff.patch.clone()))
+        .collect();
+    let diffs = truncated_diffs(patches, config.max_diff_bytes);
+
+    match plugins.group(files.to_vec(), &diffs, branch, ticket) {
+        Ok(groups) => {
+            if let Some(groups) = &groups {
+                log::info!("Plugin result: {} commit groups", groups.len());
+            }
+            groups
+        }
+        Err(e) => {
+            logging::log_error("Plugin grouping failed", &e);
+            eprintln!("⚠️  {:#}\n   Using heuristic grouping", e);
+            None
+        }
     }
+}
+
+/// Runs the analysis that guides grouping on `changed_files`: line
+/// statistics, fix/feature scores, touched declarations, classification,
+/// scopes, path rules, and links between related files.
+///
+/// Diffs are taken from `store` in batches of [`BATCH_FILES`], so only one
+/// batch has to fit in memory besides what the store caches. Runs at
+/// startup and again when the TUI refreshes the groups.
+///
+/// # Returns
+///
+/// The content hashes of the planned files.
+///
+/// # Errors
+///
+/// Returns an error if the scope or path rule configuration is invalid.
+fn analyze_changes(
+    repo: &Repository,
+    repo_path: &Path,
+    config: &Config,
+    branch: &str,
+    vocabulary: &ScopeVocabulary,
+    store: &DiffStore,
+    changed_files: &mut [ChangedFile],
+) -> Result<Snapshot> {
+    // Remember the planned content so later edits mark their groups stale
+    let snapshot = Snapshot::capture(repo_path, changed_files.iter().map(|f| f.path.as_str()))
+        .unwrap_or_else(|e| {
+            logging::log_error("Failed to fingerprint the changed files", &e);
+            Snapshot::default()
+        });
 
-    // Run TUI (AI is now always used for editing if available)
-    let app = AppState::new(groups);
-    run_tui(app, &repo_path)?;
+    // Estimate fix vs. feature from keywords and blame data, find the
+    // declarations each diff touches for heuristic commit bodies, what
+    // deleted files were for, and suggest fix, perf or refactor from both
+    // and the branch name. Blame is limited so large change sets do not
+    // stall startup
+    let no_cancel = AtomicBool::new(false);
+    let mut blame = BlameBudget::new(MAX_BLAMED_FILES, BLAME_TIME_BUDGET, &no_cancel);
+    for batch in changed_files.chunks_mut(BATCH_FILES) {
+        let diffs = match store.get_many(batch.iter().map(|f| f.path.as_str())) {
+            Ok(diffs) => diffs,
+            Err(e) => {
+                logging::log_error("Failed to collect diffs", &e);
+                HashMap::new()
+            }
+        };
+        for file in batch.iter_mut() {
+            if let Some(diff) = diffs.get(&file.path) {
+                file.set_diff_stats(diff);
+            }
+        }
+        score_files(repo, batch, &diffs, &mut blame);
+        describe_files(batch, &diffs);
+        describe_deleted_files(repo, batch);
+        classify_files(batch, &diffs, branch);
+    }
+    ScopeResolver::new(repo_path, &config.scope)?
+        .with_vocabulary(vocabulary.clone())
+        .assign(changed_files);
+    PathRules::new(&config.rules)?.assign(changed_files);
+    // Keep files that reference each other, or that past commits changed
+    // together, in one group
+    let mut links = reference_edges(repo_path, changed_files);
+    if config.history.enabled {
+        match co_change_edges(repo, changed_files, &config.history) {
+            Ok(edges) => links.extend(edges),
+            Err(e) => logging::log_error("Failed to learn from the commit history", &e),
+        }
+    }
+    link_related(changed_files, links);
 
-    Ok(())
+    Ok(snapshot)
 }
@@ -0,0 +1,186 @@
+//! HTTP proxies and certificate authorities.
+//!
+//! Every HTTP client (AI providers, issue lookup, update check) is built
+//! with [`client_builder`] from the [`NetworkSettings`] of its service, as
+//! resolved by [`crate::config::NetworkConfig::for_service`]:
+//!
+//! - `proxy` routes all requests through that proxy, except for the hosts
+//!   in `no_proxy`; an empty `proxy` connects directly. Without one, the
+//!   `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and `NO_PROXY` environment
+//!   variables apply.
+//! - `ca_bundles` and the PEM file named by [`CA_BUNDLE_ENV`] add
+//!   certificate authorities to the built-in ones, e.g. the root of a
+//!   corporate proxy that intercepts TLS.
+//!
+//! These settings only come from the user configuration, `--config`, and
+//! the environment; a cloned `.commit-wizard.toml` cannot set them.
+//!
+//! Failed requests go through [`explain_send_error`], which turns untrusted
+//! certificates into an [`UntrustedCertificate`] error saying how to trust
+//! the intercepting proxy.
+
+use std::env;
+use std::error::Error as _;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::config::NetworkSettings;
+
+/// Environment variable naming an extra PEM bundle of certificate
+/// authorities, trusted by every service.
+pub const CA_BUNDLE_ENV: &str = "COMMIT_WIZARD_CA_BUNDLE";
+
+/// Service sending HTTP requests, with its own `[network.<service>]` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    /// The OpenAI provider
+    OpenAi,
+    /// The GitHub Models provider
+    GithubModels,
+    /// The Ollama provider
+    Ollama,
+    /// Issue lookup on GitHub and GitLab
+    Forge,
+    /// The update check
+    Update,
+}
+
+impl Service {
+    /// Returns the name of the service's table under `[network]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::network::Service;
+    ///
+    /// assert_eq!(Service::GithubModels.key(), "github-models");
+    /// ```
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::OpenAi => "openai",
+            Self::GithubModels => "github-models",
+            Self::Ollama => "ollama",
+            Self::Forge => "forge",
+            Self::Update => "update",
+        }
+    }
+}
+
+/// Returns a client builder honoring the proxy and certificate authorities
+/// of `settings`.
+///
+/// # Errors
+///
+/// Returns an error if the proxy URL is invalid or a CA bundle cannot be
+/// read or holds no certificate.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::config::NetworkSettings;
+/// use commit_wizard::network::client_builder;
+///
+/// let settings = NetworkSettings {
+///     proxy: Some("not a url".to_string()),
+///     ..NetworkSettings::default()
+/// };
+/// let error = client_builder(&settings).unwrap_err();
+/// assert!(error.to_string().contains("Invalid proxy URL"));
+/// ```
+pub fn client_builder(settings: &NetworkSettings) -> Result<reqwest::blocking::ClientBuilder> {
+    let mut builder = reqwest::blocking::Client::builder();
+    match settings.proxy.as_deref().map(str::trim) {
+        Some("") => builder = builder.no_proxy(),
+        Some(url) => {
+            let no_proxy = settings
+                .no_proxy
+                .as_deref()
+                .and_then(reqwest::NoProxy::from_string);
+            let proxy = reqwest::Proxy::all(url)
+                .with_context(|| format!("Invalid proxy URL '{}'", url))?
+                .no_proxy(no_proxy);
+            builder = builder.proxy(proxy);
+        }
+        None => {}
+    }
+    for path in ca_bundles(settings) {
+        for certificate in read_ca_bundle(&path)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    Ok(builder)
+}
+
+/// Returns the CA bundles of `settings` followed by the one named by
+/// [`CA_BUNDLE_ENV`].
+fn ca_bundles(settings: &NetworkSettings) -> Vec<PathBuf> {
+    let mut paths = settings.ca_bundles.clone();
+    if let Some(path) = env::var_os(CA_BUNDLE_ENV).filter(|p| !p.is_empty()) {
+        paths.push(PathBuf::from(path));
+    }
+    paths
+}
+
+/// Reads the certificates of a PEM bundle.
+fn read_ca_bundle(path: &Path) -> Result<Vec<reqwest::Certificate>> {
+    let pem =
+        fs::read(path).with_context(|| format!("Failed to read CA bundle {}", path.display()))?;
+    let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+        .with_context(|| format!("Failed to parse CA bundle {}", path.display()))?;
+    if certificates.is_empty() {
+        anyhow::bail!("No certificate found in CA bundle {}", path.display());
+    }
+    Ok(certificates)
+}
+
+/// Error for requests whose server certificate is not trusted, typically
+/// because a proxy intercepts TLS with its own certificate authority.
+#[derive(Debug)]
+pub struct UntrustedCertificate(pub reqwest::Error);
+
+impl fmt::Display for UntrustedCertificate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The server certificate is not trusted; if a proxy intercepts TLS, add its CA \
+             certificate to ca_bundles under [network] or set {}",
+            CA_BUNDLE_ENV
+        )
+    }
+}
+
+impl std::error::Error for UntrustedCertificate {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Explains a failed request: untrusted certificates become an
+/// [`UntrustedCertificate`] error, and connection failures name the proxy
+/// they went through.
+pub fn explain_send_error(error: reqwest::Error, settings: &NetworkSettings) -> anyhow::Error {
+    if is_certificate_error(&error) {
+        return UntrustedCertificate(error).into();
+    }
+    match settings.proxy.as_deref().map(str::trim) {
+        Some(proxy) if !proxy.is_empty() && error.is_connect() => anyhow::Error::new(error)
+            .context(format!("Could not connect through the proxy {}", proxy)),
+        _ => error.into(),
+    }
+}
+
+/// Returns whether the TLS handshake rejected the server certificate.
+fn is_certificate_error(error: &reqwest::Error) -> bool {
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let text = cause.to_string().to_lowercase();
+        if text.contains("certificate") || text.contains("unknownissuer") {
+            return true;
+        }
+        source = cause.source();
+    }
+    false
+}
@@ -0,0 +1,74 @@
+//! Notes attached to a group as context for the AI.
+//!
+//! The diff shows what changed, but not always why. With `s` a text, such
+//! as design notes or an issue comment, can be pasted into the scratchpad
+//! of the selected group, or loaded from a file by entering `@path`. The
+//! notes are added to the prompts that generate and refine the group's
+//! message (see [`crate::copilot::build_commit_message_prompt`]); they go
+//! through the same redaction as the diff, and are never part of the
+//! commit itself.
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+/// Prefix of an input naming a file to load the notes from.
+pub const FILE_PREFIX: char = '@';
+
+/// Maximum size of the notes, so a large file does not crowd out the diff
+/// in the prompt.
+pub const MAX_NOTES_BYTES: usize = 8 * 1024;
+
+/// Turns the scratchpad input into the notes of a group.
+///
+/// An input starting with `@` names a file, relative to `workdir` unless
+/// absolute, whose content becomes the notes. Notes longer than
+/// [`MAX_NOTES_BYTES`] are cut at a character boundary.
+///
+/// # Returns
+///
+/// The notes, or `None` for an empty input, which removes them.
+///
+/// # Errors
+///
+/// Returns an error if the named file cannot be read.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::notes::resolve_notes;
+/// use std::path::Path;
+///
+/// let notes = resolve_notes("  Keeps the old API for the mobile app\n", Path::new(".")).unwrap();
+/// assert_eq!(notes.as_deref(), Some("Keeps the old API for the mobile app"));
+/// assert_eq!(resolve_notes("   ", Path::new(".")).unwrap(), None);
+/// ```
+pub fn resolve_notes(input: &str, workdir: &Path) -> Result<Option<String>> {
+    let input = input.trim();
+    let text = match input.strip_prefix(FILE_PREFIX) {
+        Some(path) => {
+            let path = workdir.join(path.trim());
+            let mut content = Vec::new();
+            fs::File::open(&path)
+                .and_then(|file| {
+                    file.take(MAX_NOTES_BYTES as u64 + 4)
+                        .read_to_end(&mut content)
+                })
+                .with_context(|| format!("Failed to read notes from {}", path.display()))?;
+            String::from_utf8_lossy(&content).into_owned()
+        }
+        None => input.to_string(),
+    };
+
+    let mut notes = text.trim().to_string();
+    if notes.len() > MAX_NOTES_BYTES {
+        let mut end = MAX_NOTES_BYTES;
+        while !notes.is_char_boundary(end) {
+            end -= 1;
+        }
+        notes.truncate(end);
+    }
+    Ok((!notes.is_empty()).then_some(notes))
+}
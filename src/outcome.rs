@@ -0,0 +1,124 @@
+//! Exit codes for scripts and CI.
+//!
+//! Every run ends with one [`Outcome`], whose exit code wrappers can branch
+//! on:
+//!
+//! | Code | Outcome                                         |
+//! | ---- | ----------------------------------------------- |
+//! | 0    | Success                                         |
+//! | 1    | Unexpected error                                |
+//! | 2    | Nothing to commit                               |
+//! | 3    | The last commit or checked title is invalid     |
+//! | 4    | AI unavailable or failing with `--require-ai`   |
+//! | 5    | The last commit failed                          |
+//! | 64   | Invalid command line arguments                  |
+//!
+//! Failures with a specific outcome are reported as [`OutcomeError`]; any
+//! other error maps to [`Outcome::Error`]. In the TUI, the result of the last
+//! commit attempt decides the outcome, so fixing a refused message and
+//! committing again ends with success.
+
+use std::fmt;
+
+/// How a run ended.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Outcome {
+    /// Everything requested was done
+    #[default]
+    Success,
+    /// An unexpected error occurred
+    Error,
+    /// There were no changes to commit
+    NothingToCommit,
+    /// The last commit was refused by validation, e.g. the scope policy, or
+    /// `check-title` found a title breaking the rules
+    ValidationFailed,
+    /// No AI provider was available, or AI grouping failed, although
+    /// `--require-ai` was given
+    AiUnavailable,
+    /// The last commit failed, e.g. because a hook rejected it
+    CommitFailed,
+    /// The command line arguments were invalid
+    Usage,
+}
+
+impl Outcome {
+    /// Returns the process exit code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::outcome::Outcome;
+    ///
+    /// assert_eq!(Outcome::Success.code(), 0);
+    /// assert_eq!(Outcome::NothingToCommit.code(), 2);
+    /// assert_eq!(Outcome::Usage.code(), 64);
+    /// ```
+    pub fn code(self) -> u8 {
+        match self {
+            Outcome::Success => 0,
+            Outcome::Error => 1,
+            Outcome::NothingToCommit => 2,
+            Outcome::ValidationFailed => 3,
+            Outcome::AiUnavailable => 4,
+            Outcome::CommitFailed => 5,
+            Outcome::Usage => 64,
+        }
+    }
+
+    /// Returns the outcome of a failed run.
+    ///
+    /// Looks for an [`OutcomeError`] in the error chain and falls back to
+    /// [`Outcome::Error`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use commit_wizard::outcome::{Outcome, OutcomeError};
+    ///
+    /// let error = Err::<(), _>(OutcomeError::new(Outcome::NothingToCommit, "No changes"))
+    ///     .context("Collecting changes")
+    ///     .unwrap_err();
+    /// assert_eq!(Outcome::of(&error), Outcome::NothingToCommit);
+    /// assert_eq!(Outcome::of(&anyhow::anyhow!("boom")), Outcome::Error);
+    /// ```
+    pub fn of(error: &anyhow::Error) -> Self {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<OutcomeError>())
+            .map_or(Outcome::Error, |e| e.outcome)
+    }
+}
+
+impl From<Outcome> for std::process::ExitCode {
+    fn from(outcome: Outcome) -> Self {
+        std::process::ExitCode::from(outcome.code())
+    }
+}
+
+/// Error ending a run with a specific [`Outcome`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutcomeError {
+    /// Outcome reported through the exit code
+    pub outcome: Outcome,
+    message: String,
+}
+
+impl OutcomeError {
+    /// Creates an error with the message shown to the user.
+    pub fn new(outcome: Outcome, message: impl Into<String>) -> Self {
+        Self {
+            outcome,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for OutcomeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for OutcomeError {}
@@ -0,0 +1,128 @@
+//! Colors for commit types.
+//!
+//! Groups are colored by their commit type so the composition of the planned
+//! history is visible at a glance. The built-in palette can be overridden per
+//! type in the `[colors]` configuration table, using color names (`"green"`,
+//! `"light-blue"`), indexed colors (`"208"`), or hex values (`"#ff8800"`).
+//! The built-in colors follow the interface theme (see [`crate::theme`]).
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use ratatui::style::Color;
+
+use crate::theme::ThemeName;
+use crate::types::CommitType;
+
+/// Mapping from commit type to display color.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colors: HashMap<CommitType, Color>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        let colors = CommitType::all()
+            .iter()
+            .map(|&commit_type| (commit_type, default_color(ThemeName::Dark, commit_type)))
+            .collect();
+        Self { colors }
+    }
+}
+
+impl Palette {
+    /// Builds the palette from the `[colors]` table on top of the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for keys that are not commit types and for values
+    /// that are not valid colors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use commit_wizard::palette::Palette;
+    /// use commit_wizard::types::CommitType;
+    /// use ratatui::style::Color;
+    ///
+    /// let overrides = HashMap::from([("feat".to_string(), "#00ff00".to_string())]);
+    /// let palette = Palette::from_config(&overrides).unwrap();
+    /// assert_eq!(palette.color(CommitType::Feat), Color::Rgb(0, 255, 0));
+    /// ```
+    pub fn from_config(overrides: &HashMap<String, String>) -> Result<Self> {
+        Self::for_theme(ThemeName::Dark, overrides)
+    }
+
+    /// Builds the palette from the `[colors]` table on top of the colors
+    /// of the built-in theme `theme`.
+    ///
+    /// # Errors
+    ///
+    /// Like [`Palette::from_config`].
+    pub fn for_theme(theme: ThemeName, overrides: &HashMap<String, String>) -> Result<Self> {
+        let mut palette = Self {
+            colors: CommitType::all()
+                .iter()
+                .map(|&commit_type| (commit_type, default_color(theme, commit_type)))
+                .collect(),
+        };
+        for (key, value) in overrides {
+            let commit_type = CommitType::all()
+                .iter()
+                .copied()
+                .find(|t| t.as_str() == key)
+                .ok_or_else(|| anyhow!("Unknown commit type '{}' in [colors]", key))?;
+            let color = Color::from_str(value)
+                .map_err(|_| anyhow!("Invalid color '{}' for '{}' in [colors]", value, key))?;
+            palette.colors.insert(commit_type, color);
+        }
+        Ok(palette)
+    }
+
+    /// Returns the color of a commit type.
+    pub fn color(&self, commit_type: CommitType) -> Color {
+        self.colors
+            .get(&commit_type)
+            .copied()
+            .unwrap_or_else(|| default_color(ThemeName::Dark, commit_type))
+    }
+}
+
+/// Returns the built-in color of a commit type in a theme.
+fn default_color(theme: ThemeName, commit_type: CommitType) -> Color {
+    match (theme, commit_type) {
+        (ThemeName::Dark, CommitType::Feat) => Color::Green,
+        (ThemeName::Dark, CommitType::Fix) => Color::Red,
+        (ThemeName::Dark, CommitType::Docs) => Color::Blue,
+        (ThemeName::Dark, CommitType::Style) => Color::Magenta,
+        (ThemeName::Dark, CommitType::Refactor) => Color::Cyan,
+        (ThemeName::Dark, CommitType::Perf) => Color::LightYellow,
+        (ThemeName::Dark, CommitType::Test) => Color::LightMagenta,
+        (ThemeName::Dark, CommitType::Chore) => Color::Gray,
+        (ThemeName::Dark, CommitType::Ci) => Color::LightBlue,
+        (ThemeName::Dark, CommitType::Build) => Color::LightCyan,
+        // The light variants are hard to read on white
+        (ThemeName::Light, CommitType::Feat) => Color::Indexed(28),
+        (ThemeName::Light, CommitType::Fix) => Color::Indexed(160),
+        (ThemeName::Light, CommitType::Docs) => Color::Blue,
+        (ThemeName::Light, CommitType::Style) => Color::Indexed(90),
+        (ThemeName::Light, CommitType::Refactor) => Color::Indexed(30),
+        (ThemeName::Light, CommitType::Perf) => Color::Indexed(136),
+        (ThemeName::Light, CommitType::Test) => Color::Indexed(127),
+        (ThemeName::Light, CommitType::Chore) => Color::Indexed(240),
+        (ThemeName::Light, CommitType::Ci) => Color::Indexed(25),
+        (ThemeName::Light, CommitType::Build) => Color::Indexed(31),
+        (ThemeName::HighContrast, CommitType::Feat) => Color::LightGreen,
+        (ThemeName::HighContrast, CommitType::Fix) => Color::LightRed,
+        (ThemeName::HighContrast, CommitType::Docs) => Color::LightBlue,
+        (ThemeName::HighContrast, CommitType::Style) => Color::LightMagenta,
+        (ThemeName::HighContrast, CommitType::Refactor) => Color::LightCyan,
+        (ThemeName::HighContrast, CommitType::Perf) => Color::LightYellow,
+        (ThemeName::HighContrast, CommitType::Test) => Color::Indexed(213),
+        (ThemeName::HighContrast, CommitType::Chore) => Color::White,
+        (ThemeName::HighContrast, CommitType::Ci) => Color::Indexed(117),
+        (ThemeName::HighContrast, CommitType::Build) => Color::Indexed(87),
+    }
+}
@@ -0,0 +1,608 @@
+//! Sandboxed WebAssembly plugins for grouping and validation.
+//!
+//! Teams that cannot ship native plugins can compile grouping heuristics
+//! and message checks to WebAssembly and list them in `[plugins] wasm`.
+//! A plugin is a core WebAssembly module; it gets no imports, so it cannot
+//! read files, open sockets, or start programs, and every call runs with a
+//! fresh instance, a fuel budget (roughly one unit per instruction) and a
+//! memory limit. The runtime is the `wasmi` interpreter, compiled in with
+//! the `wasm-plugins` feature.
+//!
+//! `wasmi` is used instead of `wasmtime`: plugins run a few short calls per
+//! session, so a JIT buys nothing, while `wasmtime` would add Cranelift and
+//! a native build to every release target and needs executable memory,
+//! which hardened systems refuse. `wasmi` is pure Rust, meters fuel
+//! deterministically, and adds well under a megabyte to the binary.
+//!
+//! # Interface
+//!
+//! Data is exchanged as UTF-8 JSON through the exported memory:
+//!
+//! ```text
+//! (memory (export "memory") 1)
+//! (func (export "alloc") (param $len i32) (result i32))
+//! (func (export "group") (param $ptr i32) (param $len i32) (result i64))
+//! (func (export "validate") (param $ptr i32) (param $len i32) (result i64))
+//! ```
+//!
+//! The host calls `alloc` for a buffer, writes the input there and calls
+//! `group` or `validate`, which return the output location as
+//! `(ptr << 32) | len`; the output must lie within the memory and may be
+//! at most `max_output_bytes` long. Both functions are optional, but
+//! exports with these names must have these types.
+//!
+//! `group` gets the branch, its ticket, and the changed files with their
+//! diffs (up to `max_diff_bytes` each), and returns groups; files it leaves
+//! out are grouped by the built-in heuristics, and AI grouping still
+//! replaces the result when it finishes:
+//!
+//! ```json
+//! {"branch": "feature/LU-12", "ticket": "LU-12",
+//!  "files": [{"path": "api/users.rs", "status": "added", "old_path": null, "diff": "..."}]}
+//!
+//! {"groups": [{"type": "feat", "scope": "api", "description": "add users endpoint",
+//!              "body": ["Add the users handler"], "files": ["api/users.rs"]}]}
+//! ```
+//!
+//! `validate` gets a message before it is committed and returns the reasons
+//! to reject it; an empty list accepts it:
+//!
+//! ```json
+//! {"header": "feat(api): add users endpoint", "message": "...", "type": "feat",
+//!  "scope": "api", "files": [{"path": "api/users.rs", "status": "added", "old_path": null}]}
+//!
+//! {"errors": ["API changes need a Refs footer"]}
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::PluginConfig;
+use crate::copilot::parse_commit_type;
+use crate::inference::build_groups;
+use crate::types::{ChangeGroup, ChangedFile};
+
+/// A changed file as plugins see it.
+#[derive(Debug, Serialize)]
+struct PluginFile<'a> {
+    path: &'a str,
+    status: &'static str,
+    old_path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diff: Option<&'a str>,
+}
+
+impl<'a> PluginFile<'a> {
+    fn new(file: &'a ChangedFile, diff: Option<&'a str>) -> Self {
+        let status = if file.is_deleted() {
+            "deleted"
+        } else if file.is_renamed() {
+            "renamed"
+        } else if file.is_new() || file.status.is_wt_new() {
+            "added"
+        } else {
+            "modified"
+        };
+        Self {
+            path: &file.path,
+            status,
+            old_path: file.old_path.as_deref(),
+            diff,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct GroupInput<'a> {
+    branch: &'a str,
+    ticket: Option<&'a str>,
+    files: Vec<PluginFile<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GroupOutput {
+    groups: Vec<PluginGroup>,
+}
+
+/// A group returned by a grouping plugin.
+#[derive(Debug, Deserialize)]
+struct PluginGroup {
+    #[serde(rename = "type")]
+    commit_type: String,
+    #[serde(default)]
+    scope: Option<String>,
+    description: String,
+    #[serde(default)]
+    body: Vec<String>,
+    files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ValidateInput<'a> {
+    header: String,
+    message: String,
+    #[serde(rename = "type")]
+    commit_type: &'static str,
+    scope: Option<&'a str>,
+    files: Vec<PluginFile<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ValidateOutput {
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+/// Resource limits of plugin calls.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(feature = "wasm-plugins"), allow(dead_code))]
+struct Limits {
+    fuel: u64,
+    memory_bytes: usize,
+    output_bytes: usize,
+}
+
+/// A loaded plugin module.
+pub struct WasmPlugin {
+    name: String,
+    runtime: runtime::Runtime,
+    limits: Limits,
+}
+
+impl std::fmt::Debug for WasmPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WasmPlugin")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WasmPlugin {
+    /// Loads the plugin module at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is no valid plugin.
+    pub fn load(path: &Path, config: &PluginConfig) -> Result<Self> {
+        let bytes =
+            fs::read(path).with_context(|| format!("Failed to read plugin {}", path.display()))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Self::from_bytes(&name, &bytes, config)
+    }
+
+    /// Loads a plugin module from its binary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bytes` is no valid module, the module imports
+    /// anything, it exports no `memory` and `alloc`, or the binary was built
+    /// without the `wasm-plugins` feature.
+    pub fn from_bytes(name: &str, bytes: &[u8], config: &PluginConfig) -> Result<Self> {
+        let runtime = runtime::Runtime::new(bytes)
+            .with_context(|| format!("Failed to load plugin {}", name))?;
+        Ok(Self {
+            name: name.to_string(),
+            runtime,
+            limits: Limits {
+                fuel: config.fuel,
+                memory_bytes: config.max_memory_mb.saturating_mul(1024 * 1024),
+                output_bytes: config.max_output_bytes,
+            },
+        })
+    }
+
+    /// Returns the name of the plugin, its file name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns whether the plugin groups files.
+    pub fn can_group(&self) -> bool {
+        self.runtime.exports("group")
+    }
+
+    /// Returns whether the plugin validates messages.
+    pub fn can_validate(&self) -> bool {
+        self.runtime.exports("validate")
+    }
+
+    /// Groups `files` with the plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The changed files
+    /// * `diffs` - Diffs of the files by path, shortened to the configured size
+    /// * `branch` - The current branch
+    /// * `ticket` - Ticket of the branch, set on every group
+    ///
+    /// # Returns
+    ///
+    /// The plugin's groups, and the files none of them contains.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the plugin traps, runs out of fuel or memory, or
+    /// returns invalid or too much JSON.
+    pub fn group(
+        &self,
+        files: Vec<ChangedFile>,
+        diffs: &HashMap<String, String>,
+        branch: &str,
+        ticket: Option<String>,
+    ) -> Result<(Vec<ChangeGroup>, Vec<ChangedFile>)> {
+        let input = GroupInput {
+            branch,
+            ticket: ticket.as_deref(),
+            files: files
+                .iter()
+                .map(|f| PluginFile::new(f, diffs.get(&f.path).map(String::as_str)))
+                .collect(),
+        };
+        let output: GroupOutput = self.call("group", &input)?;
+
+        let mut remaining: HashMap<String, ChangedFile> =
+            files.into_iter().map(|f| (f.path.clone(), f)).collect();
+        let mut groups = Vec::new();
+        for group in output.groups {
+            // Unknown paths and files already taken by an earlier group are
+            // dropped, so every file ends up in exactly one group
+            let group_files: Vec<ChangedFile> = group
+                .files
+                .iter()
+                .filter_map(|path| remaining.remove(path))
+                .collect();
+            if group_files.is_empty() {
+                log::warn!(
+                    "Plugin {}: dropping group \"{}\" without known files",
+                    self.name,
+                    group.description
+                );
+                continue;
+            }
+            groups.push(ChangeGroup::new(
+                parse_commit_type(&group.commit_type),
+                group.scope.filter(|scope| !scope.trim().is_empty()),
+                group_files,
+                ticket.clone(),
+                group.description,
+                group.body,
+            ));
+        }
+        let mut rest: Vec<ChangedFile> = remaining.into_values().collect();
+        rest.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok((groups, rest))
+    }
+
+    /// Validates the message of `group` with the plugin.
+    ///
+    /// # Returns
+    ///
+    /// The reasons the plugin rejects the message; empty if it accepts it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the plugin traps, runs out of fuel or memory, or
+    /// returns invalid or too much JSON.
+    pub fn validate(&self, group: &ChangeGroup) -> Result<Vec<String>> {
+        let input = ValidateInput {
+            header: group.header(),
+            message: group.full_message(),
+            commit_type: group.commit_type.as_str(),
+            scope: group.scope.as_deref(),
+            files: group
+                .files
+                .iter()
+                .map(|f| PluginFile::new(f, None))
+                .collect(),
+        };
+        let output: ValidateOutput = self.call("validate", &input)?;
+        Ok(output.errors)
+    }
+
+    /// Calls `export` with `input` and parses its output.
+    fn call<I: Serialize, O: for<'de> Deserialize<'de>>(
+        &self,
+        export: &str,
+        input: &I,
+    ) -> Result<O> {
+        let input = serde_json::to_vec(input)?;
+        let output = self
+            .runtime
+            .call(export, &input, self.limits)
+            .with_context(|| format!("Plugin {} failed in `{}`", self.name, export))?;
+        serde_json::from_slice(&output).with_context(|| {
+            format!(
+                "Plugin {} returned invalid output from `{}`",
+                self.name, export
+            )
+        })
+    }
+}
+
+/// The configured plugins.
+#[derive(Debug, Default)]
+pub struct PluginSet {
+    plugins: Vec<WasmPlugin>,
+}
+
+impl PluginSet {
+    /// Creates a set of loaded plugins.
+    pub fn new(plugins: Vec<WasmPlugin>) -> Self {
+        Self { plugins }
+    }
+
+    /// Loads the plugins listed in `[plugins] wasm`, relative to `workdir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first plugin that cannot be loaded.
+    pub fn load(config: &PluginConfig, workdir: &Path) -> Result<Self> {
+        let plugins = config
+            .wasm
+            .iter()
+            .map(|path| WasmPlugin::load(&workdir.join(path), config))
+            .collect::<Result<Vec<_>>>()?;
+        for plugin in &plugins {
+            log::info!(
+                "Loaded plugin {} (group: {}, validate: {})",
+                plugin.name(),
+                plugin.can_group(),
+                plugin.can_validate()
+            );
+        }
+        Ok(Self { plugins })
+    }
+
+    /// Returns the loaded plugins.
+    pub fn plugins(&self) -> &[WasmPlugin] {
+        &self.plugins
+    }
+
+    /// Returns whether no plugin is loaded.
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Groups `files` with the first plugin that groups files; the files it
+    /// leaves out are grouped by [`build_groups`].
+    ///
+    /// # Returns
+    ///
+    /// The groups, or `None` if no plugin groups files.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the grouping plugin.
+    pub fn group(
+        &self,
+        files: Vec<ChangedFile>,
+        diffs: &HashMap<String, String>,
+        branch: &str,
+        ticket: Option<String>,
+    ) -> Result<Option<Vec<ChangeGroup>>> {
+        let Some(plugin) = self.plugins.iter().find(|p| p.can_group()) else {
+            return Ok(None);
+        };
+        let (mut groups, rest) = plugin.group(files, diffs, branch, ticket.clone())?;
+        if !rest.is_empty() {
+            groups.extend(build_groups(rest, ticket));
+        }
+        Ok(Some(groups))
+    }
+
+    /// Validates the message of `group` with every plugin that validates
+    /// messages.
+    ///
+    /// # Returns
+    ///
+    /// The reasons to reject the message, prefixed with the plugin's name.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first plugin that fails.
+    pub fn validate(&self, group: &ChangeGroup) -> Result<Vec<String>> {
+        let mut errors = Vec::new();
+        for plugin in self.plugins.iter().filter(|p| p.can_validate()) {
+            errors.extend(
+                plugin
+                    .validate(group)?
+                    .into_iter()
+                    .map(|error| format!("{}: {}", plugin.name(), error)),
+            );
+        }
+        Ok(errors)
+    }
+}
+
+/// Returns the files' diffs by path, each cut to `max_bytes` at a
+/// character boundary, for [`PluginSet::group`].
+pub fn truncated_diffs(
+    patches: HashMap<String, String>,
+    max_bytes: usize,
+) -> HashMap<String, String> {
+    patches
+        .into_iter()
+        .map(|(path, mut patch)| {
+            if patch.len() > max_bytes {
+                let mut end = max_bytes;
+                while !patch.is_char_boundary(end) {
+                    end -= 1;
+                }
+                patch.truncate(end);
+            }
+            (path, patch)
+        })
+        .collect()
+}
+
+#[cfg(feature = "wasm-plugins")]
+mod runtime {
+    use anyhow::{bail, Context, Result};
+    use wasmi::core::ValueType::{self, I32, I64};
+    use wasmi::{Engine, ExternType, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+    use super::Limits;
+
+    /// Exported functions with their parameters, results, and whether they
+    /// are required.
+    const FUNCTIONS: [(&str, &[ValueType], &[ValueType], bool); 3] = [
+        ("alloc", &[I32], &[I32], true),
+        ("group", &[I32, I32], &[I64], false),
+        ("validate", &[I32, I32], &[I64], false),
+    ];
+
+    /// A compiled module.
+    pub struct Runtime {
+        engine: Engine,
+        module: Module,
+    }
+
+    impl Runtime {
+        pub fn new(bytes: &[u8]) -> Result<Self> {
+            let mut config = wasmi::Config::default();
+            config.consume_fuel(true);
+            let engine = Engine::new(&config);
+            let module = Module::new(&engine, bytes).context("Invalid WebAssembly module")?;
+            if let Some(import) = module.imports().next() {
+                bail!(
+                    "The module imports `{}::{}`; plugins get no imports",
+                    import.module(),
+                    import.name()
+                );
+            }
+            let runtime = Self { engine, module };
+            if !matches!(runtime.export_type("memory"), Some(ExternType::Memory(_))) {
+                bail!("The module does not export a memory `memory`");
+            }
+            for (name, params, results, required) in FUNCTIONS {
+                match runtime.export_type(name) {
+                    Some(ExternType::Func(ty))
+                        if ty.params() == params && ty.results() == results => {}
+                    Some(ExternType::Func(ty)) => bail!(
+                        "`{}` has the type {:?} -> {:?}, expected {:?} -> {:?}",
+                        name,
+                        ty.params(),
+                        ty.results(),
+                        params,
+                        results
+                    ),
+                    Some(_) => bail!("The module exports `{}`, but not as a function", name),
+                    None if required => bail!("The module does not export a function `{}`", name),
+                    None => {}
+                }
+            }
+            Ok(runtime)
+        }
+
+        /// Returns whether the module exports a function `name`.
+        pub fn exports(&self, name: &str) -> bool {
+            matches!(self.export_type(name), Some(ExternType::Func(_)))
+        }
+
+        fn export_type(&self, name: &str) -> Option<ExternType> {
+            self.module
+                .exports()
+                .find(|export| export.name() == name)
+                .map(|export| export.ty().clone())
+        }
+
+        /// Calls `export` on a fresh instance with `input` in its memory.
+        pub fn call(&self, export: &str, input: &[u8], limits: Limits) -> Result<Vec<u8>> {
+            let store_limits = StoreLimitsBuilder::new()
+                .memory_size(limits.memory_bytes)
+                .instances(1)
+                .build();
+            let mut store = Store::new(&self.engine, store_limits);
+            store.limiter(|limits: &mut StoreLimits| limits);
+            store
+                .add_fuel(limits.fuel)
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+            let linker = Linker::<StoreLimits>::new(&self.engine);
+            let instance = linker
+                .instantiate(&mut store, &self.module)
+                .and_then(|pre| pre.start(&mut store))
+                .map_err(|e| anyhow::anyhow!("Failed to instantiate: {}", e))?;
+            let memory = instance
+                .get_memory(&store, "memory")
+                .context("The module does not export `memory`")?;
+            let alloc = instance
+                .get_typed_func::<i32, i32>(&store, "alloc")
+                .map_err(|e| anyhow::anyhow!("Invalid `alloc`: {}", e))?;
+            let function = instance
+                .get_typed_func::<(i32, i32), i64>(&store, export)
+                .map_err(|e| anyhow::anyhow!("Invalid `{}`: {}", export, e))?;
+
+            let len = i32::try_from(input.len()).context("Input too large")?;
+            let ptr = alloc
+                .call(&mut store, len)
+                .map_err(|e| anyhow::anyhow!("`alloc` failed: {}", e))?;
+            memory
+                .write(&mut store, ptr as u32 as usize, input)
+                .map_err(|e| anyhow::anyhow!("`alloc` returned an invalid buffer: {}", e))?;
+
+            let result = function
+                .call(&mut store, (ptr, len))
+                .map_err(|e| anyhow::anyhow!("{}", e))?;
+            let (out_ptr, out_len) = ((result >> 32) as u32 as usize, result as u32 as usize);
+            // The length comes from the plugin; check it before allocating
+            if out_len > limits.output_bytes {
+                bail!(
+                    "The output of {} bytes exceeds the limit of {} bytes",
+                    out_len,
+                    limits.output_bytes
+                );
+            }
+            let memory_size = memory.data(&store).len();
+            if out_ptr
+                .checked_add(out_len)
+                .map_or(true, |end| end > memory_size)
+            {
+                bail!(
+                    "Invalid output location: {} bytes at {} in a memory of {} bytes",
+                    out_len,
+                    out_ptr,
+                    memory_size
+                );
+            }
+            let output = memory.data(&store)[out_ptr..out_ptr + out_len].to_vec();
+            log::debug!(
+                "Plugin `{}` used {} fuel",
+                export,
+                store.fuel_consumed().unwrap_or_default()
+            );
+            Ok(output)
+        }
+    }
+}
+
+#[cfg(not(feature = "wasm-plugins"))]
+mod runtime {
+    use anyhow::{bail, Result};
+
+    use super::Limits;
+
+    /// Stands in for the runtime in builds without plugin support.
+    pub struct Runtime;
+
+    impl Runtime {
+        pub fn new(_bytes: &[u8]) -> Result<Self> {
+            bail!("built without the `wasm-plugins` feature")
+        }
+
+        pub fn exports(&self, _name: &str) -> bool {
+            false
+        }
+
+        pub fn call(&self, _export: &str, _input: &[u8], _limits: Limits) -> Result<Vec<u8>> {
+            bail!("built without the `wasm-plugins` feature")
+        }
+    }
+}
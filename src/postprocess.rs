@@ -0,0 +1,172 @@
+//! Message post-processors.
+//!
+//! A project can configure commands in `[message]` that rewrite every
+//! commit message right before it is committed, e.g. to enforce ASCII,
+//! translate, or append tracking ids:
+//!
+//! ```toml
+//! [message]
+//! post_process = ["iconv -f utf-8 -t ascii//TRANSLIT", "./scripts/add-tracking-id"]
+//! post_process_failure = "block"
+//! ```
+//!
+//! Each command runs with the user's shell in the repository root, gets the
+//! message on standard input and prints the message to commit; the
+//! commands run in order, each on the output of the one before. They apply
+//! to every commit the wizard creates, after the message was reviewed.
+//! WebAssembly modules run through their runtime, e.g.
+//! `wasmtime run filter.wasm`.
+//!
+//! Unlike git hooks, a `.commit-wizard.toml` is part of every clone, so like
+//! the context command (see [`crate::context`]) the commands are only read
+//! from the user configuration and `--config`.
+
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::config::MessageConfig;
+use crate::context::shell_command;
+use crate::git::{execute_with_input, Cancelled};
+
+/// What happens to a commit whose message a post-processor fails to
+/// rewrite.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PostProcessFailure {
+    /// The commit is not created and the error is shown
+    #[default]
+    Block,
+    /// The message is committed as it was, with a warning
+    Keep,
+}
+
+/// A message rewritten by the post-processors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostProcessed {
+    /// Message to commit
+    pub message: String,
+    /// Why the message was committed unchanged, if a post-processor failed
+    pub warning: Option<String>,
+}
+
+/// The configured post-processors.
+#[derive(Debug, Clone, Default)]
+pub struct PostProcessors {
+    commands: Vec<String>,
+    on_failure: PostProcessFailure,
+}
+
+impl PostProcessors {
+    /// Creates post-processors running `commands` in order.
+    pub fn new(commands: Vec<String>, on_failure: PostProcessFailure) -> Self {
+        let commands = commands
+            .into_iter()
+            .filter(|command| !command.trim().is_empty())
+            .collect();
+        Self {
+            commands,
+            on_failure,
+        }
+    }
+
+    /// Creates the post-processors configured in `[message]`.
+    pub fn from_config(config: &MessageConfig) -> Self {
+        Self::new(config.post_process.clone(), config.post_process_failure)
+    }
+
+    /// Returns the commands, in the order they run.
+    pub fn commands(&self) -> &[String] {
+        &self.commands
+    }
+
+    /// Returns whether no post-processor is configured.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Rewrites `message` with the post-processors, handling failures as
+    /// configured.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message as reviewed
+    /// * `workdir` - Repository root the commands run in
+    /// * `timeout` - Time each command may take
+    /// * `cancel` - Stops the running command when set
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Cancelled`] if `cancel` was set, and the error of the
+    /// failing post-processor unless failures keep the message.
+    pub fn apply(
+        &self,
+        message: &str,
+        workdir: &Path,
+        timeout: Duration,
+        cancel: &AtomicBool,
+    ) -> Result<PostProcessed> {
+        match self.run(message, workdir, timeout, cancel) {
+            Ok(message) => Ok(PostProcessed {
+                message,
+                warning: None,
+            }),
+            Err(error) if error.is::<Cancelled>() => Err(error),
+            Err(error) if self.on_failure == PostProcessFailure::Keep => {
+                log::warn!("Committing the message unchanged: {:#}", error);
+                Ok(PostProcessed {
+                    message: message.to_string(),
+                    warning: Some(format!("{:#}; committed the message unchanged", error)),
+                })
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Pipes `message` through every command in turn.
+    fn run(
+        &self,
+        message: &str,
+        workdir: &Path,
+        timeout: Duration,
+        cancel: &AtomicBool,
+    ) -> Result<String> {
+        let mut message = message.to_string();
+        for command in &self.commands {
+            message = run_command(command, &message, workdir, timeout, cancel)
+                .with_context(|| format!("Message post-processor `{}` failed", command))?;
+        }
+        Ok(message)
+    }
+}
+
+/// Runs one post-processor on `message`.
+fn run_command(
+    command: &str,
+    message: &str,
+    workdir: &Path,
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Result<String> {
+    log::info!("Running message post-processor: {}", command);
+    let mut cmd = shell_command(command);
+    cmd.current_dir(workdir);
+    let output = execute_with_input(&mut cmd, Some(message.as_bytes()), timeout, cancel)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        match output.status.code() {
+            Some(code) => bail!("exit code {}: {}", code, stderr.trim()),
+            None => bail!("terminated by a signal: {}", stderr.trim()),
+        }
+    }
+
+    let rewritten = String::from_utf8(output.stdout).context("output is not UTF-8")?;
+    let rewritten = rewritten.trim_end();
+    if rewritten.trim().is_empty() {
+        bail!("printed no message");
+    }
+    Ok(format!("{}\n", rewritten))
+}
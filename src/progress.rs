@@ -25,6 +25,7 @@ use std::time::Duration;
 /// spinner.stop();
 /// ```
 pub struct ProgressSpinner {
+    message: Arc<Mutex<String>>,
     running: Arc<AtomicBool>,
     handle: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
 }
@@ -53,7 +54,7 @@ impl ProgressSpinner {
     /// A new `ProgressSpinner` instance that starts animating immediately.
     /// If stderr is not a terminal, the spinner will not display anything.
     pub fn new(message: impl Into<String>, step: usize, total: usize) -> Self {
-        let message = message.into();
+        let message = Arc::new(Mutex::new(message.into()));
         let running = Arc::new(AtomicBool::new(true));
 
         let msg_clone = message.clone();
@@ -65,10 +66,8 @@ impl ProgressSpinner {
                 let mut idx = 0;
 
                 while running_clone.load(Ordering::Relaxed) {
-                    eprint!(
-                        "\r\x1B[2K[{}/{}] {} {}",
-                        step, total, spinners[idx], msg_clone
-                    );
+                    let msg = msg_clone.lock().map(|m| m.clone()).unwrap_or_default();
+                    eprint!("\r\x1B[2K[{}/{}] {} {}", step, total, spinners[idx], msg);
                     let _ = io::stderr().flush();
 
                     idx = (idx + 1) % spinners.len();
@@ -84,11 +83,21 @@ impl ProgressSpinner {
         };
 
         Self {
+            message,
             running,
             handle: Arc::new(Mutex::new(handle)),
         }
     }
 
+    /// Replaces the message, e.g. to show how far the operation got.
+    ///
+    /// The new message is shown with the next animation frame.
+    pub fn set_message(&self, message: impl Into<String>) {
+        if let Ok(mut current) = self.message.lock() {
+            *current = message.into();
+        }
+    }
+
     /// Stops the spinner animation and waits for the thread to finish.
     ///
     /// This method is idempotent and can be called multiple times safely.
@@ -0,0 +1,300 @@
+//! Token budgets for diffs in AI prompts.
+//!
+//! Diffs are the largest part of every prompt. Instead of cutting them after
+//! a fixed number of bytes, this module estimates their size in tokens and
+//! condenses them to a budget: file and hunk headers are kept first, then
+//! changed lines that look like signatures (functions, types, imports),
+//! then other added lines, removed lines, and finally context lines. Omitted
+//! runs are replaced by a marker so the model knows something is missing.
+//!
+//! A prompt covering several files shares one budget: small diffs are sent
+//! in full, and the remainder is split evenly between the larger ones.
+//!
+//! All truncation happens on character boundaries, so multibyte text is
+//! never split.
+
+use std::collections::HashMap;
+
+use crate::provider::{AiProvider, ProviderLayer};
+
+/// Default number of tokens available for diffs in one prompt.
+pub const DEFAULT_DIFF_TOKENS: usize = 1500;
+
+/// Average number of characters per token used for estimates.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Smallest share worth sending for a file; files below are omitted.
+const MIN_FILE_TOKENS: usize = 32;
+
+/// Longest single diff line kept, in characters (e.g. minified files).
+const MAX_LINE_CHARS: usize = 240;
+
+/// Estimates the number of tokens of a text.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::prompt::estimate_tokens;
+///
+/// assert_eq!(estimate_tokens(""), 0);
+/// assert_eq!(estimate_tokens("abcd"), 1);
+/// assert_eq!(estimate_tokens("abcde"), 2);
+/// ```
+pub fn estimate_tokens(text: &str) -> usize {
+    (text.chars().count() + CHARS_PER_TOKEN - 1) / CHARS_PER_TOKEN
+}
+
+/// Returns at most the first `max_chars` characters of `text`.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::prompt::truncate_chars;
+///
+/// assert_eq!(truncate_chars("größer", 3), "grö");
+/// assert_eq!(truncate_chars("abc", 10), "abc");
+/// ```
+pub fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => &text[..idx],
+        None => text,
+    }
+}
+
+/// Importance of a diff line; lower values are kept first.
+fn line_priority(line: &str) -> u8 {
+    const HEADERS: &[&str] = &[
+        "diff --git",
+        "--- ",
+        "+++ ",
+        "@@",
+        "new file",
+        "deleted file",
+        "rename ",
+        "Binary files",
+    ];
+    if HEADERS.iter().any(|h| line.starts_with(h)) {
+        return 0;
+    }
+    match line.chars().next() {
+        Some(sign @ ('+' | '-')) => {
+            if is_signature(&line[1..]) {
+                1
+            } else if sign == '+' {
+                2
+            } else {
+                3
+            }
+        }
+        _ => 4,
+    }
+}
+
+/// Returns whether a line looks like a declaration or import.
+fn is_signature(code: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "fn ",
+        "pub ",
+        "async ",
+        "impl ",
+        "impl<",
+        "struct ",
+        "enum ",
+        "trait ",
+        "mod ",
+        "use ",
+        "def ",
+        "class ",
+        "interface ",
+        "func ",
+        "function ",
+        "export ",
+        "import ",
+        "type ",
+        "const ",
+    ];
+    let code = code.trim_start();
+    KEYWORDS.iter().any(|k| code.starts_with(k))
+}
+
+/// Condenses a unified diff to roughly `max_tokens` tokens.
+///
+/// Diffs within the budget are returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::prompt::{condense_diff, estimate_tokens};
+///
+/// let diff = format!("@@ -1,3 +1,3 @@\n+fn added() {{}}\n{}", " context\n".repeat(100));
+/// let condensed = condense_diff(&diff, 20);
+/// assert!(condensed.contains("+fn added"));
+/// assert!(estimate_tokens(&condensed) <= 20);
+/// ```
+pub fn condense_diff(diff: &str, max_tokens: usize) -> String {
+    if estimate_tokens(diff) <= max_tokens {
+        return diff.to_string();
+    }
+
+    let lines: Vec<String> = diff
+        .lines()
+        .map(|line| {
+            if line.chars().count() > MAX_LINE_CHARS {
+                format!("{}…", truncate_chars(line, MAX_LINE_CHARS))
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+
+    // Indices in the order they are considered for the budget
+    let mut order: Vec<usize> = (0..lines.len()).collect();
+    order.sort_by_key(|&i| line_priority(&lines[i]));
+
+    let mut keep = vec![false; lines.len()];
+    let mut selected = Vec::new();
+    let mut used = 0;
+    let budget = max_tokens * CHARS_PER_TOKEN;
+    for &i in &order {
+        let cost = lines[i].chars().count() + 1;
+        if used + cost <= budget {
+            used += cost;
+            keep[i] = true;
+            selected.push(i);
+        }
+    }
+
+    // Omission markers need room too: drop the least important lines until
+    // the result fits
+    loop {
+        let condensed = render_condensed(&lines, &keep);
+        if estimate_tokens(&condensed) <= max_tokens {
+            return condensed;
+        }
+        match selected.pop() {
+            Some(i) => keep[i] = false,
+            None => return truncate_chars(&condensed, budget).to_string(),
+        }
+    }
+}
+
+/// Joins the kept lines, replacing each omitted run with a marker.
+fn render_condensed(lines: &[String], keep: &[bool]) -> String {
+    let mut out = String::new();
+    let mut omitted = 0;
+    for (line, &kept) in lines.iter().zip(keep) {
+        if !kept {
+            omitted += 1;
+            continue;
+        }
+        if omitted > 0 {
+            out.push_str(&format!("... ({} lines omitted)\n", omitted));
+            omitted = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    if omitted > 0 {
+        out.push_str(&format!("... ({} lines omitted)\n", omitted));
+    }
+    out
+}
+
+/// Splits `max_tokens` between diffs.
+///
+/// Diffs smaller than an even share get what they need; the rest is split
+/// evenly between the larger ones. When there are too many files for that,
+/// the smallest ones get a minimal excerpt and the others get 0.
+///
+/// # Returns
+///
+/// The token budget of each diff, in input order.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::prompt::distribute_budget;
+///
+/// let small = "x".repeat(40); // 10 tokens
+/// let large = "x".repeat(4000); // 1000 tokens
+/// assert_eq!(distribute_budget(&[&small, &large], 200), vec![10, 190]);
+/// ```
+pub fn distribute_budget(diffs: &[&str], max_tokens: usize) -> Vec<usize> {
+    let needs: Vec<usize> = diffs.iter().map(|d| estimate_tokens(d)).collect();
+    let mut budgets = vec![0; diffs.len()];
+
+    let mut by_need: Vec<usize> = (0..diffs.len()).collect();
+    by_need.sort_by_key(|&i| needs[i]);
+
+    let mut remaining = max_tokens;
+    let mut open = by_need.len();
+    for &i in &by_need {
+        let share = remaining / open;
+        if needs[i] <= share {
+            budgets[i] = needs[i];
+        } else if share >= MIN_FILE_TOKENS {
+            budgets[i] = share;
+        } else if remaining >= MIN_FILE_TOKENS {
+            // Too many files for useful even shares: send a minimal excerpt
+            // of as many as possible, smallest first
+            budgets[i] = MIN_FILE_TOKENS;
+        } else {
+            break;
+        }
+        remaining -= budgets[i];
+        open -= 1;
+    }
+    budgets
+}
+
+/// Condenses a set of per-file diffs to share `max_tokens`.
+///
+/// # Returns
+///
+/// The condensed diffs ordered by path, and the number of files omitted
+/// because the budget was used up.
+pub fn fit_diffs(
+    diffs: &HashMap<String, String>,
+    max_tokens: usize,
+) -> (Vec<(String, String)>, usize) {
+    let mut entries: Vec<(&String, &String)> = diffs.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let texts: Vec<&str> = entries.iter().map(|(_, diff)| diff.as_str()).collect();
+    let budgets = distribute_budget(&texts, max_tokens);
+
+    let mut fitted = Vec::new();
+    let mut omitted = 0;
+    for ((path, diff), budget) in entries.into_iter().zip(budgets) {
+        if budget == 0 && !diff.is_empty() {
+            omitted += 1;
+        } else {
+            fitted.push((path.clone(), condense_diff(diff, budget)));
+        }
+    }
+    (fitted, omitted)
+}
+
+/// Provider that uses a configured diff token budget instead of
+/// [`DEFAULT_DIFF_TOKENS`].
+pub struct DiffBudgetProvider {
+    inner: Box<dyn AiProvider>,
+    max_tokens: usize,
+}
+
+impl DiffBudgetProvider {
+    /// Wraps `inner` so its prompts contain up to `max_tokens` of diffs.
+    pub fn new(inner: Box<dyn AiProvider>, max_tokens: usize) -> Self {
+        Self { inner, max_tokens }
+    }
+}
+
+impl ProviderLayer for DiffBudgetProvider {
+    fn inner(&self) -> &dyn AiProvider {
+        self.inner.as_ref()
+    }
+
+    fn diff_token_budget(&self) -> usize {
+        self.max_tokens
+    }
+}
@@ -0,0 +1,1051 @@
+//! Pluggable AI providers.
+//!
+//! An [`AiProvider`] turns a prompt into a response. Grouping files and
+//! generating commit messages are built on top of that single primitive, so a
+//! new backend only needs to implement [`AiProvider::is_available`] and
+//! [`AiProvider::complete`]; prompts and response parsing are shared.
+//!
+//! Built-in providers:
+//!
+//! - GitHub Copilot CLI ([`crate::copilot::CopilotCliProvider`])
+//! - GitHub Models (`GITHUB_TOKEN` / `GH_TOKEN`)
+//! - OpenAI (`OPENAI_API_KEY`)
+//! - Ollama (local models, no data leaves the machine)
+//!
+//! Requests run by an [`crate::worker::AiWorker`] can be cancelled: providers
+//! starting programs stop them once [`cancel_flag`] is set.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::config::{AiConfig, NetworkConfig, NetworkSettings};
+use crate::copilot::{self, CopilotCliProvider};
+use crate::network::{self, Service};
+use crate::prompt;
+use crate::retry::ApiError;
+use crate::types::{ChangeGroup, ChangedFile};
+
+thread_local! {
+    /// Cancel flag of the AI request running on this thread
+    static CANCEL_FLAG: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// Runs `request` with `cancel` as the [`cancel_flag`] of the AI requests it
+/// sends on this thread.
+pub fn with_cancel_flag<T>(cancel: Arc<AtomicBool>, request: impl FnOnce() -> T) -> T {
+    /// Restores the previous flag, also if `request` panics.
+    struct Restore(Option<Arc<AtomicBool>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            CANCEL_FLAG.with(|flag| *flag.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(CANCEL_FLAG.with(|flag| flag.replace(Some(cancel))));
+    request()
+}
+
+/// Returns the cancel flag of the AI request running on this thread.
+///
+/// Outside [`with_cancel_flag`] the returned flag is never set.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+///
+/// use commit_wizard::provider::{cancel_flag, with_cancel_flag};
+///
+/// let cancel = Arc::new(AtomicBool::new(true));
+/// assert!(with_cancel_flag(cancel, || cancel_flag().load(Ordering::Relaxed)));
+/// assert!(!cancel_flag().load(Ordering::Relaxed));
+/// ```
+pub fn cancel_flag() -> Arc<AtomicBool> {
+    CANCEL_FLAG
+        .with(|flag| flag.borrow().clone())
+        .unwrap_or_else(|| Arc::new(AtomicBool::new(false)))
+}
+
+/// GitHub Models API endpoint for chat completions
+const GITHUB_MODELS_API_URL: &str = "https://models.github.com/chat/completions";
+
+/// OpenAI API endpoint for chat completions
+const OPENAI_API_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+/// Default model for GitHub Models
+const GITHUB_MODELS_DEFAULT_MODEL: &str = "gpt-4";
+
+/// Default model for OpenAI
+const OPENAI_DEFAULT_MODEL: &str = "gpt-4.1-2025-04-14";
+
+/// Default Ollama server
+const OLLAMA_DEFAULT_URL: &str = "http://localhost:11434";
+
+/// Default Ollama model
+const OLLAMA_DEFAULT_MODEL: &str = "llama3";
+
+/// Timeout for API requests (60 seconds; grouping prompts can be large)
+const API_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Timeout for local model requests (local generation can be slow)
+const OLLAMA_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Timeout for checking whether the Ollama server is running
+const OLLAMA_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Maximum number of tokens requested per completion
+const MAX_RESPONSE_TOKENS: u32 = 2000;
+
+/// System prompt sent to chat completion APIs
+const SYSTEM_PROMPT: &str = "You are a helpful assistant that writes conventional commit \
+    messages. Follow the requested output format exactly.";
+
+/// Which AI provider to use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProviderKind {
+    /// First available of Copilot CLI, GitHub Models, OpenAI and Ollama
+    #[default]
+    Auto,
+    /// GitHub Copilot CLI
+    Copilot,
+    /// GitHub Models HTTP API
+    GithubModels,
+    /// OpenAI HTTP API
+    Openai,
+    /// Local Ollama server
+    Ollama,
+}
+
+/// Author of a message in a conversation with a provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatRole {
+    /// The user (prompts and feedback)
+    User,
+    /// The provider's responses
+    Assistant,
+}
+
+impl ChatRole {
+    /// Returns the role name used by chat completion APIs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Assistant => "assistant",
+        }
+    }
+}
+
+/// One message of a conversation with a provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    /// Author of the message
+    pub role: ChatRole,
+    /// Message text
+    pub content: String,
+}
+
+impl ChatMessage {
+    /// Creates a user message.
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::User,
+            content: content.into(),
+        }
+    }
+
+    /// Creates an assistant message.
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::Assistant,
+            content: content.into(),
+        }
+    }
+}
+
+/// A backend that can answer prompts for grouping and commit messages.
+///
+/// Providers are shared with background workers and must be thread-safe.
+pub trait AiProvider: Send + Sync {
+    /// Human-readable provider name for logs and status messages.
+    fn name(&self) -> &str;
+
+    /// Returns whether the provider is installed and authenticated.
+    fn is_available(&self) -> bool;
+
+    /// Explains why [`AiProvider::is_available`] returned `false`, e.g. a
+    /// missing token or CLI.
+    fn unavailable_reason(&self) -> String {
+        format!("{} is not available", self.name())
+    }
+
+    /// Model used for requests, if the provider lets the user choose one.
+    fn model_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Number of tokens available for diffs in one prompt.
+    fn diff_token_budget(&self) -> usize {
+        prompt::DEFAULT_DIFF_TOKENS
+    }
+
+    /// Returns whether `path` must never be sent to the provider, see
+    /// [`crate::exclude`].
+    fn excludes(&self, _path: &str) -> bool {
+        false
+    }
+
+    /// Short note while a failed request waits to be retried, e.g.
+    /// `retrying 2/3`.
+    fn retry_notice(&self) -> Option<String> {
+        None
+    }
+
+    /// Sends a prompt and returns the raw response text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider cannot be reached or rejects the
+    /// request.
+    fn complete(&self, prompt: &str) -> Result<String>;
+
+    /// Continues a conversation and returns the next response.
+    ///
+    /// The default flattens the conversation into a single prompt for
+    /// [`AiProvider::complete`]; chat APIs send the messages as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider cannot be reached or rejects the
+    /// request.
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        self.complete(&flatten_conversation(messages))
+    }
+
+    /// Sends a prompt whose response must be a JSON object.
+    ///
+    /// Providers with a JSON output mode enable it, so the response is
+    /// guaranteed to be syntactically valid JSON. The default calls
+    /// [`AiProvider::complete`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider cannot be reached or rejects the
+    /// request.
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        self.complete(prompt)
+    }
+
+    /// Sends a prompt and returns up to `n` alternative responses.
+    ///
+    /// The default calls [`AiProvider::complete`] `n` times; chat APIs
+    /// request all completions at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider cannot be reached or rejects the
+    /// request.
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        (0..n.max(1)).map(|_| self.complete(prompt)).collect()
+    }
+
+    /// Groups changed files into commit groups.
+    ///
+    /// Submodule pointer updates always get their own group.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider fails or the grouping assigns a file
+    /// to several groups.
+    fn group_files(
+        &self,
+        files: Vec<ChangedFile>,
+        ticket: Option<String>,
+        diffs: &HashMap<String, String>,
+    ) -> Result<Vec<ChangeGroup>> {
+        copilot::group_files_with_provider(self, files, ticket, diffs)
+    }
+
+    /// Generates a commit description and optional body for a group.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider fails.
+    fn generate_message(
+        &self,
+        group: &ChangeGroup,
+        files: &[ChangedFile],
+        diff: Option<&str>,
+    ) -> Result<(String, Option<String>)> {
+        copilot::generate_message_with_provider(self, group, files, diff)
+    }
+
+    /// Generates up to `n` alternative commit messages for a group.
+    ///
+    /// Unparsable and duplicate candidates are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider fails or no candidate is usable.
+    fn generate_messages(
+        &self,
+        group: &ChangeGroup,
+        files: &[ChangedFile],
+        diff: Option<&str>,
+        n: usize,
+    ) -> Result<Vec<(String, Option<String>)>> {
+        copilot::generate_messages_with_provider(self, group, files, diff, n)
+    }
+
+    /// Generates a refined commit message from a conversation that ends
+    /// with the user's feedback.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider fails.
+    fn refine_message(&self, conversation: &[ChatMessage]) -> Result<(String, Option<String>)> {
+        copilot::refine_message_with_provider(self, conversation)
+    }
+}
+
+/// A provider wrapping another one, e.g. to retry, meter, or redact its
+/// requests.
+///
+/// Every [`ProviderLayer`] is an [`AiProvider`] that delegates to
+/// [`ProviderLayer::inner`]; a layer only overrides what it changes.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use commit_wizard::provider::{AiProvider, ProviderLayer};
+///
+/// /// Shouts every prompt.
+/// struct Shouting(Box<dyn AiProvider>);
+///
+/// impl ProviderLayer for Shouting {
+///     fn inner(&self) -> &dyn AiProvider {
+///         self.0.as_ref()
+///     }
+///
+///     fn complete(&self, prompt: &str) -> Result<String> {
+///         self.inner().complete(&prompt.to_uppercase())
+///     }
+/// }
+/// ```
+pub trait ProviderLayer: Send + Sync {
+    /// Returns the wrapped provider.
+    fn inner(&self) -> &dyn AiProvider;
+
+    /// See [`AiProvider::name`].
+    fn name(&self) -> &str {
+        self.inner().name()
+    }
+
+    /// See [`AiProvider::is_available`].
+    fn is_available(&self) -> bool {
+        self.inner().is_available()
+    }
+
+    /// See [`AiProvider::unavailable_reason`].
+    fn unavailable_reason(&self) -> String {
+        self.inner().unavailable_reason()
+    }
+
+    /// See [`AiProvider::model_name`].
+    fn model_name(&self) -> Option<&str> {
+        self.inner().model_name()
+    }
+
+    /// See [`AiProvider::diff_token_budget`].
+    fn diff_token_budget(&self) -> usize {
+        self.inner().diff_token_budget()
+    }
+
+    /// See [`AiProvider::excludes`].
+    fn excludes(&self, path: &str) -> bool {
+        self.inner().excludes(path)
+    }
+
+    /// See [`AiProvider::retry_notice`].
+    fn retry_notice(&self) -> Option<String> {
+        self.inner().retry_notice()
+    }
+
+    /// See [`AiProvider::complete`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the wrapped provider.
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.inner().complete(prompt)
+    }
+
+    /// See [`AiProvider::chat`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the wrapped provider.
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        self.inner().chat(messages)
+    }
+
+    /// See [`AiProvider::complete_json`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the wrapped provider.
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        self.inner().complete_json(prompt)
+    }
+
+    /// See [`AiProvider::complete_n`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the wrapped provider.
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        self.inner().complete_n(prompt, n)
+    }
+}
+
+impl<L: ProviderLayer> AiProvider for L {
+    fn name(&self) -> &str {
+        ProviderLayer::name(self)
+    }
+
+    fn is_available(&self) -> bool {
+        ProviderLayer::is_available(self)
+    }
+
+    fn unavailable_reason(&self) -> String {
+        ProviderLayer::unavailable_reason(self)
+    }
+
+    fn model_name(&self) -> Option<&str> {
+        ProviderLayer::model_name(self)
+    }
+
+    fn diff_token_budget(&self) -> usize {
+        ProviderLayer::diff_token_budget(self)
+    }
+
+    fn excludes(&self, path: &str) -> bool {
+        ProviderLayer::excludes(self, path)
+    }
+
+    fn retry_notice(&self) -> Option<String> {
+        ProviderLayer::retry_notice(self)
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        ProviderLayer::complete(self, prompt)
+    }
+
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        ProviderLayer::chat(self, messages)
+    }
+
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        ProviderLayer::complete_json(self, prompt)
+    }
+
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        ProviderLayer::complete_n(self, prompt, n)
+    }
+}
+
+/// Renders a conversation as a single prompt for providers without a chat
+/// API.
+///
+/// A conversation with a single user message is returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::provider::{flatten_conversation, ChatMessage};
+///
+/// let prompt = flatten_conversation(&[
+///     ChatMessage::user("Write a message"),
+///     ChatMessage::assistant("add parser"),
+///     ChatMessage::user("shorter"),
+/// ]);
+/// assert!(prompt.contains("ASSISTANT:\nadd parser"));
+/// assert!(prompt.ends_with("USER:\nshorter"));
+/// ```
+pub fn flatten_conversation(messages: &[ChatMessage]) -> String {
+    if let [message] = messages {
+        if message.role == ChatRole::User {
+            return message.content.clone();
+        }
+    }
+
+    let mut prompt = String::from(
+        "Continue this conversation. Answer the last USER message, following the output format it asks for.\n",
+    );
+    for message in messages {
+        prompt.push_str(&format!(
+            "\n{}:\n{}\n",
+            message.role.as_str().to_uppercase(),
+            message.content
+        ));
+    }
+    prompt.trim_end().to_string()
+}
+
+/// Builds the `messages` array for a chat API, starting with the system
+/// prompt.
+fn chat_messages_json(messages: &[ChatMessage]) -> serde_json::Value {
+    std::iter::once(serde_json::json!({ "role": "system", "content": SYSTEM_PROMPT }))
+        .chain(
+            messages
+                .iter()
+                .map(|m| serde_json::json!({ "role": m.role.as_str(), "content": m.content })),
+        )
+        .collect()
+}
+
+/// Which token limit field a chat completion API expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenLimitField {
+    /// `max_tokens` (GitHub Models)
+    MaxTokens,
+    /// `max_completion_tokens` (OpenAI)
+    MaxCompletionTokens,
+}
+
+/// Provider for OpenAI-compatible chat completion APIs.
+#[derive(Debug, Clone)]
+pub struct HttpChatProvider {
+    name: String,
+    api_url: String,
+    token: Option<String>,
+    model: String,
+    token_limit_field: TokenLimitField,
+    timeout: Duration,
+    token_vars: Option<&'static str>,
+    network: NetworkSettings,
+}
+
+/// Response from a chat completion API
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+/// Individual choice from a chat completion response
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ResponseMessage,
+}
+
+/// Message in a chat completion response
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    content: String,
+}
+
+impl HttpChatProvider {
+    /// Creates a provider for an OpenAI-compatible endpoint.
+    ///
+    /// The provider is unavailable when `token` is [`None`].
+    pub fn new(
+        name: impl Into<String>,
+        api_url: impl Into<String>,
+        token: Option<String>,
+        model: impl Into<String>,
+        token_limit_field: TokenLimitField,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            api_url: api_url.into(),
+            token,
+            model: model.into(),
+            token_limit_field,
+            timeout: API_TIMEOUT,
+            token_vars: None,
+            network: NetworkSettings::default(),
+        }
+    }
+
+    /// Replaces the default request timeout of 60 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends requests through the proxy and with the certificate
+    /// authorities of `network`.
+    pub fn with_network(mut self, network: NetworkSettings) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Creates the GitHub Models provider from `GITHUB_TOKEN` / `GH_TOKEN`.
+    ///
+    /// The model is `model`, `GITHUB_COPILOT_MODEL`, or the default.
+    pub fn github_models(model: Option<&str>) -> Self {
+        let token = env_token("GITHUB_TOKEN").or_else(|| env_token("GH_TOKEN"));
+        let model = model
+            .map(str::to_string)
+            .or_else(|| env::var("GITHUB_COPILOT_MODEL").ok())
+            .unwrap_or_else(|| GITHUB_MODELS_DEFAULT_MODEL.to_string());
+        Self {
+            token_vars: Some("GITHUB_TOKEN or GH_TOKEN"),
+            ..Self::new(
+                "GitHub Models",
+                GITHUB_MODELS_API_URL,
+                token,
+                model,
+                TokenLimitField::MaxTokens,
+            )
+        }
+    }
+
+    /// Creates the OpenAI provider from `OPENAI_API_KEY`.
+    ///
+    /// The model is `model`, `OPENAI_MODEL`, or the default.
+    pub fn openai(model: Option<&str>) -> Self {
+        let model = model
+            .map(str::to_string)
+            .or_else(|| env::var("OPENAI_MODEL").ok())
+            .unwrap_or_else(|| OPENAI_DEFAULT_MODEL.to_string());
+        Self {
+            token_vars: Some("OPENAI_API_KEY"),
+            ..Self::new(
+                "OpenAI",
+                OPENAI_API_URL,
+                env_token("OPENAI_API_KEY"),
+                model,
+                TokenLimitField::MaxCompletionTokens,
+            )
+        }
+    }
+
+    /// Returns the model sent with each request.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+impl AiProvider for HttpChatProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_available(&self) -> bool {
+        self.token.is_some()
+    }
+
+    fn unavailable_reason(&self) -> String {
+        match self.token_vars {
+            Some(vars) => format!("{}: {} is not set", self.name, vars),
+            None => format!("{}: no API token is set", self.name),
+        }
+    }
+
+    fn model_name(&self) -> Option<&str> {
+        Some(&self.model)
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.chat(&[ChatMessage::user(prompt)])
+    }
+
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        self.request(messages, 1, false)?
+            .into_iter()
+            .next()
+            .with_context(|| format!("No response from {} API", self.name))
+    }
+
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        self.request(&[ChatMessage::user(prompt)], 1, true)?
+            .into_iter()
+            .next()
+            .with_context(|| format!("No response from {} API", self.name))
+    }
+
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        self.request(&[ChatMessage::user(prompt)], n, false)
+    }
+}
+
+impl HttpChatProvider {
+    /// Sends a chat completion request asking for `n` choices, optionally in
+    /// JSON mode.
+    fn request(&self, messages: &[ChatMessage], n: usize, json: bool) -> Result<Vec<String>> {
+        let token = self
+            .token
+            .as_deref()
+            .with_context(|| format!("No API token configured for {}", self.name))?;
+
+        let limit_field = match self.token_limit_field {
+            TokenLimitField::MaxTokens => "max_tokens",
+            TokenLimitField::MaxCompletionTokens => "max_completion_tokens",
+        };
+        let mut request = serde_json::json!({
+            "model": self.model,
+            "messages": chat_messages_json(messages),
+            "temperature": 0.3,
+        });
+        request[limit_field] = MAX_RESPONSE_TOKENS.into();
+        if n > 1 {
+            request["n"] = n.into();
+        }
+        if json {
+            request["response_format"] = serde_json::json!({ "type": "json_object" });
+        }
+
+        let prompt_length = messages.iter().map(|m| m.content.len()).sum();
+        crate::logging::log_api_request(&self.name, &self.model, prompt_length);
+
+        let client = network::client_builder(&self.network)?
+            .timeout(self.timeout)
+            .build()
+            .context("Failed to create HTTP client")?;
+        let response = client
+            .post(&self.api_url)
+            .bearer_auth(token)
+            .json(&request)
+            .send()
+            .map_err(|e| network::explain_send_error(e, &self.network))
+            .with_context(|| format!("Failed to send request to {} API", self.name))?;
+
+        if !response.status().is_success() {
+            crate::logging::log_api_response(&self.name, false, None);
+            let api = format!("{} API", self.name);
+            return Err(ApiError::from_response(&api, response).into());
+        }
+
+        let response: ChatResponse = response
+            .json()
+            .with_context(|| format!("Failed to parse {} API response", self.name))?;
+        let contents: Vec<String> = response
+            .choices
+            .into_iter()
+            .map(|choice| choice.message.content)
+            .collect();
+
+        let length = contents.iter().map(String::len).sum();
+        crate::logging::log_api_response(&self.name, true, Some(length));
+        Ok(contents)
+    }
+}
+
+/// Provider for a local [Ollama](https://ollama.com) server.
+///
+/// Prompts and diffs never leave the machine, which makes this provider
+/// suitable for offline use and for code that must not be sent to cloud APIs.
+#[derive(Debug, Clone)]
+pub struct OllamaProvider {
+    base_url: String,
+    model: String,
+    timeout: Duration,
+    network: NetworkSettings,
+}
+
+/// Response from the Ollama chat API
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    message: ResponseMessage,
+}
+
+/// Response from the Ollama model list API
+#[derive(Debug, Deserialize)]
+struct OllamaTagsResponse {
+    models: Vec<OllamaModel>,
+}
+
+/// A locally installed Ollama model
+#[derive(Debug, Deserialize)]
+struct OllamaModel {
+    name: String,
+}
+
+impl OllamaProvider {
+    /// Creates a provider for the Ollama server at `base_url`.
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            model: model.into(),
+            timeout: OLLAMA_TIMEOUT,
+            network: NetworkSettings::default(),
+        }
+    }
+
+    /// Replaces the default request timeout of 5 minutes.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends requests through the proxy and with the certificate
+    /// authorities of `network`.
+    pub fn with_network(mut self, network: NetworkSettings) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Creates the provider from configuration.
+    ///
+    /// The server is `base_url`, `OLLAMA_HOST`, or `http://localhost:11434`;
+    /// the model is `model`, `OLLAMA_MODEL`, or `llama3`; the request
+    /// timeout is `timeout` or 5 minutes.
+    pub fn from_config(config: &AiConfig) -> Self {
+        let base_url = config
+            .base_url
+            .clone()
+            .or_else(|| env_token("OLLAMA_HOST").map(|host| normalize_ollama_host(&host)))
+            .unwrap_or_else(|| OLLAMA_DEFAULT_URL.to_string());
+        let model = config
+            .model
+            .clone()
+            .or_else(|| env_token("OLLAMA_MODEL"))
+            .unwrap_or_else(|| OLLAMA_DEFAULT_MODEL.to_string());
+        let timeout = config.timeout.map_or(OLLAMA_TIMEOUT, Duration::from_secs);
+        Self::new(base_url, model).with_timeout(timeout)
+    }
+
+    /// Returns the model sent with each request.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Returns whether `installed` (e.g. `llama3:latest`) is the configured model.
+    fn matches_model(&self, installed: &str) -> bool {
+        installed == self.model
+            || (!self.model.contains(':')
+                && installed
+                    .strip_suffix(":latest")
+                    .is_some_and(|name| name == self.model))
+    }
+}
+
+impl AiProvider for OllamaProvider {
+    fn name(&self) -> &str {
+        "Ollama"
+    }
+
+    fn model_name(&self) -> Option<&str> {
+        Some(&self.model)
+    }
+
+    fn is_available(&self) -> bool {
+        let client = network::client_builder(&self.network).and_then(|builder| {
+            builder
+                .timeout(OLLAMA_PROBE_TIMEOUT)
+                .build()
+                .context("Failed to create HTTP client")
+        });
+        let client = match client {
+            Ok(client) => client,
+            Err(e) => {
+                log::warn!("Ollama: {:#}", e);
+                return false;
+            }
+        };
+        let tags = client
+            .get(format!("{}/api/tags", self.base_url))
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json::<OllamaTagsResponse>());
+
+        match tags {
+            Ok(tags) if tags.models.iter().any(|m| self.matches_model(&m.name)) => true,
+            Ok(_) => {
+                log::warn!(
+                    "Ollama model '{}' is not installed; run: ollama pull {}",
+                    self.model,
+                    self.model
+                );
+                false
+            }
+            Err(e) => {
+                log::debug!("Ollama not reachable at {}: {}", self.base_url, e);
+                false
+            }
+        }
+    }
+
+    fn unavailable_reason(&self) -> String {
+        format!(
+            "Ollama: no server at {} or model '{}' not installed (ollama pull {})",
+            self.base_url, self.model, self.model
+        )
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.chat(&[ChatMessage::user(prompt)])
+    }
+
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        self.request(&[ChatMessage::user(prompt)], true)
+    }
+
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        self.request(messages, false)
+    }
+}
+
+impl OllamaProvider {
+    /// Sends a chat request, optionally in JSON mode.
+    fn request(&self, messages: &[ChatMessage], json: bool) -> Result<String> {
+        let mut request = serde_json::json!({
+            "model": self.model,
+            "messages": chat_messages_json(messages),
+            "stream": false,
+            "options": { "temperature": 0.3 },
+        });
+        if json {
+            request["format"] = "json".into();
+        }
+
+        let prompt_length = messages.iter().map(|m| m.content.len()).sum();
+        crate::logging::log_api_request("Ollama", &self.model, prompt_length);
+
+        let client = network::client_builder(&self.network)?
+            .timeout(self.timeout)
+            .build()
+            .context("Failed to create HTTP client")?;
+        let response = client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&request)
+            .send()
+            .map_err(|e| network::explain_send_error(e, &self.network))
+            .with_context(|| format!("Failed to reach Ollama at {}", self.base_url))?;
+
+        if !response.status().is_success() {
+            crate::logging::log_api_response("Ollama", false, None);
+            return Err(ApiError::from_response("Ollama", response).into());
+        }
+
+        let content = response
+            .json::<OllamaChatResponse>()
+            .context("Failed to parse Ollama response")?
+            .message
+            .content;
+
+        crate::logging::log_api_response("Ollama", true, Some(content.len()));
+        Ok(content)
+    }
+}
+
+/// Turns an `OLLAMA_HOST` value such as `0.0.0.0:11434` into a base URL.
+fn normalize_ollama_host(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/');
+    if host.starts_with("http://") || host.starts_with("https://") {
+        host.to_string()
+    } else {
+        format!("http://{}", host.replace("0.0.0.0", "localhost"))
+    }
+}
+
+/// Creates a provider of the given kind without checking availability.
+///
+/// For [`ProviderKind::Auto`] the Copilot CLI provider is returned. A
+/// configured `timeout` replaces the provider's default request timeout;
+/// HTTP providers use the proxy and certificate authorities of `network`.
+pub fn create_provider(
+    kind: ProviderKind,
+    config: &AiConfig,
+    network: &NetworkConfig,
+) -> Box<dyn AiProvider> {
+    let model = config.model.as_deref();
+    let timeout = config.timeout.map(Duration::from_secs);
+    match kind {
+        ProviderKind::Auto | ProviderKind::Copilot => Box::new(CopilotCliProvider { timeout }),
+        ProviderKind::GithubModels => Box::new(
+            HttpChatProvider::github_models(model)
+                .with_timeout(timeout.unwrap_or(API_TIMEOUT))
+                .with_network(network.for_service(Service::GithubModels)),
+        ),
+        ProviderKind::Openai => Box::new(
+            HttpChatProvider::openai(model)
+                .with_timeout(timeout.unwrap_or(API_TIMEOUT))
+                .with_network(network.for_service(Service::OpenAi)),
+        ),
+        ProviderKind::Ollama => Box::new(
+            OllamaProvider::from_config(config).with_network(network.for_service(Service::Ollama)),
+        ),
+    }
+}
+
+/// Selects an available provider.
+///
+/// For [`ProviderKind::Auto`] the Copilot CLI, GitHub Models, OpenAI and
+/// Ollama are tried in that order, each with its own default model. An
+/// explicitly requested provider is only returned if it is available.
+///
+/// # Returns
+///
+/// The provider, or [`None`] if no matching provider is available.
+pub fn select_provider(
+    kind: ProviderKind,
+    config: &AiConfig,
+    network: &NetworkConfig,
+) -> Option<Box<dyn AiProvider>> {
+    select_provider_explained(kind, config, network).ok()
+}
+
+/// Selects an available provider like [`select_provider`], explaining why
+/// none is.
+///
+/// # Errors
+///
+/// Returns the [`AiProvider::unavailable_reason`] of every candidate if none
+/// is available.
+pub fn select_provider_explained(
+    kind: ProviderKind,
+    config: &AiConfig,
+    network: &NetworkConfig,
+) -> std::result::Result<Box<dyn AiProvider>, Vec<String>> {
+    // A model name belongs to one provider, so `model` only applies to an
+    // explicitly selected provider
+    let auto_config;
+    let (candidates, config) = match kind {
+        ProviderKind::Auto => {
+            auto_config = AiConfig {
+                model: None,
+                ..config.clone()
+            };
+            (
+                vec![
+                    ProviderKind::Copilot,
+                    ProviderKind::GithubModels,
+                    ProviderKind::Openai,
+                    ProviderKind::Ollama,
+                ],
+                &auto_config,
+            )
+        }
+        kind => (vec![kind], config),
+    };
+
+    let mut reasons = Vec::new();
+    for kind in candidates {
+        let provider = create_provider(kind, config, network);
+        let available = provider.is_available();
+        log::debug!("AI provider {} available: {}", provider.name(), available);
+        if available {
+            return Ok(provider);
+        }
+        reasons.push(provider.unavailable_reason());
+    }
+    Err(reasons)
+}
+
+/// Reads a non-empty token from an environment variable.
+fn env_token(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|t| !t.trim().is_empty())
+}
@@ -0,0 +1,381 @@
+//! Release tagging for simple release flows.
+//!
+//! When a committed group's header matches the configured release pattern
+//! (e.g. `chore(release): 1.4.0`), the TUI offers to create an annotated tag
+//! whose message contains release notes generated from the commits since the
+//! previous tag. Before such a commit, [`check_release_changelog`] compares
+//! the changelog entries it adds with those commits.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use git2::{Oid, Repository, Sort};
+use regex::Regex;
+
+use crate::config::ReleaseConfig;
+use crate::types::ChangedFile;
+
+/// A tag offered after a release commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagProposal {
+    /// Release version extracted from the commit header
+    pub version: String,
+    /// Name of the tag to create
+    pub tag_name: String,
+    /// Whether the tag should be GPG-signed
+    pub sign: bool,
+}
+
+/// Compiled release detection rules.
+#[derive(Debug, Clone)]
+pub struct ReleasePolicy {
+    pattern: Regex,
+    tag_format: String,
+    sign: bool,
+}
+
+impl ReleasePolicy {
+    /// Compiles the release policy from configuration, if enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is invalid or the tag format does not
+    /// contain `{version}`.
+    pub fn from_config(config: &ReleaseConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        if !config.tag_format.contains("{version}") {
+            bail!("Release tag format must contain {{version}}");
+        }
+
+        let pattern = Regex::new(&config.pattern)
+            .with_context(|| format!("Invalid release pattern '{}'", config.pattern))?;
+
+        Ok(Some(Self {
+            pattern,
+            tag_format: config.tag_format.clone(),
+            sign: config.sign,
+        }))
+    }
+
+    /// Returns a tag proposal if `header` is a release commit header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::config::ReleaseConfig;
+    /// use commit_wizard::release::ReleasePolicy;
+    ///
+    /// let policy = ReleasePolicy::from_config(&ReleaseConfig::default()).unwrap().unwrap();
+    /// let proposal = policy.match_header("chore(release): 1.4.0").unwrap();
+    /// assert_eq!(proposal.tag_name, "v1.4.0");
+    /// assert!(policy.match_header("chore(deps): bump serde").is_none());
+    /// ```
+    pub fn match_header(&self, header: &str) -> Option<TagProposal> {
+        let captures = self.pattern.captures(header.trim())?;
+        let version = captures
+            .name("version")
+            .or_else(|| captures.get(1))?
+            .as_str()
+            .to_string();
+
+        let tag_name = self.tag_format.replace("{version}", &version);
+        if !git2::Reference::is_valid_name(&format!("refs/tags/{}", tag_name)) {
+            log::warn!(
+                "Ignoring release commit with invalid tag name '{}'",
+                tag_name
+            );
+            return None;
+        }
+
+        Some(TagProposal {
+            version,
+            tag_name,
+            sign: self.sign,
+        })
+    }
+}
+
+/// Generates release notes from the commits since the previous tag.
+///
+/// HEAD is expected to be the release commit itself and is left out of the
+/// notes, as is everything reachable from an existing tag. Commit subjects are grouped by conventional commit type.
+///
+/// # Errors
+///
+/// Returns an error if the history cannot be walked.
+pub fn generate_release_notes(repo: &Repository, version: &str) -> Result<String> {
+    let head = repo
+        .head()
+        .and_then(|h| h.peel_to_commit())
+        .context("Failed to resolve HEAD")?;
+    let Some(parent) = head.parents().next() else {
+        return Ok(format!("Release {}\n", version));
+    };
+
+    let mut sections: BTreeMap<usize, (&str, Vec<String>)> = BTreeMap::new();
+    for (_, subject) in commits_since_tag(repo, parent.id())? {
+        let (order, title) = section_for(&subject);
+        sections
+            .entry(order)
+            .or_insert_with(|| (title, Vec::new()))
+            .1
+            .push(subject);
+    }
+
+    let mut notes = format!("Release {}\n", version);
+    for (title, subjects) in sections.values() {
+        notes.push_str(&format!("\n{}:\n", title));
+        for subject in subjects {
+            notes.push_str(&format!("- {}\n", subject));
+        }
+    }
+    Ok(notes)
+}
+
+/// Result of comparing a release's changelog entries with its commits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangelogCheck {
+    /// Number of commits since the previous tag
+    pub commits: usize,
+    /// Number of those commits referenced by an added entry
+    pub referenced: usize,
+    /// Features, fixes and other notable commits no added entry mentions
+    pub unreferenced: Vec<String>,
+    /// Commits mentioned by several added entries or already listed under
+    /// an earlier release
+    pub double_counted: Vec<String>,
+}
+
+impl ChangelogCheck {
+    /// Returns whether every notable commit is listed exactly once.
+    pub fn is_clean(&self) -> bool {
+        self.unreferenced.is_empty() && self.double_counted.is_empty()
+    }
+}
+
+impl fmt::Display for ChangelogCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} commit(s) since the previous tag, {} referenced",
+            self.commits, self.referenced
+        )?;
+        for commit in &self.unreferenced {
+            write!(f, "\n  • missing: {}", commit)?;
+        }
+        for commit in &self.double_counted {
+            write!(f, "\n  • counted twice: {}", commit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares added changelog entries with the commits of a release.
+///
+/// `commits` are `(short id, subject)` pairs. An entry references a commit
+/// if it contains the commit's short id or the description of its subject
+/// (the part after `type(scope):`), ignoring case and markdown emphasis.
+/// Only `feat`, `fix`, `perf`, `revert` and breaking commits must be
+/// referenced. A commit is double-counted if several added entries mention
+/// it or `previous` (the changelog before this release) already does.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::release::check_changelog;
+///
+/// let commits = [
+///     ("a1b2c3d".to_string(), "feat(api): add export".to_string()),
+///     ("e4f5a6b".to_string(), "fix: handle timeouts".to_string()),
+///     ("c7d8e9f".to_string(), "chore: bump deps".to_string()),
+/// ];
+/// let added = ["- **api:** Add export".to_string()];
+///
+/// let check = check_changelog(&commits, &added, "");
+/// assert_eq!(check.referenced, 1);
+/// assert_eq!(check.unreferenced, ["e4f5a6b fix: handle timeouts"]);
+/// ```
+pub fn check_changelog(
+    commits: &[(String, String)],
+    added: &[String],
+    previous: &str,
+) -> ChangelogCheck {
+    let added: Vec<String> = added.iter().map(|entry| normalize(entry)).collect();
+    let previous: Vec<String> = previous.lines().map(normalize).collect();
+
+    let mut check = ChangelogCheck {
+        commits: commits.len(),
+        ..Default::default()
+    };
+    for (id, subject) in commits {
+        let description = normalize(description_of(subject));
+        let mentions = |entry: &String| {
+            (!description.is_empty() && entry.contains(&description))
+                || entry.contains(&id.to_lowercase())
+        };
+        let label = format!("{} {}", id, subject);
+
+        match added.iter().filter(|entry| mentions(entry)).count() {
+            0 if is_notable(subject) => check.unreferenced.push(label),
+            0 => {}
+            1 if previous.iter().any(mentions) => {
+                check.referenced += 1;
+                check.double_counted.push(label);
+            }
+            1 => check.referenced += 1,
+            _ => {
+                check.referenced += 1;
+                check.double_counted.push(label);
+            }
+        }
+    }
+    check
+}
+
+/// Checks the changelog a release commit adds against the commits since the
+/// previous tag.
+///
+/// The changelog is the first file of `files` named `CHANGELOG*`,
+/// `CHANGES*` or `HISTORY*`. Its added entries are the list items of the
+/// working tree version that are not in the HEAD version. HEAD is the last
+/// commit of the release, as the release commit is not made yet.
+///
+/// # Returns
+///
+/// [`None`] if `files` contain no changelog.
+///
+/// # Errors
+///
+/// Returns an error if the changelog cannot be read or the history cannot be
+/// walked.
+pub fn check_release_changelog(
+    repo: &Repository,
+    files: &[ChangedFile],
+) -> Result<Option<ChangelogCheck>> {
+    let Some(file) = files.iter().find(|f| is_changelog(&f.path)) else {
+        return Ok(None);
+    };
+    let workdir = repo.workdir().context("Repository has no working tree")?;
+    let current = std::fs::read_to_string(workdir.join(&file.path))
+        .with_context(|| format!("Failed to read {}", file.path))?;
+
+    let head = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let previous = head
+        .as_ref()
+        .and_then(|commit| commit.tree().ok())
+        .and_then(|tree| tree.get_path(Path::new(&file.path)).ok())
+        .and_then(|entry| repo.find_blob(entry.id()).ok())
+        .map(|blob| String::from_utf8_lossy(blob.content()).into_owned())
+        .unwrap_or_default();
+
+    let old_lines: HashSet<&str> = previous.lines().map(str::trim).collect();
+    let added: Vec<String> = current
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with(['-', '*', '+']) && !old_lines.contains(line))
+        .map(str::to_string)
+        .collect();
+
+    let commits = match head {
+        Some(head) => commits_since_tag(repo, head.id())?
+            .into_iter()
+            .map(|(oid, subject)| (oid.to_string()[..7].to_string(), subject))
+            .collect(),
+        None => Vec::new(),
+    };
+    Ok(Some(check_changelog(&commits, &added, &previous)))
+}
+
+/// Returns the ids and subjects of the commits reachable from `start` but
+/// not from any tag, newest first.
+fn commits_since_tag(repo: &Repository, start: Oid) -> Result<Vec<(Oid, String)>> {
+    let tagged = tagged_commits(repo)?;
+    let mut walk = repo.revwalk().context("Failed to walk history")?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    walk.push(start)?;
+    for oid in tagged {
+        walk.hide(oid)?;
+    }
+
+    let mut commits = Vec::new();
+    for oid in walk {
+        let oid = oid?;
+        let commit = repo.find_commit(oid)?;
+        let subject = commit.summary().unwrap_or_default().trim().to_string();
+        if !subject.is_empty() {
+            commits.push((oid, subject));
+        }
+    }
+    Ok(commits)
+}
+
+/// Returns whether `path` names a changelog file.
+fn is_changelog(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    ["changelog", "changes", "history"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// Returns whether a commit must be listed in the changelog.
+fn is_notable(subject: &str) -> bool {
+    let header = subject.split(':').next().unwrap_or_default();
+    header.ends_with('!')
+        || matches!(
+            header.split('(').next().unwrap_or_default().trim(),
+            "feat" | "fix" | "perf" | "revert"
+        )
+}
+
+/// Returns the description of a conventional commit subject.
+fn description_of(subject: &str) -> &str {
+    match subject.split_once(": ") {
+        Some((header, description)) if !header.contains(' ') => description,
+        _ => subject,
+    }
+}
+
+/// Lowercases `text`, drops markdown emphasis and collapses whitespace.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .replace(['*', '`', '_'], "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the commits that any tag points to.
+fn tagged_commits(repo: &Repository) -> Result<HashSet<Oid>> {
+    let mut tagged = HashSet::new();
+    repo.tag_foreach(|oid, _name| {
+        if let Ok(commit) = repo
+            .find_object(oid, None)
+            .and_then(|obj| obj.peel_to_commit())
+        {
+            tagged.insert(commit.id());
+        }
+        true
+    })
+    .context("Failed to list tags")?;
+    Ok(tagged)
+}
+
+/// Maps a commit subject to its release-notes section.
+fn section_for(subject: &str) -> (usize, &'static str) {
+    let commit_type = subject
+        .split([':', '(', '!'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    match commit_type {
+        "feat" => (0, "Features"),
+        "fix" => (1, "Bug Fixes"),
+        "perf" => (2, "Performance"),
+        "docs" => (3, "Documentation"),
+        _ => (4, "Other Changes"),
+    }
+}
@@ -0,0 +1,102 @@
+//! Remote selection for pushing and pull requests.
+//!
+//! Repositories often have several remotes (`origin`, `upstream`, a fork).
+//! This module resolves which remote to push to and which remote pull
+//! requests should target, supporting triangular workflows where changes are
+//! pushed to a fork but pull requests are opened against upstream.
+
+use anyhow::{Context, Result};
+use git2::Repository;
+
+use crate::config::RemoteConfig;
+
+/// Remotes resolved for the current branch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteTargets {
+    /// All configured remotes, sorted by name
+    pub remotes: Vec<String>,
+    /// Remote to push the current branch to, if it could be determined
+    pub push: Option<String>,
+    /// Remote that pull requests should target, if it could be determined
+    pub pr_base: Option<String>,
+}
+
+impl RemoteTargets {
+    /// Returns whether pushing and pull requests go to different remotes.
+    pub fn is_triangular(&self) -> bool {
+        matches!((&self.push, &self.pr_base), (Some(push), Some(base)) if push != base)
+    }
+}
+
+/// Lists the names of all remotes, sorted alphabetically.
+///
+/// # Errors
+///
+/// Returns an error if the remotes cannot be read.
+pub fn list_remotes(repo: &Repository) -> Result<Vec<String>> {
+    let names = repo.remotes().context("Failed to list remotes")?;
+    let mut remotes: Vec<String> = names.iter().flatten().map(str::to_string).collect();
+    remotes.sort();
+    Ok(remotes)
+}
+
+/// Resolves the push and pull request remotes for `branch`.
+///
+/// The push remote is taken from, in order: the `push` setting, git's
+/// `branch.<name>.pushRemote`, `remote.pushDefault`, the branch's upstream
+/// remote, `origin`, or the only remote.
+///
+/// The pull request remote is taken from, in order: the `pr_base` setting,
+/// a remote named `upstream`, the branch's upstream remote, `origin`, or the
+/// only remote.
+///
+/// Configured names that do not exist as remotes are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the remotes cannot be read.
+pub fn resolve_remotes(
+    repo: &Repository,
+    branch: &str,
+    config: &RemoteConfig,
+) -> Result<RemoteTargets> {
+    let remotes = list_remotes(repo)?;
+    let git_config = repo.config().context("Failed to read git config")?;
+    let get = |key: &str| git_config.get_string(key).ok();
+
+    let branch_remote = get(&format!("branch.{}.remote", branch)).filter(|r| r != ".");
+    let sole_remote = match remotes.as_slice() {
+        [only] => Some(only.clone()),
+        _ => None,
+    };
+    let exists = |name: &String| remotes.contains(name);
+
+    let push = [
+        config.push.clone(),
+        get(&format!("branch.{}.pushRemote", branch)),
+        get("remote.pushDefault"),
+        branch_remote.clone(),
+        Some("origin".to_string()),
+        sole_remote.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .find(exists);
+
+    let pr_base = [
+        config.pr_base.clone(),
+        Some("upstream".to_string()),
+        branch_remote,
+        Some("origin".to_string()),
+        sole_remote,
+    ]
+    .into_iter()
+    .flatten()
+    .find(exists);
+
+    Ok(RemoteTargets {
+        remotes,
+        push,
+        pr_base,
+    })
+}
@@ -0,0 +1,202 @@
+//! Collecting and grouping the changes again during a session.
+//!
+//! With `--watch`, the TUI learns when files in the working tree change
+//! (see the `watch` module). It then asks git whether the plan is outdated:
+//! files started or stopped being changed, or planned files were edited
+//! since the plan was made. If so, a banner offers to refresh the groups
+//! with `R`, which runs the [`Replanner`] set up at startup to repeat the
+//! collection and analysis of the changed files. Groups whose files are
+//! unchanged keep their edited messages. `R` also works without `--watch`,
+//! e.g. after files were edited in another window.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Result;
+use git2::Repository;
+
+use crate::fingerprint::Snapshot;
+use crate::git::{collect_changed_files, collect_untracked_files};
+use crate::types::{ChangeGroup, ChangedFile};
+
+/// Changed files collected and analyzed again.
+#[derive(Debug, Clone, Default)]
+pub struct Replan {
+    /// Changed files with their analysis, ready for grouping
+    pub files: Vec<ChangedFile>,
+    /// Content hashes of the files
+    pub snapshot: Snapshot,
+}
+
+/// Collects and analyzes the changed files the way startup did.
+pub type Replanner = Box<dyn FnMut() -> Result<Replan>>;
+
+/// What refreshing the groups found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RescanReport {
+    /// Changed files that were not planned before
+    pub added: usize,
+    /// Planned files that are no longer changed
+    pub removed: usize,
+    /// Number of groups after the refresh
+    pub groups: usize,
+    /// Number of groups that kept their message
+    pub kept: usize,
+}
+
+impl RescanReport {
+    /// Compares the files of the uncommitted `old` groups with the changed
+    /// `files` found by the refresh.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::replan::RescanReport;
+    /// use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+    /// use git2::Status;
+    ///
+    /// let file = |path: &str| ChangedFile::new(path.into(), Status::WT_MODIFIED);
+    /// let old = vec![ChangeGroup::new(
+    ///     CommitType::Fix,
+    ///     None,
+    ///     vec![file("a.rs"), file("b.rs")],
+    ///     None,
+    ///     "fix a and b".into(),
+    ///     vec![],
+    /// )];
+    ///
+    /// let report = RescanReport::compare(&old, &[file("b.rs"), file("c.rs"), file("d.rs")]);
+    /// assert_eq!((report.added, report.removed), (2, 1));
+    /// ```
+    pub fn compare(old: &[ChangeGroup], files: &[ChangedFile]) -> Self {
+        let planned: HashSet<&str> = old
+            .iter()
+            .filter(|g| !g.is_committed())
+            .flat_map(|g| g.files.iter().map(|f| f.path.as_str()))
+            .collect();
+        let found: HashSet<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        Self {
+            added: found.difference(&planned).count(),
+            removed: planned.difference(&found).count(),
+            ..Self::default()
+        }
+    }
+}
+
+impl fmt::Display for RescanReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.added == 0 && self.removed == 0 {
+            write!(f, "no new or removed files")?;
+        } else {
+            write!(f, "{} new, {} removed file(s)", self.added, self.removed)?;
+        }
+        write!(
+            f,
+            "; {} group(s), {} kept their message",
+            self.groups, self.kept
+        )
+    }
+}
+
+/// Returns whether the uncommitted groups no longer describe the working
+/// tree.
+///
+/// The plan is outdated if a tracked file is changed but not planned, a
+/// planned file is no longer changed, or a planned file was edited since
+/// `snapshot` was taken. Untracked files are only considered if planned,
+/// so new build output does not count as a change. Files `left_out` of the
+/// groups count as planned, but editing them does not outdate the plan.
+///
+/// # Errors
+///
+/// Returns an error if the status cannot be read or a file cannot be
+/// hashed.
+pub fn plan_outdated(
+    repo: &Repository,
+    workdir: &Path,
+    groups: &[ChangeGroup],
+    left_out: &[ChangedFile],
+    snapshot: &Snapshot,
+) -> Result<bool> {
+    let grouped: HashSet<&str> = groups
+        .iter()
+        .filter(|g| !g.is_committed())
+        .flat_map(|g| g.files.iter().map(|f| f.path.as_str()))
+        .collect();
+    let planned: HashSet<&str> = grouped
+        .iter()
+        .copied()
+        .chain(left_out.iter().map(|f| f.path.as_str()))
+        .collect();
+    let mut changed: HashSet<String> = collect_changed_files(repo, false)?
+        .into_iter()
+        .map(|f| f.path)
+        .collect();
+    for file in collect_untracked_files(repo)? {
+        if planned.contains(file.path.as_str()) {
+            changed.insert(file.path);
+        }
+    }
+    if changed.len() != planned.len() || !planned.iter().all(|path| changed.contains(*path)) {
+        return Ok(true);
+    }
+    Ok(!snapshot
+        .changed(workdir, grouped.iter().copied())?
+        .is_empty())
+}
+
+/// Copies the messages of `old` groups to the `new` groups with the same
+/// files, so a refresh keeps the messages (and whether the group is
+/// skipped) of groups it did not change.
+///
+/// Committed groups in `old` are not considered.
+///
+/// # Returns
+///
+/// The number of groups that kept their message.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::replan::carry_over_messages;
+/// use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+/// use git2::Status;
+///
+/// let group = |description: &str| {
+///     let files = vec![ChangedFile::new("src/login.rs".into(), Status::WT_MODIFIED)];
+///     ChangeGroup::new(CommitType::Feat, None, files, None, description.into(), vec![])
+/// };
+/// let old = vec![group("add the login form")];
+/// let mut new = vec![group("update login")];
+///
+/// assert_eq!(carry_over_messages(&old, &mut new), 1);
+/// assert_eq!(new[0].description, "add the login form");
+/// ```
+pub fn carry_over_messages(old: &[ChangeGroup], new: &mut [ChangeGroup]) -> usize {
+    let paths = |group: &ChangeGroup| -> BTreeSet<String> {
+        group.files.iter().map(|f| f.path.clone()).collect()
+    };
+    let previous: HashMap<BTreeSet<String>, &ChangeGroup> = old
+        .iter()
+        .filter(|g| !g.is_committed())
+        .map(|g| (paths(g), g))
+        .collect();
+
+    let mut kept = 0;
+    for group in new.iter_mut() {
+        let Some(previous) = previous.get(&paths(group)) else {
+            continue;
+        };
+        group.commit_type = previous.commit_type;
+        group.scope = previous.scope.clone();
+        group.description = previous.description.clone();
+        group.body_lines = previous.body().into_owned();
+        group.body_pending = false;
+        group.description_generated = previous.description_generated;
+        group.skipped = previous.skipped;
+        group.notes = previous.notes.clone();
+        kept += 1;
+    }
+    kept
+}
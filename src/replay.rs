@@ -0,0 +1,405 @@
+//! Recording AI exchanges and replaying them offline.
+//!
+//! With `--record <dir>`, every request to the AI provider and its response
+//! are written by a [`CapturingProvider`] to a new session directory under
+//! `<dir>`, one JSON file per request (`0001-complete_json.json`, ...).
+//! Prompts are recorded as sent, after secrets were redacted.
+//!
+//! A [`ReplayProvider`] answers from such a session without network access.
+//! By default a request is answered by the recorded exchange with the same
+//! prompt; [`ReplayProvider::in_order`] answers with the recorded responses
+//! in order whatever the prompt, so prompt changes can be evaluated against
+//! a corpus of past sessions:
+//!
+//! ```no_run
+//! use commit_wizard::replay::{load_captures, ReplayProvider};
+//!
+//! for session in std::fs::read_dir("recordings").unwrap() {
+//!     let captures = load_captures(&session.unwrap().path()).unwrap();
+//!     let provider = ReplayProvider::new(captures).in_order();
+//!     // run the grouping or message generation against `provider`
+//! }
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::provider::{flatten_conversation, AiProvider, ChatMessage, ChatRole, ProviderLayer};
+
+/// Provider method a request was sent with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestKind {
+    /// [`AiProvider::complete`]
+    Complete,
+    /// [`AiProvider::complete_json`]
+    CompleteJson,
+    /// [`AiProvider::complete_n`]
+    CompleteN,
+    /// [`AiProvider::chat`]
+    Chat,
+}
+
+impl RequestKind {
+    /// Returns the name used in capture files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Complete => "complete",
+            Self::CompleteJson => "complete_json",
+            Self::CompleteN => "complete_n",
+            Self::Chat => "chat",
+        }
+    }
+}
+
+/// One message of a recorded conversation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedMessage {
+    /// `user` or `assistant`
+    pub role: String,
+    /// Message text
+    pub content: String,
+}
+
+impl CapturedMessage {
+    /// Converts the message back into a [`ChatMessage`]; unknown roles are
+    /// read as user messages.
+    pub fn to_chat_message(&self) -> ChatMessage {
+        match self.role.as_str() {
+            "assistant" => ChatMessage {
+                role: ChatRole::Assistant,
+                content: self.content.clone(),
+            },
+            _ => ChatMessage::user(self.content.clone()),
+        }
+    }
+}
+
+/// A recorded request and its response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capture {
+    /// Name of the provider that answered
+    pub provider: String,
+    /// Model used, if the provider lets the user choose one
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Method the request was sent with
+    pub kind: RequestKind,
+    /// Prompt as sent; conversations are flattened
+    pub prompt: String,
+    /// Messages of a [`RequestKind::Chat`] request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<CapturedMessage>,
+    /// Responses; several for [`RequestKind::CompleteN`]
+    #[serde(default)]
+    pub responses: Vec<String>,
+    /// Error message of a failed request
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Reads the captures of a session directory in the order they were
+/// recorded.
+///
+/// # Errors
+///
+/// Returns an error if the directory or a capture file cannot be read.
+pub fn load_captures(dir: &Path) -> Result<Vec<Capture>> {
+    let mut paths = Vec::new();
+    for entry in
+        fs::read_dir(dir).with_context(|| format!("Failed to read recording {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            let text = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_json::from_str(&text)
+                .with_context(|| format!("Failed to parse capture {}", path.display()))
+        })
+        .collect()
+}
+
+/// Writes captures to a session directory.
+pub struct Recorder {
+    dir: PathBuf,
+    count: Mutex<usize>,
+}
+
+impl Recorder {
+    /// Creates a new session directory under `root`, named after the
+    /// current time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created.
+    pub fn create(root: &Path) -> Result<Self> {
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let mut dir = root.join(&stamp);
+        let mut suffix = 2;
+        while dir.exists() {
+            dir = root.join(format!("{}-{}", stamp, suffix));
+            suffix += 1;
+        }
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create recording {}", dir.display()))?;
+        Ok(Self {
+            dir,
+            count: Mutex::new(0),
+        })
+    }
+
+    /// Returns the session directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes `capture` as the next file of the session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write(&self, capture: &Capture) -> Result<PathBuf> {
+        let mut count = self.count.lock().unwrap_or_else(|e| e.into_inner());
+        *count += 1;
+        let path = self
+            .dir
+            .join(format!("{:04}-{}.json", *count, capture.kind.as_str()));
+        let json = serde_json::to_string_pretty(capture)?;
+        fs::write(&path, json + "\n")
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Provider that writes every exchange of its inner provider to a
+/// [`Recorder`].
+///
+/// Recording failures are logged and never fail the request.
+pub struct CapturingProvider {
+    inner: Box<dyn AiProvider>,
+    recorder: Recorder,
+}
+
+impl CapturingProvider {
+    /// Wraps `inner` so its requests are written to `recorder`.
+    pub fn new(inner: Box<dyn AiProvider>, recorder: Recorder) -> Self {
+        Self { inner, recorder }
+    }
+
+    fn captured<T>(
+        &self,
+        kind: RequestKind,
+        prompt: String,
+        messages: &[ChatMessage],
+        result: Result<T>,
+        responses: impl FnOnce(&T) -> Vec<String>,
+    ) -> Result<T> {
+        let (responses, error) = match &result {
+            Ok(value) => (responses(value), None),
+            Err(e) => (Vec::new(), Some(format!("{:#}", e))),
+        };
+        let capture = Capture {
+            provider: self.inner.name().to_string(),
+            model: self.inner.model_name().map(str::to_string),
+            kind,
+            prompt,
+            messages: messages
+                .iter()
+                .map(|message| CapturedMessage {
+                    role: message.role.as_str().to_string(),
+                    content: message.content.clone(),
+                })
+                .collect(),
+            responses,
+            error,
+        };
+        if let Err(e) = self.recorder.write(&capture) {
+            log::warn!("Failed to record AI exchange: {:#}", e);
+        }
+        result
+    }
+}
+
+impl ProviderLayer for CapturingProvider {
+    fn inner(&self) -> &dyn AiProvider {
+        self.inner.as_ref()
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        let result = self.inner.complete(prompt);
+        self.captured(
+            RequestKind::Complete,
+            prompt.to_string(),
+            &[],
+            result,
+            |r| vec![r.clone()],
+        )
+    }
+
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        let result = self.inner.complete_json(prompt);
+        self.captured(
+            RequestKind::CompleteJson,
+            prompt.to_string(),
+            &[],
+            result,
+            |r| vec![r.clone()],
+        )
+    }
+
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        let result = self.inner.complete_n(prompt, n);
+        self.captured(
+            RequestKind::CompleteN,
+            prompt.to_string(),
+            &[],
+            result,
+            |r| r.clone(),
+        )
+    }
+
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        let result = self.inner.chat(messages);
+        self.captured(
+            RequestKind::Chat,
+            flatten_conversation(messages),
+            messages,
+            result,
+            |r| vec![r.clone()],
+        )
+    }
+}
+
+/// Provider answering from recorded captures instead of an AI service.
+pub struct ReplayProvider {
+    name: String,
+    model: Option<String>,
+    captures: Vec<Capture>,
+    in_order: bool,
+    used: Mutex<Vec<bool>>,
+    prompts: Mutex<Vec<String>>,
+}
+
+impl ReplayProvider {
+    /// Creates a provider answering each request with the first unused
+    /// capture of the same kind and prompt.
+    pub fn new(captures: Vec<Capture>) -> Self {
+        let name = captures
+            .first()
+            .map(|c| format!("{} (replay)", c.provider))
+            .unwrap_or_else(|| "replay".to_string());
+        let model = captures.first().and_then(|c| c.model.clone());
+        Self {
+            name,
+            model,
+            used: Mutex::new(vec![false; captures.len()]),
+            captures,
+            in_order: false,
+            prompts: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Loads the captures of a session directory, see [`load_captures`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session cannot be read.
+    pub fn load(dir: &Path) -> Result<Self> {
+        Ok(Self::new(load_captures(dir)?))
+    }
+
+    /// Answers with the next unused capture of the same kind, whatever its
+    /// prompt, e.g. to replay a session against changed prompts.
+    pub fn in_order(mut self) -> Self {
+        self.in_order = true;
+        self
+    }
+
+    /// Returns the prompts received so far, e.g. to compare them with the
+    /// recorded ones.
+    pub fn prompts(&self) -> Vec<String> {
+        self.prompts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Returns whether every capture was replayed.
+    pub fn is_exhausted(&self) -> bool {
+        self.used
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .all(|used| *used)
+    }
+
+    fn replay(&self, kind: RequestKind, prompt: &str) -> Result<Vec<String>> {
+        self.prompts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(prompt.to_string());
+        let mut used = self.used.lock().unwrap_or_else(|e| e.into_inner());
+        let found = self.captures.iter().enumerate().position(|(idx, capture)| {
+            !used[idx] && capture.kind == kind && (self.in_order || capture.prompt == prompt)
+        });
+        let Some(idx) = found else {
+            bail!("No recorded {} response for this prompt", kind.as_str());
+        };
+        used[idx] = true;
+        let capture = &self.captures[idx];
+        match &capture.error {
+            Some(error) => bail!("{}", error),
+            None => Ok(capture.responses.clone()),
+        }
+    }
+
+    fn replay_one(&self, kind: RequestKind, prompt: &str) -> Result<String> {
+        self.replay(kind, prompt)?
+            .into_iter()
+            .next()
+            .context("The recorded exchange has no response")
+    }
+}
+
+impl AiProvider for ReplayProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn model_name(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.replay_one(RequestKind::Complete, prompt)
+    }
+
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        self.replay_one(RequestKind::CompleteJson, prompt)
+    }
+
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        let mut responses = self.replay(RequestKind::CompleteN, prompt)?;
+        responses.truncate(n.max(1));
+        Ok(responses)
+    }
+
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        self.replay_one(RequestKind::Chat, &flatten_conversation(messages))
+    }
+}
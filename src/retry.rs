@@ -0,0 +1,320 @@
+//! Retries of transient AI API failures.
+//!
+//! Rate limits (HTTP 429) and server errors (5xx) of the HTTP APIs are
+//! usually over after a few seconds. Requests failing with such an
+//! [`ApiError`] are retried with exponential backoff and jitter; when the
+//! API sends a `Retry-After` header, the wizard waits as long as it asks
+//! for. Other errors are returned right away.
+//!
+//! While a retry is pending, [`RetryingProvider`] reports it through
+//! [`AiProvider::retry_notice`] so the TUI can show it next to the spinner.
+//! Cancelling the request (see [`crate::provider::cancel_flag`]) stops the
+//! wait and sends no further retries.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use crate::config::AiConfig;
+use crate::git::Cancelled;
+use crate::provider::{cancel_flag, AiProvider, ChatMessage, ProviderLayer};
+
+/// Default number of retries after the first attempt.
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// Default delay before the first retry, in milliseconds.
+pub const DEFAULT_RETRY_DELAY_MS: u64 = 1000;
+
+/// Longest wait before a retry; APIs asking for longer are not retried.
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// How often a pending retry checks whether the request was cancelled.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Error response of an HTTP API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    /// HTTP status code
+    pub status: u16,
+    /// Wait time requested with a `Retry-After` header
+    pub retry_after: Option<Duration>,
+    message: String,
+}
+
+impl ApiError {
+    /// Creates an error with a ready-made message.
+    pub fn new(status: u16, retry_after: Option<Duration>, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            retry_after,
+            message: message.into(),
+        }
+    }
+
+    /// Reads an error response, e.g. `OpenAI API returned error 429 Too
+    /// Many Requests: <body>` for `api` = `OpenAI API`.
+    pub fn from_response(api: &str, response: reqwest::blocking::Response) -> Self {
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = response.text().unwrap_or_default();
+        Self::new(
+            status.as_u16(),
+            retry_after,
+            format!("{} returned error {}: {}", api, status, body),
+        )
+    }
+
+    /// Returns whether the request may succeed when repeated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::retry::ApiError;
+    ///
+    /// assert!(ApiError::new(429, None, "rate limited").is_transient());
+    /// assert!(ApiError::new(503, None, "unavailable").is_transient());
+    /// assert!(!ApiError::new(401, None, "bad token").is_transient());
+    /// ```
+    pub fn is_transient(&self) -> bool {
+        matches!(self.status, 408 | 429 | 500..=599)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Parses a `Retry-After` header: a number of seconds or an HTTP date.
+///
+/// Dates in the past yield a zero wait.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use commit_wizard::retry::parse_retry_after;
+///
+/// assert_eq!(parse_retry_after("7"), Some(Duration::from_secs(7)));
+/// assert_eq!(
+///     parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+///     Some(Duration::ZERO)
+/// );
+/// assert_eq!(parse_retry_after("soon"), None);
+/// ```
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// How often and how long to retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each further one
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: DEFAULT_RETRIES,
+            base_delay: Duration::from_millis(DEFAULT_RETRY_DELAY_MS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates the policy configured in `[ai]`.
+    pub fn from_config(config: &AiConfig) -> Self {
+        Self {
+            retries: config.retries,
+            base_delay: Duration::from_millis(config.retry_delay_ms),
+        }
+    }
+
+    /// Returns the backoff before retry number `attempt` (starting at 1).
+    ///
+    /// `jitter` between 0 and 1 scales the delay between half and all of
+    /// the exponential value, so clients that failed together do not retry
+    /// together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use commit_wizard::retry::RetryPolicy;
+    ///
+    /// let policy = RetryPolicy { retries: 3, base_delay: Duration::from_secs(1) };
+    /// assert_eq!(policy.backoff(1, 1.0), Duration::from_secs(1));
+    /// assert_eq!(policy.backoff(3, 1.0), Duration::from_secs(4));
+    /// assert_eq!(policy.backoff(3, 0.0), Duration::from_secs(2));
+    /// ```
+    pub fn backoff(&self, attempt: u32, jitter: f64) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(MAX_DELAY);
+        delay.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+    }
+
+    /// Returns how long to wait before retry number `attempt` after
+    /// `error`, or [`None`] if it should not be retried.
+    pub fn delay(&self, attempt: u32, error: &ApiError) -> Option<Duration> {
+        if attempt > self.retries || !error.is_transient() {
+            return None;
+        }
+        match error.retry_after {
+            Some(wait) if wait > MAX_DELAY => None,
+            Some(wait) => Some(wait),
+            None => Some(self.backoff(attempt, jitter())),
+        }
+    }
+}
+
+/// Returns a pseudo-random number between 0 and 1.
+fn jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    f64::from(nanos % 1000) / 999.0
+}
+
+/// Runs `request`, retrying transient [`ApiError`]s according to `policy`.
+///
+/// `on_retry` is called with the retry number and the delay before each
+/// retry.
+///
+/// # Errors
+///
+/// Returns the last error if the request does not succeed, and
+/// [`Cancelled`] if the request is cancelled while a retry is pending.
+pub fn run_with_retries<T>(
+    policy: &RetryPolicy,
+    mut request: impl FnMut() -> Result<T>,
+    mut on_retry: impl FnMut(u32, Duration),
+) -> Result<T> {
+    let cancel = cancel_flag();
+    let mut attempt = 0;
+    loop {
+        let error = match request() {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        attempt += 1;
+        let delay = error
+            .downcast_ref::<ApiError>()
+            .and_then(|api_error| policy.delay(attempt, api_error));
+        let Some(delay) = delay else {
+            return Err(error);
+        };
+        if cancel.load(Ordering::Relaxed) {
+            return Err(Cancelled.into());
+        }
+        log::warn!(
+            "{}; retrying {}/{} in {:.1}s",
+            error,
+            attempt,
+            policy.retries,
+            delay.as_secs_f64()
+        );
+        on_retry(attempt, delay);
+        if !sleep_unless_cancelled(delay, &cancel) {
+            log::debug!("Request cancelled; not retrying");
+            return Err(Cancelled.into());
+        }
+    }
+}
+
+/// Sleeps for `delay`, returning `false` early once `cancel` is set.
+fn sleep_unless_cancelled(delay: Duration, cancel: &AtomicBool) -> bool {
+    let deadline = Instant::now() + delay;
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        thread::sleep(left.min(CANCEL_POLL_INTERVAL));
+    }
+}
+
+/// Provider that retries transient API errors of its inner provider.
+pub struct RetryingProvider {
+    inner: Box<dyn AiProvider>,
+    policy: RetryPolicy,
+    notice: Mutex<Option<String>>,
+}
+
+impl RetryingProvider {
+    /// Wraps `inner` so its requests are retried according to `policy`.
+    pub fn new(inner: Box<dyn AiProvider>, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            notice: Mutex::new(None),
+        }
+    }
+
+    fn retried<T>(&self, request: impl FnMut() -> Result<T>) -> Result<T> {
+        let result = run_with_retries(&self.policy, request, |attempt, _| {
+            self.set_notice(Some(format!(
+                "retrying {}/{}",
+                attempt, self.policy.retries
+            )));
+        });
+        self.set_notice(None);
+        result
+    }
+
+    fn set_notice(&self, notice: Option<String>) {
+        *self.notice.lock().unwrap_or_else(|e| e.into_inner()) = notice;
+    }
+}
+
+impl ProviderLayer for RetryingProvider {
+    fn inner(&self) -> &dyn AiProvider {
+        self.inner.as_ref()
+    }
+
+    fn retry_notice(&self) -> Option<String> {
+        self.notice
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.retried(|| self.inner.complete(prompt))
+    }
+
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        self.retried(|| self.inner.complete_json(prompt))
+    }
+
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        self.retried(|| self.inner.complete_n(prompt, n))
+    }
+
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        self.retried(|| self.inner.chat(messages))
+    }
+}
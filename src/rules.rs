@@ -0,0 +1,129 @@
+//! Team conventions mapping paths to commit types and scopes.
+//!
+//! `[rules]` maps glob patterns to a commit type and/or scope:
+//!
+//! ```toml
+//! [rules]
+//! "migrations/**" = { type = "chore", scope = "db" }
+//! "*.proto" = { scope = "api" }
+//! ```
+//!
+//! Rules are evaluated before the built-in heuristics of
+//! [`crate::inference`] and before package manifests and `[scope] rules`.
+//! When several patterns match a file, the longest pattern wins, so
+//! `"src/db/**"` overrides `"src/**"`.
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::config::PathRule;
+use crate::exclude::PathFilter;
+use crate::types::{ChangedFile, CommitType};
+
+/// A compiled `[rules]` entry.
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    pattern: String,
+    filter: PathFilter,
+    commit_type: Option<CommitType>,
+    scope: Option<String>,
+}
+
+/// Compiled `[rules]`, most specific pattern first.
+#[derive(Debug, Clone, Default)]
+pub struct PathRules {
+    rules: Vec<CompiledRule>,
+}
+
+impl PathRules {
+    /// Compiles the `[rules]` table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for unknown commit types, rules that set neither a
+    /// type nor a scope, and patterns that cannot be compiled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use commit_wizard::config::PathRule;
+    /// use commit_wizard::rules::PathRules;
+    /// use commit_wizard::types::CommitType;
+    ///
+    /// let rule = PathRule {
+    ///     commit_type: Some("chore".to_string()),
+    ///     scope: Some("db".to_string()),
+    /// };
+    /// let rules = PathRules::new(&HashMap::from([("migrations/**".to_string(), rule)])).unwrap();
+    ///
+    /// assert_eq!(rules.commit_type("migrations/001_init.sql"), Some(CommitType::Chore));
+    /// assert_eq!(rules.scope("migrations/001_init.sql"), Some("db"));
+    /// assert_eq!(rules.commit_type("src/main.rs"), None);
+    /// ```
+    pub fn new(config: &HashMap<String, PathRule>) -> Result<Self> {
+        let mut rules = Vec::new();
+        for (pattern, rule) in config {
+            if rule.commit_type.is_none() && rule.scope.is_none() {
+                bail!("Rule '{}' in [rules] sets neither type nor scope", pattern);
+            }
+            let commit_type = match &rule.commit_type {
+                Some(name) => match CommitType::all().iter().find(|t| t.as_str() == name.trim()) {
+                    Some(commit_type) => Some(*commit_type),
+                    None => bail!("Unknown commit type '{}' in rule '{}'", name, pattern),
+                },
+                None => None,
+            };
+            rules.push(CompiledRule {
+                pattern: pattern.clone(),
+                filter: PathFilter::new(&[pattern])?,
+                commit_type,
+                scope: rule.scope.clone(),
+            });
+        }
+        // Longest pattern first; ties in alphabetical order
+        rules.sort_by(|a, b| {
+            b.pattern
+                .len()
+                .cmp(&a.pattern.len())
+                .then_with(|| a.pattern.cmp(&b.pattern))
+        });
+        Ok(Self { rules })
+    }
+
+    /// Returns the commit type of the most specific rule matching `path`
+    /// that sets one.
+    pub fn commit_type(&self, path: &str) -> Option<CommitType> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.filter.matches(path))
+            .find_map(|rule| rule.commit_type)
+    }
+
+    /// Returns the scope of the most specific rule matching `path` that
+    /// sets one.
+    pub fn scope(&self, path: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.filter.matches(path))
+            .find_map(|rule| rule.scope.as_deref())
+    }
+
+    /// Sets the commit type and scope of every file a rule matches.
+    ///
+    /// Values not set by any rule are left as they are, so this runs after
+    /// [`crate::scope::ScopeResolver::assign`]. Submodule entries are left
+    /// alone.
+    pub fn assign(&self, files: &mut [ChangedFile]) {
+        for file in files.iter_mut().filter(|file| !file.is_submodule()) {
+            if let Some(commit_type) = self.commit_type(&file.path) {
+                file.commit_type = Some(commit_type);
+            }
+            if let Some(scope) = self.scope(&file.path) {
+                file.scope = Some(Some(scope.to_string()));
+            }
+        }
+    }
+}
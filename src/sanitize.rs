@@ -0,0 +1,305 @@
+//! Redaction of secrets in AI prompts.
+//!
+//! Diffs regularly contain credentials that were committed by accident or
+//! are about to be: `.env` files, test fixtures with real tokens, private
+//! keys. Before a prompt leaves the machine, [`Redactor`] scans it for
+//!
+//! - private keys (PEM blocks),
+//! - JSON Web Tokens,
+//! - bearer tokens,
+//! - API keys with well-known prefixes (GitHub, OpenAI, AWS, Slack, Google,
+//!   Stripe),
+//! - `.env` style assignments to names such as `DB_PASSWORD` or
+//!   `STRIPE_SECRET_KEY`,
+//! - high-entropy strings that look like random tokens,
+//! - and the patterns configured in `[redact]`,
+//!
+//! and replaces each match with `[REDACTED]`. [`RedactingProvider`] applies
+//! it to every prompt and logs what kinds of secrets were masked, never the
+//! values themselves. `--no-redact` or `enabled = false` turns it off.
+//! Diagnostic reports use the same rules through [`Redactor::for_reports`].
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Context, Result};
+use regex::{Captures, Regex};
+
+use crate::config::RedactConfig;
+use crate::provider::{AiProvider, ChatMessage, ProviderLayer};
+
+/// Replacement for masked secrets.
+pub const MASK: &str = "[REDACTED]";
+
+/// Shortest string checked for high entropy.
+const MIN_ENTROPY_LEN: usize = 24;
+
+/// Shortest run without `/`, `-`, or `_` in a random-looking string.
+const MIN_RANDOM_RUN: usize = 16;
+
+/// Entropy in bits per character above which a string counts as random.
+const ENTROPY_THRESHOLD: f64 = 4.2;
+
+/// Built-in rules as (kind, pattern). A `keep` group is left in place, so
+/// `API_TOKEN=abc` becomes `API_TOKEN=[REDACTED]`.
+const BUILTIN_RULES: &[(&str, &str)] = &[
+    (
+        "private key",
+        r"(?s)-----BEGIN [A-Z ]*PRIVATE KEY-----.*?(?:-----END [A-Z ]*PRIVATE KEY-----|\z)",
+    ),
+    (
+        "JWT",
+        r"\beyJ[A-Za-z0-9_-]{8,}\.eyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}",
+    ),
+    (
+        "API key",
+        r"\b(?:gh[pousr]_[A-Za-z0-9]{30,}|github_pat_[A-Za-z0-9_]{22,}|sk-(?:proj-)?[A-Za-z0-9_-]{20,}|(?:AKIA|ASIA)[0-9A-Z]{16}|xox[abprs]-[A-Za-z0-9-]{10,}|AIza[0-9A-Za-z_-]{35}|[rs]k_(?:live|test)_[0-9A-Za-z]{16,})",
+    ),
+    (
+        "bearer token",
+        r"(?i)(?P<keep>\bbearer\s+)[A-Za-z0-9._~+/=-]+",
+    ),
+    (
+        "secret assignment",
+        r#"(?m)(?P<keep>^[+\- ]?\s*(?:export\s+)?(?:[A-Z0-9_]*_)?(?:SECRET|TOKEN|PASSWORD|PASSWD|API_KEY|APIKEY|ACCESS_KEY|PRIVATE_KEY|CREDENTIALS?)(?:_[A-Z0-9_]*)?\s*[=:]\s*)[^\s$].*$"#,
+    ),
+];
+
+/// Rules added for diagnostic reports, whose configuration snapshots and
+/// log lines spell settings in lowercase, e.g. `api_key = "..."`.
+const REPORT_RULES: &[(&str, &str)] = &[(
+    "secret value",
+    r#"(?i)(?P<keep>\b[a-z_]*(?:token|api_?key|secret|password)"?\s*[:=]\s*"?)[^\s",}]+"#,
+)];
+
+/// Secrets found in a text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Redaction {
+    /// The text with secrets replaced by [`MASK`]
+    pub text: String,
+    /// Number of masked secrets per kind
+    pub counts: BTreeMap<&'static str, usize>,
+}
+
+impl Redaction {
+    /// Returns whether nothing was masked.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Lists what was masked, e.g. `API key (2), private key (1)`.
+    pub fn summary(&self) -> String {
+        self.counts
+            .iter()
+            .map(|(kind, count)| format!("{} ({})", kind, count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A compiled redaction rule.
+#[derive(Debug, Clone)]
+struct Rule {
+    kind: &'static str,
+    regex: Regex,
+}
+
+/// Masks secrets in text.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    rules: Vec<Rule>,
+    entropy: Option<Regex>,
+}
+
+impl Redactor {
+    /// Creates a redactor with the built-in rules and the patterns of
+    /// `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configured pattern is not a valid regular
+    /// expression.
+    pub fn new(config: &RedactConfig) -> Result<Self> {
+        let mut rules = compile_builtin(BUILTIN_RULES);
+        for pattern in &config.patterns {
+            let regex = Regex::new(pattern)
+                .with_context(|| format!("Invalid redaction pattern '{}'", pattern))?;
+            rules.push(Rule {
+                kind: "custom pattern",
+                regex,
+            });
+        }
+        let entropy = config.entropy.then(|| {
+            Regex::new(&format!(r"[A-Za-z0-9+/_=-]{{{},}}", MIN_ENTROPY_LEN))
+                .expect("valid entropy pattern")
+        });
+        Ok(Self { rules, entropy })
+    }
+
+    /// Creates a redactor for diagnostic reports: the built-in rules, the
+    /// high-entropy check, and lowercase `key = value` secrets.
+    pub fn for_reports() -> Self {
+        let mut redactor =
+            Self::new(&RedactConfig::default()).expect("built-in rules need no configuration");
+        redactor.rules.extend(compile_builtin(REPORT_RULES));
+        redactor
+    }
+
+    /// Masks all secrets in `text`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::config::RedactConfig;
+    /// use commit_wizard::sanitize::Redactor;
+    ///
+    /// let redactor = Redactor::new(&RedactConfig::default()).unwrap();
+    /// let redaction = redactor.redact("+DB_PASSWORD=hunter2\n+DEBUG=true\n");
+    /// assert_eq!(redaction.text, "+DB_PASSWORD=[REDACTED]\n+DEBUG=true\n");
+    /// assert_eq!(redaction.summary(), "secret assignment (1)");
+    /// ```
+    pub fn redact(&self, text: &str) -> Redaction {
+        let mut text = text.to_string();
+        let mut counts = BTreeMap::new();
+
+        for rule in &self.rules {
+            let mut found = 0;
+            let replaced = rule.regex.replace_all(&text, |caps: &Captures| {
+                found += 1;
+                match caps.name("keep") {
+                    Some(keep) => format!("{}{}", keep.as_str(), MASK),
+                    None => MASK.to_string(),
+                }
+            });
+            if found > 0 {
+                text = replaced.into_owned();
+                *counts.entry(rule.kind).or_insert(0) += found;
+            }
+        }
+
+        if let Some(entropy) = &self.entropy {
+            let mut found = 0;
+            let replaced = entropy.replace_all(&text, |caps: &Captures| {
+                let candidate = &caps[0];
+                if looks_random(candidate) {
+                    found += 1;
+                    MASK.to_string()
+                } else {
+                    candidate.to_string()
+                }
+            });
+            if found > 0 {
+                text = replaced.into_owned();
+                counts.insert("high-entropy string", found);
+            }
+        }
+
+        Redaction { text, counts }
+    }
+}
+
+/// Compiles built-in (kind, pattern) rules.
+fn compile_builtin(rules: &[(&'static str, &str)]) -> Vec<Rule> {
+    rules
+        .iter()
+        .map(|&(kind, pattern)| Rule {
+            kind,
+            regex: Regex::new(pattern).expect("valid built-in redaction pattern"),
+        })
+        .collect()
+}
+
+/// Returns the Shannon entropy of `text` in bits per character.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::sanitize::shannon_entropy;
+///
+/// assert_eq!(shannon_entropy("aaaa"), 0.0);
+/// assert_eq!(shannon_entropy("abab"), 1.0);
+/// assert_eq!(shannon_entropy(""), 0.0);
+/// ```
+pub fn shannon_entropy(text: &str) -> f64 {
+    let mut frequencies: HashMap<char, usize> = HashMap::new();
+    for c in text.chars() {
+        *frequencies.entry(c).or_insert(0) += 1;
+    }
+    let len = text.chars().count() as f64;
+    frequencies
+        .values()
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum::<f64>()
+        .abs()
+}
+
+/// Returns whether a token looks like a random secret.
+///
+/// Requiring upper- and lowercase letters and digits skips hex hashes such
+/// as commit ids and lock file checksums, which are not secret; requiring a
+/// long unbroken run skips paths and branch names like
+/// `feature/LU-1234-add-login`.
+fn looks_random(token: &str) -> bool {
+    let longest_run = token
+        .split(['/', '-', '_'])
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    longest_run >= MIN_RANDOM_RUN
+        && token.chars().any(|c| c.is_ascii_uppercase())
+        && token.chars().any(|c| c.is_ascii_lowercase())
+        && token.chars().any(|c| c.is_ascii_digit())
+        && shannon_entropy(token) >= ENTROPY_THRESHOLD
+}
+
+/// Provider that masks secrets in every prompt before passing it on.
+pub struct RedactingProvider {
+    inner: Box<dyn AiProvider>,
+    redactor: Redactor,
+}
+
+impl RedactingProvider {
+    /// Wraps `inner` so its prompts are redacted with `redactor`.
+    pub fn new(inner: Box<dyn AiProvider>, redactor: Redactor) -> Self {
+        Self { inner, redactor }
+    }
+
+    fn redact(&self, prompt: &str) -> String {
+        let redaction = self.redactor.redact(prompt);
+        if !redaction.is_empty() {
+            log::info!("Redacted from AI prompt: {}", redaction.summary());
+        }
+        redaction.text
+    }
+}
+
+impl ProviderLayer for RedactingProvider {
+    fn inner(&self) -> &dyn AiProvider {
+        self.inner.as_ref()
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.inner.complete(&self.redact(prompt))
+    }
+
+    fn complete_json(&self, prompt: &str) -> Result<String> {
+        self.inner.complete_json(&self.redact(prompt))
+    }
+
+    fn complete_n(&self, prompt: &str, n: usize) -> Result<Vec<String>> {
+        self.inner.complete_n(&self.redact(prompt), n)
+    }
+
+    fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        let messages: Vec<ChatMessage> = messages
+            .iter()
+            .map(|message| ChatMessage {
+                content: self.redact(&message.content),
+                ..message.clone()
+            })
+            .collect();
+        self.inner.chat(&messages)
+    }
+}
@@ -0,0 +1,431 @@
+//! Scope inference from the repository layout.
+//!
+//! [`crate::inference::infer_scope`] only looks at a path. The
+//! [`ScopeResolver`] also knows the repository: files inside a package of a
+//! monorepo get the package name as scope, read from the nearest
+//! `Cargo.toml` (crate name), `package.json` (package name without the npm
+//! scope), or `go.mod` (last module path segment). `[scope] rules` override
+//! both, and [`crate::rules`] overrides all of them. Other files get a scope
+//! from their path, skipping the generic directories of `[scope] ignore`.
+//!
+//! The manifest at the repository root is ignored: its name would be the
+//! scope of every file.
+//!
+//! A [`ScopeVocabulary`] learned from the `type(scope):` headers of earlier
+//! commits keeps inferred scopes in line with the repository's conventions:
+//! an inferred scope is spelled like the established one (`Users` →
+//! `user`), and a file whose inferred scope was never used before gets the
+//! most used established scope among its path segments instead. The same
+//! vocabulary feeds scope completion in the message editor.
+//!
+//! `[scope] aliases` rename scopes after all of that, e.g. a scope the AI
+//! keeps choosing. Renames made in the interface can be recorded in
+//! [`ALIAS_FILE`], which [`crate::config::Config::load`] reads into the same
+//! table.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use git2::{Repository, Sort};
+
+use crate::config::ScopeConfig;
+use crate::exclude::PathFilter;
+use crate::inference::infer_scope_with;
+use crate::types::ChangedFile;
+
+/// Assigns scopes from `[scope] rules` and package manifests.
+#[derive(Debug)]
+pub struct ScopeResolver {
+    root: PathBuf,
+    rules: Vec<(PathFilter, String)>,
+    ignore: Vec<String>,
+    /// Package name per directory, relative to the root
+    packages: HashMap<String, Option<String>>,
+    vocabulary: ScopeVocabulary,
+}
+
+impl ScopeResolver {
+    /// Creates a resolver for the repository at `root`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern of `[scope] rules` cannot be compiled.
+    pub fn new(root: impl Into<PathBuf>, config: &ScopeConfig) -> Result<Self> {
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| {
+                PathFilter::new(&rule.paths)
+                    .with_context(|| format!("Invalid [scope] rule for '{}'", rule.scope))
+                    .map(|filter| (filter, rule.scope.clone()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            root: root.into(),
+            rules,
+            ignore: config.ignore.clone(),
+            packages: HashMap::new(),
+            vocabulary: ScopeVocabulary::default(),
+        })
+    }
+
+    /// Prefers the scopes of `vocabulary` over scopes inferred from
+    /// packages and paths; `[scope] rules` still win.
+    pub fn with_vocabulary(mut self, vocabulary: ScopeVocabulary) -> Self {
+        self.vocabulary = vocabulary;
+        self
+    }
+
+    /// Returns the scope of `path` from the first matching rule, the
+    /// nearest package manifest, or the path.
+    ///
+    /// # Returns
+    ///
+    /// The scope, or [`None`] if the file should have none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::config::ScopeConfig;
+    /// use commit_wizard::scope::ScopeResolver;
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// std::fs::create_dir_all(dir.path().join("crates/parser")).unwrap();
+    /// std::fs::write(
+    ///     dir.path().join("crates/parser/Cargo.toml"),
+    ///     "[package]\nname = \"wizard-parser\"\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut resolver = ScopeResolver::new(dir.path(), &ScopeConfig::default()).unwrap();
+    /// assert_eq!(
+    ///     resolver.resolve("crates/parser/src/lexer.rs"),
+    ///     Some("wizard-parser".to_string())
+    /// );
+    /// assert_eq!(resolver.resolve("src/main.rs"), None);
+    /// ```
+    pub fn resolve(&mut self, path: &str) -> Option<String> {
+        if let Some((_, scope)) = self.rules.iter().find(|(filter, _)| filter.matches(path)) {
+            return Some(scope.clone());
+        }
+
+        let mut dir = Path::new(path).parent();
+        while let Some(current) = dir.filter(|d| !d.as_os_str().is_empty()) {
+            let key = current.to_string_lossy().into_owned();
+            let root = &self.root;
+            let package = self
+                .packages
+                .entry(key)
+                .or_insert_with(|| package_name(&root.join(current)));
+            if let Some(package) = package {
+                let package = package.clone();
+                return Some(self.vocabulary.canonical(&package).unwrap_or(package));
+            }
+            dir = current.parent();
+        }
+        self.vocabulary
+            .prefer(path, infer_scope_with(path, &self.ignore))
+    }
+
+    /// Resolves the scope of every file.
+    ///
+    /// Submodule entries are left alone.
+    pub fn assign(&mut self, files: &mut [ChangedFile]) {
+        for file in files.iter_mut().filter(|file| !file.is_submodule()) {
+            file.scope = Some(self.resolve(&file.path));
+        }
+    }
+}
+
+/// Scopes used by earlier commits, most used first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScopeVocabulary {
+    scopes: Vec<(String, usize)>,
+}
+
+impl ScopeVocabulary {
+    /// Builds the vocabulary from commit messages, keeping scopes used at
+    /// least `min_uses` times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::scope::ScopeVocabulary;
+    ///
+    /// let vocabulary = ScopeVocabulary::from_messages(
+    ///     ["feat(api): add users", "fix(api)!: drop v1", "docs(readme): typo", "Merge branch"],
+    ///     1,
+    /// );
+    /// assert_eq!(vocabulary.scopes().collect::<Vec<_>>(), ["api", "readme"]);
+    /// assert_eq!(vocabulary.uses("api"), 2);
+    /// ```
+    pub fn from_messages<I, S>(messages: I, min_uses: usize) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for message in messages {
+            if let Some(scope) = header_scope(message.as_ref()) {
+                *counts.entry(scope.to_string()).or_default() += 1;
+            }
+        }
+        let mut scopes: Vec<(String, usize)> = counts
+            .into_iter()
+            .filter(|(_, uses)| *uses >= min_uses.max(1))
+            .collect();
+        scopes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Self { scopes }
+    }
+
+    /// Learns the scopes of the last `limit` commits reachable from HEAD,
+    /// keeping those used at least `min_uses` times.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the history cannot be walked. A repository
+    /// without commits yields an empty vocabulary.
+    pub fn mine(repo: &Repository, limit: usize, min_uses: usize) -> Result<Self> {
+        let Ok(head) = repo.head().and_then(|head| head.peel_to_commit()) else {
+            return Ok(Self::default());
+        };
+        let mut walk = repo.revwalk().context("Failed to walk history")?;
+        walk.set_sorting(Sort::TIME)?;
+        walk.push(head.id())?;
+
+        let mut messages = Vec::new();
+        for oid in walk.take(limit) {
+            let commit = repo.find_commit(oid?)?;
+            if let Some(summary) = commit.summary() {
+                messages.push(summary.to_string());
+            }
+        }
+        Ok(Self::from_messages(messages, min_uses))
+    }
+
+    /// Returns the scopes, most used first.
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.scopes.iter().map(|(scope, _)| scope.as_str())
+    }
+
+    /// Returns how often `scope` was used.
+    pub fn uses(&self, scope: &str) -> usize {
+        self.scopes
+            .iter()
+            .find(|(known, _)| known == scope)
+            .map_or(0, |(_, uses)| *uses)
+    }
+
+    /// Returns whether no scope was learned.
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+
+    /// Returns the established spelling of `scope`, comparing case,
+    /// separators, and a plural `s` loosely.
+    pub fn canonical(&self, scope: &str) -> Option<String> {
+        let key = normalize(scope);
+        self.scopes
+            .iter()
+            .find(|(known, _)| normalize(known) == key)
+            .map(|(known, _)| known.clone())
+    }
+
+    /// Returns the scope for a file at `path` whose scope was inferred as
+    /// `inferred`.
+    ///
+    /// A known inferred scope is returned in its established spelling.
+    /// Otherwise the most used known scope among the path's directories
+    /// and file stem is preferred, falling back to `inferred`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::scope::ScopeVocabulary;
+    ///
+    /// let vocabulary = ScopeVocabulary::from_messages(["feat(auth): a", "fix(user): b"], 1);
+    /// assert_eq!(
+    ///     vocabulary.prefer("src/Users/list.rs", Some("Users".into())),
+    ///     Some("user".to_string())
+    /// );
+    /// assert_eq!(
+    ///     vocabulary.prefer("services/backend/auth/token.rs", Some("services".into())),
+    ///     Some("auth".to_string())
+    /// );
+    /// assert_eq!(vocabulary.prefer("tools/x.rs", Some("tools".into())), Some("tools".to_string()));
+    /// ```
+    pub fn prefer(&self, path: &str, inferred: Option<String>) -> Option<String> {
+        if let Some(known) = inferred.as_deref().and_then(|scope| self.canonical(scope)) {
+            return Some(known);
+        }
+        let path = Path::new(path);
+        let segments: Vec<String> = path
+            .parent()
+            .into_iter()
+            .flat_map(Path::iter)
+            .chain(path.file_stem())
+            .map(|segment| normalize(&segment.to_string_lossy()))
+            .collect();
+        self.scopes
+            .iter()
+            .find(|(known, _)| segments.contains(&normalize(known)))
+            .map(|(known, _)| known.clone())
+            .or(inferred)
+    }
+
+    /// Returns the known scopes starting with `prefix`, ignoring case, most
+    /// used first.
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.to_lowercase();
+        self.scopes
+            .iter()
+            .filter(|(known, _)| known.to_lowercase().starts_with(&prefix))
+            .map(|(known, _)| known.clone())
+            .collect()
+    }
+}
+
+/// Returns the scope of a `type(scope): description` header.
+fn header_scope(message: &str) -> Option<&str> {
+    let header = message.lines().next()?;
+    let (prefix, _) = header.split_once(':')?;
+    let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+    let (commit_type, rest) = prefix.split_once('(')?;
+    let scope = rest.strip_suffix(')')?.trim();
+    let valid_type =
+        !commit_type.is_empty() && commit_type.chars().all(|c| c.is_ascii_alphabetic());
+    let valid_scope = !scope.is_empty() && !scope.contains(['(', ')', ' ']);
+    (valid_type && valid_scope).then_some(scope)
+}
+
+/// Normalizes a scope for loose comparison: lowercase, `-` for `_`, and
+/// without a plural `s`.
+fn normalize(scope: &str) -> String {
+    let scope = scope.to_lowercase().replace('_', "-");
+    match scope.strip_suffix('s') {
+        Some(singular) if singular.len() >= 3 && !singular.ends_with('s') => singular.to_string(),
+        _ => scope,
+    }
+}
+
+/// Reads the package name from the manifest in `dir`, if there is one.
+///
+/// `Cargo.toml` files without `[package]` (virtual workspaces) do not name
+/// a package.
+pub fn package_name(dir: &Path) -> Option<String> {
+    if let Ok(content) = fs::read_to_string(dir.join("Cargo.toml")) {
+        let manifest = toml::from_str::<toml::Value>(&content).ok();
+        if let Some(name) = manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get("package"))
+            .and_then(|package| package.get("name"))
+            .and_then(toml::Value::as_str)
+        {
+            return Some(name.to_string());
+        }
+    }
+
+    if let Ok(content) = fs::read_to_string(dir.join("package.json")) {
+        let manifest = serde_json::from_str::<serde_json::Value>(&content).ok();
+        if let Some(name) = manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get("name"))
+            .and_then(serde_json::Value::as_str)
+        {
+            // "@acme/web-ui" → "web-ui"
+            let name = name.rsplit('/').next().unwrap_or(name);
+            if !name.is_empty() {
+                return Some(name.to_string());
+            }
+        }
+    }
+
+    if let Ok(content) = fs::read_to_string(dir.join("go.mod")) {
+        let module = content
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("module "))?
+            .trim()
+            .trim_matches('"');
+        // "example.com/shop/billing/v2" → "billing"
+        return module
+            .rsplit('/')
+            .find(|segment| !is_major_version(segment))
+            .map(str::to_string);
+    }
+
+    None
+}
+
+/// File in the repository recording scope renames as `from = "to"` lines.
+pub const ALIAS_FILE: &str = ".commit-wizard/scope-aliases.toml";
+
+/// Returns the scope `scope` is renamed to by `aliases`, following chains
+/// like `users` → `user` → `accounts`.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// use commit_wizard::scope::resolve_alias;
+///
+/// let aliases = HashMap::from([("users".to_string(), "user".to_string())]);
+/// assert_eq!(resolve_alias(&aliases, "users"), "user");
+/// assert_eq!(resolve_alias(&aliases, "api"), "api");
+/// ```
+pub fn resolve_alias<'a>(aliases: &'a HashMap<String, String>, scope: &'a str) -> &'a str {
+    let mut resolved = scope;
+    // A cycle stops after visiting every alias once
+    for _ in 0..aliases.len() {
+        match aliases.get(resolved) {
+            Some(target) if target != resolved => resolved = target,
+            _ => break,
+        }
+    }
+    resolved
+}
+
+/// Records that scope `from` is renamed to `to` in [`ALIAS_FILE`] under
+/// `repo_root`, creating the file if needed.
+///
+/// Aliases that led to `from` are pointed at `to`, and an alias of `to`
+/// itself is removed, so no chain or cycle is left behind.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but is not a table of strings, or
+/// cannot be written.
+pub fn record_alias(repo_root: &Path, from: &str, to: &str) -> Result<()> {
+    let path = repo_root.join(ALIAS_FILE);
+    let mut aliases: BTreeMap<String, String> = match fs::read_to_string(&path) {
+        Ok(text) => toml::from_str(&text)
+            .with_context(|| format!("Invalid scope aliases in {}", path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+        }
+    };
+
+    for target in aliases.values_mut().filter(|target| *target == from) {
+        *target = to.to_string();
+    }
+    aliases.remove(to);
+    aliases.insert(from.to_string(), to.to_string());
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let text = format!(
+        "# Scope renames recorded by commit-wizard: from = \"to\"\n{}",
+        toml::to_string(&aliases).context("Failed to serialize scope aliases")?
+    );
+    fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Returns whether a Go module path segment is a major version like `v2`.
+fn is_major_version(segment: &str) -> bool {
+    segment
+        .strip_prefix('v')
+        .is_some_and(|version| !version.is_empty() && version.bytes().all(|b| b.is_ascii_digit()))
+}
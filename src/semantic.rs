@@ -0,0 +1,623 @@
+//! Declaration-level summaries of diffs for heuristic commit bodies.
+//!
+//! Without AI, a commit body listing `modify src/config.rs` says little about
+//! the change. This module reads a file's patch and reports which
+//! declarations it touches:
+//!
+//! - declarations only on `+` lines were added, only on `-` lines removed
+//! - a declaration removed and another of the same kind added in the same
+//!   hunk was renamed
+//! - a declaration on both sides, or enclosing changed lines, was updated;
+//!   the enclosing declaration comes from the hunk header
+//!   (`@@ -10,6 +10,7 @@ fn parse_config`) and the lines before the change
+//!
+//! Declarations are recognized with per-language patterns for Rust, Python,
+//! JavaScript/TypeScript, Go, Java, Kotlin, C#, Ruby, PHP, and shell scripts.
+//! New and deleted source files are described as modules.
+//!
+//! A deleted file's patch only says that everything is gone. To tell the AI
+//! what the removal drops, [`describe_purpose`] summarizes the last committed
+//! content: the first sentence of its leading comment, or else the
+//! declarations it defined.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+
+use git2::Repository;
+use regex::Regex;
+
+use crate::types::{ChangedFile, FileDiff};
+
+/// Longest former purpose kept, in characters.
+const MAX_PURPOSE_CHARS: usize = 120;
+
+/// Declaration patterns as (kind, pattern); the first capture group that
+/// participates in a match is the name.
+type Rules = &'static [(&'static str, &'static str)];
+
+const RUST: Rules = &[
+    (
+        "function",
+        r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:const|async|unsafe|extern\s+"[^"]*")\s+)*fn\s+(\w+)"#,
+    ),
+    ("struct", r"^\s*(?:pub(?:\([^)]*\))?\s+)?struct\s+(\w+)"),
+    ("enum", r"^\s*(?:pub(?:\([^)]*\))?\s+)?enum\s+(\w+)"),
+    (
+        "trait",
+        r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:unsafe\s+)?trait\s+(\w+)",
+    ),
+    ("type", r"^\s*(?:pub(?:\([^)]*\))?\s+)?type\s+(\w+)\s*[=<]"),
+    ("module", r"^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)"),
+    ("macro", r"^\s*macro_rules!\s*(\w+)"),
+    (
+        "constant",
+        r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:const|static)\s+(?:mut\s+)?([A-Z_][A-Z0-9_]*)\s*:",
+    ),
+];
+
+const PYTHON: Rules = &[
+    ("function", r"^\s*(?:async\s+)?def\s+(\w+)"),
+    ("class", r"^\s*class\s+(\w+)"),
+];
+
+const JAVASCRIPT: Rules = &[
+    (
+        "function",
+        r"^\s*(?:export\s+)?(?:default\s+)?(?:async\s+)?function\s*\*?\s*(\w+)",
+    ),
+    (
+        "class",
+        r"^\s*(?:export\s+)?(?:default\s+)?(?:abstract\s+)?class\s+(\w+)",
+    ),
+    ("interface", r"^\s*(?:export\s+)?interface\s+(\w+)"),
+    ("type", r"^\s*(?:export\s+)?type\s+(\w+)\s*(?:<[^=]*>)?\s*="),
+    (
+        "function",
+        r"^\s*(?:export\s+)?(?:const|let|var)\s+(\w+)\s*=\s*(?:async\s+)?(?:function\b|\([^)]*\)\s*=>|\w+\s*=>)",
+    ),
+];
+
+const GO: Rules = &[
+    ("function", r"^func\s+(?:\([^)]*\)\s*)?(\w+)"),
+    ("struct", r"^type\s+(\w+)\s+struct\b"),
+    ("interface", r"^type\s+(\w+)\s+interface\b"),
+    ("type", r"^type\s+(\w+)\s"),
+];
+
+const JVM: Rules = &[
+    (
+        "class",
+        r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|sealed|data|open|partial)\s+)*class\s+(\w+)",
+    ),
+    (
+        "interface",
+        r"^\s*(?:(?:public|private|protected|internal|sealed)\s+)*interface\s+(\w+)",
+    ),
+    (
+        "enum",
+        r"^\s*(?:(?:public|private|protected|internal)\s+)*enum\s+(?:class\s+)?(\w+)",
+    ),
+    (
+        "function",
+        r"^\s*(?:(?:public|private|protected|internal|override|open|suspend|inline)\s+)*fun\s+(?:<[^>]*>\s*)?(?:\w+\.)?(\w+)\s*\(",
+    ),
+    (
+        "method",
+        r"^\s*(?:(?:public|private|protected|internal|static|final|abstract|synchronized|override|virtual|async)\s+)+[\w<>\[\],.? ]+?\s+(\w+)\s*\(",
+    ),
+];
+
+const RUBY: Rules = &[
+    ("method", r"^\s*def\s+(?:self\.)?(\w+[?!=]?)"),
+    ("class", r"^\s*class\s+(\w+(?:::\w+)*)"),
+    ("module", r"^\s*module\s+(\w+(?:::\w+)*)"),
+];
+
+const PHP: Rules = &[
+    (
+        "function",
+        r"^\s*(?:(?:public|private|protected|static|final|abstract)\s+)*function\s+&?(\w+)",
+    ),
+    (
+        "class",
+        r"^\s*(?:(?:final|abstract|readonly)\s+)*class\s+(\w+)",
+    ),
+    ("interface", r"^\s*interface\s+(\w+)"),
+    ("trait", r"^\s*trait\s+(\w+)"),
+];
+
+const SHELL: Rules = &[(
+    "function",
+    r"^\s*(?:function\s+([\w-]+)|([\w-]+)\s*\(\)\s*\{)",
+)];
+
+/// Returns the declaration patterns for a file's language.
+fn rules_for(path: &str) -> Option<Rules> {
+    let extension = path.rsplit_once('.')?.1;
+    match extension {
+        "rs" => Some(RUST),
+        "py" => Some(PYTHON),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Some(JAVASCRIPT),
+        "go" => Some(GO),
+        "java" | "kt" | "kts" | "cs" | "scala" => Some(JVM),
+        "rb" => Some(RUBY),
+        "php" => Some(PHP),
+        "sh" | "bash" | "zsh" => Some(SHELL),
+        _ => None,
+    }
+}
+
+/// What happened to a declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolAction {
+    /// The declaration is new
+    Add,
+    /// The declaration was removed; `deprecated` if it was marked so
+    Remove {
+        /// Whether the removed code mentioned a deprecation
+        deprecated: bool,
+    },
+    /// The declaration was renamed from `from`
+    Rename {
+        /// The previous name
+        from: String,
+    },
+    /// The declaration or its body changed
+    Update,
+}
+
+/// A change to one declaration, e.g. a function that was added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolChange {
+    /// What happened to the declaration
+    pub action: SymbolAction,
+    /// Kind of declaration, e.g. `function`, `struct`, or `module`
+    pub kind: &'static str,
+    /// Name of the declaration (the new name for renames)
+    pub name: String,
+}
+
+impl fmt::Display for SymbolChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = match &self.action {
+            SymbolAction::Rename { from } => {
+                return write!(f, "rename `{}` to `{}`", from, self.name);
+            }
+            SymbolAction::Add => "add",
+            SymbolAction::Remove { deprecated: true } => "remove deprecated",
+            SymbolAction::Remove { deprecated: false } => "remove",
+            SymbolAction::Update => "update",
+        };
+        if self.kind == "module" {
+            write!(f, "{} `{}` module", verb, self.name)
+        } else {
+            write!(f, "{} {} `{}`", verb, self.kind, self.name)
+        }
+    }
+}
+
+/// Compiled declaration patterns of one language.
+struct Declarations {
+    rules: Vec<(&'static str, Regex)>,
+}
+
+impl Declarations {
+    fn new(rules: Rules) -> Self {
+        Self {
+            rules: rules
+                .iter()
+                .map(|(kind, pattern)| {
+                    (
+                        *kind,
+                        Regex::new(pattern).expect("valid declaration pattern"),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the kind and name of the declaration on `line`, if any.
+    fn find(&self, line: &str) -> Option<(&'static str, String)> {
+        self.rules.iter().find_map(|(kind, regex)| {
+            let caps = regex.captures(line)?;
+            let name = caps.iter().skip(1).flatten().next()?;
+            Some((*kind, name.as_str().to_string()))
+        })
+    }
+}
+
+/// A declaration seen on a changed line.
+struct Seen {
+    kind: &'static str,
+    name: String,
+    hunk: usize,
+    position: usize,
+    deprecated: bool,
+}
+
+/// Lists the declarations a patch adds, removes, renames, or updates.
+///
+/// Returns an empty list for languages without declaration patterns.
+///
+/// # Arguments
+///
+/// * `path` - Path of the file, used to pick the language
+/// * `patch` - Unified diff of the file
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::semantic::analyze_patch;
+///
+/// let patch = "@@ -1,3 +1,6 @@\n-fn load() {}\n+fn load_config() {}\n+\n+pub fn parse_config(input: &str) {}\n";
+/// let changes: Vec<String> = analyze_patch("src/config.rs", patch)
+///     .iter()
+///     .map(ToString::to_string)
+///     .collect();
+/// assert_eq!(changes, ["rename `load` to `load_config`", "add function `parse_config`"]);
+/// ```
+pub fn analyze_patch(path: &str, patch: &str) -> Vec<SymbolChange> {
+    let Some(rules) = rules_for(path) else {
+        return Vec::new();
+    };
+    let declarations = Declarations::new(rules);
+
+    let mut added: Vec<Seen> = Vec::new();
+    let mut removed: Vec<Seen> = Vec::new();
+    let mut touched: Vec<(usize, &'static str, String)> = Vec::new();
+    let mut enclosing: Option<(&'static str, String)> = None;
+    let mut hunk = 0;
+    let mut previous_removed = "";
+
+    for (position, line) in patch.lines().enumerate() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("@@") {
+            // "@@ -10,6 +10,7 @@ fn parse_config(": the heading names the
+            // code the hunk is in
+            hunk += 1;
+            enclosing = header
+                .split_once("@@")
+                .and_then(|(_, heading)| declarations.find(heading.trim_start()));
+            continue;
+        }
+        let (origin, content) = match line.chars().next() {
+            Some(origin @ ('+' | '-' | ' ')) => (origin, &line[1..]),
+            _ => continue,
+        };
+
+        // Unindented code other than a declaration, e.g. a closing brace or
+        // an import, is outside the enclosing declaration
+        let declaration = declarations.find(content);
+        if declaration.is_none() && content.starts_with(|c: char| !c.is_whitespace()) {
+            enclosing = None;
+        }
+        match (&declaration, origin) {
+            (Some((kind, name)), '+') => added.push(Seen {
+                kind,
+                name: name.clone(),
+                hunk,
+                position,
+                deprecated: false,
+            }),
+            (Some((kind, name)), '-') => removed.push(Seen {
+                kind,
+                name: name.clone(),
+                hunk,
+                position,
+                deprecated: previous_removed.to_lowercase().contains("deprecated"),
+            }),
+            (None, '+' | '-') if !content.trim().is_empty() => {
+                if let Some((kind, name)) = &enclosing {
+                    touched.push((position, kind, name.clone()));
+                }
+            }
+            _ => {}
+        }
+        previous_removed = if origin == '-' { content } else { "" };
+        if declaration.is_some() {
+            enclosing = declaration;
+        }
+    }
+
+    resolve(added, removed, touched)
+}
+
+/// Turns the declarations seen in a patch into changes, ordered by their
+/// position in the patch.
+fn resolve(
+    added: Vec<Seen>,
+    removed: Vec<Seen>,
+    touched: Vec<(usize, &'static str, String)>,
+) -> Vec<SymbolChange> {
+    let same = |a: &Seen, b: &Seen| a.kind == b.kind && a.name == b.name;
+    let mut changes: Vec<(usize, SymbolChange)> = Vec::new();
+
+    // Declarations on both sides changed their signature
+    let (updated, removed): (Vec<Seen>, Vec<Seen>) = removed
+        .into_iter()
+        .partition(|r| added.iter().any(|a| same(a, r)));
+    let (_, mut added): (Vec<Seen>, Vec<Seen>) = added
+        .into_iter()
+        .partition(|a| updated.iter().any(|r| same(a, r)));
+    for seen in updated {
+        changes.push((
+            seen.position,
+            change(SymbolAction::Update, seen.kind, seen.name),
+        ));
+    }
+
+    // A declaration replaced by another of the same kind in the same hunk
+    // was renamed
+    for seen in removed {
+        let rename = added
+            .iter()
+            .position(|a| a.kind == seen.kind && a.hunk == seen.hunk);
+        let entry = match rename {
+            Some(index) => {
+                let new = added.remove(index);
+                let action = SymbolAction::Rename { from: seen.name };
+                (seen.position, change(action, new.kind, new.name))
+            }
+            None => {
+                let action = SymbolAction::Remove {
+                    deprecated: seen.deprecated,
+                };
+                (seen.position, change(action, seen.kind, seen.name))
+            }
+        };
+        changes.push(entry);
+    }
+    for seen in added {
+        changes.push((
+            seen.position,
+            change(SymbolAction::Add, seen.kind, seen.name),
+        ));
+    }
+
+    // Changed lines inside a declaration update it, unless it is already
+    // listed
+    for (position, kind, name) in touched {
+        let listed = changes
+            .iter()
+            .any(|(_, c)| c.kind == kind && c.name == name);
+        if !listed {
+            changes.push((position, change(SymbolAction::Update, kind, name)));
+        }
+    }
+
+    changes.sort_by_key(|(position, _)| *position);
+    changes.into_iter().map(|(_, change)| change).collect()
+}
+
+fn change(action: SymbolAction, kind: &'static str, name: String) -> SymbolChange {
+    SymbolChange { action, kind, name }
+}
+
+/// Returns the module name of a source file: the file stem, or the directory
+/// name for `mod.rs`, `__init__.py`, and `index.js` style files.
+pub(crate) fn module_name(path: &str) -> Option<String> {
+    rules_for(path)?;
+    let mut parts = path.rsplit('/');
+    let stem = parts.next()?.split('.').next()?;
+    match stem {
+        "mod" | "__init__" | "index" => parts.next().map(str::to_string),
+        "" => None,
+        stem => Some(stem.to_string()),
+    }
+}
+
+/// Describes the declaration changes of one file.
+///
+/// New and deleted source files are one added or removed module; renamed
+/// files report the module rename along with the changes in the patch.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::semantic::describe_file;
+/// use commit_wizard::types::ChangedFile;
+/// use git2::Status;
+///
+/// let file = ChangedFile::new("src/legacy.rs".into(), Status::INDEX_DELETED);
+/// let changes = describe_file(&file, "-#![deprecated]\n-pub fn old() {}\n");
+/// assert_eq!(changes[0].to_string(), "remove deprecated `legacy` module");
+/// ```
+pub fn describe_file(file: &ChangedFile, patch: &str) -> Vec<SymbolChange> {
+    if file.is_new() || file.status.is_wt_new() {
+        return module_name(&file.path)
+            .map(|name| change(SymbolAction::Add, "module", name))
+            .into_iter()
+            .collect();
+    }
+    if file.is_deleted() {
+        let deprecated = patch.to_lowercase().contains("deprecated");
+        return module_name(&file.path)
+            .map(|name| change(SymbolAction::Remove { deprecated }, "module", name))
+            .into_iter()
+            .collect();
+    }
+
+    let mut changes = Vec::new();
+    if let Some(old_path) = &file.old_path {
+        if let (Some(from), Some(name)) = (module_name(old_path), module_name(&file.path)) {
+            if from != name {
+                changes.push(change(SymbolAction::Rename { from }, "module", name));
+            }
+        }
+    }
+    changes.extend(analyze_patch(&file.path, patch));
+    changes
+}
+
+/// Records the declaration changes of every file that has a text diff.
+///
+/// Binary and submodule entries are left without changes.
+pub fn describe_files<D: Borrow<FileDiff>>(files: &mut [ChangedFile], diffs: &HashMap<String, D>) {
+    for file in files.iter_mut() {
+        if file.is_submodule() {
+            continue;
+        }
+        let Some(diff) = diffs
+            .get(&file.path)
+            .map(Borrow::borrow)
+            .filter(|d| !d.is_binary)
+        else {
+            continue;
+        };
+        file.symbol_changes = describe_file(file, &diff.patch);
+    }
+}
+
+/// Block comments as (start, end) delimiters.
+const BLOCK_COMMENTS: &[(&str, &str)] = &[
+    ("/*", "*/"),
+    ("\"\"\"", "\"\"\""),
+    ("'''", "'''"),
+    ("<!--", "-->"),
+];
+
+/// Line comment markers, longest first.
+const LINE_COMMENTS: &[&str] = &["//!", "///", "//", "#", "--"];
+
+/// Summarizes what a file is for from its content.
+///
+/// The summary is the first sentence of the leading comment (a Rust module
+/// doc, Python docstring, block comment, or Markdown title); files without
+/// one are summarized by the first declarations they define. Shebangs and
+/// license or encoding lines are skipped.
+///
+/// # Returns
+///
+/// The summary, or [`None`] if the content has neither a leading comment
+/// nor recognized declarations.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::semantic::describe_purpose;
+///
+/// let content = "//! Parser for the legacy INI config format.\n//!\n//! Details.\n";
+/// assert_eq!(
+///     describe_purpose("src/legacy.rs", content).as_deref(),
+///     Some("Parser for the legacy INI config format")
+/// );
+///
+/// let content = "pub fn load() {}\npub struct Settings;\n";
+/// assert_eq!(
+///     describe_purpose("src/settings.rs", content).as_deref(),
+///     Some("defined function `load`, struct `Settings`")
+/// );
+/// ```
+pub fn describe_purpose(path: &str, content: &str) -> Option<String> {
+    if let Some(comment) = leading_comment(content) {
+        return Some(comment);
+    }
+    let patch: String = content.lines().map(|line| format!("+{}\n", line)).collect();
+    let declarations: Vec<String> = analyze_patch(path, &patch)
+        .iter()
+        .take(3)
+        .map(|change| format!("{} `{}`", change.kind, change.name))
+        .collect();
+    (!declarations.is_empty()).then(|| format!("defined {}", declarations.join(", ")))
+}
+
+/// Returns the first sentence of the comment a file starts with.
+fn leading_comment(content: &str) -> Option<String> {
+    let mut lines = content
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty() || line.starts_with("#!") || is_boilerplate(line))
+        .peekable();
+    let first = *lines.peek()?;
+
+    let mut text: Vec<&str> = Vec::new();
+    if let Some((start, end)) = BLOCK_COMMENTS
+        .iter()
+        .find(|(start, _)| first.starts_with(start))
+    {
+        for (index, line) in lines.enumerate() {
+            let line = if index == 0 {
+                &line[start.len()..]
+            } else {
+                line
+            };
+            let (part, closed) = match line.split_once(end) {
+                Some((part, _)) => (part, true),
+                None => (line, false),
+            };
+            let part = part.trim_start_matches('*').trim();
+            if part.is_empty() && !text.is_empty() {
+                break;
+            }
+            if !part.is_empty() {
+                text.push(part);
+            }
+            if closed {
+                break;
+            }
+        }
+    } else {
+        let marker = LINE_COMMENTS
+            .iter()
+            .find(|marker| first.starts_with(*marker))?;
+        for line in lines {
+            // `#include` or `#[derive]` are code, not comments
+            let Some(part) = line
+                .strip_prefix(marker)
+                .filter(|part| part.is_empty() || part.starts_with([' ', '\t', '#']))
+            else {
+                break;
+            };
+            let part = part.trim_start_matches('#').trim();
+            if part.is_empty() && !text.is_empty() {
+                break;
+            }
+            if !part.is_empty() && !is_boilerplate(part) {
+                text.push(part);
+            }
+        }
+    }
+
+    let text = text.join(" ");
+    let sentence = match text.find(". ") {
+        Some(end) => &text[..end],
+        None => text.trim_end_matches('.'),
+    };
+    if sentence.is_empty() {
+        return None;
+    }
+    let shortened = crate::prompt::truncate_chars(sentence, MAX_PURPOSE_CHARS);
+    if shortened.len() < sentence.len() {
+        Some(format!("{}...", shortened.trim_end()))
+    } else {
+        Some(sentence.to_string())
+    }
+}
+
+/// Returns whether a comment line is a license, copyright, or encoding
+/// notice rather than a description.
+fn is_boilerplate(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    lower.contains("copyright") || lower.contains("spdx-license") || lower.contains("-*-")
+}
+
+/// Records the former purpose of every deleted file from its content in
+/// HEAD.
+///
+/// Files whose content cannot be read are left without one.
+pub fn describe_deleted_files(repo: &Repository, files: &mut [ChangedFile]) {
+    for file in files.iter_mut() {
+        if !file.is_deleted() || file.is_submodule() {
+            continue;
+        }
+        match crate::git::head_content(repo, &file.path) {
+            Ok(content) => {
+                file.former_purpose = content.and_then(|c| describe_purpose(&file.path, &c));
+            }
+            Err(e) => log::debug!("No former purpose for {}: {:#}", file.path, e),
+        }
+    }
+}
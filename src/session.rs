@@ -0,0 +1,351 @@
+//! Saved plans of unfinished sessions.
+//!
+//! When the TUI exits with groups left uncommitted, the plan (groups with
+//! their edited messages, files, and which were committed) is written to
+//! `commit-wizard-session.json` in the git directory. The next run offers to
+//! resume it instead of grouping the changes again, as long as the working
+//! tree still matches: same branch and `HEAD`, the same changed files, and
+//! none of them edited since the plan was made. A session that ends with
+//! every group committed removes the file.
+//!
+//! The file lives in the git directory of the worktree, so it is never
+//! committed and each linked worktree keeps its own session.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use git2::{Oid, Status};
+use serde::{Deserialize, Serialize};
+
+use crate::fingerprint::{fingerprint_file, Snapshot};
+use crate::types::{ChangeGroup, ChangedFile, CommitType, FileKind};
+
+/// Name of the session file in the git directory.
+pub const SESSION_FILE: &str = "commit-wizard-session.json";
+
+/// Version of the session file format; files of other versions are ignored.
+const FORMAT_VERSION: u32 = 1;
+
+/// Returns the session file of the worktree with git directory `git_dir`.
+pub fn session_path(git_dir: &Path) -> PathBuf {
+    git_dir.join(SESSION_FILE)
+}
+
+/// A file of a saved group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedFile {
+    /// Path relative to the repository root
+    pub path: String,
+    /// Git status flags, as bits
+    pub status: u32,
+    /// Previous path if the file was renamed
+    pub old_path: Option<String>,
+    /// Whether the file is a submodule
+    pub submodule: bool,
+    /// Number of added lines, if known
+    pub lines_added: Option<usize>,
+    /// Number of removed lines, if known
+    pub lines_removed: Option<usize>,
+    /// Blob id of the content the plan was made from, if the file existed
+    pub fingerprint: Option<String>,
+}
+
+impl SavedFile {
+    fn from_file(file: &ChangedFile, fingerprint: Option<Oid>) -> Self {
+        Self {
+            path: file.path.clone(),
+            status: file.status.bits(),
+            old_path: file.old_path.clone(),
+            submodule: file.is_submodule(),
+            lines_added: file.lines_added,
+            lines_removed: file.lines_removed,
+            fingerprint: fingerprint.map(|id| id.to_string()),
+        }
+    }
+
+    fn to_file(&self) -> ChangedFile {
+        let mut file = ChangedFile::new(self.path.clone(), Status::from_bits_truncate(self.status));
+        file.old_path = self.old_path.clone();
+        if self.submodule {
+            file.kind = FileKind::Submodule;
+        }
+        file.lines_added = self.lines_added;
+        file.lines_removed = self.lines_removed;
+        file
+    }
+}
+
+/// A group of a saved plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedGroup {
+    /// Commit type, e.g. `feat`
+    pub commit_type: String,
+    /// Scope, if any
+    pub scope: Option<String>,
+    /// Ticket reference, if any
+    pub ticket: Option<String>,
+    /// Description as last edited
+    pub description: String,
+    /// Body lines as last edited
+    pub body_lines: Vec<String>,
+    /// Whether the description was generated because the message had none
+    pub description_generated: bool,
+    /// Whether the group was skipped
+    #[serde(default)]
+    pub skipped: bool,
+    /// Notes for the AI, if any
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Why the group is at its position in the commit order, if known
+    #[serde(default)]
+    pub order_note: Option<String>,
+    /// Whether the group was committed
+    pub committed: bool,
+    /// Id of the group's commit, if known
+    pub committed_as: Option<String>,
+    /// Files of the group
+    pub files: Vec<SavedFile>,
+}
+
+/// The plan of an unfinished session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSession {
+    /// Format version
+    pub version: u32,
+    /// Seconds since the Unix epoch when the session was saved
+    pub saved_at: u64,
+    /// Branch the session worked on
+    pub branch: String,
+    /// Commit `HEAD` pointed to when the session was saved, if any
+    pub head: Option<String>,
+    /// Groups in plan order
+    pub groups: Vec<SavedGroup>,
+    /// Files taken out of their groups
+    #[serde(default)]
+    pub orphaned: Vec<SavedFile>,
+}
+
+impl SavedSession {
+    /// Captures the plan of a session.
+    ///
+    /// Files are saved with the fingerprint `snapshot` recorded when the
+    /// plan was made, so edits made during the session keep the saved plan
+    /// from being resumed.
+    pub fn capture(
+        groups: &[ChangeGroup],
+        snapshot: &Snapshot,
+        branch: &str,
+        head: Option<Oid>,
+        saved_at: u64,
+    ) -> Self {
+        let groups = groups
+            .iter()
+            .map(|group| SavedGroup {
+                commit_type: group.commit_type.as_str().to_string(),
+                scope: group.scope.clone(),
+                ticket: group.ticket.clone(),
+                description: group.description.clone(),
+                body_lines: group.body().into_owned(),
+                description_generated: group.description_generated,
+                skipped: group.skipped,
+                notes: group.notes.clone(),
+                order_note: group.order_note.clone(),
+                committed: group.is_committed(),
+                committed_as: group.committed_as.map(|id| id.to_string()),
+                files: group
+                    .files
+                    .iter()
+                    .map(|file| SavedFile::from_file(file, snapshot.recorded(&file.path).flatten()))
+                    .collect(),
+            })
+            .collect();
+        Self {
+            version: FORMAT_VERSION,
+            saved_at,
+            branch: branch.to_string(),
+            head: head.map(|id| id.to_string()),
+            groups,
+            orphaned: Vec::new(),
+        }
+    }
+
+    /// Adds the files taken out of their groups, which stay left out when
+    /// the session is resumed.
+    pub fn with_orphaned_files(mut self, files: &[ChangedFile]) -> Self {
+        self.orphaned = files
+            .iter()
+            .map(|file| SavedFile::from_file(file, None))
+            .collect();
+        self
+    }
+
+    /// Returns the number of groups and of committed groups.
+    pub fn progress(&self) -> (usize, usize) {
+        let committed = self.groups.iter().filter(|g| g.committed).count();
+        (self.groups.len(), committed)
+    }
+
+    /// Returns whether the saved plan still describes the working tree.
+    ///
+    /// The branch and `HEAD` must be unchanged, the uncommitted groups and
+    /// the left-out files must be exactly the files in `changed`, and none
+    /// of the grouped files may have been edited since the plan was made.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be read.
+    pub fn matches(
+        &self,
+        workdir: &Path,
+        branch: &str,
+        head: Option<Oid>,
+        changed: &[ChangedFile],
+    ) -> Result<bool> {
+        if self.version != FORMAT_VERSION
+            || self.branch != branch
+            || self.head != head.map(|id| id.to_string())
+            || self
+                .groups
+                .iter()
+                .any(|g| parse_commit_type(&g.commit_type).is_none())
+        {
+            return Ok(false);
+        }
+
+        let planned: Vec<&SavedFile> = self
+            .groups
+            .iter()
+            .filter(|g| !g.committed)
+            .flat_map(|g| &g.files)
+            .collect();
+        let planned_paths: HashSet<&str> = planned
+            .iter()
+            .copied()
+            .chain(&self.orphaned)
+            .map(|f| f.path.as_str())
+            .collect();
+        let changed_paths: HashSet<&str> = changed.iter().map(|f| f.path.as_str()).collect();
+        if planned_paths != changed_paths {
+            return Ok(false);
+        }
+
+        for file in planned {
+            let current = fingerprint_file(workdir, &file.path)?.map(|id| id.to_string());
+            if current != file.fingerprint {
+                log::debug!("{} changed since the session was saved", file.path);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Rebuilds the groups of the plan.
+    ///
+    /// Files of uncommitted groups are taken from `changed`, so they carry
+    /// the analysis of this run; files of committed groups are restored as
+    /// saved. Check [`Self::matches`] first.
+    pub fn restore(&self, changed: Vec<ChangedFile>) -> Vec<ChangeGroup> {
+        let mut changed: HashMap<String, ChangedFile> = changed
+            .into_iter()
+            .map(|file| (file.path.clone(), file))
+            .collect();
+        self.groups
+            .iter()
+            .map(|saved| {
+                let files = saved
+                    .files
+                    .iter()
+                    .map(|file| match changed.remove(&file.path) {
+                        Some(current) if !saved.committed => current,
+                        _ => file.to_file(),
+                    })
+                    .collect();
+                let mut group = ChangeGroup::new(
+                    parse_commit_type(&saved.commit_type).unwrap_or(CommitType::Chore),
+                    saved.scope.clone(),
+                    files,
+                    saved.ticket.clone(),
+                    saved.description.clone(),
+                    saved.body_lines.clone(),
+                );
+                group.description_generated = saved.description_generated;
+                group.skipped = saved.skipped;
+                group.notes = saved.notes.clone();
+                group.order_note = saved.order_note.clone();
+                if saved.committed {
+                    match saved.committed_as.as_deref().map(Oid::from_str) {
+                        Some(Ok(id)) => group.mark_as_committed_as(id),
+                        _ => group.mark_as_committed(),
+                    }
+                }
+                group
+            })
+            .collect()
+    }
+
+    /// Returns the files of `changed` that were left out of the groups.
+    ///
+    /// Call before [`Self::restore`], which takes the other files.
+    pub fn restore_orphaned(&self, changed: &[ChangedFile]) -> Vec<ChangedFile> {
+        changed
+            .iter()
+            .filter(|file| self.orphaned.iter().any(|saved| saved.path == file.path))
+            .cloned()
+            .collect()
+    }
+
+    /// Reads the session saved at `path`.
+    ///
+    /// # Returns
+    ///
+    /// The session, or [`None`] if no session is saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let session = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Some(session))
+    }
+
+    /// Writes the session to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize the session")?;
+        fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Removes the session saved at `path`, if any.
+///
+/// # Errors
+///
+/// Returns an error if an existing file cannot be removed.
+pub fn remove_session(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Parses a commit type name such as `feat`.
+fn parse_commit_type(name: &str) -> Option<CommitType> {
+    CommitType::all()
+        .iter()
+        .copied()
+        .find(|t| t.as_str() == name)
+}
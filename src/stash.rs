@@ -0,0 +1,274 @@
+//! Stashing the changes that are not part of the commits for the session.
+//!
+//! Hooks and verification commands see the whole working tree, including
+//! changes the session leaves uncommitted: files left out of their groups,
+//! skipped groups, and untracked files that were declined. Stashing those
+//! for the session lets the hooks check exactly what is committed.
+//!
+//! The stash is restored when the TUI exits, and on every other exit path
+//! through [`Drop`]. The id of the stash is recorded in
+//! `commit-wizard-stash` in the git directory while it is held, so a
+//! session that was killed before it could restore is restored by the next
+//! run (see [`restore_leftover`]).
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use git2::{Oid, Repository};
+
+use crate::config::TimeoutConfig;
+use crate::git::execute_with_timeout;
+use crate::types::ChangeGroup;
+
+/// Message of the stash entries the session creates.
+const STASH_MESSAGE: &str = "commit-wizard: changes outside the groups";
+
+/// Name of the file recording the held stash in the git directory.
+pub const STASH_FILE: &str = "commit-wizard-stash";
+
+/// Returns the file recording the stash of the worktree with git directory
+/// `git_dir`.
+pub fn stash_marker_path(git_dir: &Path) -> PathBuf {
+    git_dir.join(STASH_FILE)
+}
+
+/// Lists the changed and untracked paths that no group to be committed
+/// contains, i.e. what the session leaves uncommitted.
+///
+/// Committed and skipped groups do not count; ignored files and
+/// submodules are never listed.
+///
+/// # Errors
+///
+/// Returns an error if the status cannot be read.
+pub fn unrelated_paths(repo: &Repository, groups: &[ChangeGroup]) -> Result<Vec<String>> {
+    let planned: HashSet<&str> = groups
+        .iter()
+        .filter(|g| !g.is_committed() && !g.skipped)
+        .flat_map(|g| &g.files)
+        .flat_map(|f| std::iter::once(f.path.as_str()).chain(f.old_path.as_deref()))
+        .collect();
+
+    let mut options = git2::StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .exclude_submodules(true);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .context("Failed to read the repository status")?;
+    let mut paths: Vec<String> = statuses
+        .iter()
+        .filter(|entry| !entry.status().is_ignored())
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .filter(|path| !planned.contains(path.as_str()))
+        .collect();
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+/// Changes stashed for the session; restored when dropped.
+#[derive(Debug)]
+pub struct SessionStash {
+    workdir: PathBuf,
+    git_dir: PathBuf,
+    commit: Oid,
+    paths: Vec<String>,
+    timeout: Duration,
+    restored: bool,
+}
+
+impl SessionStash {
+    /// Stashes the changes of the repository at `repo_path` that no group
+    /// to be committed contains (see [`unrelated_paths`]), untracked files
+    /// included.
+    ///
+    /// # Returns
+    ///
+    /// The stash, or `None` if there is nothing to stash.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a stash of an earlier session is still held, or
+    /// the changes cannot be stashed.
+    pub fn push(
+        repo_path: &Path,
+        groups: &[ChangeGroup],
+        timeouts: &TimeoutConfig,
+    ) -> Result<Option<Self>> {
+        let repo = Repository::discover(repo_path).context("Failed to open repository")?;
+        let workdir = repo
+            .workdir()
+            .context("Repository has no working directory")?
+            .to_path_buf();
+        let git_dir = repo.path().to_path_buf();
+        if stash_marker_path(&git_dir).exists() {
+            bail!("Changes of an earlier session are still stashed");
+        }
+
+        let paths = unrelated_paths(&repo, groups)?;
+        if paths.is_empty() {
+            return Ok(None);
+        }
+
+        // libgit2 stashes the whole working tree regardless of pathspecs,
+        // so this needs git itself
+        let previous = repo.refname_to_id("refs/stash").ok();
+        let timeout = timeouts.stage();
+        let mut cmd = git(&workdir);
+        cmd.args([
+            "stash",
+            "push",
+            "--include-untracked",
+            "-m",
+            STASH_MESSAGE,
+            "--",
+        ])
+        .args(&paths);
+        run(&mut cmd, timeout).context("Failed to stash the changes")?;
+        let commit = repo
+            .refname_to_id("refs/stash")
+            .ok()
+            .filter(|id| Some(*id) != previous)
+            .context("git stash created no stash entry")?;
+        log::info!("Stashed {} path(s) as {}", paths.len(), commit);
+
+        let stash = Self {
+            workdir,
+            git_dir,
+            commit,
+            paths,
+            timeout,
+            restored: false,
+        };
+        fs::write(stash_marker_path(&stash.git_dir), commit.to_string())
+            .context("Failed to record the stash")?;
+        Ok(Some(stash))
+    }
+
+    /// Returns the stashed paths, sorted.
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    /// Returns whether `path` is stashed.
+    pub fn contains(&self, path: &str) -> bool {
+        self.paths
+            .binary_search_by(|p| p.as_str().cmp(path))
+            .is_ok()
+    }
+
+    /// Puts the stashed changes back, staged parts into the index again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stash no longer exists or cannot be applied,
+    /// e.g. because a stashed file was created again meanwhile; the stash
+    /// is then kept for `git stash pop`.
+    pub fn restore(mut self) -> Result<()> {
+        self.pop()
+    }
+
+    fn pop(&mut self) -> Result<()> {
+        self.restored = true;
+        let mut repo = Repository::open(&self.git_dir).context("Failed to open repository")?;
+        pop_stash(&mut repo, &self.workdir, self.commit, self.timeout)?;
+        remove_marker(&self.git_dir)
+    }
+}
+
+impl Drop for SessionStash {
+    fn drop(&mut self) {
+        if self.restored {
+            return;
+        }
+        if let Err(e) = self.pop() {
+            log::error!("Failed to restore the session stash: {:#}", e);
+            eprintln!(
+                "⚠️  Could not restore the changes stashed for the session: {:#}\n   They are kept in the stash; restore them with `git stash pop`",
+                e
+            );
+        }
+    }
+}
+
+/// Restores the stash of a session that ended without restoring it, in the
+/// repository at `repo_path`.
+///
+/// # Returns
+///
+/// Whether a stash was restored.
+///
+/// # Errors
+///
+/// Returns an error if the recorded stash cannot be applied; it is then
+/// kept and no longer recorded, so later runs do not try again.
+pub fn restore_leftover(repo_path: &Path, timeouts: &TimeoutConfig) -> Result<bool> {
+    let mut repo = Repository::discover(repo_path).context("Failed to open repository")?;
+    let marker = stash_marker_path(repo.path());
+    let id = match fs::read_to_string(&marker) {
+        Ok(id) => id,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e).context("Failed to read the recorded stash"),
+    };
+    let git_dir = repo.path().to_path_buf();
+    let workdir = repo
+        .workdir()
+        .context("Repository has no working directory")?
+        .to_path_buf();
+    let result = Oid::from_str(id.trim())
+        .context("Invalid stash id")
+        .and_then(|commit| pop_stash(&mut repo, &workdir, commit, timeouts.stage()));
+    remove_marker(&git_dir)?;
+    result.map(|()| true)
+}
+
+/// Pops the stash entry `commit`, wherever it is in the stash list.
+fn pop_stash(repo: &mut Repository, workdir: &Path, commit: Oid, timeout: Duration) -> Result<()> {
+    let mut index = None;
+    repo.stash_foreach(|n, _, id| {
+        if *id == commit {
+            index = Some(n);
+        }
+        index.is_none()
+    })?;
+    let Some(index) = index else {
+        bail!("The stash {} no longer exists", commit);
+    };
+    let mut cmd = git(workdir);
+    cmd.args(["stash", "pop", "--index", &format!("stash@{{{}}}", index)]);
+    run(&mut cmd, timeout).with_context(|| format!("Failed to apply stash@{{{}}}", index))
+}
+
+/// Returns a git command running in `workdir`; paths are taken literally.
+fn git(workdir: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(workdir).env("GIT_LITERAL_PATHSPECS", "1");
+    cmd
+}
+
+/// Runs `cmd`, failing with its error output if it fails.
+fn run(cmd: &mut Command, timeout: Duration) -> Result<()> {
+    let output = execute_with_timeout(cmd, timeout, &AtomicBool::new(false))?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Removes the record of the session stash; a missing record is fine.
+fn remove_marker(git_dir: &Path) -> Result<()> {
+    match fs::remove_file(stash_marker_path(git_dir)) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            Err(e).context("Failed to remove the stash record")
+        }
+        _ => Ok(()),
+    }
+}
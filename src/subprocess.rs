@@ -0,0 +1,45 @@
+//! Policy for starting external programs.
+//!
+//! By default git operations run the `git` binary, so hooks, signing, and
+//! credential helpers behave as on the command line, and the GitHub Copilot
+//! provider runs the `copilot` CLI. Environments that forbid tools from
+//! spawning programs pass `--no-subprocess`: status, diffs, commits, and
+//! tags then go through libgit2, only HTTP AI providers are used, and
+//! anything that cannot work without a program (pushing, signed tags, the
+//! context command) fails with an explanation instead.
+//!
+//! The policy is process-wide and can only be tightened, so no code path
+//! can turn it back on once set.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{bail, Result};
+
+/// Whether starting external programs has been disabled.
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Forbids starting external programs for the rest of the process.
+pub fn disable() {
+    DISABLED.store(true, Ordering::SeqCst);
+    log::info!("External programs are disabled; git operations use libgit2");
+}
+
+/// Returns whether starting external programs has been disabled.
+pub fn is_disabled() -> bool {
+    DISABLED.load(Ordering::SeqCst)
+}
+
+/// Checks that `program` may be started.
+///
+/// # Errors
+///
+/// Returns an error naming `program` if external programs are disabled.
+pub fn ensure_allowed(program: &str) -> Result<()> {
+    if is_disabled() {
+        bail!(
+            "`{}` was not started: external programs are disabled by --no-subprocess",
+            program
+        );
+    }
+    Ok(())
+}
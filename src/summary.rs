@@ -0,0 +1,175 @@
+//! Report of a session, printed to stdout when the TUI exits.
+//!
+//! The report lists the groups that were committed (commit id, header, and
+//! number of files), the groups left uncommitted, and totals. With
+//! `--summary json` the same report is printed as JSON, so scripts wrapping
+//! the wizard can pick up the commits it created.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::types::ChangeGroup;
+
+/// Format of the session report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SummaryFormat {
+    /// Human-readable lines, omitted if there were no groups
+    #[default]
+    Text,
+    /// Pretty-printed JSON object, always printed
+    Json,
+}
+
+/// A group in the session report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GroupSummary {
+    /// Full id of the group's commit, if committed and known
+    pub commit: Option<String>,
+    /// Conventional commit header
+    pub header: String,
+    /// Number of files in the group
+    pub files: usize,
+}
+
+impl GroupSummary {
+    fn from_group(group: &ChangeGroup) -> Self {
+        Self {
+            commit: group.committed_as.map(|id| id.to_string()),
+            header: group.header(),
+            files: group.files.len(),
+        }
+    }
+}
+
+/// Totals of the session report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SummaryTotals {
+    /// Number of groups planned
+    pub groups: usize,
+    /// Number of groups committed
+    pub committed: usize,
+    /// Number of groups left uncommitted
+    pub skipped: usize,
+    /// Number of files in committed groups
+    pub files_committed: usize,
+    /// Lines added by committed groups, where known
+    pub lines_added: usize,
+    /// Lines removed by committed groups, where known
+    pub lines_removed: usize,
+}
+
+/// What a session committed and skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SessionSummary {
+    /// Committed groups, in plan order
+    pub committed: Vec<GroupSummary>,
+    /// Groups left uncommitted, in plan order
+    pub skipped: Vec<GroupSummary>,
+    /// Totals over all groups
+    pub totals: SummaryTotals,
+}
+
+impl SessionSummary {
+    /// Summarizes the groups at the end of a session.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::summary::SessionSummary;
+    /// use commit_wizard::types::{ChangeGroup, CommitType};
+    ///
+    /// let group = |commit_type, description: &str| {
+    ///     ChangeGroup::new(commit_type, None, vec![], None, description.into(), vec![])
+    /// };
+    /// let mut done = group(CommitType::Feat, "add login");
+    /// done.mark_as_committed();
+    /// let open = group(CommitType::Docs, "describe login");
+    ///
+    /// let summary = SessionSummary::from_groups(&[done, open]);
+    /// assert_eq!(summary.totals.committed, 1);
+    /// assert_eq!(summary.skipped[0].header, "docs: describe login");
+    /// ```
+    pub fn from_groups(groups: &[ChangeGroup]) -> Self {
+        let mut summary = Self::default();
+        for group in groups {
+            if group.is_committed() {
+                summary.totals.files_committed += group.files.len();
+                for file in &group.files {
+                    summary.totals.lines_added += file.lines_added.unwrap_or(0);
+                    summary.totals.lines_removed += file.lines_removed.unwrap_or(0);
+                }
+                summary.committed.push(GroupSummary::from_group(group));
+            } else {
+                summary.skipped.push(GroupSummary::from_group(group));
+            }
+        }
+        summary.totals.groups = groups.len();
+        summary.totals.committed = summary.committed.len();
+        summary.totals.skipped = summary.skipped.len();
+        summary
+    }
+
+    /// Renders the report in `format`.
+    ///
+    /// # Returns
+    ///
+    /// The report, or an empty string for a text report without groups.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if JSON serialization fails.
+    pub fn render(&self, format: SummaryFormat) -> anyhow::Result<String> {
+        match format {
+            SummaryFormat::Text => Ok(self.to_string()),
+            SummaryFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+        }
+    }
+}
+
+/// Returns `count` with the singular or plural of `noun`.
+fn plural(count: usize, noun: &str) -> String {
+    format!("{} {}{}", count, noun, if count == 1 { "" } else { "s" })
+}
+
+impl fmt::Display for SessionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.totals.groups == 0 {
+            return Ok(());
+        }
+        if !self.committed.is_empty() {
+            writeln!(
+                f,
+                "Committed {} of {}:",
+                self.totals.committed,
+                plural(self.totals.groups, "group")
+            )?;
+            for group in &self.committed {
+                let id = group
+                    .commit
+                    .as_deref()
+                    .map_or("???????", |id| id.get(..7).unwrap_or(id));
+                writeln!(
+                    f,
+                    "  {} {} ({})",
+                    id,
+                    group.header,
+                    plural(group.files, "file")
+                )?;
+            }
+        }
+        if !self.skipped.is_empty() {
+            writeln!(f, "Skipped {}:", plural(self.totals.skipped, "group"))?;
+            for group in &self.skipped {
+                writeln!(f, "  {} ({})", group.header, plural(group.files, "file"))?;
+            }
+        }
+        write!(
+            f,
+            "Total: {} committed, +{} -{} lines",
+            plural(self.totals.files_committed, "file"),
+            self.totals.lines_added,
+            self.totals.lines_removed
+        )
+    }
+}
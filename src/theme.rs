@@ -0,0 +1,258 @@
+//! Colors of the terminal interface.
+//!
+//! Panels, popups, the diff viewer and the shortcuts bar take their colors
+//! from a [`Theme`], which names each color by its role (`border`,
+//! `highlight`, `diff_added`, ...). The built-in `dark` theme suits dark
+//! terminal backgrounds, `light` light ones, and `high-contrast` uses only
+//! bright colors. Single roles can be overridden in the `[ui.colors]`
+//! configuration table with the color syntax of `[colors]` (see
+//! [`crate::palette`]):
+//!
+//! ```toml
+//! [ui]
+//! theme = "light"
+//!
+//! [ui.colors]
+//! highlight = "#d75f00"
+//! diff_added = "28"
+//! ```
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use ratatui::style::Color;
+use serde::Deserialize;
+
+/// A built-in theme.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// Colors for dark terminal backgrounds
+    #[default]
+    Dark,
+    /// Colors for light terminal backgrounds
+    Light,
+    /// Bright colors only
+    HighContrast,
+}
+
+/// Names of the roles that `[ui.colors]` can override.
+pub const ROLES: &[&str] = &[
+    "border",
+    "active_border",
+    "panel_border",
+    "popup_border",
+    "text",
+    "subtle",
+    "muted",
+    "highlight",
+    "info",
+    "success",
+    "error",
+    "special",
+    "inverse",
+    "diff_added",
+    "diff_removed",
+    "diff_hunk",
+    "diff_header",
+    "syntax_keyword",
+    "syntax_string",
+    "syntax_number",
+    "syntax_comment",
+];
+
+/// Colors of the interface by role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Borders of the panels and popups
+    pub border: Color,
+    /// Border of the focused panel
+    pub active_border: Color,
+    /// Borders of the file panels when not focused
+    pub panel_border: Color,
+    /// Borders of popups asking for input
+    pub popup_border: Color,
+    /// Emphasized text
+    pub text: Color,
+    /// Text of skipped groups
+    pub subtle: Color,
+    /// Labels, hints and committed groups
+    pub muted: Color,
+    /// Selection, shortcut keys and warnings
+    pub highlight: Color,
+    /// Informational marks
+    pub info: Color,
+    /// Successful operations and positive counts
+    pub success: Color,
+    /// Errors and problems
+    pub error: Color,
+    /// Diff viewer border and other accents
+    pub special: Color,
+    /// Text on a `highlight` or `success` background
+    pub inverse: Color,
+    /// Added lines in the diff viewer
+    pub diff_added: Color,
+    /// Removed lines in the diff viewer
+    pub diff_removed: Color,
+    /// Hunk headers in the diff viewer
+    pub diff_hunk: Color,
+    /// File headers in the diff viewer
+    pub diff_header: Color,
+    /// Keywords in the file viewer
+    pub syntax_keyword: Color,
+    /// String literals in the file viewer
+    pub syntax_string: Color,
+    /// Number literals in the file viewer
+    pub syntax_number: Color,
+    /// Comments in the file viewer
+    pub syntax_comment: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builtin(ThemeName::Dark)
+    }
+}
+
+impl Theme {
+    /// Returns a built-in theme.
+    pub fn builtin(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Self {
+                border: Color::Cyan,
+                active_border: Color::Green,
+                panel_border: Color::Blue,
+                popup_border: Color::Yellow,
+                text: Color::White,
+                subtle: Color::Gray,
+                muted: Color::DarkGray,
+                highlight: Color::Yellow,
+                info: Color::Cyan,
+                success: Color::Green,
+                error: Color::Red,
+                special: Color::Magenta,
+                inverse: Color::Black,
+                diff_added: Color::Green,
+                diff_removed: Color::Red,
+                diff_hunk: Color::Cyan,
+                diff_header: Color::Yellow,
+                syntax_keyword: Color::Magenta,
+                syntax_string: Color::Green,
+                syntax_number: Color::Yellow,
+                syntax_comment: Color::DarkGray,
+            },
+            // Dark shades of the 256-color palette stay readable on white
+            ThemeName::Light => Self {
+                border: Color::Blue,
+                active_border: Color::Indexed(28),
+                panel_border: Color::Indexed(240),
+                popup_border: Color::Indexed(130),
+                text: Color::Black,
+                subtle: Color::Indexed(240),
+                muted: Color::Indexed(245),
+                highlight: Color::Indexed(130),
+                info: Color::Blue,
+                success: Color::Indexed(28),
+                error: Color::Indexed(160),
+                special: Color::Indexed(90),
+                inverse: Color::White,
+                diff_added: Color::Indexed(28),
+                diff_removed: Color::Indexed(160),
+                diff_hunk: Color::Blue,
+                diff_header: Color::Indexed(130),
+                syntax_keyword: Color::Indexed(90),
+                syntax_string: Color::Indexed(28),
+                syntax_number: Color::Indexed(130),
+                syntax_comment: Color::Indexed(245),
+            },
+            ThemeName::HighContrast => Self {
+                border: Color::White,
+                active_border: Color::LightGreen,
+                panel_border: Color::White,
+                popup_border: Color::LightYellow,
+                text: Color::White,
+                subtle: Color::White,
+                muted: Color::Gray,
+                highlight: Color::LightYellow,
+                info: Color::LightCyan,
+                success: Color::LightGreen,
+                error: Color::LightRed,
+                special: Color::LightMagenta,
+                inverse: Color::Black,
+                diff_added: Color::LightGreen,
+                diff_removed: Color::LightRed,
+                diff_hunk: Color::LightCyan,
+                diff_header: Color::LightYellow,
+                syntax_keyword: Color::LightMagenta,
+                syntax_string: Color::LightGreen,
+                syntax_number: Color::LightYellow,
+                syntax_comment: Color::Gray,
+            },
+        }
+    }
+
+    /// Builds the theme from `[ui]`: the built-in theme `name` with the
+    /// roles in `overrides` replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for keys that are not roles and for values that
+    /// are not valid colors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use commit_wizard::theme::{Theme, ThemeName};
+    /// use ratatui::style::Color;
+    ///
+    /// let overrides = HashMap::from([("highlight".to_string(), "#d75f00".to_string())]);
+    /// let theme = Theme::from_config(ThemeName::Light, &overrides).unwrap();
+    /// assert_eq!(theme.highlight, Color::Rgb(215, 95, 0));
+    /// assert_eq!(theme.border, Theme::builtin(ThemeName::Light).border);
+    /// ```
+    pub fn from_config(name: ThemeName, overrides: &HashMap<String, String>) -> Result<Self> {
+        let mut theme = Self::builtin(name);
+        for (key, value) in overrides {
+            let role = theme.role_mut(key).ok_or_else(|| {
+                anyhow!(
+                    "Unknown color role '{}' in [ui.colors], expected one of: {}",
+                    key,
+                    ROLES.join(", ")
+                )
+            })?;
+            *role = Color::from_str(value)
+                .map_err(|_| anyhow!("Invalid color '{}' for '{}' in [ui.colors]", value, key))?;
+        }
+        Ok(theme)
+    }
+
+    /// Returns the color of the role named `name`.
+    fn role_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "border" => &mut self.border,
+            "active_border" => &mut self.active_border,
+            "panel_border" => &mut self.panel_border,
+            "popup_border" => &mut self.popup_border,
+            "text" => &mut self.text,
+            "subtle" => &mut self.subtle,
+            "muted" => &mut self.muted,
+            "highlight" => &mut self.highlight,
+            "info" => &mut self.info,
+            "success" => &mut self.success,
+            "error" => &mut self.error,
+            "special" => &mut self.special,
+            "inverse" => &mut self.inverse,
+            "diff_added" => &mut self.diff_added,
+            "diff_removed" => &mut self.diff_removed,
+            "diff_hunk" => &mut self.diff_hunk,
+            "diff_header" => &mut self.diff_header,
+            "syntax_keyword" => &mut self.syntax_keyword,
+            "syntax_string" => &mut self.syntax_string,
+            "syntax_number" => &mut self.syntax_number,
+            "syntax_comment" => &mut self.syntax_comment,
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,99 @@
+//! Ticket detection in branch names.
+//!
+//! By default tickets are Jira-style keys such as `LU-1234`. `[ticket]
+//! patterns` replaces them with other trackers' formats, e.g. GitHub
+//! (`#123`), GitLab merge requests (`!45`), or Azure Boards (`AB#123`). A
+//! branch may name several tickets; they are reported in the order they
+//! appear. Where the tickets go in the message is set by `[ticket]
+//! placement` (see [`crate::types::TicketPlacement`]).
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::config::TicketConfig;
+
+/// Pattern of Jira-style ticket keys, the default of `[ticket] patterns`.
+pub const DEFAULT_TICKET_PATTERN: &str = r"[A-Z]+-\d+";
+
+/// Finds tickets in branch names.
+#[derive(Debug, Clone)]
+pub struct TicketExtractor {
+    patterns: Vec<Regex>,
+}
+
+impl TicketExtractor {
+    /// Compiles the patterns of `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern is not a valid regular expression.
+    pub fn from_config(config: &TicketConfig) -> Result<Self> {
+        let patterns = config
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid [ticket] pattern '{}'", pattern))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Returns the tickets named by `branch`, in order of appearance and
+    /// without duplicates.
+    ///
+    /// Where matches of different patterns overlap, the longer one wins, so
+    /// `AB#123` is not also reported as `#123`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::config::TicketConfig;
+    /// use commit_wizard::ticket::TicketExtractor;
+    ///
+    /// let config = TicketConfig {
+    ///     patterns: vec![r"AB#\d+".into(), r"#\d+".into(), r"[A-Z]+-\d+".into()],
+    ///     ..Default::default()
+    /// };
+    /// let extractor = TicketExtractor::from_config(&config).unwrap();
+    /// assert_eq!(
+    ///     extractor.extract("fix/AB#12-and-#34-LU-5"),
+    ///     ["AB#12", "#34", "LU-5"]
+    /// );
+    /// ```
+    pub fn extract(&self, branch: &str) -> Vec<String> {
+        let mut matches: Vec<(usize, usize, String)> = Vec::new();
+        for pattern in &self.patterns {
+            for caps in pattern.captures_iter(branch) {
+                let Some(found) = caps.get(1).or_else(|| caps.get(0)) else {
+                    continue;
+                };
+                if !found.as_str().is_empty() {
+                    matches.push((found.start(), found.end(), found.as_str().to_string()));
+                }
+            }
+        }
+        // Earliest first; at the same position the longest first
+        matches.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+        let mut tickets: Vec<String> = Vec::new();
+        let mut covered = 0;
+        for (start, end, ticket) in matches {
+            if start < covered {
+                continue;
+            }
+            covered = end;
+            if !tickets.contains(&ticket) {
+                tickets.push(ticket);
+            }
+        }
+        tickets
+    }
+
+    /// Returns the tickets of `branch` joined for a commit message, e.g.
+    /// `LU-1, LU-2`, or [`None`] if there are none.
+    pub fn ticket_for(&self, branch: &str) -> Option<String> {
+        let tickets = self.extract(branch);
+        (!tickets.is_empty()).then(|| tickets.join(", "))
+    }
+}
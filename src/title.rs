@@ -0,0 +1,296 @@
+//! Checking pull request titles against the commit conventions.
+//!
+//! Repositories that squash-merge pull requests use the title as the commit
+//! header, so a title that breaks the conventions ends up in the history no
+//! matter how carefully the commits were made. `commit-wizard check-title`
+//! checks a title with the rules the wizard applies to its own commits: a
+//! known commit type, a description, and the `[scope]` policy (see
+//! [`ScopePolicy`]). CI jobs get the result as JSON, or as GitHub Actions
+//! error annotations with `--format github`.
+
+use std::fmt;
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::types::{ChangeGroup, CommitType};
+use crate::validation::{ScopeFix, ScopePolicy};
+
+/// Environment variable the title is read from when not given on the
+/// command line.
+pub const TITLE_ENV: &str = "PR_TITLE";
+
+/// Format of the check result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TitleFormat {
+    /// A line per problem
+    #[default]
+    Text,
+    /// Pretty-printed JSON object
+    Json,
+    /// GitHub Actions `::error` annotations
+    Github,
+}
+
+/// The rule a title breaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TitleRule {
+    /// The title is not `type(scope): description`
+    Format,
+    /// The commit type is unknown
+    Type,
+    /// The description is empty
+    Description,
+    /// The scope breaks the `[scope]` policy
+    Scope,
+}
+
+/// A rule a title breaks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TitleProblem {
+    /// The rule
+    pub rule: TitleRule,
+    /// What is wrong
+    pub message: String,
+}
+
+/// The result of checking a title.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TitleCheck {
+    /// The checked title
+    pub title: String,
+    /// Whether the title follows every rule
+    pub valid: bool,
+    /// Commit type, if the title has one
+    #[serde(rename = "type")]
+    pub commit_type: Option<String>,
+    /// Scope, if any
+    pub scope: Option<String>,
+    /// Whether the title marks a breaking change with `!`
+    pub breaking: bool,
+    /// Description, if the title has one
+    pub description: Option<String>,
+    /// The rules the title breaks
+    pub problems: Vec<TitleProblem>,
+    /// The title with the suggested fixes applied, if there are any
+    pub suggestion: Option<String>,
+}
+
+impl TitleCheck {
+    /// Checks `title` against the commit conventions and `policy`.
+    ///
+    /// A scope that breaks the policy is fixed in the suggestion where the
+    /// policy can tell the closest allowed scope; an uppercase type is
+    /// lowercased.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::config::ScopeConfig;
+    /// use commit_wizard::title::{TitleCheck, TitleRule};
+    /// use commit_wizard::validation::ScopePolicy;
+    ///
+    /// let policy = ScopePolicy::from_config(&ScopeConfig {
+    ///     allowed: vec!["auth".into(), "ui".into()],
+    ///     ..Default::default()
+    /// })
+    /// .unwrap();
+    ///
+    /// assert!(TitleCheck::run("feat(auth): add login", &policy).valid);
+    ///
+    /// let check = TitleCheck::run("feat(atuh): add login", &policy);
+    /// assert_eq!(check.problems[0].rule, TitleRule::Scope);
+    /// assert_eq!(check.suggestion.as_deref(), Some("feat(auth): add login"));
+    ///
+    /// let check = TitleCheck::run("Add login", &policy);
+    /// assert_eq!(check.problems[0].rule, TitleRule::Format);
+    /// ```
+    pub fn run(title: &str, policy: &ScopePolicy) -> Self {
+        let title = title.trim();
+        let mut check = Self {
+            title: title.to_string(),
+            valid: false,
+            commit_type: None,
+            scope: None,
+            breaking: false,
+            description: None,
+            problems: Vec::new(),
+            suggestion: None,
+        };
+
+        let pattern = Regex::new(
+            r"^(?P<type>[A-Za-z]+)(?:\((?P<scope>[^()]*)\))?(?P<breaking>!)?:(?P<description>.*)$",
+        )
+        .expect("valid title pattern");
+        let Some(captures) = pattern.captures(title) else {
+            check.problem(
+                TitleRule::Format,
+                format!(
+                    "'{}' is not a conventional commit header: expected 'type(scope): description' with type one of {}",
+                    title,
+                    type_names()
+                ),
+            );
+            return check;
+        };
+
+        let type_name = &captures["type"];
+        let scope = captures
+            .name("scope")
+            .map(|s| s.as_str().trim().to_string())
+            .filter(|s| !s.is_empty());
+        let rest = &captures["description"];
+        let description = rest.trim().to_string();
+        if !rest.is_empty() && !rest.starts_with(' ') {
+            check.problem(TitleRule::Format, "a space must follow the ':'".into());
+        }
+        check.commit_type = Some(type_name.to_string());
+        check.scope = scope.clone();
+        check.breaking = captures.name("breaking").is_some();
+        check.description = (!description.is_empty()).then(|| description.clone());
+
+        let mut fixed_type = type_name.to_string();
+        let commit_type = match parse_type(type_name) {
+            Some(commit_type) if commit_type.as_str() == type_name => Some(commit_type),
+            Some(commit_type) => {
+                check.problem(
+                    TitleRule::Type,
+                    format!(
+                        "type '{}' must be lowercase: '{}'",
+                        type_name,
+                        commit_type.as_str()
+                    ),
+                );
+                fixed_type = commit_type.as_str().to_string();
+                Some(commit_type)
+            }
+            None => {
+                check.problem(
+                    TitleRule::Type,
+                    format!(
+                        "unknown type '{}', expected one of {}",
+                        type_name,
+                        type_names()
+                    ),
+                );
+                None
+            }
+        };
+
+        if description.is_empty() {
+            check.problem(TitleRule::Description, "the description is empty".into());
+        }
+
+        let mut fixed_scope = scope.clone();
+        if let Some(commit_type) = commit_type {
+            let group = ChangeGroup::new(
+                commit_type,
+                scope,
+                Vec::new(),
+                None,
+                description.clone(),
+                Vec::new(),
+            );
+            if let Some(violation) = policy.validate(&group) {
+                check.problem(TitleRule::Scope, violation.to_string());
+                match violation.suggestion {
+                    Some(ScopeFix::Set(scope)) => fixed_scope = Some(scope),
+                    Some(ScopeFix::Remove) => fixed_scope = None,
+                    None => {}
+                }
+            }
+        }
+
+        check.valid = check.problems.is_empty();
+        if !check.valid {
+            let fixed = format!(
+                "{}{}{}: {}",
+                fixed_type,
+                fixed_scope
+                    .map(|scope| format!("({})", scope))
+                    .unwrap_or_default(),
+                if check.breaking { "!" } else { "" },
+                description
+            );
+            // Only worth showing if it fixes something
+            if fixed != title && !description.is_empty() {
+                check.suggestion = Some(fixed);
+            }
+        }
+        check
+    }
+
+    fn problem(&mut self, rule: TitleRule, message: String) {
+        self.problems.push(TitleProblem { rule, message });
+    }
+
+    /// Renders the result in `format`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if JSON serialization fails.
+    pub fn render(&self, format: TitleFormat) -> anyhow::Result<String> {
+        Ok(match format {
+            TitleFormat::Text => self.to_string(),
+            TitleFormat::Json => serde_json::to_string_pretty(self)?,
+            TitleFormat::Github => {
+                let mut out = String::new();
+                for problem in &self.problems {
+                    let mut message = problem.message.clone();
+                    if let Some(suggestion) = &self.suggestion {
+                        message.push_str(&format!("\nSuggested title: {}", suggestion));
+                    }
+                    out.push_str(&format!(
+                        "::error title=Pull request title::{}\n",
+                        escape_annotation(&message)
+                    ));
+                }
+                if self.valid {
+                    out.push_str(&format!("✓ {}\n", self.title));
+                }
+                out.trim_end().to_string()
+            }
+        })
+    }
+}
+
+impl fmt::Display for TitleCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.valid {
+            return write!(f, "✓ {}", self.title);
+        }
+        write!(f, "✗ {}", self.title)?;
+        for problem in &self.problems {
+            write!(f, "\n  • {}", problem.message)?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  → {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the commit type named `name`, ignoring case.
+fn parse_type(name: &str) -> Option<CommitType> {
+    CommitType::all()
+        .iter()
+        .copied()
+        .find(|t| t.as_str().eq_ignore_ascii_case(name))
+}
+
+fn type_names() -> String {
+    CommitType::all()
+        .iter()
+        .map(|t| t.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Escapes the characters GitHub Actions workflow commands treat specially.
+fn escape_annotation(message: &str) -> String {
+    message
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
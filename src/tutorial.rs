@@ -0,0 +1,178 @@
+//! Guided tour shown on the first run.
+//!
+//! Without a user configuration file and without a record of a previous
+//! tour, the TUI opens with an overlay that walks through the three panels
+//! and the core keys. Dismissing it, or reaching its end, writes a marker
+//! file to the state directory so it is shown only once. Delete
+//! `<state dir>/commit-wizard/tutorial-seen` to see it again.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::types::ActivePanel;
+
+/// One page of the tour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TutorialStep {
+    /// Title of the overlay
+    pub title: &'static str,
+    /// Panel the step is about, highlighted while the step is shown
+    pub panel: Option<ActivePanel>,
+    /// Explanation shown above the keys
+    pub text: &'static str,
+    /// Keys introduced by the step, as (key, description)
+    pub keys: &'static [(&'static str, &'static str)],
+}
+
+/// The pages of the tour, in order.
+pub const STEPS: &[TutorialStep] = &[
+    TutorialStep {
+        title: "Welcome to commit-wizard",
+        panel: None,
+        text: "Your changes were grouped into conventional commits. \
+               This short tour shows the three panels and the core keys.",
+        keys: &[],
+    },
+    TutorialStep {
+        title: "Commit groups",
+        panel: Some(ActivePanel::Groups),
+        text: "The left panel lists the planned commits. \
+               Each group becomes one commit.",
+        keys: &[("↑/↓", "Select a group"), ("Tab", "Switch between panels")],
+    },
+    TutorialStep {
+        title: "Commit message",
+        panel: Some(ActivePanel::CommitMessage),
+        text: "The top right panel shows the message of the selected group.",
+        keys: &[
+            ("e", "Edit the message"),
+            ("a", "Generate the message with AI"),
+        ],
+    },
+    TutorialStep {
+        title: "Files",
+        panel: Some(ActivePanel::Files),
+        text: "The bottom right panel lists the files of the selected group.",
+        keys: &[("d", "View the diff of the selected file")],
+    },
+    TutorialStep {
+        title: "Committing",
+        panel: None,
+        text: "When the groups look right, commit them.",
+        keys: &[
+            ("c", "Commit the selected group"),
+            ("C", "Commit all groups"),
+            ("?", "List all keys"),
+            ("q", "Quit"),
+        ],
+    },
+];
+
+/// Progress through the tour.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tutorial {
+    step: usize,
+    marker: Option<PathBuf>,
+}
+
+impl Tutorial {
+    /// Returns the tour if this is the first run, judged by the user
+    /// configuration file and the marker in the state directory.
+    pub fn first_run() -> Option<Self> {
+        let config = crate::config::user_config_path();
+        Self::detect(marker_path(), config.as_deref())
+    }
+
+    /// Returns the tour unless `marker` or `config` exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `marker` - File recording that the tour was seen; without one, the
+    ///   tour cannot be recorded and is not shown
+    /// * `config` - The user configuration file, if there is a location for it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::tutorial::Tutorial;
+    ///
+    /// let dir = tempfile::tempdir().unwrap();
+    /// let marker = dir.path().join("tutorial-seen");
+    ///
+    /// let tutorial = Tutorial::detect(Some(marker.clone()), None).unwrap();
+    /// tutorial.dismiss().unwrap();
+    /// assert!(Tutorial::detect(Some(marker), None).is_none());
+    /// ```
+    pub fn detect(marker: Option<PathBuf>, config: Option<&Path>) -> Option<Self> {
+        let marker = marker?;
+        if marker.exists() || config.is_some_and(Path::exists) {
+            return None;
+        }
+        Some(Self {
+            step: 0,
+            marker: Some(marker),
+        })
+    }
+
+    /// Returns the tour without a marker, e.g. to show it again on request.
+    pub fn replay() -> Self {
+        Self {
+            step: 0,
+            marker: None,
+        }
+    }
+
+    /// Returns the index of the current step.
+    pub fn index(&self) -> usize {
+        self.step
+    }
+
+    /// Returns the current step.
+    pub fn step(&self) -> &'static TutorialStep {
+        &STEPS[self.step]
+    }
+
+    /// Moves to the next step.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the current step was the last one.
+    pub fn forward(&mut self) -> bool {
+        if self.step + 1 < STEPS.len() {
+            self.step += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Moves to the previous step, if any.
+    pub fn back(&mut self) {
+        self.step = self.step.saturating_sub(1);
+    }
+
+    /// Records that the tour was seen.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the marker file cannot be written.
+    pub fn dismiss(&self) -> Result<()> {
+        let Some(marker) = &self.marker else {
+            return Ok(());
+        };
+        if let Some(dir) = marker.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::write(marker, "").with_context(|| format!("Failed to write {}", marker.display()))
+    }
+}
+
+/// Returns the file recording that the tour was seen.
+pub fn marker_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_dir)
+        .map(|dir| dir.join("commit-wizard").join("tutorial-seen"))
+}
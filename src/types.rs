@@ -4,6 +4,7 @@
 //! including commit types, changed files, commit groups, and application state.
 
 use git2::Status;
+use serde::Deserialize;
 
 /// Represents which panel is currently active for user interaction.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -36,6 +37,108 @@ impl ActivePanel {
     }
 }
 
+/// Where the ticket of the branch goes in commit messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TicketPlacement {
+    /// Before the description: `feat(api): LU-12: add users`
+    #[default]
+    Header,
+    /// In a `Refs: LU-12` footer
+    Footer,
+    /// Not in the message
+    Omit,
+}
+
+/// Order of the groups in the Groups panel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupSort {
+    /// The order the groups were created in
+    #[default]
+    Original,
+    /// By commit type (feat, fix, docs, ...)
+    Type,
+    /// Groups with the most files first
+    Size,
+    /// Uncommitted groups before committed ones
+    UncommittedFirst,
+}
+
+impl GroupSort {
+    /// Returns the next sort order (`o` key).
+    pub fn next(self) -> Self {
+        match self {
+            Self::Original => Self::Type,
+            Self::Type => Self::Size,
+            Self::Size => Self::UncommittedFirst,
+            Self::UncommittedFirst => Self::Original,
+        }
+    }
+
+    /// Returns a short label for the panel title.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Original => "original order",
+            Self::Type => "by type",
+            Self::Size => "by size",
+            Self::UncommittedFirst => "uncommitted first",
+        }
+    }
+}
+
+/// Which groups the Groups panel shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupFilter {
+    /// All groups
+    #[default]
+    All,
+    /// Only groups that are not committed yet
+    Uncommitted,
+    /// Only `feat` and `fix` groups
+    FeatFix,
+}
+
+impl GroupFilter {
+    /// Returns the next filter (`f` key).
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Self::Uncommitted,
+            Self::Uncommitted => Self::FeatFix,
+            Self::FeatFix => Self::All,
+        }
+    }
+
+    /// Returns a short label for the panel title.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Uncommitted => "uncommitted",
+            Self::FeatFix => "feat/fix",
+        }
+    }
+
+    /// Returns whether `group` passes the filter.
+    pub fn matches(self, group: &ChangeGroup) -> bool {
+        match self {
+            Self::All => true,
+            Self::Uncommitted => !group.is_committed(),
+            Self::FeatFix => matches!(group.commit_type, CommitType::Feat | CommitType::Fix),
+        }
+    }
+}
+
+/// What the diff viewer popup shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffView {
+    /// The file's diff
+    #[default]
+    Diff,
+    /// The full current content of the file
+    Preview,
+}
+
 /// Conventional commit types following the Conventional Commits specification.
 ///
 /// See: <https://www.conventionalcommits.org/>
@@ -89,6 +192,31 @@ impl CommitType {
         }
     }
 
+    /// Returns the icon marking commits of this type in `commit-wizard log`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::types::CommitType;
+    ///
+    /// assert_eq!(CommitType::Feat.icon(), "✨");
+    /// assert_eq!(CommitType::Fix.icon(), "🐛");
+    /// ```
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Feat => "✨",
+            Self::Fix => "🐛",
+            Self::Docs => "📝",
+            Self::Style => "🎨",
+            Self::Refactor => "🔨",
+            Self::Perf => "⚡",
+            Self::Test => "✅",
+            Self::Chore => "🔧",
+            Self::Ci => "👷",
+            Self::Build => "📦",
+        }
+    }
+
     /// Returns all available commit types.
     pub fn all() -> &'static [Self] {
         &[
@@ -106,6 +234,16 @@ impl CommitType {
     }
 }
 
+/// The kind of entry a [`ChangedFile`] refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileKind {
+    /// A regular file or symlink
+    #[default]
+    Regular,
+    /// A submodule whose recorded commit changed
+    Submodule,
+}
+
 /// Represents a single file that has been changed in the git repository.
 #[derive(Debug, Clone)]
 pub struct ChangedFile {
@@ -113,12 +251,100 @@ pub struct ChangedFile {
     pub path: String,
     /// Git status flags for this file
     pub status: Status,
+    /// Previous path if the file was renamed
+    pub old_path: Option<String>,
+    /// Whether this is a regular file or a submodule
+    pub kind: FileKind,
+    /// Number of added lines, if known (not set for binary files)
+    pub lines_added: Option<usize>,
+    /// Number of removed lines, if known (not set for binary files)
+    pub lines_removed: Option<usize>,
+    /// Estimated likelihood (0.0 to 1.0) that the change fixes a bug, if scored
+    pub fix_likelihood: Option<f32>,
+    /// Declarations the diff adds, removes, renames, or updates, if analyzed
+    pub symbol_changes: Vec<crate::semantic::SymbolChange>,
+    /// Scope assigned from the repository layout and `[scope]` or `[rules]`
+    /// settings, if resolved; `Some(None)` means no scope
+    pub scope: Option<Option<String>>,
+    /// Commit type from `[rules]`, if assigned
+    pub commit_type: Option<CommitType>,
+    /// Commit type suggested by the diff content and branch name, if any
+    pub suggested_type: Option<CommitType>,
+    /// Smallest path of the changed files this file is linked to by
+    /// references, if any; see [`crate::analysis::link_files`]
+    pub related_to: Option<String>,
+    /// What a deleted file was for, from its last committed content, if
+    /// known; see [`crate::semantic::describe_purpose`]
+    pub former_purpose: Option<String>,
 }
 
 impl ChangedFile {
     /// Creates a new changed file entry.
     pub fn new(path: String, status: Status) -> Self {
-        Self { path, status }
+        Self {
+            path,
+            status,
+            old_path: None,
+            kind: FileKind::Regular,
+            lines_added: None,
+            lines_removed: None,
+            fix_likelihood: None,
+            symbol_changes: Vec::new(),
+            scope: None,
+            commit_type: None,
+            suggested_type: None,
+            related_to: None,
+            former_purpose: None,
+        }
+    }
+
+    /// Sets the previous path of a renamed file.
+    pub fn with_old_path(mut self, old_path: Option<String>) -> Self {
+        self.old_path = old_path;
+        self
+    }
+
+    /// Sets the kind of entry.
+    pub fn with_kind(mut self, kind: FileKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Checks if the entry is a submodule pointer update.
+    pub fn is_submodule(&self) -> bool {
+        self.kind == FileKind::Submodule
+    }
+
+    /// Returns the path for display, as `old → new` for renames.
+    pub fn display_path(&self) -> String {
+        match &self.old_path {
+            Some(old_path) => format!("{} → {}", old_path, self.path),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Sets the line statistics from a collected diff.
+    ///
+    /// Binary diffs leave the statistics unset.
+    pub fn set_diff_stats(&mut self, diff: &FileDiff) {
+        if diff.is_binary {
+            self.lines_added = None;
+            self.lines_removed = None;
+        } else {
+            self.lines_added = Some(diff.lines_added);
+            self.lines_removed = Some(diff.lines_removed);
+        }
+    }
+
+    /// Returns whether the change is scored as a likely bug fix.
+    pub fn is_likely_fix(&self) -> bool {
+        self.fix_likelihood
+            .is_some_and(|score| score >= crate::classify::FIX_THRESHOLD)
+    }
+
+    /// Returns the `(added, removed)` line statistics, if known.
+    pub fn diff_stats(&self) -> Option<(usize, usize)> {
+        Some((self.lines_added?, self.lines_removed?))
     }
 
     /// Checks if the file was newly added.
@@ -131,17 +357,36 @@ impl ChangedFile {
         self.status.is_index_modified()
     }
 
-    /// Checks if the file was deleted.
+    /// Checks if the file was deleted, staged or not.
     pub fn is_deleted(&self) -> bool {
-        self.status.is_index_deleted()
+        self.status.is_index_deleted() || self.status.is_wt_deleted()
     }
 
     /// Checks if the file was renamed.
     pub fn is_renamed(&self) -> bool {
-        self.status.is_index_renamed()
+        self.status.is_index_renamed() || self.old_path.is_some()
     }
 }
 
+/// Diff output and line statistics for a single changed file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileDiff {
+    /// Unified diff text for the file
+    pub patch: String,
+    /// Number of added lines
+    pub lines_added: usize,
+    /// Number of removed lines
+    pub lines_removed: usize,
+    /// Whether git considers the file binary
+    pub is_binary: bool,
+}
+
+/// Prefix of the footer holding the ticket.
+const TICKET_FOOTER: &str = "Refs: ";
+
+/// Prefix of footers closing an issue.
+const CLOSES_FOOTER: &str = "Closes ";
+
 /// A logical group of changes representing a single potential commit.
 ///
 /// Files are grouped by commit type and scope to create cohesive,
@@ -154,14 +399,42 @@ pub struct ChangeGroup {
     pub scope: Option<String>,
     /// Files included in this commit
     pub files: Vec<ChangedFile>,
-    /// Optional ticket/issue reference (e.g., "LU-1234")
+    /// Optional ticket/issue reference (e.g., "LU-1234"); several tickets
+    /// are separated by ", "
     pub ticket: Option<String>,
+    /// Where the ticket goes in the message
+    pub ticket_placement: TicketPlacement,
+    /// Footers closing issues, e.g. `Closes #123: Login fails`
+    pub footers: Vec<String>,
     /// Short description of the changes
     pub description: String,
     /// Detailed bullet points for the commit body
     pub body_lines: Vec<String>,
+    /// Whether the body lines are still to be generated from the files;
+    /// large heuristic groups get them when first shown or committed
+    pub body_pending: bool,
     /// Whether this group has been committed
     pub committed: bool,
+    /// The commit created for this group, if it could be read back
+    pub committed_as: Option<git2::Oid>,
+    /// Whether files changed since the group was planned, so the message
+    /// may describe outdated changes
+    pub stale: bool,
+    /// Whether the description was generated from the files because the
+    /// message had none, and has not been reviewed in the editor yet
+    pub description_generated: bool,
+    /// Whether the group is skipped: committing all groups leaves it out
+    /// and its files stay untouched in the working tree
+    pub skipped: bool,
+    /// Context for the AI from the scratchpad, e.g. design notes; see
+    /// [`crate::notes`]
+    pub notes: Option<String>,
+    /// Why the AI placed the group at its position in the commit order,
+    /// e.g. `after the build change adding the regex crate`
+    pub order_note: Option<String>,
+    /// Output of every attempt to commit the group this session, oldest
+    /// first: hook output, hints and the summary line, or the error
+    pub commit_log: Vec<String>,
 }
 
 impl ChangeGroup {
@@ -182,9 +455,19 @@ impl ChangeGroup {
             scope,
             files,
             ticket,
+            ticket_placement: TicketPlacement::default(),
+            footers: Vec::new(),
             description,
             body_lines,
+            body_pending: false,
             committed: false,
+            skipped: false,
+            committed_as: None,
+            stale: false,
+            description_generated: false,
+            notes: None,
+            order_note: None,
+            commit_log: Vec::new(),
         }
     }
 
@@ -193,14 +476,134 @@ impl ChangeGroup {
         self.committed = true;
     }
 
+    /// Marks this group as committed as `commit`.
+    pub fn mark_as_committed_as(&mut self, commit: git2::Oid) {
+        self.committed = true;
+        self.committed_as = Some(commit);
+    }
+
+    /// Returns the commit log of the group as shown by `O`, one block per
+    /// attempt, or [`None`] before the first attempt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::types::{ChangeGroup, CommitType};
+    ///
+    /// let mut group = ChangeGroup::new(CommitType::Fix, None, vec![], None, "x".into(), vec![]);
+    /// assert_eq!(group.commit_log_text(), None);
+    /// group.commit_log.push("✗ hook failed".into());
+    /// group.commit_log.push("[main 1a2b3c4] fix: x".into());
+    /// assert_eq!(
+    ///     group.commit_log_text().unwrap(),
+    ///     "Attempt 1:\n✗ hook failed\n\nAttempt 2:\n[main 1a2b3c4] fix: x"
+    /// );
+    /// ```
+    pub fn commit_log_text(&self) -> Option<String> {
+        if self.commit_log.is_empty() {
+            return None;
+        }
+        let attempts: Vec<String> = self
+            .commit_log
+            .iter()
+            .enumerate()
+            .map(|(n, output)| format!("Attempt {}:\n{}", n + 1, output.trim_end()))
+            .collect();
+        Some(attempts.join("\n\n"))
+    }
+
+    /// Marks this group as not committed, after its commit was undone.
+    pub fn mark_as_uncommitted(&mut self) {
+        self.committed = false;
+        self.committed_as = None;
+    }
+
+    /// Returns the abbreviated id of the group's commit, if known.
+    pub fn short_commit(&self) -> Option<String> {
+        self.committed_as
+            .map(|id| id.to_string().chars().take(7).collect())
+    }
+
     /// Checks if this group has been committed.
     pub fn is_committed(&self) -> bool {
         self.committed
     }
 
+    /// Returns the body lines, generated from the files if they are still
+    /// pending (see [`Self::body_pending`]).
+    pub fn body(&self) -> std::borrow::Cow<'_, [String]> {
+        if self.body_pending {
+            std::borrow::Cow::Owned(crate::inference::infer_body_lines(&self.files))
+        } else {
+            std::borrow::Cow::Borrowed(&self.body_lines)
+        }
+    }
+
+    /// Generates the body lines if they are still pending and keeps them.
+    pub fn ensure_body(&mut self) {
+        if self.body_pending {
+            self.body_lines = crate::inference::infer_body_lines(&self.files);
+            self.body_pending = false;
+        }
+    }
+
+    /// Returns whether the message has no description, so the header would
+    /// end after the type, e.g. `feat: `.
+    pub fn has_empty_description(&self) -> bool {
+        self.description.trim().is_empty()
+    }
+
+    /// Generates a description from the files if the message has none,
+    /// and marks it as generated (see [`Self::description_generated`]).
+    ///
+    /// # Returns
+    ///
+    /// Whether a description was generated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::types::{ChangeGroup, CommitType};
+    ///
+    /// let mut group = ChangeGroup::new(
+    ///     CommitType::Feat,
+    ///     Some("api".to_string()),
+    ///     vec![],
+    ///     None,
+    ///     "  ".to_string(),
+    ///     vec![],
+    /// );
+    /// assert!(group.fill_empty_description());
+    /// assert_eq!(group.header(), "feat(api): add api");
+    /// assert!(group.description_generated);
+    /// ```
+    pub fn fill_empty_description(&mut self) -> bool {
+        if !self.has_empty_description() {
+            return false;
+        }
+        self.description =
+            crate::inference::infer_description(&self.files, self.commit_type, &self.scope);
+        self.description_generated = true;
+        true
+    }
+
+    /// Returns the summed `(added, removed)` line statistics of all files.
+    ///
+    /// Files without statistics are skipped; returns `None` if no file has any.
+    pub fn diff_stats(&self) -> Option<(usize, usize)> {
+        self.files
+            .iter()
+            .filter_map(ChangedFile::diff_stats)
+            .fold(None, |acc, (added, removed)| {
+                let (a, r) = acc.unwrap_or((0, 0));
+                Some((a + added, r + removed))
+            })
+    }
+
     /// Generates the commit message header line.
     ///
-    /// Format: `<type>[(<scope>)]: <ticket>: <description>`
+    /// Format: `<type>[(<scope>)]: <ticket>: <description>`; the ticket is
+    /// only part of the header with [`TicketPlacement::Header`].
     ///
     /// The header is automatically truncated if it exceeds [`Self::MAX_HEADER_LENGTH`].
     pub fn header(&self) -> String {
@@ -213,6 +616,7 @@ impl ChangeGroup {
         let ticket_part = self
             .ticket
             .as_ref()
+            .filter(|_| self.ticket_placement == TicketPlacement::Header)
             .map(|t| format!("{}: ", t))
             .unwrap_or_default();
 
@@ -242,14 +646,21 @@ impl ChangeGroup {
     ///
     /// - <body line 1>
     /// - <body line 2>
+    ///
+    /// Refs: <ticket>
+    /// Closes #123: <issue title>
     /// ```
+    ///
+    /// The `Refs:` footer is only added with [`TicketPlacement::Footer`],
+    /// `Closes` footers only if [`Self::footers`] has any.
     pub fn full_message(&self) -> String {
         let mut msg = String::new();
         msg.push_str(&self.header());
 
-        if !self.body_lines.is_empty() {
+        let body = self.body();
+        if !body.is_empty() {
             msg.push_str("\n\n");
-            for line in &self.body_lines {
+            for line in body.iter() {
                 msg.push_str("- ");
                 // Defensive: strip '- ' prefix if present (shouldn't happen, but be safe)
                 let clean_line = line.strip_prefix("- ").unwrap_or(line);
@@ -258,19 +669,55 @@ impl ChangeGroup {
             }
         }
 
+        let footers: Vec<String> = self
+            .ticket_footer()
+            .into_iter()
+            .chain(self.footers.iter().cloned())
+            .collect();
+        if !footers.is_empty() {
+            msg.push_str(if msg.ends_with('\n') { "\n" } else { "\n\n" });
+            msg.push_str(&footers.join("\n"));
+        }
+
         msg
     }
 
+    /// Returns the `Refs:` footer of the ticket, if the ticket goes in a
+    /// footer.
+    pub fn ticket_footer(&self) -> Option<String> {
+        self.ticket
+            .as_ref()
+            .filter(|_| self.ticket_placement == TicketPlacement::Footer)
+            .map(|ticket| format!("{}{}", TICKET_FOOTER, ticket))
+    }
+
     /// Updates the group from user-edited commit text.
     ///
     /// Parses the first line as the new description and subsequent
-    /// lines starting with "- " as body lines.
+    /// lines starting with "- " as body lines. A header starting with the
+    /// group's type sets the scope, e.g. `feat(api): ...`, or removes it,
+    /// e.g. `feat: ...`. With
+    /// [`TicketPlacement::Footer`], a `Refs:` line sets the ticket; `Closes`
+    /// lines replace the footers.
     pub fn set_from_commit_text(&mut self, text: &str) {
         let mut lines = text.lines();
 
         // Extract description from the first line
         if let Some(header) = lines.next() {
             let header_trimmed = header.trim();
+            if let Some((prefix, _)) = header_trimmed.split_once(": ") {
+                let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+                if let Some(rest) = prefix.strip_prefix(self.commit_type.as_str()) {
+                    if rest.is_empty() {
+                        self.scope = None;
+                    } else if let Some(scope) =
+                        rest.strip_prefix('(').and_then(|r| r.strip_suffix(')'))
+                    {
+                        self.scope = Some(scope.to_string());
+                    }
+                }
+            }
+            self.description_generated = false;
             // Try to extract description after the last ": "
             if let Some(idx) = header_trimmed.rfind(": ") {
                 self.description = header_trimmed[idx + 2..].trim().to_string();
@@ -282,8 +729,20 @@ impl ChangeGroup {
 
         // Extract body lines
         let mut body = Vec::new();
+        let mut footers = Vec::new();
         for line in lines {
             let trimmed = line.trim();
+            if trimmed.starts_with(CLOSES_FOOTER) {
+                footers.push(trimmed.to_string());
+                continue;
+            }
+            if self.ticket_placement == TicketPlacement::Footer {
+                if let Some(ticket) = trimmed.strip_prefix(TICKET_FOOTER) {
+                    let ticket = ticket.trim();
+                    self.ticket = (!ticket.is_empty()).then(|| ticket.to_string());
+                    continue;
+                }
+            }
             if let Some(stripped) = trimmed.strip_prefix("- ") {
                 body.push(stripped.to_string());
             } else if !trimmed.is_empty() {
@@ -292,6 +751,445 @@ impl ChangeGroup {
             }
         }
         self.body_lines = body;
+        self.body_pending = false;
+        self.footers = footers;
+    }
+}
+
+/// State of the remote picker popup.
+#[derive(Debug, Clone, Default)]
+pub struct RemotePicker {
+    /// Branch that will be pushed
+    pub branch: String,
+    /// Resolved remotes for the branch
+    pub targets: crate::remote::RemoteTargets,
+    /// Index of the highlighted remote
+    pub selected: usize,
+}
+
+impl RemotePicker {
+    /// Returns the highlighted remote name.
+    pub fn selected_remote(&self) -> Option<&str> {
+        self.targets.remotes.get(self.selected).map(String::as_str)
+    }
+
+    /// Moves the highlight down, stopping at the last remote.
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.targets.remotes.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Moves the highlight up, stopping at the first remote.
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+}
+
+/// State of the popup for cherry-picking new commits onto other branches.
+#[derive(Debug, Clone, Default)]
+pub struct BranchPicker {
+    /// Commits to pick, oldest first
+    pub commits: Vec<git2::Oid>,
+    /// Local branches that can be picked onto
+    pub branches: Vec<String>,
+    /// Whether each branch is chosen
+    pub chosen: Vec<bool>,
+    /// Index of the highlighted branch
+    pub selected: usize,
+}
+
+impl BranchPicker {
+    /// Creates a picker for `commits` with no branch chosen.
+    pub fn new(commits: Vec<git2::Oid>, branches: Vec<String>) -> Self {
+        Self {
+            commits,
+            chosen: vec![false; branches.len()],
+            branches,
+            selected: 0,
+        }
+    }
+
+    /// Moves the highlight down, stopping at the last branch.
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.branches.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Moves the highlight up, stopping at the first branch.
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Chooses or unchooses the highlighted branch.
+    pub fn toggle(&mut self) {
+        if let Some(chosen) = self.chosen.get_mut(self.selected) {
+            *chosen = !*chosen;
+        }
+    }
+
+    /// Returns the chosen branches, or the highlighted one if none is
+    /// chosen.
+    pub fn chosen_branches(&self) -> Vec<String> {
+        let chosen: Vec<String> = self
+            .branches
+            .iter()
+            .zip(&self.chosen)
+            .filter(|(_, &chosen)| chosen)
+            .map(|(branch, _)| branch.clone())
+            .collect();
+        if chosen.is_empty() {
+            self.branches
+                .get(self.selected)
+                .cloned()
+                .into_iter()
+                .collect()
+        } else {
+            chosen
+        }
+    }
+}
+
+/// Context of the session, shown in the header line.
+#[derive(Debug, Clone)]
+pub struct SessionInfo {
+    /// Repository name (the working directory's name)
+    pub repo_name: String,
+    /// Checked out branch
+    pub branch: String,
+    /// Ticket detected in the branch name
+    pub ticket: Option<String>,
+    /// HEAD commit and distance to the upstream
+    pub branch_status: crate::git::BranchStatus,
+    /// When the session started
+    pub started: std::time::Instant,
+}
+
+impl Default for SessionInfo {
+    fn default() -> Self {
+        Self {
+            repo_name: String::new(),
+            branch: String::new(),
+            ticket: None,
+            branch_status: crate::git::BranchStatus::default(),
+            started: std::time::Instant::now(),
+        }
+    }
+}
+
+impl SessionInfo {
+    /// Formats the time since the session started as `m:ss` or `h:mm:ss`.
+    pub fn elapsed_label(&self) -> String {
+        format_elapsed(self.started.elapsed())
+    }
+}
+
+/// Formats a duration as `m:ss`, or `h:mm:ss` from one hour on.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use commit_wizard::types::format_elapsed;
+///
+/// assert_eq!(format_elapsed(Duration::from_secs(75)), "1:15");
+/// assert_eq!(format_elapsed(Duration::from_secs(3725)), "1:02:05");
+/// ```
+pub fn format_elapsed(elapsed: std::time::Duration) -> String {
+    let secs = elapsed.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// State of the popup for choosing between AI message candidates.
+#[derive(Debug, Clone, Default)]
+pub struct CandidatePicker {
+    /// Index of the group the candidates belong to
+    pub group_index: usize,
+    /// Alternative descriptions with optional bodies
+    pub candidates: Vec<(String, Option<String>)>,
+    /// Index of the shown candidate
+    pub selected: usize,
+}
+
+impl CandidatePicker {
+    /// Returns the shown candidate.
+    pub fn selected_candidate(&self) -> Option<&(String, Option<String>)> {
+        self.candidates.get(self.selected)
+    }
+
+    /// Shows the next candidate, wrapping around after the last.
+    pub fn select_next(&mut self) {
+        if !self.candidates.is_empty() {
+            self.selected = (self.selected + 1) % self.candidates.len();
+        }
+    }
+
+    /// Shows the previous candidate, wrapping around before the first.
+    pub fn select_previous(&mut self) {
+        if !self.candidates.is_empty() {
+            self.selected = (self.selected + self.candidates.len() - 1) % self.candidates.len();
+        }
+    }
+}
+
+/// State of the popup listing groups whose files changed since planning.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StaleGroups {
+    /// Indices of the stale groups
+    pub groups: Vec<usize>,
+    /// Files that changed, in group order
+    pub files: Vec<String>,
+}
+
+/// State of the input renaming a scope in all groups of the session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScopeRename {
+    /// Scope being renamed
+    pub from: String,
+    /// New scope typed so far
+    pub input: String,
+    /// Whether to record the rename in [`crate::scope::ALIAS_FILE`] too
+    pub record: bool,
+}
+
+/// An action that waits for the user to confirm it (see
+/// [`crate::config::UiConfig::confirm`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Confirmation {
+    /// Commit all uncommitted groups
+    CommitAll {
+        /// Number of groups that would be committed
+        groups: usize,
+    },
+    /// Quit while groups are not committed yet
+    Quit {
+        /// Number of uncommitted groups
+        uncommitted: usize,
+    },
+}
+
+impl Confirmation {
+    /// Returns the question shown in the popup.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::types::Confirmation;
+    ///
+    /// assert_eq!(Confirmation::CommitAll { groups: 4 }.question(), "Commit 4 groups?");
+    /// assert_eq!(
+    ///     Confirmation::Quit { uncommitted: 1 }.question(),
+    ///     "Quit with 1 uncommitted group?"
+    /// );
+    /// ```
+    pub fn question(&self) -> String {
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        match *self {
+            Self::CommitAll { groups } => format!("Commit {} group{}?", groups, plural(groups)),
+            Self::Quit { uncommitted } => format!(
+                "Quit with {} uncommitted group{}?",
+                uncommitted,
+                plural(uncommitted)
+            ),
+        }
+    }
+}
+
+/// State of a group in a commit-all batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitState {
+    /// Not committed yet
+    Pending,
+    /// Committed, with the created commit if it could be read back
+    Committed(Option<git2::Oid>),
+    /// Committing failed
+    Failed,
+}
+
+/// A group of a commit-all batch as shown in the progress popup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitProgressEntry {
+    /// Index of the group in [`AppState::groups`]
+    pub index: usize,
+    /// Header of the commit message
+    pub header: String,
+    /// Where the group is
+    pub state: CommitState,
+    /// Output of `git commit`, or the error that stopped the group
+    pub output: String,
+}
+
+/// State of the popup following a commit-all batch group by group.
+///
+/// The worker stops a batch at the first failure; the popup then lets the
+/// user continue with the groups after the failed one, or abort.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::types::{ChangeGroup, CommitProgress, CommitType};
+///
+/// let group = |description: &str| {
+///     ChangeGroup::new(CommitType::Feat, None, vec![], None, description.into(), vec![])
+/// };
+/// let mut progress = CommitProgress::new(&[(0, group("add a")), (2, group("add b"))]);
+/// progress.record_failure("hook failed");
+/// assert!(progress.awaits_decision());
+/// assert_eq!(progress.remaining(), vec![2]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitProgress {
+    /// Groups of the batch, in commit order
+    pub entries: Vec<CommitProgressEntry>,
+    /// Whether the worker is committing
+    pub running: bool,
+    /// Scroll offset of the popup, in lines
+    pub scroll: usize,
+}
+
+impl CommitProgress {
+    /// Starts following a batch of `groups`, each with its index.
+    pub fn new(groups: &[(usize, ChangeGroup)]) -> Self {
+        Self {
+            entries: groups
+                .iter()
+                .map(|(index, group)| CommitProgressEntry {
+                    index: *index,
+                    header: group.header(),
+                    state: CommitState::Pending,
+                    output: String::new(),
+                })
+                .collect(),
+            running: true,
+            scroll: 0,
+        }
+    }
+
+    /// Records that the group at `index` was committed as `commit`.
+    pub fn record_committed(&mut self, index: usize, commit: Option<git2::Oid>, output: &str) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.index == index) {
+            entry.state = CommitState::Committed(commit);
+            entry.output = output.trim_end().to_string();
+        }
+    }
+
+    /// Records that the batch stopped with `error` at the first pending
+    /// group, which the worker was committing.
+    pub fn record_failure(&mut self, error: &str) {
+        self.running = false;
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|e| e.state == CommitState::Pending)
+        {
+            entry.state = CommitState::Failed;
+            entry.output = error.trim_end().to_string();
+        }
+    }
+
+    /// Returns the group being committed, if any.
+    pub fn current(&self) -> Option<usize> {
+        if !self.running {
+            return None;
+        }
+        self.entries
+            .iter()
+            .find(|e| e.state == CommitState::Pending)
+            .map(|e| e.index)
+    }
+
+    /// Returns the indices of the groups not committed yet, excluding the
+    /// failed ones.
+    pub fn remaining(&self) -> Vec<usize> {
+        self.entries
+            .iter()
+            .filter(|e| e.state == CommitState::Pending)
+            .map(|e| e.index)
+            .collect()
+    }
+
+    /// Returns the number of failed groups.
+    pub fn failed(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.state == CommitState::Failed)
+            .count()
+    }
+
+    /// Returns whether the batch stopped at a failure and groups are left
+    /// to continue with.
+    pub fn awaits_decision(&self) -> bool {
+        !self.running && self.failed() > 0 && !self.remaining().is_empty()
+    }
+
+    /// Returns the lines shown in the popup: each group with its state,
+    /// followed by its output.
+    pub fn lines(&self) -> Vec<String> {
+        let current = self.current();
+        let mut lines = Vec::new();
+        for entry in &self.entries {
+            let mark = match &entry.state {
+                CommitState::Committed(Some(commit)) => {
+                    format!("✓ {}", &commit.to_string()[..7])
+                }
+                CommitState::Committed(None) => "✓".to_string(),
+                CommitState::Failed => "✗".to_string(),
+                CommitState::Pending if current == Some(entry.index) => "⏳".to_string(),
+                CommitState::Pending => "·".to_string(),
+            };
+            lines.push(format!("{} {}", mark, entry.header));
+            lines.extend(entry.output.lines().map(|line| format!("    {}", line)));
+        }
+        lines
+    }
+
+    /// Scrolls the popup down by one line.
+    pub fn scroll_down(&mut self) {
+        if self.scroll + 1 < self.lines().len() {
+            self.scroll += 1;
+        }
+    }
+
+    /// Scrolls the popup up by one line.
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+}
+
+/// State of the popup comparing a regenerated message with the current one.
+#[derive(Debug, Clone, Default)]
+pub struct MessageComparison {
+    /// Index of the group the message belongs to
+    pub group_index: usize,
+    /// Generated description
+    pub description: String,
+    /// Generated body
+    pub body: Option<String>,
+    /// Refinement conversation kept if the new message is accepted
+    pub conversation: Option<Vec<crate::provider::ChatMessage>>,
+    /// Line diff from the current to the new commit message
+    pub diff: Vec<crate::compare::DiffLine>,
+    /// Scroll offset of the diff
+    pub scroll: usize,
+}
+
+impl MessageComparison {
+    /// Scrolls the diff down by one line.
+    pub fn scroll_down(&mut self) {
+        if self.scroll + 1 < self.diff.len() {
+            self.scroll += 1;
+        }
+    }
+
+    /// Scrolls the diff up by one line.
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
     }
 }
 
@@ -321,6 +1219,10 @@ pub struct AppState {
     pub diff_scroll_offset: usize,
     /// Path of the file being diffed
     pub diff_file_path: String,
+    /// Full current content of the file being diffed, if it could be read
+    pub preview_content: Option<String>,
+    /// Whether the diff viewer shows the diff or the file preview
+    pub diff_view: DiffView,
     /// Scroll offset for commit message panel
     pub commit_message_scroll_offset: usize,
     /// Selected file index in files panel
@@ -333,6 +1235,119 @@ pub struct AppState {
     pub commit_output_scroll: usize,
     /// Whether the commit output popup is shown
     pub show_commit_output: bool,
+    /// Group indices whose pre-commit warnings the user has already seen,
+    /// with their messages then; editing a message shows them again
+    pub commit_warnings_shown_for: Option<(Vec<usize>, Vec<String>)>,
+    /// Loaded configuration
+    pub config: crate::config::Config,
+    /// Newly added files that are missing the configured license header
+    pub license_offenders: std::collections::HashSet<String>,
+    /// Tag offered after committing a release commit
+    pub pending_tag: Option<crate::release::TagProposal>,
+    /// Remote picker shown before pushing
+    pub remote_picker: Option<RemotePicker>,
+    /// Commits created by the last commit batch, offered for cherry-picking
+    pub last_commits: Vec<git2::Oid>,
+    /// Commits created in this session with the index of their group,
+    /// oldest first; only these can be undone
+    pub session_commits: Vec<(usize, git2::Oid)>,
+    /// Branch picker for cherry-picking the last commits
+    pub branch_picker: Option<BranchPicker>,
+    /// Working tree the session operates on, shown when the repository has
+    /// several worktrees
+    pub worktree_label: Option<String>,
+    /// Provider for in-TUI message generation, if AI is enabled
+    pub ai_provider: Option<std::sync::Arc<dyn crate::provider::AiProvider>>,
+    /// AI request running in the background
+    pub ai_worker: Option<crate::worker::AiWorker>,
+    /// Worker running commits, pushes, and tagging in the background
+    pub events: Option<crate::events::EventBus>,
+    /// Output of the configured context command
+    pub command_context: Option<crate::context::CommandContext>,
+    /// `Closes` footers of the issues looked up for the branch
+    pub issue_footers: Vec<String>,
+    /// Hint that a newer release is available, shown in the header line
+    pub update_hint: Option<String>,
+    /// Result of the last commit attempt, reported as the exit code
+    pub outcome: crate::outcome::Outcome,
+    /// Colors of the commit types
+    pub palette: crate::palette::Palette,
+    /// Colors of the interface
+    pub theme: crate::theme::Theme,
+    /// Whether the help popup with shortcuts and color legend is shown
+    pub show_help: bool,
+    /// Lines the help popup is scrolled down by
+    pub help_scroll: u16,
+    /// Conversation with the AI per group index, used to refine messages
+    pub ai_conversations: std::collections::HashMap<usize, Vec<crate::provider::ChatMessage>>,
+    /// Feedback being typed for refining the selected group's message
+    pub feedback_input: Option<String>,
+    /// Name of a branch to create and switch to, while it is typed
+    pub branch_input: Option<String>,
+    /// Scope being renamed with `A`, if any
+    pub scope_rename: Option<ScopeRename>,
+    /// Notes for the selected group, while they are typed or pasted
+    pub notes_input: Option<String>,
+    /// Popup for choosing between generated message candidates
+    pub candidate_picker: Option<CandidatePicker>,
+    /// Popup comparing a regenerated message with the current one
+    pub message_comparison: Option<MessageComparison>,
+    /// Groups that must be refreshed before committing, if any
+    pub stale_groups: Option<StaleGroups>,
+    /// Action waiting for confirmation, if any
+    pub confirmation: Option<Confirmation>,
+    /// Progress of the running or stopped commit-all batch, if any
+    pub commit_progress: Option<CommitProgress>,
+    /// Indices of the groups sent to the worker by the running commit, in
+    /// commit order
+    pub commit_batch: Vec<usize>,
+    /// Content hashes of the files when the groups were planned
+    pub snapshot: crate::fingerprint::Snapshot,
+    /// Order of the Groups panel
+    pub group_sort: GroupSort,
+    /// Which groups the Groups panel shows
+    pub group_filter: GroupFilter,
+    /// Repository, branch, and timing shown in the header line
+    pub session: SessionInfo,
+    /// Why the session fell back from AI to heuristics, oldest first
+    pub ai_fallbacks: Vec<crate::fallback::AiFallback>,
+    /// Repository information shown in the info popup, with the AI
+    /// fallbacks, while it is open
+    pub repo_info: Option<crate::git::RepoInfo>,
+    /// Latest commits of the branch with their files, while the recent
+    /// commits popup is open
+    pub recent_commits: Option<Vec<crate::commit_log::RecentCommit>>,
+    /// Scroll offset of the recent commits popup
+    pub recent_commits_scroll: usize,
+    /// First-run tour, while it is shown
+    pub tutorial: Option<crate::tutorial::Tutorial>,
+    /// Format of the session report printed on exit
+    pub summary_format: crate::summary::SummaryFormat,
+    /// Actions and side panel added by an embedding application
+    pub extensions: crate::extension::Extensions,
+    /// Collects and analyzes the changed files again for `R`, if set up
+    pub replanner: Option<crate::replan::Replanner>,
+    /// Flag raised by `--watch` when files in the working tree change
+    pub worktree_events: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+    /// Whether the working tree changed in a way that outdates the groups
+    pub worktree_changed: bool,
+    /// When the working tree was last checked against the groups
+    pub worktree_checked: Option<std::time::Instant>,
+    /// Diffs shared by prompts, the diff viewer, and refreshes, if set up
+    pub diff_store: Option<std::sync::Arc<crate::diff_store::DiffStore>>,
+    /// WebAssembly plugins validating messages before committing
+    pub plugins: crate::plugin::PluginSet,
+    /// Files taken out of their groups; they are neither staged nor
+    /// committed this session
+    pub orphaned_files: Vec<ChangedFile>,
+    /// Files marked for a bulk operation, as (group index, file index)
+    pub marked_files: std::collections::HashSet<(usize, usize)>,
+    /// Query of the group and file search, while a search is active
+    pub search: Option<String>,
+    /// Whether the search query is being typed
+    pub search_typing: bool,
+    /// Changes stashed for the session, restored on exit
+    pub session_stash: Option<crate::stash::SessionStash>,
 }
 
 impl AppState {
@@ -351,13 +1366,747 @@ impl AppState {
             diff_content: String::new(),
             diff_scroll_offset: 0,
             diff_file_path: String::new(),
+            preview_content: None,
+            diff_view: DiffView::Diff,
             commit_message_scroll_offset: 0,
             selected_file_index: 0,
             files_scroll_offset: 0,
             commit_output: String::new(),
             commit_output_scroll: 0,
             show_commit_output: false,
+            commit_warnings_shown_for: None,
+            config: crate::config::Config::default(),
+            license_offenders: std::collections::HashSet::new(),
+            pending_tag: None,
+            remote_picker: None,
+            last_commits: Vec::new(),
+            session_commits: Vec::new(),
+            branch_picker: None,
+            worktree_label: None,
+            ai_provider: None,
+            ai_worker: None,
+            events: None,
+            command_context: None,
+            issue_footers: Vec::new(),
+            update_hint: None,
+            outcome: crate::outcome::Outcome::Success,
+            palette: crate::palette::Palette::default(),
+            theme: crate::theme::Theme::default(),
+            show_help: false,
+            help_scroll: 0,
+            ai_conversations: std::collections::HashMap::new(),
+            feedback_input: None,
+            notes_input: None,
+            branch_input: None,
+            scope_rename: None,
+            candidate_picker: None,
+            message_comparison: None,
+            stale_groups: None,
+            confirmation: None,
+            commit_progress: None,
+            commit_batch: Vec::new(),
+            snapshot: crate::fingerprint::Snapshot::default(),
+            group_sort: GroupSort::default(),
+            group_filter: GroupFilter::default(),
+            session: SessionInfo::default(),
+            ai_fallbacks: Vec::new(),
+            summary_format: crate::summary::SummaryFormat::default(),
+            repo_info: None,
+            recent_commits: None,
+            recent_commits_scroll: 0,
+            tutorial: None,
+            extensions: crate::extension::Extensions::default(),
+            replanner: None,
+            worktree_events: None,
+            worktree_changed: false,
+            worktree_checked: None,
+            diff_store: None,
+            plugins: crate::plugin::PluginSet::default(),
+            orphaned_files: Vec::new(),
+            marked_files: std::collections::HashSet::new(),
+            search: None,
+            search_typing: false,
+            session_stash: None,
+        }
+    }
+
+    /// Sets the configuration used by the session.
+    ///
+    /// Also applies the configured default sort order and filter, and the
+    /// ticket placement to the groups.
+    pub fn with_config(mut self, config: crate::config::Config) -> Self {
+        for group in &mut self.groups {
+            group.ticket_placement = config.ticket.placement;
+        }
+        self.group_sort = config.ui.sort;
+        self.group_filter = config.ui.filter;
+        self.config = config;
+        self.normalize_scopes();
+        self.fill_empty_descriptions();
+        self.ensure_selection_visible();
+        self
+    }
+
+    /// Generates the body lines of the selected group if they are still
+    /// pending, before it is shown.
+    pub fn ensure_selected_body(&mut self) {
+        if let Some(group) = self.groups.get_mut(self.selected_index) {
+            group.ensure_body();
+        }
+    }
+
+    /// Generates descriptions for uncommitted groups whose message has
+    /// none, unless `[message] empty_description` blocks them instead.
+    pub fn fill_empty_descriptions(&mut self) {
+        if self.config.message.empty_description != crate::validation::EmptyDescription::Fallback {
+            return;
+        }
+        for group in self.groups.iter_mut().filter(|g| !g.is_committed()) {
+            if group.fill_empty_description() {
+                log::warn!("Empty description replaced by '{}'", group.description);
+            }
+        }
+    }
+
+    /// Normalizes the scopes of all groups to the configured casing (see
+    /// [`crate::validation::ScopeCasing::normalize`]) and applies
+    /// `[scope] aliases`; scopes with nothing valid left are removed.
+    pub fn normalize_scopes(&mut self) {
+        let casing = self.config.scope.casing;
+        let aliases = &self.config.scope.aliases;
+        for group in &mut self.groups {
+            group.scope = group
+                .scope
+                .as_deref()
+                .and_then(|s| casing.normalize(s))
+                .map(|s| crate::scope::resolve_alias(aliases, &s).to_string());
+        }
+    }
+
+    /// Renames the scope `from` to `to` in every uncommitted group; an
+    /// empty `to` removes the scope.
+    ///
+    /// Returns the normalized new scope and the number of renamed groups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::types::{AppState, ChangeGroup, CommitType};
+    ///
+    /// let group = |scope: &str| {
+    ///     ChangeGroup::new(CommitType::Feat, Some(scope.into()), vec![], None, "x".into(), vec![])
+    /// };
+    /// let mut app = AppState::new(vec![group("users"), group("api"), group("users")]);
+    /// assert_eq!(app.rename_scope("users", "User"), (Some("user".to_string()), 2));
+    /// assert_eq!(app.groups[2].scope.as_deref(), Some("user"));
+    /// ```
+    pub fn rename_scope(&mut self, from: &str, to: &str) -> (Option<String>, usize) {
+        let to = self.config.scope.casing.normalize(to);
+        let mut renamed = 0;
+        for group in self.groups.iter_mut().filter(|g| !g.is_committed()) {
+            if group.scope.as_deref() == Some(from) {
+                group.scope = to.clone();
+                renamed += 1;
+            }
+        }
+        (to, renamed)
+    }
+
+    /// Summarizes which groups were committed and which were skipped,
+    /// printed when the session ends.
+    pub fn session_summary(&self) -> crate::summary::SessionSummary {
+        crate::summary::SessionSummary::from_groups(&self.groups)
+    }
+
+    /// Sets the AI provider, or [`None`] to disable AI features.
+    ///
+    /// `worker` is an already running request, such as the initial grouping.
+    pub fn with_ai(
+        mut self,
+        provider: Option<std::sync::Arc<dyn crate::provider::AiProvider>>,
+        worker: Option<crate::worker::AiWorker>,
+    ) -> Self {
+        self.ai_provider = provider;
+        self.ai_worker = worker;
+        self
+    }
+
+    /// Sets the AI fallbacks recorded before the TUI started, such as no
+    /// provider being available.
+    pub fn with_ai_fallbacks(mut self, fallbacks: Vec<crate::fallback::AiFallback>) -> Self {
+        self.ai_fallbacks = fallbacks;
+        self
+    }
+
+    /// Replaces all groups, e.g. with the result of AI grouping.
+    ///
+    /// Resets the selection, scroll positions, and AI conversations, and
+    /// applies the configured ticket placement, scope casing, empty
+    /// description handling, and the issue footers. Commits of the replaced groups can no longer be undone.
+    pub fn replace_groups(&mut self, mut groups: Vec<ChangeGroup>) {
+        // Files left out stay left out, e.g. when AI grouping finishes
+        if !self.orphaned_files.is_empty() {
+            for group in groups.iter_mut().filter(|g| !g.is_committed()) {
+                group.files.retain(|f| !self.is_orphaned(&f.path));
+            }
+            groups.retain(|g| !g.files.is_empty());
+        }
+        self.groups = groups;
+        self.session_commits.clear();
+        self.marked_files.clear();
+        for group in &mut self.groups {
+            group.ticket_placement = self.config.ticket.placement;
+            group.footers = self.issue_footers.clone();
+        }
+        self.normalize_scopes();
+        self.fill_empty_descriptions();
+        self.ai_conversations.clear();
+        self.selected_index = 0;
+        self.selected_file_index = 0;
+        self.commit_message_scroll_offset = 0;
+        self.ensure_selection_visible();
+    }
+
+    /// Replaces the uncommitted groups, keeping the committed ones in front
+    /// so the session summary still lists them.
+    ///
+    /// See [`Self::replace_groups`].
+    pub fn replace_open_groups(&mut self, groups: Vec<ChangeGroup>) {
+        let mut kept: Vec<ChangeGroup> = std::mem::take(&mut self.groups)
+            .into_iter()
+            .filter(ChangeGroup::is_committed)
+            .collect();
+        kept.extend(groups);
+        self.replace_groups(kept);
+    }
+
+    /// Takes a file out of its group, leaving it uncommitted this session.
+    ///
+    /// The file moves to [`Self::orphaned_files`]; the AI conversation of
+    /// the group is reset because its message was made for other files.
+    ///
+    /// # Returns
+    ///
+    /// The file that was taken out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the group or file does not exist, the group is
+    /// committed, or the file is the last one of the group.
+    pub fn remove_file_from_group(
+        &mut self,
+        group_index: usize,
+        file_index: usize,
+    ) -> anyhow::Result<&ChangedFile> {
+        let Some(group) = self.groups.get_mut(group_index) else {
+            anyhow::bail!("No group {}", group_index);
+        };
+        if group.is_committed() {
+            anyhow::bail!("Group already committed");
+        }
+        if file_index >= group.files.len() {
+            anyhow::bail!("No file {} in the group", file_index);
+        }
+        if group.files.len() == 1 {
+            anyhow::bail!("The last file of a group cannot be taken out; skip the group instead");
+        }
+
+        let file = group.files.remove(file_index);
+        if self.selected_index == group_index && self.selected_file_index >= group.files.len() {
+            self.selected_file_index = group.files.len() - 1;
+        }
+        self.ai_conversations.remove(&group_index);
+        // Later files of the group moved up by one
+        self.marked_files = self
+            .marked_files
+            .iter()
+            .filter(|&&mark| mark != (group_index, file_index))
+            .map(|&(group, file)| {
+                if group == group_index && file > file_index {
+                    (group, file - 1)
+                } else {
+                    (group, file)
+                }
+            })
+            .collect();
+        self.orphaned_files.push(file);
+        Ok(self.orphaned_files.last().expect("file was just added"))
+    }
+
+    /// Returns whether the file at `path` was taken out of its group.
+    pub fn is_orphaned(&self, path: &str) -> bool {
+        self.orphaned_files.iter().any(|f| f.path == path)
+    }
+
+    /// Marks the file at `file_index` of a group for a bulk operation, or
+    /// unmarks it.
+    ///
+    /// # Returns
+    ///
+    /// Whether the file is marked now.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the group does not exist or is committed, or the
+    /// file index is out of range.
+    pub fn toggle_file_mark(
+        &mut self,
+        group_index: usize,
+        file_index: usize,
+    ) -> anyhow::Result<bool> {
+        let Some(group) = self.groups.get(group_index) else {
+            anyhow::bail!("No group {}", group_index);
+        };
+        if group.is_committed() {
+            anyhow::bail!("Files of a committed group cannot be marked");
+        }
+        if file_index >= group.files.len() {
+            anyhow::bail!("No file {} in the group", file_index);
+        }
+        let mark = (group_index, file_index);
+        if self.marked_files.remove(&mark) {
+            return Ok(false);
+        }
+        self.marked_files.insert(mark);
+        Ok(true)
+    }
+
+    /// Returns the marked files of uncommitted groups, in group and file
+    /// order.
+    pub fn marks(&self) -> Vec<(usize, usize)> {
+        let mut marks: Vec<(usize, usize)> = self
+            .marked_files
+            .iter()
+            .copied()
+            .filter(|&(group, file)| {
+                self.groups
+                    .get(group)
+                    .is_some_and(|g| !g.is_committed() && file < g.files.len())
+            })
+            .collect();
+        marks.sort_unstable();
+        marks
+    }
+
+    /// Returns how many files of the group at `group_index` are marked.
+    pub fn marked_count(&self, group_index: usize) -> usize {
+        self.marked_files
+            .iter()
+            .filter(|(group, _)| *group == group_index)
+            .count()
+    }
+
+    /// Takes the marked files out of their groups like
+    /// [`Self::remove_file_from_group`] and clears the marks.
+    ///
+    /// # Returns
+    ///
+    /// The number of files left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, changing nothing, if no file is marked or a group
+    /// would be left without files.
+    pub fn remove_marked_files(&mut self) -> anyhow::Result<usize> {
+        let marks = self.marks();
+        if marks.is_empty() {
+            anyhow::bail!("No files marked");
+        }
+        self.check_marks_leave_files(&marks, None)?;
+
+        // Last files first, so the indices of the others stay valid
+        for &(group, file) in marks.iter().rev() {
+            let file = self.groups[group].files.remove(file);
+            self.ai_conversations.remove(&group);
+            self.orphaned_files.push(file);
+        }
+        self.marked_files.clear();
+        self.clamp_file_selection();
+        Ok(marks.len())
+    }
+
+    /// Moves the marked files into the group at `target` and clears the
+    /// marks. Files marked in the target group stay where they are.
+    ///
+    /// # Returns
+    ///
+    /// The number of files moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, changing nothing, if no file is marked, the target
+    /// group does not exist or is committed, or a group would be left
+    /// without files.
+    pub fn move_marked_files(&mut self, target: usize) -> anyhow::Result<usize> {
+        match self.groups.get(target) {
+            None => anyhow::bail!("No group {}", target),
+            Some(group) if group.is_committed() => {
+                anyhow::bail!("Files cannot be moved into a committed group")
+            }
+            Some(_) => {}
+        }
+        let marks: Vec<(usize, usize)> = self
+            .marks()
+            .into_iter()
+            .filter(|&(group, _)| group != target)
+            .collect();
+        if marks.is_empty() {
+            anyhow::bail!("No files marked in other groups");
+        }
+        self.check_marks_leave_files(&marks, Some(target))?;
+
+        let mut moved = Vec::new();
+        for &(group, file) in marks.iter().rev() {
+            moved.push(self.groups[group].files.remove(file));
+            self.ai_conversations.remove(&group);
+        }
+        moved.reverse();
+        self.groups[target].files.extend(moved);
+        self.ai_conversations.remove(&target);
+        self.marked_files.clear();
+        self.clamp_file_selection();
+        Ok(marks.len())
+    }
+
+    /// Moves the selected group one place up or down in the commit order,
+    /// swapping it with the neighbouring group shown in the Groups panel.
+    /// Committed groups keep their place; the group moves past them.
+    ///
+    /// `C` commits the groups in this order. State kept per group index
+    /// (AI conversations, marked files, session commits) moves with the
+    /// groups, and the moved group stays selected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, changing nothing, if the panel is sorted (the
+    /// order shown is then not the commit order), the selected group is
+    /// committed, or it is already first or last.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::types::{AppState, ChangeGroup, CommitType};
+    ///
+    /// let group = |t| ChangeGroup::new(t, None, vec![], None, "x".to_string(), vec![]);
+    /// let mut app = AppState::new(vec![group(CommitType::Feat), group(CommitType::Build)]);
+    /// app.select_group(1);
+    /// app.move_selected_group(true).unwrap();
+    /// assert_eq!(app.groups[0].commit_type, CommitType::Build);
+    /// assert_eq!(app.selected_index, 0);
+    /// assert!(app.move_selected_group(true).is_err());
+    /// ```
+    pub fn move_selected_group(&mut self, up: bool) -> anyhow::Result<()> {
+        if self.group_sort != GroupSort::Original {
+            anyhow::bail!(
+                "Groups are shown {}; press o until the original order to reorder them",
+                self.group_sort.label()
+            );
+        }
+        let from = self.selected_index;
+        match self.groups.get(from) {
+            None => anyhow::bail!("No group selected"),
+            Some(group) if group.is_committed() => {
+                anyhow::bail!("Committed groups keep their place")
+            }
+            Some(_) => {}
+        }
+        // Committed groups keep their place, so the group moves past them
+        let visible: Vec<usize> = self
+            .visible_groups()
+            .into_iter()
+            .filter(|&idx| idx == from || !self.groups[idx].is_committed())
+            .collect();
+        let position = visible.iter().position(|&idx| idx == from);
+        let to = match (position, up) {
+            (None, _) => anyhow::bail!("The selected group is hidden by the filter"),
+            (Some(0), true) => anyhow::bail!("The group is already first"),
+            (Some(pos), true) => visible[pos - 1],
+            (Some(pos), false) => match visible.get(pos + 1) {
+                Some(&idx) => idx,
+                None => anyhow::bail!("The group is already last"),
+            },
+        };
+
+        self.groups.swap(from, to);
+        let swap = |index: usize| match index {
+            i if i == from => to,
+            i if i == to => from,
+            i => i,
+        };
+        self.ai_conversations = std::mem::take(&mut self.ai_conversations)
+            .into_iter()
+            .map(|(index, conversation)| (swap(index), conversation))
+            .collect();
+        self.marked_files = self
+            .marked_files
+            .iter()
+            .map(|&(group, file)| (swap(group), file))
+            .collect();
+        for (index, _) in &mut self.session_commits {
+            *index = swap(*index);
+        }
+        self.commit_warnings_shown_for = None;
+        self.selected_index = to;
+        Ok(())
+    }
+
+    /// Fails if taking `marks` out of their groups empties a group other
+    /// than `target`.
+    fn check_marks_leave_files(
+        &self,
+        marks: &[(usize, usize)],
+        target: Option<usize>,
+    ) -> anyhow::Result<()> {
+        for (index, group) in self.groups.iter().enumerate() {
+            if Some(index) == target || group.files.is_empty() {
+                continue;
+            }
+            let marked = marks.iter().filter(|(g, _)| *g == index).count();
+            if marked == group.files.len() {
+                anyhow::bail!(
+                    "'{}' would be left without files; unmark one or skip the group",
+                    group.header()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether `text` contains the search query, ignoring ASCII
+    /// case. Nothing matches an empty or inactive search.
+    pub fn matches_search(&self, text: &str) -> bool {
+        match self.search.as_deref() {
+            Some(query) if !query.is_empty() => text
+                .to_ascii_lowercase()
+                .contains(&query.to_ascii_lowercase()),
+            _ => false,
+        }
+    }
+
+    /// Returns the search matches in display order: a group whose header
+    /// matches as `(group, None)`, followed by its matching files as
+    /// `(group, Some(file))`. Hidden groups are left out.
+    pub fn search_matches(&self) -> Vec<(usize, Option<usize>)> {
+        let mut matches = Vec::new();
+        for idx in self.visible_groups() {
+            let group = &self.groups[idx];
+            if self.matches_search(&group.header()) {
+                matches.push((idx, None));
+            }
+            for (file_idx, file) in group.files.iter().enumerate() {
+                if self.matches_search(&file.display_path()) {
+                    matches.push((idx, Some(file_idx)));
+                }
+            }
+        }
+        matches
+    }
+
+    /// Sets the search query and selects the first match at or after the
+    /// selection.
+    pub fn set_search(&mut self, query: String) {
+        self.search = Some(query);
+        self.jump_to_match(true, true);
+    }
+
+    /// Selects the next search match, wrapping around.
+    ///
+    /// # Returns
+    ///
+    /// Whether there was a match to select.
+    pub fn next_match(&mut self) -> bool {
+        self.jump_to_match(true, false)
+    }
+
+    /// Selects the previous search match, wrapping around.
+    ///
+    /// # Returns
+    ///
+    /// Whether there was a match to select.
+    pub fn previous_match(&mut self) -> bool {
+        self.jump_to_match(false, false)
+    }
+
+    /// Ends the search.
+    pub fn clear_search(&mut self) {
+        self.search = None;
+        self.search_typing = false;
+    }
+
+    /// Selects the match after (or before) the selection; with `inclusive`,
+    /// a match at the selection itself is kept.
+    fn jump_to_match(&mut self, forward: bool, inclusive: bool) -> bool {
+        let matches = self.search_matches();
+        if matches.is_empty() {
+            return false;
+        }
+        let visible = self.visible_groups();
+        let position = |(group, file): (usize, Option<usize>)| {
+            let group_pos = visible.iter().position(|&idx| idx == group);
+            (group_pos, file.map_or(0, |f| f + 1))
+        };
+        let current = position((
+            self.selected_index,
+            (self.active_panel == ActivePanel::Files).then_some(self.selected_file_index),
+        ));
+        let target = if forward {
+            matches
+                .iter()
+                .find(|&&m| {
+                    let pos = position(m);
+                    pos > current || (inclusive && pos == current)
+                })
+                .or_else(|| matches.first())
+        } else {
+            matches
+                .iter()
+                .rev()
+                .find(|&&m| {
+                    let pos = position(m);
+                    pos < current || (inclusive && pos == current)
+                })
+                .or_else(|| matches.last())
+        };
+        let Some(&(group, file)) = target else {
+            return false;
+        };
+        if group != self.selected_index {
+            self.select_group(group);
+        }
+        match file {
+            Some(file) => {
+                self.selected_file_index = file;
+                self.active_panel = ActivePanel::Files;
+            }
+            None => self.active_panel = ActivePanel::Groups,
+        }
+        true
+    }
+
+    /// Keeps the file selection within the selected group's files.
+    fn clamp_file_selection(&mut self) {
+        let len = self.selected_group().map_or(0, |g| g.files.len());
+        self.selected_file_index = self.selected_file_index.min(len.saturating_sub(1));
+    }
+
+    /// Returns whether an AI request is running in the background.
+    pub fn is_ai_busy(&self) -> bool {
+        self.ai_worker.is_some()
+    }
+
+    /// Returns whether a git operation runs in the background.
+    pub fn is_git_busy(&self) -> bool {
+        self.events
+            .as_ref()
+            .is_some_and(crate::events::EventBus::is_busy)
+    }
+
+    /// Sets the session context shown in the header line.
+    pub fn with_session(mut self, session: SessionInfo) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Sets the colors of the commit types.
+    pub fn with_palette(mut self, palette: crate::palette::Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Sets the colors of the interface.
+    pub fn with_theme(mut self, theme: crate::theme::Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets the output of the context command run before grouping.
+    pub fn with_command_context(mut self, context: Option<crate::context::CommandContext>) -> Self {
+        self.command_context = context;
+        self
+    }
+
+    /// Sets how `R` collects and analyzes the changed files again.
+    pub fn with_replanner(mut self, replanner: Option<crate::replan::Replanner>) -> Self {
+        self.replanner = replanner;
+        self
+    }
+
+    /// Sets the flag raised when files in the working tree change, which
+    /// makes the TUI check whether the groups are outdated.
+    pub fn with_worktree_events(
+        mut self,
+        events: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+    ) -> Self {
+        self.worktree_events = events;
+        self
+    }
+
+    /// Sets the store the diffs are loaded from; without one, diffs are
+    /// asked from git each time.
+    pub fn with_diff_store(
+        mut self,
+        store: Option<std::sync::Arc<crate::diff_store::DiffStore>>,
+    ) -> Self {
+        self.diff_store = store;
+        self
+    }
+
+    /// Sets the files left out of the groups, e.g. of a resumed session.
+    pub fn with_orphaned_files(mut self, files: Vec<ChangedFile>) -> Self {
+        self.orphaned_files = files;
+        self
+    }
+
+    /// Sets the hint that a newer release is available.
+    pub fn with_update_hint(mut self, hint: Option<String>) -> Self {
+        self.update_hint = hint;
+        self
+    }
+
+    /// Sets the content hashes of the planned files, used to detect files
+    /// edited before their group is committed.
+    pub fn with_snapshot(mut self, snapshot: crate::fingerprint::Snapshot) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Sets the `Closes` footers added to every group, e.g. for the issues
+    /// named by the branch.
+    pub fn with_issue_footers(mut self, footers: Vec<String>) -> Self {
+        for group in &mut self.groups {
+            group.footers = footers.clone();
         }
+        self.issue_footers = footers;
+        self
+    }
+
+    /// Sets the WebAssembly plugins that validate messages before
+    /// committing.
+    pub fn with_plugins(mut self, plugins: crate::plugin::PluginSet) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Sets the scopes the message editor completes.
+    pub fn with_scope_vocabulary(mut self, vocabulary: crate::scope::ScopeVocabulary) -> Self {
+        self.editor.set_scope_vocabulary(vocabulary);
+        self
+    }
+
+    /// Sets the working tree label shown in the shortcuts bar.
+    pub fn with_worktree_label(mut self, label: Option<String>) -> Self {
+        self.worktree_label = label;
+        self
+    }
+
+    /// Returns whether any file of the group at `index` lacks the license header.
+    pub fn group_has_license_offenders(&self, index: usize) -> bool {
+        self.groups.get(index).is_some_and(|group| {
+            group
+                .files
+                .iter()
+                .any(|f| self.license_offenders.contains(&f.path))
+        })
     }
 
     /// Returns a mutable reference to the currently selected group.
@@ -370,25 +2119,87 @@ impl AppState {
         self.groups.get(self.selected_index)
     }
 
-    /// Moves selection to the next group (wraps around).
+    /// Returns the indices of the groups shown in the Groups panel, in
+    /// display order.
+    pub fn visible_groups(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.groups.len())
+            .filter(|&idx| self.group_filter.matches(&self.groups[idx]))
+            .collect();
+
+        // Stable sorts keep the original order among equal groups
+        let groups = &self.groups;
+        match self.group_sort {
+            GroupSort::Original => {}
+            GroupSort::Type => indices.sort_by_key(|&idx| groups[idx].commit_type),
+            GroupSort::Size => {
+                indices.sort_by_key(|&idx| std::cmp::Reverse(groups[idx].files.len()))
+            }
+            GroupSort::UncommittedFirst => indices.sort_by_key(|&idx| groups[idx].is_committed()),
+        }
+        indices
+    }
+
+    /// Moves the selection to the first visible group if the selected group
+    /// is hidden.
+    ///
+    /// A filter that hides every uncommitted group is reset, so actions
+    /// never apply to a group that cannot be seen.
+    pub fn ensure_selection_visible(&mut self) {
+        let mut visible = self.visible_groups();
+        if visible.is_empty() && self.groups.iter().any(|g| !g.is_committed()) {
+            self.group_filter = GroupFilter::All;
+            visible = self.visible_groups();
+        }
+        if !visible.contains(&self.selected_index) {
+            if let Some(&first) = visible.first() {
+                self.select_group(first);
+            }
+        }
+    }
+
+    /// Switches to the next sort order.
+    pub fn cycle_group_sort(&mut self) {
+        self.group_sort = self.group_sort.next();
+    }
+
+    /// Switches to the next filter, skipping filters that match no group.
+    pub fn cycle_group_filter(&mut self) {
+        let mut filter = self.group_filter.next();
+        while filter != GroupFilter::All && !self.groups.iter().any(|g| filter.matches(g)) {
+            filter = filter.next();
+        }
+        self.group_filter = filter;
+        self.ensure_selection_visible();
+    }
+
+    /// Selects the group at `index`, resetting per-group view state.
+    pub fn select_group(&mut self, index: usize) {
+        self.selected_index = index;
+        self.reset_file_selection();
+        self.reset_commit_message_scroll();
+    }
+
+    /// Moves selection to the next visible group (wraps around).
     pub fn select_next(&mut self) {
-        if !self.groups.is_empty() {
-            self.selected_index = (self.selected_index + 1) % self.groups.len();
-            self.reset_file_selection();
-            self.reset_commit_message_scroll();
+        let visible = self.visible_groups();
+        let next = match visible.iter().position(|&idx| idx == self.selected_index) {
+            Some(pos) => visible.get((pos + 1) % visible.len()),
+            None => visible.first(),
+        };
+        if let Some(&next) = next {
+            self.select_group(next);
         }
     }
 
-    /// Moves selection to the previous group (wraps around).
+    /// Moves selection to the previous visible group (wraps around).
     pub fn select_previous(&mut self) {
-        if !self.groups.is_empty() {
-            self.selected_index = if self.selected_index == 0 {
-                self.groups.len() - 1
-            } else {
-                self.selected_index - 1
-            };
-            self.reset_file_selection();
-            self.reset_commit_message_scroll();
+        let visible = self.visible_groups();
+        let previous = match visible.iter().position(|&idx| idx == self.selected_index) {
+            Some(pos) => visible.get((pos + visible.len() - 1) % visible.len()),
+            None => visible.first(),
+        };
+        if let Some(&previous) = previous {
+            self.select_group(previous);
         }
     }
 
@@ -439,6 +2250,50 @@ impl AppState {
         self.diff_content = content;
         self.diff_scroll_offset = 0;
         self.show_diff_viewer = true;
+        self.preview_content = None;
+        self.diff_view = DiffView::Diff;
+    }
+
+    /// Shows the diff viewer with the file's diff and current content.
+    ///
+    /// Opens on the preview when `view` is [`DiffView::Preview`] and a
+    /// preview is available, otherwise on the diff.
+    pub fn show_diff_with_preview(
+        &mut self,
+        file_path: String,
+        diff: String,
+        preview: Option<String>,
+        view: DiffView,
+    ) {
+        self.show_diff(file_path, diff);
+        self.diff_view = if preview.is_some() {
+            view
+        } else {
+            DiffView::Diff
+        };
+        self.preview_content = preview;
+    }
+
+    /// Switches the diff viewer between diff and file preview.
+    ///
+    /// Does nothing if no preview is available.
+    pub fn toggle_diff_view(&mut self) {
+        if self.preview_content.is_none() {
+            return;
+        }
+        self.diff_view = match self.diff_view {
+            DiffView::Diff => DiffView::Preview,
+            DiffView::Preview => DiffView::Diff,
+        };
+        self.diff_scroll_offset = 0;
+    }
+
+    /// Returns the text currently shown in the diff viewer.
+    pub fn diff_viewer_content(&self) -> &str {
+        match (self.diff_view, &self.preview_content) {
+            (DiffView::Preview, Some(preview)) => preview,
+            _ => &self.diff_content,
+        }
     }
 
     /// Closes the diff viewer.
@@ -447,12 +2302,15 @@ impl AppState {
         self.diff_content.clear();
         self.diff_file_path.clear();
         self.diff_scroll_offset = 0;
+        self.preview_content = None;
+        self.diff_view = DiffView::Diff;
     }
 
     /// Scrolls the diff viewer down.
     pub fn scroll_diff_down(&mut self) {
-        if !self.diff_content.is_empty() {
-            let max_offset = self.diff_content.lines().count().saturating_sub(1);
+        let content = self.diff_viewer_content();
+        if !content.is_empty() {
+            let max_offset = content.lines().count().saturating_sub(1);
             if self.diff_scroll_offset < max_offset {
                 self.diff_scroll_offset += 1;
             }
@@ -476,6 +2334,65 @@ impl AppState {
         self.show_editor_help = false;
     }
 
+    /// Toggles the help popup.
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+        self.help_scroll = 0;
+    }
+
+    /// Adds the actions and side panel of an embedding application.
+    pub fn with_extensions(mut self, extensions: crate::extension::Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Sets the format of the session report printed on exit.
+    pub fn with_summary_format(mut self, format: crate::summary::SummaryFormat) -> Self {
+        self.summary_format = format;
+        self
+    }
+
+    /// Shows the first-run tour, if any.
+    pub fn with_tutorial(mut self, tutorial: Option<crate::tutorial::Tutorial>) -> Self {
+        self.tutorial = tutorial;
+        self.focus_tutorial_panel();
+        self
+    }
+
+    /// Moves the tour to its next step, closing it after the last one.
+    pub fn advance_tutorial(&mut self) {
+        match self.tutorial.as_mut().map(|tutorial| tutorial.forward()) {
+            Some(true) => self.focus_tutorial_panel(),
+            Some(false) => self.close_tutorial(),
+            None => {}
+        }
+    }
+
+    /// Moves the tour back to its previous step.
+    pub fn rewind_tutorial(&mut self) {
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.back();
+            self.focus_tutorial_panel();
+        }
+    }
+
+    /// Closes the tour and records that it was seen.
+    pub fn close_tutorial(&mut self) {
+        if let Some(tutorial) = self.tutorial.take() {
+            if let Err(e) = tutorial.dismiss() {
+                log::warn!("Failed to record the tutorial as seen: {:#}", e);
+            }
+        }
+        self.active_panel = ActivePanel::Groups;
+    }
+
+    /// Activates the panel the current tour step is about.
+    fn focus_tutorial_panel(&mut self) {
+        if let Some(panel) = self.tutorial.as_ref().and_then(|t| t.step().panel) {
+            self.active_panel = panel;
+        }
+    }
+
     /// Scrolls commit message panel down.
     pub fn scroll_commit_message_down(&mut self) {
         if let Some(group) = self.selected_group() {
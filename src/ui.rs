@@ -3,8 +3,11 @@
 //! This module provides an interactive TUI using `ratatui` for selecting
 //! and managing commit groups.
 
+use std::collections::HashSet;
 use std::io;
 use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
@@ -13,16 +16,42 @@ use ratatui::crossterm::event::{self, Event as CEvent, KeyCode, KeyEvent, KeyMod
 use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use ratatui::crossterm::{execute, terminal};
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Margin, Rect};
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{
-    Block, Borders, Clear, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation,
+    Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation,
     ScrollbarState, Wrap,
 };
 use ratatui::Terminal;
 
-use crate::git::commit_group;
-use crate::types::{ActivePanel, AppState};
+use crate::budget::BudgetExceeded;
+use crate::compare::DiffLine;
+use crate::events::{Action, CommittedGroup, Event, EventBus};
+use crate::fallback::{session_report, AiFallback};
+use crate::git::{discover_repo, Cancelled, CherryPick};
+use crate::highlight::{highlight_line, syntax_for_path, TokenKind};
+use crate::keymap::{self, Command};
+use crate::outcome::Outcome;
+use crate::postprocess::PostProcessors;
+use crate::provider::{AiProvider, ChatMessage, ChatRole};
+use crate::theme::Theme;
+use crate::types::{
+    format_elapsed, ActivePanel, AppState, BranchPicker, CandidatePicker, ChangeGroup,
+    CommitProgress, CommitState, CommitType, Confirmation, DiffView, GroupFilter, GroupSort,
+    MessageComparison, RemotePicker, ScopeRename, StaleGroups,
+};
+use crate::validation::EmptyDescription;
+use crate::worker::{spinner_frame, AiJob, AiOutcome, AiWorker};
+
+/// Maximum number of bytes shown in the file preview
+const MAX_PREVIEW_BYTES: usize = 1024 * 1024;
+
+/// How long a git operation runs before its timeout countdown is shown
+const COUNTDOWN_AFTER: Duration = Duration::from_secs(3);
+
+/// Minimum time between two checks of the working tree against the groups,
+/// so a burst of file events (e.g. a checkout or build) costs one check
+const WORKTREE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Runs the terminal user interface event loop.
 ///
@@ -34,22 +63,76 @@ use crate::types::{ActivePanel, AppState};
 ///
 /// # Returns
 ///
-/// Ok if the user quits normally, Err on terminal errors.
+/// The [`Outcome`] of the last commit attempt when the user quits, Err on
+/// terminal errors.
 ///
 /// # Keyboard Controls
 ///
 /// - `↑`/`↓` or `k`/`j` - Navigate between commit groups
-/// - `e` - Edit the selected commit message in external editor
-/// - `a` - Generate commit message using AI (if enabled)
+/// - `Shift+↑`/`Shift+↓` - Move the selected group up or down in the order
+///   `C` commits in
+/// - `e` - Edit the selected commit message in the integrated editor
+/// - `E` - Edit the selected commit message in the external editor
+/// - `d` - View the diff or full content of the selected file
+/// - `a` - Generate commit message using AI in the background (if enabled)
+/// - `r` - Refine the commit message with feedback (if AI is enabled)
+/// - `s` - Attach notes for the AI to the selected group: pasted text, or
+///   `@path` to load a file
 /// - `c` - Commit the selected group
-/// - `C` - Commit all groups
+/// - `C` - Commit all groups that are not skipped, after a confirmation;
+///   a popup shows each group's progress and lets the batch continue or
+///   stop after a failure
+/// - `x` - Skip the selected group, or include it again
+/// - `x`/`Delete` (Files panel) - Leave the selected file out of its group
+/// - `Space` (Files panel) - Mark the selected file; `m` moves the marked
+///   files into the selected group, `x` leaves them out, `d` shows their
+///   diffs together, and `Esc` clears the marks
+/// - `W` - Fix whitespace problems in the selected group
+/// - `H` - Insert the license header into the selected group's new files
+/// - `S` - Apply the suggested scope fix
+/// - `A` - Rename the selected group's scope in all uncommitted groups,
+///   optionally recording the rename as a scope alias
+/// - `P` - Push the current branch
+/// - `F` - Fetch the upstream and rebase the branch onto it
+/// - `B` - Create and switch to a new branch before committing
+/// - `U` - Undo the last commit created in this session
+/// - `O` - Show the git output of every commit attempt of the selected
+///   group
+/// - `L` - Show the latest commits of the branch with the files each
+///   changed
+/// - `Z` - Stash the changes no group to be committed contains, for the
+///   session (restored on exit), or restore them
+/// - `R` - Re-scan the repository and group the changes again (offered by
+///   `--watch` when files change)
+/// - `o` / `f` - Cycle the group sort order / filter
+/// - `/` - Search group headers and file paths; `n`/`N` jump between the
+///   matches, `Esc` ends the search
+/// - `?` - Show all shortcuts and the color legend
+/// - `i` - Show the repository info: branch, upstream, last commit,
+///   stashes, dirty submodules, and why AI fell back to heuristics
 /// - `Ctrl+L` - Clear status message
-/// - `q` or `Esc` - Quit
-pub fn run_tui(mut app: AppState, repo_path: &Path) -> Result<()> {
+/// - `q` or `Esc` - Quit, after a confirmation while groups are
+///   uncommitted (`Esc` cancels a running git operation or AI request first)
+///
+/// Commits, pushes, and tagging run on the worker of an [`EventBus`], so
+/// the interface stays responsive while hooks or the network are slow.
+pub fn run_tui(mut app: AppState, repo_path: &Path) -> Result<Outcome> {
+    refresh_license_offenders(&mut app, repo_path);
+    app.events = Some(EventBus::start_with_post_processors(
+        repo_path.to_path_buf(),
+        app.config.timeouts,
+        app.diff_store.clone(),
+        PostProcessors::from_config(&app.config.message),
+    ));
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, terminal::EnterAlternateScreen)?;
+    execute!(
+        stdout,
+        terminal::EnterAlternateScreen,
+        event::EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
@@ -59,10 +142,62 @@ pub fn run_tui(mut app: AppState, repo_path: &Path) -> Result<()> {
 
     // Restore terminal state
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), terminal::LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        event::DisableBracketedPaste,
+        terminal::LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
 
-    result
+    if let Some(stash) = app.session_stash.take() {
+        let count = stash.paths().len();
+        match stash.restore() {
+            Ok(()) => eprintln!("📦 Restored {} stashed file(s)", count),
+            Err(e) => eprintln!(
+                "⚠️  Could not restore the changes stashed for the session: {:#}\n   They are kept in the stash; restore them with `git stash pop`",
+                e
+            ),
+        }
+    }
+
+    if let Err(e) = save_session(&app, repo_path) {
+        log::warn!("Failed to save the session: {:#}", e);
+    }
+    let summary = app.session_summary().render(app.summary_format)?;
+    if !summary.is_empty() {
+        println!("{}", summary);
+    }
+    let report = session_report(&app.ai_fallbacks);
+    if !report.is_empty() {
+        eprintln!("{}", report);
+    }
+
+    result.map(|()| app.outcome)
+}
+
+/// Saves the plan for the next run if groups are left uncommitted, and
+/// removes a saved plan otherwise (see [`crate::session`]).
+fn save_session(app: &AppState, repo_path: &Path) -> Result<()> {
+    use crate::session::{remove_session, session_path, SavedSession};
+
+    let ctx = discover_repo(repo_path)?;
+    let path = session_path(&ctx.git_dir);
+    if app.groups.iter().all(ChangeGroup::is_committed) {
+        return remove_session(&path);
+    }
+    let repo = ctx.open()?;
+    let branch = crate::git::get_current_branch(&repo)?;
+    let head = repo.head().ok().and_then(|head| head.target());
+    let saved_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    SavedSession::capture(&app.groups, &app.snapshot, &branch, head, saved_at)
+        .with_orphaned_files(&app.orphaned_files)
+        .save(&path)?;
+    log::info!("Session saved to {}", path.display());
+    eprintln!("↺ Unfinished session saved; the next run offers to resume it");
+    Ok(())
 }
 
 /// Runs the main event loop.
@@ -72,22 +207,37 @@ fn run_event_loop<B: ratatui::backend::Backend + std::io::Write>(
     repo_path: &Path,
 ) -> Result<()> {
     let tick_rate = Duration::from_millis(250);
+    // Redraw faster while background work runs so the spinner animates
+    let busy_tick_rate = Duration::from_millis(100);
     let mut last_tick = Instant::now();
 
     loop {
+        poll_ai_worker(app);
+        poll_events(app, repo_path);
+        poll_worktree(app, repo_path);
+
         // Draw UI
         draw_ui(terminal, app)?;
 
         // Handle events
+        let tick_rate = if app.is_ai_busy() || app.is_git_busy() {
+            busy_tick_rate
+        } else {
+            tick_rate
+        };
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
 
         if event::poll(timeout)? {
-            if let CEvent::Key(key) = event::read()? {
-                if handle_key_event(key, app, repo_path, terminal)? {
-                    break; // User wants to quit
+            match event::read()? {
+                CEvent::Key(key) => {
+                    if handle_key_event(key, app, repo_path, terminal)? {
+                        break; // User wants to quit
+                    }
                 }
+                CEvent::Paste(text) => handle_paste(app, text)?,
+                _ => {}
             }
         }
 
@@ -109,33 +259,92 @@ fn handle_key_event<B: ratatui::backend::Backend + std::io::Write>(
     repo_path: &Path,
     terminal: &mut Terminal<B>,
 ) -> Result<bool> {
+    // The branch picker opens above the commit output, so it goes first
+    if let Some(picker) = app.branch_picker.as_mut() {
+        match keymap::BRANCH_PICKER.command(&key) {
+            Some(Command::Close) => app.branch_picker = None,
+            Some(Command::Down) => picker.select_next(),
+            Some(Command::Up) => picker.select_previous(),
+            Some(Command::ToggleMark) => picker.toggle(),
+            Some(Command::Confirm) => {
+                if let Some(picker) = app.branch_picker.take() {
+                    let branches = picker.chosen_branches();
+                    if !branches.is_empty() {
+                        dispatch(
+                            app,
+                            Action::CherryPick {
+                                commits: picker.commits,
+                                branches,
+                            },
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // The progress of committing all groups stays above everything else
+    if let Some(progress) = app.commit_progress.as_mut() {
+        match keymap::COMMIT_PROGRESS.command(&key) {
+            Some(Command::Down) => progress.scroll_down(),
+            Some(Command::Up) => progress.scroll_up(),
+            Some(Command::Close) if progress.running => cancel_git_operation(app),
+            Some(Command::Confirm) if progress.awaits_decision() => continue_commit_batch(app),
+            Some(Command::Close) if progress.awaits_decision() => {
+                let left = progress.remaining().len();
+                app.commit_progress = None;
+                app.set_status(format!(
+                    "ℹ Aborted after the failure; {} group(s) left uncommitted",
+                    left
+                ));
+            }
+            Some(Command::Confirm | Command::Close) if !progress.running => {
+                app.commit_progress = None;
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+
     // If commit output popup is shown, handle it first
     if app.show_commit_output {
-        match key.code {
-            KeyCode::Esc => {
+        match keymap::COMMIT_OUTPUT.command(&key) {
+            Some(Command::Close) => {
                 app.show_commit_output = false;
                 app.commit_output.clear();
                 app.commit_output_scroll = 0;
+                app.pending_tag = None;
+                app.last_commits.clear();
+                return Ok(false);
+            }
+            Some(Command::Tag) if app.pending_tag.is_some() => {
+                handle_tag_action(app, repo_path)?;
                 return Ok(false);
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Some(Command::CherryPick) if !app.last_commits.is_empty() => {
+                open_branch_picker(app, repo_path)?;
+                return Ok(false);
+            }
+            Some(Command::Down) => {
                 let lines = app.commit_output.lines().count();
                 if app.commit_output_scroll + 1 < lines {
                     app.commit_output_scroll += 1;
                 }
                 return Ok(false);
             }
-            KeyCode::Up | KeyCode::Char('k') => {
+            Some(Command::Up) => {
                 app.commit_output_scroll = app.commit_output_scroll.saturating_sub(1);
                 return Ok(false);
             }
-            KeyCode::PageDown => {
+            Some(Command::PageDown) => {
                 let lines = app.commit_output.lines().count();
                 app.commit_output_scroll =
                     (app.commit_output_scroll + 10).min(lines.saturating_sub(1));
                 return Ok(false);
             }
-            KeyCode::PageUp => {
+            Some(Command::PageUp) => {
                 app.commit_output_scroll = app.commit_output_scroll.saturating_sub(10);
                 return Ok(false);
             }
@@ -143,40 +352,280 @@ fn handle_key_event<B: ratatui::backend::Backend + std::io::Write>(
         }
     }
 
+    // If the remote picker is shown, handle it first
+    if let Some(picker) = app.remote_picker.as_mut() {
+        match keymap::REMOTE_PICKER.command(&key) {
+            Some(Command::Close) => app.remote_picker = None,
+            Some(Command::Down) => picker.select_next(),
+            Some(Command::Up) => picker.select_previous(),
+            Some(Command::Confirm) => {
+                if let Some(picker) = app.remote_picker.take() {
+                    if let Some(remote) = picker.selected_remote() {
+                        push_to_remote(app, remote, &picker);
+                    }
+                }
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // Committing all groups and quitting with uncommitted groups ask first
+    if let Some(confirmation) = app.confirmation {
+        match keymap::CONFIRMATION.command(&key) {
+            Some(Command::Confirm) => {
+                app.confirmation = None;
+                match confirmation {
+                    Confirmation::CommitAll { .. } => {
+                        handle_commit_all_action(app, repo_path, true)?;
+                    }
+                    Confirmation::Quit { .. } => return Ok(true),
+                }
+            }
+            Some(Command::Close) => app.confirmation = None,
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // If groups must be refreshed before committing, handle it first
+    if app.stale_groups.is_some() {
+        match keymap::STALE_GROUPS.command(&key) {
+            Some(Command::Close) => {
+                app.stale_groups = None;
+                app.set_status("ℹ Commit cancelled; stale groups stay marked with ⟳");
+            }
+            Some(Command::Confirm) => {
+                if let Some(stale) = app.stale_groups.take() {
+                    refresh_stale_groups(app, repo_path, &stale)?;
+                }
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // If a regenerated message is compared with the current one, handle it
+    // first
+    if let Some(comparison) = app.message_comparison.as_mut() {
+        match keymap::MESSAGE_COMPARISON.command(&key) {
+            Some(Command::Close) => {
+                app.message_comparison = None;
+                app.set_status("ℹ Kept the current commit message");
+            }
+            Some(Command::Confirm) => {
+                if let Some(comparison) = app.message_comparison.take() {
+                    accept_proposed_message(app, comparison);
+                }
+            }
+            Some(Command::Down) => comparison.scroll_down(),
+            Some(Command::Up) => comparison.scroll_up(),
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // If message candidates are shown, handle the picker first
+    if let Some(picker) = app.candidate_picker.as_mut() {
+        match keymap::CANDIDATE_PICKER.command(&key) {
+            Some(Command::Close) => {
+                app.candidate_picker = None;
+                app.set_status("ℹ Kept the current commit message");
+            }
+            Some(Command::Next) => picker.select_next(),
+            Some(Command::Previous) => picker.select_previous(),
+            Some(Command::Confirm) => {
+                if let Some(picker) = app.candidate_picker.take() {
+                    let index = picker.group_index;
+                    if let Some((description, body)) =
+                        picker.candidates.into_iter().nth(picker.selected)
+                    {
+                        if apply_generated_message(app, index, description, body).is_some() {
+                            app.ai_conversations.remove(&index);
+                            app.set_status("✨ Applied the chosen commit message");
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // Notes for the AI are typed or pasted into the scratchpad
+    if let Some(input) = app.notes_input.as_mut() {
+        match (keymap::TEXT_INPUT.command(&key), key.code) {
+            (Some(Command::Close), _) => app.notes_input = None,
+            (Some(Command::DeleteChar), _) => {
+                input.pop();
+            }
+            (Some(Command::Confirm), _) => {
+                if let Some(input) = app.notes_input.take() {
+                    save_notes(app, repo_path, &input);
+                }
+            }
+            (_, KeyCode::Char(c)) => input.push(c),
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // If feedback for refining a message is being typed, handle it first
+    if let Some(input) = app.feedback_input.as_mut() {
+        match (keymap::TEXT_INPUT.command(&key), key.code) {
+            (Some(Command::Close), _) => app.feedback_input = None,
+            (Some(Command::DeleteChar), _) => {
+                input.pop();
+            }
+            (Some(Command::Confirm), _) => {
+                if let Some(feedback) = app.feedback_input.take() {
+                    if !feedback.trim().is_empty() {
+                        submit_feedback(app, repo_path, &feedback)?;
+                    }
+                }
+            }
+            (_, KeyCode::Char(c)) => input.push(c),
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // While the search query is typed, the selection follows the matches
+    if app.search_typing {
+        match (keymap::SEARCH.command(&key), key.code) {
+            (Some(Command::Close), _) => app.clear_search(),
+            (Some(Command::Confirm), _) => {
+                app.search_typing = false;
+                if app.search.as_deref().is_some_and(str::is_empty) {
+                    app.clear_search();
+                }
+            }
+            (Some(Command::DeleteChar), _) => {
+                let mut query = app.search.clone().unwrap_or_default();
+                query.pop();
+                app.set_search(query);
+            }
+            (Some(Command::NextMatch), _) => {
+                app.next_match();
+            }
+            (Some(Command::PreviousMatch), _) => {
+                app.previous_match();
+            }
+            (_, KeyCode::Char(c)) => {
+                let mut query = app.search.clone().unwrap_or_default();
+                query.push(c);
+                app.set_search(query);
+            }
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // If the name of a new branch is being typed, handle it first
+    if let Some(input) = app.branch_input.as_mut() {
+        match (keymap::TEXT_INPUT.command(&key), key.code) {
+            (Some(Command::Close), _) => app.branch_input = None,
+            (Some(Command::DeleteChar), _) => {
+                input.pop();
+            }
+            (Some(Command::Confirm), _) => {
+                if let Some(name) = app.branch_input.take() {
+                    create_and_switch_branch(app, repo_path, name.trim())?;
+                }
+            }
+            (_, KeyCode::Char(c)) if !c.is_whitespace() => input.push(c),
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // If a scope is being renamed, handle it first
+    if let Some(rename) = app.scope_rename.as_mut() {
+        match (keymap::SCOPE_RENAME.command(&key), key.code) {
+            (Some(Command::Close), _) => app.scope_rename = None,
+            (Some(Command::DeleteChar), _) => {
+                rename.input.pop();
+            }
+            (Some(Command::ToggleMark), _) => rename.record = !rename.record,
+            (Some(Command::Confirm), _) => {
+                if let Some(rename) = app.scope_rename.take() {
+                    rename_scope(app, repo_path, &rename);
+                }
+            }
+            (_, KeyCode::Char(c)) if !c.is_whitespace() => rename.input.push(c),
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // The first-run tour takes all keys until it is closed
+    if app.tutorial.is_some() {
+        match keymap::TUTORIAL.command(&key) {
+            Some(Command::Next) => app.advance_tutorial(),
+            Some(Command::Previous) => app.rewind_tutorial(),
+            Some(Command::Close) => app.close_tutorial(),
+            _ => {}
+        }
+        return Ok(false);
+    }
+
     // If editor help is shown, handle it first
     if app.show_editor_help {
-        match key.code {
-            KeyCode::Esc | KeyCode::Char('?') => {
-                app.close_editor_help();
-                return Ok(false);
+        if keymap::EDITOR_HELP.command(&key) == Some(Command::Close) {
+            app.close_editor_help();
+        }
+        return Ok(false);
+    }
+
+    // If the help popup is shown, any of its toggle keys closes it
+    if app.show_help {
+        match keymap::HELP.command(&key) {
+            Some(Command::Close) => app.toggle_help(),
+            Some(Command::Down) => app.help_scroll = app.help_scroll.saturating_add(1),
+            Some(Command::Up) => app.help_scroll = app.help_scroll.saturating_sub(1),
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    // If the info popup is shown, any of its toggle keys closes it
+    if app.repo_info.is_some() {
+        if keymap::REPO_INFO.command(&key) == Some(Command::Close) {
+            app.repo_info = None;
+        }
+        return Ok(false);
+    }
+
+    if app.recent_commits.is_some() {
+        match keymap::RECENT_COMMITS.command(&key) {
+            Some(Command::Close) => app.recent_commits = None,
+            Some(Command::Down) => {
+                app.recent_commits_scroll = app.recent_commits_scroll.saturating_add(1)
             }
-            _ => return Ok(false),
+            Some(Command::Up) => {
+                app.recent_commits_scroll = app.recent_commits_scroll.saturating_sub(1)
+            }
+            _ => {}
         }
+        return Ok(false);
     }
 
     // If diff viewer is active, handle its keys
     if app.show_diff_viewer {
-        match key.code {
-            KeyCode::Esc => {
-                app.close_diff();
-                return Ok(false);
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-                app.scroll_diff_down();
-                return Ok(false);
-            }
-            KeyCode::Up | KeyCode::Char('k') => {
-                app.scroll_diff_up();
-                return Ok(false);
-            }
-            _ => return Ok(false),
+        match keymap::DIFF_VIEWER.command(&key) {
+            Some(Command::Close) => app.close_diff(),
+            Some(Command::Down) => app.scroll_diff_down(),
+            Some(Command::Up) => app.scroll_diff_up(),
+            Some(Command::ToggleView) => app.toggle_diff_view(),
+            _ => {}
         }
+        return Ok(false);
     }
 
     // If editor is active, route all events to the editor
     if app.editor.is_active() {
         // Check for help toggle first
-        if key.code == KeyCode::Char('?') && key.modifiers.is_empty() {
+        if keymap::EDITOR.command(&key) == Some(Command::Help) && key.modifiers.is_empty() {
             app.toggle_editor_help();
             return Ok(false);
         }
@@ -188,12 +637,13 @@ fn handle_key_event<B: ratatui::backend::Backend + std::io::Write>(
         if !editor_continues {
             // Editor was closed (Ctrl+S = save, Ctrl+C = cancel)
             // Check if it was a save (not a cancel)
-            if key.code == KeyCode::Char('s') && key.modifiers == KeyModifiers::CONTROL {
+            if keymap::EDITOR.command(&key) == Some(Command::Save) {
                 // Save: transfer text back to the selected group
                 let text = app.editor.text();
                 if let Some(group) = app.selected_group_mut() {
                     group.set_from_commit_text(&text);
                 }
+                app.normalize_scopes();
             }
             // For Ctrl+C, editor.cancel() already restored original text
         }
@@ -203,39 +653,88 @@ fn handle_key_event<B: ratatui::backend::Backend + std::io::Write>(
 
     // If popup is active, handle popup-specific keys first
     if app.popup_active {
+        match keymap::STATUS.command(&key) {
+            Some(Command::Close) => app.clear_status(),
+            Some(Command::Down) => app.scroll_popup_down(),
+            Some(Command::Up) => app.scroll_popup_up(),
+            _ => {}
+        }
+        return Ok(false);
+    }
+
+    let command = keymap::MAIN.command(&key);
+
+    // While a git operation runs, Esc cancels it and actions that change
+    // the groups or the repository wait for it; quitting would abort it
+    if app.is_git_busy() {
         match key.code {
-            KeyCode::Esc | KeyCode::Enter => {
-                app.clear_status();
+            KeyCode::Esc => {
+                cancel_git_operation(app);
                 return Ok(false);
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                app.scroll_popup_down();
+            _ if command.is_some_and(Command::waits_for_git) => {
+                if let Some((kind, _)) = app.events.as_ref().and_then(EventBus::running) {
+                    app.set_status(format!(
+                        "⏳ {} in progress, please wait (Esc to cancel)",
+                        kind.label()
+                    ));
+                }
                 return Ok(false);
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                app.scroll_popup_up();
+            _ => {}
+        }
+    }
+
+    // While an AI request runs, Esc cancels it and actions that change the
+    // groups wait for its result
+    if app.is_ai_busy() {
+        match key.code {
+            KeyCode::Esc => {
+                cancel_ai_request(app);
                 return Ok(false);
             }
-            _ => return Ok(false),
+            _ if command.is_some_and(Command::waits_for_ai) => {
+                app.set_status("⏳ Waiting for the AI request to finish (Esc to cancel)");
+                return Ok(false);
+            }
+            _ => {}
         }
     }
 
     // Normal mode key handling
-    match key.code {
-        KeyCode::Char('q') | KeyCode::Esc => {
-            return Ok(true);
+    match command {
+        Some(Command::Quit) if key.code == KeyCode::Esc && app.search.is_some() => {
+            app.clear_search();
         }
-        KeyCode::Tab => {
-            if key.modifiers.contains(KeyModifiers::SHIFT) {
-                app.activate_previous_panel();
-            } else {
-                app.activate_next_panel();
+        Some(Command::Quit) if key.code == KeyCode::Esc && !app.marked_files.is_empty() => {
+            app.marked_files.clear();
+        }
+        Some(Command::Quit) => {
+            let uncommitted = app
+                .groups
+                .iter()
+                .filter(|g| !g.is_committed() && !g.skipped)
+                .count();
+            if !app.config.ui.confirm || uncommitted == 0 {
+                return Ok(true);
             }
+            app.confirmation = Some(Confirmation::Quit { uncommitted });
         }
-        KeyCode::BackTab => {
+        Some(Command::NextPanel) => {
+            app.activate_next_panel();
+        }
+        Some(Command::PreviousPanel) => {
             app.activate_previous_panel();
         }
-        KeyCode::Down | KeyCode::Char('j') => {
+        Some(Command::MoveGroupDown | Command::MoveGroupUp)
+            if app.active_panel == ActivePanel::Groups =>
+        {
+            if let Err(e) = app.move_selected_group(command == Some(Command::MoveGroupUp)) {
+                app.set_status(format!("✗ {}", e));
+            }
+        }
+        // Elsewhere Shift+↓ and Shift+↑ navigate like ↓ and ↑
+        Some(Command::Down | Command::MoveGroupDown) => {
             // Navigate based on active panel
             match app.active_panel {
                 ActivePanel::Groups => app.select_next(),
@@ -243,7 +742,7 @@ fn handle_key_event<B: ratatui::backend::Backend + std::io::Write>(
                 ActivePanel::Files => app.select_next_file(),
             }
         }
-        KeyCode::Up | KeyCode::Char('k') => {
+        Some(Command::Up | Command::MoveGroupUp) => {
             // Navigate based on active panel
             match app.active_panel {
                 ActivePanel::Groups => app.select_previous(),
@@ -251,101 +750,1492 @@ fn handle_key_event<B: ratatui::backend::Backend + std::io::Write>(
                 ActivePanel::Files => app.select_previous_file(),
             }
         }
-        KeyCode::Char('e') => {
+        Some(Command::Edit) => {
             handle_edit_action(app, terminal)?;
         }
-        KeyCode::Char('d') => {
+        Some(Command::ExternalEdit) => {
+            handle_external_edit_action(app, terminal)?;
+        }
+        Some(Command::GenerateMessage) => {
+            handle_generate_message_action(app, repo_path)?;
+        }
+        Some(Command::Refine) => {
+            handle_refine_action(app);
+        }
+        Some(Command::Notes) => {
+            open_notes_input(app);
+        }
+        Some(Command::Help) => {
+            app.toggle_help();
+        }
+        Some(Command::RepoInfo) => {
+            show_repo_info(app, repo_path);
+        }
+        Some(Command::CycleSort) => {
+            app.cycle_group_sort();
+        }
+        Some(Command::CycleFilter) => {
+            app.cycle_group_filter();
+        }
+        Some(Command::Diff) => {
             handle_diff_action(app, repo_path)?;
         }
-        KeyCode::Char('c') => {
+        Some(Command::Commit) => {
             handle_commit_action(app, repo_path)?;
         }
-        KeyCode::Char('C') if key.modifiers.contains(KeyModifiers::SHIFT) => {
-            handle_commit_all_action(app, repo_path)?;
+        Some(Command::CommitAll) => {
+            handle_commit_all_action(app, repo_path, false)?;
+        }
+        Some(Command::FixWhitespace) => {
+            handle_whitespace_fix_action(app, repo_path)?;
+        }
+        Some(Command::FixLicense) => {
+            handle_license_fix_action(app, repo_path)?;
+        }
+        Some(Command::FixScope) => {
+            handle_scope_fix_action(app)?;
+        }
+        Some(Command::RenameScope) => {
+            open_scope_rename(app);
+        }
+        Some(Command::CommitLog) => {
+            show_group_commit_log(app);
+        }
+        Some(Command::RecentCommits) => {
+            show_recent_commits(app, repo_path);
+        }
+        Some(Command::Push) => {
+            handle_push_action(app, repo_path)?;
+        }
+        Some(Command::Pull) => {
+            handle_pull_action(app);
+        }
+        Some(Command::NewBranch) => {
+            open_branch_input(app);
         }
-        KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+        Some(Command::Undo) => {
+            undo_last_commit(app, repo_path)?;
+        }
+        Some(Command::Refresh) => {
+            refresh_groups(app, repo_path);
+        }
+        Some(Command::Skip | Command::LeaveOut) if app.active_panel == ActivePanel::Files => {
+            leave_out_selected_file(app);
+        }
+        Some(Command::Skip) => {
+            toggle_skip(app);
+        }
+        Some(Command::ToggleMark) if app.active_panel == ActivePanel::Files => {
+            toggle_selected_mark(app);
+        }
+        Some(Command::MoveMarked) => {
+            move_marked_files(app);
+        }
+        Some(Command::Stash) => {
+            toggle_session_stash(app, repo_path);
+        }
+        Some(Command::Search) => {
+            app.search_typing = true;
+            if app.search.is_none() {
+                app.search = Some(String::new());
+            }
+        }
+        Some(Command::NextMatch) if app.search.is_some() => {
+            if !app.next_match() {
+                app.set_status("ℹ No matches");
+            }
+        }
+        Some(Command::PreviousMatch) if app.search.is_some() => {
+            if !app.previous_match() {
+                app.set_status("ℹ No matches");
+            }
+        }
+        Some(Command::ClearStatus) => {
             app.clear_status();
         }
-        _ => {}
+        // Keys without a built-in action may run an extension
+        _ => {
+            if let KeyCode::Char(c) = key.code {
+                if !key.modifiers.contains(KeyModifiers::CONTROL) {
+                    if let Err(e) = crate::extension::run_action(app, repo_path, c) {
+                        app.set_status(format!("✗ {:#}", e));
+                    }
+                }
+            }
+        }
     }
 
     Ok(false)
 }
 
-/// Handles the edit action (activates integrated editor).
-fn handle_edit_action<B: ratatui::backend::Backend + std::io::Write>(
-    app: &mut AppState,
-    _terminal: &mut Terminal<B>,
-) -> Result<()> {
-    // Check if selected group is already committed
-    if let Some(group) = app.selected_group() {
-        if group.is_committed() {
-            app.set_status("✗ Cannot edit already committed group");
-            return Ok(());
-        }
+/// Returns the AI provider and the selected group if a message can be
+/// generated for it, otherwise shows why not.
+fn ai_message_target(app: &mut AppState) -> Option<(Arc<dyn AiProvider>, ChangeGroup)> {
+    let Some(provider) = app.ai_provider.clone() else {
+        app.set_status("ℹ AI is not available (see --verbose for details)");
+        return None;
+    };
+    let group = app.selected_group()?.clone();
+    if group.is_committed() {
+        app.set_status("✗ Cannot generate a message for an already committed group");
+        return None;
     }
+    Some((provider, group))
+}
 
-    // Get the current commit message first
-    let message = app
-        .selected_group()
-        .map(|g| g.full_message())
-        .unwrap_or_default();
+/// Collects the diff of a group's files as prompt context.
+///
+/// Deleted files without a staged diff contribute their committed content
+/// (see [`crate::git::deleted_file_patch`]); other files without a diff are
+/// skipped.
+fn group_diff(app: &AppState, repo_path: &Path, group: &ChangeGroup) -> Result<Option<String>> {
+    let repo = open_repo(repo_path)?;
+    let diffs = match app.diff_store {
+        Some(_) => Some(current_diffs(app, repo_path, &group.files)?),
+        None => None,
+    };
+    let diff: String = group
+        .files
+        .iter()
+        .filter_map(|file| {
+            let diff = match &diffs {
+                Some(diffs) => Some(
+                    diffs
+                        .get(&file.path)
+                        .map(|d| d.patch.clone())
+                        .unwrap_or_default(),
+                ),
+                None => crate::git::get_file_diff(&repo, &file.path).ok(),
+            };
+            match diff {
+                Some(diff) if !diff.trim().is_empty() || !file.is_deleted() => Some(diff),
+                _ => crate::git::deleted_file_patch(&repo, &file.path)
+                    .ok()
+                    .flatten(),
+            }
+        })
+        .collect();
+    Ok((!diff.trim().is_empty()).then_some(diff))
+}
 
-    // Activate the integrated editor
-    app.editor.activate(message);
+/// Forgets the cached diffs of `paths` that were edited since they were
+/// planned, so they are loaded again.
+fn forget_edited_diffs(app: &AppState, repo_path: &Path, paths: &[&str]) {
+    let Some(store) = &app.diff_store else {
+        return;
+    };
+    match app
+        .snapshot
+        .changed(&repo_workdir(repo_path), paths.iter().copied())
+    {
+        Ok(changed) => store.invalidate(changed.iter().map(String::as_str)),
+        Err(_) => store.invalidate(paths.iter().copied()),
+    }
+}
+
+/// Returns the current diffs of `files`, from the diff store if there is
+/// one, otherwise from git.
+///
+/// # Errors
+///
+/// Returns an error if the diffs cannot be loaded.
+fn current_diffs(
+    app: &AppState,
+    repo_path: &Path,
+    files: &[crate::types::ChangedFile],
+) -> Result<std::collections::HashMap<String, Arc<crate::types::FileDiff>>> {
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    match &app.diff_store {
+        Some(store) => {
+            forget_edited_diffs(app, repo_path, &paths);
+            store.get_many(paths)
+        }
+        None => {
+            let repo = open_repo(repo_path)?;
+            Ok(crate::git::collect_diffs(&repo, files)?
+                .into_iter()
+                .map(|(path, diff)| (path, Arc::new(diff)))
+                .collect())
+        }
+    }
+}
+
+/// Starts generating the selected group's message in the background.
+fn handle_generate_message_action(app: &mut AppState, repo_path: &Path) -> Result<()> {
+    let Some((provider, group)) = ai_message_target(app) else {
+        return Ok(());
+    };
+    let diff = group_diff(app, repo_path, &group)?;
 
+    let index = app.selected_index;
+    let candidates = app.config.ai.candidates;
+    app.ai_worker = Some(AiWorker::spawn_message(
+        provider, index, group, diff, candidates,
+    ));
     Ok(())
 }
 
-/// Handles the diff viewer action (shows diff for selected file).
-fn handle_diff_action(app: &mut AppState, repo_path: &Path) -> Result<()> {
-    use git2::Repository;
+/// Handles text pasted into the terminal, which arrives in one piece with
+/// bracketed paste.
+fn handle_paste(app: &mut AppState, text: String) -> Result<()> {
+    use ratatui::crossterm::event::Event as CrosstermEvent;
 
-    // Only allow diff from Files panel
-    if app.active_panel != ActivePanel::Files {
-        app.set_status("ℹ Switch to Files panel (Tab) to view diffs");
-        return Ok(());
+    if app.editor.is_active() {
+        app.editor.handle_event(CrosstermEvent::Paste(text))?;
+    } else if let Some(input) = app.notes_input.as_mut() {
+        input.push_str(&text.replace("\r\n", "\n"));
+    } else if let Some(input) = app.feedback_input.as_mut() {
+        input.push_str(&text.split_whitespace().collect::<Vec<_>>().join(" "));
+    } else if let Some(input) = app.branch_input.as_mut() {
+        input.extend(text.chars().filter(|c| !c.is_whitespace()));
+    } else if let Some(rename) = app.scope_rename.as_mut() {
+        rename
+            .input
+            .extend(text.chars().filter(|c| !c.is_whitespace()));
     }
+    Ok(())
+}
 
-    // Check if group is committed first
-    let is_committed = app
-        .selected_group()
-        .map(|g| g.is_committed())
-        .unwrap_or(false);
-    if is_committed {
-        app.set_status("ℹ Viewing diff for already committed group");
+/// Opens the scratchpad with the notes of the selected group.
+fn open_notes_input(app: &mut AppState) {
+    match app.selected_group() {
+        Some(group) if group.is_committed() => {
+            app.set_status("ℹ The group is already committed");
+        }
+        Some(group) => app.notes_input = Some(group.notes.clone().unwrap_or_default()),
+        None => {}
     }
+}
 
-    // Get the selected file from the active group
-    let file_path = match app.selected_file() {
-        Some(file) => file.path.clone(),
-        None => {
-            app.set_status("✗ No files in selected group");
-            return Ok(());
+/// Attaches the scratchpad input to the selected group as notes for the
+/// AI; an empty input removes them.
+fn save_notes(app: &mut AppState, repo_path: &Path, input: &str) {
+    let notes = match crate::notes::resolve_notes(input, repo_path) {
+        Ok(notes) => notes,
+        Err(e) => {
+            app.set_status(format!("✗ {:#}", e));
+            return;
         }
     };
+    let Some(group) = app.selected_group_mut() else {
+        return;
+    };
+    let status = match &notes {
+        Some(notes) => format!(
+            "✓ Attached {} line(s) of notes; a and r pass them to the AI",
+            notes.lines().count()
+        ),
+        None => "ℹ Notes removed".to_string(),
+    };
+    group.notes = notes;
+    app.set_status(status);
+}
 
-    // Get the repository
-    let repo = Repository::discover(repo_path)?;
+/// Opens the feedback input for refining the selected group's message.
+fn handle_refine_action(app: &mut AppState) {
+    if ai_message_target(app).is_some() {
+        app.feedback_input = Some(String::new());
+    }
+}
 
-    // Get the diff for the file
-    match crate::git::get_file_diff(&repo, &file_path) {
-        Ok(diff_content) => {
-            if diff_content.trim().is_empty() {
-                app.set_status("✗ No staged changes for this file");
-            } else {
-                app.show_diff(file_path, diff_content);
-            }
+/// Starts refining the selected group's message with `feedback`.
+///
+/// The conversation so far is reused while the message is unchanged since
+/// the last AI response; otherwise a new conversation starts from the
+/// current message, so hand-edited messages can be refined too.
+fn submit_feedback(app: &mut AppState, repo_path: &Path, feedback: &str) -> Result<()> {
+    use crate::copilot::{build_commit_message_prompt, build_refine_prompt, visible_to_ai};
+
+    let Some((provider, group)) = ai_message_target(app) else {
+        return Ok(());
+    };
+    let index = app.selected_index;
+    let current = message_text(&group);
+
+    let mut conversation = match app.ai_conversations.get(&index) {
+        Some(conversation)
+            if conversation
+                .last()
+                .is_some_and(|m| m.role == ChatRole::Assistant && m.content == current) =>
+        {
+            conversation.clone()
         }
-        Err(e) => {
-            app.set_status(format!("✗ Failed to get diff: {}", e));
+        _ => {
+            let diff = group_diff(app, repo_path, &group)?;
+            let (files, diff) = visible_to_ai(provider.as_ref(), &group.files, diff.as_deref())?;
+            vec![
+                ChatMessage::user(build_commit_message_prompt(
+                    &group,
+                    &files,
+                    diff.as_deref(),
+                    provider.diff_token_budget(),
+                )),
+                ChatMessage::assistant(current),
+            ]
         }
-    }
+    };
+    conversation.push(ChatMessage::user(build_refine_prompt(feedback)));
 
+    app.ai_worker = Some(AiWorker::spawn_refine(provider, index, conversation));
     Ok(())
 }
 
-/// Handles committing a single group.
+/// Returns a group's message as the AI writes it: the description, then the
+/// body lines.
+fn message_text(group: &ChangeGroup) -> String {
+    let mut text = group.description.clone();
+    let body = group.body();
+    if !body.is_empty() {
+        text.push_str("\n\n");
+        text.push_str(&body.join("\n"));
+    }
+    text
+}
+
+/// Sets a generated message on the group at `index`.
+///
+/// An empty description is replaced as configured by `[message]
+/// empty_description`.
+///
+/// Returns the group, or [`None`] if it no longer exists or was committed
+/// meanwhile.
+fn apply_generated_message(
+    app: &mut AppState,
+    index: usize,
+    description: String,
+    body: Option<String>,
+) -> Option<&ChangeGroup> {
+    let Some(group) = app.groups.get_mut(index).filter(|g| !g.is_committed()) else {
+        log::debug!("Discarding AI message for group {}", index);
+        return None;
+    };
+    set_generated_message(group, description, body.as_deref());
+    if app.config.message.empty_description == EmptyDescription::Fallback
+        && group.fill_empty_description()
+    {
+        log::warn!("AI returned no description; using '{}'", group.description);
+    }
+    Some(group)
+}
+
+/// Offers a generated message for the group at `index`.
+///
+/// A message that differs from the current one is shown as a diff to
+/// accept or reject; an identical one is applied right away.
+/// `conversation` is the refinement conversation that produced it.
+fn propose_message(
+    app: &mut AppState,
+    index: usize,
+    description: String,
+    body: Option<String>,
+    conversation: Option<Vec<ChatMessage>>,
+) {
+    let Some(group) = app.groups.get(index).filter(|g| !g.is_committed()) else {
+        log::debug!("Discarding AI message for group {}", index);
+        return;
+    };
+    let mut proposed = group.clone();
+    set_generated_message(&mut proposed, description.clone(), body.as_deref());
+    let diff = crate::compare::diff_lines(&group.full_message(), &proposed.full_message());
+
+    let comparison = MessageComparison {
+        group_index: index,
+        description,
+        body,
+        conversation,
+        diff,
+        scroll: 0,
+    };
+    if comparison
+        .diff
+        .iter()
+        .all(|line| matches!(line, DiffLine::Same(_)))
+    {
+        accept_proposed_message(app, comparison);
+    } else {
+        app.message_comparison = Some(comparison);
+    }
+}
+
+/// Applies a compared message and keeps its refinement conversation.
+fn accept_proposed_message(app: &mut AppState, comparison: MessageComparison) {
+    let MessageComparison {
+        group_index: index,
+        description,
+        body,
+        conversation,
+        ..
+    } = comparison;
+    let Some(group) = apply_generated_message(app, index, description, body) else {
+        return;
+    };
+    match conversation {
+        Some(mut conversation) => {
+            conversation.push(ChatMessage::assistant(message_text(group)));
+            app.ai_conversations.insert(index, conversation);
+            app.set_status("✨ Refined commit message (r to give more feedback)");
+        }
+        None => {
+            // A new message starts a new conversation
+            app.ai_conversations.remove(&index);
+            app.set_status("✨ Generated commit message");
+        }
+    }
+}
+
+/// Sets a generated description and body on a group.
+fn set_generated_message(group: &mut ChangeGroup, description: String, body: Option<&str>) {
+    group.description = description;
+    group.description_generated = false;
+    group.body_pending = false;
+    group.body_lines = body
+        .iter()
+        .flat_map(|body| body.lines())
+        .map(|line| line.trim().trim_start_matches("- ").to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+}
+
+/// Applies the result of a finished background AI request.
+fn poll_ai_worker(app: &mut AppState) {
+    let Some(outcome) = app.ai_worker.as_ref().and_then(AiWorker::try_result) else {
+        return;
+    };
+    app.ai_worker = None;
+
+    match outcome {
+        AiOutcome::Groups(Ok(groups)) => {
+            let file_count = groups.iter().map(|g| g.files.len()).sum();
+            log::info!("AI grouping successful: {} groups created", groups.len());
+            crate::logging::log_grouping_result(file_count, groups.len(), true);
+            let (groups, merged) =
+                crate::consolidate::merge_small_groups(groups, &app.config.grouping);
+            if merged > 0 {
+                app.set_status(format!(
+                    "✨ AI created {} commit group(s), {} small one(s) merged",
+                    groups.len(),
+                    merged
+                ));
+            } else {
+                app.set_status(format!("✨ AI created {} commit group(s)", groups.len()));
+            }
+            // Groups of files the AI was not asked about, such as the
+            // .gitattributes entries for generated files, stay
+            let grouped: HashSet<&str> = groups
+                .iter()
+                .flat_map(|g| g.files.iter().map(|f| f.path.as_str()))
+                .collect();
+            let kept: Vec<ChangeGroup> = app
+                .groups
+                .iter()
+                .filter(|g| !g.is_committed())
+                .filter(|g| g.files.iter().all(|f| !grouped.contains(f.path.as_str())))
+                .cloned()
+                .collect();
+            let mut groups = groups;
+            groups.extend(kept);
+            app.replace_groups(groups);
+        }
+        AiOutcome::Groups(Err(e)) if e.is::<BudgetExceeded>() => {
+            log::warn!("{}", e);
+            app.ai_fallbacks
+                .push(AiFallback::from_error("grouping", &e));
+            app.set_status(format!("ℹ {}; keeping heuristic groups", e));
+        }
+        AiOutcome::Groups(Err(e)) => {
+            crate::logging::log_error("AI grouping failed", &e);
+            let fallback = AiFallback::from_error("grouping", &e);
+            app.set_status(format!(
+                "⚠ {}, keeping heuristic groups (i for details)",
+                fallback.summary()
+            ));
+            app.ai_fallbacks.push(fallback);
+        }
+        AiOutcome::Message {
+            index,
+            result: Ok(mut candidates),
+        } => {
+            if candidates.len() > 1 {
+                app.candidate_picker = Some(CandidatePicker {
+                    group_index: index,
+                    candidates,
+                    selected: 0,
+                });
+            } else if let Some((description, body)) = candidates.pop() {
+                propose_message(app, index, description, body, None);
+            }
+        }
+        AiOutcome::Refined {
+            index,
+            conversation,
+            result: Ok((description, body)),
+        } => propose_message(app, index, description, body, Some(conversation)),
+        AiOutcome::Message { result: Err(e), .. } if e.is::<BudgetExceeded>() => {
+            log::warn!("{}", e);
+            app.ai_fallbacks
+                .push(AiFallback::from_error("message generation", &e));
+            app.set_status(format!("ℹ {}; edit the message with e", e));
+        }
+        AiOutcome::Refined { result: Err(e), .. } if e.is::<BudgetExceeded>() => {
+            log::warn!("{}", e);
+            app.ai_fallbacks
+                .push(AiFallback::from_error("refinement", &e));
+            app.set_status(format!("ℹ {}; edit the message with e", e));
+        }
+        AiOutcome::Message { result: Err(e), .. } => {
+            crate::logging::log_error("AI message generation failed", &e);
+            record_message_failure(app, AiFallback::from_error("message generation", &e));
+        }
+        AiOutcome::Refined { result: Err(e), .. } => {
+            crate::logging::log_error("AI refinement failed", &e);
+            record_message_failure(app, AiFallback::from_error("refinement", &e));
+        }
+    }
+}
+
+/// Records a failed message request and points the user to the details.
+fn record_message_failure(app: &mut AppState, fallback: AiFallback) {
+    app.set_status(format!(
+        "✗ {}: {} (i for details)",
+        fallback.summary(),
+        fallback.detail
+    ));
+    app.ai_fallbacks.push(fallback);
+}
+
+/// Cancels the running AI request.
+fn cancel_ai_request(app: &mut AppState) {
+    let Some(worker) = app.ai_worker.take() else {
+        return;
+    };
+    let message = match worker.job() {
+        AiJob::Grouping => "ℹ AI grouping cancelled, keeping heuristic groups",
+        AiJob::Message(_) | AiJob::Refine(_) => "ℹ AI message generation cancelled",
+    };
+    log::info!("Cancelled AI request after {:?}", worker.elapsed());
+    worker.cancel();
+    app.set_status(message);
+}
+
+/// Handles the edit action (activates integrated editor, or the external
+/// one with `[ui] external_editor`).
+fn handle_edit_action<B: ratatui::backend::Backend + std::io::Write>(
+    app: &mut AppState,
+    terminal: &mut Terminal<B>,
+) -> Result<()> {
+    if app.config.ui.external_editor {
+        return handle_external_edit_action(app, terminal);
+    }
+
+    // Check if selected group is already committed
+    if let Some(group) = app.selected_group() {
+        if group.is_committed() {
+            app.set_status("✗ Cannot edit already committed group");
+            return Ok(());
+        }
+    }
+
+    // Get the current commit message first
+    let message = app
+        .selected_group()
+        .map(|g| g.full_message())
+        .unwrap_or_default();
+
+    // Activate the integrated editor
+    app.editor.activate(message);
+
+    Ok(())
+}
+
+/// Edits the selected group's message in the external editor.
+///
+/// The TUI is suspended while the editor runs and restored afterwards; the
+/// edited message replaces the group's message unless it is empty.
+fn handle_external_edit_action<B: ratatui::backend::Backend + std::io::Write>(
+    app: &mut AppState,
+    terminal: &mut Terminal<B>,
+) -> Result<()> {
+    let Some(group) = app.selected_group() else {
+        return Ok(());
+    };
+    if group.is_committed() {
+        app.set_status("✗ Cannot edit already committed group");
+        return Ok(());
+    }
+    let message = group.full_message();
+    let editor = match crate::editor::get_editor(app.config.ui.editor.as_deref()) {
+        Ok(editor) => editor,
+        Err(e) => {
+            app.set_status(format!("✗ {:#}", e));
+            return Ok(());
+        }
+    };
+
+    // Hand the terminal over to the editor
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        event::DisableBracketedPaste,
+        terminal::LeaveAlternateScreen
+    )?;
+    terminal.show_cursor()?;
+
+    let edited = crate::editor::edit_externally(&editor, &message);
+
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        terminal::EnterAlternateScreen,
+        event::EnableBracketedPaste
+    )?;
+    terminal.clear()?;
+    terminal.hide_cursor()?;
+
+    match edited {
+        Err(e) => {
+            log::warn!("External editor failed: {:#}", e);
+            app.set_status(format!("✗ {:#}", e));
+        }
+        Ok(text) if text.is_empty() => {
+            app.set_status("ℹ Empty message, keeping the previous one");
+        }
+        Ok(text) if text == message.trim() => app.set_status("ℹ Message unchanged"),
+        Ok(text) => {
+            if let Some(group) = app.selected_group_mut() {
+                group.set_from_commit_text(&text);
+            }
+            app.normalize_scopes();
+            app.set_status("✓ Message updated from the external editor");
+        }
+    }
+    Ok(())
+}
+
+/// Handles the diff viewer action (shows diff for selected file).
+fn handle_diff_action(app: &mut AppState, repo_path: &Path) -> Result<()> {
+    // Only allow diff from Files panel
+    if app.active_panel != ActivePanel::Files {
+        app.set_status("ℹ Switch to Files panel (Tab) to view diffs");
+        return Ok(());
+    }
+
+    // Check if group is committed first
+    let is_committed = app
+        .selected_group()
+        .map(|g| g.is_committed())
+        .unwrap_or(false);
+    if is_committed {
+        app.set_status("ℹ Viewing diff for already committed group");
+    }
+
+    // Marked files are shown together
+    let marks = app.marks();
+    if !marks.is_empty() {
+        let paths: Vec<String> = marks
+            .iter()
+            .map(|&(group, file)| app.groups[group].files[file].path.clone())
+            .collect();
+        let refs: Vec<&str> = paths.iter().map(String::as_str).collect();
+        forget_edited_diffs(app, repo_path, &refs);
+        dispatch(app, Action::CombinedDiff { paths });
+        return Ok(());
+    }
+
+    // Get the selected file from the active group
+    let Some(file) = app.selected_file() else {
+        app.set_status("✗ No files in selected group");
+        return Ok(());
+    };
+    let path = file.path.clone();
+
+    // The diff is loaded in the background; huge files can take a while
+    forget_edited_diffs(app, repo_path, &[&path]);
+    dispatch(app, Action::Diff { path });
+    Ok(())
+}
+
+/// Opens the diff viewer with a loaded diff.
+fn show_diff(app: &mut AppState, repo_path: &Path, file_path: String, result: Result<String>) {
+    let diff_content = match result {
+        Ok(diff_content) => diff_content,
+        Err(e) if e.is::<Cancelled>() => {
+            app.set_status("ℹ Loading the diff was cancelled");
+            return;
+        }
+        Err(e) => {
+            app.set_status(format!("✗ Failed to get diff: {:#}", e));
+            return;
+        }
+    };
+    let is_new = app
+        .groups
+        .iter()
+        .flat_map(|g| &g.files)
+        .find(|f| f.path == file_path)
+        .is_some_and(|f| f.is_new());
+    let preview = read_file_preview(&repo_workdir(repo_path), &file_path);
+
+    // New files open on the preview, which is easier to read than a diff of
+    // added lines
+    if diff_content.trim().is_empty() && preview.is_none() {
+        app.set_status("✗ No staged changes for this file");
+    } else {
+        let view = if is_new || diff_content.trim().is_empty() {
+            DiffView::Preview
+        } else {
+            DiffView::Diff
+        };
+        app.show_diff_with_preview(file_path, diff_content, preview, view);
+    }
+}
+
+/// Opens the diff viewer with the diffs of the marked files.
+fn show_combined_diff(app: &mut AppState, paths: Vec<String>, result: Result<String>) {
+    match result {
+        Ok(diff) if diff.trim().is_empty() => {
+            app.set_status("✗ No changes in the marked files");
+        }
+        Ok(diff) => app.show_diff(format!("{} marked files", paths.len()), diff),
+        Err(e) if e.is::<Cancelled>() => app.set_status("ℹ Loading the diffs was cancelled"),
+        Err(e) => app.set_status(format!("✗ Failed to get the diffs: {:#}", e)),
+    }
+}
+
+/// Reads the current content of a file for the preview.
+///
+/// Returns [`None`] for missing, binary, or unreadable files. Large files are
+/// cut off after [`MAX_PREVIEW_BYTES`].
+fn read_file_preview(workdir: &Path, file_path: &str) -> Option<String> {
+    use std::io::Read;
+
+    let file = std::fs::File::open(workdir.join(file_path)).ok()?;
+    if !file.metadata().ok()?.is_file() {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    file.take(MAX_PREVIEW_BYTES as u64 + 1)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    if bytes.contains(&0) {
+        return None;
+    }
+
+    let truncated = bytes.len() > MAX_PREVIEW_BYTES;
+    bytes.truncate(MAX_PREVIEW_BYTES);
+    let mut content = String::from_utf8_lossy(&bytes).into_owned();
+    if truncated {
+        content.push_str("\n… (truncated)");
+    }
+    Some(content)
+}
+
+/// Runs pre-commit checks (whitespace, license headers, context command) for
+/// the given groups.
+///
+/// Returns `true` if committing may proceed. The first time problems are
+/// found for a set of groups, a warning is shown instead; repeating the
+/// commit action proceeds anyway.
+fn check_before_commit(app: &mut AppState, repo_path: &Path, indices: &[usize]) -> Result<bool> {
+    if !check_unstashed(app, indices) {
+        return Ok(false);
+    }
+
+    // Outdated messages cannot be ignored like the warnings below
+    if !check_stale_groups(app, repo_path, indices) {
+        return Ok(false);
+    }
+
+    if !check_descriptions(app, indices) {
+        return Ok(false);
+    }
+
+    if !check_header_charset(app, indices) {
+        return Ok(false);
+    }
+
+    // Policy violations and plugin rejections cannot be ignored like the
+    // warnings below
+    if !check_scope_policy(app, indices) {
+        return Ok(false);
+    }
+
+    if !check_plugin_validation(app, indices) {
+        return Ok(false);
+    }
+
+    let shown = (indices.to_vec(), group_messages(app, indices));
+    if app.commit_warnings_shown_for.as_ref() == Some(&shown) {
+        app.commit_warnings_shown_for = None;
+        return Ok(true);
+    }
+
+    let repo = open_repo(repo_path)?;
+    let files: Vec<_> = indices
+        .iter()
+        .filter_map(|&idx| app.groups.get(idx))
+        .flat_map(|g| g.files.iter().cloned())
+        .collect();
+
+    let mut warnings = Vec::new();
+
+    match crate::whitespace::check_files(&repo, &files) {
+        Ok(issues) if !issues.is_empty() => warnings.push(format!(
+            "⚠ Whitespace problems in {} place(s):\n{}\nPress W to fix automatically",
+            issues.len(),
+            crate::whitespace::format_issues(&issues)
+        )),
+        Ok(_) => {}
+        Err(e) => log::warn!("Whitespace check failed: {}", e),
+    }
+
+    let generated: Vec<_> = indices
+        .iter()
+        .filter_map(|&idx| app.groups.get(idx))
+        .filter(|g| g.description_generated)
+        .map(|g| format!("  • {}", g.header()))
+        .collect();
+    if !generated.is_empty() {
+        warnings.push(format!(
+            "⚠ The message had no description; one was generated from the files:\n{}\nPress e to review it",
+            generated.join("\n")
+        ));
+    }
+
+    let missing_license: Vec<_> = files
+        .iter()
+        .filter(|f| app.license_offenders.contains(&f.path))
+        .map(|f| format!("  • {}", f.path))
+        .collect();
+    if !missing_license.is_empty() {
+        warnings.push(format!(
+            "⚠ Missing license header in {} new file(s):\n{}\nPress H to insert the header",
+            missing_license.len(),
+            missing_license.join("\n")
+        ));
+    }
+
+    if let Ok(Some(policy)) = crate::release::ReleasePolicy::from_config(&app.config.release) {
+        for group in indices.iter().filter_map(|&idx| app.groups.get(idx)) {
+            if policy.match_header(&group.header()).is_none() {
+                continue;
+            }
+            match crate::release::check_release_changelog(&repo, &group.files) {
+                Ok(Some(check)) if !check.is_clean() => warnings.push(format!(
+                    "⚠ Changelog does not match the release commits: {}",
+                    check
+                )),
+                Ok(_) => {}
+                Err(e) => log::warn!("Changelog check failed: {}", e),
+            }
+        }
+    }
+
+    if matches!(app.session.branch.as_str(), "main" | "master") {
+        warnings.push(format!(
+            "⚠ Committing directly on '{}'\nPress B to create a branch first",
+            app.session.branch
+        ));
+    }
+
+    let status = &app.session.branch_status;
+    if status.is_behind() {
+        warnings.push(format!(
+            "⚠ '{}' is {} commit(s) behind {}\nPress F to fetch and rebase first",
+            app.session.branch,
+            status.behind,
+            status.upstream.as_deref().unwrap_or("its upstream")
+        ));
+    }
+
+    if let Some(ctx) = &app.command_context {
+        if app.config.context.warn_on_failure && !ctx.success() {
+            let lines: Vec<&str> = ctx.output.lines().collect();
+            let tail = &lines[lines.len().saturating_sub(5)..];
+            warnings.push(format!(
+                "⚠ Context command `{}` failed ({}):\n{}",
+                ctx.command,
+                ctx.status_text(),
+                tail.join("\n")
+            ));
+        }
+    }
+
+    if warnings.is_empty() {
+        return Ok(true);
+    }
+
+    app.commit_warnings_shown_for = Some(shown);
+    app.set_status(format!(
+        "{}\n\nCommit again to ignore these warnings",
+        warnings.join("\n\n")
+    ));
+    Ok(false)
+}
+
+/// Checks that no file of the given groups is stashed for the session,
+/// e.g. of a group that was skipped when the changes were stashed.
+fn check_unstashed(app: &mut AppState, indices: &[usize]) -> bool {
+    let Some(stash) = &app.session_stash else {
+        return true;
+    };
+    let stashed: Vec<String> = indices
+        .iter()
+        .filter_map(|&idx| app.groups.get(idx))
+        .flat_map(|g| &g.files)
+        .filter(|f| stash.contains(&f.path))
+        .map(|f| format!("  • {}", f.path))
+        .collect();
+    if stashed.is_empty() {
+        return true;
+    }
+    app.set_status(format!(
+        "✗ Files of the group are stashed for the session:\n{}\n\nPress Z to restore them first",
+        stashed.join("\n")
+    ));
+    false
+}
+
+/// Checks that the given groups have a description.
+///
+/// Returns `false` and lists the groups if any would be committed with a
+/// header such as `feat: `.
+fn check_descriptions(app: &mut AppState, indices: &[usize]) -> bool {
+    let empty: Vec<String> = indices
+        .iter()
+        .filter_map(|&idx| app.groups.get(idx))
+        .filter(|g| g.has_empty_description())
+        .map(|g| format!("  • {}", g.header()))
+        .collect();
+    if empty.is_empty() {
+        return true;
+    }
+
+    app.outcome = Outcome::ValidationFailed;
+    app.set_status(format!(
+        "✗ Commit message without description:\n{}\n\nPress e to write one, or a to generate one with AI",
+        empty.join("\n")
+    ));
+    false
+}
+
+/// Transliterates the headers of the given groups to ASCII if
+/// `[message] charset = "ascii"` is set.
+///
+/// Returns `false` if a header still has characters without an ASCII
+/// replacement.
+fn check_header_charset(app: &mut AppState, indices: &[usize]) -> bool {
+    use crate::validation::{transliterate_header, HeaderCharset};
+
+    if app.config.message.charset != HeaderCharset::Ascii {
+        return true;
+    }
+    let mut blocked = Vec::new();
+    for &idx in indices {
+        let Some(group) = app.groups.get_mut(idx) else {
+            continue;
+        };
+        let before = group.header();
+        let left = transliterate_header(group);
+        if group.header() != before {
+            log::info!("Transliterated '{}' to '{}'", before, group.header());
+        }
+        if !left.is_empty() {
+            let left: Vec<String> = left.iter().map(|c| format!("'{}'", c)).collect();
+            blocked.push(format!("  • {} ({})", group.header(), left.join(", ")));
+        }
+    }
+    if blocked.is_empty() {
+        return true;
+    }
+
+    app.outcome = Outcome::ValidationFailed;
+    app.set_status(format!(
+        "✗ Headers must be ASCII ([message] charset = \"ascii\"):\n{}\n\nPress e to replace the characters",
+        blocked.join("\n")
+    ));
+    false
+}
+
+/// Checks whether files of the given groups changed since planning.
+///
+/// Stale groups are marked and listed in a popup offering to refresh them;
+/// returns `false` if there are any.
+fn check_stale_groups(app: &mut AppState, repo_path: &Path, indices: &[usize]) -> bool {
+    if app.snapshot.is_empty() {
+        return true;
+    }
+    let workdir = repo_workdir(repo_path);
+    let mut stale = StaleGroups::default();
+    for &idx in indices {
+        let Some(group) = app.groups.get(idx) else {
+            continue;
+        };
+        let paths = group.files.iter().map(|f| f.path.as_str());
+        match app.snapshot.changed(&workdir, paths) {
+            Ok(changed) if !changed.is_empty() => {
+                stale.groups.push(idx);
+                stale.files.extend(changed);
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to check for changed files: {:#}", e),
+        }
+    }
+    if stale.groups.is_empty() {
+        return true;
+    }
+
+    for &idx in &stale.groups {
+        if let Some(group) = app.groups.get_mut(idx) {
+            group.stale = true;
+        }
+    }
+    app.stale_groups = Some(stale);
+    false
+}
+
+/// Refreshes the line statistics and fingerprints of stale groups.
+///
+/// With AI available, the message of the first group is regenerated and
+/// shown for review; other messages are left to the user.
+fn refresh_stale_groups(app: &mut AppState, repo_path: &Path, stale: &StaleGroups) -> Result<()> {
+    let files: Vec<_> = stale
+        .groups
+        .iter()
+        .filter_map(|&idx| app.groups.get(idx))
+        .flat_map(|g| g.files.iter().cloned())
+        .collect();
+    let diffs = match current_diffs(app, repo_path, &files) {
+        Ok(diffs) => diffs,
+        Err(e) => {
+            app.set_status(format!("✗ Failed to refresh the diffs: {:#}", e));
+            return Ok(());
+        }
+    };
+    if let Err(e) = app.snapshot.refresh(
+        &repo_workdir(repo_path),
+        files.iter().map(|f| f.path.as_str()),
+    ) {
+        app.set_status(format!("✗ Failed to refresh the fingerprints: {:#}", e));
+        return Ok(());
+    }
+
+    for &idx in &stale.groups {
+        if let Some(group) = app.groups.get_mut(idx) {
+            for file in &mut group.files {
+                match diffs.get(&file.path) {
+                    Some(diff) => file.set_diff_stats(diff),
+                    None => {
+                        file.lines_added = None;
+                        file.lines_removed = None;
+                    }
+                }
+            }
+            group.stale = false;
+        }
+    }
+
+    let count = stale.groups.len();
+    match stale.groups.first() {
+        Some(&first) if app.ai_provider.is_some() && !app.is_ai_busy() => {
+            app.select_group(first);
+            handle_generate_message_action(app, repo_path)?;
+            app.set_status(format!(
+                "✓ Refreshed {} group(s); regenerating the message of the selected group",
+                count
+            ));
+        }
+        _ => app.set_status(format!(
+            "✓ Refreshed {} group(s); review their messages before committing",
+            count
+        )),
+    }
+    Ok(())
+}
+
+/// Compiles the configured scope policy, reporting configuration errors.
+fn scope_policy(app: &mut AppState) -> Option<crate::validation::ScopePolicy> {
+    match crate::validation::ScopePolicy::from_config(&app.config.scope) {
+        Ok(policy) => Some(policy),
+        Err(e) => {
+            app.set_status(format!("✗ Invalid scope policy: {:#}", e));
+            None
+        }
+    }
+}
+
+/// Returns the messages of the groups at `indices`.
+fn group_messages(app: &AppState, indices: &[usize]) -> Vec<String> {
+    indices
+        .iter()
+        .filter_map(|&idx| app.groups.get(idx))
+        .map(ChangeGroup::full_message)
+        .collect()
+}
+
+/// Checks the given groups against the scope policy.
+///
+/// Violations cannot be ignored; returns `false` and shows them with
+/// suggested fixes.
+fn check_scope_policy(app: &mut AppState, indices: &[usize]) -> bool {
+    use crate::validation::ScopeFix;
+
+    let Some(policy) = scope_policy(app) else {
+        return false;
+    };
+    if policy.is_empty() {
+        return true;
+    }
+
+    let errors: Vec<String> = indices
+        .iter()
+        .filter_map(|&idx| {
+            let group = app.groups.get(idx)?;
+            let violation = policy.validate(group)?;
+            let hint = match &violation.suggestion {
+                Some(ScopeFix::Set(scope)) => format!(" → suggested scope '{}'", scope),
+                Some(ScopeFix::Remove) => " → remove the scope".to_string(),
+                None => String::new(),
+            };
+            Some(format!("  • {}: {}{}", group.header(), violation, hint))
+        })
+        .collect();
+
+    if errors.is_empty() {
+        return true;
+    }
+
+    app.outcome = Outcome::ValidationFailed;
+    app.set_status(format!(
+        "✗ Scope policy violated:\n{}\n\nPress S to apply the suggested fix for the selected group, or edit the message with e",
+        errors.join("\n")
+    ));
+    false
+}
+
+/// Checks the messages of the given groups with the validation plugins.
+///
+/// A plugin that fails is logged and does not block committing.
+fn check_plugin_validation(app: &mut AppState, indices: &[usize]) -> bool {
+    if app.plugins.is_empty() {
+        return true;
+    }
+
+    let mut errors = Vec::new();
+    for group in indices.iter().filter_map(|&idx| app.groups.get(idx)) {
+        match app.plugins.validate(group) {
+            Ok(rejections) => errors.extend(
+                rejections
+                    .into_iter()
+                    .map(|reason| format!("  • {}: {}", group.header(), reason)),
+            ),
+            Err(e) => log::warn!("Plugin validation skipped: {:#}", e),
+        }
+    }
+
+    if errors.is_empty() {
+        return true;
+    }
+
+    app.outcome = Outcome::ValidationFailed;
+    app.set_status(format!(
+        "✗ Rejected by plugins:\n{}\n\nEdit the message with e",
+        errors.join("\n")
+    ));
+    false
+}
+
+/// Handles applying the suggested scope fix to the selected group.
+fn handle_scope_fix_action(app: &mut AppState) -> Result<()> {
+    use crate::validation::apply_scope_fix;
+
+    let Some(policy) = scope_policy(app) else {
+        return Ok(());
+    };
+    let Some(group) = app.selected_group_mut() else {
+        return Ok(());
+    };
+    if group.is_committed() {
+        app.set_status("✗ Group already committed");
+        return Ok(());
+    }
+
+    match policy.validate(group) {
+        None => app.set_status("✓ Scope already satisfies the policy"),
+        Some(violation) => match &violation.suggestion {
+            Some(fix) => {
+                apply_scope_fix(group, fix);
+                let header = group.header();
+                app.set_status(format!("✓ Scope fixed: {}", header));
+            }
+            None => app.set_status(format!(
+                "✗ {}; no scope could be derived from the file paths, edit the message with e",
+                violation
+            )),
+        },
+    }
+    Ok(())
+}
+
+/// Opens the input for renaming the selected group's scope in all groups.
+fn open_scope_rename(app: &mut AppState) {
+    let Some(group) = app.selected_group() else {
+        return;
+    };
+    if group.is_committed() {
+        app.set_status("✗ Group already committed");
+        return;
+    }
+    let Some(scope) = group.scope.clone() else {
+        app.set_status("ℹ The group has no scope to rename; edit the message with e");
+        return;
+    };
+    app.scope_rename = Some(ScopeRename {
+        input: scope.clone(),
+        from: scope,
+        record: false,
+    });
+}
+
+/// Renames a scope in all uncommitted groups, and records the rename as a
+/// scope alias if asked to.
+fn rename_scope(app: &mut AppState, repo_path: &Path, rename: &ScopeRename) {
+    let (to, renamed) = app.rename_scope(&rename.from, &rename.input);
+    let Some(to) = to else {
+        app.set_status(format!(
+            "✓ Removed the scope '{}' from {} group(s)",
+            rename.from, renamed
+        ));
+        return;
+    };
+    if to == rename.from {
+        app.set_status("ℹ Scope unchanged");
+        return;
+    }
+    let mut status = format!(
+        "✓ Renamed scope '{}' to '{}' in {} group(s)",
+        rename.from, to, renamed
+    );
+    if rename.record {
+        match crate::scope::record_alias(&repo_workdir(repo_path), &rename.from, &to) {
+            Ok(()) => {
+                app.config
+                    .scope
+                    .aliases
+                    .insert(rename.from.clone(), to.clone());
+                status.push_str(&format!(
+                    "\nRecorded in {}; later groups use '{}' too",
+                    crate::scope::ALIAS_FILE,
+                    to
+                ));
+            }
+            Err(e) => status.push_str(&format!("\n✗ Failed to record the alias: {:#}", e)),
+        }
+    }
+    app.set_status(status);
+}
+
+/// Opens the repository containing `repo_path`.
+fn open_repo(repo_path: &Path) -> Result<git2::Repository> {
+    discover_repo(repo_path)?.open()
+}
+
+/// Returns the working directory of the repository containing `repo_path`.
+fn repo_workdir(repo_path: &Path) -> std::path::PathBuf {
+    discover_repo(repo_path)
+        .map(|ctx| ctx.workdir)
+        .unwrap_or_else(|_| repo_path.to_path_buf())
+}
+
+/// Checks whether the groups are outdated after `--watch` saw files change
+/// (see [`crate::replan::plan_outdated`]).
+fn poll_worktree(app: &mut AppState, repo_path: &Path) {
+    let Some(events) = app.worktree_events.clone() else {
+        return;
+    };
+    // Commits change the status while they run; check once they are done
+    if app.is_git_busy()
+        || app
+            .worktree_checked
+            .is_some_and(|at| at.elapsed() < WORKTREE_CHECK_INTERVAL)
+        || !events.swap(false, Ordering::SeqCst)
+    {
+        return;
+    }
+    app.worktree_checked = Some(Instant::now());
+
+    let outdated = discover_repo(repo_path).and_then(|ctx| {
+        let repo = ctx.open()?;
+        crate::replan::plan_outdated(
+            &repo,
+            &ctx.workdir,
+            &app.groups,
+            &app.orphaned_files,
+            &app.snapshot,
+        )
+    });
+    match outdated {
+        Ok(outdated) => {
+            if outdated && !app.worktree_changed {
+                log::info!("The working tree changed since the groups were planned");
+            }
+            app.worktree_changed = outdated;
+        }
+        Err(e) => log::debug!("Failed to check the working tree: {:#}", e),
+    }
+}
+
+/// Collects and groups the changed files again, keeping the messages of
+/// groups whose files are unchanged.
+///
+/// The new groups come from the heuristics; `a` generates AI messages for
+/// them. Committed groups stay in the list.
+fn refresh_groups(app: &mut AppState, repo_path: &Path) {
+    use crate::replan::{carry_over_messages, RescanReport};
+
+    let Some(replanner) = app.replanner.as_mut() else {
+        app.set_status("ℹ Refreshing the groups is not available");
+        return;
+    };
+    let replan = match replanner() {
+        Ok(replan) => replan,
+        Err(e) => {
+            crate::logging::log_error("Failed to refresh the groups", &e);
+            app.set_status(format!("✗ Failed to refresh the groups: {:#}", e));
+            return;
+        }
+    };
+
+    // Files left out stay left out while they are changed
+    let mut files = replan.files;
+    app.orphaned_files
+        .retain(|orphan| files.iter().any(|f| f.path == orphan.path));
+    files.retain(|f| !app.is_orphaned(&f.path));
+
+    let mut report = RescanReport::compare(&app.groups, &files);
+    let groups = crate::inference::build_groups(files, app.session.ticket.clone());
+    let (mut groups, _) = crate::consolidate::merge_small_groups(groups, &app.config.grouping);
+    report.kept = carry_over_messages(&app.groups, &mut groups);
+    report.groups = groups.len();
+    app.replace_open_groups(groups);
+    app.snapshot = replan.snapshot;
+    app.stale_groups = None;
+    app.worktree_changed = false;
+    refresh_license_offenders(app, repo_path);
+    log::info!("Refreshed groups: {}", report);
+
+    if report.groups == 0 {
+        app.set_status(format!(
+            "✓ No uncommitted changes left ({} file(s) removed)",
+            report.removed
+        ));
+    } else {
+        app.set_status(format!("⟳ Rescanned: {}", report));
+    }
+}
+
+/// Recomputes which newly added files lack the configured license header.
+fn refresh_license_offenders(app: &mut AppState, repo_path: &Path) {
+    use crate::license::{find_missing_headers, LicenseChecker};
+
+    app.license_offenders.clear();
+    let workdir = repo_workdir(repo_path);
+    match LicenseChecker::from_config(&app.config.license, &workdir) {
+        Ok(Some(checker)) => {
+            let files: Vec<_> = app
+                .groups
+                .iter()
+                .filter(|g| !g.is_committed())
+                .flat_map(|g| g.files.iter().cloned())
+                .collect();
+            app.license_offenders = find_missing_headers(&checker, &workdir, &files)
+                .into_iter()
+                .collect();
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("License header check disabled: {}", e),
+    }
+}
+
+/// Handles inserting the license header into the selected group's new files.
+fn handle_license_fix_action(app: &mut AppState, repo_path: &Path) -> Result<()> {
+    use crate::license::{insert_headers, LicenseChecker};
+
+    let offenders: Vec<String> = match app.selected_group() {
+        Some(group) => group
+            .files
+            .iter()
+            .filter(|f| app.license_offenders.contains(&f.path))
+            .map(|f| f.path.clone())
+            .collect(),
+        None => return Ok(()),
+    };
+
+    if offenders.is_empty() {
+        app.set_status("✓ No files with missing license header in this group");
+        return Ok(());
+    }
+
+    let checker = match LicenseChecker::from_config(&app.config.license, &repo_workdir(repo_path)) {
+        Ok(Some(checker)) => checker,
+        Ok(None) => {
+            app.set_status("✗ No license header template configured");
+            return Ok(());
+        }
+        Err(e) => {
+            app.set_status(format!("✗ Invalid license configuration: {}", e));
+            return Ok(());
+        }
+    };
+
+    let repo = open_repo(repo_path)?;
+    match insert_headers(&checker, &repo, &offenders) {
+        Ok(count) => {
+            app.commit_warnings_shown_for = None;
+            refresh_license_offenders(app, repo_path);
+            app.set_status(format!("✓ Inserted license header into {} file(s)", count));
+        }
+        Err(e) => app.set_status(format!("✗ Failed to insert license header: {}", e)),
+    }
+
+    Ok(())
+}
+
+/// Handles fixing whitespace problems in the selected group's files.
+fn handle_whitespace_fix_action(app: &mut AppState, repo_path: &Path) -> Result<()> {
+    let files = match app.selected_group() {
+        Some(group) if !group.is_committed() => group.files.clone(),
+        Some(_) => {
+            app.set_status("✗ Group already committed");
+            return Ok(());
+        }
+        None => return Ok(()),
+    };
+
+    let repo = open_repo(repo_path)?;
+    let workdir = match repo.workdir() {
+        Some(dir) => dir.to_path_buf(),
+        None => {
+            app.set_status("✗ Repository has no working directory");
+            return Ok(());
+        }
+    };
+
+    let result = crate::whitespace::check_files(&repo, &files)
+        .and_then(|issues| crate::whitespace::fix_issues(&workdir, &issues));
+    match result {
+        Ok(0) => app.set_status("✓ No whitespace problems found"),
+        Ok(count) => {
+            app.commit_warnings_shown_for = None;
+            app.set_status(format!("✓ Fixed whitespace in {} file(s)", count));
+        }
+        Err(e) => app.set_status(format!("✗ Whitespace fix failed: {}", e)),
+    }
+
+    Ok(())
+}
+
+/// Handles committing a single group.
 fn handle_commit_action(app: &mut AppState, repo_path: &Path) -> Result<()> {
     let selected_idx = app.selected_index;
     if let Some(group) = app.selected_group() {
@@ -354,74 +2244,749 @@ fn handle_commit_action(app: &mut AppState, repo_path: &Path) -> Result<()> {
             app.set_status("✗ Group already committed");
             return Ok(());
         }
+        if group.skipped {
+            app.set_status("ℹ Group is skipped (x includes it again)");
+            return Ok(());
+        }
+
+        if !check_before_commit(app, repo_path, &[selected_idx])? {
+            return Ok(());
+        }
+        if let Some(group) = app.selected_group().cloned() {
+            dispatch_commit(app, vec![(selected_idx, group)]);
+        }
+    }
+    Ok(())
+}
+
+/// Handles committing all groups.
+fn handle_commit_all_action(app: &mut AppState, repo_path: &Path, confirmed: bool) -> Result<()> {
+    // Leave out committed and skipped groups
+    let uncommitted: Vec<usize> = app
+        .groups
+        .iter()
+        .enumerate()
+        .filter(|(_, g)| !g.is_committed() && !g.skipped)
+        .map(|(idx, _)| idx)
+        .collect();
+
+    if uncommitted.is_empty() {
+        let skipped = app.groups.iter().filter(|g| g.skipped).count();
+        if skipped == 0 {
+            app.set_status("✗ All groups already committed");
+        } else {
+            app.set_status(format!(
+                "✗ Nothing to commit: {} group(s) skipped (x includes a group again)",
+                skipped
+            ));
+        }
+        return Ok(());
+    }
+    if app.config.ui.confirm && !confirmed {
+        app.confirmation = Some(Confirmation::CommitAll {
+            groups: uncommitted.len(),
+        });
+        return Ok(());
+    }
+    if !check_before_commit(app, repo_path, &uncommitted)? {
+        return Ok(());
+    }
+
+    let groups: Vec<(usize, ChangeGroup)> = uncommitted
+        .into_iter()
+        .filter_map(|idx| app.groups.get(idx).map(|g| (idx, g.clone())))
+        .collect();
+    app.commit_progress = Some(CommitProgress::new(&groups));
+    dispatch_commit(app, groups);
+    if !app.is_git_busy() {
+        app.commit_progress = None;
+    }
+    Ok(())
+}
+
+/// Continues a commit-all batch after a failure with the groups following
+/// the failed one.
+fn continue_commit_batch(app: &mut AppState) {
+    let Some(progress) = app.commit_progress.as_mut() else {
+        return;
+    };
+    let groups: Vec<(usize, ChangeGroup)> = progress
+        .remaining()
+        .into_iter()
+        .filter_map(|idx| app.groups.get(idx).map(|g| (idx, g.clone())))
+        .collect();
+    progress.running = true;
+    dispatch_commit(app, groups);
+    if !app.is_git_busy() {
+        if let Some(progress) = app.commit_progress.as_mut() {
+            progress.running = false;
+        }
+    }
+}
 
-        match commit_group(repo_path, group) {
-            Ok(output) => {
-                // Mark the group as committed
-                if let Some(group) = app.groups.get_mut(selected_idx) {
-                    group.mark_as_committed();
-                }
-                app.set_status("✓ Committed selected group successfully");
+/// Marks the selected group as skipped, or includes it again.
+///
+/// Skipped groups are left out when committing all groups; nothing in the
+/// working tree changes.
+fn toggle_skip(app: &mut AppState) {
+    let Some(group) = app.groups.get_mut(app.selected_index) else {
+        return;
+    };
+    if group.is_committed() {
+        app.set_status("ℹ Group already committed");
+        return;
+    }
+    group.skipped = !group.skipped;
+    let status = if group.skipped {
+        format!("⊘ Skipped '{}'; C leaves it out", group.header())
+    } else {
+        format!("✓ '{}' is committed with C again", group.header())
+    };
+    app.set_status(status);
+}
 
-                // Show commit output in popup
-                app.commit_output = output;
-                app.commit_output_scroll = 0;
-                app.show_commit_output = true;
+/// Takes the selected file out of its group, so it is neither staged nor
+/// committed this session.
+///
+/// With files marked, all marked files are left out instead.
+fn leave_out_selected_file(app: &mut AppState) {
+    if !app.marks().is_empty() {
+        let status = match app.remove_marked_files() {
+            Ok(count) => format!("⊖ Left out {} marked file(s); they stay uncommitted", count),
+            Err(e) => format!("ℹ {}", e),
+        };
+        app.set_status(status);
+        return;
+    }
+    let (group, file) = (app.selected_index, app.selected_file_index);
+    let status = match app.remove_file_from_group(group, file) {
+        Ok(file) => format!("⊖ Left out {}; it stays uncommitted", file.path),
+        Err(e) => format!("ℹ {}", e),
+    };
+    app.set_status(status);
+}
+
+/// Stashes the changes no group to be committed contains, or restores
+/// them if they are stashed.
+fn toggle_session_stash(app: &mut AppState, repo_path: &Path) {
+    if let Some(stash) = app.session_stash.take() {
+        let count = stash.paths().len();
+        match stash.restore() {
+            Ok(()) => app.set_status(format!("✓ Restored {} stashed file(s)", count)),
+            Err(e) => app.set_status(format!(
+                "✗ Failed to restore the stash: {:#}\nRestore it with `git stash pop`",
+                e
+            )),
+        }
+        return;
+    }
+    match crate::stash::SessionStash::push(repo_path, &app.groups, &app.config.timeouts) {
+        Ok(Some(stash)) => {
+            let paths: Vec<String> = stash
+                .paths()
+                .iter()
+                .map(|path| format!("  • {}", path))
+                .collect();
+            app.set_status(format!(
+                "📦 Stashed {} file(s) outside the groups:\n{}\n\nThey come back on exit, or with Z",
+                paths.len(),
+                paths.join("\n")
+            ));
+            app.session_stash = Some(stash);
+        }
+        Ok(None) => app.set_status("ℹ No changes outside the groups to stash"),
+        Err(e) => app.set_status(format!("✗ {:#}", e)),
+    }
+}
+
+/// Marks the selected file for a bulk operation, or unmarks it.
+fn toggle_selected_mark(app: &mut AppState) {
+    if let Err(e) = app.toggle_file_mark(app.selected_index, app.selected_file_index) {
+        app.set_status(format!("ℹ {}", e));
+    }
+}
+
+/// Moves the marked files into the selected group.
+fn move_marked_files(app: &mut AppState) {
+    if app.marks().is_empty() {
+        app.set_status("ℹ Mark files with Space in the Files panel first");
+        return;
+    }
+    let target = app.selected_index;
+    let status = match app.move_marked_files(target) {
+        Ok(count) => format!(
+            "✓ Moved {} file(s) into '{}'",
+            count,
+            app.groups[target].header()
+        ),
+        Err(e) => format!("ℹ {}", e),
+    };
+    app.set_status(status);
+}
+
+/// Undoes the newest commit created in this session with a soft reset and
+/// restores its group, message included, as uncommitted.
+///
+/// Commits made outside the session, or pushed already, are refused by
+/// [`crate::git::undo_commit`].
+fn undo_last_commit(app: &mut AppState, repo_path: &Path) -> Result<()> {
+    let Some(&(index, commit)) = app.session_commits.last() else {
+        app.set_status("ℹ No commit of this session to undo");
+        return Ok(());
+    };
+    let repo = open_repo(repo_path)?;
+    if let Err(e) = crate::git::undo_commit(&repo, commit) {
+        app.set_status(format!("✗ Cannot undo the last commit: {:#}", e));
+        return Ok(());
+    }
+
+    app.session_commits.pop();
+    app.last_commits.retain(|c| *c != commit);
+    refresh_branch_status(app, repo_path);
+    if let Some(group) = app.groups.get_mut(index) {
+        group.mark_as_uncommitted();
+        app.select_group(index);
+    }
+    app.set_status(format!(
+        "✓ Undid commit {}; its changes are staged and the group is uncommitted again",
+        &commit.to_string()[..7]
+    ));
+    Ok(())
+}
+
+/// Opens the input for a new branch, suggesting a name from the ticket and
+/// the first uncommitted group.
+fn open_branch_input(app: &mut AppState) {
+    let suggestion = match app.groups.iter().find(|g| !g.is_committed()) {
+        Some(group) => crate::git::suggest_branch_name(
+            group.commit_type,
+            app.session.ticket.as_deref(),
+            &group.description,
+        ),
+        None => {
+            app.set_status("✗ All groups already committed");
+            return;
+        }
+    };
+    app.branch_input = Some(suggestion);
+}
+
+/// Creates the branch `name` at HEAD and switches to it, keeping all
+/// uncommitted changes.
+fn create_and_switch_branch(app: &mut AppState, repo_path: &Path, name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Ok(());
+    }
+    let repo = open_repo(repo_path)?;
+    match crate::git::create_branch(&repo, name) {
+        Ok(()) => {
+            app.session.branch = name.to_string();
+            refresh_branch_status(app, repo_path);
+            if app.worktree_label.is_some() {
+                app.worktree_label = Some(format!("{} @ {}", name, repo_path.display()));
             }
-            Err(e) => {
-                app.set_status(format!("✗ Commit failed: {}", e));
+            app.commit_warnings_shown_for = None;
+            app.set_status(format!("✓ Switched to new branch '{}'", name));
+        }
+        Err(e) => app.set_status(format!("✗ {:#}", e)),
+    }
+    Ok(())
+}
+
+/// Handles pushing the current branch.
+///
+/// With several remotes a picker is shown, preselecting the resolved push
+/// remote; with a single remote the branch is pushed right away.
+fn handle_push_action(app: &mut AppState, repo_path: &Path) -> Result<()> {
+    use crate::git::get_current_branch;
+    use crate::remote::resolve_remotes;
+
+    let repo = open_repo(repo_path)?;
+    let branch = match get_current_branch(&repo) {
+        Ok(branch) if branch != "HEAD" => branch,
+        Ok(_) => {
+            app.set_status("✗ Cannot push a detached HEAD");
+            return Ok(());
+        }
+        Err(e) => {
+            app.set_status(format!("✗ Failed to determine branch: {}", e));
+            return Ok(());
+        }
+    };
+
+    let targets = match resolve_remotes(&repo, &branch, &app.config.remote) {
+        Ok(targets) => targets,
+        Err(e) => {
+            app.set_status(format!("✗ Failed to read remotes: {}", e));
+            return Ok(());
+        }
+    };
+
+    let picker = RemotePicker {
+        selected: targets
+            .push
+            .as_ref()
+            .and_then(|push| targets.remotes.iter().position(|r| r == push))
+            .unwrap_or(0),
+        branch,
+        targets,
+    };
+
+    match picker.targets.remotes.len() {
+        0 => app.set_status("✗ No remotes configured"),
+        1 => {
+            if let Some(remote) = picker.selected_remote() {
+                push_to_remote(app, remote, &picker);
             }
         }
+        _ => app.remote_picker = Some(picker),
     }
     Ok(())
 }
 
-/// Handles committing all groups.
-fn handle_commit_all_action(app: &mut AppState, repo_path: &Path) -> Result<()> {
-    use crate::git::commit_group;
+/// Starts fetching the upstream and rebasing the branch onto it.
+fn handle_pull_action(app: &mut AppState) {
+    if app.session.branch_status.upstream.is_none() {
+        app.set_status(format!(
+            "✗ '{}' has no upstream to rebase onto",
+            app.session.branch
+        ));
+        return;
+    }
+    dispatch(app, Action::Pull);
+}
+
+/// Opens the info popup with the repository's current state.
+fn show_repo_info(app: &mut AppState, repo_path: &Path) {
+    let info = open_repo(repo_path).and_then(|repo| crate::git::RepoInfo::collect(&repo));
+    match info {
+        Ok(info) => {
+            app.session.branch_status = info.status.clone();
+            app.repo_info = Some(info);
+        }
+        Err(e) => app.set_status(format!("✗ Failed to read the repository: {:#}", e)),
+    }
+}
+
+/// Reads the HEAD commit and the distance to the upstream again, e.g.
+/// after committing or pushing.
+fn refresh_branch_status(app: &mut AppState, repo_path: &Path) {
+    let status = open_repo(repo_path).and_then(|repo| crate::git::BranchStatus::collect(&repo));
+    match status {
+        Ok(status) => app.session.branch_status = status,
+        Err(e) => log::warn!("Failed to compare the branch with its upstream: {:#}", e),
+    }
+}
+
+/// Starts pushing the picker's branch to `remote`.
+fn push_to_remote(app: &mut AppState, remote: &str, picker: &RemotePicker) {
+    dispatch(
+        app,
+        Action::Push {
+            remote: remote.to_string(),
+            branch: picker.branch.clone(),
+            pr_base: picker.targets.pr_base.clone(),
+        },
+    );
+}
+
+/// Offers to tag HEAD if `header` is a release commit.
+fn offer_release_tag(app: &mut AppState, header: &str) {
+    use crate::release::ReleasePolicy;
+
+    app.pending_tag = None;
+    match ReleasePolicy::from_config(&app.config.release) {
+        Ok(Some(policy)) => {
+            if let Some(proposal) = policy.match_header(header) {
+                app.commit_output.push_str(&format!(
+                    "\n\n🏷  Release commit detected. Press t to create {} tag {} (Esc to skip)",
+                    if proposal.sign { "signed" } else { "annotated" },
+                    proposal.tag_name
+                ));
+                app.pending_tag = Some(proposal);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Release tagging disabled: {}", e),
+    }
+}
 
-    // Filter out already committed groups
-    let uncommitted_count = app.groups.iter().filter(|g| !g.is_committed()).count();
+/// Handles creating the offered release tag with generated release notes.
+fn handle_tag_action(app: &mut AppState, repo_path: &Path) -> Result<()> {
+    use crate::release::generate_release_notes;
 
-    if uncommitted_count == 0 {
-        app.set_status("✗ All groups already committed");
+    let Some(proposal) = app.pending_tag.take() else {
         return Ok(());
+    };
+
+    let repo = open_repo(repo_path)?;
+    let notes = generate_release_notes(&repo, &proposal.version).unwrap_or_else(|e| {
+        log::warn!("Failed to generate release notes: {}", e);
+        format!("Release {}\n", proposal.version)
+    });
+
+    dispatch(
+        app,
+        Action::Tag {
+            name: proposal.tag_name,
+            notes,
+            sign: proposal.sign,
+        },
+    );
+    Ok(())
+}
+
+/// Offers to cherry-pick the commits of the last batch onto other branches,
+/// if there are commits and branches to pick onto.
+fn offer_cherry_pick(app: &mut AppState, repo_path: &Path, committed: &[CommittedGroup]) {
+    use crate::git::cherry_pick_targets;
+
+    app.last_commits.clear();
+    let Some(commits) = committed
+        .iter()
+        .map(|done| done.commit)
+        .collect::<Option<Vec<_>>>()
+    else {
+        return;
+    };
+    let has_targets = open_repo(repo_path)
+        .and_then(|repo| cherry_pick_targets(&repo))
+        .map(|targets| !targets.is_empty())
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to list branches: {}", e);
+            false
+        });
+    if has_targets {
+        app.commit_output.push_str(&format!(
+            "\n\n🍒 Press b to cherry-pick the {} commit(s) onto other branches",
+            commits.len()
+        ));
+        app.last_commits = commits;
+    }
+}
+
+/// Opens the picker for the branches to cherry-pick the last commits onto.
+fn open_branch_picker(app: &mut AppState, repo_path: &Path) -> Result<()> {
+    use crate::git::cherry_pick_targets;
+
+    let repo = open_repo(repo_path)?;
+    match cherry_pick_targets(&repo) {
+        Ok(branches) if branches.is_empty() => {
+            app.set_status("ℹ No other local branches to cherry-pick onto");
+        }
+        Ok(branches) => {
+            app.branch_picker = Some(BranchPicker::new(app.last_commits.clone(), branches));
+        }
+        Err(e) => app.set_status(format!("✗ Failed to list branches: {:#}", e)),
+    }
+    Ok(())
+}
+
+/// Shows the outcome of cherry-picking onto each branch.
+fn apply_cherry_pick_result(app: &mut AppState, results: Vec<(String, Result<CherryPick>)>) {
+    let mut lines = Vec::new();
+    let mut failed = 0;
+    for (branch, result) in &results {
+        match result {
+            Ok(pick) => {
+                if !pick.is_clean() {
+                    failed += 1;
+                }
+                lines.push(pick.to_string());
+            }
+            Err(e) => {
+                failed += 1;
+                lines.push(format!("✗ {}: {:#}", branch, e));
+            }
+        }
+    }
+
+    if failed == 0 {
+        app.set_status(format!("✓ Cherry-picked onto {} branch(es)", results.len()));
+    } else {
+        app.set_status(format!(
+            "✗ Cherry-picking failed on {} of {} branch(es)",
+            failed,
+            results.len()
+        ));
+    }
+    app.last_commits.clear();
+    app.commit_output = lines.join("\n");
+    app.commit_output_scroll = 0;
+    app.show_commit_output = true;
+}
+
+/// Sends an action to the background worker.
+fn dispatch(app: &mut AppState, action: Action) {
+    let Some(bus) = app.events.as_mut() else {
+        app.set_status("✗ Background worker is not running");
+        return;
+    };
+    if let Err(e) = bus.dispatch(action) {
+        app.set_status(format!("✗ {}", e));
+    }
+}
+
+/// Sends groups to the background worker to be committed, remembering
+/// them so a failure can be logged on its group.
+fn dispatch_commit(app: &mut AppState, groups: Vec<(usize, ChangeGroup)>) {
+    app.commit_batch = groups.iter().map(|(index, _)| *index).collect();
+    dispatch(app, Action::Commit(groups));
+}
+
+/// Shows the output of every commit attempt of the selected group.
+fn show_group_commit_log(app: &mut AppState) {
+    let Some(group) = app.selected_group() else {
+        return;
+    };
+    let Some(log) = group.commit_log_text() else {
+        app.set_status("ℹ The group has not been committed yet");
+        return;
+    };
+    app.commit_output = format!("Commit log of '{}'\n\n{}", group.header(), log);
+    app.commit_output_scroll = 0;
+    app.pending_tag = None;
+    app.last_commits.clear();
+    app.show_commit_output = true;
+}
+
+/// Opens the popup with the latest commits of the branch.
+fn show_recent_commits(app: &mut AppState, repo_path: &Path) {
+    let count = app.config.ui.recent_commits;
+    let commits =
+        open_repo(repo_path).and_then(|repo| crate::commit_log::recent_commits(&repo, count));
+    match commits {
+        Ok(commits) => {
+            app.recent_commits = Some(commits);
+            app.recent_commits_scroll = 0;
+        }
+        Err(e) => app.set_status(format!("✗ Failed to read the history: {:#}", e)),
     }
+}
 
-    let mut committed_count = 0;
-    let mut failed = false;
-    let mut all_outputs = Vec::new();
+/// Cancels the running git operation.
+fn cancel_git_operation(app: &mut AppState) {
+    let Some((kind, elapsed)) = app.events.as_ref().and_then(EventBus::running) else {
+        return;
+    };
+    log::info!("Cancelling {} after {:?}", kind.label(), elapsed);
+    if let Some(bus) = app.events.as_ref() {
+        bus.cancel();
+    }
+    app.set_status(format!("ℹ Stopping: {}…", kind.label()));
+}
 
-    for group in &mut app.groups {
-        if !group.is_committed() {
-            match commit_group(repo_path, group) {
-                Ok(output) => {
-                    group.mark_as_committed();
-                    committed_count += 1;
-                    all_outputs.push(format!("Group {}: {}", committed_count, output));
+/// Applies the events reported by the background worker.
+fn poll_events(app: &mut AppState, repo_path: &Path) {
+    while let Some(event) = app.events.as_mut().and_then(EventBus::try_event) {
+        match event {
+            Event::Committed(done) => {
+                if let Some(group) = app.groups.get_mut(done.index) {
+                    mark_committed(group, &done);
+                }
+                if let Some(progress) = app.commit_progress.as_mut() {
+                    progress.record_committed(done.index, done.commit, &done.output);
+                }
+                // The "uncommitted" filter hides the group now
+                app.ensure_selection_visible();
+            }
+            Event::CommitFinished {
+                committed,
+                error,
+                cancelled,
+            } => apply_commit_result(app, repo_path, committed, error, cancelled),
+            Event::Pushed {
+                remote,
+                branch,
+                pr_base,
+                result: Ok(output),
+            } => {
+                refresh_branch_status(app, repo_path);
+                let mut text = format!("✓ Pushed {} to {}\n\n{}", branch, remote, output);
+                if let Some(base) = pr_base.as_deref().filter(|b| *b != remote) {
+                    text.push_str(&format!(
+                        "\nOpen pull requests against '{}' (pushed to '{}')",
+                        base, remote
+                    ));
+                }
+                app.commit_output = text;
+                app.commit_output_scroll = 0;
+                app.show_commit_output = true;
+            }
+            Event::Pushed { result: Err(e), .. } if e.is::<Cancelled>() => {
+                app.set_status("ℹ Push cancelled");
+            }
+            Event::Pushed { result: Err(e), .. } => {
+                app.set_status(format!("✗ Push failed: {:#}", e));
+            }
+            Event::Pulled { result: Ok(output) } => {
+                refresh_branch_status(app, repo_path);
+                let mut text = format!(
+                    "✓ Rebased '{}' onto {}\n\n{}",
+                    app.session.branch,
+                    app.session
+                        .branch_status
+                        .upstream
+                        .as_deref()
+                        .unwrap_or("the upstream"),
+                    output
+                );
+                if app.groups.iter().any(|g| !g.is_committed()) {
+                    text.push_str("\nPress R to group the changes against the new HEAD");
                 }
-                Err(e) => {
-                    app.set_status(format!("✗ Failed to commit group: {}", e));
-                    failed = true;
-                    break;
+                app.commit_warnings_shown_for = None;
+                app.commit_output = text;
+                app.commit_output_scroll = 0;
+                app.show_commit_output = true;
+            }
+            Event::Pulled { result: Err(e) } if e.is::<Cancelled>() => {
+                refresh_branch_status(app, repo_path);
+                app.set_status("ℹ Fetch and rebase cancelled");
+            }
+            Event::Pulled { result: Err(e) } => {
+                refresh_branch_status(app, repo_path);
+                app.set_status(format!("✗ Fetch and rebase failed: {:#}", e));
+            }
+            Event::Tagged {
+                name,
+                notes,
+                result,
+            } => {
+                match result {
+                    Ok(output) => {
+                        app.commit_output =
+                            format!("✓ Created tag {}\n\n{}{}", name, notes, output);
+                        app.set_status(format!("✓ Created tag {}", name));
+                    }
+                    Err(e) if e.is::<Cancelled>() => {
+                        app.commit_output.push_str("\n\nℹ Tagging cancelled");
+                    }
+                    Err(e) => {
+                        app.commit_output
+                            .push_str(&format!("\n\n✗ Failed to create tag: {:#}", e));
+                    }
                 }
+                app.commit_output_scroll = 0;
+                app.show_commit_output = true;
             }
+            Event::Diff { path, result } => show_diff(app, repo_path, path, result),
+            Event::CombinedDiff { paths, result } => show_combined_diff(app, paths, result),
+            Event::CherryPicked { results } => apply_cherry_pick_result(app, results),
+        }
+    }
+}
+
+/// Marks a group as committed, with its commit if it could be read back.
+fn mark_committed(group: &mut ChangeGroup, done: &CommittedGroup) {
+    match done.commit {
+        Some(commit) => group.mark_as_committed_as(commit),
+        None => group.mark_as_committed(),
+    }
+}
+
+/// Shows the outcome of a commit batch.
+fn apply_commit_result(
+    app: &mut AppState,
+    repo_path: &Path,
+    committed: Vec<CommittedGroup>,
+    error: Option<anyhow::Error>,
+    cancelled: bool,
+) {
+    for done in &committed {
+        if let Some(group) = app.groups.get_mut(done.index) {
+            mark_committed(group, done);
+            group.commit_log.push(done.output.clone());
+        }
+        if let Some(commit) = done.commit {
+            app.session_commits.push((done.index, commit));
+        }
+    }
+    app.ensure_selection_visible();
+    if !committed.is_empty() {
+        refresh_branch_status(app, repo_path);
+    }
+
+    if let Some(progress) = app.commit_progress.as_mut() {
+        progress.running = false;
+        if let Some(e) = &error {
+            // The popup shows the failure and asks whether to go on
+            progress.record_failure(&format!("{:#}", e));
+        } else if cancelled || progress.failed() == 0 {
+            app.commit_progress = None;
         }
     }
 
-    if !failed {
+    // The worker commits in order, so the first group of the batch left
+    // uncommitted is the one that failed
+    let batch = std::mem::take(&mut app.commit_batch);
+    if let Some(e) = &error {
+        let failed = batch
+            .iter()
+            .find(|index| !committed.iter().any(|done| done.index == **index));
+        if let Some(group) = failed.and_then(|index| app.groups.get_mut(*index)) {
+            group.commit_log.push(format!("✗ {:#}", e));
+        }
+    }
+
+    if let Some(e) = error {
+        app.outcome = Outcome::CommitFailed;
+        if committed.is_empty() {
+            app.set_status(format!("✗ Commit failed: {:#}", e));
+        } else {
+            app.set_status(format!(
+                "✗ Committed {} group(s), then failed: {:#}",
+                committed.len(),
+                e
+            ));
+        }
+        return;
+    }
+    if cancelled {
+        if committed.is_empty() {
+            app.set_status("ℹ Commit cancelled, the index was restored");
+        } else {
+            app.set_status(format!(
+                "ℹ Cancelled after committing {} group(s)",
+                committed.len()
+            ));
+        }
+        return;
+    }
+    let Some(last) = committed.last() else {
+        return;
+    };
+    let last_header = last.header.clone();
+    app.outcome = Outcome::Success;
+
+    if committed.len() == 1 {
+        app.set_status("✓ Committed selected group successfully");
+        app.commit_output = committed[0].output.clone();
+    } else {
         app.set_status(format!(
             "✓ Successfully committed {} group(s)",
-            committed_count
+            committed.len()
         ));
-
-        // Show combined output in popup
-        app.commit_output = all_outputs.join("\n\n");
-        app.commit_output_scroll = 0;
-        app.show_commit_output = true;
+        app.commit_output = committed
+            .iter()
+            .enumerate()
+            .map(|(n, done)| match done.commit {
+                Some(commit) => format!(
+                    "Group {} ({}): {}",
+                    n + 1,
+                    &commit.to_string()[..7],
+                    done.output
+                ),
+                None => format!("Group {}: {}", n + 1, done.output),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
     }
+    app.commit_output_scroll = 0;
+    app.show_commit_output = true;
 
-    Ok(())
+    // Only the last commit is at HEAD and can be tagged
+    offer_release_tag(app, &last_header);
+    offer_cherry_pick(app, repo_path, &committed);
 }
 
 /// Draws the user interface.
@@ -429,20 +2994,42 @@ fn draw_ui<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut AppState,
 ) -> io::Result<()> {
+    app.ensure_selected_body();
     terminal.draw(|f| {
         let size = f.area();
 
-        // Main vertical layout: content area and shortcuts bar (3 lines for one text line)
+        // Main vertical layout: header line, content area, and shortcuts bar
+        // (3 lines for one text line)
         let vertical_chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(5), Constraint::Length(3)])
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(5),
+                Constraint::Length(3),
+            ])
             .split(size);
 
-        // Content area: left panel (50%) and right panel (50%)
+        draw_header_line(f, app, vertical_chunks[0]);
+
+        // An extension side panel takes the right edge of the content area
+        let mut content_area = vertical_chunks[1];
+        if let Some(panel) = app.extensions.side_panel() {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(100 - panel.width),
+                    Constraint::Percentage(panel.width),
+                ])
+                .split(content_area);
+            content_area = chunks[0];
+            panel.render(f, chunks[1], app);
+        }
+
+        // Content area: left panel (35%) and right panel (65%)
         let content_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
-            .split(vertical_chunks[0]);
+            .split(content_area);
 
         // Left panel: group list
         let is_groups_active = app.active_panel == ActivePanel::Groups;
@@ -462,8 +3049,12 @@ fn draw_ui<B: ratatui::backend::Backend>(
         let is_files_active = app.active_panel == ActivePanel::Files;
         draw_files_panel(f, app, right_chunks[1], is_files_active);
 
-        // Bottom shortcuts bar
-        draw_shortcuts_bar(f, vertical_chunks[1]);
+        // Bottom shortcuts bar, or the search while one is active
+        if let Some(query) = &app.search {
+            draw_search_bar(f, app, query, vertical_chunks[2]);
+        } else {
+            draw_shortcuts_bar(f, app, vertical_chunks[2]);
+        }
 
         // Draw status popup overlay if there's a status message
         if !app.status_message.is_empty() {
@@ -480,6 +3071,18 @@ fn draw_ui<B: ratatui::backend::Backend>(
             draw_diff_viewer_popup(f, app, size);
         }
 
+        if app.show_help {
+            draw_help_popup(f, app, size);
+        }
+
+        if let Some(info) = &app.repo_info {
+            draw_repo_info_popup(f, app, info, size);
+        }
+
+        if let Some(commits) = &app.recent_commits {
+            draw_recent_commits_popup(f, app, commits, size);
+        }
+
         // Draw editor help popup if active (highest z-order)
         if app.show_editor_help {
             draw_editor_help_popup(f, app, size);
@@ -489,6 +3092,55 @@ fn draw_ui<B: ratatui::backend::Backend>(
         if app.show_commit_output {
             draw_commit_output_popup(f, app, size);
         }
+
+        // Draw remote picker above everything else
+        if let Some(picker) = &app.remote_picker {
+            draw_remote_picker_popup(f, &app.theme, picker, size);
+        }
+
+        if let Some(picker) = &app.branch_picker {
+            draw_branch_picker_popup(f, &app.theme, picker, size);
+        }
+
+        if let Some(input) = &app.feedback_input {
+            draw_feedback_popup(f, &app.theme, input, size);
+        }
+
+        if let Some(input) = &app.notes_input {
+            draw_notes_popup(f, &app.theme, input, size);
+        }
+
+        if let Some(input) = &app.branch_input {
+            draw_branch_input_popup(f, &app.theme, input, size);
+        }
+
+        if let Some(rename) = &app.scope_rename {
+            draw_scope_rename_popup(f, app, rename, size);
+        }
+
+        if let Some(picker) = &app.candidate_picker {
+            draw_candidate_picker_popup(f, app, picker, size);
+        }
+
+        if let Some(comparison) = &app.message_comparison {
+            draw_message_comparison_popup(f, &app.theme, comparison, size);
+        }
+
+        if let Some(stale) = &app.stale_groups {
+            draw_stale_groups_popup(f, app, stale, size);
+        }
+
+        if let Some(confirmation) = &app.confirmation {
+            draw_confirmation_popup(f, &app.theme, confirmation, size);
+        }
+
+        if let Some(progress) = &app.commit_progress {
+            draw_commit_progress_popup(f, app, progress, size);
+        }
+
+        if let Some(tutorial) = &app.tutorial {
+            draw_tutorial_popup(f, &app.theme, tutorial, size);
+        }
     })?;
 
     Ok(())
@@ -503,7 +3155,7 @@ fn draw_editor_overlay(f: &mut ratatui::Frame, app: &mut AppState, area: ratatui
     let block = Block::default()
         .borders(Borders::ALL)
         .title(" Commit Message Editor (Ctrl+S=save, Ctrl+C=cancel) ")
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(app.theme.border));
 
     // Get the inner area for the editor view
     let inner_area = block.inner(area);
@@ -514,9 +3166,101 @@ fn draw_editor_overlay(f: &mut ratatui::Frame, app: &mut AppState, area: ratatui
     // Render the block first
     f.render_widget(block, area);
 
+    // The lint status goes below the text
+    let lint_lines = lint_status_lines(app);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(lint_lines.len() as u16),
+        ])
+        .split(inner_area);
+    f.render_widget(Paragraph::new(lint_lines), chunks[1]);
+
     // Render the editor view inside
     let editor_view = EditorView::new(app.editor.state_mut());
-    f.render_widget(editor_view, inner_area);
+    f.render_widget(editor_view, chunks[0]);
+}
+
+/// Maximum number of problems listed below the editor.
+const MAX_LINT_LINES: usize = 3;
+
+/// Builds the status lines below the editor: the lengths of the header and
+/// the longest body line, and the problems of the message being edited.
+fn lint_status_lines(app: &AppState) -> Vec<Line<'static>> {
+    use crate::lint::{MessageLint, Severity};
+    use crate::validation::ScopePolicy;
+
+    let Some(commit_type) = app.selected_group().map(|group| group.commit_type) else {
+        return Vec::new();
+    };
+    // An invalid policy is reported when committing
+    let policy = ScopePolicy::from_config(&app.config.scope).unwrap_or_default();
+    let lint = MessageLint::check(&app.editor.text(), commit_type, &policy);
+
+    let ruler_style = if lint.header_length > ChangeGroup::MAX_HEADER_LENGTH {
+        Style::default().fg(app.theme.highlight)
+    } else {
+        Style::default().fg(app.theme.muted)
+    };
+    let mut ruler = vec![Span::styled(lint.ruler(), ruler_style)];
+    if lint.issues.is_empty() {
+        ruler.push(Span::styled(
+            "  ✓ No problems",
+            Style::default().fg(app.theme.success),
+        ));
+    } else {
+        ruler.push(Span::styled(
+            format!(
+                "  {} error(s), {} warning(s)",
+                lint.errors(),
+                lint.issues.len() - lint.errors()
+            ),
+            Style::default().fg(app.theme.muted),
+        ));
+    }
+
+    let mut lines = vec![Line::from(ruler)];
+    for issue in lint.issues.iter().take(MAX_LINT_LINES) {
+        let (icon, color) = match issue.severity {
+            Severity::Error => ("✗", app.theme.error),
+            Severity::Warning => ("⚠", app.theme.highlight),
+        };
+        lines.push(Line::from(Span::styled(
+            format!("{} {}", icon, issue),
+            Style::default().fg(color),
+        )));
+    }
+    if lint.issues.len() > MAX_LINT_LINES {
+        lines.push(Line::from(Span::styled(
+            format!("  … {} more", lint.issues.len() - MAX_LINT_LINES),
+            Style::default().fg(app.theme.muted),
+        )));
+    }
+    lines
+}
+
+/// Builds the `  +added -removed` spans shown next to files and groups.
+fn diff_stats_spans(
+    theme: &Theme,
+    added: usize,
+    removed: usize,
+    dimmed: bool,
+) -> [Span<'static>; 3] {
+    let (added_style, removed_style) = if dimmed {
+        let dim = Style::default().fg(theme.muted);
+        (dim, dim)
+    } else {
+        (
+            Style::default().fg(theme.success),
+            Style::default().fg(theme.error),
+        )
+    };
+    [
+        Span::raw("  "),
+        Span::styled(format!("+{}", added), added_style),
+        Span::styled(format!(" -{}", removed), removed_style),
+    ]
 }
 
 /// Draws the left panel showing the list of commit groups.
@@ -526,42 +3270,166 @@ fn draw_groups_panel(
     area: ratatui::layout::Rect,
     is_active: bool,
 ) {
-    let items: Vec<ListItem> = app
-        .groups
+    let visible = app.visible_groups();
+    let mut items: Vec<ListItem> = visible
         .iter()
-        .enumerate()
-        .map(|(idx, group)| {
+        .map(|&idx| {
+            let group = &app.groups[idx];
             let header = group.header();
             let is_selected = idx == app.selected_index;
             let is_committed = group.is_committed();
+            let is_skipped = group.skipped && !is_committed;
 
             let style = if is_committed {
                 // Committed groups are grayed out
                 Style::default()
-                    .fg(Color::DarkGray)
+                    .fg(app.theme.muted)
                     .add_modifier(Modifier::DIM)
+            } else if is_skipped {
+                // Skipped groups are muted, but stay readable and selectable
+                let style = Style::default()
+                    .fg(app.theme.subtle)
+                    .add_modifier(Modifier::ITALIC);
+                if is_selected {
+                    style.add_modifier(Modifier::BOLD)
+                } else {
+                    style
+                }
             } else if is_selected {
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD)
             } else {
                 Style::default()
             };
 
             let prefix = if is_committed {
-                "✓ "
+                match group.short_commit() {
+                    Some(id) => format!("✓ {} ", id),
+                    None => "✓ ".to_string(),
+                }
+            } else if is_skipped {
+                "⊘ ".to_string()
             } else if is_selected {
-                "▶ "
+                "▶ ".to_string()
+            } else {
+                "  ".to_string()
+            };
+            // Color the type so the mix of commit types shows at a glance
+            let type_str = group.commit_type.as_str();
+            let type_style = if is_committed || is_skipped {
+                style
             } else {
-                "  "
+                style.fg(app.palette.color(group.commit_type))
             };
-            let content = format!("{}{}", prefix, header);
+            let rest = header.strip_prefix(type_str).unwrap_or(&header).to_string();
+
+            let mut spans = vec![
+                Span::styled(prefix, style),
+                Span::styled(type_str, type_style),
+            ];
+            if app.matches_search(&header) {
+                spans.extend(search_spans(app, rest, style));
+            } else {
+                spans.push(Span::styled(rest, style));
+            }
+            if let Some((added, removed)) = group.diff_stats() {
+                spans.extend(diff_stats_spans(
+                    &app.theme,
+                    added,
+                    removed,
+                    is_committed || is_skipped,
+                ));
+            }
+            if !is_committed && app.group_has_license_offenders(idx) {
+                spans.push(Span::styled(" ⚖", Style::default().fg(app.theme.error)));
+            }
+            if !is_committed && group.stale {
+                spans.push(Span::styled(" ⟳", Style::default().fg(app.theme.highlight)));
+            }
+            if !is_committed && group.notes.is_some() {
+                spans.push(Span::styled(" ¶", Style::default().fg(app.theme.info)));
+            }
+            if !is_committed && group.order_note.is_some() {
+                spans.push(Span::styled(" ⇅", Style::default().fg(app.theme.info)));
+            }
+            if !is_committed && group.has_empty_description() {
+                spans.push(Span::styled(" ∅", Style::default().fg(app.theme.error)));
+            } else if !is_committed && group.description_generated {
+                spans.push(Span::styled(" ✎", Style::default().fg(app.theme.highlight)));
+            }
+            let found = group
+                .files
+                .iter()
+                .filter(|f| app.matches_search(&f.display_path()))
+                .count();
+            if found > 0 {
+                spans.push(Span::styled(
+                    format!(" ⌕{}", found),
+                    Style::default().fg(app.theme.highlight),
+                ));
+            }
+            let marked = app.marked_count(idx);
+            if !is_committed && marked > 0 {
+                spans.push(Span::styled(
+                    format!(" ☑{}", marked),
+                    Style::default().fg(app.theme.info),
+                ));
+            }
 
-            ListItem::new(Line::from(Span::styled(content, style)))
+            ListItem::new(Line::from(spans))
         })
         .collect();
-    let border_color = if is_active { Color::Green } else { Color::Cyan };
-    let title = format!(" Commit Groups ({}) ", app.groups.len());
+    // Files left out of their groups, shown like a group that is never
+    // committed
+    if !app.orphaned_files.is_empty() {
+        let style = Style::default()
+            .fg(app.theme.muted)
+            .add_modifier(Modifier::ITALIC);
+        let mut lines = vec![Line::from(Span::styled(
+            format!("⊖ left out: {} file(s)", app.orphaned_files.len()),
+            style,
+        ))];
+        lines.extend(
+            app.orphaned_files
+                .iter()
+                .map(|file| Line::from(Span::styled(format!("    {}", file.path), style))),
+        );
+        items.push(ListItem::new(lines));
+    }
+
+    let border_color = if is_active {
+        app.theme.active_border
+    } else {
+        app.theme.border
+    };
+    let count = if visible.len() == app.groups.len() {
+        app.groups.len().to_string()
+    } else {
+        format!("{}/{}", visible.len(), app.groups.len())
+    };
+    let mut view = Vec::new();
+    if app.group_sort != GroupSort::Original {
+        view.push(app.group_sort.label());
+    }
+    if app.group_filter != GroupFilter::All {
+        view.push(app.group_filter.label());
+    }
+    let count = if view.is_empty() {
+        count
+    } else {
+        format!("{} · {}", count, view.join(" · "))
+    };
+    let title = match &app.ai_worker {
+        Some(worker) if worker.job() == AiJob::Grouping => format!(
+            " Commit Groups ({}) {} AI grouping via {}{}… (Esc cancels) ",
+            count,
+            worker.spinner(),
+            worker.provider_name(),
+            retry_suffix(app)
+        ),
+        _ => format!(" Commit Groups ({}) ", count),
+    };
     let list = List::new(items).block(
         Block::default()
             .title(title)
@@ -569,14 +3437,20 @@ fn draw_groups_panel(
             .border_style(Style::default().fg(border_color)),
     );
 
-    f.render_widget(list, area);
+    // Keep the selected group scrolled into view
+    let position = visible
+        .iter()
+        .position(|&idx| idx == app.selected_index)
+        .unwrap_or(0);
+    let mut list_state = ListState::default().with_selected(Some(position));
+    f.render_stateful_widget(list, area, &mut list_state);
 
     // Add scrollbar on the right edge
     let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
         .begin_symbol(Some("↑"))
         .end_symbol(Some("↓"));
     let mut scrollbar_state =
-        ScrollbarState::new(app.groups.len().saturating_sub(1)).position(app.selected_index);
+        ScrollbarState::new(visible.len().saturating_sub(1)).position(position);
     f.render_stateful_widget(
         scrollbar,
         area.inner(Margin {
@@ -600,9 +3474,9 @@ fn draw_commit_message_panel(
         let line_count = all_lines.len();
 
         let border_color = if is_active {
-            Color::Green
+            app.theme.active_border
         } else {
-            Color::White
+            app.theme.text
         };
 
         // Calculate visible lines with scroll offset
@@ -611,13 +3485,23 @@ fn draw_commit_message_panel(
         let end_line = (start_line + visible_height).min(line_count);
         let visible_text = all_lines[start_line..end_line].join("\n");
 
+        let mut block = Block::default()
+            .title(commit_message_title(app))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color));
+        // The AI's reason for the group's place in the commit order
+        if let Some(note) = group
+            .order_note
+            .as_deref()
+            .filter(|_| !group.is_committed())
+        {
+            block = block.title_bottom(Line::from(Span::styled(
+                format!(" ⇅ {} ", note),
+                Style::default().fg(app.theme.info),
+            )));
+        }
         let paragraph = Paragraph::new(visible_text)
-            .block(
-                Block::default()
-                    .title(" Commit Message ")
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(border_color)),
-            )
+            .block(block)
             // We intentionally use `trim: true` here to remove trailing whitespace from commit messages,
             // as trailing spaces are rarely meaningful in commit messages and can cause formatting issues.
             // Note: The files panel uses `trim: false` to preserve whitespace, which is important for file diffs.
@@ -642,9 +3526,9 @@ fn draw_commit_message_panel(
         }
     } else {
         let border_color = if is_active {
-            Color::Green
+            app.theme.active_border
         } else {
-            Color::White
+            app.theme.text
         };
         let empty = Paragraph::new("No group selected").block(
             Block::default()
@@ -664,6 +3548,8 @@ fn draw_files_panel(
     is_active: bool,
 ) {
     if let Some(group) = app.selected_group() {
+        // Checkboxes are shown while files are marked
+        let marking = !app.marked_files.is_empty() && !group.is_committed();
         let file_lines: Vec<Line> = group
             .files
             .iter()
@@ -671,7 +3557,9 @@ fn draw_files_panel(
             .map(|(idx, file)| {
                 let is_selected = idx == app.selected_file_index;
 
-                let status_icon = if file.is_new() {
+                let status_icon = if file.is_submodule() {
+                    "◈"
+                } else if file.is_new() {
                     "+"
                 } else if file.is_deleted() {
                     "-"
@@ -686,29 +3574,55 @@ fn draw_files_panel(
                 let prefix = if is_selected { "▶ " } else { "  " };
                 let style = if is_selected {
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(app.theme.highlight)
                         .add_modifier(Modifier::BOLD)
                 } else {
                     Style::default()
                 };
 
-                Line::from(vec![
-                    Span::raw(prefix),
-                    Span::styled(
-                        format!("{} ", status_icon),
-                        Style::default().fg(Color::Magenta),
-                    ),
-                    Span::styled(&file.path, style),
-                ])
+                let mut spans = vec![Span::raw(prefix)];
+                if marking {
+                    spans.push(if app.marked_files.contains(&(app.selected_index, idx)) {
+                        Span::styled("☑ ", Style::default().fg(app.theme.info))
+                    } else {
+                        Span::styled("☐ ", Style::default().fg(app.theme.muted))
+                    });
+                }
+                spans.extend([Span::styled(
+                    format!("{} ", status_icon),
+                    Style::default().fg(app.theme.special),
+                )]);
+                spans.extend(search_spans(app, file.display_path(), style));
+                if let Some((added, removed)) = file.diff_stats() {
+                    spans.extend(diff_stats_spans(&app.theme, added, removed, false));
+                }
+                if app.license_offenders.contains(&file.path) {
+                    spans.push(Span::styled(
+                        "  ⚖ missing license header",
+                        Style::default().fg(app.theme.error),
+                    ));
+                }
+                Line::from(spans)
             })
             .collect();
 
-        let border_color = if is_active { Color::Green } else { Color::Blue };
+        let border_color = if is_active {
+            app.theme.active_border
+        } else {
+            app.theme.panel_border
+        };
         let file_lines_len = file_lines.len();
         let files_paragraph = Paragraph::new(file_lines)
             .block(
                 Block::default()
-                    .title(format!(" Files ({}) ", group.files.len()))
+                    .title(match app.marks().len() {
+                        0 => format!(" Files ({}) ", group.files.len()),
+                        marked => format!(
+                            " Files ({}, {} marked: m moves, x leaves out, d diffs) ",
+                            group.files.len(),
+                            marked
+                        ),
+                    })
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(border_color)),
             )
@@ -732,7 +3646,11 @@ fn draw_files_panel(
             );
         }
     } else {
-        let border_color = if is_active { Color::Green } else { Color::Blue };
+        let border_color = if is_active {
+            app.theme.active_border
+        } else {
+            app.theme.panel_border
+        };
         let empty = Paragraph::new("No files").block(
             Block::default()
                 .title(" Files ")
@@ -743,71 +3661,318 @@ fn draw_files_panel(
     }
 }
 
+/// Returns the commit message panel title, with a spinner while the selected
+/// group's message is being generated.
+fn commit_message_title(app: &AppState) -> String {
+    match &app.ai_worker {
+        Some(worker) if worker.job() == AiJob::Message(app.selected_index) => format!(
+            " Commit Message {} generating via {}{}… (Esc cancels) ",
+            worker.spinner(),
+            worker.provider_name(),
+            retry_suffix(app)
+        ),
+        Some(worker) if worker.job() == AiJob::Refine(app.selected_index) => format!(
+            " Commit Message {} refining via {}{}… (Esc cancels) ",
+            worker.spinner(),
+            worker.provider_name(),
+            retry_suffix(app)
+        ),
+        _ => " Commit Message ".to_string(),
+    }
+}
+
+/// Returns `, retrying 2/3` while a failed AI request waits to be retried.
+fn retry_suffix(app: &AppState) -> String {
+    app.ai_provider
+        .as_ref()
+        .and_then(|provider| provider.retry_notice())
+        .map(|notice| format!(", {}", notice))
+        .unwrap_or_default()
+}
+
+/// Longest HEAD summary shown in the header, in characters.
+const HEAD_SUMMARY_CHARS: usize = 40;
+
+/// Draws the header line with repository, branch and its upstream, HEAD,
+/// ticket, AI provider, and session time, and a hint when a newer release
+/// is available.
+///
+/// The status on the right keeps its place; when the line is too narrow for
+/// both, the HEAD commit and the model name are left out first, so the
+/// repository, branch, ticket, and provider stay readable.
+fn draw_header_line(f: &mut ratatui::Frame, app: &AppState, area: ratatui::layout::Rect) {
+    let label = Style::default().fg(app.theme.muted);
+    let value = Style::default().add_modifier(Modifier::BOLD);
+
+    let mut right = Vec::new();
+    if let Some(events) = &app.events {
+        if let Some((kind, elapsed)) = events.running() {
+            // Quick operations finish before a countdown would be readable
+            let countdown = match events.remaining() {
+                Some(remaining) if elapsed >= COUNTDOWN_AFTER => {
+                    format!(", {} left", format_elapsed(remaining))
+                }
+                _ => String::new(),
+            };
+            right.push(Span::styled(
+                format!(
+                    "{} {}…{} (Esc cancels)  ",
+                    spinner_frame(elapsed),
+                    kind.label(),
+                    countdown
+                ),
+                value.fg(app.theme.highlight),
+            ));
+        }
+    }
+    if app.worktree_changed {
+        right.push(Span::styled(
+            "⟳ Files changed, R refreshes the groups  ",
+            value.fg(app.theme.highlight),
+        ));
+    }
+    if let Some(hint) = &app.update_hint {
+        right.push(Span::styled(
+            format!("⬆ {}  ", hint),
+            label.fg(app.theme.highlight),
+        ));
+    }
+    right.push(Span::styled(
+        format!("⏱ {} ", app.session.elapsed_label()),
+        label,
+    ));
+    let right = Line::from(right);
+    let right_width = (right.width() as u16).min(area.width);
+
+    let mut left = Line::from(header_spans(app, true));
+    if left.width() as u16 + right_width > area.width {
+        left = Line::from(header_spans(app, false));
+    }
+    let left_area = Rect {
+        width: area.width - right_width,
+        ..area
+    };
+    let right_area = Rect {
+        x: area.x + left_area.width,
+        width: right_width,
+        ..area
+    };
+    f.render_widget(Paragraph::new(left), left_area);
+    f.render_widget(
+        Paragraph::new(right).alignment(Alignment::Right),
+        right_area,
+    );
+}
+
+/// Returns the left part of the header line; without `detailed`, the HEAD
+/// commit and the model name are left out.
+fn header_spans(app: &AppState, detailed: bool) -> Vec<Span<'static>> {
+    let session = &app.session;
+    let label = Style::default().fg(app.theme.muted);
+    let value = Style::default().add_modifier(Modifier::BOLD);
+
+    let mut spans = vec![
+        Span::styled(" 📁 ", label),
+        Span::styled(session.repo_name.clone(), value),
+        Span::styled("  🌿 ", label),
+        Span::styled(session.branch.clone(), value.fg(app.theme.success)),
+    ];
+    let status = &session.branch_status;
+    if let Some(tracking) = status.tracking_label() {
+        let style = if status.is_behind() {
+            value.fg(app.theme.highlight)
+        } else {
+            label
+        };
+        spans.push(Span::styled(format!(" {}", tracking), style));
+    }
+    if let (Some(id), true) = (&status.head_id, detailed) {
+        spans.push(Span::styled(
+            format!(
+                "  ⎇ {} {}",
+                id,
+                crate::prompt::truncate_chars(&status.head_summary, HEAD_SUMMARY_CHARS)
+            ),
+            label,
+        ));
+    }
+    if let Some(ticket) = &session.ticket {
+        spans.push(Span::styled("  🎫 ", label));
+        spans.push(Span::styled(ticket.clone(), value.fg(app.theme.highlight)));
+    }
+    spans.push(Span::styled("  🤖 ", label));
+    spans.push(match &app.ai_provider {
+        Some(provider) => Span::styled(
+            match provider.model_name().filter(|_| detailed) {
+                Some(model) => format!("{} ({})", provider.name(), model),
+                None => provider.name().to_string(),
+            },
+            value.fg(app.theme.info),
+        ),
+        None => Span::styled("AI off", label),
+    });
+    spans
+}
+
 /// Draws the keyboard shortcuts bar at the bottom.
-fn draw_shortcuts_bar(f: &mut ratatui::Frame, area: ratatui::layout::Rect) {
+///
+/// When the repository has several worktrees, the active one is shown on the
+/// right of the bar.
+fn draw_shortcuts_bar(f: &mut ratatui::Frame, app: &AppState, area: ratatui::layout::Rect) {
     let shortcuts = vec![
         Span::styled(
             " ↑↓/jk ",
             Style::default()
-                .fg(Color::Yellow)
+                .fg(app.theme.highlight)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw("Navigate "),
         Span::styled(
             " e ",
             Style::default()
-                .fg(Color::Cyan)
+                .fg(app.theme.info)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw("Edit "),
         Span::styled(
             " d ",
             Style::default()
-                .fg(Color::Magenta)
+                .fg(app.theme.special)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw("Diff "),
         Span::styled(
             " c ",
             Style::default()
-                .fg(Color::Green)
+                .fg(app.theme.success)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw("Commit "),
         Span::styled(
             " C ",
             Style::default()
-                .fg(Color::Green)
+                .fg(app.theme.success)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw("Commit All "),
         Span::styled(
             " Ctrl+L ",
             Style::default()
-                .fg(Color::White)
+                .fg(app.theme.text)
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw("Clear Status "),
         Span::styled(
             " q ",
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            Style::default()
+                .fg(app.theme.error)
+                .add_modifier(Modifier::BOLD),
         ),
         Span::raw("Quit"),
     ];
 
     let shortcuts_line = Line::from(shortcuts);
+    let mut block = Block::default()
+        .title(" Keyboard Shortcuts ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border));
+    if let Some(label) = &app.worktree_label {
+        block = block.title(
+            Line::from(Span::styled(
+                format!(" 🌳 {} ", label),
+                Style::default()
+                    .fg(app.theme.special)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .right_aligned(),
+        );
+    }
     let shortcuts_paragraph = Paragraph::new(shortcuts_line)
-        .block(
-            Block::default()
-                .title(" Keyboard Shortcuts ")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
-        )
+        .block(block)
         .alignment(Alignment::Center);
 
     f.render_widget(shortcuts_paragraph, area);
 }
 
+/// Draws the search query and the number of matches in place of the
+/// shortcuts bar.
+fn draw_search_bar(f: &mut ratatui::Frame, app: &AppState, query: &str, area: Rect) {
+    let matches = app.search_matches();
+    let mut spans = vec![
+        Span::styled(" / ", Style::default().fg(app.theme.highlight)),
+        Span::raw(query.to_string()),
+    ];
+    if app.search_typing {
+        spans.push(Span::styled("█", Style::default().fg(app.theme.highlight)));
+    }
+    spans.push(Span::raw("   "));
+    spans.push(match matches.len() {
+        _ if query.is_empty() => Span::styled(
+            "type to search group headers and file paths",
+            Style::default().fg(app.theme.muted),
+        ),
+        0 => Span::styled("no matches", Style::default().fg(app.theme.error)),
+        n => {
+            let current = matches.iter().position(|&(group, file)| {
+                group == app.selected_index
+                    && match file {
+                        Some(file) => {
+                            app.active_panel == ActivePanel::Files
+                                && file == app.selected_file_index
+                        }
+                        None => app.active_panel != ActivePanel::Files,
+                    }
+            });
+            let label = match current {
+                Some(pos) => format!("{} of {} matches", pos + 1, n),
+                None => format!("{} matches", n),
+            };
+            Span::styled(label, Style::default().fg(app.theme.success))
+        }
+    });
+
+    let title = if app.search_typing {
+        " Search (Enter keeps, Esc clears, ↑↓ jump) "
+    } else {
+        " Search (n/N jump, / edits, Esc clears) "
+    };
+    let paragraph = Paragraph::new(Line::from(spans)).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.theme.popup_border)),
+    );
+    f.render_widget(paragraph, area);
+}
+
+/// Splits `text` into spans in `style`, highlighting the parts that match
+/// the active search.
+fn search_spans(app: &AppState, text: String, style: Style) -> Vec<Span<'static>> {
+    let query = match app.search.as_deref() {
+        Some(query) if !query.is_empty() => query.to_ascii_lowercase(),
+        _ => return vec![Span::styled(text, style)],
+    };
+    // ASCII lowercasing keeps the byte offsets of `text`
+    let lower = text.to_ascii_lowercase();
+    let highlight = style.fg(app.theme.inverse).bg(app.theme.highlight);
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (pos, _) in lower.match_indices(&query) {
+        if pos > start {
+            spans.push(Span::styled(text[start..pos].to_string(), style));
+        }
+        spans.push(Span::styled(
+            text[pos..pos + query.len()].to_string(),
+            highlight,
+        ));
+        start = pos + query.len();
+    }
+    if start < text.len() {
+        spans.push(Span::styled(text[start..].to_string(), style));
+    }
+    spans
+}
+
 /// Draws a centered status popup overlay.
 fn draw_status_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatui::layout::Rect) {
     // Fixed height provides consistent appearance across terminal sizes
@@ -832,9 +3997,9 @@ fn draw_status_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatui::layo
 
     // Render popup border first - highlight if active
     let border_color = if app.popup_active {
-        Color::Green
+        app.theme.active_border
     } else {
-        Color::Yellow
+        app.theme.popup_border
     };
     let title = if app.popup_active {
         " Status (↑↓ scroll, Enter/Esc close) "
@@ -899,8 +4064,8 @@ fn draw_status_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatui::layo
     // Create close button highlighted to show it's active (Enter closes)
     let button_text = "[ Close ]";
     let button_style = Style::default()
-        .fg(Color::Black)
-        .bg(Color::Green)
+        .fg(app.theme.inverse)
+        .bg(app.theme.success)
         .add_modifier(Modifier::BOLD);
     let button = Paragraph::new(button_text)
         .style(button_style)
@@ -908,8 +4073,295 @@ fn draw_status_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatui::layo
     f.render_widget(button, button_area);
 }
 
+/// Returns one line per binding of `section`, keys first.
+fn binding_lines(section: &keymap::Section, key_style: Style) -> Vec<Line<'static>> {
+    section
+        .bindings
+        .iter()
+        .map(|binding| {
+            Line::from(vec![
+                Span::styled(format!("{:<10}", binding.label()), key_style),
+                Span::raw(binding.description),
+            ])
+        })
+        .collect()
+}
+
+/// Draws the help popup with the shortcuts of every view, generated from
+/// the key bindings, and the commit type colors.
+fn draw_help_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatui::layout::Rect) {
+    let popup_width = (area.width as f32 * 0.6) as u16;
+    let popup_height = (area.height as f32 * 0.8) as u16;
+
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+        y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let popup_block = Block::default()
+        .title(" Keyboard Shortcuts (↑/↓ to scroll, ? or Esc to close) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border));
+    let inner_area = popup_block.inner(popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    let key_style = Style::default()
+        .fg(app.theme.highlight)
+        .add_modifier(Modifier::BOLD);
+    let mut lines: Vec<Line> = Vec::new();
+    for section in keymap::SECTIONS {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            section.title,
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        lines.extend(binding_lines(section, key_style));
+    }
+
+    if !app.extensions.actions().is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Extensions",
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        for action in app.extensions.actions() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:<10}", action.key), key_style),
+                Span::raw(action.label.clone()),
+            ]));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Commit types",
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+    let mut legend = Vec::new();
+    for &commit_type in CommitType::all() {
+        legend.push(Span::styled(
+            format!("■ {}", commit_type.as_str()),
+            Style::default().fg(app.palette.color(commit_type)),
+        ));
+        legend.push(Span::raw("  "));
+    }
+    lines.push(Line::from(legend));
+
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((app.help_scroll, 0));
+    f.render_widget(paragraph, inner_area);
+}
+
+/// Draws the current step of the first-run tour beside the panel it is
+/// about, so the highlighted panel stays visible.
+fn draw_tutorial_popup(
+    f: &mut ratatui::Frame,
+    theme: &Theme,
+    tutorial: &crate::tutorial::Tutorial,
+    area: ratatui::layout::Rect,
+) {
+    let step = tutorial.step();
+    let key_style = Style::default()
+        .fg(theme.highlight)
+        .add_modifier(Modifier::BOLD);
+
+    let mut lines = vec![Line::from(step.text), Line::from("")];
+    for (key, description) in step.keys {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<6}", key), key_style),
+            Span::raw(*description),
+        ]));
+    }
+    if !step.keys.is_empty() {
+        lines.push(Line::from(""));
+    }
+    let last = tutorial.index() + 1 == crate::tutorial::STEPS.len();
+    lines.push(Line::from(Span::styled(
+        format!(
+            "Enter: {} · ←: back · Esc: skip",
+            if last { "start" } else { "next" }
+        ),
+        Style::default().fg(theme.muted),
+    )));
+
+    let popup_width = ((area.width as f32 * 0.45) as u16).max(40).min(area.width);
+    let popup_height = (lines.len() as u16 + 4).min(area.height);
+    // Groups are on the left, message and files on the right
+    let x = match step.panel {
+        Some(ActivePanel::Groups) => area.width.saturating_sub(popup_width + 2),
+        Some(_) => 2,
+        None => area.width.saturating_sub(popup_width) / 2,
+    };
+    let popup_area = Rect {
+        x: area.x + x.min(area.width.saturating_sub(popup_width)),
+        y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    f.render_widget(Clear, popup_area);
+    let popup_block = Block::default()
+        .title(format!(
+            " {} ({}/{}) ",
+            step.title,
+            tutorial.index() + 1,
+            crate::tutorial::STEPS.len()
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border));
+    let inner_area = popup_block.inner(popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+    f.render_widget(paragraph, inner_area);
+}
+
+/// Draws the info popup: the repository's location, branch and upstream,
+/// last commit, stashes, and dirty submodules, followed by why AI fell back
+/// to heuristics, newest first.
+fn draw_repo_info_popup(
+    f: &mut ratatui::Frame,
+    app: &AppState,
+    info: &crate::git::RepoInfo,
+    area: ratatui::layout::Rect,
+) {
+    let popup_width = (area.width as f32 * 0.6) as u16;
+    let popup_height = (area.height as f32 * 0.8) as u16;
+
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+        y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+        width: popup_width,
+        height: popup_height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let popup_block = Block::default()
+        .title(" Repository Info (i or Esc to close) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme.border));
+    let inner_area = popup_block.inner(popup_area);
+    f.render_widget(popup_block, popup_area);
+
+    let label = Style::default().fg(app.theme.muted);
+    let row = |name: &str, value: Span<'static>| {
+        Line::from(vec![Span::styled(format!("{:<13}", name), label), value])
+    };
+    let muted = |text: &str| Span::styled(text.to_string(), label);
+
+    let status = &info.status;
+    let mut lines = vec![
+        row("Repository", Span::raw(info.workdir.display().to_string())),
+        row(
+            "Branch",
+            match &info.branch {
+                Some(branch) => {
+                    Span::styled(branch.clone(), Style::default().fg(app.theme.success))
+                }
+                None => muted("detached HEAD"),
+            },
+        ),
+        row(
+            "Upstream",
+            match status.tracking_label() {
+                Some(tracking) if status.is_behind() => {
+                    Span::styled(tracking, Style::default().fg(app.theme.highlight))
+                }
+                Some(tracking) => Span::raw(tracking),
+                None => muted("none"),
+            },
+        ),
+    ];
+    if status.upstream.is_some() {
+        lines.push(row(
+            "",
+            muted(&format!(
+                "{} ahead, {} behind as of the last fetch (F fetches and rebases)",
+                status.ahead, status.behind
+            )),
+        ));
+    }
+    match &status.head_id {
+        Some(id) => {
+            lines.push(row(
+                "Last commit",
+                Span::raw(format!("{} {}", id, status.head_summary)),
+            ));
+            let by = match (&info.author, &info.committed_at) {
+                (Some(author), Some(at)) => format!("{}, {}", author, at),
+                (Some(author), None) => author.clone(),
+                (None, Some(at)) => at.clone(),
+                (None, None) => String::new(),
+            };
+            if !by.is_empty() {
+                lines.push(row("", muted(&by)));
+            }
+        }
+        None => lines.push(row("Last commit", muted("none yet"))),
+    }
+    lines.push(row(
+        "Stashes",
+        match info.stashes {
+            0 => muted("none"),
+            n => Span::styled(n.to_string(), Style::default().fg(app.theme.highlight)),
+        },
+    ));
+    if info.dirty_submodules.is_empty() {
+        lines.push(row("Submodules", muted("clean")));
+    }
+    for (n, submodule) in info.dirty_submodules.iter().enumerate() {
+        lines.push(row(
+            if n == 0 { "Submodules" } else { "" },
+            Span::styled(
+                format!("{}: {}", submodule.path, submodule.changes.join(", ")),
+                Style::default().fg(app.theme.highlight),
+            ),
+        ));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "AI fallbacks",
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+    if app.ai_fallbacks.is_empty() {
+        lines.push(Line::from(muted(
+            "AI has not fallen back to heuristics in this session",
+        )));
+    }
+    for fallback in app.ai_fallbacks.iter().rev() {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{} ", fallback.time),
+                Style::default().fg(app.theme.muted),
+            ),
+            Span::styled(
+                fallback.summary(),
+                Style::default()
+                    .fg(app.theme.highlight)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]));
+        for detail in fallback.detail.lines() {
+            lines.push(Line::from(format!("  {}", detail)));
+        }
+        lines.push(Line::from(""));
+    }
+
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+    f.render_widget(paragraph, inner_area);
+}
+
 /// Draws the editor help popup showing keyboard shortcuts.
-fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui::layout::Rect) {
+fn draw_editor_help_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatui::layout::Rect) {
     // Calculate popup size (60% width, 70% height)
     let popup_width = (area.width as f32 * 0.6) as u16;
     let popup_height = (area.height as f32 * 0.7) as u16;
@@ -931,46 +4383,23 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
     let popup_block = Block::default()
         .title(" Editor Keyboard Shortcuts (Esc to close) ")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(app.theme.border));
     f.render_widget(popup_block.clone(), popup_area);
 
     // Inner area for content
     let inner_area = popup_block.inner(popup_area);
 
     // Help text content
-    let help_text = vec![
-        Line::from(vec![
-            Span::styled(
-                "Ctrl+S",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw("    Save and close"),
-        ]),
-        Line::from(vec![
-            Span::styled(
-                "Ctrl+C",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw("    Cancel without saving"),
-        ]),
-        Line::from(vec![
-            Span::styled(
-                "?",
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw("         Toggle this help"),
-        ]),
+    let key_style = Style::default()
+        .fg(app.theme.highlight)
+        .add_modifier(Modifier::BOLD);
+    let mut help_text = binding_lines(&keymap::EDITOR, key_style);
+    help_text.extend(vec![
         Line::from(""),
         Line::from(vec![Span::styled(
             "--- Vim-Style Navigation ---",
             Style::default()
-                .fg(Color::Green)
+                .fg(app.theme.success)
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
@@ -978,7 +4407,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "h j k l",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("   Left, Down, Up, Right"),
@@ -987,7 +4416,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "0",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Start of line"),
@@ -996,7 +4425,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "$",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         End of line"),
@@ -1005,7 +4434,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "w",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Next word"),
@@ -1014,7 +4443,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "b",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Previous word"),
@@ -1023,7 +4452,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "gg",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("        Go to first line"),
@@ -1032,7 +4461,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "G",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Go to last line"),
@@ -1041,7 +4470,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
         Line::from(vec![Span::styled(
             "--- Vim-Style Editing ---",
             Style::default()
-                .fg(Color::Green)
+                .fg(app.theme.success)
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(""),
@@ -1049,7 +4478,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "i",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Insert mode"),
@@ -1058,7 +4487,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "a",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Append after cursor"),
@@ -1067,7 +4496,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "A",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Append at end of line"),
@@ -1076,7 +4505,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "o",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Open line below"),
@@ -1085,7 +4514,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "O",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Open line above"),
@@ -1094,7 +4523,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "x",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Delete character"),
@@ -1103,7 +4532,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "dd",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("        Delete line"),
@@ -1112,7 +4541,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "yy",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("        Yank (copy) line"),
@@ -1121,7 +4550,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "p",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Paste"),
@@ -1130,7 +4559,7 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "u",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("         Undo"),
@@ -1139,12 +4568,12 @@ fn draw_editor_help_popup(f: &mut ratatui::Frame, _app: &AppState, area: ratatui
             Span::styled(
                 "Ctrl+R",
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(app.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
             Span::raw("    Redo"),
         ]),
-    ];
+    ]);
 
     let paragraph = Paragraph::new(help_text)
         .wrap(Wrap { trim: false })
@@ -1172,21 +4601,39 @@ fn draw_diff_viewer_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatui:
     f.render_widget(Clear, popup_area);
 
     // Render popup border
-    let title = format!(
-        " Diff Viewer: {} (↑↓ scroll, Esc close) ",
-        app.diff_file_path
-    );
+    let toggle_hint = if app.preview_content.is_some() {
+        match app.diff_view {
+            DiffView::Diff => "p preview, ",
+            DiffView::Preview => "p diff, ",
+        }
+    } else {
+        ""
+    };
+    let syntax = syntax_for_path(&app.diff_file_path);
+    let title = match app.diff_view {
+        DiffView::Diff => format!(
+            " Diff Viewer: {} ({}↑↓ scroll, Esc close) ",
+            app.diff_file_path, toggle_hint
+        ),
+        DiffView::Preview => format!(
+            " Preview: {}{} ({}↑↓ scroll, Esc close) ",
+            app.diff_file_path,
+            syntax.map(|s| format!(" [{}]", s.name)).unwrap_or_default(),
+            toggle_hint
+        ),
+    };
     let popup_block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Magenta));
+        .border_style(Style::default().fg(app.theme.special));
     f.render_widget(popup_block.clone(), popup_area);
 
     // Inner area for content
     let inner_area = popup_block.inner(popup_area);
 
     // Add scrollbar if there is more text than fits
-    let total_lines = app.diff_content.lines().count();
+    let content = app.diff_viewer_content();
+    let total_lines = content.lines().count();
     let visible_lines = inner_area.height as usize;
     if total_lines > visible_lines {
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
@@ -1204,30 +4651,59 @@ fn draw_diff_viewer_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatui:
         );
     }
 
-    // Parse and render diff with syntax highlighting
-    let lines: Vec<_> = app.diff_content.lines().collect();
-    let start_line = app.diff_scroll_offset;
+    let lines: Vec<_> = content.lines().collect();
+    let start_line = app.diff_scroll_offset.min(lines.len());
     let end_line = (start_line + visible_lines).min(lines.len());
 
-    let styled_lines: Vec<Line> = lines[start_line..end_line]
-        .iter()
-        .map(|line| {
-            let style = if line.starts_with('+') && !line.starts_with("+++") {
-                Style::default().fg(Color::Green)
-            } else if line.starts_with('-') && !line.starts_with("---") {
-                Style::default().fg(Color::Red)
-            } else if line.starts_with("@@") {
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD)
-            } else if line.starts_with("diff") || line.starts_with("index") {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            };
-            Line::from(Span::styled(*line, style))
-        })
-        .collect();
+    let styled_lines: Vec<Line> = match app.diff_view {
+        // Preview: line numbers plus syntax highlighting
+        DiffView::Preview => {
+            let number_width = lines.len().to_string().len();
+            lines[start_line..end_line]
+                .iter()
+                .enumerate()
+                .map(|(offset, line)| {
+                    let mut spans = vec![Span::styled(
+                        format!(
+                            "{:>width$} │ ",
+                            start_line + offset + 1,
+                            width = number_width
+                        ),
+                        Style::default().fg(app.theme.muted),
+                    )];
+                    match &syntax {
+                        Some(syntax) => {
+                            spans.extend(highlight_line(line, syntax).into_iter().map(
+                                |(kind, text)| Span::styled(text, token_style(&app.theme, kind)),
+                            ))
+                        }
+                        None => spans.push(Span::raw(*line)),
+                    }
+                    Line::from(spans)
+                })
+                .collect()
+        }
+        // Diff: colorize added/removed lines and hunk headers
+        DiffView::Diff => lines[start_line..end_line]
+            .iter()
+            .map(|line| {
+                let style = if line.starts_with('+') && !line.starts_with("+++") {
+                    Style::default().fg(app.theme.diff_added)
+                } else if line.starts_with('-') && !line.starts_with("---") {
+                    Style::default().fg(app.theme.diff_removed)
+                } else if line.starts_with("@@") {
+                    Style::default()
+                        .fg(app.theme.diff_hunk)
+                        .add_modifier(Modifier::BOLD)
+                } else if line.starts_with("diff") || line.starts_with("index") {
+                    Style::default().fg(app.theme.diff_header)
+                } else {
+                    Style::default()
+                };
+                Line::from(Span::styled(*line, style))
+            })
+            .collect(),
+    };
 
     let paragraph = Paragraph::new(styled_lines)
         .wrap(Wrap { trim: false })
@@ -1235,6 +4711,21 @@ fn draw_diff_viewer_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatui:
     f.render_widget(paragraph, inner_area);
 }
 
+/// Returns the style for a syntax highlighting token.
+fn token_style(theme: &Theme, kind: TokenKind) -> Style {
+    match kind {
+        TokenKind::Plain => Style::default(),
+        TokenKind::Keyword => Style::default()
+            .fg(theme.syntax_keyword)
+            .add_modifier(Modifier::BOLD),
+        TokenKind::String => Style::default().fg(theme.syntax_string),
+        TokenKind::Number => Style::default().fg(theme.syntax_number),
+        TokenKind::Comment => Style::default()
+            .fg(theme.syntax_comment)
+            .add_modifier(Modifier::ITALIC),
+    }
+}
+
 /// Draws a popup displaying git commit output.
 fn draw_commit_output_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatui::layout::Rect) {
     use ratatui::text::{Line, Span};
@@ -1252,7 +4743,7 @@ fn draw_commit_output_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatu
     let block = Block::default()
         .borders(Borders::ALL)
         .title(" Commit Output (Press Esc to close) ")
-        .border_style(Style::default().fg(Color::Green));
+        .border_style(Style::default().fg(app.theme.success));
 
     let inner_area = block.inner(popup_area);
     f.render_widget(block, popup_area);
@@ -1290,6 +4781,568 @@ fn draw_commit_output_popup(f: &mut ratatui::Frame, app: &AppState, area: ratatu
     f.render_widget(paragraph, inner_area);
 }
 
+/// Draws the remote picker popup.
+fn draw_remote_picker_popup(
+    f: &mut ratatui::Frame,
+    theme: &Theme,
+    picker: &RemotePicker,
+    area: ratatui::layout::Rect,
+) {
+    let popup_area = centered_rect(50, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let items: Vec<ListItem> = picker
+        .targets
+        .remotes
+        .iter()
+        .enumerate()
+        .map(|(idx, remote)| {
+            let is_selected = idx == picker.selected;
+            let style = if is_selected {
+                Style::default()
+                    .fg(theme.highlight)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let prefix = if is_selected { "▶ " } else { "  " };
+
+            let mut spans = vec![Span::styled(format!("{}{}", prefix, remote), style)];
+            if picker.targets.push.as_ref() == Some(remote) {
+                spans.push(Span::styled(
+                    " (default)",
+                    Style::default().fg(theme.success),
+                ));
+            }
+            if picker.targets.pr_base.as_ref() == Some(remote) {
+                spans.push(Span::styled(" (PR base)", Style::default().fg(theme.info)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(format!(
+                " Push {} to (Enter to push, Esc to cancel) ",
+                picker.branch
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.popup_border)),
+    );
+    f.render_widget(list, popup_area);
+}
+
+/// Draws the popup for choosing the branches to cherry-pick onto.
+fn draw_branch_picker_popup(
+    f: &mut ratatui::Frame,
+    theme: &Theme,
+    picker: &BranchPicker,
+    area: ratatui::layout::Rect,
+) {
+    let popup_area = centered_rect(50, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let items: Vec<ListItem> = picker
+        .branches
+        .iter()
+        .zip(&picker.chosen)
+        .enumerate()
+        .map(|(idx, (branch, &chosen))| {
+            let is_selected = idx == picker.selected;
+            let style = if is_selected {
+                Style::default()
+                    .fg(theme.highlight)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let prefix = if is_selected { "▶ " } else { "  " };
+            let mark = if chosen { "[x] " } else { "[ ] " };
+            ListItem::new(Line::from(Span::styled(
+                format!("{}{}{}", prefix, mark, branch),
+                style,
+            )))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(format!(
+                " Cherry-pick {} commit(s) onto (Space to choose, Enter to pick, Esc to cancel) ",
+                picker.commits.len()
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.popup_border)),
+    );
+    f.render_widget(list, popup_area);
+}
+
+/// Draws the popup for choosing between generated message candidates.
+fn draw_candidate_picker_popup(
+    f: &mut ratatui::Frame,
+    app: &AppState,
+    picker: &CandidatePicker,
+    area: ratatui::layout::Rect,
+) {
+    let popup_area = centered_rect(70, 50, area);
+    f.render_widget(Clear, popup_area);
+
+    // Preview the candidate as the full commit message
+    let mut lines = Vec::new();
+    if let (Some(group), Some((description, body))) = (
+        app.groups.get(picker.group_index),
+        picker.selected_candidate(),
+    ) {
+        let mut preview = group.clone();
+        set_generated_message(&mut preview, description.clone(), body.as_deref());
+        for (idx, line) in preview.full_message().lines().enumerate() {
+            let style = if idx == 0 {
+                Style::default()
+                    .fg(app.palette.color(group.commit_type))
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            lines.push(Line::from(Span::styled(line.to_string(), style)));
+        }
+    }
+
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(format!(
+                " Candidate {}/{} (←/→ to browse, Enter to accept, Esc to keep current) ",
+                picker.selected + 1,
+                picker.candidates.len()
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.theme.popup_border)),
+    );
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Draws the diff between the current and a regenerated message.
+fn draw_message_comparison_popup(
+    f: &mut ratatui::Frame,
+    theme: &Theme,
+    comparison: &MessageComparison,
+    area: ratatui::layout::Rect,
+) {
+    let popup_area = centered_rect(70, 50, area);
+    f.render_widget(Clear, popup_area);
+
+    let lines: Vec<Line> = comparison
+        .diff
+        .iter()
+        .skip(comparison.scroll)
+        .map(|line| match line {
+            DiffLine::Same(text) => Line::from(Span::raw(format!("  {}", text))),
+            DiffLine::Removed(text) => Line::from(Span::styled(
+                format!("- {}", text),
+                Style::default().fg(theme.diff_removed),
+            )),
+            DiffLine::Added(text) => Line::from(Span::styled(
+                format!("+ {}", text),
+                Style::default().fg(theme.diff_added),
+            )),
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(" New message vs. current (Enter/n to use new, Esc/o to keep current) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.popup_border)),
+    );
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Draws the popup listing groups whose files changed since planning.
+fn draw_stale_groups_popup(
+    f: &mut ratatui::Frame,
+    app: &AppState,
+    stale: &StaleGroups,
+    area: ratatui::layout::Rect,
+) {
+    let popup_area = centered_rect(60, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let mut lines = vec![
+        Line::from("Files changed after the groups were planned, so these messages"),
+        Line::from("may describe outdated changes:"),
+        Line::from(""),
+    ];
+    lines.extend(
+        stale
+            .groups
+            .iter()
+            .filter_map(|&idx| app.groups.get(idx))
+            .map(|group| {
+                Line::from(Span::styled(
+                    format!("  ⟳ {}", group.header()),
+                    Style::default().fg(app.theme.highlight),
+                ))
+            }),
+    );
+    lines.push(Line::from(""));
+    lines.extend(
+        stale
+            .files
+            .iter()
+            .map(|path| Line::from(format!("  • {}", path))),
+    );
+
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(" Stale groups (Enter/r to refresh, Esc to cancel the commit) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.theme.popup_border)),
+    );
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Draws the question before committing all groups or quitting.
+fn draw_confirmation_popup(
+    f: &mut ratatui::Frame,
+    theme: &Theme,
+    confirmation: &Confirmation,
+    area: ratatui::layout::Rect,
+) {
+    let question = confirmation.question();
+    let width = (question.chars().count() as u16 + 12).min(area.width);
+    let popup_area = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(3) / 2,
+        width,
+        height: 3.min(area.height),
+    };
+    f.render_widget(Clear, popup_area);
+
+    let text = Line::from(vec![
+        Span::raw(question),
+        Span::styled(" [y/N]", Style::default().fg(theme.highlight)),
+    ]);
+    let paragraph = Paragraph::new(text).alignment(Alignment::Center).block(
+        Block::default()
+            .title(" Confirm ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.popup_border)),
+    );
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Draws the progress of committing all groups, with each group's state
+/// and git output.
+fn draw_commit_progress_popup(
+    f: &mut ratatui::Frame,
+    app: &AppState,
+    progress: &CommitProgress,
+    area: ratatui::layout::Rect,
+) {
+    let popup_area = centered_rect(80, 70, area);
+    f.render_widget(Clear, popup_area);
+
+    let theme = &app.theme;
+    let current = progress.current();
+    let mut lines = Vec::new();
+    for entry in &progress.entries {
+        let (mark, color) = match &entry.state {
+            CommitState::Committed(Some(commit)) => {
+                (format!("✓ {} ", &commit.to_string()[..7]), theme.success)
+            }
+            CommitState::Committed(None) => ("✓ ".to_string(), theme.success),
+            CommitState::Failed => ("✗ ".to_string(), theme.error),
+            CommitState::Pending if current == Some(entry.index) => {
+                let elapsed = app
+                    .events
+                    .as_ref()
+                    .and_then(EventBus::running)
+                    .map(|(_, elapsed)| elapsed)
+                    .unwrap_or_default();
+                (format!("{} ", spinner_frame(elapsed)), theme.highlight)
+            }
+            CommitState::Pending => ("· ".to_string(), theme.muted),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(mark, Style::default().fg(color)),
+            Span::raw(entry.header.clone()),
+        ]));
+        lines.extend(entry.output.lines().map(|line| {
+            Line::from(Span::styled(
+                format!("    {}", line),
+                Style::default().fg(theme.muted),
+            ))
+        }));
+    }
+
+    let committed = progress
+        .entries
+        .iter()
+        .filter(|e| matches!(e.state, CommitState::Committed(_)))
+        .count();
+    let title = if progress.running {
+        format!(
+            " Committing {}/{} (Esc to cancel) ",
+            committed + 1,
+            progress.entries.len()
+        )
+    } else if progress.awaits_decision() {
+        format!(
+            " Commit failed: Enter continues with {} group(s), Esc aborts ",
+            progress.remaining().len()
+        )
+    } else {
+        format!(
+            " Committed {} of {} group(s) (Enter to close) ",
+            committed,
+            progress.entries.len()
+        )
+    };
+    let border = if progress.failed() > 0 {
+        theme.error
+    } else {
+        theme.popup_border
+    };
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((progress.scroll as u16, 0))
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border)),
+        );
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Draws the latest commits of the branch, each with its type colored and
+/// the files it changed below it.
+fn draw_recent_commits_popup(
+    f: &mut ratatui::Frame,
+    app: &AppState,
+    commits: &[crate::commit_log::RecentCommit],
+    area: ratatui::layout::Rect,
+) {
+    let popup_area = centered_rect(80, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let theme = &app.theme;
+    let muted = Style::default().fg(theme.muted);
+    let mut lines = Vec::new();
+    for commit in commits {
+        let entry = &commit.entry;
+        let header = match entry.commit_type {
+            Some(commit_type) => Span::styled(
+                entry.header.clone(),
+                Style::default().fg(app.palette.color(commit_type)),
+            ),
+            None => Span::styled(entry.header.clone(), muted),
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!("{} ", entry.icon())),
+            Span::styled(format!("{} ", entry.short_id), muted),
+            header,
+            Span::styled(format!("  — {}, {}", entry.author, entry.date), muted),
+        ]));
+        lines.extend(
+            commit
+                .files
+                .iter()
+                .map(|file| Line::from(Span::styled(format!("    {}", file), muted))),
+        );
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled("No commits yet", muted)));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((app.recent_commits_scroll as u16, 0))
+        .block(
+            Block::default()
+                .title(format!(
+                    " Last {} commit(s) (l or Esc to close) ",
+                    commits.len()
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.popup_border)),
+        );
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Draws the input popup for feedback on the selected group's message.
+fn draw_feedback_popup(
+    f: &mut ratatui::Frame,
+    theme: &Theme,
+    input: &str,
+    area: ratatui::layout::Rect,
+) {
+    let width = (area.width as f32 * 0.6) as u16;
+    let popup_area = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(5) / 2,
+        width,
+        height: 5.min(area.height),
+    };
+    f.render_widget(Clear, popup_area);
+
+    let text = vec![
+        Line::from(Span::styled(
+            "e.g. \"shorter\", \"mention the migration\"",
+            Style::default().fg(theme.muted),
+        )),
+        Line::from(vec![
+            Span::raw(input),
+            Span::styled("█", Style::default().fg(theme.highlight)),
+        ]),
+    ];
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(" Refine message: feedback (Enter to send, Esc to cancel) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.popup_border)),
+    );
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Draws the scratchpad for the notes of the selected group, showing the
+/// end of the text as it grows.
+fn draw_notes_popup(
+    f: &mut ratatui::Frame,
+    theme: &Theme,
+    input: &str,
+    area: ratatui::layout::Rect,
+) {
+    let width = (area.width as f32 * 0.7) as u16;
+    let height = ((area.height as f32 * 0.5) as u16).max(6).min(area.height);
+    let popup_area = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    };
+    f.render_widget(Clear, popup_area);
+
+    let hint = "Why the change was made: paste design notes or an issue comment, or enter @path to load a file. Empty removes the notes.";
+    let inner_width = usize::from(width.saturating_sub(2)).max(1);
+    // Rows a line takes when wrapped, with room for the cursor
+    let rows = |line: &str| line.chars().count() / inner_width + 1;
+    let mut text = vec![Line::from(Span::styled(
+        hint,
+        Style::default().fg(theme.muted),
+    ))];
+
+    // Show as many lines from the end as fit below the hint
+    let input_lines: Vec<&str> = input.split('\n').collect();
+    let mut free = usize::from(height.saturating_sub(2)).saturating_sub(rows(hint));
+    let mut start = input_lines.len();
+    while start > 0 && rows(input_lines[start - 1]) <= free {
+        free -= rows(input_lines[start - 1]);
+        start -= 1;
+    }
+    let start = start.min(input_lines.len() - 1);
+    let last = input_lines.len() - 1;
+    for (idx, line) in input_lines.iter().enumerate().skip(start) {
+        let mut spans = vec![Span::raw(line.to_string())];
+        if idx == last {
+            spans.push(Span::styled("█", Style::default().fg(theme.highlight)));
+        }
+        text.push(Line::from(spans));
+    }
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(" Notes for the AI (Enter to save, Esc to cancel) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.popup_border)),
+    );
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Draws the input popup for the name of a new branch.
+fn draw_branch_input_popup(
+    f: &mut ratatui::Frame,
+    theme: &Theme,
+    input: &str,
+    area: ratatui::layout::Rect,
+) {
+    let width = (area.width as f32 * 0.6) as u16;
+    let popup_area = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(5) / 2,
+        width,
+        height: 5.min(area.height),
+    };
+    f.render_widget(Clear, popup_area);
+
+    let text = vec![
+        Line::from(Span::styled(
+            "Uncommitted changes move to the new branch",
+            Style::default().fg(theme.muted),
+        )),
+        Line::from(vec![
+            Span::raw(input),
+            Span::styled("█", Style::default().fg(theme.highlight)),
+        ]),
+    ];
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(" New branch (Enter to create and switch, Esc to cancel) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.popup_border)),
+    );
+    f.render_widget(paragraph, popup_area);
+}
+
+/// Draws the input popup for renaming a scope in all groups.
+fn draw_scope_rename_popup(
+    f: &mut ratatui::Frame,
+    app: &AppState,
+    rename: &ScopeRename,
+    area: ratatui::layout::Rect,
+) {
+    let theme = &app.theme;
+    let width = (area.width as f32 * 0.6) as u16;
+    let popup_area = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(6) / 2,
+        width,
+        height: 6.min(area.height),
+    };
+    f.render_widget(Clear, popup_area);
+
+    let groups = app
+        .groups
+        .iter()
+        .filter(|g| !g.is_committed() && g.scope.as_deref() == Some(rename.from.as_str()))
+        .count();
+    let mark = if rename.record { "[x]" } else { "[ ]" };
+    let text = vec![
+        Line::from(Span::styled(
+            format!("'{}' is used by {} group(s)", rename.from, groups),
+            Style::default().fg(theme.muted),
+        )),
+        Line::from(vec![
+            Span::raw(rename.input.as_str()),
+            Span::styled("█", Style::default().fg(theme.highlight)),
+        ]),
+        Line::from(Span::styled(
+            format!(
+                "{} Record as alias in {} (Tab)",
+                mark,
+                crate::scope::ALIAS_FILE
+            ),
+            Style::default().fg(theme.muted),
+        )),
+    ];
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(" Rename scope (Enter to rename, Esc to cancel) ")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.popup_border)),
+    );
+    f.render_widget(paragraph, popup_area);
+}
+
 /// Creates a centered rectangle with the given percentage of width and height.
 fn centered_rect(
     percent_x: u16,
@@ -1314,3 +5367,149 @@ fn centered_rect(
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ChangedFile;
+    use ratatui::backend::TestBackend;
+
+    /// Provider that only has a name and a model, for the header.
+    struct NamedProvider;
+
+    impl AiProvider for NamedProvider {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn model_name(&self) -> Option<&str> {
+            Some("model-x")
+        }
+
+        fn complete(&self, _prompt: &str) -> Result<String> {
+            unreachable!("the header sends no requests")
+        }
+    }
+
+    /// Renders the header line at `width` and returns its text.
+    fn render_header(width: u16) -> String {
+        let mut app = AppState::new(Vec::new());
+        app.session.repo_name = "repo".to_string();
+        app.session.branch = "main".to_string();
+        app.session.branch_status.head_id = Some("abc1234".to_string());
+        app.session.branch_status.head_summary = "fix: previous change".to_string();
+        app.ai_provider = Some(Arc::new(NamedProvider));
+
+        let mut terminal = Terminal::new(TestBackend::new(width, 1)).unwrap();
+        terminal
+            .draw(|f| draw_header_line(f, &app, f.area()))
+            .unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_header_line_shows_all_details_when_wide() {
+        let line = render_header(120);
+        assert!(line.contains("main"));
+        assert!(line.contains("⎇ abc1234 fix: previous change"));
+        assert!(line.contains("fake (model-x)"));
+        assert!(line.contains("⏱"));
+    }
+
+    #[test]
+    fn test_header_line_drops_head_and_model_when_narrow() {
+        let line = render_header(50);
+        assert!(line.contains("repo"));
+        assert!(line.contains("main"));
+        assert!(line.contains("fake"));
+        assert!(!line.contains("abc1234"));
+        assert!(!line.contains("model-x"));
+        // The timer keeps its own space instead of covering the left part
+        assert!(line.contains("⏱"));
+    }
+
+    /// App with one `feat(api)` group whose description was generated,
+    /// which warns before committing.
+    fn app_with_warning() -> AppState {
+        let mut group = ChangeGroup::new(
+            CommitType::Feat,
+            Some("api".to_string()),
+            vec![ChangedFile::new(
+                "src/api.rs".to_string(),
+                git2::Status::WT_MODIFIED,
+            )],
+            None,
+            "update api".to_string(),
+            vec![],
+        );
+        group.description_generated = true;
+        AppState::new(vec![group])
+    }
+
+    #[test]
+    fn test_editing_a_message_shows_ignored_warnings_again() {
+        let dir = tempfile::TempDir::new().unwrap();
+        git2::Repository::init(dir.path()).unwrap();
+        let mut app = app_with_warning();
+
+        assert!(!check_before_commit(&mut app, dir.path(), &[0]).unwrap());
+        assert!(app.status_message.contains("Commit again to ignore"));
+        app.groups[0].description = "add users endpoint".to_string();
+        assert!(!check_before_commit(&mut app, dir.path(), &[0]).unwrap());
+        // Unchanged since the warnings were shown, so they are ignored
+        assert!(check_before_commit(&mut app, dir.path(), &[0]).unwrap());
+    }
+
+    #[test]
+    fn test_ignored_warnings_do_not_skip_the_scope_policy() {
+        let dir = tempfile::TempDir::new().unwrap();
+        git2::Repository::init(dir.path()).unwrap();
+        let mut app = app_with_warning();
+        app.config.scope.allowed = vec!["api".to_string()];
+
+        assert!(!check_before_commit(&mut app, dir.path(), &[0]).unwrap());
+        app.groups[0].scope = Some("web".to_string());
+        assert!(!check_before_commit(&mut app, dir.path(), &[0]).unwrap());
+        assert!(app.status_message.contains("Scope policy violated"));
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[test]
+    fn test_ignored_warnings_do_not_skip_plugin_validation() {
+        use crate::config::PluginConfig;
+        use crate::plugin::{PluginSet, WasmPlugin};
+
+        let json = r#"{"errors": ["needs a ticket"]}"#;
+        let bytes = wat::parse_str(format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 1024) "{}")
+                (func (export "alloc") (param i32) (result i32) (i32.const 4096))
+                (func (export "validate") (param i32 i32) (result i64)
+                    (i64.or (i64.shl (i64.const 1024) (i64.const 32)) (i64.const {}))))"#,
+            json.replace('"', "\\\""),
+            json.len()
+        ))
+        .unwrap();
+        let plugin =
+            WasmPlugin::from_bytes("ticket.wasm", &bytes, &PluginConfig::default()).unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        git2::Repository::init(dir.path()).unwrap();
+        let mut app = app_with_warning();
+        app.plugins = PluginSet::new(vec![plugin]);
+        app.commit_warnings_shown_for = Some((vec![0], group_messages(&app, &[0])));
+
+        assert!(!check_before_commit(&mut app, dir.path(), &[0]).unwrap());
+        assert!(app.status_message.contains("needs a ticket"));
+    }
+}
@@ -0,0 +1,201 @@
+//! Opt-in check for a newer release.
+//!
+//! With `[update] check = true`, startup compares the running version with
+//! the latest GitHub release and the header line shows a hint when a newer
+//! one exists. The answer is cached for `interval_days` (a week by default),
+//! so GitHub is asked at most once per interval. Nothing but the request
+//! for the latest release is sent: no identifiers, usage data, or version.
+//!
+//! The check is compiled only with the `update-check` cargo feature, which
+//! is enabled by default; packagers who update through their own channels
+//! can build without it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{NetworkSettings, UpdateConfig};
+use crate::network;
+
+/// GitHub API endpoint of the latest release.
+pub const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/jfheinrich-eu/commit-wizard/releases/latest";
+
+/// Time allowed for asking GitHub, so a slow network barely delays startup.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Release response of the GitHub API
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// Latest release as last seen, stored in the cache file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedRelease {
+    /// Seconds since the Unix epoch when GitHub was asked
+    pub checked_at: u64,
+    /// Version of the latest release, without a leading `v`
+    pub version: String,
+}
+
+/// Parses a version such as `v1.2.3` or `1.2.3-beta.1` into its numeric
+/// parts; pre-release and build suffixes are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::update::parse_version;
+///
+/// assert_eq!(parse_version("v1.2.3"), Some((1, 2, 3)));
+/// assert_eq!(parse_version("0.10.0-rc.1"), Some((0, 10, 0)));
+/// assert_eq!(parse_version("latest"), None);
+/// ```
+pub fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+/// Returns the hint shown when `latest` is newer than `current`.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::update::update_hint;
+///
+/// assert_eq!(
+///     update_hint("0.1.0", "v0.2.0").as_deref(),
+///     Some("commit-wizard 0.2.0 is available (running 0.1.0)")
+/// );
+/// assert_eq!(update_hint("0.2.0", "v0.2.0"), None);
+/// ```
+pub fn update_hint(current: &str, latest: &str) -> Option<String> {
+    let newer = parse_version(latest)? > parse_version(current)?;
+    newer.then(|| {
+        format!(
+            "commit-wizard {} is available (running {})",
+            latest.trim().trim_start_matches('v'),
+            current
+        )
+    })
+}
+
+/// Asks `url` for the latest release, through the proxy of `network`.
+///
+/// # Errors
+///
+/// Returns an error if the request fails or the response has no tag.
+pub fn fetch_latest_version(
+    url: &str,
+    timeout: Duration,
+    network: &NetworkSettings,
+) -> Result<String> {
+    let client = network::client_builder(network)?
+        .timeout(timeout)
+        .user_agent("commit-wizard")
+        .build()
+        .context("Failed to create HTTP client")?;
+    let response = client
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .map_err(|e| network::explain_send_error(e, network))
+        .context("Failed to ask GitHub for the latest release")?;
+    if !response.status().is_success() {
+        return Err(crate::retry::ApiError::from_response("GitHub API", response).into());
+    }
+    let release: Release = response
+        .json()
+        .context("Failed to parse the latest release")?;
+    Ok(release.tag_name.trim_start_matches('v').to_string())
+}
+
+/// Returns the latest release version, from the cache at `cache` if it is
+/// younger than `max_age`, otherwise from `url` through the proxy of
+/// `network`.
+///
+/// A fresh answer is written back to the cache; failing to write it is not
+/// an error.
+///
+/// # Errors
+///
+/// Returns an error if the cache is stale and GitHub cannot be asked.
+pub fn latest_version(
+    url: &str,
+    cache: Option<&Path>,
+    max_age: Duration,
+    now: SystemTime,
+    network: &NetworkSettings,
+) -> Result<String> {
+    let now_secs = now
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let cached: Option<CachedRelease> = cache
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok());
+    if let Some(cached) = cached {
+        if now_secs.saturating_sub(cached.checked_at) < max_age.as_secs() {
+            log::debug!("Latest release {} (cached)", cached.version);
+            return Ok(cached.version);
+        }
+    }
+
+    let version = fetch_latest_version(url, REQUEST_TIMEOUT, network)?;
+    log::debug!("Latest release {}", version);
+    if let Some(path) = cache {
+        let entry = CachedRelease {
+            checked_at: now_secs,
+            version: version.clone(),
+        };
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(path, serde_json::to_string(&entry).unwrap_or_default()));
+        if let Err(e) = written {
+            log::warn!("Failed to cache the latest release: {}", e);
+        }
+    }
+    Ok(version)
+}
+
+/// Returns the cache file of the latest release.
+pub fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("commit-wizard").join("latest-release.json"))
+}
+
+/// Runs the configured update check, asking GitHub through the proxy of
+/// `network`.
+///
+/// # Returns
+///
+/// The hint to show, or [`None`] if the check is disabled, the running
+/// version is current, or the check failed (failures are only logged).
+pub fn check_for_update(config: &UpdateConfig, network: &NetworkSettings) -> Option<String> {
+    if !config.check {
+        return None;
+    }
+    let url = config.url.as_deref().unwrap_or(LATEST_RELEASE_URL);
+    let max_age = Duration::from_secs(config.interval_days.saturating_mul(24 * 60 * 60));
+    match latest_version(
+        url,
+        cache_path().as_deref(),
+        max_age,
+        SystemTime::now(),
+        network,
+    ) {
+        Ok(latest) => update_hint(env!("CARGO_PKG_VERSION"), &latest),
+        Err(e) => {
+            log::debug!("Update check failed: {:#}", e);
+            None
+        }
+    }
+}
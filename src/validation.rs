@@ -0,0 +1,530 @@
+//! Validation of commit groups against configured policies.
+//!
+//! Currently this covers the conventional commit scope policy: which commit
+//! types require or forbid a scope, which scopes are allowed, and which
+//! format a scope must have. Violations block the commit and come with a
+//! suggested fix where one can be derived from the scope itself (the closest
+//! allowed scope) or the group's file paths.
+//!
+//! Before any of that, scopes from AI grouping, the editor, or the
+//! configuration are normalized to the configured [`ScopeCasing`], so a
+//! scope like `User Auth ` ends up as `user-auth` rather than in a header
+//! such as `feat(User Auth ): ...`.
+//!
+//! With `[message] charset = "ascii"`, headers are transliterated to ASCII
+//! before committing, for tooling that cannot handle anything else:
+//! typographic quotes and dashes become their plain forms and umlauts and
+//! accented letters are spelled out. Bodies are left as they are.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::config::ScopeConfig;
+use crate::inference::file_scope;
+use crate::types::{ChangeGroup, CommitType};
+
+/// What happens to a group whose message has no description, e.g. because
+/// the AI response was empty.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmptyDescription {
+    /// Generate one from the files, like heuristic grouping does, and warn
+    /// before committing so it gets reviewed
+    #[default]
+    Fallback,
+    /// Keep it empty; committing is blocked until one is written
+    Block,
+}
+
+/// Characters allowed in commit headers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HeaderCharset {
+    /// Any character
+    #[default]
+    Unicode,
+    /// ASCII only: known characters are transliterated, headers with
+    /// others cannot be committed
+    Ascii,
+}
+
+/// Casing scopes are normalized to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScopeCasing {
+    /// Lowercase words joined by hyphens: `user-auth`
+    #[default]
+    Kebab,
+    /// Lowercase, keeping `_`, `.`, and `/`: `user_auth`
+    Lower,
+    /// As written, only cleaned of whitespace and invalid characters
+    Preserve,
+}
+
+impl ScopeCasing {
+    /// Normalizes a scope to this casing.
+    ///
+    /// Surrounding whitespace is removed; runs of whitespace and of
+    /// characters not allowed in a scope (anything but letters, digits,
+    /// `-`, and with [`ScopeCasing::Lower`] or [`ScopeCasing::Preserve`]
+    /// also `_`, `.`, and `/`) become a single `-`. Kebab-case also splits
+    /// camelCase words.
+    ///
+    /// # Returns
+    ///
+    /// The scope, or [`None`] if nothing valid is left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::validation::ScopeCasing;
+    ///
+    /// assert_eq!(ScopeCasing::Kebab.normalize("User Auth ").as_deref(), Some("user-auth"));
+    /// assert_eq!(ScopeCasing::Kebab.normalize("userAuth").as_deref(), Some("user-auth"));
+    /// assert_eq!(ScopeCasing::Lower.normalize(" Core_API").as_deref(), Some("core_api"));
+    /// assert_eq!(ScopeCasing::Preserve.normalize("Core API)").as_deref(), Some("Core-API"));
+    /// assert_eq!(ScopeCasing::Kebab.normalize(" () "), None);
+    /// ```
+    pub fn normalize(self, scope: &str) -> Option<String> {
+        let mut normalized = String::with_capacity(scope.len());
+        let mut previous: Option<char> = None;
+        for c in scope.trim().chars() {
+            let allowed = c.is_alphanumeric()
+                || c == '-'
+                || (self != ScopeCasing::Kebab && matches!(c, '_' | '.' | '/'));
+            if !allowed {
+                if !normalized.is_empty() && !normalized.ends_with('-') {
+                    normalized.push('-');
+                }
+            } else if self == ScopeCasing::Preserve {
+                normalized.push(c);
+            } else {
+                // "userAuth" is two words in kebab-case
+                let word_start = self == ScopeCasing::Kebab
+                    && c.is_uppercase()
+                    && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit());
+                if word_start && !normalized.ends_with('-') {
+                    normalized.push('-');
+                }
+                normalized.extend(c.to_lowercase());
+            }
+            previous = Some(c);
+        }
+
+        let mut collapsed = String::with_capacity(normalized.len());
+        for c in normalized.chars() {
+            if !(c == '-' && collapsed.ends_with('-')) {
+                collapsed.push(c);
+            }
+        }
+        let collapsed = collapsed.trim_matches('-');
+        (!collapsed.is_empty()).then(|| collapsed.to_string())
+    }
+}
+
+/// Compiled scope policy.
+#[derive(Debug, Clone, Default)]
+pub struct ScopePolicy {
+    required_for: Vec<CommitType>,
+    forbidden_for: Vec<CommitType>,
+    allowed: Vec<String>,
+    pattern: Option<Regex>,
+    casing: ScopeCasing,
+}
+
+/// The kind of scope policy violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScopeViolationKind {
+    /// The commit type requires a scope but none is set
+    Missing,
+    /// The commit type must not have a scope
+    Forbidden,
+    /// The scope is not one of the allowed scopes
+    NotAllowed {
+        /// The allowed scopes
+        allowed: Vec<String>,
+    },
+    /// The scope does not match the configured pattern
+    InvalidFormat {
+        /// The configured pattern
+        pattern: String,
+    },
+}
+
+/// A suggested fix for a scope policy violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScopeFix {
+    /// Set the scope to the given value
+    Set(String),
+    /// Remove the scope
+    Remove,
+}
+
+/// A scope policy violation for a single commit group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeViolation {
+    /// Commit type of the group
+    pub commit_type: CommitType,
+    /// Current scope of the group
+    pub scope: Option<String>,
+    /// What is wrong with the scope
+    pub kind: ScopeViolationKind,
+    /// Suggested fix, if one could be derived
+    pub suggestion: Option<ScopeFix>,
+}
+
+impl fmt::Display for ScopeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ctype = self.commit_type.as_str();
+        let scope = self.scope.as_deref().unwrap_or_default();
+        match &self.kind {
+            ScopeViolationKind::Missing => write!(f, "'{}' commits require a scope", ctype),
+            ScopeViolationKind::Forbidden => {
+                write!(f, "'{}' commits must not have a scope ('{}')", ctype, scope)
+            }
+            ScopeViolationKind::NotAllowed { allowed } => write!(
+                f,
+                "scope '{}' is not one of the allowed scopes ({})",
+                scope,
+                allowed.join(", ")
+            ),
+            ScopeViolationKind::InvalidFormat { pattern } => {
+                write!(f, "scope '{}' does not match pattern {}", scope, pattern)
+            }
+        }
+    }
+}
+
+impl ScopePolicy {
+    /// Compiles the scope policy from configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a commit type is unknown or the pattern is not a
+    /// valid regular expression.
+    pub fn from_config(config: &ScopeConfig) -> Result<Self> {
+        let pattern = config
+            .pattern
+            .as_deref()
+            .map(|p| Regex::new(p).with_context(|| format!("Invalid scope pattern '{}'", p)))
+            .transpose()?;
+
+        Ok(Self {
+            required_for: parse_commit_types(&config.required_for)?,
+            forbidden_for: parse_commit_types(&config.forbidden_for)?,
+            allowed: config
+                .allowed
+                .iter()
+                .filter_map(|scope| config.casing.normalize(scope))
+                .collect(),
+            pattern,
+            casing: config.casing,
+        })
+    }
+
+    /// Returns whether the policy has no rules.
+    pub fn is_empty(&self) -> bool {
+        self.required_for.is_empty()
+            && self.forbidden_for.is_empty()
+            && self.allowed.is_empty()
+            && self.pattern.is_none()
+    }
+
+    /// Validates the scope of a commit group.
+    ///
+    /// # Returns
+    ///
+    /// The violation, or [`None`] if the scope satisfies the policy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::config::ScopeConfig;
+    /// use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+    /// use commit_wizard::validation::{ScopeFix, ScopePolicy};
+    /// use git2::Status;
+    ///
+    /// let policy = ScopePolicy::from_config(&ScopeConfig {
+    ///     required_for: vec!["feat".into()],
+    ///     ..Default::default()
+    /// })
+    /// .unwrap();
+    /// let file = ChangedFile::new("api/users.rs".into(), Status::INDEX_NEW);
+    /// let group = ChangeGroup::new(CommitType::Feat, None, vec![file], None, "add users".into(), vec![]);
+    ///
+    /// let violation = policy.validate(&group).unwrap();
+    /// assert_eq!(violation.suggestion, Some(ScopeFix::Set("api".into())));
+    /// ```
+    pub fn validate(&self, group: &ChangeGroup) -> Option<ScopeViolation> {
+        let scope = group.scope.as_deref().filter(|s| !s.trim().is_empty());
+
+        let kind = match scope {
+            None if self.required_for.contains(&group.commit_type) => ScopeViolationKind::Missing,
+            None => return None,
+            Some(_) if self.forbidden_for.contains(&group.commit_type) => {
+                ScopeViolationKind::Forbidden
+            }
+            Some(scope) if !self.is_allowed(scope) => ScopeViolationKind::NotAllowed {
+                allowed: self.allowed.clone(),
+            },
+            Some(scope) => match &self.pattern {
+                Some(pattern) if !pattern.is_match(scope) => ScopeViolationKind::InvalidFormat {
+                    pattern: pattern.as_str().to_string(),
+                },
+                _ => return None,
+            },
+        };
+
+        let suggestion = match kind {
+            ScopeViolationKind::Forbidden => Some(ScopeFix::Remove),
+            ScopeViolationKind::NotAllowed { .. } => scope
+                .and_then(|scope| self.closest_allowed(scope))
+                .or_else(|| self.suggest_scope(group))
+                .map(ScopeFix::Set),
+            _ => self.suggest_scope(group).map(ScopeFix::Set),
+        };
+
+        Some(ScopeViolation {
+            commit_type: group.commit_type,
+            scope: scope.map(str::to_string),
+            kind,
+            suggestion,
+        })
+    }
+
+    /// Returns whether `scope` is allowed: there is no list of allowed
+    /// scopes, or it is on the list.
+    pub fn is_allowed(&self, scope: &str) -> bool {
+        self.allowed.is_empty() || self.allowed.iter().any(|allowed| allowed == scope)
+    }
+
+    /// Returns the allowed scope closest to a scope that is not allowed,
+    /// e.g. `auth` for `atuh`.
+    ///
+    /// Scopes are compared by edit distance after normalizing the casing;
+    /// an allowed scope that starts with the scope, or the other way round,
+    /// also counts as close. Scopes needing more edits than a third of
+    /// their length are not suggested.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use commit_wizard::config::ScopeConfig;
+    /// use commit_wizard::validation::ScopePolicy;
+    ///
+    /// let policy = ScopePolicy::from_config(&ScopeConfig {
+    ///     allowed: vec!["auth".into(), "billing".into(), "ui".into()],
+    ///     ..Default::default()
+    /// })
+    /// .unwrap();
+    /// assert_eq!(policy.closest_allowed("atuh").as_deref(), Some("auth"));
+    /// assert_eq!(policy.closest_allowed("Billing").as_deref(), Some("billing"));
+    /// assert_eq!(policy.closest_allowed("authentication").as_deref(), Some("auth"));
+    /// assert_eq!(policy.closest_allowed("database"), None);
+    /// ```
+    pub fn closest_allowed(&self, scope: &str) -> Option<String> {
+        let scope = self.casing.normalize(scope)?;
+        self.allowed
+            .iter()
+            .filter_map(|allowed| {
+                let distance = edit_distance(&scope, allowed);
+                let limit = (scope.chars().count().max(allowed.chars().count()) / 3).max(1);
+                let prefix = scope.starts_with(allowed.as_str()) || allowed.starts_with(&scope);
+                (distance <= limit || prefix).then_some((distance, allowed))
+            })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, allowed)| allowed.clone())
+    }
+
+    /// Derives a scope from the group's file paths that satisfies the pattern.
+    ///
+    /// The most common allowed file scope (see [`file_scope`]) is used,
+    /// normalized to the configured casing.
+    pub fn suggest_scope(&self, group: &ChangeGroup) -> Option<String> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        for file in &group.files {
+            if let Some(scope) = file_scope(file).and_then(|s| self.casing.normalize(&s)) {
+                *counts.entry(scope).or_default() += 1;
+            }
+        }
+
+        let mut candidates: Vec<_> = counts.into_iter().collect();
+        // Most common first; ties keep alphabetical order
+        candidates.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        candidates
+            .into_iter()
+            .map(|(scope, _)| scope)
+            .filter(|scope| self.is_allowed(scope))
+            .find(|scope| self.pattern.as_ref().map_or(true, |p| p.is_match(scope)))
+    }
+}
+
+/// Applies a suggested fix to a group.
+pub fn apply_scope_fix(group: &mut ChangeGroup, fix: &ScopeFix) {
+    group.scope = match fix {
+        ScopeFix::Set(scope) => Some(scope.clone()),
+        ScopeFix::Remove => None,
+    };
+}
+
+/// Transliterates `text` to ASCII where a replacement is known.
+///
+/// Typographic quotes, dashes, spaces and the ellipsis become their plain
+/// forms, German umlauts and `ß` are spelled out (`ä` → `ae`), and other
+/// accented Latin letters lose their accent. Characters without a known
+/// replacement, such as emoji, are kept.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::validation::transliterate_ascii;
+///
+/// assert_eq!(transliterate_ascii("fix “Größe” – naïve…"), "fix \"Groesse\" - naive...");
+/// assert_eq!(transliterate_ascii("add ✨"), "add ✨");
+/// ```
+pub fn transliterate_ascii(text: &str) -> String {
+    let mut ascii = String::with_capacity(text.len());
+    for c in text.chars() {
+        let replacement = match c {
+            '‘' | '’' | '‚' | '‛' | '′' => "'",
+            '“' | '”' | '„' | '‟' | '″' | '«' | '»' => "\"",
+            '‹' => "<",
+            '›' => ">",
+            '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => "-",
+            '…' => "...",
+            '\u{a0}' | '\u{2002}'..='\u{200a}' | '\u{202f}' => " ",
+            '→' => "->",
+            '←' => "<-",
+            '×' => "x",
+            '•' | '·' => "*",
+            'ä' => "ae",
+            'ö' => "oe",
+            'ü' => "ue",
+            'Ä' => "Ae",
+            'Ö' => "Oe",
+            'Ü' => "Ue",
+            'ß' => "ss",
+            'æ' => "ae",
+            'Æ' => "AE",
+            'œ' => "oe",
+            'Œ' => "OE",
+            'à' | 'á' | 'â' | 'ã' | 'å' | 'ā' | 'ą' => "a",
+            'À' | 'Á' | 'Â' | 'Ã' | 'Å' | 'Ā' | 'Ą' => "A",
+            'ç' | 'ć' | 'č' => "c",
+            'Ç' | 'Ć' | 'Č' => "C",
+            'ď' | 'đ' => "d",
+            'Ď' | 'Đ' => "D",
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => "e",
+            'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ę' | 'Ě' => "E",
+            'ì' | 'í' | 'î' | 'ï' | 'ī' => "i",
+            'Ì' | 'Í' | 'Î' | 'Ï' | 'Ī' => "I",
+            'ł' => "l",
+            'Ł' => "L",
+            'ñ' | 'ń' | 'ň' => "n",
+            'Ñ' | 'Ń' | 'Ň' => "N",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ø' | 'ō' | 'ő' => "o",
+            'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ø' | 'Ō' | 'Ő' => "O",
+            'ř' => "r",
+            'Ř' => "R",
+            'ś' | 'š' | 'ş' => "s",
+            'Ś' | 'Š' | 'Ş' => "S",
+            'ť' | 'ţ' => "t",
+            'Ť' | 'Ţ' => "T",
+            'ù' | 'ú' | 'û' | 'ū' | 'ů' | 'ű' => "u",
+            'Ù' | 'Ú' | 'Û' | 'Ū' | 'Ů' | 'Ű' => "U",
+            'ý' | 'ÿ' => "y",
+            'Ý' | 'Ÿ' => "Y",
+            'ź' | 'ż' | 'ž' => "z",
+            'Ź' | 'Ż' | 'Ž' => "Z",
+            _ => {
+                ascii.push(c);
+                continue;
+            }
+        };
+        ascii.push_str(replacement);
+    }
+    ascii
+}
+
+/// Transliterates the scope and description of `group` to ASCII (see
+/// [`transliterate_ascii`]), leaving the body as it is.
+///
+/// # Returns
+///
+/// The characters left in the header that have no ASCII replacement, in
+/// order and without duplicates; empty if the header is ASCII now.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::types::{ChangeGroup, CommitType};
+/// use commit_wizard::validation::transliterate_header;
+///
+/// let mut group = ChangeGroup::new(
+///     CommitType::Fix,
+///     None,
+///     vec![],
+///     None,
+///     "handle “Müller” — again".to_string(),
+///     vec!["Keep “quotes” here".to_string()],
+/// );
+/// assert!(transliterate_header(&mut group).is_empty());
+/// assert_eq!(group.header(), "fix: handle \"Mueller\" - again");
+/// assert_eq!(group.body_lines[0], "Keep “quotes” here");
+/// ```
+pub fn transliterate_header(group: &mut ChangeGroup) -> Vec<char> {
+    group.description = transliterate_ascii(&group.description);
+    group.scope = group.scope.as_deref().map(transliterate_ascii);
+    let mut left: Vec<char> = Vec::new();
+    for c in group.header().chars().filter(|c| !c.is_ascii()) {
+        if !left.contains(&c) {
+            left.push(c);
+        }
+    }
+    left
+}
+
+/// Returns the number of single-character insertions, deletions,
+/// substitutions, and swaps of neighbouring characters turning `a` into `b`
+/// (optimal string alignment distance), so the typo `atuh` is one edit away
+/// from `auth`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // rows[i][j] is the distance between the first i chars of a and j of b
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    rows[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Parses commit type names from configuration.
+fn parse_commit_types(names: &[String]) -> Result<Vec<CommitType>> {
+    names
+        .iter()
+        .map(
+            |name| match CommitType::all().iter().find(|t| t.as_str() == name.trim()) {
+                Some(t) => Ok(*t),
+                None => bail!("Unknown commit type '{}' in scope policy", name),
+            },
+        )
+        .collect()
+}
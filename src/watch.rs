@@ -0,0 +1,69 @@
+//! Notifications about changes in the working tree.
+//!
+//! With `--watch`, a [`WorktreeWatcher`] watches the working tree for as
+//! long as the TUI runs and raises a flag whenever a file outside the git
+//! directory changes. The TUI polls the flag, checks with git whether the
+//! plan is outdated (see [`crate::replan::plan_outdated`]), and offers to
+//! refresh the groups.
+//!
+//! The watcher is compiled only with the `watch` cargo feature, which is
+//! enabled by default.
+
+use std::path::{Component, Path};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Watches a working tree until dropped.
+pub struct WorktreeWatcher {
+    // Kept alive for its notifications
+    _watcher: RecommendedWatcher,
+    changed: Arc<AtomicBool>,
+}
+
+impl WorktreeWatcher {
+    /// Starts watching `workdir` and everything below it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the platform's file notifications cannot be set
+    /// up, e.g. when the inotify watch limit is reached.
+    pub fn start(workdir: &Path) -> Result<Self> {
+        let changed = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&changed);
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) if is_worktree_change(&event) => flag.store(true, Ordering::SeqCst),
+                Ok(_) => {}
+                Err(e) => log::debug!("File watcher error: {}", e),
+            })
+            .context("Failed to set up file notifications")?;
+        watcher
+            .watch(workdir, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", workdir.display()))?;
+        log::info!("Watching {} for changes", workdir.display());
+        Ok(Self {
+            _watcher: watcher,
+            changed,
+        })
+    }
+
+    /// Returns the flag raised when a file changes; whoever handles the
+    /// change clears it.
+    pub fn events(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.changed)
+    }
+}
+
+/// Returns whether `event` may change what git reports for the working
+/// tree: reads and changes inside `.git` (e.g. our own commits) do not.
+fn is_worktree_change(event: &Event) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event.paths.iter().any(|path| {
+            !path
+                .components()
+                .any(|c| c == Component::Normal(".git".as_ref()))
+        })
+}
@@ -0,0 +1,277 @@
+//! Whitespace and line-ending checks for introduced changes.
+//!
+//! This module mirrors git's whitespace checks for the lines a group adds:
+//! trailing whitespace, mixed line endings, and missing final newlines.
+//! Problems are reported as [`WhitespaceIssue`]s and can be fixed in place
+//! with [`fix_issues`] before the files are staged and committed.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use git2::Repository;
+
+use crate::types::ChangedFile;
+
+/// The kind of whitespace problem found in a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WhitespaceIssueKind {
+    /// An added line ends with spaces or tabs
+    TrailingWhitespace,
+    /// Added lines use both LF and CRLF line endings
+    MixedLineEndings,
+    /// The new version of the file does not end with a newline
+    MissingFinalNewline,
+}
+
+impl WhitespaceIssueKind {
+    /// Returns a short human-readable label.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::TrailingWhitespace => "trailing whitespace",
+            Self::MixedLineEndings => "mixed line endings",
+            Self::MissingFinalNewline => "no newline at end of file",
+        }
+    }
+}
+
+/// A single whitespace problem in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhitespaceIssue {
+    /// Path of the affected file relative to the repository root
+    pub path: String,
+    /// 1-based line number in the new file, if the issue is line-specific
+    pub line: Option<usize>,
+    /// The kind of problem
+    pub kind: WhitespaceIssueKind,
+}
+
+impl fmt::Display for WhitespaceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path, line, self.kind.label()),
+            None => write!(f, "{}: {}", self.path, self.kind.label()),
+        }
+    }
+}
+
+/// Checks a unified diff for whitespace problems on added lines.
+///
+/// Only lines introduced by the diff are inspected, so pre-existing problems
+/// in unchanged lines are not reported.
+///
+/// # Examples
+///
+/// ```
+/// use commit_wizard::whitespace::{check_diff, WhitespaceIssueKind};
+///
+/// let patch = "@@ -0,0 +1,1 @@\n+let x = 1;   \n";
+/// let issues = check_diff("src/lib.rs", patch);
+/// assert_eq!(issues[0].kind, WhitespaceIssueKind::TrailingWhitespace);
+/// assert_eq!(issues[0].line, Some(1));
+/// ```
+pub fn check_diff(path: &str, patch: &str) -> Vec<WhitespaceIssue> {
+    let mut issues = Vec::new();
+    let mut new_line: usize = 0;
+    let mut in_hunk = false;
+    let mut last_origin: Option<char> = None;
+    let mut crlf_lines = 0usize;
+    let mut lf_lines = 0usize;
+    let mut last_added_crlf: Option<bool> = None;
+
+    for raw in patch.split_inclusive('\n') {
+        if let Some(start) = parse_hunk_new_start(raw) {
+            new_line = start;
+            in_hunk = true;
+            last_origin = None;
+            continue;
+        }
+
+        if !in_hunk {
+            continue;
+        }
+
+        if raw.starts_with("\\ No newline at end of file") {
+            if last_origin == Some('+') {
+                // The previous added line has no line ending after all
+                if last_added_crlf == Some(false) {
+                    lf_lines -= 1;
+                }
+                issues.push(WhitespaceIssue {
+                    path: path.to_string(),
+                    line: None,
+                    kind: WhitespaceIssueKind::MissingFinalNewline,
+                });
+            }
+            continue;
+        }
+
+        let origin = raw.chars().next();
+        match origin {
+            Some('+') => {
+                let body = raw[1..].strip_suffix('\n').unwrap_or(&raw[1..]);
+                let (content, is_crlf) = match body.strip_suffix('\r') {
+                    Some(stripped) => (stripped, true),
+                    None => (body, false),
+                };
+                if is_crlf {
+                    crlf_lines += 1;
+                } else {
+                    lf_lines += 1;
+                }
+                last_added_crlf = Some(is_crlf);
+                if content.ends_with(' ') || content.ends_with('\t') {
+                    issues.push(WhitespaceIssue {
+                        path: path.to_string(),
+                        line: Some(new_line),
+                        kind: WhitespaceIssueKind::TrailingWhitespace,
+                    });
+                }
+                new_line += 1;
+            }
+            Some(' ') => new_line += 1,
+            Some('-') => {}
+            _ => {
+                // Anything else (e.g. the next file header) ends the hunk
+                in_hunk = false;
+            }
+        }
+        last_origin = origin;
+    }
+
+    if crlf_lines > 0 && lf_lines > 0 {
+        issues.push(WhitespaceIssue {
+            path: path.to_string(),
+            line: None,
+            kind: WhitespaceIssueKind::MixedLineEndings,
+        });
+    }
+
+    issues
+}
+
+/// Checks the working-tree changes of the given files for whitespace problems.
+///
+/// Binary files are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the diffs cannot be collected.
+pub fn check_files(repo: &Repository, files: &[ChangedFile]) -> Result<Vec<WhitespaceIssue>> {
+    let diffs = crate::git::collect_diffs(repo, files)?;
+
+    let mut issues = Vec::new();
+    for file in files {
+        if let Some(diff) = diffs.get(&file.path) {
+            if !diff.is_binary {
+                issues.extend(check_diff(&file.path, &diff.patch));
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Extracts the new-file start line from a hunk header like `@@ -1,2 +3,4 @@`.
+fn parse_hunk_new_start(line: &str) -> Option<usize> {
+    let rest = line.strip_prefix("@@ ")?;
+    let plus = rest.split_whitespace().find(|part| part.starts_with('+'))?;
+    let start = plus[1..].split(',').next()?;
+    start.parse().ok()
+}
+
+/// Formats a list of issues as a multi-line warning message.
+pub fn format_issues(issues: &[WhitespaceIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| format!("  • {}", issue))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Fixes the given whitespace issues in the working tree.
+///
+/// Trailing whitespace is stripped only from the reported lines, mixed line
+/// endings are normalized to the file's dominant style, and a final newline
+/// is appended where missing. Files must be re-staged afterwards.
+///
+/// # Returns
+///
+/// The number of files that were rewritten.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read or written.
+pub fn fix_issues(workdir: &Path, issues: &[WhitespaceIssue]) -> Result<usize> {
+    let mut by_file: BTreeMap<&str, Vec<&WhitespaceIssue>> = BTreeMap::new();
+    for issue in issues {
+        by_file.entry(issue.path.as_str()).or_default().push(issue);
+    }
+
+    let mut fixed = 0;
+    for (path, file_issues) in by_file {
+        let full_path = workdir.join(path);
+        let original = fs::read_to_string(&full_path)
+            .with_context(|| format!("Failed to read {}", full_path.display()))?;
+        let updated = fix_content(&original, &file_issues);
+
+        if updated != original {
+            fs::write(&full_path, updated)
+                .with_context(|| format!("Failed to write {}", full_path.display()))?;
+            fixed += 1;
+        }
+    }
+
+    Ok(fixed)
+}
+
+/// Applies fixes for one file's issues to its content.
+fn fix_content(content: &str, issues: &[&WhitespaceIssue]) -> String {
+    let crlf_count = content.matches("\r\n").count();
+    let lf_count = content.matches('\n').count() - crlf_count;
+    let ending = if crlf_count > lf_count { "\r\n" } else { "\n" };
+
+    let normalize_endings = issues
+        .iter()
+        .any(|i| i.kind == WhitespaceIssueKind::MixedLineEndings);
+    let add_final_newline = issues
+        .iter()
+        .any(|i| i.kind == WhitespaceIssueKind::MissingFinalNewline);
+    let trailing_lines: Vec<usize> = issues
+        .iter()
+        .filter(|i| i.kind == WhitespaceIssueKind::TrailingWhitespace)
+        .filter_map(|i| i.line)
+        .collect();
+
+    let mut result = String::with_capacity(content.len() + 1);
+    for (idx, raw) in content.split_inclusive('\n').enumerate() {
+        let (body, line_ending) = if let Some(stripped) = raw.strip_suffix("\r\n") {
+            (stripped, "\r\n")
+        } else if let Some(stripped) = raw.strip_suffix('\n') {
+            (stripped, "\n")
+        } else {
+            (raw, "")
+        };
+
+        let body = if trailing_lines.contains(&(idx + 1)) {
+            body.trim_end_matches([' ', '\t'])
+        } else {
+            body
+        };
+
+        result.push_str(body);
+        if line_ending.is_empty() {
+            if add_final_newline {
+                result.push_str(ending);
+            }
+        } else if normalize_endings {
+            result.push_str(ending);
+        } else {
+            result.push_str(line_ending);
+        }
+    }
+
+    result
+}
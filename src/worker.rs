@@ -0,0 +1,208 @@
+//! Background execution of AI requests.
+//!
+//! AI grouping and message generation can take many seconds. An [`AiWorker`]
+//! runs one request on a background thread and hands the result back through
+//! a channel that the TUI event loop polls, so the interface stays responsive
+//! and the request can be cancelled.
+//!
+//! Cancelling sets the worker's flag, which the provider call sees as
+//! [`crate::provider::cancel_flag`]: the Copilot CLI is killed together with
+//! the processes it started. Blocking HTTP requests cannot be aborted; the
+//! worker is detached and its result is discarded when it arrives.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+use crate::provider::{with_cancel_flag, AiProvider, ChatMessage};
+use crate::types::{ChangeGroup, ChangedFile};
+
+/// Spinner frames shown while a request is running.
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Time each spinner frame is shown.
+const SPINNER_FRAME_TIME: Duration = Duration::from_millis(100);
+
+/// Returns the spinner frame for a task running since `elapsed`.
+pub fn spinner_frame(elapsed: Duration) -> &'static str {
+    let frame = elapsed.as_millis() / SPINNER_FRAME_TIME.as_millis();
+    SPINNER_FRAMES[frame as usize % SPINNER_FRAMES.len()]
+}
+
+/// What a worker is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiJob {
+    /// Grouping all changed files
+    Grouping,
+    /// Generating the message of the group at this index
+    Message(usize),
+    /// Refining the message of the group at this index with feedback
+    Refine(usize),
+}
+
+/// Result delivered by a worker.
+#[derive(Debug)]
+pub enum AiOutcome {
+    /// Groups created by AI grouping
+    Groups(Result<Vec<ChangeGroup>>),
+    /// Message candidates (descriptions with optional bodies) generated for
+    /// the group at `index`
+    Message {
+        /// Index of the group the messages belong to
+        index: usize,
+        /// The generated candidates, at least one on success
+        result: Result<Vec<(String, Option<String>)>>,
+    },
+    /// Refined message for the group at `index`
+    Refined {
+        /// Index of the group the message belongs to
+        index: usize,
+        /// The conversation the message was generated from, ending with the
+        /// user's feedback
+        conversation: Vec<ChatMessage>,
+        /// The refined message
+        result: Result<(String, Option<String>)>,
+    },
+}
+
+/// A single AI request running on a background thread.
+#[derive(Debug)]
+pub struct AiWorker {
+    job: AiJob,
+    provider_name: String,
+    receiver: Receiver<AiOutcome>,
+    cancelled: Arc<AtomicBool>,
+    started: Instant,
+}
+
+impl AiWorker {
+    /// Starts grouping `files` in the background.
+    pub fn spawn_grouping(
+        provider: Arc<dyn AiProvider>,
+        files: Vec<ChangedFile>,
+        ticket: Option<String>,
+        diffs: HashMap<String, String>,
+    ) -> Self {
+        Self::spawn(AiJob::Grouping, provider, move |provider| {
+            AiOutcome::Groups(provider.group_files(files, ticket, &diffs))
+        })
+    }
+
+    /// Starts generating `candidates` alternative messages for the group at
+    /// `index` in the background.
+    pub fn spawn_message(
+        provider: Arc<dyn AiProvider>,
+        index: usize,
+        group: ChangeGroup,
+        diff: Option<String>,
+        candidates: usize,
+    ) -> Self {
+        Self::spawn(AiJob::Message(index), provider, move |provider| {
+            let result = if candidates > 1 {
+                provider.generate_messages(&group, &group.files, diff.as_deref(), candidates)
+            } else {
+                provider
+                    .generate_message(&group, &group.files, diff.as_deref())
+                    .map(|message| vec![message])
+            };
+            AiOutcome::Message { index, result }
+        })
+    }
+
+    /// Starts refining the message of the group at `index` in the background.
+    ///
+    /// `conversation` ends with the user's feedback.
+    pub fn spawn_refine(
+        provider: Arc<dyn AiProvider>,
+        index: usize,
+        conversation: Vec<ChatMessage>,
+    ) -> Self {
+        Self::spawn(AiJob::Refine(index), provider, move |provider| {
+            let result = provider.refine_message(&conversation);
+            AiOutcome::Refined {
+                index,
+                conversation,
+                result,
+            }
+        })
+    }
+
+    fn spawn<F>(job: AiJob, provider: Arc<dyn AiProvider>, run: F) -> Self
+    where
+        F: FnOnce(&dyn AiProvider) -> AiOutcome + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let provider_name = provider.name().to_string();
+
+        let cancelled_clone = cancelled.clone();
+        thread::spawn(move || {
+            let outcome = with_cancel_flag(cancelled_clone.clone(), || run(provider.as_ref()));
+            if cancelled_clone.load(Ordering::Relaxed) {
+                log::debug!("Discarding result of cancelled AI request");
+                return;
+            }
+            // The receiver is gone if the TUI quit meanwhile
+            let _ = sender.send(outcome);
+        });
+
+        Self {
+            job,
+            provider_name,
+            receiver,
+            cancelled,
+            started: Instant::now(),
+        }
+    }
+
+    /// Returns what the worker is doing.
+    pub fn job(&self) -> AiJob {
+        self.job
+    }
+
+    /// Returns the name of the provider handling the request.
+    pub fn provider_name(&self) -> &str {
+        &self.provider_name
+    }
+
+    /// Returns the time since the request was started.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Returns the spinner frame for the current time.
+    pub fn spinner(&self) -> &'static str {
+        spinner_frame(self.elapsed())
+    }
+
+    /// Returns the result if the request has finished, without blocking.
+    ///
+    /// A worker thread that ended without a result (e.g. a panicking
+    /// provider) is reported as an error for the job.
+    pub fn try_result(&self) -> Option<AiOutcome> {
+        match self.receiver.try_recv() {
+            Ok(outcome) => Some(outcome),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                let error = anyhow!("AI request ended without a result");
+                Some(match self.job {
+                    AiJob::Grouping => AiOutcome::Groups(Err(error)),
+                    AiJob::Message(index) | AiJob::Refine(index) => AiOutcome::Message {
+                        index,
+                        result: Err(error),
+                    },
+                })
+            }
+        }
+    }
+
+    /// Cancels the request; its result will be discarded.
+    pub fn cancel(self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
@@ -396,10 +396,11 @@ fn test_parse_commit_message_body_with_blank_lines() {
 }
 
 // HTTP mocked tests using mockito
+use commit_wizard::provider::{HttpChatProvider, TokenLimitField};
 use mockito::Server;
 
 #[test]
-fn test_openai_api_call_success() {
+fn test_generate_with_openai_success() {
     let mut server = Server::new();
 
     let mock_response = r#"{
@@ -418,7 +419,6 @@ fn test_openai_api_call_success() {
         .with_body(mock_response)
         .create();
 
-    let client = reqwest::blocking::Client::new();
     let files = vec![ChangedFile::new(
         "src/auth.rs".to_string(),
         Status::INDEX_NEW,
@@ -432,15 +432,15 @@ fn test_openai_api_call_success() {
         vec![],
     );
 
-    let config = commit_wizard::ai::ApiConfig {
-        token: "test-token".to_string(),
-        api_url: server.url(),
-        model: "gpt-4".to_string(),
-        system_prompt: "system prompt".to_string(),
-    };
+    let provider = HttpChatProvider::new(
+        "OpenAI",
+        server.url(),
+        Some("test-token".to_string()),
+        "gpt-4",
+        TokenLimitField::MaxCompletionTokens,
+    );
 
-    let result =
-        commit_wizard::ai::openai_api_call_with_client(&client, &group, &files, None, &config);
+    let result = commit_wizard::ai::generate_with_provider(&provider, &group, &files, None);
 
     mock.assert();
     assert!(result.is_ok());
@@ -450,7 +450,7 @@ fn test_openai_api_call_success() {
 }
 
 #[test]
-fn test_openai_api_call_with_body() {
+fn test_generate_with_openai_with_body() {
     let mut server = Server::new();
 
     let mock_response = r#"{
@@ -469,7 +469,6 @@ fn test_openai_api_call_with_body() {
         .with_body(mock_response)
         .create();
 
-    let client = reqwest::blocking::Client::new();
     let files = vec![ChangedFile::new(
         "src/oauth.rs".to_string(),
         Status::INDEX_NEW,
@@ -483,15 +482,15 @@ fn test_openai_api_call_with_body() {
         vec![],
     );
 
-    let config = commit_wizard::ai::ApiConfig {
-        token: "test-token".to_string(),
-        api_url: server.url(),
-        model: "gpt-4".to_string(),
-        system_prompt: "system prompt".to_string(),
-    };
+    let provider = HttpChatProvider::new(
+        "OpenAI",
+        server.url(),
+        Some("test-token".to_string()),
+        "gpt-4",
+        TokenLimitField::MaxCompletionTokens,
+    );
 
-    let result =
-        commit_wizard::ai::openai_api_call_with_client(&client, &group, &files, None, &config);
+    let result = commit_wizard::ai::generate_with_provider(&provider, &group, &files, None);
 
     mock.assert();
     assert!(result.is_ok());
@@ -504,7 +503,7 @@ fn test_openai_api_call_with_body() {
 }
 
 #[test]
-fn test_openai_api_call_error_response() {
+fn test_generate_with_openai_error_response() {
     let mut server = Server::new();
 
     let mock = server
@@ -514,7 +513,6 @@ fn test_openai_api_call_error_response() {
         .with_body(r#"{"error": {"message": "Invalid API key"}}"#)
         .create();
 
-    let client = reqwest::blocking::Client::new();
     let files = vec![ChangedFile::new(
         "src/test.rs".to_string(),
         Status::INDEX_MODIFIED,
@@ -528,15 +526,15 @@ fn test_openai_api_call_error_response() {
         vec![],
     );
 
-    let config = commit_wizard::ai::ApiConfig {
-        token: "invalid-token".to_string(),
-        api_url: server.url(),
-        model: "gpt-4".to_string(),
-        system_prompt: "system prompt".to_string(),
-    };
+    let provider = HttpChatProvider::new(
+        "OpenAI",
+        server.url(),
+        Some("invalid-token".to_string()),
+        "gpt-4",
+        TokenLimitField::MaxCompletionTokens,
+    );
 
-    let result =
-        commit_wizard::ai::openai_api_call_with_client(&client, &group, &files, None, &config);
+    let result = commit_wizard::ai::generate_with_provider(&provider, &group, &files, None);
 
     mock.assert();
     assert!(result.is_err());
@@ -545,7 +543,7 @@ fn test_openai_api_call_error_response() {
 }
 
 #[test]
-fn test_github_models_api_call_success() {
+fn test_generate_with_github_models_success() {
     let mut server = Server::new();
 
     let mock_response = r#"{
@@ -564,7 +562,6 @@ fn test_github_models_api_call_success() {
         .with_body(mock_response)
         .create();
 
-    let client = reqwest::blocking::Client::new();
     let files = vec![ChangedFile::new(
         "src/cache.rs".to_string(),
         Status::INDEX_MODIFIED,
@@ -578,17 +575,16 @@ fn test_github_models_api_call_success() {
         vec![],
     );
 
-    let config = commit_wizard::ai::ApiConfig {
-        token: "ghp_test123".to_string(),
-        api_url: server.url(),
-        model: "gpt-4".to_string(),
-        system_prompt: "system prompt".to_string(),
-    };
-
-    let result = commit_wizard::ai::github_models_api_call_with_client(
-        &client, &group, &files, None, &config,
+    let provider = HttpChatProvider::new(
+        "GitHub Models",
+        server.url(),
+        Some("ghp_test123".to_string()),
+        "gpt-4",
+        TokenLimitField::MaxTokens,
     );
 
+    let result = commit_wizard::ai::generate_with_provider(&provider, &group, &files, None);
+
     mock.assert();
     assert!(result.is_ok());
     let (desc, body) = result.unwrap();
@@ -597,7 +593,7 @@ fn test_github_models_api_call_success() {
 }
 
 #[test]
-fn test_github_models_api_call_with_diff() {
+fn test_generate_with_github_models_with_diff() {
     let mut server = Server::new();
 
     let mock_response = r#"{
@@ -616,7 +612,6 @@ fn test_github_models_api_call_with_diff() {
         .with_body(mock_response)
         .create();
 
-    let client = reqwest::blocking::Client::new();
     let files = vec![ChangedFile::new(
         "src/db.rs".to_string(),
         Status::INDEX_MODIFIED,
@@ -631,17 +626,16 @@ fn test_github_models_api_call_with_diff() {
     );
     let diff = Some("+ fn optimized_query() {}\n- fn slow_query() {}");
 
-    let config = commit_wizard::ai::ApiConfig {
-        token: "ghp_test456".to_string(),
-        api_url: server.url(),
-        model: "gpt-4".to_string(),
-        system_prompt: "system prompt".to_string(),
-    };
-
-    let result = commit_wizard::ai::github_models_api_call_with_client(
-        &client, &group, &files, diff, &config,
+    let provider = HttpChatProvider::new(
+        "GitHub Models",
+        server.url(),
+        Some("ghp_test456".to_string()),
+        "gpt-4",
+        TokenLimitField::MaxTokens,
     );
 
+    let result = commit_wizard::ai::generate_with_provider(&provider, &group, &files, diff);
+
     mock.assert();
     assert!(result.is_ok());
     let (desc, _) = result.unwrap();
@@ -0,0 +1,188 @@
+//! Tests for the reference graph between changed files.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use git2::Status;
+use tempfile::TempDir;
+
+use commit_wizard::analysis::{link_files, tested_module, untracked_references, ReferenceScanner};
+use commit_wizard::inference::build_groups;
+use commit_wizard::types::{ChangedFile, CommitType};
+
+fn changed(paths: &[&str]) -> HashSet<String> {
+    paths.iter().map(|p| p.to_string()).collect()
+}
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn test_rust_references() {
+    let set = changed(&[
+        "src/config/mod.rs",
+        "src/config/loader.rs",
+        "src/parser.rs",
+        "src/parser/lexer.rs",
+        "tests/parser_tests.rs",
+    ]);
+    let scanner = ReferenceScanner::new();
+
+    assert_eq!(
+        scanner.references("src/parser.rs", "mod lexer;\nuse std::fmt;\n", &set),
+        ["src/parser/lexer.rs"]
+    );
+    assert_eq!(
+        scanner.references(
+            "src/config/loader.rs",
+            "use super::Config;\nuse crate::parser::lexer::Token;\n",
+            &set
+        ),
+        ["src/config/mod.rs", "src/parser.rs", "src/parser/lexer.rs"]
+    );
+    assert_eq!(
+        scanner.references(
+            "tests/parser_tests.rs",
+            "use my_crate::parser::parse;\n",
+            &set
+        ),
+        ["src/parser.rs"]
+    );
+    // Inline modules are not files
+    assert!(scanner
+        .references("src/config/mod.rs", "mod tests {\n}\n", &set)
+        .is_empty());
+}
+
+#[test]
+fn test_js_and_python_references() {
+    let set = changed(&[
+        "web/src/api/index.ts",
+        "web/src/cart.js",
+        "web/src/app.ts",
+        "app/models/user.py",
+        "app/models/__init__.py",
+        "app/views.py",
+    ]);
+    let scanner = ReferenceScanner::new();
+
+    assert_eq!(
+        scanner.references(
+            "web/src/app.ts",
+            "import { get } from './api';\nconst cart = require(\"./cart\");\nimport React from 'react';\n",
+            &set
+        ),
+        ["web/src/api/index.ts", "web/src/cart.js"]
+    );
+    assert_eq!(
+        scanner.references("app/views.py", "from .models import user\n", &set),
+        ["app/models/__init__.py", "app/models/user.py"]
+    );
+    assert_eq!(
+        scanner.references("app/models/__init__.py", "import app.views\n", &set),
+        ["app/views.py"]
+    );
+}
+
+#[test]
+fn test_tested_modules_by_name() {
+    let set = changed(&[
+        "pkg/store/store.go",
+        "pkg/store/store_test.go",
+        "tests/test_client.py",
+        "lib/client.py",
+        "src/a/util.rs",
+        "src/b/util.rs",
+        "tests/util_tests.rs",
+    ]);
+
+    assert_eq!(
+        tested_module("pkg/store/store_test.go", &set),
+        Some("pkg/store/store.go".to_string())
+    );
+    assert_eq!(
+        tested_module("tests/test_client.py", &set),
+        Some("lib/client.py".to_string())
+    );
+    // Ambiguous names link nothing
+    assert_eq!(tested_module("tests/util_tests.rs", &set), None);
+}
+
+#[test]
+fn test_untracked_references() {
+    let untracked = changed(&["src/parser.rs", "src/lexer.rs", "web/new.ts"]);
+    let lib_patch = "\
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,3 @@
+ mod lexer;
+ mod cli;
++mod parser;
+";
+    let app_patch = "\
+--- a/web/app.ts
++++ b/web/app.ts
+@@ -1 +1,2 @@
++import { run } from './new';
+ export {};
+";
+    let main_patch = "+use crate::parser::parse;\n";
+
+    let references = untracked_references(
+        [
+            ("src/lib.rs", lib_patch),
+            ("web/app.ts", app_patch),
+            ("src/main.rs", main_patch),
+            ("README.md", "+see src/lexer.rs\n"),
+        ],
+        &untracked,
+    );
+
+    assert_eq!(references["src/parser.rs"], ["src/lib.rs", "src/main.rs"]);
+    assert_eq!(references["web/new.ts"], ["web/app.ts"]);
+    // Context lines were there before, other files are not scanned
+    assert!(!references.contains_key("src/lexer.rs"));
+}
+
+#[test]
+fn test_linked_files_are_grouped_together() {
+    let dir = TempDir::new().unwrap();
+    write(dir.path(), "src/lib.rs", "pub mod parser;\n");
+    write(dir.path(), "src/parser/mod.rs", "pub fn parse() {}\n");
+    write(
+        dir.path(),
+        "tests/parser_tests.rs",
+        "#[test]\nfn parses() {}\n",
+    );
+    write(dir.path(), "docs/guide.md", "# Guide\n");
+    let mut files: Vec<ChangedFile> = [
+        "src/lib.rs",
+        "src/parser/mod.rs",
+        "tests/parser_tests.rs",
+        "docs/guide.md",
+    ]
+    .iter()
+    .map(|path| ChangedFile::new(path.to_string(), Status::INDEX_MODIFIED))
+    .collect();
+
+    link_files(dir.path(), &mut files);
+
+    assert_eq!(files[0].related_to.as_deref(), Some("src/lib.rs"));
+    assert_eq!(files[2].related_to.as_deref(), Some("src/lib.rs"));
+    assert_eq!(files[3].related_to, None);
+
+    let groups = build_groups(files, None);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].commit_type, CommitType::Feat);
+    assert_eq!(groups[0].scope.as_deref(), Some("parser"));
+    let mut paths: Vec<&str> = groups[0].files.iter().map(|f| f.path.as_str()).collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        ["src/lib.rs", "src/parser/mod.rs", "tests/parser_tests.rs"]
+    );
+}
@@ -0,0 +1,75 @@
+//! Tests for the per-session AI budget.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use commit_wizard::budget::{AiBudget, BudgetExceeded, BudgetedProvider};
+use commit_wizard::config::Config;
+use commit_wizard::provider::AiProvider;
+
+/// Provider that always answers "ok".
+struct EchoProvider;
+
+impl AiProvider for EchoProvider {
+    fn name(&self) -> &str {
+        "echo"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn complete(&self, _prompt: &str) -> Result<String> {
+        Ok("ok".to_string())
+    }
+}
+
+#[test]
+fn test_budget_from_config() {
+    assert!(AiBudget::from_config(&Config::default().ai).is_none());
+
+    let config = Config::from_toml_str("[ai]\nmax_requests = 2\nmax_seconds = 60\n").unwrap();
+    assert_eq!(config.ai.max_requests, Some(2));
+    assert_eq!(config.ai.max_seconds, Some(60));
+    assert!(AiBudget::from_config(&config.ai).is_some());
+}
+
+#[test]
+fn test_request_limit_stops_further_requests() {
+    let budget = Arc::new(AiBudget::new(Some(2), None));
+    let provider = BudgetedProvider::new(Box::new(EchoProvider), budget.clone());
+
+    assert_eq!(provider.complete("one").unwrap(), "ok");
+    assert_eq!(provider.complete("two").unwrap(), "ok");
+
+    let err = provider.complete("three").unwrap_err();
+    let exceeded = err.downcast_ref::<BudgetExceeded>().unwrap();
+    assert_eq!(exceeded.requests, 2);
+    assert_eq!(budget.usage().0, 2);
+}
+
+#[test]
+fn test_time_limit_stops_further_requests() {
+    let budget = AiBudget::new(None, Some(Duration::from_secs(10)));
+
+    assert!(budget.start_request().is_ok());
+    budget.record_time(Duration::from_secs(4));
+    assert!(budget.start_request().is_ok());
+    budget.record_time(Duration::from_secs(7));
+
+    let exceeded = budget.start_request().unwrap_err();
+    assert_eq!(exceeded.time, Duration::from_secs(11));
+    assert!(exceeded.to_string().contains("2 request(s), 11s"));
+}
+
+#[test]
+fn test_budget_is_shared_between_providers() {
+    let budget = Arc::new(AiBudget::new(Some(1), None));
+    let first = BudgetedProvider::new(Box::new(EchoProvider), budget.clone());
+    let second = BudgetedProvider::new(Box::new(EchoProvider), budget);
+
+    assert!(first.complete("one").is_ok());
+    assert!(second.complete("two").is_err());
+}
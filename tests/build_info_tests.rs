@@ -0,0 +1,40 @@
+//! Tests for build information reporting.
+
+use commit_wizard::build_info::BuildInfo;
+
+#[test]
+fn test_build_info_current() {
+    let info = BuildInfo::current();
+
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert!(!info.git_sha.is_empty());
+    assert!(!info.features.iter().any(|f| f == "default"));
+    assert_eq!(info.libgit2_version.split('.').count(), 3);
+    assert!(!info.target.is_empty());
+    assert_eq!(info.short(), format!("commit-wizard {}", info.version));
+}
+
+#[test]
+fn test_build_info_json_and_display() {
+    let info = BuildInfo {
+        version: "1.2.3".to_string(),
+        git_sha: "abc123".to_string(),
+        features: vec!["vendored-openssl".to_string()],
+        libgit2_version: "1.9.0".to_string(),
+        libgit2_vendored: false,
+        target: "x86_64-unknown-linux-gnu".to_string(),
+        profile: "release".to_string(),
+    };
+
+    let json: serde_json::Value = serde_json::from_str(&info.to_json().unwrap()).unwrap();
+    assert_eq!(json["version"], "1.2.3");
+    assert_eq!(json["git_sha"], "abc123");
+    assert_eq!(json["features"][0], "vendored-openssl");
+    assert_eq!(json["libgit2_version"], "1.9.0");
+
+    let text = info.to_string();
+    assert!(text.starts_with("commit-wizard 1.2.3\n"));
+    assert!(text.contains("commit:   abc123"));
+    assert!(text.contains("features: vendored-openssl"));
+    assert!(text.contains("libgit2:  1.9.0 (system)"));
+}
@@ -0,0 +1,310 @@
+//! Tests for fix-likelihood scoring and suggested commit types.
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use git2::{Repository, Signature, Status, Time};
+use tempfile::TempDir;
+
+use commit_wizard::classify::{
+    changed_assertions, classify_files, fix_likelihood, keyword_hits, recent_line_ratio,
+    removed_line_numbers, score_files, suggest_commit_type, BlameBudget, FIX_THRESHOLD,
+};
+use commit_wizard::git::{collect_changed_files, collect_diffs};
+use commit_wizard::inference::{build_groups, file_commit_type};
+use commit_wizard::types::{ChangedFile, CommitType, FileDiff};
+
+fn diff(patch: &str, added: usize, removed: usize) -> FileDiff {
+    FileDiff {
+        patch: patch.to_string(),
+        lines_added: added,
+        lines_removed: removed,
+        is_binary: false,
+    }
+}
+
+/// Commits `content` to `src/lib.rs` with the given commit time.
+fn commit_at(repo: &Repository, content: &str, seconds: i64) {
+    let workdir = repo.workdir().unwrap();
+    fs::create_dir_all(workdir.join("src")).unwrap();
+    fs::write(workdir.join("src/lib.rs"), content).unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("src/lib.rs")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::new("Test User", "test@example.com", &Time::new(seconds, 0)).unwrap();
+    let parents: Vec<_> = repo
+        .head()
+        .ok()
+        .and_then(|h| h.peel_to_commit().ok())
+        .into_iter()
+        .collect();
+    let parents: Vec<_> = parents.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, "commit", &tree, &parents)
+        .unwrap();
+}
+
+#[test]
+fn test_keyword_hits_only_counts_changed_lines() {
+    let patch = "diff --git a/x.rs b/x.rs\n--- a/x.rs\n+++ b/x.rs\n@@ -1,3 +1,3 @@\n fn handle_error() {\n-    value.unwrap()\n+    value.unwrap_or_default() // avoid panic on null\n }\n";
+    // "error" only appears in context lines
+    assert_eq!(keyword_hits(patch), 2);
+    // Keywords match whole words only
+    assert_eq!(keyword_hits("+let prefix = racecar;\n"), 0);
+    assert_eq!(keyword_hits("+// Off By One\n"), 1);
+}
+
+#[test]
+fn test_removed_line_numbers() {
+    let patch = "--- a/x.rs\n+++ b/x.rs\n@@ -3,4 +3,3 @@\n a\n-b\n-c\n+C\n d\n@@ -20 +19,2 @@\n-x\n+y\n+z\n";
+    assert_eq!(removed_line_numbers(patch), vec![4, 5, 20]);
+    assert!(removed_line_numbers("@@ -0,0 +1 @@\n+new\n").is_empty());
+}
+
+#[test]
+fn test_fix_likelihood_combines_signals() {
+    let small_edit = "@@ -1 +1 @@\n-let a = v[i + 1];\n+let a = v[i];\n";
+    let small_fix = "@@ -1 +1 @@\n-let a = v[i + 1];\n+let a = v[i]; // off-by-one panic\n";
+    let additions = "@@ -1 +1,3 @@\n+fn handle_error() {}\n+fn panic_hook() {}\n";
+
+    // Editing recent code without bug keywords is not enough on its own
+    assert!(fix_likelihood(&diff(small_edit, 1, 1), Some(1.0)) < FIX_THRESHOLD);
+    // Keywords plus a small edit of recent code is a fix
+    assert!(fix_likelihood(&diff(small_fix, 1, 1), Some(1.0)) >= FIX_THRESHOLD);
+    // Blame data raises the score
+    assert!(
+        fix_likelihood(&diff(small_fix, 1, 1), Some(1.0))
+            > fix_likelihood(&diff(small_fix, 1, 1), Some(0.0))
+    );
+    // Pure additions look like features
+    assert!(fix_likelihood(&diff(additions, 2, 0), None) < FIX_THRESHOLD);
+
+    let score = fix_likelihood(&diff(small_fix, 1, 1), Some(5.0));
+    assert!((0.0..=1.0).contains(&score));
+}
+
+#[test]
+fn test_recent_line_ratio_uses_commit_times() {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let now = chrono::Utc::now().timestamp();
+
+    // Line 1 is old, line 2 was written yesterday
+    commit_at(&repo, "old line\n", now - 400 * 24 * 60 * 60);
+    commit_at(&repo, "old line\nnew line\n", now - 24 * 60 * 60);
+
+    assert_eq!(
+        recent_line_ratio(&repo, "src/lib.rs", &[1]).unwrap(),
+        Some(0.0)
+    );
+    assert_eq!(
+        recent_line_ratio(&repo, "src/lib.rs", &[2]).unwrap(),
+        Some(1.0)
+    );
+    assert_eq!(
+        recent_line_ratio(&repo, "src/lib.rs", &[1, 2]).unwrap(),
+        Some(0.5)
+    );
+    assert_eq!(recent_line_ratio(&repo, "src/lib.rs", &[]).unwrap(), None);
+    assert!(recent_line_ratio(&repo, "missing.rs", &[1]).is_err());
+}
+
+#[test]
+fn test_score_files_turns_recent_bug_fix_into_fix_group() {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let now = chrono::Utc::now().timestamp();
+    commit_at(
+        &repo,
+        "fn get(v: &[u8], i: usize) -> u8 {\n    v[i + 1]\n}\n",
+        now - 3600,
+    );
+
+    fs::write(
+        tmp.path().join("src/lib.rs"),
+        "fn get(v: &[u8], i: usize) -> u8 {\n    v[i] // off-by-one caused a panic\n}\n",
+    )
+    .unwrap();
+    fs::write(tmp.path().join("src/new.rs"), "// fix panic on null\n").unwrap();
+
+    let mut files = collect_changed_files(&repo, true).unwrap();
+    let diffs = collect_diffs(&repo, &files).unwrap();
+    let cancel = AtomicBool::new(false);
+    let mut budget = BlameBudget::new(10, Duration::from_secs(60), &cancel);
+    score_files(&repo, &mut files, &diffs, &mut budget);
+
+    let modified = files.iter().find(|f| f.path == "src/lib.rs").unwrap();
+    assert!(modified.is_likely_fix(), "{:?}", modified.fix_likelihood);
+    // New files are not scored
+    let new = files.iter().find(|f| f.path == "src/new.rs").unwrap();
+    assert_eq!(new.fix_likelihood, None);
+
+    let modified = modified.clone();
+    let groups = build_groups(vec![modified], None);
+    assert_eq!(groups[0].commit_type, CommitType::Fix);
+}
+
+#[test]
+fn test_score_files_skips_blame_beyond_the_budget() {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let now = chrono::Utc::now().timestamp();
+    commit_at(&repo, "a\nb\nc\n", now - 3600);
+    fs::write(tmp.path().join("src/lib.rs"), "a\nB\nc\n").unwrap();
+
+    let files = collect_changed_files(&repo, true).unwrap();
+    let diffs = collect_diffs(&repo, &files).unwrap();
+    let with_blame = fix_likelihood(&diffs["src/lib.rs"], Some(1.0));
+    let without_blame = fix_likelihood(&diffs["src/lib.rs"], None);
+    let score = |budget: &mut BlameBudget| {
+        let mut files = files.clone();
+        score_files(&repo, &mut files, &diffs, budget);
+        files[0].fix_likelihood.unwrap()
+    };
+
+    let cancel = AtomicBool::new(false);
+    assert_eq!(
+        score(&mut BlameBudget::new(1, Duration::from_secs(60), &cancel)),
+        with_blame
+    );
+    // No files left, no time left, or cancelled: scored without blame
+    assert_eq!(
+        score(&mut BlameBudget::new(0, Duration::from_secs(60), &cancel)),
+        without_blame
+    );
+    assert_eq!(
+        score(&mut BlameBudget::new(1, Duration::ZERO, &cancel)),
+        without_blame
+    );
+    let cancelled = AtomicBool::new(true);
+    assert_eq!(
+        score(&mut BlameBudget::new(
+            1,
+            Duration::from_secs(60),
+            &cancelled
+        )),
+        without_blame
+    );
+}
+
+#[test]
+fn test_recent_line_ratio_stops_at_the_recent_history() {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let now = chrono::Utc::now().timestamp();
+    let day = 24 * 60 * 60;
+
+    // Lines from before the cutoff stay old, however far back they go
+    commit_at(&repo, "first\n", now - 900 * day);
+    commit_at(&repo, "first\nsecond\n", now - 400 * day);
+    commit_at(&repo, "first\nsecond\nthird\n", now - day);
+
+    assert_eq!(
+        recent_line_ratio(&repo, "src/lib.rs", &[1, 2]).unwrap(),
+        Some(0.0)
+    );
+    assert_eq!(
+        recent_line_ratio(&repo, "src/lib.rs", &[2, 3]).unwrap(),
+        Some(0.5)
+    );
+}
+
+#[test]
+fn test_unscored_files_keep_heuristic_type() {
+    let file = ChangedFile::new("src/lib.rs".to_string(), Status::WT_MODIFIED);
+    assert!(!file.is_likely_fix());
+    let groups = build_groups(vec![file], None);
+    assert_eq!(groups[0].commit_type, CommitType::Feat);
+
+    // Non-feature types are never changed
+    let mut docs = ChangedFile::new("docs/guide.md".to_string(), Status::WT_MODIFIED);
+    docs.fix_likelihood = Some(0.9);
+    let groups = build_groups(vec![docs], None);
+    assert_eq!(groups[0].commit_type, CommitType::Docs);
+}
+
+/// Returns a modified `src/lib.rs` with declarations analyzed from `patch`.
+fn modified_file(patch: &str, added: usize, removed: usize) -> ChangedFile {
+    let mut file = ChangedFile::new("src/lib.rs".to_string(), Status::INDEX_MODIFIED);
+    file.set_diff_stats(&diff(patch, added, removed));
+    file.symbol_changes = commit_wizard::semantic::describe_file(&file, patch);
+    file
+}
+
+#[test]
+fn test_changed_assertions_count_as_fix_signal() {
+    let patch = "@@ -10,2 +10,2 @@\n-    assert_eq!(total(&[1, 2]), 4);\n+    assert_eq!(total(&[1, 2]), 3);\n";
+    assert_eq!(changed_assertions(patch), 1);
+    assert_eq!(keyword_hits(patch), 0);
+
+    let plain = "@@ -10 +10 @@\n-    let total = 4;\n+    let total = 3;\n";
+    assert!(fix_likelihood(&diff(patch, 1, 1), None) > fix_likelihood(&diff(plain, 1, 1), None));
+}
+
+#[test]
+fn test_branch_prefix_suggests_type() {
+    let patch = "@@ -3,3 +3,8 @@\n fn load() {\n-    read();\n+    read_all();\n+}\n+\n+pub fn export() {\n+    write();\n }\n";
+    let file = modified_file(patch, 6, 1);
+
+    assert_eq!(
+        suggest_commit_type(&file, patch, Some(CommitType::Fix)),
+        Some(CommitType::Fix)
+    );
+    // Added declarations keep the feature on other branches
+    assert_eq!(
+        suggest_commit_type(&file, patch, Some(CommitType::Refactor)),
+        None
+    );
+
+    let new_file = ChangedFile::new("src/export.rs".to_string(), Status::INDEX_NEW);
+    assert_eq!(
+        suggest_commit_type(&new_file, "", Some(CommitType::Fix)),
+        None
+    );
+}
+
+#[test]
+fn test_diff_content_suggests_perf_and_refactor() {
+    let perf = "@@ -3 +3,2 @@\n-    let mut out = Vec::new();\n+    // Preallocate to avoid allocations\n+    let mut out = Vec::with_capacity(items.len());\n";
+    let file = modified_file(perf, 2, 1);
+    assert_eq!(
+        suggest_commit_type(&file, perf, None),
+        Some(CommitType::Perf)
+    );
+
+    let rename = "@@ -1,3 +1,3 @@\n-fn load() {\n+fn load_config() {\n     read();\n }\n";
+    let file = modified_file(rename, 1, 1);
+    assert_eq!(
+        suggest_commit_type(&file, rename, None),
+        Some(CommitType::Refactor)
+    );
+
+    let edit = "@@ -3 +3 @@\n-    let limit = 10;\n+    let limit = 20;\n";
+    let file = modified_file(edit, 1, 1);
+    assert_eq!(suggest_commit_type(&file, edit, None), None);
+}
+
+#[test]
+fn test_classify_files_drives_grouping() {
+    let patch = "@@ -3 +3 @@\n-    let limit = 10;\n+    let limit = 20;\n";
+    let mut files = vec![
+        modified_file(patch, 1, 1),
+        ChangedFile::new("docs/guide.md".to_string(), Status::INDEX_MODIFIED),
+    ];
+    let diffs = [
+        ("src/lib.rs".to_string(), diff(patch, 1, 1)),
+        ("docs/guide.md".to_string(), diff(patch, 1, 1)),
+    ]
+    .into_iter()
+    .collect();
+
+    classify_files(&mut files, &diffs, "bugfix/PROJ-7-limit");
+
+    assert_eq!(files[0].suggested_type, Some(CommitType::Fix));
+    assert_eq!(file_commit_type(&files[0]), CommitType::Fix);
+    // Only features take the suggestion
+    assert_eq!(file_commit_type(&files[1]), CommitType::Docs);
+}
@@ -0,0 +1,146 @@
+//! Tests for `commit-wizard log` and the recent commits popup.
+
+use std::fs;
+
+use git2::{Repository, Signature};
+use tempfile::TempDir;
+
+use commit_wizard::commit_log::{recent_commits, CommitLog, LogEntry, LogFormat, MERGE_ICON};
+use commit_wizard::palette::Palette;
+use commit_wizard::types::CommitType;
+
+/// Creates a repository with an empty commit per message, oldest first.
+fn repo_with_messages(messages: &[&str]) -> (TempDir, Repository) {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    {
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        for message in messages {
+            let parent = repo.head().ok().map(|h| h.peel_to_commit().unwrap());
+            let parents: Vec<_> = parent.iter().collect();
+            repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+                .unwrap();
+        }
+    }
+    (tmp, repo)
+}
+
+fn entry(message: &str) -> LogEntry {
+    LogEntry::new("0123456789abcdef", message, "Jane", "2026-10-01", false)
+}
+
+#[test]
+fn test_parse_headers() {
+    let feat = entry("feat(ui): add themes");
+    assert_eq!(feat.commit_type, Some(CommitType::Feat));
+    assert_eq!(feat.scope.as_deref(), Some("ui"));
+    assert!(!feat.breaking);
+    assert_eq!(feat.short_id, "0123456");
+    assert_eq!(feat.icon(), "✨");
+
+    let footer = entry("refactor: drop the v1 API\n\nBREAKING CHANGE: v1 is gone");
+    assert_eq!(footer.commit_type, Some(CommitType::Refactor));
+    assert!(footer.breaking);
+
+    // Unknown or uppercase types and a missing space are not conventional
+    for message in ["feature: add x", "Feat: add x", "fix:typo", "Fix the build"] {
+        let entry = entry(message);
+        assert_eq!(entry.commit_type, None, "{}", message);
+        assert!(!entry.breaking);
+    }
+
+    let merge = LogEntry::new("abc", "Merge branch 'x'", "Jane", "2026-10-01", true);
+    assert_eq!(merge.icon(), MERGE_ICON);
+}
+
+#[test]
+fn test_read_log() {
+    let (_tmp, repo) = repo_with_messages(&[
+        "chore: initial commit",
+        "Update readme",
+        "feat(cli)!: add log",
+        "fix(cli): count merges",
+    ]);
+
+    let log = CommitLog::read(&repo, 3).unwrap();
+    let headers: Vec<&str> = log.commits.iter().map(|e| e.header.as_str()).collect();
+    assert_eq!(
+        headers,
+        [
+            "fix(cli): count merges",
+            "feat(cli)!: add log",
+            "Update readme"
+        ]
+    );
+    assert_eq!(log.commits[0].author, "Test User");
+    assert_eq!(log.conventional(), 2);
+    assert_eq!(log.counts(), [(CommitType::Feat, 1), (CommitType::Fix, 1)]);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&log.render(LogFormat::Json, None).unwrap()).unwrap();
+    assert_eq!(json["commits"][1]["type"], "feat");
+    assert_eq!(json["commits"][1]["breaking"], true);
+    assert_eq!(json["commits"][2]["type"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_empty_log() {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let log = CommitLog::read(&repo, 10).unwrap();
+    assert!(log.commits.is_empty());
+    assert_eq!(
+        log.render(LogFormat::Text, None).unwrap(),
+        "ℹ No commits yet"
+    );
+}
+
+#[test]
+fn test_colored_log() {
+    let log = CommitLog {
+        commits: vec![entry("feat(ui)!: add themes"), entry("WIP")],
+    };
+    let plain = log.render(LogFormat::Text, None).unwrap();
+    assert!(!plain.contains('\u{1b}'));
+    assert!(plain.contains("✨ 0123456 feat(ui)!: add themes  — Jane, 2026-10-01"));
+
+    let colored = log
+        .render(LogFormat::Text, Some(&Palette::default()))
+        .unwrap();
+    assert!(colored.contains('\u{1b}'));
+    assert!(colored.contains("feat(ui)"));
+    assert!(colored.contains(": add themes"));
+    assert!(colored.contains("1 conventional (50%)"));
+}
+
+#[test]
+fn test_recent_commits_list_their_files() {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    let commit = |files: &[&str], message: &str| {
+        let mut index = repo.index().unwrap();
+        for file in files {
+            fs::write(tmp.path().join(file), message).unwrap();
+            index.add_path(std::path::Path::new(file)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = repo.head().ok().map(|h| h.peel_to_commit().unwrap());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap();
+    };
+    commit(&["README.md", "main.rs"], "chore: initial commit");
+    commit(&["main.rs"], "fix: handle empty input");
+
+    let commits = recent_commits(&repo, 10).unwrap();
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0].entry.header, "fix: handle empty input");
+    assert_eq!(commits[0].files, ["main.rs"]);
+    assert_eq!(commits[1].files, ["README.md", "main.rs"]);
+
+    assert_eq!(recent_commits(&repo, 1).unwrap().len(), 1);
+}
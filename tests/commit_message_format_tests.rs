@@ -5,10 +5,10 @@ use git2::Status;
 
 #[test]
 fn test_full_message_without_body() {
-    let files = vec![ChangedFile {
-        path: "src/api/users.rs".to_string(),
-        status: Status::INDEX_NEW,
-    }];
+    let files = vec![ChangedFile::new(
+        "src/api/users.rs".to_string(),
+        Status::INDEX_NEW,
+    )];
 
     let group = ChangeGroup::new(
         CommitType::Feat,
@@ -25,10 +25,10 @@ fn test_full_message_without_body() {
 
 #[test]
 fn test_full_message_with_body_lines() {
-    let files = vec![ChangedFile {
-        path: "src/api/users.rs".to_string(),
-        status: Status::INDEX_NEW,
-    }];
+    let files = vec![ChangedFile::new(
+        "src/api/users.rs".to_string(),
+        Status::INDEX_NEW,
+    )];
 
     let group = ChangeGroup::new(
         CommitType::Feat,
@@ -59,10 +59,10 @@ fn test_full_message_with_body_lines() {
 #[test]
 fn test_body_lines_already_with_prefix() {
     // This tests the defensive case where body_lines incorrectly have '- ' prefix
-    let files = vec![ChangedFile {
-        path: "src/api/users.rs".to_string(),
-        status: Status::INDEX_NEW,
-    }];
+    let files = vec![ChangedFile::new(
+        "src/api/users.rs".to_string(),
+        Status::INDEX_NEW,
+    )];
 
     // Simulate incorrectly prefixed body lines (should not happen if parsing is correct)
     let group = ChangeGroup::new(
@@ -91,10 +91,10 @@ fn test_body_lines_already_with_prefix() {
 
 #[test]
 fn test_set_from_commit_text_strips_prefixes() {
-    let files = vec![ChangedFile {
-        path: "src/main.rs".to_string(),
-        status: Status::INDEX_MODIFIED,
-    }];
+    let files = vec![ChangedFile::new(
+        "src/main.rs".to_string(),
+        Status::INDEX_MODIFIED,
+    )];
 
     let mut group = ChangeGroup::new(
         CommitType::Fix,
@@ -130,10 +130,10 @@ fn test_set_from_commit_text_strips_prefixes() {
 
 #[test]
 fn test_mixed_body_lines_with_and_without_prefix() {
-    let files = vec![ChangedFile {
-        path: "src/main.rs".to_string(),
-        status: Status::INDEX_MODIFIED,
-    }];
+    let files = vec![ChangedFile::new(
+        "src/main.rs".to_string(),
+        Status::INDEX_MODIFIED,
+    )];
 
     // Edge case: some lines with prefix, some without
     let group = ChangeGroup::new(
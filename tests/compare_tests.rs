@@ -0,0 +1,68 @@
+//! Tests for the diff between a message and its regenerated version.
+
+use commit_wizard::compare::{diff_lines, DiffLine};
+use commit_wizard::types::MessageComparison;
+
+#[test]
+fn test_identical_messages_have_no_changes() {
+    let message = "feat(api): add users\n\n- list endpoint";
+    assert!(diff_lines(message, message)
+        .iter()
+        .all(|line| matches!(line, DiffLine::Same(_))));
+}
+
+#[test]
+fn test_changed_header_and_body() {
+    let diff = diff_lines(
+        "feat(api): add users\n\n- list endpoint\n- create endpoint",
+        "feat(api): add user endpoints\n\n- list endpoint\n- delete endpoint\n- validate input",
+    );
+
+    assert_eq!(
+        diff,
+        [
+            DiffLine::Removed("feat(api): add users".into()),
+            DiffLine::Added("feat(api): add user endpoints".into()),
+            DiffLine::Same("".into()),
+            DiffLine::Same("- list endpoint".into()),
+            DiffLine::Removed("- create endpoint".into()),
+            DiffLine::Added("- delete endpoint".into()),
+            DiffLine::Added("- validate input".into()),
+        ]
+    );
+    assert_eq!(diff[1].text(), "feat(api): add user endpoints");
+}
+
+#[test]
+fn test_added_and_removed_body() {
+    assert_eq!(
+        diff_lines("fix: typo", "fix: typo\n\n- in README"),
+        [
+            DiffLine::Same("fix: typo".into()),
+            DiffLine::Added("".into()),
+            DiffLine::Added("- in README".into()),
+        ]
+    );
+    assert_eq!(
+        diff_lines("fix: typo\n\n- in README", "fix: typo"),
+        [
+            DiffLine::Same("fix: typo".into()),
+            DiffLine::Removed("".into()),
+            DiffLine::Removed("- in README".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_comparison_scrolls_within_diff() {
+    let mut comparison = MessageComparison {
+        diff: diff_lines("a\nb", "a\nc"),
+        ..Default::default()
+    };
+    for _ in 0..5 {
+        comparison.scroll_down();
+    }
+    assert_eq!(comparison.scroll, 2);
+    comparison.scroll_up();
+    assert_eq!(comparison.scroll, 1);
+}
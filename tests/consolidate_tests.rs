@@ -0,0 +1,202 @@
+//! Tests for merging small groups into related larger ones.
+
+use commit_wizard::config::GroupingConfig;
+use commit_wizard::consolidate::{merge_small_groups, COLLECTED_DESCRIPTION};
+use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+use git2::Status;
+
+fn file(path: &str, lines: usize) -> ChangedFile {
+    let mut file = ChangedFile::new(path.into(), Status::WT_MODIFIED);
+    file.lines_added = Some(lines);
+    file.lines_removed = Some(0);
+    file
+}
+
+fn group(
+    commit_type: CommitType,
+    scope: Option<&str>,
+    files: Vec<ChangedFile>,
+    description: &str,
+) -> ChangeGroup {
+    ChangeGroup::new(
+        commit_type,
+        scope.map(String::from),
+        files,
+        None,
+        description.into(),
+        vec![],
+    )
+}
+
+const CONFIG: GroupingConfig = GroupingConfig {
+    min_files: 2,
+    min_lines: 10,
+};
+
+fn paths(group: &ChangeGroup) -> Vec<&str> {
+    group.files.iter().map(|f| f.path.as_str()).collect()
+}
+
+#[test]
+fn test_off_by_default() {
+    let groups = vec![
+        group(CommitType::Feat, None, vec![file("src/a.rs", 1)], "add a"),
+        group(CommitType::Fix, None, vec![file("src/b.rs", 1)], "fix b"),
+    ];
+    let (groups, merged) = merge_small_groups(groups, &GroupingConfig::default());
+    assert_eq!(merged, 0);
+    assert_eq!(groups.len(), 2);
+}
+
+#[test]
+fn test_merge_into_related_group() {
+    let mut test = file("tests/login.rs", 3);
+    test.related_to = Some("src/auth/login.rs".into());
+    let groups = vec![
+        group(
+            CommitType::Feat,
+            Some("auth"),
+            vec![file("src/auth/login.rs", 40), file("src/auth/mod.rs", 5)],
+            "add login",
+        ),
+        group(
+            CommitType::Feat,
+            Some("ui"),
+            vec![file("src/ui/theme.rs", 30), file("src/ui/mod.rs", 2)],
+            "add themes",
+        ),
+        // References a file of the first group
+        group(CommitType::Test, None, vec![test], "test login"),
+        // Same scope as the second group
+        group(
+            CommitType::Fix,
+            Some("ui"),
+            vec![file("assets/ui.css", 1)],
+            "fix colors",
+        ),
+        // Shares src/auth with the first group
+        group(
+            CommitType::Docs,
+            None,
+            vec![file("src/auth/README.md", 2)],
+            "document auth",
+        ),
+    ];
+
+    let (groups, merged) = merge_small_groups(groups, &CONFIG);
+    assert_eq!(merged, 3);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(
+        paths(&groups[0]),
+        [
+            "src/auth/login.rs",
+            "src/auth/mod.rs",
+            "tests/login.rs",
+            "src/auth/README.md"
+        ]
+    );
+    assert_eq!(
+        paths(&groups[1]),
+        ["src/ui/theme.rs", "src/ui/mod.rs", "assets/ui.css"]
+    );
+    assert_eq!(groups[0].header(), "feat(auth): add login");
+    assert!(groups[0].body_lines.contains(&"test login".to_string()));
+    assert!(groups[1].body_lines.contains(&"fix colors".to_string()));
+}
+
+#[test]
+fn test_collect_unrelated_groups() {
+    let groups = vec![
+        group(
+            CommitType::Feat,
+            None,
+            vec![file("src/app.rs", 40), file("src/main.rs", 20)],
+            "add app",
+        ),
+        group(
+            CommitType::Docs,
+            None,
+            vec![file("README.md", 1)],
+            "fix typo",
+        ),
+        group(
+            CommitType::Docs,
+            None,
+            vec![file("docs/usage.md", 2)],
+            "fix link",
+        ),
+    ];
+    let (groups, merged) = merge_small_groups(groups, &CONFIG);
+    assert_eq!(merged, 1);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[1].commit_type, CommitType::Docs);
+    assert_eq!(groups[1].description, COLLECTED_DESCRIPTION);
+    assert_eq!(groups[1].body_lines, ["fix typo", "fix link"]);
+
+    // Without a common type the collected group is a chore
+    let groups = vec![
+        group(
+            CommitType::Docs,
+            None,
+            vec![file("README.md", 1)],
+            "fix typo",
+        ),
+        group(CommitType::Ci, None, vec![file(".ci.yml", 1)], "bump image"),
+    ];
+    let (groups, merged) = merge_small_groups(groups, &CONFIG);
+    assert_eq!(merged, 1);
+    assert_eq!(groups[0].header(), "chore: apply small changes");
+    assert_eq!(paths(&groups[0]), ["README.md", ".ci.yml"]);
+}
+
+#[test]
+fn test_single_unrelated_group_stays() {
+    let groups = vec![
+        group(
+            CommitType::Feat,
+            None,
+            vec![file("src/app.rs", 40)],
+            "add app",
+        ),
+        group(
+            CommitType::Docs,
+            None,
+            vec![file("README.md", 1)],
+            "fix typo",
+        ),
+    ];
+    let config = GroupingConfig {
+        min_files: 0,
+        min_lines: 10,
+    };
+    let (groups, merged) = merge_small_groups(groups, &config);
+    assert_eq!(merged, 0);
+    assert_eq!(groups[1].header(), "docs: fix typo");
+}
+
+#[test]
+fn test_notes_are_merged() {
+    let mut large = group(
+        CommitType::Feat,
+        Some("auth"),
+        vec![file("src/auth/login.rs", 40), file("src/auth/mod.rs", 5)],
+        "add login",
+    );
+    large.notes = Some("Needed for SSO".into());
+    let mut small = group(
+        CommitType::Fix,
+        Some("auth"),
+        vec![file("src/auth/token.rs", 1)],
+        "fix expiry",
+    );
+    small.notes = Some("Tokens expired a day early".into());
+    small.stale = true;
+
+    let (groups, _) = merge_small_groups(vec![large, small], &CONFIG);
+    assert_eq!(groups.len(), 1);
+    assert_eq!(
+        groups[0].notes.as_deref(),
+        Some("Needed for SSO\n\nTokens expired a day early")
+    );
+    assert!(groups[0].stale);
+}
@@ -0,0 +1,97 @@
+//! Tests for the pre-generation context command.
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use tempfile::TempDir;
+
+use commit_wizard::config::ContextConfig;
+use commit_wizard::context::{run_context_command, CommandContext, ContextProvider};
+use commit_wizard::provider::AiProvider;
+
+fn config(command: &str, max_lines: usize) -> ContextConfig {
+    ContextConfig {
+        command: Some(command.to_string()),
+        max_lines,
+        ..ContextConfig::default()
+    }
+}
+
+#[test]
+fn test_no_command_configured() {
+    let dir = TempDir::new().unwrap();
+    let result = run_context_command(&ContextConfig::default(), dir.path()).unwrap();
+    assert!(result.is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_output_is_captured_in_workdir() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("marker.txt"), "").unwrap();
+
+    let ctx = run_context_command(&config("ls; echo 'warning: unused' >&2", 50), dir.path())
+        .unwrap()
+        .unwrap();
+
+    assert!(ctx.success());
+    assert_eq!(ctx.output, "marker.txt\nwarning: unused");
+    assert!(!ctx.truncated);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_failing_command_keeps_tail() {
+    let dir = TempDir::new().unwrap();
+
+    let ctx = run_context_command(
+        &config("for i in 1 2 3 4 5; do echo line $i; done; exit 3", 2),
+        dir.path(),
+    )
+    .unwrap()
+    .unwrap();
+
+    assert!(!ctx.success());
+    assert_eq!(ctx.exit_code, Some(3));
+    assert_eq!(ctx.status_text(), "exit code 3");
+    assert_eq!(ctx.output, "line 4\nline 5");
+    assert!(ctx.truncated);
+}
+
+/// Provider recording the prompts it saw.
+struct RecordingProvider {
+    prompts: Arc<Mutex<Vec<String>>>,
+}
+
+impl AiProvider for RecordingProvider {
+    fn name(&self) -> &str {
+        "recording"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+        Ok("ok".to_string())
+    }
+}
+
+#[test]
+fn test_context_provider_prepends_output() {
+    let ctx = CommandContext::new("cargo test", "test result: FAILED. 1 failed", Some(101), 50);
+    let prompts = Arc::new(Mutex::new(Vec::new()));
+    let inner = Box::new(RecordingProvider {
+        prompts: prompts.clone(),
+    });
+    let provider = ContextProvider::new(inner, &ctx);
+
+    assert_eq!(provider.name(), "recording");
+    provider.complete("Generate a message").unwrap();
+
+    let prompts = prompts.lock().unwrap();
+    assert!(prompts[0].starts_with("PROJECT CHECK OUTPUT (`cargo test`, exit code 101):"));
+    assert!(prompts[0].contains("test result: FAILED"));
+    assert!(prompts[0].ends_with("Generate a message"));
+}
@@ -12,10 +12,12 @@
 //! Manual testing should be performed to verify CLI integration before releases.
 
 use commit_wizard::copilot::{
-    build_commit_message_prompt, build_grouping_prompt, check_copilot_availability_with_executor,
-    extract_response_between_markers, parse_commit_message, parse_commit_type,
+    build_commit_message_prompt, build_grouping_prompt, build_refine_prompt,
+    check_copilot_availability_with_executor, extract_response_between_markers,
+    parse_commit_message, parse_commit_type, parse_grouping_response, reconcile_groups,
     validate_no_duplicate_files,
 };
+use commit_wizard::prompt::DEFAULT_DIFF_TOKENS;
 use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
 use git2::Status;
 use std::collections::HashMap;
@@ -570,7 +572,7 @@ fn test_build_grouping_prompt_basic() {
     let files = vec![mock_file("src/api.rs"), mock_file("tests/api_tests.rs")];
     let diffs = HashMap::new();
 
-    let prompt = build_grouping_prompt(&files, None, &diffs);
+    let prompt = build_grouping_prompt(&files, None, &diffs, DEFAULT_DIFF_TOKENS);
 
     // Verify structure
     assert!(prompt.contains("Analyze these changed files"));
@@ -589,30 +591,82 @@ fn test_build_grouping_prompt_with_ticket() {
     let files = vec![mock_file("src/main.rs")];
     let diffs = HashMap::new();
 
-    let prompt = build_grouping_prompt(&files, Some("TICKET-123"), &diffs);
+    let prompt = build_grouping_prompt(&files, Some("TICKET-123"), &diffs, DEFAULT_DIFF_TOKENS);
 
     assert!(prompt.contains("Ticket/Issue: TICKET-123"));
 }
 
+#[test]
+fn test_build_grouping_prompt_with_fix_likelihood() {
+    let mut fixed = mock_file("src/parser.rs");
+    fixed.fix_likelihood = Some(0.72);
+    let files = vec![fixed, mock_file("src/main.rs")];
+
+    let prompt = build_grouping_prompt(&files, None, &HashMap::new(), DEFAULT_DIFF_TOKENS);
+
+    assert!(prompt.contains("src/parser.rs (fix likelihood: 72%)"));
+    assert!(prompt.contains("prefer 'fix' over 'feat'"));
+
+    // Without scores the hint is left out
+    let prompt = build_grouping_prompt(
+        &[mock_file("src/main.rs")],
+        None,
+        &HashMap::new(),
+        DEFAULT_DIFF_TOKENS,
+    );
+    assert!(!prompt.contains("fix likelihood"));
+}
+
 #[test]
 fn test_build_grouping_prompt_with_diffs() {
     let files = vec![mock_file("src/api.rs")];
     let mut diffs = HashMap::new();
     diffs.insert("src/api.rs".to_string(), "diff content here".to_string());
 
-    let prompt = build_grouping_prompt(&files, None, &diffs);
+    let prompt = build_grouping_prompt(&files, None, &diffs, DEFAULT_DIFF_TOKENS);
 
     assert!(prompt.contains("DIFF PREVIEW:"));
     assert!(prompt.contains("src/api.rs:"));
     assert!(prompt.contains("diff content here"));
 }
 
+#[test]
+fn test_parse_grouping_response_accepts_array_and_object() {
+    let group = r#"{"type": "fix", "scope": "core", "description": "handle empty input", "files": ["src/lib.rs"], "extra": 1}"#;
+
+    let from_array = parse_grouping_response(&format!("[{}]", group)).unwrap();
+    let from_object = parse_grouping_response(&format!(r#"{{"groups": [{}]}}"#, group)).unwrap();
+
+    assert_eq!(from_array, from_object);
+    assert_eq!(from_array[0].commit_type, "fix");
+    assert_eq!(from_array[0].scope.as_deref(), Some("core"));
+    assert!(from_array[0].body_lines.is_empty());
+}
+
+#[test]
+fn test_parse_grouping_response_reports_errors() {
+    let err = parse_grouping_response("[{\"type\": \"fix\",}]").unwrap_err();
+    assert!(format!("{:#}", err).contains("not valid JSON: trailing comma at line 1"));
+
+    let err = parse_grouping_response(r#"{"result": []}"#).unwrap_err();
+    assert!(err.to_string().contains("no \"groups\" array"));
+
+    let err = parse_grouping_response(
+        r#"[{"type": "fix", "description": "a", "files": ["a"]}, {"type": "docs", "description": "b"}]"#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "Invalid group #2 in AI grouping response: missing field `files`"
+    );
+}
+
 #[test]
 fn test_build_grouping_prompt_json_format_example() {
     let files = vec![mock_file("src/test.rs")];
     let diffs = HashMap::new();
 
-    let prompt = build_grouping_prompt(&files, None, &diffs);
+    let prompt = build_grouping_prompt(&files, None, &diffs, DEFAULT_DIFF_TOKENS);
 
     // Verify JSON example structure
     assert!(prompt.contains("\"type\": \"feat\""));
@@ -628,7 +682,7 @@ fn test_build_commit_message_prompt_basic() {
     let files = vec![mock_file("src/api.rs")];
     let group = mock_group(CommitType::Feat, Some("api".to_string()), files.clone());
 
-    let prompt = build_commit_message_prompt(&group, &files, None);
+    let prompt = build_commit_message_prompt(&group, &files, None, DEFAULT_DIFF_TOKENS);
 
     assert!(prompt.contains("Generate a conventional commit message"));
     assert!(prompt.contains("REQUIREMENTS:"));
@@ -639,35 +693,75 @@ fn test_build_commit_message_prompt_basic() {
     assert!(prompt.contains(END_MARKER));
 }
 
+#[test]
+fn test_build_refine_prompt() {
+    let prompt = build_refine_prompt("  mention the migration \n");
+
+    assert!(prompt.contains("feedback:\nmention the migration\n"));
+    assert!(prompt.contains(START_MARKER));
+    assert!(prompt.contains(END_MARKER));
+}
+
 #[test]
 fn test_build_commit_message_prompt_with_ticket() {
     let files = vec![mock_file("src/main.rs")];
     let mut group = mock_group(CommitType::Fix, None, files.clone());
     group.ticket = Some("ISSUE-456".to_string());
 
-    let prompt = build_commit_message_prompt(&group, &files, None);
+    let prompt = build_commit_message_prompt(&group, &files, None, DEFAULT_DIFF_TOKENS);
 
     assert!(prompt.contains("Ticket number: ISSUE-456"));
 }
 
+#[test]
+fn test_build_commit_message_prompt_with_notes() {
+    let files = vec![mock_file("src/main.rs")];
+    let mut group = mock_group(CommitType::Fix, None, files.clone());
+
+    let prompt = build_commit_message_prompt(&group, &files, None, DEFAULT_DIFF_TOKENS);
+    assert!(!prompt.contains("NOTES FROM THE AUTHOR"));
+
+    group.notes = Some("Support asked for this after #42".to_string());
+    let prompt = build_commit_message_prompt(&group, &files, None, DEFAULT_DIFF_TOKENS);
+    let notes = prompt.find("Support asked for this after #42").unwrap();
+    assert!(prompt.find("NOTES FROM THE AUTHOR").unwrap() < notes);
+    assert!(notes < prompt.find("CHANGED FILES:").unwrap());
+}
+
 #[test]
 fn test_build_commit_message_prompt_with_diff() {
     let files = vec![mock_file("src/test.rs")];
     let group = mock_group(CommitType::Refactor, None, files.clone());
     let diff = "diff --git a/src/test.rs\n+new line\n-old line";
 
-    let prompt = build_commit_message_prompt(&group, &files, Some(diff));
+    let prompt = build_commit_message_prompt(&group, &files, Some(diff), DEFAULT_DIFF_TOKENS);
 
     assert!(prompt.contains("DIFF:"));
     assert!(prompt.contains("diff --git"));
 }
 
+#[test]
+fn test_build_commit_message_prompt_condenses_multibyte_diff() {
+    let files = vec![mock_file("docs/README.de.md")];
+    let group = mock_group(CommitType::Docs, None, files.clone());
+    let diff = format!(
+        "diff --git a/docs/README.de.md b/docs/README.de.md\n@@ -1,400 +1,400 @@\n{}",
+        "+Größenänderung für Übersetzungen\n".repeat(400)
+    );
+
+    let prompt = build_commit_message_prompt(&group, &files, Some(&diff), 100);
+
+    assert!(prompt.contains("diff --git a/docs/README.de.md"));
+    assert!(prompt.contains("lines omitted"));
+    assert!(prompt.len() < diff.len());
+}
+
 #[test]
 fn test_build_commit_message_prompt_requirements() {
     let files = vec![mock_file("src/test.rs")];
     let group = mock_group(CommitType::Feat, None, files.clone());
 
-    let prompt = build_commit_message_prompt(&group, &files, None);
+    let prompt = build_commit_message_prompt(&group, &files, None, DEFAULT_DIFF_TOKENS);
 
     // Verify key requirements are mentioned
     assert!(prompt.contains("Use imperative mood"));
@@ -685,7 +779,7 @@ fn test_build_grouping_prompt_empty_files() {
     let files: Vec<ChangedFile> = vec![];
     let diffs = HashMap::new();
 
-    let prompt = build_grouping_prompt(&files, None, &diffs);
+    let prompt = build_grouping_prompt(&files, None, &diffs, DEFAULT_DIFF_TOKENS);
 
     // Should still have structure even with empty files
     assert!(prompt.contains("CHANGED FILES:"));
@@ -697,7 +791,7 @@ fn test_build_commit_message_prompt_no_scope() {
     let files = vec![mock_file("README.md")];
     let group = mock_group(CommitType::Docs, None, files.clone());
 
-    let prompt = build_commit_message_prompt(&group, &files, None);
+    let prompt = build_commit_message_prompt(&group, &files, None, DEFAULT_DIFF_TOKENS);
 
     assert!(prompt.contains("Type: docs"));
     // Should not have "Scope:" line when scope is None
@@ -875,3 +969,94 @@ fn test_check_copilot_auth_error_empty_output() {
     // Should return true with empty output and success status
     assert!(result);
 }
+
+#[test]
+fn test_reconcile_groups_prefers_same_directory() {
+    let mut groups = vec![
+        mock_group(
+            CommitType::Feat,
+            Some("ui".to_string()),
+            vec![mock_file("src/ui/panel.rs")],
+        ),
+        mock_group(
+            CommitType::Test,
+            None,
+            vec![mock_file("tests/panel_tests.rs")],
+        ),
+    ];
+    let files = vec![
+        mock_file("src/ui/panel.rs"),
+        mock_file("tests/panel_tests.rs"),
+        mock_file("tests/layout_tests.rs"),
+        mock_file("src/ui/layout.rs"),
+    ];
+
+    assert_eq!(reconcile_groups(&mut groups, &files, None), 2);
+
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].files[1].path, "src/ui/layout.rs");
+    assert_eq!(groups[1].files[1].path, "tests/layout_tests.rs");
+    validate_no_duplicate_files(&groups).unwrap();
+}
+
+#[test]
+fn test_reconcile_groups_creates_groups_for_unmatched_files() {
+    let mut groups = vec![mock_group(
+        CommitType::Feat,
+        Some("api".to_string()),
+        vec![mock_file("src/api/users.rs")],
+    )];
+    let files = vec![
+        mock_file("src/api/users.rs"),
+        mock_file(".github/workflows/ci.yml"),
+    ];
+
+    assert_eq!(
+        reconcile_groups(&mut groups, &files, Some("ABC-1".to_string())),
+        1
+    );
+
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[1].commit_type, CommitType::Ci);
+    assert_eq!(groups[1].ticket.as_deref(), Some("ABC-1"));
+}
+
+#[test]
+fn test_reconcile_groups_without_missing_files() {
+    let mut groups = vec![mock_group(
+        CommitType::Docs,
+        None,
+        vec![mock_file("README.md")],
+    )];
+
+    assert_eq!(
+        reconcile_groups(&mut groups, &[mock_file("README.md")], None),
+        0
+    );
+    assert_eq!(groups.len(), 1);
+}
+
+#[test]
+fn test_prompts_mark_deleted_files_with_former_purpose() {
+    let mut deleted = ChangedFile::new("src/legacy.rs".to_string(), Status::INDEX_DELETED);
+    deleted.former_purpose = Some("Parser for the legacy INI config format".to_string());
+    let files = vec![deleted, mock_file("src/main.rs")];
+    let group = mock_group(CommitType::Refactor, None, files.clone());
+
+    let prompt = build_commit_message_prompt(&group, &files, None, DEFAULT_DIFF_TOKENS);
+    assert!(prompt.contains(
+        "  - src/legacy.rs (deleted; former purpose: Parser for the legacy INI config format)\n"
+    ));
+    assert!(prompt.contains("  - src/main.rs\n"));
+    assert!(prompt.contains("Deleted files are removed entirely"));
+
+    let prompt = build_grouping_prompt(&files, None, &HashMap::new(), DEFAULT_DIFF_TOKENS);
+    assert!(prompt.contains(
+        "deleted - src/legacy.rs (former purpose: Parser for the legacy INI config format)"
+    ));
+    assert!(prompt.contains("Deleted files are removed entirely"));
+
+    let unchanged = vec![mock_file("src/main.rs")];
+    let prompt = build_grouping_prompt(&unchanged, None, &HashMap::new(), DEFAULT_DIFF_TOKENS);
+    assert!(!prompt.contains("Deleted files"));
+}
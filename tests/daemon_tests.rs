@@ -0,0 +1,87 @@
+//! Tests for the status daemon
+#![cfg(unix)]
+
+use std::fs;
+use std::path::Path;
+use std::thread;
+
+use commit_wizard::daemon::{query_status, socket_path, stop, Daemon, RepoStatus};
+use commit_wizard::git::{discover_repo, RepoContext};
+use git2::{Repository, Signature};
+use tempfile::TempDir;
+
+/// Creates a repository with `a.txt` committed and modified, and an
+/// untracked `new.txt`.
+fn setup() -> (TempDir, RepoContext) {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+    {
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+    }
+    fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+    fs::write(dir.path().join("new.txt"), "new\n").unwrap();
+    let ctx = discover_repo(dir.path()).unwrap();
+    (dir, ctx)
+}
+
+fn paths(status: &RepoStatus) -> (Vec<String>, Vec<String>) {
+    let paths =
+        |files: &[commit_wizard::ChangedFile]| files.iter().map(|f| f.path.clone()).collect();
+    (paths(&status.tracked), paths(&status.untracked))
+}
+
+#[test]
+fn test_without_daemon_the_status_is_collected_directly() {
+    let (_dir, ctx) = setup();
+    assert!(query_status(&ctx).unwrap().is_none());
+    assert!(!stop(&ctx).unwrap());
+}
+
+#[test]
+fn test_daemon_serves_the_status_until_stopped() {
+    let (_dir, ctx) = setup();
+    let daemon = Daemon::bind(&ctx).unwrap();
+    assert!(socket_path(&ctx.git_dir).exists());
+    let server = thread::spawn(move || daemon.serve());
+
+    // A second daemon for the same worktree is refused
+    assert!(Daemon::bind(&ctx).is_err());
+
+    let direct = RepoStatus::collect(&ctx.open().unwrap()).unwrap();
+    let served = query_status(&ctx).unwrap().unwrap();
+    assert_eq!(paths(&served), paths(&direct));
+    assert_eq!(served.tracked[0].status, direct.tracked[0].status);
+
+    // Staging a file changes the index, so the status is collected again
+    let repo = ctx.open().unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("new.txt")).unwrap();
+    index.write().unwrap();
+    let served = query_status(&ctx).unwrap().unwrap();
+    assert!(served.tracked.iter().any(|f| f.path == "new.txt"));
+    assert!(served.untracked.is_empty());
+
+    assert!(stop(&ctx).unwrap());
+    server.join().unwrap().unwrap();
+    assert!(!socket_path(&ctx.git_dir).exists());
+}
+
+#[test]
+fn test_stale_socket_is_replaced() {
+    let (_dir, ctx) = setup();
+    fs::write(socket_path(&ctx.git_dir), "").unwrap();
+    assert!(query_status(&ctx).unwrap().is_none());
+
+    let daemon = Daemon::bind(&ctx).unwrap();
+    let server = thread::spawn(move || daemon.serve());
+    assert!(query_status(&ctx).unwrap().is_some());
+    assert!(stop(&ctx).unwrap());
+    server.join().unwrap().unwrap();
+}
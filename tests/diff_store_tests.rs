@@ -0,0 +1,123 @@
+//! Tests for the lazily loaded, size-limited diff store
+
+use std::fs;
+use std::path::Path;
+
+use commit_wizard::diff_store::DiffStore;
+use commit_wizard::git::discover_repo;
+use git2::{Repository, Signature};
+use tempfile::TempDir;
+
+/// Creates a repository with `a.txt`, `b.txt`, and `same.txt` committed,
+/// then modifies `a.txt` and `b.txt`.
+fn setup() -> (TempDir, DiffStore) {
+    setup_with_budget(1 << 20)
+}
+
+fn setup_with_budget(budget: usize) -> (TempDir, DiffStore) {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    for name in ["a.txt", "b.txt", "same.txt"] {
+        fs::write(dir.path().join(name), "one\n").unwrap();
+    }
+    {
+        let mut index = repo.index().unwrap();
+        for name in ["a.txt", "b.txt", "same.txt"] {
+            index.add_path(Path::new(name)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+    }
+    fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+    fs::write(dir.path().join("b.txt"), "two\n").unwrap();
+
+    let store = DiffStore::new(discover_repo(dir.path()).unwrap(), budget);
+    (dir, store)
+}
+
+#[test]
+fn test_get_loads_and_caches_diffs() {
+    let (_dir, store) = setup();
+    assert!(!store.is_cached("a.txt"));
+
+    let diff = store.get("a.txt").unwrap().unwrap();
+    assert_eq!((diff.lines_added, diff.lines_removed), (1, 0));
+    assert!(diff.patch.contains("+two"));
+    assert!(store.is_cached("a.txt"));
+    assert!(store.cached_bytes() > 0);
+
+    // Files without changes are cached as such
+    assert!(store.get("same.txt").unwrap().is_none());
+    assert!(store.is_cached("same.txt"));
+}
+
+#[test]
+fn test_get_many_loads_missing_diffs() {
+    let (_dir, store) = setup();
+    store.get("a.txt").unwrap();
+
+    let diffs = store.get_many(["a.txt", "b.txt", "same.txt"]).unwrap();
+    assert_eq!(diffs.len(), 2);
+    assert_eq!(diffs["b.txt"].lines_removed, 1);
+}
+
+#[test]
+fn test_budget_evicts_least_recently_used() {
+    // Measure what each diff takes in the cache
+    let (_dir, store) = setup();
+    store.get("a.txt").unwrap();
+    let a_size = store.cached_bytes();
+    store.get("b.txt").unwrap();
+    let b_size = store.cached_bytes() - a_size;
+
+    // Room for both diffs, but not for all three
+    let (_dir, store) = setup_with_budget(a_size + b_size);
+    store.get("a.txt").unwrap();
+    store.get("b.txt").unwrap();
+    store.get("a.txt").unwrap();
+    store.get("same.txt").unwrap();
+
+    assert!(store.is_cached("a.txt"));
+    assert!(!store.is_cached("b.txt"));
+    assert!(store.is_cached("same.txt"));
+    assert!(store.cached_bytes() <= a_size + b_size);
+}
+
+#[test]
+fn test_diffs_larger_than_the_budget_are_returned_but_not_cached() {
+    let (_dir, store) = setup_with_budget(16);
+    let diff = store.get("a.txt").unwrap();
+    assert!(diff.is_some());
+    assert!(!store.is_cached("a.txt"));
+    assert_eq!(store.cached_bytes(), 0);
+}
+
+#[test]
+fn test_invalidate_reloads_edited_files() {
+    let (dir, store) = setup();
+    assert_eq!(store.get("a.txt").unwrap().unwrap().lines_added, 1);
+
+    fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+    assert_eq!(store.get("a.txt").unwrap().unwrap().lines_added, 1);
+
+    store.invalidate(["a.txt"]);
+    assert_eq!(store.get("a.txt").unwrap().unwrap().lines_added, 2);
+
+    store.clear();
+    assert_eq!(store.cached_bytes(), 0);
+}
+
+#[test]
+fn test_condensed_patches_fit_the_token_budget() {
+    let (dir, store) = setup();
+    let long: String = (0..500).map(|i| format!("line {}\n", i)).collect();
+    fs::write(dir.path().join("a.txt"), long).unwrap();
+
+    let patches = store.condensed_patches(&["a.txt", "b.txt"], 50).unwrap();
+    assert_eq!(patches.len(), 2);
+    assert!(patches["a.txt"].len() < 500);
+    assert!(patches["b.txt"].contains("+two"));
+}
@@ -1,4 +1,7 @@
-use commit_wizard::editor::CommitMessageEditor;
+use commit_wizard::editor::{
+    edit_externally, get_editor, validate_editor_command, CommitMessageEditor,
+};
+use commit_wizard::scope::ScopeVocabulary;
 use ratatui::crossterm::event::{Event as CrosstermEvent, KeyCode, KeyEvent, KeyModifiers};
 
 #[test]
@@ -112,3 +115,76 @@ fn test_empty_lines() {
     let editor = CommitMessageEditor::new(text.to_string());
     assert_eq!(editor.text(), text);
 }
+
+#[test]
+fn test_tab_completes_and_cycles_scopes() {
+    let mut editor = CommitMessageEditor::new("feat(a): add login\n\n- body".to_string());
+    editor.set_scope_vocabulary(ScopeVocabulary::from_messages(
+        [
+            "fix(auth): a",
+            "fix(auth): b",
+            "feat(api): c",
+            "docs(readme): d",
+        ],
+        1,
+    ));
+    editor.state_mut().cursor = edtui::Index2::new(0, 6);
+
+    let tab = CrosstermEvent::Key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+    assert!(editor.handle_event(tab.clone()).unwrap());
+    assert_eq!(editor.text(), "feat(auth): add login\n\n- body");
+    editor.handle_event(tab.clone()).unwrap();
+    assert_eq!(editor.text(), "feat(api): add login\n\n- body");
+    editor.handle_event(tab).unwrap();
+    assert_eq!(editor.text(), "feat(auth): add login\n\n- body");
+
+    // Outside the parentheses nothing is completed
+    editor.state_mut().cursor = edtui::Index2::new(0, 14);
+    assert!(!editor.complete_scope());
+    editor.state_mut().cursor = edtui::Index2::new(2, 0);
+    assert!(!editor.complete_scope());
+}
+
+#[test]
+fn test_validate_editor_command() {
+    assert!(validate_editor_command("vim && rm -rf ~").is_err());
+    assert!(validate_editor_command("$(evil)").is_err());
+    assert!(validate_editor_command("   ").is_err());
+    let err = validate_editor_command("no-such-editor-commit-wizard --wait").unwrap_err();
+    assert!(err.to_string().contains("not found"));
+}
+
+/// Writes an executable script standing in for an editor.
+#[cfg(unix)]
+fn fake_editor(dir: &std::path::Path, script: &str) -> String {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join("editor.sh");
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_edit_externally_returns_the_edited_message() {
+    let dir = tempfile::tempdir().unwrap();
+    let editor = fake_editor(
+        dir.path(),
+        r#"grep -q '^feat: old' "$1" && printf 'fix: edited\n\n- body\n# comment\n\n' > "$1""#,
+    );
+    assert_eq!(get_editor(Some(&editor)).unwrap(), editor);
+
+    let edited = edit_externally(&editor, "feat: old").unwrap();
+    assert_eq!(edited, "fix: edited\n\n- body");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_edit_externally_reports_a_failing_editor() {
+    let dir = tempfile::tempdir().unwrap();
+    let editor = fake_editor(dir.path(), "exit 3");
+
+    let err = edit_externally(&editor, "feat: old").unwrap_err();
+    assert!(err.to_string().contains("was not changed"));
+}
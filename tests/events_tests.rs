@@ -0,0 +1,368 @@
+//! Tests for the event bus running git operations in the background.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use git2::{Repository, Signature, Status};
+use tempfile::TempDir;
+
+use commit_wizard::config::TimeoutConfig;
+use commit_wizard::events::{Action, ActionKind, Event, EventBus};
+use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+
+fn create_test_repo() -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Test User").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+
+    fs::write(tmp.path().join("README.md"), "# Test Repo").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("README.md")).unwrap();
+    index.write().unwrap();
+
+    let tree_id = index.write_tree().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .unwrap();
+
+    tmp
+}
+
+fn group(path: &str, description: &str) -> ChangeGroup {
+    ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        vec![ChangedFile::new(path.to_string(), Status::WT_NEW)],
+        None,
+        description.to_string(),
+        vec![],
+    )
+}
+
+/// Collects events until the final event of the running action.
+fn wait_for_events(bus: &mut EventBus) -> Vec<Event> {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let mut events = Vec::new();
+    while Instant::now() < deadline {
+        match bus.try_event() {
+            Some(event) => {
+                let done = event.is_final();
+                events.push(event);
+                if done {
+                    return events;
+                }
+            }
+            None => std::thread::sleep(Duration::from_millis(10)),
+        }
+    }
+    panic!("no final event, got {:?}", events);
+}
+
+#[test]
+fn test_commit_batch_reports_each_group() {
+    let tmp = create_test_repo();
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+    fs::write(tmp.path().join("b.txt"), "b\n").unwrap();
+
+    let mut bus = EventBus::start(tmp.path().to_path_buf(), TimeoutConfig::default());
+    bus.dispatch(Action::Commit(vec![
+        (0, group("a.txt", "add a")),
+        (2, group("b.txt", "add b")),
+    ]))
+    .unwrap();
+    assert!(bus.is_busy());
+    assert_eq!(
+        bus.running().map(|(kind, _)| kind),
+        Some(ActionKind::Commit)
+    );
+
+    let events = wait_for_events(&mut bus);
+    assert!(!bus.is_busy());
+    assert_eq!(events.len(), 3);
+    match &events[..] {
+        [Event::Committed(first), Event::Committed(second), Event::CommitFinished {
+            committed,
+            error: None,
+            cancelled: false,
+        }] => {
+            assert_eq!(first.index, 0);
+            assert_eq!(second.index, 2);
+            assert_eq!(second.header, "feat: add b");
+            assert_eq!(committed, &vec![first.clone(), second.clone()]);
+        }
+        other => panic!("unexpected events: {:?}", other),
+    }
+
+    let repo = Repository::open(tmp.path()).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert!(head.message().unwrap().starts_with("feat: add b"));
+    match &events[1] {
+        Event::Committed(second) => assert_eq!(second.commit, Some(head.id())),
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[test]
+fn test_cherry_pick_reports_each_branch() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+    let base = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("release/1.0", &base, false).unwrap();
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+
+    let mut bus = EventBus::start(tmp.path().to_path_buf(), TimeoutConfig::default());
+    bus.dispatch(Action::Commit(vec![(0, group("a.txt", "add a"))]))
+        .unwrap();
+    let commit = match wait_for_events(&mut bus).last() {
+        Some(Event::CommitFinished { committed, .. }) => committed[0].commit.unwrap(),
+        other => panic!("unexpected event: {:?}", other),
+    };
+
+    bus.dispatch(Action::CherryPick {
+        commits: vec![commit],
+        branches: vec!["release/1.0".to_string(), "missing".to_string()],
+    })
+    .unwrap();
+    assert_eq!(
+        bus.running().map(|(kind, _)| kind),
+        Some(ActionKind::CherryPick)
+    );
+
+    match wait_for_events(&mut bus).as_slice() {
+        [Event::CherryPicked { results }] => {
+            assert_eq!(results.len(), 2);
+            let (branch, pick) = &results[0];
+            assert_eq!(branch, "release/1.0");
+            assert_eq!(pick.as_ref().unwrap().created.len(), 1);
+            assert!(results[1].1.is_err());
+        }
+        other => panic!("unexpected events: {:?}", other),
+    }
+}
+
+#[test]
+fn test_commit_batch_stops_at_first_failure() {
+    let tmp = create_test_repo();
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+
+    let mut bus = EventBus::start(tmp.path().to_path_buf(), TimeoutConfig::default());
+    bus.dispatch(Action::Commit(vec![
+        (0, group("../outside.txt", "escape")),
+        (1, group("a.txt", "add a")),
+    ]))
+    .unwrap();
+
+    match wait_for_events(&mut bus).as_slice() {
+        [Event::CommitFinished {
+            committed,
+            error: Some(error),
+            cancelled: false,
+        }] => {
+            assert!(committed.is_empty());
+            assert!(error.to_string().contains("Invalid file path"));
+        }
+        other => panic!("unexpected events: {:?}", other),
+    }
+}
+
+#[test]
+fn test_dispatch_rejects_concurrent_actions() {
+    let tmp = create_test_repo();
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+
+    let mut bus = EventBus::start(tmp.path().to_path_buf(), TimeoutConfig::default());
+    bus.dispatch(Action::Commit(vec![(0, group("a.txt", "add a"))]))
+        .unwrap();
+
+    let error = bus
+        .dispatch(Action::Push {
+            remote: "origin".to_string(),
+            branch: "main".to_string(),
+            pr_base: None,
+        })
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Committing is still in progress");
+
+    wait_for_events(&mut bus);
+    assert!(bus.try_event().is_none());
+}
+
+#[test]
+fn test_push_failure_is_reported() {
+    let tmp = create_test_repo();
+
+    let mut bus = EventBus::start(tmp.path().to_path_buf(), TimeoutConfig::default());
+    bus.dispatch(Action::Push {
+        remote: "origin".to_string(),
+        branch: "main".to_string(),
+        pr_base: Some("upstream".to_string()),
+    })
+    .unwrap();
+
+    match wait_for_events(&mut bus).as_slice() {
+        [Event::Pushed {
+            remote,
+            pr_base,
+            result: Err(_),
+            ..
+        }] => {
+            assert_eq!(remote, "origin");
+            assert_eq!(pr_base.as_deref(), Some("upstream"));
+        }
+        other => panic!("unexpected events: {:?}", other),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cancel_kills_slow_hook_and_restores_index() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = create_test_repo();
+    let hook = tmp.path().join(".git/hooks/pre-commit");
+    fs::create_dir_all(hook.parent().unwrap()).unwrap();
+    fs::write(&hook, "#!/bin/sh\nsleep 30\n").unwrap();
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+
+    let mut bus = EventBus::start(tmp.path().to_path_buf(), TimeoutConfig::default());
+    let started = Instant::now();
+    bus.dispatch(Action::Commit(vec![(0, group("a.txt", "add a"))]))
+        .unwrap();
+
+    // Wait until the hook runs, which is when git holds the index lock
+    let lock = tmp.path().join(".git/index.lock");
+    while !lock.exists() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    bus.cancel();
+
+    match wait_for_events(&mut bus).as_slice() {
+        [Event::CommitFinished {
+            committed,
+            error: None,
+            cancelled: true,
+        }] => assert!(committed.is_empty()),
+        other => panic!("unexpected events: {:?}", other),
+    }
+    assert!(started.elapsed() < Duration::from_secs(20));
+    assert!(!lock.exists());
+
+    let repo = Repository::open(tmp.path()).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.message().unwrap(), "Initial commit");
+    let index = repo.index().unwrap();
+    assert!(index.get_path(Path::new("a.txt"), 0).is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_slow_hook_times_out_with_configured_limit() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = create_test_repo();
+    let hook = tmp.path().join(".git/hooks/pre-commit");
+    fs::create_dir_all(hook.parent().unwrap()).unwrap();
+    fs::write(&hook, "#!/bin/sh\nsleep 30\n").unwrap();
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+
+    let timeouts = TimeoutConfig {
+        commit: 1,
+        ..TimeoutConfig::default()
+    };
+    let mut bus = EventBus::start(tmp.path().to_path_buf(), timeouts);
+    let started = Instant::now();
+    bus.dispatch(Action::Commit(vec![(0, group("a.txt", "add a"))]))
+        .unwrap();
+    let remaining = bus.remaining().unwrap();
+    assert!(remaining <= Duration::from_secs(11), "{:?}", remaining);
+
+    match wait_for_events(&mut bus).as_slice() {
+        [Event::CommitFinished {
+            committed,
+            error: Some(error),
+            cancelled: false,
+        }] => {
+            assert!(committed.is_empty());
+            assert!(format!("{:#}", error).contains("timed out after 1s"));
+        }
+        other => panic!("unexpected events: {:?}", other),
+    }
+    assert!(started.elapsed() < Duration::from_secs(20));
+    assert!(bus.remaining().is_none());
+}
+
+#[test]
+fn test_diff_from_store_includes_unstaged_changes() {
+    use commit_wizard::diff_store::DiffStore;
+    use commit_wizard::git::discover_repo;
+    use std::sync::Arc;
+
+    let tmp = create_test_repo();
+    fs::write(tmp.path().join("README.md"), "# Test Repo\nMore\n").unwrap();
+    let store = Arc::new(DiffStore::new(discover_repo(tmp.path()).unwrap(), 1 << 20));
+
+    let mut bus = EventBus::start_with_diff_store(
+        tmp.path().to_path_buf(),
+        TimeoutConfig::default(),
+        Some(Arc::clone(&store)),
+    );
+    bus.dispatch(Action::Diff {
+        path: "README.md".to_string(),
+    })
+    .unwrap();
+
+    match &wait_for_events(&mut bus)[..] {
+        [Event::Diff {
+            path,
+            result: Ok(diff),
+        }] => {
+            assert_eq!(path, "README.md");
+            assert!(diff.contains("+More"), "{}", diff);
+        }
+        other => panic!("unexpected events: {:?}", other),
+    }
+    assert!(store.is_cached("README.md"));
+}
+
+#[test]
+fn test_combined_diff_of_several_files() {
+    use commit_wizard::diff_store::DiffStore;
+    use commit_wizard::git::discover_repo;
+    use std::sync::Arc;
+
+    let tmp = create_test_repo();
+    fs::write(tmp.path().join("README.md"), "# Test Repo\nMore\n").unwrap();
+    fs::write(tmp.path().join("notes.txt"), "new notes\n").unwrap();
+    let store = Arc::new(DiffStore::new(discover_repo(tmp.path()).unwrap(), 1 << 20));
+
+    let mut bus = EventBus::start_with_diff_store(
+        tmp.path().to_path_buf(),
+        TimeoutConfig::default(),
+        Some(store),
+    );
+    bus.dispatch(Action::CombinedDiff {
+        paths: vec!["README.md".to_string(), "notes.txt".to_string()],
+    })
+    .unwrap();
+    assert_eq!(bus.running().map(|(kind, _)| kind), Some(ActionKind::Diff));
+
+    match &wait_for_events(&mut bus)[..] {
+        [Event::CombinedDiff {
+            paths,
+            result: Ok(diff),
+        }] => {
+            assert_eq!(paths.len(), 2);
+            let more = diff.find("+More").expect("README.md diff");
+            let notes = diff.find("+new notes").expect("notes.txt diff");
+            assert!(more < notes, "{}", diff);
+        }
+        other => panic!("unexpected events: {:?}", other),
+    }
+}
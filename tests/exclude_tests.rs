@@ -0,0 +1,203 @@
+//! Tests for keeping excluded files out of AI prompts.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use git2::Status;
+
+use commit_wizard::exclude::{strip_excluded_diffs, ExcludingProvider, PathFilter};
+use commit_wizard::provider::AiProvider;
+use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+
+/// Provider answering every prompt with `response` and recording prompts.
+struct CapturingProvider {
+    response: String,
+    prompts: Arc<Mutex<Vec<String>>>,
+}
+
+impl AiProvider for CapturingProvider {
+    fn name(&self) -> &str {
+        "capturing"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+        Ok(self.response.clone())
+    }
+}
+
+fn excluding(response: &str, patterns: &[&str]) -> (ExcludingProvider, Arc<Mutex<Vec<String>>>) {
+    let prompts = Arc::new(Mutex::new(Vec::new()));
+    let inner = CapturingProvider {
+        response: response.to_string(),
+        prompts: prompts.clone(),
+    };
+    let filter = PathFilter::new(patterns).unwrap();
+    (ExcludingProvider::new(Box::new(inner), filter), prompts)
+}
+
+fn file(path: &str) -> ChangedFile {
+    ChangedFile::new(path.to_string(), Status::INDEX_MODIFIED)
+}
+
+#[test]
+fn test_glob_patterns() {
+    let filter = PathFilter::new(&["secrets/**", "*.pem", "vendor/", "/config/?.env"]).unwrap();
+
+    assert!(filter.matches("secrets/db.env"));
+    assert!(filter.matches("secrets/prod/eu/db.env"));
+    assert!(filter.matches("certs/server.pem"));
+    assert!(filter.matches("server.pem"));
+    assert!(filter.matches("vendor/lib/mod.go"));
+    assert!(filter.matches("third_party/vendor/mod.go"));
+    assert!(filter.matches("config/a.env"));
+
+    assert!(!filter.matches("src/secrets.rs"));
+    assert!(!filter.matches("app/secrets/db.env"));
+    assert!(!filter.matches("server.pem.md"));
+    assert!(!filter.matches("vendoring.md"));
+    assert!(!filter.matches("config/ab.env"));
+    assert!(!filter.matches("app/config/a.env"));
+    assert!(PathFilter::new(&[] as &[&str]).unwrap().is_empty());
+}
+
+#[test]
+fn test_double_star_in_the_middle() {
+    let filter = PathFilter::new(&["docs/**/internal.md"]).unwrap();
+
+    assert!(filter.matches("docs/internal.md"));
+    assert!(filter.matches("docs/a/b/internal.md"));
+    assert!(!filter.matches("docs/public.md"));
+}
+
+#[test]
+fn test_excluded_sections_are_stripped_from_diffs() {
+    let diff = concat!(
+        "diff --git a/src/lib.rs b/src/lib.rs\n",
+        "+pub mod db;\n",
+        "diff --git a/secrets/db.env b/secrets/db.env\n",
+        "+PASSWORD=hunter2\n",
+        "diff --git a/old.pem b/keys/new.txt\n",
+        "+key material\n",
+        "diff --git a/README.md b/README.md\n",
+        "+## Database\n",
+    );
+    let filter = PathFilter::new(&["secrets/**", "*.pem"]).unwrap();
+
+    let kept = strip_excluded_diffs(diff, |path| filter.matches(path));
+
+    assert_eq!(
+        kept,
+        concat!(
+            "diff --git a/src/lib.rs b/src/lib.rs\n",
+            "+pub mod db;\n",
+            "diff --git a/README.md b/README.md\n",
+            "+## Database\n",
+        )
+    );
+}
+
+#[test]
+fn test_grouping_prompt_omits_excluded_files() {
+    let response = r#"[{"type": "feat", "scope": "db", "description": "add database", "files": ["src/db.rs"]}]"#;
+    let (provider, prompts) = excluding(response, &["secrets/**", "*.pem"]);
+    let files = vec![
+        file("src/db.rs"),
+        file("secrets/db.env"),
+        ChangedFile::new("keys/server.key".to_string(), Status::INDEX_RENAMED)
+            .with_old_path(Some("server.pem".to_string())),
+    ];
+    let diffs: HashMap<String, String> = [
+        ("src/db.rs", "+pub fn connect() {}"),
+        ("secrets/db.env", "+PASSWORD=hunter2"),
+    ]
+    .into_iter()
+    .map(|(path, diff)| (path.to_string(), diff.to_string()))
+    .collect();
+
+    let groups = provider.group_files(files, None, &diffs).unwrap();
+
+    let prompt = prompts.lock().unwrap()[0].clone();
+    assert!(prompt.contains("src/db.rs"));
+    assert!(prompt.contains("connect"));
+    assert!(!prompt.contains("secrets/db.env"));
+    assert!(!prompt.contains("hunter2"));
+    assert!(!prompt.contains("server"));
+
+    let mut grouped: Vec<&str> = groups
+        .iter()
+        .flat_map(|group| group.files.iter().map(|file| file.path.as_str()))
+        .collect();
+    grouped.sort_unstable();
+    assert_eq!(
+        grouped,
+        vec!["keys/server.key", "secrets/db.env", "src/db.rs"]
+    );
+}
+
+#[test]
+fn test_grouping_without_ai_files_sends_nothing() {
+    let (provider, prompts) = excluding("unused", &["secrets/**"]);
+
+    let groups = provider
+        .group_files(vec![file("secrets/db.env")], None, &HashMap::new())
+        .unwrap();
+
+    assert_eq!(groups.len(), 1);
+    assert!(prompts.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_message_prompt_omits_excluded_files() {
+    let response = "**START COMMIT MESSAGE**\nadd database\n**END COMMIT MESSAGE**";
+    let (provider, prompts) = excluding(response, &["secrets/**"]);
+    let files = vec![file("src/db.rs"), file("secrets/db.env")];
+    let group = ChangeGroup::new(
+        CommitType::Feat,
+        Some("db".to_string()),
+        files.clone(),
+        None,
+        "add database".to_string(),
+        vec![],
+    );
+    let diff = concat!(
+        "diff --git a/src/db.rs b/src/db.rs\n",
+        "+pub fn connect() {}\n",
+        "diff --git a/secrets/db.env b/secrets/db.env\n",
+        "+PASSWORD=hunter2\n",
+    );
+
+    let (description, _) = provider
+        .generate_message(&group, &files, Some(diff))
+        .unwrap();
+
+    assert_eq!(description, "add database");
+    let prompt = prompts.lock().unwrap()[0].clone();
+    assert!(prompt.contains("connect"));
+    assert!(!prompt.contains("secrets/db.env"));
+    assert!(!prompt.contains("hunter2"));
+}
+
+#[test]
+fn test_message_for_excluded_group_is_refused() {
+    let (provider, prompts) = excluding("unused", &["*.pem"]);
+    let files = vec![file("certs/server.pem")];
+    let group = ChangeGroup::new(
+        CommitType::Chore,
+        None,
+        files.clone(),
+        None,
+        "rotate certificate".to_string(),
+        vec![],
+    );
+
+    let error = provider.generate_message(&group, &files, None).unwrap_err();
+
+    assert!(error.to_string().contains("excluded from AI"));
+    assert!(prompts.lock().unwrap().is_empty());
+}
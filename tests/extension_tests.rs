@@ -0,0 +1,83 @@
+//! Tests for actions and side panels added by embedding applications.
+
+use std::cell::Cell;
+use std::path::Path;
+use std::rc::Rc;
+
+use ratatui::backend::TestBackend;
+use ratatui::widgets::Paragraph;
+use ratatui::Terminal;
+
+use commit_wizard::extension::{run_action, Extensions};
+use commit_wizard::types::AppState;
+
+#[test]
+fn test_actions_cannot_take_builtin_or_bound_keys() {
+    let mut extensions = Extensions::new();
+    extensions
+        .add_action('J', "Create subtask", |_, _| Ok(()))
+        .unwrap();
+
+    let error = extensions
+        .add_action('J', "Link issue", |_, _| Ok(()))
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Key 'J' of action 'Link issue' is already bound to 'Create subtask'"
+    );
+    assert!(extensions.add_action('q', "Quit", |_, _| Ok(())).is_err());
+    assert_eq!(extensions.actions().len(), 1);
+}
+
+#[test]
+fn test_run_action_changes_the_state() {
+    let calls = Rc::new(Cell::new(0));
+    let counter = Rc::clone(&calls);
+    let mut extensions = Extensions::new();
+    extensions
+        .add_action('J', "Create subtask", move |app, repo_path| {
+            counter.set(counter.get() + 1);
+            app.set_status(format!("created in {}", repo_path.display()));
+            Ok(())
+        })
+        .unwrap()
+        .add_action('X', "Broken", |_, _| anyhow::bail!("server unreachable"))
+        .unwrap();
+    let mut app = AppState::new(Vec::new()).with_extensions(extensions);
+
+    assert!(run_action(&mut app, Path::new("/repo"), 'J').unwrap());
+    assert_eq!(calls.get(), 1);
+    assert_eq!(app.status_message, "created in /repo");
+
+    assert!(!run_action(&mut app, Path::new("/repo"), 'Z').unwrap());
+    let error = run_action(&mut app, Path::new("/repo"), 'X').unwrap_err();
+    assert_eq!(
+        format!("{:#}", error),
+        "Action 'Broken' failed: server unreachable"
+    );
+    // The registry is restored after each action
+    assert_eq!(app.extensions.actions().len(), 2);
+}
+
+#[test]
+fn test_side_panel_renders_with_the_state() {
+    let mut extensions = Extensions::new();
+    extensions.set_side_panel("Groups", 90, |f, area, app| {
+        f.render_widget(Paragraph::new(format!("{} groups", app.groups.len())), area);
+    });
+    let app = AppState::new(Vec::new()).with_extensions(extensions);
+    let panel = app.extensions.side_panel().unwrap();
+    assert_eq!(panel.width, 60);
+
+    let mut terminal = Terminal::new(TestBackend::new(20, 1)).unwrap();
+    terminal.draw(|f| panel.render(f, f.area(), &app)).unwrap();
+
+    let line: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect();
+    assert!(line.starts_with("0 groups"));
+}
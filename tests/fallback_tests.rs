@@ -0,0 +1,165 @@
+//! Tests for explaining why AI fell back to heuristics.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use git2::Status;
+
+use commit_wizard::budget::BudgetExceeded;
+use commit_wizard::fallback::{session_report, AiFallback, FallbackCause, UnparsableResponse};
+use commit_wizard::git::TimedOut;
+use commit_wizard::provider::{AiProvider, HttpChatProvider, OllamaProvider, TokenLimitField};
+use commit_wizard::retry::ApiError;
+use commit_wizard::types::ChangedFile;
+
+/// Provider answering every prompt with the same response.
+struct ChattyProvider(&'static str);
+
+impl AiProvider for ChattyProvider {
+    fn name(&self) -> &str {
+        "chatty"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn complete(&self, _prompt: &str) -> Result<String> {
+        Ok(self.0.to_string())
+    }
+}
+
+#[test]
+fn test_typed_errors_are_classified() {
+    let cases = [
+        (
+            ApiError::new(403, None, "Forbidden"),
+            FallbackCause::Authentication,
+        ),
+        (
+            ApiError::new(429, None, "Too many requests"),
+            FallbackCause::RateLimited,
+        ),
+        (
+            ApiError::new(504, None, "Gateway timeout"),
+            FallbackCause::Timeout,
+        ),
+        (
+            ApiError::new(500, None, "Internal error"),
+            FallbackCause::Api(500),
+        ),
+    ];
+    for (api_error, cause) in cases {
+        let error = anyhow::Error::new(api_error).context("AI request failed");
+        assert_eq!(FallbackCause::of(&error), cause);
+    }
+
+    let error = anyhow::Error::new(TimedOut(Duration::from_secs(60))).context("copilot failed");
+    assert_eq!(FallbackCause::of(&error), FallbackCause::Timeout);
+
+    let error = anyhow::Error::new(BudgetExceeded {
+        requests: 10,
+        time: Duration::from_secs(30),
+    });
+    assert_eq!(FallbackCause::of(&error), FallbackCause::Budget);
+}
+
+#[test]
+fn test_cli_errors_are_classified_by_wording() {
+    let error = anyhow::anyhow!("Copilot CLI failed: please run /login to authenticate");
+    assert_eq!(FallbackCause::of(&error), FallbackCause::Authentication);
+
+    let error = anyhow::anyhow!("copilot: command not found");
+    assert_eq!(FallbackCause::of(&error), FallbackCause::Unavailable);
+
+    let error = anyhow::anyhow!("something unexpected");
+    assert_eq!(FallbackCause::of(&error), FallbackCause::Other);
+    assert_eq!(FallbackCause::Other.label(), "request failed");
+}
+
+#[test]
+fn test_unparsable_response_snippet_is_truncated() {
+    let response = format!("Here you go:\n\n{}", "x".repeat(300));
+
+    let snippet = UnparsableResponse::new(&response).snippet;
+
+    assert!(snippet.starts_with("Here you go: xxx"));
+    assert!(snippet.ends_with('…'));
+    assert_eq!(snippet.chars().count(), 121);
+    assert_eq!(UnparsableResponse::new("  short\n").snippet, "short");
+}
+
+#[test]
+fn test_grouping_parse_failure_keeps_the_response() {
+    let provider = ChattyProvider("Sure! I grouped your files by feature.");
+    let files = vec![ChangedFile::new(
+        "src/lib.rs".to_string(),
+        Status::INDEX_MODIFIED,
+    )];
+
+    let error = provider
+        .group_files(files, None, &HashMap::new())
+        .unwrap_err();
+    let fallback = AiFallback::from_error("grouping", &error);
+
+    assert_eq!(fallback.cause, FallbackCause::Parse);
+    assert_eq!(
+        fallback.summary(),
+        "AI grouping failed (unparsable response)"
+    );
+    assert!(fallback
+        .detail
+        .contains("\"Sure! I grouped your files by feature.\""));
+}
+
+#[test]
+fn test_unavailable_reasons_name_the_missing_piece() {
+    let http = HttpChatProvider::new(
+        "Test",
+        "http://localhost:1",
+        None,
+        "model",
+        TokenLimitField::MaxTokens,
+    );
+    assert_eq!(http.unavailable_reason(), "Test: no API token is set");
+
+    let ollama = OllamaProvider::new("http://localhost:1", "llama3.2");
+    assert!(ollama
+        .unavailable_reason()
+        .contains("http://localhost:1 or model 'llama3.2' not installed"));
+}
+
+#[test]
+fn test_session_report() {
+    assert_eq!(session_report(&[]), "");
+
+    let unavailable = AiFallback::unavailable(&[
+        "OpenAI: OPENAI_API_KEY is not set".to_string(),
+        "Ollama: not reachable".to_string(),
+    ]);
+    let parse = AiFallback::from_error(
+        "message generation",
+        &anyhow::anyhow!("missing markers").context(UnparsableResponse::new("Hello")),
+    );
+
+    let report = session_report(&[unavailable, parse]);
+    let lines: Vec<&str> = report.lines().collect();
+
+    assert_eq!(lines[0], "AI fell back to heuristics 2 time(s):");
+    assert!(lines[1].ends_with(
+        "No AI provider available: OpenAI: OPENAI_API_KEY is not set; Ollama: not reachable"
+    ));
+    assert!(lines[2].contains("AI message generation failed (unparsable response)"));
+    assert!(lines[2].contains("Unparsable AI response \"Hello\": missing markers"));
+}
+
+#[test]
+fn test_context_on_results_is_detected() {
+    let error = "not json"
+        .parse::<u32>()
+        .context(UnparsableResponse::new("not json"))
+        .unwrap_err();
+
+    assert_eq!(FallbackCause::of(&error), FallbackCause::Parse);
+}
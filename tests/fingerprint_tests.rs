@@ -0,0 +1,66 @@
+//! Tests for detecting files changed after planning.
+
+use std::fs;
+
+use git2::Repository;
+use tempfile::TempDir;
+
+use commit_wizard::fingerprint::{fingerprint_file, Snapshot};
+
+#[test]
+fn test_fingerprint_matches_git_blob_id() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    fs::write(dir.path().join("a.txt"), "hello\n").unwrap();
+
+    let id = fingerprint_file(dir.path(), "a.txt").unwrap();
+    assert_eq!(id, Some(repo.blob(b"hello\n").unwrap()));
+    assert_eq!(fingerprint_file(dir.path(), "missing.txt").unwrap(), None);
+}
+
+#[test]
+fn test_snapshot_reports_changed_files() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.txt"), "one").unwrap();
+    fs::write(dir.path().join("b.txt"), "one").unwrap();
+    let mut snapshot = Snapshot::capture(dir.path(), ["a.txt", "b.txt"]).unwrap();
+    assert!(snapshot
+        .changed(dir.path(), ["a.txt", "b.txt"])
+        .unwrap()
+        .is_empty());
+
+    fs::write(dir.path().join("a.txt"), "two").unwrap();
+    fs::remove_file(dir.path().join("b.txt")).unwrap();
+    fs::write(dir.path().join("c.txt"), "new").unwrap();
+    assert_eq!(
+        snapshot
+            .changed(dir.path(), ["a.txt", "b.txt", "c.txt"])
+            .unwrap(),
+        ["a.txt", "b.txt"]
+    );
+
+    // Refreshed files are current again
+    snapshot.refresh(dir.path(), ["a.txt"]).unwrap();
+    assert_eq!(
+        snapshot.changed(dir.path(), ["a.txt", "b.txt"]).unwrap(),
+        ["b.txt"]
+    );
+}
+
+#[test]
+fn test_snapshot_tracks_files_deleted_when_planned() {
+    let dir = TempDir::new().unwrap();
+    let snapshot = Snapshot::capture(dir.path(), ["gone.txt"]).unwrap();
+    assert!(!snapshot.is_empty());
+    assert!(snapshot
+        .changed(dir.path(), ["gone.txt"])
+        .unwrap()
+        .is_empty());
+
+    // Restoring a deleted file changes what the commit would record
+    fs::write(dir.path().join("gone.txt"), "back").unwrap();
+    assert_eq!(
+        snapshot.changed(dir.path(), ["gone.txt"]).unwrap(),
+        ["gone.txt"]
+    );
+}
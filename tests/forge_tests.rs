@@ -0,0 +1,188 @@
+//! Tests for issue lookup on GitHub and GitLab.
+
+use std::time::Duration;
+
+use mockito::{Matcher, Server};
+
+use commit_wizard::config::{ForgeConfig, NetworkSettings};
+use commit_wizard::forge::{
+    issue_numbers, parse_remote_url, tracker_from_config, ForgeKind, GitHubClient, GitLabClient,
+    Issue, IssueTracker,
+};
+
+#[test]
+fn test_parse_remote_urls() {
+    let cases = [
+        (
+            "https://github.com/owner/repo.git",
+            "github.com",
+            "owner/repo",
+        ),
+        ("git@github.com:owner/repo.git", "github.com", "owner/repo"),
+        (
+            "ssh://git@gitlab.example.com:2222/group/sub/app.git",
+            "gitlab.example.com",
+            "group/sub/app",
+        ),
+        (
+            "https://user:pw@gitlab.com/group/app/",
+            "gitlab.com",
+            "group/app",
+        ),
+    ];
+    for (url, host, project) in cases {
+        let remote = parse_remote_url(url).unwrap_or_else(|| panic!("{} not parsed", url));
+        assert_eq!(remote.host, host, "{}", url);
+        assert_eq!(remote.project, project, "{}", url);
+    }
+
+    assert!(parse_remote_url("../local/repo").is_none());
+    assert!(parse_remote_url("https://github.com/").is_none());
+}
+
+#[test]
+fn test_forge_kind_from_host() {
+    let kind = |url: &str| parse_remote_url(url).unwrap().kind();
+    assert_eq!(kind("git@github.com:o/r.git"), Some(ForgeKind::GitHub));
+    assert_eq!(
+        kind("https://gitlab.example.com/g/r"),
+        Some(ForgeKind::GitLab)
+    );
+    assert_eq!(kind("https://git.example.com/g/r"), None);
+
+    assert_eq!(
+        ForgeKind::GitHub.api_url("github.com"),
+        "https://api.github.com"
+    );
+    assert_eq!(
+        ForgeKind::GitHub.api_url("ghe.corp"),
+        "https://ghe.corp/api/v3"
+    );
+    assert_eq!(
+        ForgeKind::GitLab.api_url("gitlab.com"),
+        "https://gitlab.com/api/v4"
+    );
+}
+
+#[test]
+fn test_issue_numbers_skip_other_trackers() {
+    assert_eq!(issue_numbers("#5"), [5]);
+    assert_eq!(issue_numbers("LU-1, #22"), [22]);
+    assert!(issue_numbers("AB#3, !4").is_empty());
+}
+
+#[test]
+fn test_issue_footer_and_prompt_section() {
+    let issue = Issue {
+        number: 7,
+        title: "Login fails ".to_string(),
+        description: "x".repeat(3000),
+        url: "https://github.com/o/r/issues/7".to_string(),
+    };
+    assert_eq!(issue.footer(), "Closes #7: Login fails");
+
+    let section = issue.prompt_section();
+    assert!(section.starts_with("ISSUE #7: Login fails\n"));
+    assert!(section.contains("description shortened"));
+    assert!(section.len() < 2500);
+}
+
+#[test]
+fn test_github_client_fetches_issue() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("GET", "/repos/owner/repo/issues/42")
+        .match_header("authorization", "Bearer secret")
+        .match_header("user-agent", Matcher::Regex("^commit-wizard/".to_string()))
+        .with_status(200)
+        .with_body(
+            r#"{"title": "Crash on start", "body": null, "html_url": "https://github.com/owner/repo/issues/42"}"#,
+        )
+        .create();
+
+    let client = GitHubClient::new(
+        &server.url(),
+        "owner/repo",
+        "secret",
+        Duration::from_secs(5),
+    );
+    let issue = client.issue(42).unwrap();
+
+    mock.assert();
+    assert_eq!(issue.number, 42);
+    assert_eq!(issue.title, "Crash on start");
+    assert_eq!(issue.description, "");
+    assert_eq!(issue.url, "https://github.com/owner/repo/issues/42");
+}
+
+#[test]
+fn test_gitlab_client_fetches_issue() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("GET", "/projects/group%2Fapp/issues/3")
+        .match_header("private-token", "secret")
+        .with_status(200)
+        .with_body(
+            r#"{"title": "Slow search", "description": "Takes 10s", "web_url": "https://gitlab.com/group/app/-/issues/3"}"#,
+        )
+        .create();
+
+    let client = GitLabClient::new(&server.url(), "group/app", "secret", Duration::from_secs(5));
+    let issue = client.issue(3).unwrap();
+
+    mock.assert();
+    assert_eq!(issue.title, "Slow search");
+    assert_eq!(issue.description, "Takes 10s");
+}
+
+#[test]
+fn test_client_reports_api_errors() {
+    let mut server = Server::new();
+    server
+        .mock("GET", "/repos/owner/repo/issues/1")
+        .with_status(404)
+        .with_body(r#"{"message": "Not Found"}"#)
+        .create();
+
+    let client = GitHubClient::new(
+        &server.url(),
+        "owner/repo",
+        "secret",
+        Duration::from_secs(5),
+    );
+    let error = client.issue(1).unwrap_err();
+    assert!(format!("{:#}", error).contains("404"));
+}
+
+#[test]
+fn test_tracker_from_config() {
+    let config = ForgeConfig {
+        token_env: Some("COMMIT_WIZARD_TEST_FORGE_TOKEN".to_string()),
+        ..Default::default()
+    };
+    let remote = Some("git@gitlab.com:group/app.git");
+    let network = NetworkSettings::default();
+
+    std::env::remove_var("COMMIT_WIZARD_TEST_FORGE_TOKEN");
+    assert!(tracker_from_config(&config, remote, &network).is_none());
+
+    std::env::set_var("COMMIT_WIZARD_TEST_FORGE_TOKEN", "secret");
+    let tracker = tracker_from_config(&config, remote, &network).unwrap();
+    assert_eq!(tracker.name(), "GitLab");
+
+    // Unknown hosts need an explicit kind
+    assert!(tracker_from_config(&config, Some("https://git.example.com/g/r"), &network).is_none());
+    let config = ForgeConfig {
+        kind: Some(ForgeKind::GitHub),
+        ..config
+    };
+    let tracker =
+        tracker_from_config(&config, Some("https://git.example.com/g/r"), &network).unwrap();
+    assert_eq!(tracker.name(), "GitHub");
+
+    let disabled = ForgeConfig {
+        enabled: false,
+        ..config
+    };
+    assert!(tracker_from_config(&disabled, remote, &network).is_none());
+}
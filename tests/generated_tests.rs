@@ -0,0 +1,169 @@
+//! Tests for suggesting `.gitattributes` entries for generated files.
+
+use std::fs;
+use std::path::Path;
+
+use git2::{Repository, Signature, Status};
+use tempfile::TempDir;
+
+use commit_wizard::config::GeneratedConfig;
+use commit_wizard::generated::{
+    add_to_gitattributes, gitattributes_entries, looks_generated, unmarked_generated_files,
+    GeneratedFile,
+};
+use commit_wizard::types::ChangedFile;
+
+/// Commits `content` to each of `paths`
+fn commit(repo: &Repository, paths: &[&str], content: &str) {
+    let workdir = repo.workdir().unwrap();
+    let mut index = repo.index().unwrap();
+    for path in paths {
+        fs::write(workdir.join(path), content).unwrap();
+        index.add_path(Path::new(path)).unwrap();
+    }
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    let parents: Vec<_> = repo
+        .head()
+        .ok()
+        .and_then(|head| head.peel_to_commit().ok())
+        .into_iter()
+        .collect();
+    let parents: Vec<_> = parents.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, "Update", &tree, &parents)
+        .unwrap();
+}
+
+/// A lock file of about `kib` KiB, different for every `version`
+fn lock_file(kib: usize, version: usize) -> String {
+    format!(
+        "# version {}\n{}",
+        version,
+        "x = 1\n".repeat(kib * 1024 / 6)
+    )
+}
+
+/// Creates a repository where `Cargo.lock` and the small `yarn.lock` were
+/// committed three times and are changed again
+fn create_test_repo() -> (TempDir, Repository, Vec<ChangedFile>) {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    for version in 0..3 {
+        commit(&repo, &["Cargo.lock"], &lock_file(40, version));
+        commit(&repo, &["yarn.lock"], &format!("small {}\n", version));
+    }
+    commit(&repo, &["src.rs"], &lock_file(40, 0));
+
+    fs::write(tmp.path().join("Cargo.lock"), lock_file(40, 3)).unwrap();
+    fs::write(tmp.path().join("yarn.lock"), "small 3\n").unwrap();
+    fs::write(tmp.path().join("src.rs"), lock_file(40, 1)).unwrap();
+    let files = ["Cargo.lock", "yarn.lock", "src.rs"]
+        .iter()
+        .map(|path| ChangedFile::new(path.to_string(), Status::WT_MODIFIED))
+        .collect();
+    (tmp, repo, files)
+}
+
+#[test]
+fn test_looks_generated() {
+    assert!(looks_generated("Cargo.lock", ""));
+    assert!(looks_generated("static/app.min.js", ""));
+    assert!(looks_generated("api/service.pb.go", ""));
+    assert!(looks_generated(
+        "src/schema.rs",
+        "// This file is autogenerated by diesel\n"
+    ));
+    assert!(looks_generated(
+        "gen/types.go",
+        "// Code generated by protoc-gen-go. DO NOT EDIT.\n"
+    ));
+    assert!(!looks_generated("src/main.rs", "fn main() {}\n"));
+    // Only the first lines count
+    let late = format!("{}// @generated\n", "\n".repeat(10));
+    assert!(!looks_generated("src/late.rs", &late));
+}
+
+#[test]
+fn test_unmarked_generated_files() {
+    let (tmp, repo, files) = create_test_repo();
+    let config = GeneratedConfig::default();
+
+    let generated = unmarked_generated_files(&repo, &files, &config).unwrap();
+    assert_eq!(generated.len(), 1, "{:?}", generated);
+    assert_eq!(generated[0].path, "Cargo.lock");
+    assert_eq!(generated[0].commits, 3);
+    assert!(generated[0].size >= 32 * 1024);
+
+    // Not changed often enough
+    let config = GeneratedConfig {
+        min_commits: 4,
+        ..GeneratedConfig::default()
+    };
+    assert!(unmarked_generated_files(&repo, &files, &config)
+        .unwrap()
+        .is_empty());
+
+    // Marked files are not suggested again
+    fs::write(tmp.path().join(".gitattributes"), "Cargo.lock -diff\n").unwrap();
+    assert!(
+        unmarked_generated_files(&repo, &files, &GeneratedConfig::default())
+            .unwrap()
+            .is_empty()
+    );
+
+    let config = GeneratedConfig {
+        suggest: false,
+        ..GeneratedConfig::default()
+    };
+    fs::remove_file(tmp.path().join(".gitattributes")).unwrap();
+    assert!(unmarked_generated_files(&repo, &files, &config)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_gitattributes_entries() {
+    let file = |path: &str| GeneratedFile {
+        path: path.to_string(),
+        size: 1,
+        commits: 1,
+    };
+    assert_eq!(
+        gitattributes_entries(&[file("web/yarn.lock"), file("gen/[id].min.js")]),
+        "/web/yarn.lock linguist-generated -diff\n/gen/\\[id].min.js linguist-generated -diff\n"
+    );
+    assert_eq!(
+        gitattributes_entries(&[file("my app/app.min.js")]),
+        "\"/my app/app.min.js\" linguist-generated -diff\n"
+    );
+}
+
+#[test]
+fn test_add_to_gitattributes() {
+    let (tmp, repo, files) = create_test_repo();
+    let generated = unmarked_generated_files(&repo, &files, &GeneratedConfig::default()).unwrap();
+
+    let attributes = add_to_gitattributes(tmp.path(), &generated).unwrap();
+    assert_eq!(attributes.path, ".gitattributes");
+    assert_eq!(attributes.status, Status::WT_NEW);
+    assert_eq!(
+        fs::read_to_string(tmp.path().join(".gitattributes")).unwrap(),
+        "/Cargo.lock linguist-generated -diff\n"
+    );
+    // Now marked
+    assert!(
+        unmarked_generated_files(&repo, &files, &GeneratedConfig::default())
+            .unwrap()
+            .is_empty()
+    );
+
+    // Existing entries are kept
+    fs::write(tmp.path().join(".gitattributes"), "*.png binary").unwrap();
+    let attributes = add_to_gitattributes(tmp.path(), &generated).unwrap();
+    assert_eq!(attributes.status, Status::WT_MODIFIED);
+    assert_eq!(
+        fs::read_to_string(tmp.path().join(".gitattributes")).unwrap(),
+        "*.png binary\n/Cargo.lock linguist-generated -diff\n"
+    );
+}
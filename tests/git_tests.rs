@@ -9,9 +9,11 @@ use git2::{Repository, Signature};
 use tempfile::TempDir;
 
 // Import git functions from the library
+use commit_wizard::config::TimeoutConfig;
 use commit_wizard::git::{
-    collect_changed_files, commit_group, extract_ticket_from_branch, get_current_branch,
-    get_file_diff,
+    collect_changed_files, collect_diffs, commit_group, create_branch, deleted_file_patch,
+    extract_ticket_from_branch, get_current_branch, get_file_diff, head_content,
+    suggest_branch_name, undo_commit, RepoInfo,
 };
 use commit_wizard::types::ChangeGroup;
 
@@ -293,6 +295,56 @@ fn test_get_current_branch_custom() {
     assert_eq!(branch, "feature/test-branch");
 }
 
+#[test]
+fn test_create_branch_keeps_uncommitted_changes() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+    let head = repo.head().unwrap().target();
+    fs::write(tmp.path().join("README.md"), "# Changed").unwrap();
+    fs::write(tmp.path().join("new.txt"), "new").unwrap();
+    let before = collect_changed_files(&repo, true).unwrap();
+
+    create_branch(&repo, "feat/PROJ-1-add-auth").unwrap();
+
+    assert_eq!(get_current_branch(&repo).unwrap(), "feat/PROJ-1-add-auth");
+    assert_eq!(repo.head().unwrap().target(), head);
+    let after = collect_changed_files(&repo, true).unwrap();
+    assert_eq!(after.len(), before.len());
+
+    let error = create_branch(&repo, "feat/PROJ-1-add-auth").unwrap_err();
+    assert!(error.to_string().contains("already exists"));
+    assert!(create_branch(&repo, "bad..name").is_err());
+    assert!(create_branch(&repo, "-f").is_err());
+}
+
+#[test]
+fn test_create_branch_in_empty_repository() {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+
+    create_branch(&repo, "feat/first").unwrap();
+    assert_eq!(
+        repo.find_reference("HEAD").unwrap().symbolic_target(),
+        Some("refs/heads/feat/first")
+    );
+}
+
+#[test]
+fn test_suggest_branch_name_shortens_long_descriptions() {
+    use commit_wizard::types::CommitType;
+
+    let name = suggest_branch_name(
+        CommitType::Refactor,
+        None,
+        "Split the configuration loader into layered sources with validation",
+    );
+    assert_eq!(name, "refactor/split-the-configuration-loader-into");
+    assert_eq!(
+        suggest_branch_name(CommitType::Feat, Some("AB#12"), ""),
+        "feat/AB-12"
+    );
+}
+
 // ============================================================================
 // Tests for get_file_diff()
 // ============================================================================
@@ -339,6 +391,77 @@ fn test_get_file_diff_nonexistent_file() {
     assert_eq!(diff, "", "Diff for non-existent file should be empty");
 }
 
+// ============================================================================
+// Tests for collect_diffs()
+// ============================================================================
+
+#[test]
+fn test_collect_diffs_reports_line_stats() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    // Unstaged modification plus a staged new file
+    fs::write(tmp.path().join("README.md"), "# Changed\nsecond line\n").unwrap();
+    fs::write(tmp.path().join("new.txt"), "one\ntwo\nthree\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("new.txt")).unwrap();
+    index.write().unwrap();
+
+    let files = collect_changed_files(&repo, false).unwrap();
+    let diffs = collect_diffs(&repo, &files).unwrap();
+
+    let readme = diffs.get("README.md").expect("README.md diff missing");
+    assert_eq!(readme.lines_added, 2);
+    assert_eq!(readme.lines_removed, 1);
+    assert!(readme.patch.contains("+second line"));
+
+    let new_file = diffs.get("new.txt").expect("new.txt diff missing");
+    assert_eq!(new_file.lines_added, 3);
+    assert_eq!(new_file.lines_removed, 0);
+    assert!(!new_file.is_binary);
+}
+
+#[test]
+fn test_collect_diffs_includes_untracked_content() {
+    use commit_wizard::git::collect_untracked_files;
+
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    fs::write(tmp.path().join("untracked.txt"), "hello\n").unwrap();
+
+    let files = collect_untracked_files(&repo).unwrap();
+    let diffs = collect_diffs(&repo, &files).unwrap();
+
+    let diff = diffs.get("untracked.txt").expect("untracked diff missing");
+    assert_eq!(diff.lines_added, 1);
+    assert!(diff.patch.contains("+hello"));
+}
+
+#[test]
+fn test_collect_diffs_only_requested_files() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+    fs::write(tmp.path().join("b.txt"), "b\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("a.txt")).unwrap();
+    index.add_path(Path::new("b.txt")).unwrap();
+    index.write().unwrap();
+
+    let files: Vec<_> = collect_changed_files(&repo, false)
+        .unwrap()
+        .into_iter()
+        .filter(|f| f.path == "a.txt")
+        .collect();
+    let diffs = collect_diffs(&repo, &files).unwrap();
+
+    assert_eq!(diffs.len(), 1);
+    assert!(diffs.contains_key("a.txt"));
+    assert!(collect_diffs(&repo, &[]).unwrap().is_empty());
+}
+
 // ============================================================================
 // Tests for commit_group()
 // ============================================================================
@@ -377,6 +500,44 @@ fn test_commit_group_success() {
     assert!(commit.message().unwrap().contains("add new feature"));
 }
 
+#[test]
+fn test_commit_group_cancelled_before_start() {
+    use commit_wizard::git::{commit_group_cancellable, Cancelled};
+    use commit_wizard::types::CommitType;
+    use std::sync::atomic::AtomicBool;
+
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+    fs::write(tmp.path().join("feature.txt"), "new feature\n").unwrap();
+
+    let files = collect_changed_files(&repo, false).unwrap();
+    let group = ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        files,
+        None,
+        "add new feature".to_string(),
+        vec![],
+    );
+
+    let error = commit_group_cancellable(
+        tmp.path(),
+        &group,
+        &TimeoutConfig::default(),
+        &AtomicBool::new(true),
+    )
+    .unwrap_err();
+    assert!(error.is::<Cancelled>());
+
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.message().unwrap(), "Initial commit");
+    assert!(repo
+        .index()
+        .unwrap()
+        .get_path(Path::new("feature.txt"), 0)
+        .is_none());
+}
+
 #[test]
 fn test_commit_group_with_body() {
     use commit_wizard::types::CommitType;
@@ -502,3 +663,515 @@ fn test_commit_group_with_scope_and_ticket() {
         "Should include description"
     );
 }
+
+// ============================================================================
+// Tests for rename handling
+// ============================================================================
+
+#[test]
+fn test_collect_changed_files_staged_rename_has_old_path() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    // Equivalent of `git mv README.md GUIDE.md`
+    fs::rename(tmp.path().join("README.md"), tmp.path().join("GUIDE.md")).unwrap();
+    let mut index = repo.index().unwrap();
+    index.remove_path(Path::new("README.md")).unwrap();
+    index.add_path(Path::new("GUIDE.md")).unwrap();
+    index.write().unwrap();
+
+    let files = collect_changed_files(&repo, false).unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, "GUIDE.md");
+    assert_eq!(files[0].old_path.as_deref(), Some("README.md"));
+    assert!(files[0].is_renamed());
+    assert_eq!(files[0].display_path(), "README.md → GUIDE.md");
+}
+
+#[test]
+fn test_commit_group_commits_both_sides_of_rename() {
+    use commit_wizard::types::CommitType;
+
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    // Rename only in the working tree; nothing is staged yet
+    fs::rename(tmp.path().join("README.md"), tmp.path().join("GUIDE.md")).unwrap();
+
+    let files = collect_changed_files(&repo, true).unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].old_path.as_deref(), Some("README.md"));
+
+    let group = ChangeGroup::new(
+        CommitType::Docs,
+        None,
+        files,
+        None,
+        "rename readme".to_string(),
+        vec![],
+    );
+    let result = commit_group(tmp.path(), &group);
+    assert!(result.is_ok(), "Commit should succeed: {:?}", result.err());
+
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    assert!(tree.get_path(Path::new("GUIDE.md")).is_ok());
+    assert!(tree.get_path(Path::new("README.md")).is_err());
+    assert!(collect_changed_files(&repo, true).unwrap().is_empty());
+}
+
+// ============================================================================
+// Tests for submodule handling
+// ============================================================================
+
+/// Runs a git command in `dir`, panicking on failure
+fn git(dir: &Path, args: &[&str]) {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "-c",
+            "user.name=Test User",
+            "-c",
+            "user.email=test@example.com",
+        ])
+        .args(["-c", "protocol.file.allow=always"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Helper to create a repository with a committed submodule at `sub`
+fn create_repo_with_submodule() -> (TempDir, TempDir) {
+    let upstream = create_test_repo();
+    let tmp = create_test_repo();
+    git(
+        tmp.path(),
+        &["submodule", "add", upstream.path().to_str().unwrap(), "sub"],
+    );
+    git(tmp.path(), &["commit", "-m", "Add submodule"]);
+    (tmp, upstream)
+}
+
+#[test]
+fn test_collect_changed_files_submodule_pointer_update() {
+    use commit_wizard::types::FileKind;
+
+    let (tmp, _upstream) = create_repo_with_submodule();
+    let sub = tmp.path().join("sub");
+    fs::write(sub.join("new.txt"), "new\n").unwrap();
+    git(&sub, &["add", "new.txt"]);
+    git(&sub, &["commit", "-m", "New commit in submodule"]);
+
+    let repo = Repository::open(tmp.path()).unwrap();
+    let files = collect_changed_files(&repo, false).unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, "sub");
+    assert_eq!(files[0].kind, FileKind::Submodule);
+
+    // Committing the group records the new submodule commit
+    let group = commit_wizard::inference::build_submodule_group(files, None).unwrap();
+    commit_group(tmp.path(), &group).unwrap();
+    assert!(collect_changed_files(&repo, false).unwrap().is_empty());
+}
+
+#[test]
+fn test_collect_changed_files_skips_dirty_submodule_content() {
+    let (tmp, _upstream) = create_repo_with_submodule();
+
+    // Uncommitted changes inside the submodule do not move its pointer
+    fs::write(tmp.path().join("sub").join("README.md"), "changed").unwrap();
+    fs::write(tmp.path().join("README.md"), "# Changed").unwrap();
+
+    let repo = Repository::open(tmp.path()).unwrap();
+    let files = collect_changed_files(&repo, false).unwrap();
+    let paths: Vec<_> = files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["README.md"]);
+    assert!(!files[0].is_submodule());
+}
+
+// ============================================================================
+// Tests for discover_repo()
+// ============================================================================
+
+#[test]
+fn test_discover_repo_from_subdirectory() {
+    use commit_wizard::git::discover_repo;
+
+    let tmp = create_test_repo();
+    let nested = tmp.path().join("src").join("deep");
+    fs::create_dir_all(&nested).unwrap();
+
+    let ctx = discover_repo(&nested).unwrap();
+    let expected = tmp.path().canonicalize().unwrap();
+    assert_eq!(ctx.workdir.canonicalize().unwrap(), expected);
+    assert!(!ctx.is_worktree);
+    assert_eq!(ctx.git_dir, ctx.common_dir);
+
+    let repo = ctx.open().unwrap();
+    assert_eq!(repo.workdir().unwrap().canonicalize().unwrap(), expected);
+}
+
+#[test]
+fn test_discover_repo_linked_worktree() {
+    use commit_wizard::git::discover_repo;
+
+    let tmp = create_test_repo();
+    let worktree_parent = TempDir::new().unwrap();
+    let worktree = worktree_parent.path().join("wt");
+    git(
+        tmp.path(),
+        &[
+            "worktree",
+            "add",
+            "-b",
+            "wt-branch",
+            worktree.to_str().unwrap(),
+        ],
+    );
+
+    let ctx = discover_repo(&worktree).unwrap();
+    assert!(ctx.is_worktree);
+    assert_eq!(
+        ctx.workdir.canonicalize().unwrap(),
+        worktree.canonicalize().unwrap()
+    );
+    assert_ne!(ctx.git_dir, ctx.common_dir);
+
+    // Changes in the worktree are seen and committed there
+    fs::write(worktree.join("wt.txt"), "worktree\n").unwrap();
+    let repo = ctx.open().unwrap();
+    assert_eq!(get_current_branch(&repo).unwrap(), "wt-branch");
+    let files = collect_changed_files(&repo, true).unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, "wt.txt");
+}
+
+#[test]
+fn test_list_worktrees_reports_changes_per_worktree() {
+    use commit_wizard::git::{discover_repo, list_worktrees};
+
+    let tmp = create_test_repo();
+    let worktree_parent = TempDir::new().unwrap();
+    let worktree = worktree_parent.path().join("wt");
+    git(
+        tmp.path(),
+        &[
+            "worktree",
+            "add",
+            "-b",
+            "wt-branch",
+            worktree.to_str().unwrap(),
+        ],
+    );
+    fs::write(worktree.join("a.txt"), "a\n").unwrap();
+    fs::write(worktree.join("b.txt"), "b\n").unwrap();
+
+    let ctx = discover_repo(&worktree).unwrap();
+    let worktrees = list_worktrees(&ctx).unwrap();
+    assert_eq!(worktrees.len(), 2);
+
+    let main = &worktrees[0];
+    assert_eq!(main.name, None);
+    assert_eq!(main.changes, 0);
+    assert!(!main.is_current);
+
+    let linked = &worktrees[1];
+    assert_eq!(linked.name.as_deref(), Some("wt"));
+    assert_eq!(linked.branch.as_deref(), Some("wt-branch"));
+    assert_eq!(linked.changes, 2);
+    assert!(linked.is_current);
+    assert_eq!(linked.label(), "wt-branch (worktree)");
+
+    // A worktree whose directory was removed is skipped
+    fs::remove_dir_all(&worktree).unwrap();
+    let ctx = discover_repo(tmp.path()).unwrap();
+    let worktrees = list_worktrees(&ctx).unwrap();
+    assert_eq!(worktrees.len(), 1);
+    assert!(worktrees[0].is_current);
+}
+
+#[test]
+fn test_discover_repo_rejects_bare_repository() {
+    use commit_wizard::git::discover_repo;
+
+    let tmp = TempDir::new().unwrap();
+    Repository::init_bare(tmp.path()).unwrap();
+
+    let err = discover_repo(tmp.path()).unwrap_err();
+    assert!(err.to_string().contains("bare repository"), "{}", err);
+}
+
+#[test]
+fn test_discover_repo_not_a_repository() {
+    use commit_wizard::git::discover_repo;
+
+    let tmp = TempDir::new().unwrap();
+    assert!(discover_repo(tmp.path()).is_err());
+}
+
+/// Commits `content` to `path` on the branch `HEAD` points to.
+fn commit_file(repo: &Repository, path: &str, content: &str, message: &str) -> git2::Oid {
+    fs::write(repo.workdir().unwrap().join(path), content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(path)).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent])
+        .unwrap()
+}
+
+#[test]
+fn test_cherry_pick_onto_other_branches() {
+    use commit_wizard::git::{cherry_pick_onto, cherry_pick_targets};
+
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+    let base = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("release/1.0", &base, false).unwrap();
+    let current = get_current_branch(&repo).unwrap();
+
+    let fix = commit_file(&repo, "fix.txt", "fixed\n", "fix: handle empty input");
+    let docs = commit_file(&repo, "notes.txt", "notes\n", "docs: add notes");
+
+    // The checked-out branch is not a target
+    assert_eq!(cherry_pick_targets(&repo).unwrap(), ["release/1.0"]);
+    assert!(cherry_pick_onto(tmp.path(), &[fix], &current).is_err());
+
+    let pick = cherry_pick_onto(tmp.path(), &[fix, docs], "release/1.0").unwrap();
+    assert!(pick.is_clean());
+    assert_eq!(pick.created.len(), 2);
+    assert!(pick
+        .to_string()
+        .starts_with("✓ release/1.0: 2 commit(s) picked"));
+
+    let tip = repo
+        .find_branch("release/1.0", git2::BranchType::Local)
+        .unwrap()
+        .get()
+        .peel_to_commit()
+        .unwrap();
+    assert_eq!(tip.id(), pick.created[1]);
+    assert_eq!(tip.message(), Some("docs: add notes"));
+    assert_eq!(
+        tip.parent(0).unwrap().message(),
+        Some("fix: handle empty input")
+    );
+    assert_eq!(tip.parent(0).unwrap().parent_id(0).unwrap(), base.id());
+
+    // Commits already on the branch are skipped
+    let again = cherry_pick_onto(tmp.path(), &[fix], "release/1.0").unwrap();
+    assert!(again.created.is_empty());
+    assert_eq!(again.skipped, [fix]);
+}
+
+#[test]
+fn test_cherry_pick_conflict_leaves_branch_unchanged() {
+    use commit_wizard::git::cherry_pick_onto;
+
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+    let current = get_current_branch(&repo).unwrap();
+
+    // The other branch changed the same file differently
+    let base = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("release/1.0", &base, false).unwrap();
+    repo.set_head("refs/heads/release/1.0").unwrap();
+    let release_tip = commit_file(&repo, "config.txt", "release\n", "chore: release config");
+    repo.set_head(&format!("refs/heads/{}", current)).unwrap();
+    repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+
+    let docs = commit_file(&repo, "notes.txt", "notes\n", "docs: add notes");
+    let config = commit_file(&repo, "config.txt", "main\n", "fix: main config");
+
+    let pick = cherry_pick_onto(tmp.path(), &[docs, config], "release/1.0").unwrap();
+    assert!(!pick.is_clean());
+    assert!(pick.created.is_empty());
+    assert_eq!(
+        pick.conflict,
+        Some((config, vec!["config.txt".to_string()]))
+    );
+    assert!(pick.to_string().contains("conflicts in config.txt"));
+
+    let tip = repo
+        .find_branch("release/1.0", git2::BranchType::Local)
+        .unwrap()
+        .get()
+        .target()
+        .unwrap();
+    assert_eq!(tip, release_tip);
+}
+
+#[test]
+fn test_head_content_reads_deleted_file() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    fs::remove_file(tmp.path().join("README.md")).unwrap();
+
+    assert_eq!(
+        head_content(&repo, "README.md").unwrap().as_deref(),
+        Some("# Test Repo")
+    );
+    assert_eq!(head_content(&repo, "missing.txt").unwrap(), None);
+}
+
+#[test]
+fn test_deleted_file_patch_for_unstaged_deletion() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    fs::remove_file(tmp.path().join("README.md")).unwrap();
+    // Nothing is staged, so the staged diff says nothing about the file
+    assert!(get_file_diff(&repo, "README.md").unwrap().trim().is_empty());
+
+    let patch = deleted_file_patch(&repo, "README.md").unwrap().unwrap();
+    assert!(patch.contains("deleted file\n"));
+    assert!(patch.ends_with("@@ -1,1 +0,0 @@\n-# Test Repo\n"));
+    assert_eq!(deleted_file_patch(&repo, "missing.txt").unwrap(), None);
+}
+
+#[test]
+fn test_deleted_file_patch_is_truncated() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    let content: String = (0..250).map(|i| format!("line {}\n", i)).collect();
+    fs::write(tmp.path().join("big.txt"), content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("big.txt")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Add big file", &tree, &[&parent])
+        .unwrap();
+
+    let patch = deleted_file_patch(&repo, "big.txt").unwrap().unwrap();
+    assert!(patch.contains("-line 199\n"));
+    assert!(!patch.contains("-line 200\n"));
+    assert!(patch.ends_with("... (50 more lines of the deleted file omitted)\n"));
+}
+
+/// Commits a new file `name` with commit_group and returns the commit.
+fn commit_new_file(tmp: &TempDir, name: &str) -> git2::Oid {
+    use commit_wizard::types::{ChangedFile, CommitType};
+
+    fs::write(tmp.path().join(name), "content\n").unwrap();
+    let file = ChangedFile::new(name.to_string(), git2::Status::WT_NEW);
+    let group = ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        vec![file],
+        None,
+        format!("add {}", name),
+        vec![],
+    );
+    commit_group(tmp.path(), &group).unwrap();
+    let repo = Repository::open(tmp.path()).unwrap();
+    let id = repo.head().unwrap().peel_to_commit().unwrap().id();
+    id
+}
+
+#[test]
+fn test_undo_commit_keeps_changes_staged() {
+    let tmp = create_test_repo();
+    let commit = commit_new_file(&tmp, "feature.txt");
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    undo_commit(&repo, commit).unwrap();
+
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.message(), Some("Initial commit"));
+    let status = repo.status_file(Path::new("feature.txt")).unwrap();
+    assert!(status.is_index_new());
+}
+
+#[test]
+fn test_undo_commit_refuses_other_commits() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    // The root commit has no parent to return to
+    let root = repo.head().unwrap().peel_to_commit().unwrap().id();
+    let err = undo_commit(&repo, root).unwrap_err();
+    assert!(err.to_string().contains("parents"));
+
+    // HEAD moved on, e.g. by a commit made outside the wizard
+    let first = commit_new_file(&tmp, "one.txt");
+    commit_new_file(&tmp, "two.txt");
+    let err = undo_commit(&repo, first).unwrap_err();
+    assert!(err.to_string().contains("not the commit"));
+}
+
+#[test]
+fn test_undo_commit_refuses_pushed_commits() {
+    let tmp = create_test_repo();
+    let commit = commit_new_file(&tmp, "feature.txt");
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    // Pretend the commit was pushed to origin
+    repo.remote("origin", "https://example.com/repo.git")
+        .unwrap();
+    let branch = get_current_branch(&repo).unwrap();
+    repo.reference(
+        &format!("refs/remotes/origin/{}", branch),
+        commit,
+        true,
+        "test",
+    )
+    .unwrap();
+    let mut config = repo.config().unwrap();
+    config
+        .set_str(&format!("branch.{}.remote", branch), "origin")
+        .unwrap();
+    config
+        .set_str(
+            &format!("branch.{}.merge", branch),
+            &format!("refs/heads/{}", branch),
+        )
+        .unwrap();
+
+    let err = undo_commit(&repo, commit).unwrap_err();
+    assert!(err.to_string().contains("already been pushed"));
+    assert_eq!(repo.head().unwrap().target(), Some(commit));
+}
+
+#[test]
+fn test_repo_info() {
+    let tmp = create_test_repo();
+    let mut repo = Repository::open(tmp.path()).unwrap();
+
+    let info = RepoInfo::collect(&repo).unwrap();
+    assert_eq!(
+        info.workdir.canonicalize().unwrap(),
+        tmp.path().canonicalize().unwrap()
+    );
+    assert!(info.branch.is_some());
+    assert_eq!(info.status.head_summary, "Initial commit");
+    assert_eq!(info.status.upstream, None);
+    assert_eq!(info.author.as_deref(), Some("Test User"));
+    assert!(info.committed_at.is_some());
+    assert_eq!(info.stashes, 0);
+    assert!(info.dirty_submodules.is_empty());
+
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    for content in ["one", "two"] {
+        fs::write(tmp.path().join("README.md"), content).unwrap();
+        repo.stash_save(&sig, content, None).unwrap();
+    }
+    assert_eq!(RepoInfo::collect(&repo).unwrap().stashes, 2);
+
+    // A detached HEAD has no branch
+    let head = repo.head().unwrap().target().unwrap();
+    repo.set_head_detached(head).unwrap();
+    assert_eq!(RepoInfo::collect(&repo).unwrap().branch, None);
+}
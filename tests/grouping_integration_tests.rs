@@ -9,34 +9,13 @@ use git2::Status;
 fn test_heuristic_grouping_produces_no_duplicates() {
     // Create a realistic set of changed files
     let files = vec![
-        ChangedFile {
-            path: "src/api/users.rs".to_string(),
-            status: Status::INDEX_MODIFIED,
-        },
-        ChangedFile {
-            path: "src/api/posts.rs".to_string(),
-            status: Status::INDEX_MODIFIED,
-        },
-        ChangedFile {
-            path: "src/models/user.rs".to_string(),
-            status: Status::INDEX_NEW,
-        },
-        ChangedFile {
-            path: "src/ui/button.rs".to_string(),
-            status: Status::INDEX_MODIFIED,
-        },
-        ChangedFile {
-            path: "tests/api_tests.rs".to_string(),
-            status: Status::INDEX_NEW,
-        },
-        ChangedFile {
-            path: "README.md".to_string(),
-            status: Status::INDEX_MODIFIED,
-        },
-        ChangedFile {
-            path: ".github/workflows/ci.yml".to_string(),
-            status: Status::INDEX_NEW,
-        },
+        ChangedFile::new("src/api/users.rs".to_string(), Status::INDEX_MODIFIED),
+        ChangedFile::new("src/api/posts.rs".to_string(), Status::INDEX_MODIFIED),
+        ChangedFile::new("src/models/user.rs".to_string(), Status::INDEX_NEW),
+        ChangedFile::new("src/ui/button.rs".to_string(), Status::INDEX_MODIFIED),
+        ChangedFile::new("tests/api_tests.rs".to_string(), Status::INDEX_NEW),
+        ChangedFile::new("README.md".to_string(), Status::INDEX_MODIFIED),
+        ChangedFile::new(".github/workflows/ci.yml".to_string(), Status::INDEX_NEW),
     ];
 
     // Build groups using heuristic inference
@@ -56,17 +35,17 @@ fn test_heuristic_grouping_with_many_similar_files() {
     let mut files = vec![];
 
     for i in 0..20 {
-        files.push(ChangedFile {
-            path: format!("src/api/endpoint_{}.rs", i),
-            status: Status::INDEX_MODIFIED,
-        });
+        files.push(ChangedFile::new(
+            format!("src/api/endpoint_{}.rs", i),
+            Status::INDEX_MODIFIED,
+        ));
     }
 
     for i in 0..15 {
-        files.push(ChangedFile {
-            path: format!("tests/test_{}.rs", i),
-            status: Status::INDEX_NEW,
-        });
+        files.push(ChangedFile::new(
+            format!("tests/test_{}.rs", i),
+            Status::INDEX_NEW,
+        ));
     }
 
     let groups = build_groups(files.clone(), None);
@@ -86,10 +65,10 @@ fn test_heuristic_grouping_with_many_similar_files() {
 
 #[test]
 fn test_edge_case_single_file() {
-    let files = vec![ChangedFile {
-        path: "src/main.rs".to_string(),
-        status: Status::INDEX_MODIFIED,
-    }];
+    let files = vec![ChangedFile::new(
+        "src/main.rs".to_string(),
+        Status::INDEX_MODIFIED,
+    )];
 
     let groups = build_groups(files, None);
 
@@ -106,3 +85,31 @@ fn test_edge_case_empty_files() {
     assert!(validate_no_duplicate_files(&groups).is_ok());
     assert!(groups.is_empty());
 }
+
+#[test]
+fn test_heuristic_grouping_puts_submodules_in_own_chore_group() {
+    use commit_wizard::types::{CommitType, FileKind};
+
+    let files = vec![
+        ChangedFile::new("src/main.rs".to_string(), Status::INDEX_MODIFIED),
+        ChangedFile::new("src/vendor".to_string(), Status::WT_MODIFIED)
+            .with_kind(FileKind::Submodule),
+        ChangedFile::new("libs/core".to_string(), Status::WT_MODIFIED)
+            .with_kind(FileKind::Submodule),
+    ];
+
+    let groups = build_groups(files, Some("LU-1".to_string()));
+    assert!(validate_no_duplicate_files(&groups).is_ok());
+
+    let submodule_groups: Vec<_> = groups
+        .iter()
+        .filter(|g| g.files.iter().any(|f| f.is_submodule()))
+        .collect();
+    assert_eq!(submodule_groups.len(), 1);
+    let group = submodule_groups[0];
+    assert_eq!(group.commit_type, CommitType::Chore);
+    assert_eq!(group.files.len(), 2);
+    assert!(group.files.iter().all(|f| f.is_submodule()));
+    assert_eq!(group.description, "update 2 submodules");
+    assert_eq!(group.ticket.as_deref(), Some("LU-1"));
+}
@@ -0,0 +1,57 @@
+//! Tests for the file preview syntax highlighter.
+
+use commit_wizard::highlight::{highlight_line, syntax_for_path, TokenKind};
+
+fn join(tokens: &[(TokenKind, &str)]) -> String {
+    tokens.iter().map(|(_, text)| *text).collect()
+}
+
+#[test]
+fn test_syntax_for_path() {
+    assert_eq!(syntax_for_path("src/lib.rs").unwrap().name, "Rust");
+    assert_eq!(syntax_for_path("app/main.py").unwrap().name, "Python");
+    assert_eq!(syntax_for_path("web/index.tsx").unwrap().name, "JavaScript");
+    assert_eq!(syntax_for_path("Cargo.toml").unwrap().name, "Config");
+    assert_eq!(syntax_for_path("build/Makefile").unwrap().name, "Shell");
+    assert!(syntax_for_path("README").is_none());
+    assert!(syntax_for_path("image.png").is_none());
+}
+
+#[test]
+fn test_highlight_rust_line() {
+    let syntax = syntax_for_path("main.rs").unwrap();
+    let line = r#"    let name = "a \"quoted\" word"; // greet 42"#;
+    let tokens = highlight_line(line, &syntax);
+
+    assert_eq!(join(&tokens), line);
+    assert!(tokens.contains(&(TokenKind::Keyword, "let")));
+    assert!(tokens.contains(&(TokenKind::String, r#""a \"quoted\" word""#)));
+    assert_eq!(tokens.last(), Some(&(TokenKind::Comment, "// greet 42")));
+}
+
+#[test]
+fn test_highlight_numbers_and_identifiers() {
+    let syntax = syntax_for_path("main.rs").unwrap();
+    let tokens = highlight_line("let letter = 0x1F + 3.5;", &syntax);
+
+    assert!(tokens.contains(&(TokenKind::Number, "0x1F")));
+    assert!(tokens.contains(&(TokenKind::Number, "3.5")));
+    // Keywords only match whole words
+    assert!(!tokens.iter().any(|t| t == &(TokenKind::Keyword, "letter")));
+}
+
+#[test]
+fn test_highlight_unterminated_string_and_unicode() {
+    let syntax = syntax_for_path("script.py").unwrap();
+    let line = "print('héllo wörld";
+    let tokens = highlight_line(line, &syntax);
+
+    assert_eq!(join(&tokens), line);
+    assert_eq!(tokens.last(), Some(&(TokenKind::String, "'héllo wörld")));
+}
+
+#[test]
+fn test_highlight_empty_line() {
+    let syntax = syntax_for_path("main.go").unwrap();
+    assert!(highlight_line("", &syntax).is_empty());
+}
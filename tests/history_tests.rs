@@ -0,0 +1,111 @@
+//! Tests for grouping by co-change history.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use git2::{Repository, Signature, Status};
+use tempfile::TempDir;
+
+use commit_wizard::analysis::link_related;
+use commit_wizard::config::HistoryConfig;
+use commit_wizard::history::{co_change_edges, mine, CoChangeStats};
+use commit_wizard::inference::build_groups;
+use commit_wizard::types::ChangedFile;
+
+/// Creates a repository with one commit per entry, changing the given files.
+fn repo_with_history(commits: &[&[&str]]) -> (TempDir, Repository) {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+
+    for (idx, paths) in commits.iter().enumerate() {
+        let mut index = repo.index().unwrap();
+        for path in *paths {
+            let full = tmp.path().join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(&full, format!("{} {}\n", path, idx)).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = repo.head().ok().map(|h| h.peel_to_commit().unwrap());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, "change", &tree, &parents)
+            .unwrap();
+    }
+    drop(repo);
+    let repo = Repository::open(tmp.path()).unwrap();
+    (tmp, repo)
+}
+
+fn paths(paths: &[&str]) -> HashSet<String> {
+    paths.iter().map(|p| p.to_string()).collect()
+}
+
+#[test]
+fn test_stats_ignore_untracked_paths() {
+    let tracked = paths(&["api/handler.go", "web/view.html"]);
+    let mut stats = CoChangeStats::default();
+    for _ in 0..3 {
+        stats.record(&["api/handler.go", "web/view.html", "go.sum"], &tracked);
+    }
+    stats.record(&["api/handler.go"], &tracked);
+
+    assert_eq!(stats.together("web/view.html", "api/handler.go"), 3);
+    assert_eq!(stats.together("api/handler.go", "go.sum"), 0);
+    assert_eq!(stats.affinity("api/handler.go", "web/view.html"), 0.75);
+
+    let config = HistoryConfig {
+        affinity: 0.8,
+        ..Default::default()
+    };
+    assert!(stats.affine_pairs(&config).is_empty());
+}
+
+#[test]
+fn test_mine_counts_commits_changing_files_together() {
+    let (_tmp, repo) = repo_with_history(&[
+        &["api/handler.go", "web/view.html", "README.md"],
+        &["api/handler.go", "web/view.html"],
+        &["api/handler.go", "web/view.html"],
+        &["docs/guide.md"],
+        &["docs/guide.md", "api/handler.go"],
+    ]);
+    let tracked = paths(&["api/handler.go", "web/view.html", "docs/guide.md"]);
+
+    let stats = mine(&repo, &tracked, 100).unwrap();
+    assert_eq!(stats.together("api/handler.go", "web/view.html"), 3);
+    assert_eq!(stats.together("api/handler.go", "docs/guide.md"), 1);
+
+    // Only the most recent commits are learned from
+    let recent = mine(&repo, &tracked, 2).unwrap();
+    assert_eq!(recent.together("api/handler.go", "web/view.html"), 0);
+}
+
+#[test]
+fn test_files_changed_together_are_grouped() {
+    let (_tmp, repo) = repo_with_history(&[
+        &["api/handler.go", "web/view.html"],
+        &["api/handler.go", "web/view.html"],
+        &["api/handler.go", "web/view.html"],
+        &["docs/guide.md"],
+    ]);
+    let mut files: Vec<ChangedFile> = ["api/handler.go", "web/view.html", "docs/guide.md"]
+        .iter()
+        .map(|path| ChangedFile::new(path.to_string(), Status::WT_MODIFIED))
+        .collect();
+
+    let edges = co_change_edges(&repo, &files, &HistoryConfig::default()).unwrap();
+    assert_eq!(
+        edges,
+        [("api/handler.go".to_string(), "web/view.html".to_string())]
+    );
+
+    link_related(&mut files, edges);
+    let groups = build_groups(files, None);
+    assert_eq!(groups.len(), 2);
+    assert!(groups
+        .iter()
+        .any(|group| group.files.len() == 2 && group.scope.as_deref() == Some("api")));
+}
@@ -6,8 +6,10 @@ use git2::Status;
 
 // Import inference functions and types from the library
 use commit_wizard::inference::{
-    build_groups, infer_body_lines, infer_commit_type, infer_description, infer_scope,
+    build_groups, build_groups_with_progress, build_removal_group, file_scope, infer_body_lines,
+    infer_commit_type, infer_description, infer_scope, LAZY_BODY_FILES,
 };
+use commit_wizard::semantic::analyze_patch;
 use commit_wizard::types::{ChangedFile, CommitType};
 
 #[test]
@@ -75,7 +77,7 @@ fn test_infer_commit_type_style_files() {
 
 #[test]
 fn test_infer_scope_with_directory() {
-    assert_eq!(infer_scope("src/main.rs"), Some("src".to_string()));
+    assert_eq!(infer_scope("src/main.rs"), None); // Source roots are no scope
     assert_eq!(infer_scope("backend/api.rs"), Some("backend".to_string()));
     assert_eq!(infer_scope("frontend/ui.js"), Some("frontend".to_string()));
     assert_eq!(infer_scope("docs/guide.md"), Some("docs".to_string()));
@@ -98,12 +100,49 @@ fn test_infer_scope_hidden_directories() {
 
 #[test]
 fn test_infer_scope_nested_paths() {
-    // Should only return first segment
-    assert_eq!(infer_scope("src/api/users.rs"), Some("src".to_string()));
+    // Should return the first segment below source roots
+    assert_eq!(infer_scope("src/api/users.rs"), Some("api".to_string()));
     assert_eq!(
         infer_scope("backend/db/schema.sql"),
         Some("backend".to_string())
     );
+    assert_eq!(
+        infer_scope("internal/auth/token.go"),
+        Some("auth".to_string())
+    );
+    assert_eq!(
+        infer_scope("cmd/server/main.go"),
+        Some("server".to_string())
+    );
+    assert_eq!(
+        infer_scope("app/models/user.rb"),
+        Some("models".to_string())
+    );
+    assert_eq!(infer_scope("lib/utils.js"), None);
+}
+
+#[test]
+fn test_infer_scope_jvm_packages() {
+    assert_eq!(
+        infer_scope("src/main/java/com/acme/billing/Invoice.java"),
+        Some("billing".to_string())
+    );
+    assert_eq!(
+        infer_scope("src/test/kotlin/com/acme/api/UsersTest.kt"),
+        Some("api".to_string())
+    );
+}
+
+#[test]
+fn test_file_scope_prefers_assigned_scope() {
+    let mut file = ChangedFile::new("src/api/users.rs".to_string(), Status::INDEX_MODIFIED);
+    assert_eq!(file_scope(&file), Some("api".to_string()));
+
+    file.scope = Some(Some("server".to_string()));
+    assert_eq!(file_scope(&file), Some("server".to_string()));
+
+    file.scope = Some(None);
+    assert_eq!(file_scope(&file), None);
 }
 
 #[test]
@@ -174,6 +213,26 @@ fn test_infer_body_lines() {
     assert!(body_lines[2].contains("remove") && body_lines[2].contains("tests/test.rs"));
 }
 
+#[test]
+fn test_infer_body_lines_from_declarations() {
+    let mut config = ChangedFile::new("src/config.rs".to_string(), Status::INDEX_MODIFIED);
+    config.symbol_changes = analyze_patch(
+        "src/config.rs",
+        "@@ -1 +1,2 @@\n fn a() {}\n+pub fn parse_config() {}\n",
+    );
+    let files = vec![
+        config,
+        ChangedFile::new("README.md".to_string(), Status::INDEX_MODIFIED),
+    ];
+
+    let body_lines = infer_body_lines(&files);
+
+    assert_eq!(
+        body_lines,
+        vec!["add function `parse_config`", "modify README.md"]
+    );
+}
+
 #[test]
 fn test_infer_body_lines_truncation() {
     // Create more than 20 files
@@ -191,15 +250,15 @@ fn test_infer_body_lines_truncation() {
 #[test]
 fn test_build_groups_single_type() {
     let files = vec![
-        ChangedFile::new("src/main.rs".to_string(), Status::INDEX_MODIFIED),
-        ChangedFile::new("src/lib.rs".to_string(), Status::INDEX_NEW),
+        ChangedFile::new("src/api/mod.rs".to_string(), Status::INDEX_MODIFIED),
+        ChangedFile::new("src/api/users.rs".to_string(), Status::INDEX_NEW),
     ];
 
     let groups = build_groups(files, None);
 
     assert_eq!(groups.len(), 1);
     assert_eq!(groups[0].commit_type, CommitType::Feat);
-    assert_eq!(groups[0].scope, Some("src".to_string()));
+    assert_eq!(groups[0].scope, Some("api".to_string()));
     assert_eq!(groups[0].files.len(), 2);
 }
 
@@ -230,7 +289,7 @@ fn test_build_groups_multiple_types() {
 #[test]
 fn test_build_groups_same_type_different_scopes() {
     let files = vec![
-        ChangedFile::new("src/main.rs".to_string(), Status::INDEX_NEW),
+        ChangedFile::new("src/cli/main.rs".to_string(), Status::INDEX_NEW),
         ChangedFile::new("backend/api.rs".to_string(), Status::INDEX_NEW),
         ChangedFile::new("frontend/ui.js".to_string(), Status::INDEX_NEW),
     ];
@@ -241,7 +300,7 @@ fn test_build_groups_same_type_different_scopes() {
     assert_eq!(groups.len(), 3);
 
     let scopes: Vec<_> = groups.iter().map(|g| g.scope.as_ref()).collect();
-    assert!(scopes.contains(&Some(&"src".to_string())));
+    assert!(scopes.contains(&Some(&"cli".to_string())));
     assert!(scopes.contains(&Some(&"backend".to_string())));
     assert!(scopes.contains(&Some(&"frontend".to_string())));
 }
@@ -272,3 +331,105 @@ fn test_build_groups_ordering_deterministic() {
         assert_eq!(g1.scope, g2.scope);
     }
 }
+
+#[test]
+fn test_build_groups_deleted_only_is_one_removal() {
+    let files = vec![
+        ChangedFile::new("src/legacy/mod.rs".to_string(), Status::INDEX_DELETED),
+        ChangedFile::new("src/legacy/parser.rs".to_string(), Status::WT_DELETED),
+        ChangedFile::new("src/legacy/README.md".to_string(), Status::INDEX_DELETED),
+    ];
+
+    let groups = build_groups(files, Some("LU-7".to_string()));
+
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].commit_type, CommitType::Refactor);
+    assert_eq!(groups[0].scope.as_deref(), Some("legacy"));
+    assert_eq!(groups[0].description, "remove legacy module");
+    assert_eq!(groups[0].files.len(), 3);
+    assert_eq!(groups[0].ticket.as_deref(), Some("LU-7"));
+}
+
+#[test]
+fn test_build_groups_mixed_changes_are_not_a_removal() {
+    let files = vec![
+        ChangedFile::new("src/legacy.rs".to_string(), Status::INDEX_DELETED),
+        ChangedFile::new("src/main.rs".to_string(), Status::INDEX_MODIFIED),
+    ];
+
+    let groups = build_groups(files, None);
+
+    assert!(groups
+        .iter()
+        .all(|g| !g.description.starts_with("remove legacy")));
+}
+
+#[test]
+fn test_build_removal_group_descriptions() {
+    let group = |paths: &[&str]| {
+        let files = paths
+            .iter()
+            .map(|p| ChangedFile::new(p.to_string(), Status::INDEX_DELETED))
+            .collect();
+        build_removal_group(files, None).unwrap()
+    };
+
+    let single = group(&["src/legacy.rs"]);
+    assert_eq!(single.commit_type, CommitType::Refactor);
+    assert_eq!(single.description, "remove legacy module");
+
+    let docs = group(&["docs/old/setup.md", "docs/old/usage.md"]);
+    assert_eq!(docs.commit_type, CommitType::Chore);
+    assert_eq!(docs.description, "remove docs/old");
+
+    // `src` names no module
+    let scattered = group(&["src/a.rs", "src/b.rs"]);
+    assert_eq!(scattered.description, "remove 2 files");
+
+    let config = group(&["old.toml"]);
+    assert_eq!(config.commit_type, CommitType::Chore);
+    assert_eq!(config.description, "remove old.toml");
+
+    let mixed = vec![
+        ChangedFile::new("src/a.rs".to_string(), Status::INDEX_DELETED),
+        ChangedFile::new("src/b.rs".to_string(), Status::INDEX_NEW),
+    ];
+    assert!(build_removal_group(mixed, None).is_none());
+}
+
+#[test]
+fn test_build_groups_defers_bodies_of_large_groups() {
+    let files: Vec<ChangedFile> = (0..=LAZY_BODY_FILES)
+        .map(|i| ChangedFile::new(format!("vendor/lib/file{}.go", i), Status::WT_MODIFIED))
+        .collect();
+    let mut groups = build_groups(files, None);
+    assert_eq!(groups.len(), 1);
+
+    let group = &mut groups[0];
+    assert!(group.body_pending);
+    assert!(group.body_lines.is_empty());
+    // The body is still part of the message before it is kept
+    let body = group.body().into_owned();
+    assert_eq!(
+        body.last().map(String::as_str),
+        Some("... and 181 more files")
+    );
+    assert!(body[0].ends_with("vendor/lib/file0.go"));
+    assert!(group.full_message().contains(&format!("- {}", body[0])));
+
+    group.ensure_body();
+    assert!(!group.body_pending);
+    assert_eq!(group.body_lines, body);
+}
+
+#[test]
+fn test_build_groups_reports_progress() {
+    let files: Vec<ChangedFile> = (0..1200)
+        .map(|i| ChangedFile::new(format!("src/api/file{}.rs", i), Status::WT_MODIFIED))
+        .collect();
+    let mut reports = Vec::new();
+    let groups = build_groups_with_progress(files, None, |done, total| reports.push((done, total)));
+
+    assert_eq!(reports, [(500, 1200), (1000, 1200), (1200, 1200)]);
+    assert_eq!(groups[0].files.len(), 1200);
+}
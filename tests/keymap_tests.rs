@@ -0,0 +1,134 @@
+//! Tests for the key bindings behind the handlers and the help overlay.
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use commit_wizard::extension::RESERVED_KEYS;
+use commit_wizard::keymap::{Command, Key, CONFIRMATION, EDITOR, HELP, MAIN, SECTIONS};
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+    KeyEvent::new(code, modifiers)
+}
+
+#[test]
+fn test_main_commands() {
+    let plain = |c| key(KeyCode::Char(c), KeyModifiers::NONE);
+    assert_eq!(MAIN.command(&plain('c')), Some(Command::Commit));
+    assert_eq!(MAIN.command(&plain('j')), Some(Command::Down));
+    assert_eq!(
+        MAIN.command(&key(KeyCode::Char('C'), KeyModifiers::SHIFT)),
+        Some(Command::CommitAll)
+    );
+    assert_eq!(
+        MAIN.command(&key(KeyCode::Tab, KeyModifiers::NONE)),
+        Some(Command::NextPanel)
+    );
+    assert_eq!(
+        MAIN.command(&key(KeyCode::Tab, KeyModifiers::SHIFT)),
+        Some(Command::PreviousPanel)
+    );
+    assert_eq!(
+        MAIN.command(&key(KeyCode::Char('l'), KeyModifiers::CONTROL)),
+        Some(Command::ClearStatus)
+    );
+    // Plain keys do not fire with Ctrl held
+    assert_eq!(
+        MAIN.command(&key(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+        None
+    );
+    assert_eq!(MAIN.command(&plain('L')), Some(Command::RecentCommits));
+    assert_eq!(MAIN.command(&plain('E')), Some(Command::ExternalEdit));
+    assert_eq!(MAIN.command(&plain('l')), None);
+    assert_eq!(
+        MAIN.command(&key(KeyCode::Down, KeyModifiers::SHIFT)),
+        Some(Command::MoveGroupDown)
+    );
+    assert_eq!(
+        MAIN.command(&key(KeyCode::Up, KeyModifiers::NONE)),
+        Some(Command::Up)
+    );
+
+    assert_eq!(
+        EDITOR.command(&key(KeyCode::Char('s'), KeyModifiers::CONTROL)),
+        Some(Command::Save)
+    );
+    assert_eq!(EDITOR.command(&plain('s')), None);
+}
+
+#[test]
+fn test_confirmation_defaults_to_no() {
+    let plain = |c| key(KeyCode::Char(c), KeyModifiers::NONE);
+    assert_eq!(CONFIRMATION.command(&plain('y')), Some(Command::Confirm));
+    assert_eq!(
+        CONFIRMATION.command(&key(KeyCode::Char('Y'), KeyModifiers::SHIFT)),
+        Some(Command::Confirm)
+    );
+    for code in [KeyCode::Enter, KeyCode::Esc, KeyCode::Char('n')] {
+        assert_eq!(
+            CONFIRMATION.command(&key(code, KeyModifiers::NONE)),
+            Some(Command::Close)
+        );
+    }
+    assert_eq!(CONFIRMATION.command(&plain('q')), None);
+}
+
+#[test]
+fn test_labels() {
+    assert_eq!(MAIN.binding(Command::Down).unwrap().label(), "↓ j");
+    assert_eq!(
+        MAIN.binding(Command::ClearStatus).unwrap().label(),
+        "Ctrl+L"
+    );
+    assert_eq!(MAIN.binding(Command::ToggleMark).unwrap().label(), "Space");
+    // BackTab and Shift+Tab are listed once
+    assert_eq!(
+        MAIN.binding(Command::PreviousPanel).unwrap().label(),
+        "Shift+Tab"
+    );
+    assert_eq!(HELP.binding(Command::Close).unwrap().label(), "? Esc q");
+}
+
+#[test]
+fn test_busy_commands() {
+    assert!(Command::Commit.waits_for_ai());
+    assert!(Command::Commit.waits_for_git());
+    assert!(Command::Push.waits_for_git());
+    assert!(!Command::Push.waits_for_ai());
+    assert!(!Command::Down.waits_for_git());
+    assert!(!Command::Help.waits_for_git());
+}
+
+#[test]
+fn test_main_keys_are_reserved() {
+    // Extensions must not take a key the main view uses
+    for binding in MAIN.bindings {
+        for key in binding.keys {
+            if let Key::Char(c) = key {
+                assert!(
+                    RESERVED_KEYS.contains(c),
+                    "'{}' ({:?}) is not reserved",
+                    c,
+                    binding.command
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_no_key_bound_twice() {
+    for section in SECTIONS {
+        let mut seen = Vec::new();
+        for binding in section.bindings {
+            for key in binding.keys {
+                assert!(
+                    !seen.contains(key),
+                    "{:?} is bound twice in '{}'",
+                    key,
+                    section.title
+                );
+                seen.push(*key);
+            }
+            assert!(!binding.description.is_empty());
+        }
+    }
+}
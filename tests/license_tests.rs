@@ -0,0 +1,83 @@
+//! Tests for license header checks.
+
+use std::fs;
+use std::path::Path;
+
+use git2::{Repository, Status};
+use tempfile::TempDir;
+
+use commit_wizard::license::{find_missing_headers, insert_headers, LicenseChecker};
+use commit_wizard::types::ChangedFile;
+
+fn checker() -> LicenseChecker {
+    LicenseChecker::new(
+        "// SPDX-License-Identifier: MIT\n// Copyright (c) {year} ACME",
+        vec!["rs".to_string()],
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_has_header_matches_any_year() {
+    let checker = checker();
+    assert!(checker
+        .has_header("// SPDX-License-Identifier: MIT\n// Copyright (c) 2019 ACME\nfn main() {}\n"));
+    assert!(checker
+        .has_header("// SPDX-License-Identifier: MIT\r\n// Copyright (c) 2019-2024 ACME\r\n"));
+    assert!(!checker.has_header("// Copyright (c) 2019 ACME\n"));
+}
+
+#[test]
+fn test_applies_to_extension() {
+    let checker = checker();
+    assert!(checker.applies_to("src/lib.rs"));
+    assert!(checker.applies_to("src/LIB.RS"));
+    assert!(!checker.applies_to("README.md"));
+    assert!(!checker.applies_to("Makefile"));
+}
+
+#[test]
+fn test_insert_header_keeps_shebang() {
+    let checker = LicenseChecker::new("# Copyright {year}", vec!["py".to_string()]).unwrap();
+    let result = checker.insert_header("#!/usr/bin/env python\nprint('hi')\n");
+    let lines: Vec<_> = result.lines().collect();
+    assert_eq!(lines[0], "#!/usr/bin/env python");
+    assert!(lines[1].starts_with("# Copyright 2"));
+    assert_eq!(lines[2], "");
+    assert_eq!(lines[3], "print('hi')");
+    assert!(checker.has_header(&result));
+}
+
+#[test]
+fn test_find_and_insert_missing_headers() {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let checker = checker();
+
+    fs::write(tmp.path().join("new.rs"), "fn main() {}\n").unwrap();
+    fs::write(tmp.path().join("old.rs"), "fn old() {}\n").unwrap();
+    fs::write(tmp.path().join("notes.md"), "# Notes\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("new.rs")).unwrap();
+    index.write().unwrap();
+
+    let files = vec![
+        ChangedFile::new("new.rs".to_string(), Status::INDEX_NEW),
+        ChangedFile::new("old.rs".to_string(), Status::INDEX_MODIFIED),
+        ChangedFile::new("notes.md".to_string(), Status::WT_NEW),
+    ];
+
+    // Only newly added files with a matching extension are flagged
+    let missing = find_missing_headers(&checker, tmp.path(), &files);
+    assert_eq!(missing, vec!["new.rs".to_string()]);
+
+    let updated = insert_headers(&checker, &repo, &missing).unwrap();
+    assert_eq!(updated, 1);
+    assert!(find_missing_headers(&checker, tmp.path(), &files).is_empty());
+
+    // The staged blob contains the header as well
+    let index = repo.index().unwrap();
+    let entry = index.get_path(Path::new("new.rs"), 0).unwrap();
+    let blob = repo.find_blob(entry.id).unwrap();
+    assert!(checker.has_header(std::str::from_utf8(blob.content()).unwrap()));
+}
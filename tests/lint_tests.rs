@@ -0,0 +1,69 @@
+//! Tests for the live checks of the edited commit message.
+
+use commit_wizard::config::ScopeConfig;
+use commit_wizard::lint::{MessageLint, Severity, MAX_BODY_LINE_LENGTH};
+use commit_wizard::types::CommitType;
+use commit_wizard::validation::ScopePolicy;
+
+fn rules(lint: &MessageLint) -> Vec<&str> {
+    lint.issues.iter().map(|issue| issue.rule).collect()
+}
+
+#[test]
+fn test_clean_message() {
+    let lint = MessageLint::check(
+        "fix(git): handle detached HEAD\n\n- Read the commit instead of the branch\n\nCloses #12: Crash",
+        CommitType::Fix,
+        &ScopePolicy::default(),
+    );
+    assert!(lint.issues.is_empty(), "{:?}", lint.issues);
+    assert_eq!(lint.errors(), 0);
+    assert_eq!(lint.header_length, 30);
+    assert_eq!(lint.longest_body_line, Some((3, 39)));
+}
+
+#[test]
+fn test_lengths() {
+    let header = format!("feat: {}", "a".repeat(70));
+    let body = "b".repeat(MAX_BODY_LINE_LENGTH + 1);
+    let lint = MessageLint::check(
+        &format!("{}\n\n- short\n{}", header, body),
+        CommitType::Feat,
+        &ScopePolicy::default(),
+    );
+    assert_eq!(rules(&lint), ["header-max-length", "body-max-line-length"]);
+    assert_eq!(lint.issues[1].line, 4);
+    assert_eq!(lint.errors(), 0);
+    assert_eq!(lint.ruler(), "Header 76/72 · Body 73/72 (L4)");
+}
+
+#[test]
+fn test_header_rules() {
+    let policy = ScopePolicy::from_config(&ScopeConfig {
+        allowed: vec!["auth".into()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    // Errors come first
+    let lint = MessageLint::check("feat(atuh): add login\nBody", CommitType::Feat, &policy);
+    assert_eq!(rules(&lint), ["scope-enum", "body-leading-blank"]);
+    assert_eq!(lint.issues[0].severity, Severity::Error);
+    assert_eq!(lint.issues[1].severity, Severity::Warning);
+
+    let lint = MessageLint::check("feat(auth): ", CommitType::Feat, &policy);
+    assert_eq!(rules(&lint), ["subject-empty"]);
+
+    let lint = MessageLint::check("feture: add login", CommitType::Feat, &policy);
+    assert_eq!(rules(&lint), ["type-enum", "type-fixed"]);
+
+    // The editor keeps the group's type
+    let lint = MessageLint::check("fix: add login", CommitType::Feat, &policy);
+    assert_eq!(rules(&lint), ["type-fixed"]);
+    assert!(lint.issues[0].to_string().contains("stays 'feat'"));
+
+    // A header without a type becomes the description
+    let lint = MessageLint::check("add login", CommitType::Feat, &policy);
+    assert_eq!(rules(&lint), ["header-format"]);
+    assert_eq!(lint.errors(), 0);
+}
@@ -0,0 +1,55 @@
+//! Tests for the notes attached to groups for the AI.
+
+use std::fs;
+
+use tempfile::TempDir;
+
+use commit_wizard::notes::{resolve_notes, MAX_NOTES_BYTES};
+
+#[test]
+fn test_notes_from_text() {
+    let dir = TempDir::new().unwrap();
+    let notes = resolve_notes(
+        "\n Design: keep v1 for old clients\r\nSee #12 \n",
+        dir.path(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(notes, "Design: keep v1 for old clients\r\nSee #12");
+    assert_eq!(resolve_notes("", dir.path()).unwrap(), None);
+}
+
+#[test]
+fn test_notes_from_file() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir(dir.path().join("docs")).unwrap();
+    fs::write(
+        dir.path().join("docs/design.md"),
+        "# Design\n\nWhy we cache\n",
+    )
+    .unwrap();
+
+    let notes = resolve_notes("@ docs/design.md", dir.path()).unwrap();
+    assert_eq!(notes.as_deref(), Some("# Design\n\nWhy we cache"));
+
+    // An empty file removes the notes
+    fs::write(dir.path().join("empty.txt"), "  \n").unwrap();
+    assert_eq!(resolve_notes("@empty.txt", dir.path()).unwrap(), None);
+
+    let error = resolve_notes("@missing.md", dir.path()).unwrap_err();
+    assert!(format!("{:#}", error).contains("missing.md"));
+}
+
+#[test]
+fn test_long_notes_are_cut() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("long.txt"), "ä".repeat(MAX_NOTES_BYTES)).unwrap();
+    let notes = resolve_notes("@long.txt", dir.path()).unwrap().unwrap();
+    assert_eq!(notes.len(), MAX_NOTES_BYTES);
+    assert!(notes.chars().all(|c| c == 'ä'));
+
+    let notes = resolve_notes(&"x".repeat(MAX_NOTES_BYTES + 10), dir.path())
+        .unwrap()
+        .unwrap();
+    assert_eq!(notes.len(), MAX_NOTES_BYTES);
+}
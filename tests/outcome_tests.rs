@@ -0,0 +1,121 @@
+//! Tests for the exit code contract.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::Context;
+use git2::{Repository, Signature};
+use tempfile::TempDir;
+
+use commit_wizard::outcome::{Outcome, OutcomeError};
+
+fn create_clean_repo() -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    std::fs::write(tmp.path().join("README.md"), "# Test Repo").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("README.md")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .unwrap();
+    tmp
+}
+
+fn create_dirty_repo() -> TempDir {
+    let tmp = create_clean_repo();
+    std::fs::write(tmp.path().join("main.rs"), "fn main() {}\n").unwrap();
+    tmp
+}
+
+fn wizard(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_commit-wizard"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_codes_are_stable() {
+    let codes: Vec<u8> = [
+        Outcome::Success,
+        Outcome::Error,
+        Outcome::NothingToCommit,
+        Outcome::ValidationFailed,
+        Outcome::AiUnavailable,
+        Outcome::CommitFailed,
+        Outcome::Usage,
+    ]
+    .iter()
+    .map(|outcome| outcome.code())
+    .collect();
+    assert_eq!(codes, vec![0, 1, 2, 3, 4, 5, 64]);
+}
+
+#[test]
+fn test_outcome_is_found_behind_context() {
+    let error = Err::<(), _>(OutcomeError::new(Outcome::CommitFailed, "hook rejected"))
+        .context("Committing")
+        .context("Running the wizard")
+        .unwrap_err();
+    assert_eq!(Outcome::of(&error), Outcome::CommitFailed);
+    assert_eq!(
+        format!("{:#}", error),
+        "Running the wizard: Committing: hook rejected"
+    );
+}
+
+#[test]
+fn test_clean_repository_exits_with_nothing_to_commit() {
+    let tmp = create_clean_repo();
+    let output = wizard(&["--no-ai", "--repo", tmp.path().to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No changed files detected"));
+}
+
+#[test]
+fn test_invalid_arguments_exit_with_usage_error() {
+    assert_eq!(wizard(&["--no-such-flag"]).status.code(), Some(64));
+    assert_eq!(wizard(&["--no-ai", "--require-ai"]).status.code(), Some(64));
+    assert_eq!(wizard(&["--help"]).status.code(), Some(0));
+}
+
+#[test]
+fn test_require_ai_fails_when_grouping_fails() {
+    let mut server = mockito::Server::new();
+    let _tags = server
+        .mock("GET", "/api/tags")
+        .with_body(r#"{"models": [{"name": "llama3:latest"}]}"#)
+        .create();
+    let _chat = server
+        .mock("POST", "/api/chat")
+        .with_status(400)
+        .with_body("model crashed")
+        .create();
+    let tmp = create_dirty_repo();
+    std::fs::write(
+        tmp.path().join(".commit-wizard.toml"),
+        format!(
+            "[ai]\nprovider = \"ollama\"\nbase_url = \"{}\"\nretries = 0\n",
+            server.url()
+        ),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_commit-wizard"))
+        .args(["--require-ai", "--repo", tmp.path().to_str().unwrap()])
+        .env("HOME", tmp.path())
+        .env("XDG_CONFIG_HOME", tmp.path())
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("AI grouping failed and --require-ai was given"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("model crashed"), "{}", stderr);
+}
@@ -0,0 +1,42 @@
+//! Tests for commit type colors.
+
+use std::collections::HashMap;
+
+use ratatui::style::Color;
+
+use commit_wizard::config::Config;
+use commit_wizard::palette::Palette;
+use commit_wizard::types::CommitType;
+
+#[test]
+fn test_default_palette_colors_every_type() {
+    let palette = Palette::default();
+    assert_eq!(palette.color(CommitType::Feat), Color::Green);
+    assert_eq!(palette.color(CommitType::Fix), Color::Red);
+    for &commit_type in CommitType::all() {
+        assert_ne!(palette.color(commit_type), Color::Reset);
+    }
+}
+
+#[test]
+fn test_palette_overrides_from_config() {
+    let config =
+        Config::from_toml_str("[colors]\nfeat = \"light-green\"\nchore = \"244\"\n").unwrap();
+    let palette = Palette::from_config(&config.colors).unwrap();
+
+    assert_eq!(palette.color(CommitType::Feat), Color::LightGreen);
+    assert_eq!(palette.color(CommitType::Chore), Color::Indexed(244));
+    // Types without an override keep their default
+    assert_eq!(palette.color(CommitType::Fix), Color::Red);
+}
+
+#[test]
+fn test_palette_rejects_unknown_type_and_color() {
+    let unknown_type = HashMap::from([("feature".to_string(), "green".to_string())]);
+    let err = Palette::from_config(&unknown_type).unwrap_err();
+    assert!(err.to_string().contains("Unknown commit type 'feature'"));
+
+    let invalid_color = HashMap::from([("fix".to_string(), "reddish".to_string())]);
+    let err = Palette::from_config(&invalid_color).unwrap_err();
+    assert!(err.to_string().contains("Invalid color 'reddish'"));
+}
@@ -0,0 +1,263 @@
+//! Tests for the WebAssembly plugins.
+
+use std::collections::HashMap;
+
+use git2::Status;
+
+use commit_wizard::config::PluginConfig;
+use commit_wizard::plugin::{truncated_diffs, PluginSet, WasmPlugin};
+use commit_wizard::types::ChangedFile;
+
+/// Builds a plugin whose `export` returns `json`, whatever the input.
+fn plugin_returning(export: &str, json: &str) -> Vec<u8> {
+    let escaped = json.replace('"', "\\\"").replace('\n', "\\n");
+    wat::parse_str(format!(
+        r#"(module
+            (memory (export "memory") 1)
+            (data (i32.const 1024) "{escaped}")
+            (func (export "alloc") (param i32) (result i32) (i32.const 4096))
+            (func (export "{export}") (param i32 i32) (result i64)
+                (i64.or (i64.shl (i64.const 1024) (i64.const 32)) (i64.const {len}))))"#,
+        len = json.len()
+    ))
+    .unwrap()
+}
+
+fn files() -> Vec<ChangedFile> {
+    vec![
+        ChangedFile::new("README.md".to_string(), Status::WT_MODIFIED),
+        ChangedFile::new("src/lib.rs".to_string(), Status::WT_MODIFIED),
+    ]
+}
+
+#[test]
+fn test_truncated_diffs() {
+    let patches = HashMap::from([
+        ("a".to_string(), "short".to_string()),
+        ("b".to_string(), "ääää".to_string()),
+    ]);
+    let diffs = truncated_diffs(patches, 5);
+    assert_eq!(diffs["a"], "short");
+    // Cut at a character boundary
+    assert_eq!(diffs["b"], "ää");
+}
+
+#[test]
+fn test_empty_plugin_set() {
+    let plugins = PluginSet::default();
+    assert!(plugins.is_empty());
+    assert!(plugins
+        .group(files(), &HashMap::new(), "main", None)
+        .unwrap()
+        .is_none());
+}
+
+#[cfg(not(feature = "wasm-plugins"))]
+#[test]
+fn test_plugins_need_the_feature() {
+    let bytes = plugin_returning("group", "{}");
+    let error = WasmPlugin::from_bytes("group.wasm", &bytes, &PluginConfig::default()).unwrap_err();
+    assert!(format!("{:#}", error).contains("wasm-plugins"));
+}
+
+#[cfg(feature = "wasm-plugins")]
+mod runtime {
+    use super::*;
+
+    use commit_wizard::types::{ChangeGroup, CommitType};
+
+    fn load(name: &str, bytes: &[u8]) -> WasmPlugin {
+        WasmPlugin::from_bytes(name, bytes, &PluginConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_group_with_plugin() {
+        let plugin = load(
+            "docs.wasm",
+            &plugin_returning(
+                "group",
+                r#"{"groups": [
+                    {"type": "docs", "scope": "readme", "description": "update readme",
+                     "body": ["Describe the plugins"], "files": ["README.md", "missing.md"]},
+                    {"type": "fix", "description": "dropped", "files": ["README.md"]}
+                ]}"#,
+            ),
+        );
+        assert!(plugin.can_group());
+        assert!(!plugin.can_validate());
+
+        let plugins = PluginSet::new(vec![plugin]);
+        let groups: Vec<ChangeGroup> = plugins
+            .group(files(), &HashMap::new(), "main", Some("LU-1".to_string()))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(groups[0].commit_type, CommitType::Docs);
+        assert_eq!(groups[0].header(), "docs(readme): LU-1: update readme");
+        assert_eq!(groups[0].body_lines, ["Describe the plugins"]);
+        let paths: Vec<&str> = groups[0].files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["README.md"]);
+        // The second group only had a file taken by the first; the file the
+        // plugin left out is grouped by the heuristics
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1].files[0].path, "src/lib.rs");
+    }
+
+    #[test]
+    fn test_validate_with_plugins() {
+        let group = ChangeGroup::new(
+            CommitType::Feat,
+            None,
+            files(),
+            None,
+            "add plugins".to_string(),
+            vec![],
+        );
+        let accepting = load(
+            "ok.wasm",
+            &plugin_returning("validate", r#"{"errors": []}"#),
+        );
+        let rejecting = load(
+            "ticket.wasm",
+            &plugin_returning("validate", r#"{"errors": ["needs a ticket"]}"#),
+        );
+
+        assert!(PluginSet::new(vec![accepting])
+            .validate(&group)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            PluginSet::new(vec![rejecting]).validate(&group).unwrap(),
+            ["ticket.wasm: needs a ticket"]
+        );
+    }
+
+    #[test]
+    fn test_plugin_sandbox() {
+        // No imports are provided
+        let importing = wat::parse_str(
+            r#"(module
+                (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) (i32.const 0)))"#,
+        )
+        .unwrap();
+        let error =
+            WasmPlugin::from_bytes("io.wasm", &importing, &PluginConfig::default()).unwrap_err();
+        assert!(format!("{:#}", error).contains("fd_write"), "{:#}", error);
+
+        // Endless loops run out of fuel
+        let looping = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                (func (export "group") (param i32 i32) (result i64)
+                    (loop $forever (br $forever))
+                    (i64.const 0)))"#,
+        )
+        .unwrap();
+        let config = PluginConfig {
+            fuel: 10_000,
+            ..PluginConfig::default()
+        };
+        let plugin = WasmPlugin::from_bytes("loop.wasm", &looping, &config).unwrap();
+        let error = plugin
+            .group(files(), &HashMap::new(), "main", None)
+            .unwrap_err();
+        assert!(format!("{:#}", error).contains("loop.wasm"), "{:#}", error);
+
+        // Invalid output is an error
+        let garbage = load("garbage.wasm", &plugin_returning("group", "not json"));
+        assert!(garbage
+            .group(files(), &HashMap::new(), "main", None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_plugin_exports_are_type_checked() {
+        let error_of = |wat: &str| {
+            let bytes = wat::parse_str(wat).unwrap();
+            let error =
+                WasmPlugin::from_bytes("typed.wasm", &bytes, &PluginConfig::default()).unwrap_err();
+            format!("{:#}", error)
+        };
+
+        // A global named like an entry point is no entry point
+        let error = error_of(
+            r#"(module
+                (memory (export "memory") 1)
+                (global (export "group") i32 (i32.const 0))
+                (func (export "alloc") (param i32) (result i32) (i32.const 0)))"#,
+        );
+        assert!(
+            error.contains("`group`, but not as a function"),
+            "{}",
+            error
+        );
+
+        let error = error_of(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                (func (export "validate") (param i32) (result i32) (i32.const 0)))"#,
+        );
+        assert!(error.contains("`validate` has the type"), "{}", error);
+
+        let error = error_of(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "alloc")))"#,
+        );
+        assert!(error.contains("`alloc` has the type"), "{}", error);
+
+        let error = error_of(
+            r#"(module
+                (memory 1)
+                (func (export "memory"))
+                (func (export "alloc") (param i32) (result i32) (i32.const 0)))"#,
+        );
+        assert!(error.contains("memory `memory`"), "{}", error);
+    }
+
+    #[test]
+    fn test_plugin_output_is_checked_before_reading() {
+        // Claims 4 GiB of output at the end of its single 64 KiB page
+        let oversized = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                (func (export "validate") (param i32 i32) (result i64)
+                    (i64.const 0xfff0_ffff_ffff)))"#,
+        )
+        .unwrap();
+        let group = ChangeGroup::new(
+            CommitType::Feat,
+            None,
+            files(),
+            None,
+            "add plugins".to_string(),
+            vec![],
+        );
+
+        let plugin = load("oversized.wasm", &oversized);
+        let error = plugin.validate(&group).unwrap_err();
+        assert!(
+            format!("{:#}", error).contains("exceeds the limit of 1048576 bytes"),
+            "{:#}",
+            error
+        );
+
+        // Without a cap, the output must still lie within the memory
+        let config = PluginConfig {
+            max_output_bytes: usize::MAX,
+            ..PluginConfig::default()
+        };
+        let plugin = WasmPlugin::from_bytes("oversized.wasm", &oversized, &config).unwrap();
+        let error = plugin.validate(&group).unwrap_err();
+        assert!(
+            format!("{:#}", error).contains("Invalid output location"),
+            "{:#}",
+            error
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Tests for the message post-processors.
+#![cfg(unix)]
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use git2::{Repository, Signature, Status};
+use tempfile::TempDir;
+
+use commit_wizard::config::TimeoutConfig;
+use commit_wizard::events::{Action, Event, EventBus};
+use commit_wizard::postprocess::{PostProcessFailure, PostProcessors};
+use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn create_test_repo() -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Test User").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+
+    fs::write(tmp.path().join("README.md"), "# Test Repo").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("README.md")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .unwrap();
+
+    tmp
+}
+
+fn processors(commands: &[&str], on_failure: PostProcessFailure) -> PostProcessors {
+    PostProcessors::new(commands.iter().map(|c| c.to_string()).collect(), on_failure)
+}
+
+fn commit(tmp: &TempDir, post_processors: PostProcessors) -> Event {
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+    let group = ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        vec![ChangedFile::new("a.txt".to_string(), Status::WT_NEW)],
+        None,
+        "add café".to_string(),
+        vec![],
+    );
+    let mut bus = EventBus::start_with_post_processors(
+        tmp.path().to_path_buf(),
+        TimeoutConfig::default(),
+        None,
+        post_processors,
+    );
+    bus.dispatch(Action::Commit(vec![(0, group)])).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while Instant::now() < deadline {
+        match bus.try_event() {
+            Some(event) if event.is_final() => return event,
+            Some(_) => {}
+            None => std::thread::sleep(Duration::from_millis(10)),
+        }
+    }
+    panic!("no final event");
+}
+
+fn head_message(tmp: &TempDir) -> String {
+    let repo = Repository::open(tmp.path()).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    head.message().unwrap().to_string()
+}
+
+#[test]
+fn test_post_processors_run_in_order() {
+    let tmp = create_test_repo();
+    let post_processors = processors(
+        &["tr a-z A-Z", "cat; echo; echo 'Tracking-Id: T-1'"],
+        PostProcessFailure::Block,
+    );
+
+    let processed = post_processors
+        .apply(
+            "feat: add a\n",
+            tmp.path(),
+            TIMEOUT,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+    assert_eq!(processed.message, "FEAT: ADD A\n\nTracking-Id: T-1\n");
+    assert_eq!(processed.warning, None);
+
+    // Without post-processors the message is unchanged
+    let processed = PostProcessors::default()
+        .apply(
+            "feat: add a\n",
+            tmp.path(),
+            TIMEOUT,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+    assert_eq!(processed.message, "feat: add a\n");
+}
+
+#[test]
+fn test_post_processor_failure() {
+    let tmp = create_test_repo();
+    let cancel = AtomicBool::new(false);
+
+    let failing = processors(&["echo broken >&2; exit 3"], PostProcessFailure::Block);
+    let error = failing
+        .apply("feat: add a", tmp.path(), TIMEOUT, &cancel)
+        .unwrap_err();
+    let text = format!("{:#}", error);
+    assert!(text.contains("echo broken >&2; exit 3"), "{}", text);
+    assert!(text.contains("exit code 3: broken"), "{}", text);
+
+    // An empty message is a failure too
+    let empty = processors(&["cat >/dev/null"], PostProcessFailure::Block);
+    assert!(empty
+        .apply("feat: add a", tmp.path(), TIMEOUT, &cancel)
+        .is_err());
+
+    let keeping = processors(&["exit 1"], PostProcessFailure::Keep);
+    let processed = keeping
+        .apply("feat: add a", tmp.path(), TIMEOUT, &cancel)
+        .unwrap();
+    assert_eq!(processed.message, "feat: add a");
+    assert!(processed.warning.unwrap().contains("unchanged"));
+}
+
+#[test]
+fn test_commit_uses_post_processed_message() {
+    let tmp = create_test_repo();
+    let event = commit(
+        &tmp,
+        processors(&["sed 's/é/e/g'"], PostProcessFailure::Block),
+    );
+    assert!(
+        matches!(&event, Event::CommitFinished { committed, error: None, .. } if committed.len() == 1),
+        "{:?}",
+        event
+    );
+    assert_eq!(head_message(&tmp), "feat: add cafe\n");
+}
+
+#[test]
+fn test_commit_blocked_by_failing_post_processor() {
+    let tmp = create_test_repo();
+    let event = commit(&tmp, processors(&["exit 1"], PostProcessFailure::Block));
+    match event {
+        Event::CommitFinished {
+            committed,
+            error: Some(error),
+            ..
+        } => {
+            assert!(committed.is_empty());
+            assert!(format!("{:#}", error).contains("post-processor"));
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+    assert_eq!(head_message(&tmp), "Initial commit");
+}
+
+#[test]
+fn test_commit_keeps_message_if_post_processor_fails() {
+    let tmp = create_test_repo();
+    let event = commit(&tmp, processors(&["exit 1"], PostProcessFailure::Keep));
+    match event {
+        Event::CommitFinished {
+            committed,
+            error: None,
+            ..
+        } => assert!(
+            committed[0].output.starts_with("⚠ "),
+            "{}",
+            committed[0].output
+        ),
+        other => panic!("unexpected event: {:?}", other),
+    }
+    assert_eq!(head_message(&tmp), "feat: add café\n");
+}
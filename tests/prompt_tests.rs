@@ -0,0 +1,104 @@
+//! Tests for diff token budgets.
+
+use std::collections::HashMap;
+
+use commit_wizard::prompt::{
+    condense_diff, distribute_budget, estimate_tokens, fit_diffs, truncate_chars,
+};
+
+#[test]
+fn test_truncate_chars_respects_char_boundaries() {
+    assert_eq!(truncate_chars("äöü", 2), "äö");
+    assert_eq!(truncate_chars("日本語", 1), "日");
+    assert_eq!(truncate_chars("", 5), "");
+    assert_eq!(truncate_chars("abc", 0), "");
+}
+
+#[test]
+fn test_condense_diff_keeps_small_diffs() {
+    let diff = "@@ -1 +1 @@\n-old\n+new\n";
+    assert_eq!(condense_diff(diff, 100), diff);
+}
+
+#[test]
+fn test_condense_diff_prioritizes_headers_and_signatures() {
+    let diff = format!(
+        "diff --git a/src/lib.rs b/src/lib.rs\n\
+         --- a/src/lib.rs\n\
+         +++ b/src/lib.rs\n\
+         @@ -10,40 +10,41 @@\n\
+         {}\
+         +pub fn parse(input: &str) -> Result<Ast> {{\n\
+         {}\
+         -    old_body();\n",
+        " unchanged context line\n".repeat(40),
+        "+    let value = compute(input);\n".repeat(20),
+    );
+
+    let condensed = condense_diff(&diff, 60);
+
+    assert!(estimate_tokens(&condensed) <= 60);
+    assert!(condensed.starts_with("diff --git a/src/lib.rs b/src/lib.rs\n"));
+    assert!(condensed.contains("@@ -10,40 +10,41 @@"));
+    assert!(condensed.contains("+pub fn parse(input: &str)"));
+    assert!(!condensed.contains("unchanged context line"));
+    assert!(condensed.contains("lines omitted"));
+}
+
+#[test]
+fn test_condense_diff_keeps_original_order() {
+    let diff = format!(
+        "@@ -1 +1 @@\n+first added\n{}+fn second() {{}}\n",
+        " context\n".repeat(50)
+    );
+
+    let condensed = condense_diff(&diff, 20);
+    let first = condensed.find("+first added").unwrap();
+    let second = condensed.find("+fn second").unwrap();
+    assert!(first < second);
+}
+
+#[test]
+fn test_condense_diff_shortens_long_lines() {
+    let diff = format!("+{}\n", "ü".repeat(2000));
+    let condensed = condense_diff(&diff, 100);
+
+    assert!(estimate_tokens(&condensed) <= 100);
+}
+
+#[test]
+fn test_distribute_budget() {
+    let tiny = "x".repeat(20);
+    let medium = "x".repeat(800);
+    let large = "x".repeat(8000);
+
+    // Everything fits
+    assert_eq!(distribute_budget(&[&tiny, &medium], 1000), vec![5, 200]);
+
+    // The large diff gets what the others leave over
+    assert_eq!(
+        distribute_budget(&[&large, &tiny, &medium], 600),
+        vec![395, 5, 200]
+    );
+
+    // Too many files: the smallest get a minimal excerpt
+    let many: Vec<&str> = std::iter::repeat(large.as_str()).take(10).collect();
+    let budgets = distribute_budget(&many, 100);
+    assert_eq!(budgets.iter().filter(|&&b| b > 0).count(), 3);
+    assert!(budgets.iter().sum::<usize>() <= 100);
+}
+
+#[test]
+fn test_fit_diffs_orders_by_path_and_counts_omitted() {
+    let diffs: HashMap<String, String> = (0..10)
+        .map(|i| (format!("src/file{}.rs", i), "+changed line\n".repeat(100)))
+        .collect();
+
+    let (fitted, omitted) = fit_diffs(&diffs, 100);
+
+    assert_eq!(fitted.len() + omitted, 10);
+    assert!(omitted > 0);
+    assert_eq!(fitted[0].0, "src/file0.rs");
+    let total: usize = fitted.iter().map(|(_, d)| estimate_tokens(d)).sum();
+    assert!(total <= 100);
+}
@@ -0,0 +1,595 @@
+//! Tests for the pluggable AI provider abstraction.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+use git2::Status;
+use mockito::{Matcher, Server};
+
+use commit_wizard::config::{AiConfig, NetworkConfig};
+use commit_wizard::exclude::{ExcludingProvider, PathFilter};
+use commit_wizard::prompt::DiffBudgetProvider;
+use commit_wizard::provider::{
+    select_provider, AiProvider, ChatMessage, HttpChatProvider, OllamaProvider, ProviderKind,
+    TokenLimitField,
+};
+use commit_wizard::retry::{ApiError, RetryPolicy, RetryingProvider};
+use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType, FileKind};
+
+/// Provider returning canned responses and recording the prompts it saw.
+struct FakeProvider {
+    responses: Mutex<Vec<String>>,
+    prompts: Mutex<Vec<String>>,
+}
+
+impl FakeProvider {
+    fn new(responses: &[&str]) -> Self {
+        Self {
+            responses: Mutex::new(responses.iter().rev().map(|s| s.to_string()).collect()),
+            prompts: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl AiProvider for FakeProvider {
+    fn name(&self) -> &str {
+        "fake"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn complete(&self, prompt: &str) -> Result<String> {
+        self.prompts.lock().unwrap().push(prompt.to_string());
+        self.responses
+            .lock()
+            .unwrap()
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("no more responses"))
+    }
+}
+
+fn files() -> Vec<ChangedFile> {
+    vec![
+        ChangedFile::new("src/api/users.rs".to_string(), Status::INDEX_NEW),
+        ChangedFile::new("README.md".to_string(), Status::INDEX_MODIFIED),
+    ]
+}
+
+#[test]
+fn test_custom_provider_groups_files() {
+    let response = r#"```json
+[
+  {"type": "feat", "scope": "api", "description": "add users endpoint", "files": ["src/api/users.rs"], "body_lines": ["- add handler"]},
+  {"type": "docs", "scope": null, "description": "document users endpoint", "files": ["README.md"], "body_lines": []}
+]
+```"#;
+    let provider = FakeProvider::new(&[response]);
+
+    let groups = provider
+        .group_files(files(), Some("ABC-1".to_string()), &HashMap::new())
+        .unwrap();
+
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].commit_type, CommitType::Feat);
+    assert_eq!(groups[0].scope.as_deref(), Some("api"));
+    assert_eq!(groups[0].body_lines, vec!["add handler"]);
+    assert_eq!(groups[1].commit_type, CommitType::Docs);
+    assert_eq!(groups[1].ticket.as_deref(), Some("ABC-1"));
+    assert!(provider.prompts.lock().unwrap()[0].contains("src/api/users.rs"));
+}
+
+#[test]
+fn test_grouping_reports_invalid_json() {
+    let provider = FakeProvider::new(&["not json at all"]);
+
+    let err = provider
+        .group_files(files(), None, &HashMap::new())
+        .unwrap_err();
+
+    let message = format!("{:#}", err);
+    assert!(message.contains("not valid JSON"));
+    assert!(message.contains("at line 1 column"));
+}
+
+#[test]
+fn test_grouping_repairs_unassigned_files() {
+    let response = r#"{"groups": [{"type": "feat", "scope": "api", "description": "add users endpoint", "files": ["src/api/users.rs"]}]}"#;
+    let provider = FakeProvider::new(&[response]);
+
+    let groups = provider
+        .group_files(files(), None, &HashMap::new())
+        .unwrap();
+
+    // README.md matches no group and gets a heuristic docs group
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].files.len(), 1);
+    assert_eq!(groups[1].commit_type, CommitType::Docs);
+    assert_eq!(groups[1].files[0].path, "README.md");
+}
+
+#[test]
+fn test_grouping_keeps_duplicates_in_first_group() {
+    let response = r#"[
+        {"type": "feat", "description": "add users", "files": ["src/api/users.rs", "README.md"]},
+        {"type": "docs", "description": "document users", "files": ["README.md"]}
+    ]"#;
+    let provider = FakeProvider::new(&[response]);
+
+    let groups = provider
+        .group_files(files(), None, &HashMap::new())
+        .unwrap();
+
+    // The second group only had the duplicate and is dropped
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].files.len(), 2);
+}
+
+#[test]
+fn test_grouping_commits_dependencies_first() {
+    let response = r#"[
+        {"type": "feat", "description": "add users", "files": ["src/api/users.rs"], "depends_on": [2], "order_note": " needs the documented API first "},
+        {"type": "docs", "description": "document users", "files": ["README.md"], "depends_on": ["none", 2], "order_note": ""}
+    ]"#;
+    let provider = FakeProvider::new(&[response]);
+
+    let groups = provider
+        .group_files(files(), None, &HashMap::new())
+        .unwrap();
+
+    // Docs ignores its self reference and moves before the feature
+    assert_eq!(groups[0].commit_type, CommitType::Docs);
+    assert_eq!(groups[0].order_note, None);
+    assert_eq!(groups[1].commit_type, CommitType::Feat);
+    assert_eq!(
+        groups[1].order_note.as_deref(),
+        Some("needs the documented API first")
+    );
+    assert!(provider.prompts.lock().unwrap()[0].contains("depends_on"));
+}
+
+#[test]
+fn test_grouping_reports_unknown_commit_type() {
+    let response = r#"[{"type": "feature", "description": "add users", "files": ["src/api/users.rs", "README.md"]}]"#;
+    let provider = FakeProvider::new(&[response]);
+
+    let err = provider
+        .group_files(files(), None, &HashMap::new())
+        .unwrap_err();
+
+    assert!(err.to_string().contains("group #1"));
+    assert!(err.to_string().contains("unknown commit type 'feature'"));
+}
+
+#[test]
+fn test_grouping_keeps_submodules_separate() {
+    let mut all = files();
+    all.push(
+        ChangedFile::new("vendor/lib".to_string(), Status::INDEX_MODIFIED)
+            .with_kind(FileKind::Submodule),
+    );
+    let response = r#"[{"type": "feat", "description": "add users", "files": ["src/api/users.rs", "README.md"]}]"#;
+    let provider = FakeProvider::new(&[response]);
+
+    let groups = provider.group_files(all, None, &HashMap::new()).unwrap();
+
+    assert_eq!(groups.len(), 2);
+    assert!(!provider.prompts.lock().unwrap()[0].contains("vendor/lib"));
+    assert_eq!(groups[1].files[0].path, "vendor/lib");
+}
+
+#[test]
+fn test_custom_provider_generates_message() {
+    let provider = FakeProvider::new(&[
+        "Sure!\n**START COMMIT MESSAGE**\nadd users endpoint\n**END COMMIT MESSAGE**",
+    ]);
+    let files = files();
+    let group = ChangeGroup::new(
+        CommitType::Feat,
+        Some("api".to_string()),
+        files.clone(),
+        None,
+        "placeholder".to_string(),
+        vec![],
+    );
+
+    let (description, body) = provider.generate_message(&group, &files, None).unwrap();
+
+    assert_eq!(description, "add users endpoint");
+    assert_eq!(body, None);
+}
+
+#[test]
+fn test_http_provider_without_token_is_unavailable() {
+    let provider = HttpChatProvider::new(
+        "Test",
+        "http://localhost:1",
+        None,
+        "model",
+        TokenLimitField::MaxTokens,
+    );
+
+    assert!(!provider.is_available());
+    assert!(provider.complete("hello").is_err());
+}
+
+#[test]
+fn test_wrapped_provider_reports_why_it_is_unavailable() {
+    let inner = HttpChatProvider::new(
+        "Test",
+        "http://localhost:1",
+        None,
+        "model",
+        TokenLimitField::MaxTokens,
+    );
+    let excluding = ExcludingProvider::new(Box::new(inner), PathFilter::new(&["*.lock"]).unwrap());
+    let provider = RetryingProvider::new(
+        Box::new(DiffBudgetProvider::new(Box::new(excluding), 100)),
+        RetryPolicy::default(),
+    );
+
+    assert!(!provider.is_available());
+    assert_eq!(provider.unavailable_reason(), "Test: no API token is set");
+    assert_eq!(provider.name(), "Test");
+    assert_eq!(provider.diff_token_budget(), 100);
+    assert!(provider.excludes("Cargo.lock"));
+}
+
+#[test]
+fn test_http_provider_sends_chat_request() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/")
+        .match_header("authorization", "Bearer secret")
+        .match_body(Matcher::PartialJsonString(
+            r#"{"model": "test-model", "max_completion_tokens": 2000}"#.to_string(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "hello back"}}]}"#)
+        .create();
+
+    let provider = HttpChatProvider::new(
+        "Test",
+        server.url(),
+        Some("secret".to_string()),
+        "test-model",
+        TokenLimitField::MaxCompletionTokens,
+    );
+
+    assert!(provider.is_available());
+    assert_eq!(provider.complete("hello").unwrap(), "hello back");
+    mock.assert();
+}
+
+#[test]
+fn test_http_provider_reports_api_errors() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/")
+        .match_body(Matcher::PartialJsonString(
+            r#"{"max_tokens": 2000}"#.to_string(),
+        ))
+        .with_status(401)
+        .with_body("unauthorized")
+        .create();
+
+    let provider = HttpChatProvider::new(
+        "Test",
+        server.url(),
+        Some("secret".to_string()),
+        "test-model",
+        TokenLimitField::MaxTokens,
+    );
+
+    let err = provider.complete("hello").unwrap_err().to_string();
+    assert!(err.contains("401"));
+    assert!(err.contains("unauthorized"));
+    mock.assert();
+}
+
+#[test]
+fn test_http_provider_reports_rate_limits_with_retry_after() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/")
+        .with_status(429)
+        .with_header("retry-after", "7")
+        .with_body("slow down")
+        .create();
+
+    let provider = HttpChatProvider::new(
+        "Test",
+        server.url(),
+        Some("secret".to_string()),
+        "test-model",
+        TokenLimitField::MaxTokens,
+    );
+
+    let err = provider.complete("hello").unwrap_err();
+    let api_error = err.downcast_ref::<ApiError>().unwrap();
+    assert_eq!(api_error.status, 429);
+    assert_eq!(api_error.retry_after, Some(Duration::from_secs(7)));
+    assert!(api_error.is_transient());
+    assert!(err.to_string().contains("slow down"));
+    mock.assert();
+}
+
+#[test]
+fn test_model_name_is_reported() {
+    let http = HttpChatProvider::new(
+        "OpenAI",
+        "http://localhost",
+        None,
+        "gpt-4o-mini",
+        TokenLimitField::MaxTokens,
+    );
+    assert_eq!(http.model_name(), Some("gpt-4o-mini"));
+    assert_eq!(
+        OllamaProvider::new("http://localhost", "llama3").model_name(),
+        Some("llama3")
+    );
+}
+
+#[test]
+fn test_configured_model_applies_to_the_selected_provider() {
+    let mut server = Server::new();
+    server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"models": [{"name": "qwen2.5-coder:latest"}]}"#)
+        .create();
+    let config = AiConfig {
+        model: Some("qwen2.5-coder".to_string()),
+        base_url: Some(server.url()),
+        ..AiConfig::default()
+    };
+
+    let provider = select_provider(ProviderKind::Ollama, &config, &NetworkConfig::default())
+        .expect("Ollama should be available");
+    assert_eq!(provider.model_name(), Some("qwen2.5-coder"));
+}
+
+#[test]
+fn test_configured_model_is_not_sent_to_every_provider_in_auto_mode() {
+    let config = AiConfig {
+        model: Some("gpt-4o-mini".to_string()),
+        base_url: Some("http://127.0.0.1:1".to_string()),
+        ..AiConfig::default()
+    };
+
+    // Whichever provider is available here, it must use its own model
+    if let Some(provider) = select_provider(ProviderKind::Auto, &config, &NetworkConfig::default())
+    {
+        assert_ne!(provider.model_name(), Some("gpt-4o-mini"));
+    }
+}
+
+#[test]
+fn test_ollama_availability_requires_installed_model() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("GET", "/api/tags")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"models": [{"name": "llama3:latest"}, {"name": "qwen2.5-coder:7b"}]}"#)
+        .expect(4)
+        .create();
+
+    assert!(OllamaProvider::new(server.url(), "llama3").is_available());
+    assert!(OllamaProvider::new(server.url(), "qwen2.5-coder:7b").is_available());
+    assert!(!OllamaProvider::new(server.url(), "qwen2.5-coder").is_available());
+    assert!(!OllamaProvider::new(server.url(), "mistral").is_available());
+    mock.assert();
+}
+
+#[test]
+fn test_ollama_unreachable_is_unavailable() {
+    // Nothing listens on port 1
+    assert!(!OllamaProvider::new("http://127.0.0.1:1", "llama3").is_available());
+}
+
+#[test]
+fn test_ollama_sends_chat_request() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/chat")
+        .match_body(Matcher::PartialJsonString(
+            r#"{"model": "qwen2.5-coder", "stream": false}"#.to_string(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"message": {"role": "assistant", "content": "fix parser crash"}, "done": true}"#,
+        )
+        .create();
+
+    let provider = OllamaProvider::new(format!("{}/", server.url()), "qwen2.5-coder");
+    let files = files();
+    let group = ChangeGroup::new(
+        CommitType::Fix,
+        None,
+        files.clone(),
+        None,
+        "placeholder".to_string(),
+        vec![],
+    );
+
+    let (description, body) = provider.generate_message(&group, &files, None).unwrap();
+    assert_eq!(description, "fix parser crash");
+    assert_eq!(body, None);
+    mock.assert();
+}
+
+#[test]
+fn test_custom_provider_refines_message_from_flattened_conversation() {
+    let provider = FakeProvider::new(&[
+        "**START COMMIT MESSAGE**\nadd users endpoint with pagination\n**END COMMIT MESSAGE**",
+    ]);
+    let conversation = vec![
+        ChatMessage::user("Generate a conventional commit message"),
+        ChatMessage::assistant("add users endpoint"),
+        ChatMessage::user("mention the pagination"),
+    ];
+
+    let (description, _) = provider.refine_message(&conversation).unwrap();
+
+    assert_eq!(description, "add users endpoint with pagination");
+    let prompt = &provider.prompts.lock().unwrap()[0];
+    assert!(prompt.contains("ASSISTANT:\nadd users endpoint"));
+    assert!(prompt.ends_with("USER:\nmention the pagination"));
+}
+
+#[test]
+fn test_http_provider_sends_conversation_as_chat_messages() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::Regex(r#""role":"assistant""#.to_string()),
+            Matcher::Regex("mention the migration".to_string()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"choices": [{"message": {"role": "assistant", "content": "add users table migration"}}]}"#)
+        .create();
+
+    let provider = HttpChatProvider::new(
+        "Test",
+        server.url(),
+        Some("secret".to_string()),
+        "test-model",
+        TokenLimitField::MaxTokens,
+    );
+    let conversation = vec![
+        ChatMessage::user("Generate a conventional commit message"),
+        ChatMessage::assistant("add users table"),
+        ChatMessage::user("mention the migration"),
+    ];
+
+    assert_eq!(
+        provider.chat(&conversation).unwrap(),
+        "add users table migration"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_custom_provider_generates_distinct_candidates() {
+    let provider = FakeProvider::new(&[
+        "add users endpoint",
+        "add users endpoint",
+        "introduce user listing API",
+    ]);
+    let files = files();
+    let group = ChangeGroup::new(
+        CommitType::Feat,
+        Some("api".to_string()),
+        files.clone(),
+        None,
+        "placeholder".to_string(),
+        vec![],
+    );
+
+    let candidates = provider.generate_messages(&group, &files, None, 3).unwrap();
+
+    // The default asks once per candidate and drops duplicates
+    assert_eq!(provider.prompts.lock().unwrap().len(), 3);
+    assert_eq!(
+        candidates,
+        vec![
+            ("add users endpoint".to_string(), None),
+            ("introduce user listing API".to_string(), None),
+        ]
+    );
+}
+
+#[test]
+fn test_http_provider_groups_in_json_mode() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/")
+        .match_body(Matcher::PartialJsonString(
+            r#"{"response_format": {"type": "json_object"}}"#.to_string(),
+        ))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"choices": [{"message": {"role": "assistant", "content": "{\"groups\": [{\"type\": \"feat\", \"scope\": null, \"description\": \"add users\", \"files\": [\"src/api/users.rs\", \"README.md\"]}]}"}}]}"#,
+        )
+        .create();
+
+    let provider = HttpChatProvider::new(
+        "Test",
+        server.url(),
+        Some("secret".to_string()),
+        "test-model",
+        TokenLimitField::MaxTokens,
+    );
+    let groups = provider
+        .group_files(files(), None, &HashMap::new())
+        .unwrap();
+
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].files.len(), 2);
+    assert_eq!(groups[0].scope, None);
+    mock.assert();
+}
+
+#[test]
+fn test_ollama_groups_in_json_mode() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/api/chat")
+        .match_body(Matcher::PartialJsonString(r#"{"format": "json"}"#.to_string()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"message": {"role": "assistant", "content": "{\"groups\": [{\"type\": \"docs\", \"description\": \"document users\", \"files\": [\"src/api/users.rs\", \"README.md\"]}]}"}, "done": true}"#,
+        )
+        .create();
+
+    let provider = OllamaProvider::new(server.url(), "llama3");
+    let groups = provider
+        .group_files(files(), None, &HashMap::new())
+        .unwrap();
+
+    assert_eq!(groups[0].commit_type, CommitType::Docs);
+    mock.assert();
+}
+
+#[test]
+fn test_http_provider_requests_n_choices_at_once() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("POST", "/")
+        .match_body(Matcher::PartialJsonString(r#"{"n": 2}"#.to_string()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            r#"{"choices": [
+                {"message": {"role": "assistant", "content": "first"}},
+                {"message": {"role": "assistant", "content": "second"}}
+            ]}"#,
+        )
+        .expect(1)
+        .create();
+
+    let provider = HttpChatProvider::new(
+        "Test",
+        server.url(),
+        Some("secret".to_string()),
+        "test-model",
+        TokenLimitField::MaxTokens,
+    );
+
+    assert_eq!(
+        provider.complete_n("hello", 2).unwrap(),
+        vec!["first", "second"]
+    );
+    mock.assert();
+}
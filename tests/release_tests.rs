@@ -0,0 +1,225 @@
+//! Tests for release detection, release notes and tagging.
+
+use std::fs;
+use std::path::Path;
+
+use git2::{Repository, Signature, Status};
+use tempfile::TempDir;
+
+use commit_wizard::config::ReleaseConfig;
+use commit_wizard::git::create_tag;
+use commit_wizard::release::{
+    check_changelog, check_release_changelog, generate_release_notes, ReleasePolicy,
+};
+use commit_wizard::types::ChangedFile;
+
+/// Helper to create a repository with one commit per subject
+fn create_repo_with_commits(subjects: &[&str]) -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Test User").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    for (idx, subject) in subjects.iter().enumerate() {
+        fs::write(tmp.path().join("file.txt"), idx.to_string()).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("file.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parents: Vec<_> = repo
+            .head()
+            .ok()
+            .map(|h| h.peel_to_commit().unwrap())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<_> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, subject, &tree, &parent_refs)
+            .unwrap();
+    }
+
+    tmp
+}
+
+#[test]
+fn test_default_policy_matches_release_headers() {
+    let policy = ReleasePolicy::from_config(&ReleaseConfig::default())
+        .unwrap()
+        .unwrap();
+
+    let proposal = policy.match_header("chore(release): 1.4.0").unwrap();
+    assert_eq!(proposal.version, "1.4.0");
+    assert_eq!(proposal.tag_name, "v1.4.0");
+    assert!(!proposal.sign);
+
+    // Ticket prefixes and a leading "v" are accepted
+    let proposal = policy
+        .match_header("chore(release): LU-12: v2.0.0-rc.1")
+        .unwrap();
+    assert_eq!(proposal.tag_name, "v2.0.0-rc.1");
+
+    assert!(policy.match_header("chore: release 1.4.0").is_none());
+    assert!(policy.match_header("feat(release): 1.4.0").is_none());
+}
+
+#[test]
+fn test_custom_policy_uses_first_group_and_format() {
+    let config = ReleaseConfig {
+        pattern: r"^release: (\S+)$".to_string(),
+        tag_format: "release-{version}".to_string(),
+        sign: true,
+        ..Default::default()
+    };
+    let policy = ReleasePolicy::from_config(&config).unwrap().unwrap();
+    let proposal = policy.match_header("release: 3.1").unwrap();
+    assert_eq!(proposal.tag_name, "release-3.1");
+    assert!(proposal.sign);
+
+    // Versions that would produce an invalid tag name are ignored
+    assert!(policy.match_header("release: bad..name").is_none());
+}
+
+#[test]
+fn test_policy_config_errors_and_disabled() {
+    let disabled = ReleaseConfig {
+        enabled: false,
+        ..Default::default()
+    };
+    assert!(ReleasePolicy::from_config(&disabled).unwrap().is_none());
+
+    let no_placeholder = ReleaseConfig {
+        tag_format: "latest".to_string(),
+        ..Default::default()
+    };
+    assert!(ReleasePolicy::from_config(&no_placeholder).is_err());
+
+    let bad_pattern = ReleaseConfig {
+        pattern: "(".to_string(),
+        ..Default::default()
+    };
+    assert!(ReleasePolicy::from_config(&bad_pattern).is_err());
+}
+
+#[test]
+fn test_release_notes_since_previous_tag() {
+    let tmp = create_repo_with_commits(&["feat: old feature", "chore(release): 1.0.0"]);
+    create_tag(tmp.path(), "v1.0.0", "Release 1.0.0\n", false).unwrap();
+
+    let tmp_path = tmp.path();
+    let repo = Repository::open(tmp_path).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    for subject in [
+        "fix(api): handle timeouts",
+        "feat: add export",
+        "docs: update readme",
+        "chore(release): 1.1.0",
+    ] {
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let tree = head.tree().unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, subject, &tree, &[&head])
+            .unwrap();
+    }
+
+    let notes = generate_release_notes(&repo, "1.1.0").unwrap();
+    assert_eq!(
+        notes,
+        "Release 1.1.0\n\nFeatures:\n- feat: add export\n\nBug Fixes:\n- fix(api): handle timeouts\n\nDocumentation:\n- docs: update readme\n"
+    );
+}
+
+#[test]
+fn test_create_tag_annotated() {
+    let tmp = create_repo_with_commits(&["chore(release): 0.2.0"]);
+    create_tag(tmp.path(), "v0.2.0", "Release 0.2.0\n", false).unwrap();
+
+    let repo = Repository::open(tmp.path()).unwrap();
+    let tag = repo
+        .revparse_single("refs/tags/v0.2.0")
+        .unwrap()
+        .into_tag()
+        .expect("tag should be annotated");
+    assert_eq!(tag.message(), Some("Release 0.2.0\n"));
+    assert_eq!(
+        tag.target_id(),
+        repo.head().unwrap().peel_to_commit().unwrap().id()
+    );
+
+    // Existing tags and invalid names are rejected
+    assert!(create_tag(tmp.path(), "v0.2.0", "again", false).is_err());
+    assert!(create_tag(tmp.path(), "-d", "x", false).is_err());
+    assert!(create_tag(tmp.path(), "bad..name", "x", false).is_err());
+}
+
+#[test]
+fn test_changelog_check_flags_missing_and_double_counted_commits() {
+    let commits = [
+        ("1111111".to_string(), "feat(api): add export".to_string()),
+        ("2222222".to_string(), "fix: handle timeouts".to_string()),
+        ("3333333".to_string(), "perf: cache lookups".to_string()),
+        ("4444444".to_string(), "docs: update readme".to_string()),
+        ("5555555".to_string(), "feat: parse `--since`".to_string()),
+    ];
+    let added = [
+        "- Add export".to_string(),
+        "- Export now covers **add export** for archives".to_string(),
+        "- Handle timeouts (2222222)".to_string(),
+        "- Parse --since".to_string(),
+    ];
+    let previous = "## 1.0.0\n- Parse --since\n";
+
+    let check = check_changelog(&commits, &added, previous);
+
+    assert_eq!(check.commits, 5);
+    assert_eq!(check.referenced, 3);
+    assert_eq!(check.unreferenced, ["3333333 perf: cache lookups"]);
+    assert_eq!(
+        check.double_counted,
+        [
+            "1111111 feat(api): add export",
+            "5555555 feat: parse `--since`"
+        ]
+    );
+    assert!(!check.is_clean());
+    assert!(check
+        .to_string()
+        .starts_with("5 commit(s) since the previous tag, 3 referenced"));
+}
+
+#[test]
+fn test_release_changelog_compares_with_commits_since_tag() {
+    let tmp = create_repo_with_commits(&["feat: old feature", "chore(release): 1.0.0"]);
+    create_tag(tmp.path(), "v1.0.0", "Release 1.0.0\n", false).unwrap();
+    let changelog = "# Changelog\n\n## 1.0.0\n- Old feature\n";
+    fs::write(tmp.path().join("CHANGELOG.md"), changelog).unwrap();
+    let repo = Repository::open(tmp.path()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("CHANGELOG.md")).unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    for subject in ["feat: add export", "fix: handle timeouts"] {
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, subject, &tree, &[&head])
+            .unwrap();
+    }
+    fs::write(
+        tmp.path().join("CHANGELOG.md"),
+        "# Changelog\n\n## 1.1.0\n- Add export\n\n## 1.0.0\n- Old feature\n",
+    )
+    .unwrap();
+    let files = [
+        ChangedFile::new("Cargo.toml".to_string(), Status::WT_MODIFIED),
+        ChangedFile::new("CHANGELOG.md".to_string(), Status::WT_MODIFIED),
+    ];
+
+    let check = check_release_changelog(&repo, &files).unwrap().unwrap();
+
+    assert_eq!(check.commits, 2);
+    assert_eq!(check.referenced, 1);
+    assert_eq!(check.unreferenced.len(), 1);
+    assert!(check.unreferenced[0].ends_with(" fix: handle timeouts"));
+    assert!(check.double_counted.is_empty());
+    assert!(check_release_changelog(&repo, &files[..1])
+        .unwrap()
+        .is_none());
+}
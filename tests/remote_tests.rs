@@ -0,0 +1,209 @@
+//! Tests for remote resolution and pushing.
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+
+use git2::{Repository, Signature};
+use tempfile::TempDir;
+
+use commit_wizard::config::{RemoteConfig, TimeoutConfig};
+use commit_wizard::git::{pull_rebase_cancellable, push_branch, BranchStatus};
+use commit_wizard::remote::{list_remotes, resolve_remotes};
+
+/// Helper to create a repository on branch `main` with the given remotes
+fn create_repo_with_remotes(remotes: &[&str]) -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    repo.set_head("refs/heads/main").unwrap();
+
+    fs::write(tmp.path().join("README.md"), "# Test Repo").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("README.md")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .unwrap();
+
+    for name in remotes {
+        repo.remote(name, &format!("https://example.com/{}/repo.git", name))
+            .unwrap();
+    }
+
+    tmp
+}
+
+#[test]
+fn test_list_remotes_sorted() {
+    let tmp = create_repo_with_remotes(&["upstream", "fork", "origin"]);
+    let repo = Repository::open(tmp.path()).unwrap();
+    assert_eq!(
+        list_remotes(&repo).unwrap(),
+        vec!["fork", "origin", "upstream"]
+    );
+}
+
+#[test]
+fn test_resolve_defaults_to_origin_and_upstream() {
+    let tmp = create_repo_with_remotes(&["origin", "upstream"]);
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    let targets = resolve_remotes(&repo, "main", &RemoteConfig::default()).unwrap();
+    assert_eq!(targets.push.as_deref(), Some("origin"));
+    assert_eq!(targets.pr_base.as_deref(), Some("upstream"));
+    assert!(targets.is_triangular());
+}
+
+#[test]
+fn test_resolve_single_remote() {
+    let tmp = create_repo_with_remotes(&["mine"]);
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    let targets = resolve_remotes(&repo, "main", &RemoteConfig::default()).unwrap();
+    assert_eq!(targets.push.as_deref(), Some("mine"));
+    assert_eq!(targets.pr_base.as_deref(), Some("mine"));
+    assert!(!targets.is_triangular());
+}
+
+#[test]
+fn test_resolve_honors_git_push_settings() {
+    let tmp = create_repo_with_remotes(&["origin", "fork", "upstream"]);
+    let repo = Repository::open(tmp.path()).unwrap();
+    let mut config = repo.config().unwrap();
+
+    config.set_str("remote.pushDefault", "fork").unwrap();
+    let targets = resolve_remotes(&repo, "main", &RemoteConfig::default()).unwrap();
+    assert_eq!(targets.push.as_deref(), Some("fork"));
+
+    // Branch-specific push remote wins over remote.pushDefault
+    config.set_str("branch.main.pushRemote", "origin").unwrap();
+    let targets = resolve_remotes(&repo, "main", &RemoteConfig::default()).unwrap();
+    assert_eq!(targets.push.as_deref(), Some("origin"));
+}
+
+#[test]
+fn test_resolve_config_overrides_and_ignores_unknown() {
+    let tmp = create_repo_with_remotes(&["origin", "fork", "upstream"]);
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    let config = RemoteConfig {
+        push: Some("fork".to_string()),
+        pr_base: Some("origin".to_string()),
+    };
+    let targets = resolve_remotes(&repo, "main", &config).unwrap();
+    assert_eq!(targets.push.as_deref(), Some("fork"));
+    assert_eq!(targets.pr_base.as_deref(), Some("origin"));
+
+    let config = RemoteConfig {
+        push: Some("missing".to_string()),
+        pr_base: None,
+    };
+    let targets = resolve_remotes(&repo, "main", &config).unwrap();
+    assert_eq!(targets.push.as_deref(), Some("origin"));
+}
+
+#[test]
+fn test_push_branch_to_local_remote() {
+    let tmp = create_repo_with_remotes(&[]);
+    let bare_dir = TempDir::new().unwrap();
+    Repository::init_bare(bare_dir.path()).unwrap();
+
+    let repo = Repository::open(tmp.path()).unwrap();
+    repo.remote("fork", bare_dir.path().to_str().unwrap())
+        .unwrap();
+
+    push_branch(tmp.path(), "fork", "main").unwrap();
+
+    let bare = Repository::open_bare(bare_dir.path()).unwrap();
+    let pushed = bare.refname_to_id("refs/heads/main").unwrap();
+    assert_eq!(pushed, repo.refname_to_id("refs/heads/main").unwrap());
+
+    // Option-like names are rejected before running git
+    assert!(push_branch(tmp.path(), "--mirror", "main").is_err());
+    assert!(push_branch(tmp.path(), "fork", "-f").is_err());
+}
+
+/// Commits `path` with `content` on the checked out branch.
+fn commit_file(repo: &Repository, path: &str, content: &str) {
+    fs::write(repo.workdir().unwrap().join(path), content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(path)).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &sig,
+        &sig,
+        &format!("Add {}", path),
+        &tree,
+        &[&parent],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_branch_status_and_pull_rebase() {
+    let tmp = create_repo_with_remotes(&[]);
+    let bare_dir = TempDir::new().unwrap();
+    let bare = Repository::init_bare(bare_dir.path()).unwrap();
+    bare.set_head("refs/heads/main").unwrap();
+    let repo = Repository::open(tmp.path()).unwrap();
+    assert_eq!(BranchStatus::collect(&repo).unwrap().tracking_label(), None);
+
+    repo.remote("origin", bare_dir.path().to_str().unwrap())
+        .unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("branch.main.remote", "origin").unwrap();
+    config
+        .set_str("branch.main.merge", "refs/heads/main")
+        .unwrap();
+    config.set_str("user.name", "Test User").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+    push_branch(tmp.path(), "origin", "main").unwrap();
+
+    let status = BranchStatus::collect(&repo).unwrap();
+    assert_eq!(status.upstream.as_deref(), Some("origin/main"));
+    assert_eq!((status.ahead, status.behind), (0, 0));
+    assert_eq!(status.head_summary, "Initial commit");
+    assert_eq!(status.head_id.as_deref().map(str::len), Some(7));
+
+    // Someone else pushes, and a local commit is made meanwhile
+    let other_dir = TempDir::new().unwrap();
+    let other = Repository::clone(bare_dir.path().to_str().unwrap(), other_dir.path()).unwrap();
+    commit_file(&other, "theirs.txt", "theirs\n");
+    push_branch(other_dir.path(), "origin", "main").unwrap();
+    commit_file(&repo, "mine.txt", "mine\n");
+    fs::write(tmp.path().join("README.md"), "# Edited").unwrap();
+
+    // Not known before fetching
+    let status = BranchStatus::collect(&repo).unwrap();
+    assert_eq!((status.ahead, status.behind), (1, 0));
+    repo.find_remote("origin")
+        .unwrap()
+        .fetch(&["main"], None, None)
+        .unwrap();
+    let status = BranchStatus::collect(&repo).unwrap();
+    assert!(status.is_behind());
+    assert_eq!(
+        status.tracking_label().as_deref(),
+        Some("origin/main ↑1 ↓1")
+    );
+
+    pull_rebase_cancellable(
+        tmp.path(),
+        &TimeoutConfig::default(),
+        &AtomicBool::new(false),
+    )
+    .unwrap();
+    let status = BranchStatus::collect(&repo).unwrap();
+    assert_eq!((status.ahead, status.behind), (1, 0));
+    assert_eq!(status.head_summary, "Add mine.txt");
+    assert!(tmp.path().join("theirs.txt").exists());
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("README.md")).unwrap(),
+        "# Edited"
+    );
+}
@@ -0,0 +1,142 @@
+//! Tests for refreshing the groups when the working tree changes
+
+use std::fs;
+use std::path::Path;
+
+use commit_wizard::fingerprint::Snapshot;
+use commit_wizard::git::collect_changed_files;
+use commit_wizard::inference::build_groups;
+use commit_wizard::replan::{carry_over_messages, plan_outdated, RescanReport};
+use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+use git2::{Repository, Signature, Status};
+use tempfile::TempDir;
+
+/// Creates a repository with `a.txt` and `b.txt` committed and `a.txt`
+/// modified, and plans the modification.
+fn setup() -> (TempDir, Repository, Vec<ChangeGroup>, Snapshot) {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+    fs::write(dir.path().join("b.txt"), "one\n").unwrap();
+    {
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a.txt")).unwrap();
+        index.add_path(Path::new("b.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test User", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+    }
+    fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+
+    let files = collect_changed_files(&repo, false).unwrap();
+    let snapshot = Snapshot::capture(dir.path(), files.iter().map(|f| f.path.as_str())).unwrap();
+    let groups = build_groups(files, None);
+    (dir, repo, groups, snapshot)
+}
+
+#[test]
+fn test_plan_matches_unchanged_worktree() {
+    let (dir, repo, groups, snapshot) = setup();
+    assert!(!plan_outdated(&repo, dir.path(), &groups, &[], &snapshot).unwrap());
+
+    // New untracked files, e.g. build output, do not outdate the plan
+    fs::write(dir.path().join("out.log"), "built\n").unwrap();
+    assert!(!plan_outdated(&repo, dir.path(), &groups, &[], &snapshot).unwrap());
+}
+
+#[test]
+fn test_plan_outdated_by_new_change() {
+    let (dir, repo, groups, snapshot) = setup();
+    fs::write(dir.path().join("b.txt"), "two\n").unwrap();
+    assert!(plan_outdated(&repo, dir.path(), &groups, &[], &snapshot).unwrap());
+
+    // A file left out of the groups is still part of the plan
+    let left_out = [ChangedFile::new("b.txt".to_string(), Status::WT_MODIFIED)];
+    assert!(!plan_outdated(&repo, dir.path(), &groups, &left_out, &snapshot).unwrap());
+    fs::write(dir.path().join("b.txt"), "three\n").unwrap();
+    assert!(!plan_outdated(&repo, dir.path(), &groups, &left_out, &snapshot).unwrap());
+}
+
+#[test]
+fn test_plan_outdated_by_edit_or_revert() {
+    let (dir, repo, groups, snapshot) = setup();
+    fs::write(dir.path().join("a.txt"), "three\n").unwrap();
+    assert!(plan_outdated(&repo, dir.path(), &groups, &[], &snapshot).unwrap());
+
+    fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+    assert!(plan_outdated(&repo, dir.path(), &groups, &[], &snapshot).unwrap());
+}
+
+#[test]
+fn test_committed_groups_do_not_count_as_planned() {
+    let (dir, repo, mut groups, snapshot) = setup();
+    for group in &mut groups {
+        group.mark_as_committed();
+    }
+    assert!(plan_outdated(&repo, dir.path(), &groups, &[], &snapshot).unwrap());
+}
+
+fn group(paths: &[&str], description: &str) -> ChangeGroup {
+    let files = paths
+        .iter()
+        .map(|path| ChangedFile::new(path.to_string(), Status::WT_MODIFIED))
+        .collect();
+    ChangeGroup::new(
+        CommitType::Chore,
+        None,
+        files,
+        None,
+        description.to_string(),
+        vec![],
+    )
+}
+
+#[test]
+fn test_carry_over_keeps_messages_of_unchanged_groups() {
+    let mut edited = group(&["src/a.rs", "src/b.rs"], "update a and b");
+    edited.commit_type = CommitType::Feat;
+    edited.set_from_commit_text("feat(core): add the parser\n\n- parse headers");
+    edited.skipped = true;
+    edited.notes = Some("Headers come from the RFC".to_string());
+    let mut committed = group(&["docs/guide.md"], "describe the parser");
+    committed.mark_as_committed();
+    let old = vec![edited, committed];
+
+    let mut new = vec![
+        group(&["src/b.rs", "src/a.rs"], "update b and a"),
+        group(&["src/c.rs"], "update c"),
+        group(&["docs/guide.md"], "update guide"),
+    ];
+    assert_eq!(carry_over_messages(&old, &mut new), 1);
+
+    assert_eq!(new[0].commit_type, CommitType::Feat);
+    assert_eq!(new[0].scope.as_deref(), Some("core"));
+    assert_eq!(new[0].description, "add the parser");
+    assert_eq!(new[0].body_lines, ["parse headers"]);
+    assert!(new[0].skipped);
+    assert_eq!(new[0].notes.as_deref(), Some("Headers come from the RFC"));
+    assert!(!new[1].skipped);
+    assert_eq!(new[1].description, "update c");
+    assert_eq!(new[2].description, "update guide");
+}
+
+#[test]
+fn test_rescan_report_counts_new_and_removed_files() {
+    let old = vec![group(&["src/a.rs", "src/b.rs"], "update a and b")];
+    let files = [
+        ChangedFile::new("src/b.rs".to_string(), Status::WT_MODIFIED),
+        ChangedFile::new("notes.txt".to_string(), Status::WT_NEW),
+    ];
+    let mut report = RescanReport::compare(&old, &files);
+    assert_eq!((report.added, report.removed), (1, 1));
+
+    report.groups = 2;
+    assert_eq!(
+        report.to_string(),
+        "1 new, 1 removed file(s); 2 group(s), 0 kept their message"
+    );
+    let unchanged = RescanReport::compare(&old, &old[0].files);
+    assert!(unchanged.to_string().starts_with("no new or removed files"));
+}
@@ -0,0 +1,122 @@
+//! Tests for recording AI exchanges and replaying them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use git2::Status;
+use tempfile::TempDir;
+
+use commit_wizard::provider::{AiProvider, ChatMessage};
+use commit_wizard::replay::{
+    load_captures, CapturingProvider, Recorder, ReplayProvider, RequestKind,
+};
+use commit_wizard::types::{ChangedFile, CommitType};
+
+/// Provider returning canned responses in order.
+struct FakeProvider {
+    responses: Mutex<Vec<String>>,
+}
+
+impl FakeProvider {
+    fn boxed(responses: &[&str]) -> Box<dyn AiProvider> {
+        Box::new(Self {
+            responses: Mutex::new(responses.iter().rev().map(|s| s.to_string()).collect()),
+        })
+    }
+}
+
+impl AiProvider for FakeProvider {
+    fn name(&self) -> &str {
+        "fake"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn complete(&self, _prompt: &str) -> Result<String> {
+        self.responses
+            .lock()
+            .unwrap()
+            .pop()
+            .ok_or_else(|| anyhow::anyhow!("no more responses"))
+    }
+}
+
+fn files() -> Vec<ChangedFile> {
+    vec![
+        ChangedFile::new("src/api/users.rs".to_string(), Status::INDEX_NEW),
+        ChangedFile::new("README.md".to_string(), Status::INDEX_MODIFIED),
+    ]
+}
+
+const GROUPING: &str = r#"[
+    {"type": "feat", "scope": "api", "description": "add users endpoint", "files": ["src/api/users.rs"]},
+    {"type": "docs", "description": "document users endpoint", "files": ["README.md"]}
+]"#;
+
+#[test]
+fn test_recorded_session_replays_the_grouping() {
+    let dir = TempDir::new().unwrap();
+    let recorder = Recorder::create(dir.path()).unwrap();
+    let session = recorder.dir().to_path_buf();
+    let provider = CapturingProvider::new(FakeProvider::boxed(&[GROUPING]), recorder);
+
+    let recorded = provider
+        .group_files(files(), None, &HashMap::new())
+        .unwrap();
+    assert!(provider.complete("one more").is_err());
+
+    let captures = load_captures(&session).unwrap();
+    assert_eq!(captures.len(), 2);
+    assert_eq!(captures[0].provider, "fake");
+    assert_eq!(captures[0].kind, RequestKind::CompleteJson);
+    assert_eq!(captures[1].error.as_deref(), Some("no more responses"));
+
+    let replay = ReplayProvider::new(captures);
+    let replayed = replay.group_files(files(), None, &HashMap::new()).unwrap();
+    assert_eq!(replayed.len(), recorded.len());
+    assert_eq!(replayed[0].commit_type, CommitType::Feat);
+    assert_eq!(replayed[1].description, "document users endpoint");
+
+    // Recorded failures fail again, and nothing is left to replay
+    let err = replay.complete("one more").unwrap_err();
+    assert_eq!(err.to_string(), "no more responses");
+    assert!(replay.is_exhausted());
+}
+
+#[test]
+fn test_replay_matches_prompts_unless_in_order() {
+    let dir = TempDir::new().unwrap();
+    let recorder = Recorder::create(dir.path()).unwrap();
+    let session = recorder.dir().to_path_buf();
+    let provider = CapturingProvider::new(FakeProvider::boxed(&["first", "second"]), recorder);
+    provider.complete("old prompt").unwrap();
+    provider
+        .chat(&[ChatMessage::user("hi"), ChatMessage::assistant("hello")])
+        .unwrap();
+    assert_eq!(fs::read_dir(&session).unwrap().count(), 2);
+
+    let captures = load_captures(&session).unwrap();
+    assert_eq!(captures[1].messages.len(), 2);
+    assert_eq!(
+        captures[1].messages[1].to_chat_message(),
+        ChatMessage::assistant("hello")
+    );
+
+    let exact = ReplayProvider::new(captures.clone());
+    let err = exact.complete("new prompt").unwrap_err();
+    assert!(err.to_string().contains("No recorded complete response"));
+    assert_eq!(exact.complete("old prompt").unwrap(), "first");
+
+    let in_order = ReplayProvider::new(captures).in_order();
+    assert_eq!(in_order.complete("new prompt").unwrap(), "first");
+    assert_eq!(
+        in_order.chat(&[ChatMessage::user("hey")]).unwrap(),
+        "second"
+    );
+    assert_eq!(in_order.prompts()[0], "new prompt");
+    assert_eq!(in_order.name(), "fake (replay)");
+}
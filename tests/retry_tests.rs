@@ -0,0 +1,180 @@
+//! Tests for retrying transient AI API failures.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use commit_wizard::git::Cancelled;
+use commit_wizard::provider::{with_cancel_flag, AiProvider};
+use commit_wizard::retry::{ApiError, RetryPolicy, RetryingProvider};
+
+/// Provider failing with scripted errors, then answering `ok`.
+struct ScriptedProvider {
+    script: Mutex<VecDeque<ApiError>>,
+    calls: Arc<Mutex<usize>>,
+}
+
+impl ScriptedProvider {
+    fn boxed(errors: Vec<ApiError>) -> (Box<dyn AiProvider>, Arc<Mutex<usize>>) {
+        let calls = Arc::new(Mutex::new(0));
+        let provider = Self {
+            script: Mutex::new(errors.into()),
+            calls: calls.clone(),
+        };
+        (Box::new(provider), calls)
+    }
+}
+
+impl AiProvider for ScriptedProvider {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn complete(&self, _prompt: &str) -> Result<String> {
+        *self.calls.lock().unwrap() += 1;
+        match self.script.lock().unwrap().pop_front() {
+            Some(error) => Err(error.into()),
+            None => Ok("ok".to_string()),
+        }
+    }
+}
+
+fn fast_policy(retries: u32) -> RetryPolicy {
+    RetryPolicy {
+        retries,
+        base_delay: Duration::from_millis(1),
+    }
+}
+
+fn unavailable() -> ApiError {
+    ApiError::new(503, None, "API returned error 503 Service Unavailable")
+}
+
+#[test]
+fn test_transient_errors_are_retried() {
+    let (inner, calls) = ScriptedProvider::boxed(vec![unavailable(), unavailable()]);
+    let provider = RetryingProvider::new(inner, fast_policy(3));
+
+    assert_eq!(provider.complete("prompt").unwrap(), "ok");
+    assert_eq!(*calls.lock().unwrap(), 3);
+    assert_eq!(provider.retry_notice(), None);
+}
+
+#[test]
+fn test_gives_up_after_configured_retries() {
+    let errors = vec![unavailable(); 5];
+    let (inner, calls) = ScriptedProvider::boxed(errors);
+    let provider = RetryingProvider::new(inner, fast_policy(2));
+
+    let error = provider.complete("prompt").unwrap_err();
+    assert_eq!(error.downcast_ref::<ApiError>().unwrap().status, 503);
+    assert_eq!(*calls.lock().unwrap(), 3);
+}
+
+#[test]
+fn test_permanent_errors_are_not_retried() {
+    let (inner, calls) = ScriptedProvider::boxed(vec![ApiError::new(401, None, "bad credentials")]);
+    let provider = RetryingProvider::new(inner, fast_policy(3));
+
+    assert!(provider.complete("prompt").is_err());
+    assert_eq!(*calls.lock().unwrap(), 1);
+}
+
+#[test]
+fn test_retry_after_is_respected() {
+    let wait = Duration::from_millis(300);
+    let (inner, calls) =
+        ScriptedProvider::boxed(vec![ApiError::new(429, Some(wait), "rate limited")]);
+    let provider = Arc::new(RetryingProvider::new(inner, fast_policy(3)));
+
+    let worker = {
+        let provider = provider.clone();
+        std::thread::spawn(move || provider.complete("prompt"))
+    };
+
+    // The notice is shown while the retry waits
+    let mut notice = None;
+    for _ in 0..100 {
+        notice = provider.retry_notice();
+        if notice.is_some() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(notice.as_deref(), Some("retrying 1/3"));
+
+    assert_eq!(worker.join().unwrap().unwrap(), "ok");
+    assert_eq!(*calls.lock().unwrap(), 2);
+    assert_eq!(provider.retry_notice(), None);
+}
+
+#[test]
+fn test_cancel_stops_pending_retries() {
+    let wait = Some(Duration::from_secs(30));
+    let errors = vec![ApiError::new(429, wait, "rate limited"); 3];
+    let (inner, calls) = ScriptedProvider::boxed(errors);
+    let provider = Arc::new(RetryingProvider::new(inner, fast_policy(3)));
+    let cancel = Arc::new(AtomicBool::new(false));
+
+    let worker = {
+        let provider = provider.clone();
+        let cancel = cancel.clone();
+        std::thread::spawn(move || with_cancel_flag(cancel, || provider.complete("prompt")))
+    };
+    while provider.retry_notice().is_none() {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    let started = Instant::now();
+    cancel.store(true, Ordering::Relaxed);
+
+    let error = worker.join().unwrap().unwrap_err();
+    assert!(error.is::<Cancelled>(), "{:#}", error);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    // No retry was sent after the cancel
+    assert_eq!(*calls.lock().unwrap(), 1);
+}
+
+#[test]
+fn test_long_retry_after_is_not_waited_for() {
+    let hour = Some(Duration::from_secs(3600));
+    let (inner, calls) =
+        ScriptedProvider::boxed(vec![ApiError::new(429, hour, "daily limit reached")]);
+    let provider = RetryingProvider::new(inner, fast_policy(3));
+
+    assert!(provider.complete("prompt").is_err());
+    assert_eq!(*calls.lock().unwrap(), 1);
+}
+
+#[test]
+fn test_backoff_grows_and_is_capped() {
+    let policy = RetryPolicy {
+        retries: 10,
+        base_delay: Duration::from_millis(500),
+    };
+    assert_eq!(policy.backoff(1, 1.0), Duration::from_millis(500));
+    assert_eq!(policy.backoff(2, 1.0), Duration::from_secs(1));
+    assert_eq!(policy.backoff(20, 1.0), Duration::from_secs(60));
+
+    for attempt in 1..5 {
+        let full = policy.backoff(attempt, 1.0);
+        let delay = policy.delay(attempt, &unavailable()).unwrap();
+        assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+    }
+    assert_eq!(policy.delay(11, &unavailable()), None);
+}
+
+#[test]
+fn test_zero_retries_disable_retrying() {
+    let (inner, calls) = ScriptedProvider::boxed(vec![unavailable()]);
+    let provider = RetryingProvider::new(inner, fast_policy(0));
+
+    assert!(provider.complete("prompt").is_err());
+    assert_eq!(*calls.lock().unwrap(), 1);
+}
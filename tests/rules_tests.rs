@@ -0,0 +1,84 @@
+//! Tests for `[rules]` mapping paths to commit types and scopes.
+
+use git2::Status;
+
+use commit_wizard::config::Config;
+use commit_wizard::inference::build_groups;
+use commit_wizard::rules::PathRules;
+use commit_wizard::types::{ChangedFile, CommitType};
+
+fn rules(toml: &str) -> anyhow::Result<PathRules> {
+    PathRules::new(&Config::from_toml_str(toml)?.rules)
+}
+
+#[test]
+fn test_most_specific_rule_wins() {
+    let rules = rules(
+        r#"
+[rules]
+"src/**" = { scope = "core" }
+"src/db/**" = { type = "refactor", scope = "db" }
+"*.sql" = { type = "chore" }
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(rules.scope("src/db/pool.rs"), Some("db"));
+    assert_eq!(
+        rules.commit_type("src/db/pool.rs"),
+        Some(CommitType::Refactor)
+    );
+    assert_eq!(rules.scope("src/main.rs"), Some("core"));
+    assert_eq!(rules.commit_type("src/main.rs"), None);
+    // A less specific rule still supplies what the more specific one leaves out
+    assert_eq!(rules.commit_type("src/schema.sql"), Some(CommitType::Chore));
+    assert_eq!(rules.scope("src/schema.sql"), Some("core"));
+    assert_eq!(rules.scope("README.md"), None);
+}
+
+#[test]
+fn test_invalid_rules_are_rejected() {
+    let error = rules("[rules]\n\"a/**\" = { type = \"feature\" }\n").unwrap_err();
+    assert!(error.to_string().contains("Unknown commit type 'feature'"));
+
+    let error = rules("[rules]\n\"a/**\" = {}\n").unwrap_err();
+    assert!(error.to_string().contains("sets neither type nor scope"));
+
+    assert!(Config::from_toml_str("[rules]\n\"a/**\" = { kind = \"fix\" }\n").is_err());
+}
+
+#[test]
+fn test_rules_override_heuristics() {
+    let rules = rules(
+        r#"
+[rules]
+"migrations/**" = { type = "chore", scope = "db" }
+"docs/adr/**" = { type = "docs", scope = "adr" }
+"#,
+    )
+    .unwrap();
+    let mut likely_fix = ChangedFile::new("migrations/002_fix.sql".to_string(), Status::INDEX_NEW);
+    likely_fix.fix_likelihood = Some(1.0);
+    let mut files = vec![
+        ChangedFile::new("migrations/001_init.sql".to_string(), Status::INDEX_NEW),
+        likely_fix,
+        ChangedFile::new("docs/adr/0001-use-rust.md".to_string(), Status::INDEX_NEW),
+        ChangedFile::new("src/api/users.rs".to_string(), Status::INDEX_MODIFIED),
+    ];
+
+    rules.assign(&mut files);
+    let groups = build_groups(files, None);
+
+    let summary: Vec<_> = groups
+        .iter()
+        .map(|g| (g.commit_type, g.scope.as_deref(), g.files.len()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (CommitType::Feat, Some("api"), 1),
+            (CommitType::Docs, Some("adr"), 1),
+            (CommitType::Chore, Some("db"), 2),
+        ]
+    );
+}
@@ -0,0 +1,317 @@
+//! Tests for scope inference from package manifests and `[scope] rules`.
+
+use std::fs;
+use std::path::Path;
+
+use git2::{Repository, Signature, Status};
+use tempfile::TempDir;
+
+use commit_wizard::config::Config;
+use commit_wizard::config::{ScopeConfig, ScopeRule};
+use commit_wizard::inference::build_groups;
+use commit_wizard::scope::{
+    package_name, record_alias, resolve_alias, ScopeResolver, ScopeVocabulary, ALIAS_FILE,
+};
+use commit_wizard::types::ChangedFile;
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn resolver(root: &Path, rules: &[(&[&str], &str)]) -> ScopeResolver {
+    let config = ScopeConfig {
+        rules: rules
+            .iter()
+            .map(|(paths, scope)| ScopeRule {
+                paths: paths.iter().map(|p| p.to_string()).collect(),
+                scope: scope.to_string(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+    ScopeResolver::new(root, &config).unwrap()
+}
+
+#[test]
+fn test_cargo_workspace_uses_crate_names() {
+    let dir = TempDir::new().unwrap();
+    write(
+        dir.path(),
+        "Cargo.toml",
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    );
+    write(
+        dir.path(),
+        "crates/core/Cargo.toml",
+        "[package]\nname = \"wizard-core\"\nversion = \"0.1.0\"\n",
+    );
+
+    let mut resolver = resolver(dir.path(), &[]);
+
+    assert_eq!(
+        resolver.resolve("crates/core/src/parser/mod.rs"),
+        Some("wizard-core".to_string())
+    );
+    assert_eq!(
+        resolver.resolve("crates/core/Cargo.toml"),
+        Some("wizard-core".to_string())
+    );
+    // The root manifest names no scope; other files fall back to the path
+    assert_eq!(resolver.resolve("src/main.rs"), None);
+    assert_eq!(resolver.resolve("docs/guide.md"), Some("docs".to_string()));
+}
+
+#[test]
+fn test_ignored_directories_are_configurable() {
+    let dir = TempDir::new().unwrap();
+    let config = ScopeConfig {
+        ignore: vec!["web".to_string(), "components".to_string()],
+        ..Default::default()
+    };
+    let mut resolver = ScopeResolver::new(dir.path(), &config).unwrap();
+
+    assert_eq!(
+        resolver.resolve("web/components/checkout/Cart.tsx"),
+        Some("checkout".to_string())
+    );
+    assert_eq!(resolver.resolve("web/index.ts"), None);
+    // Replacing the list drops the defaults
+    assert_eq!(resolver.resolve("src/main.rs"), Some("src".to_string()));
+}
+
+#[test]
+fn test_resolved_scopes_are_kept_when_omitted() {
+    let dir = TempDir::new().unwrap();
+    let config = ScopeConfig {
+        ignore: vec!["backend".to_string()],
+        ..Default::default()
+    };
+    let mut files = vec![ChangedFile::new(
+        "backend/server.rs".to_string(),
+        Status::INDEX_MODIFIED,
+    )];
+
+    ScopeResolver::new(dir.path(), &config)
+        .unwrap()
+        .assign(&mut files);
+    let groups = build_groups(files, None);
+
+    assert_eq!(groups[0].scope, None);
+}
+
+#[test]
+fn test_npm_and_go_packages() {
+    let dir = TempDir::new().unwrap();
+    write(
+        dir.path(),
+        "packages/web/package.json",
+        r#"{"name": "@acme/web-ui", "private": true}"#,
+    );
+    write(
+        dir.path(),
+        "services/billing/go.mod",
+        "module example.com/shop/billing/v2\n\ngo 1.22\n",
+    );
+    write(dir.path(), "broken/Cargo.toml", "not toml [");
+
+    assert_eq!(
+        package_name(&dir.path().join("packages/web")),
+        Some("web-ui".to_string())
+    );
+    assert_eq!(
+        package_name(&dir.path().join("services/billing")),
+        Some("billing".to_string())
+    );
+    assert_eq!(package_name(&dir.path().join("broken")), None);
+    assert_eq!(package_name(&dir.path().join("missing")), None);
+}
+
+#[test]
+fn test_rules_take_precedence() {
+    let dir = TempDir::new().unwrap();
+    write(
+        dir.path(),
+        "crates/core/Cargo.toml",
+        "[package]\nname = \"core\"\n",
+    );
+
+    let mut resolver = resolver(
+        dir.path(),
+        &[
+            (&["migrations/**", "crates/core/src/db/**"], "db"),
+            (&["*.proto"], "api"),
+        ],
+    );
+
+    assert_eq!(
+        resolver.resolve("crates/core/src/db/schema.rs"),
+        Some("db".to_string())
+    );
+    assert_eq!(
+        resolver.resolve("migrations/001_init.sql"),
+        Some("db".to_string())
+    );
+    assert_eq!(
+        resolver.resolve("proto/users.proto"),
+        Some("api".to_string())
+    );
+    assert_eq!(
+        resolver.resolve("crates/core/src/lib.rs"),
+        Some("core".to_string())
+    );
+}
+
+#[test]
+fn test_assigned_scopes_drive_grouping() {
+    let dir = TempDir::new().unwrap();
+    write(
+        dir.path(),
+        "apps/cli/Cargo.toml",
+        "[package]\nname = \"cli\"\n",
+    );
+    let mut files = vec![
+        ChangedFile::new("apps/cli/src/main.rs".to_string(), Status::INDEX_MODIFIED),
+        ChangedFile::new("apps/cli/src/args.rs".to_string(), Status::INDEX_MODIFIED),
+        ChangedFile::new("src/api/users.rs".to_string(), Status::INDEX_MODIFIED),
+    ];
+
+    resolver(dir.path(), &[]).assign(&mut files);
+    let groups = build_groups(files, None);
+
+    let mut scopes: Vec<_> = groups
+        .iter()
+        .map(|g| (g.scope.clone().unwrap(), g.files.len()))
+        .collect();
+    scopes.sort();
+    assert_eq!(scopes, [("api".to_string(), 1), ("cli".to_string(), 2)]);
+}
+
+#[test]
+fn test_vocabulary_ranks_established_scopes() {
+    let vocabulary = ScopeVocabulary::from_messages(
+        [
+            "feat(api): add users",
+            "fix(api): handle empty body",
+            "feat(auth): add tokens",
+            "fix(auth): expire tokens",
+            "fix(auth)!: drop basic auth",
+            "docs(readme): typo",
+            "chore: bump deps",
+            "Revert \"feat(ui): dark mode\"",
+        ],
+        2,
+    );
+
+    assert_eq!(vocabulary.scopes().collect::<Vec<_>>(), ["auth", "api"]);
+    assert_eq!(vocabulary.uses("readme"), 0);
+    assert_eq!(vocabulary.complete("A"), ["auth", "api"]);
+    assert_eq!(vocabulary.complete("ap"), ["api"]);
+    assert_eq!(vocabulary.canonical("Auth"), Some("auth".to_string()));
+    assert_eq!(vocabulary.canonical("apis"), Some("api".to_string()));
+}
+
+#[test]
+fn test_vocabulary_is_mined_from_history() {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    let mut parents = Vec::new();
+    for message in [
+        "feat(billing): add invoices",
+        "fix(billing): round totals",
+        "docs: readme",
+    ] {
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        let id = repo
+            .commit(Some("HEAD"), &sig, &sig, message, &tree, &parent_refs)
+            .unwrap();
+        parents = vec![repo.find_commit(id).unwrap()];
+    }
+
+    let vocabulary = ScopeVocabulary::mine(&repo, 100, 2).unwrap();
+    assert_eq!(vocabulary.scopes().collect::<Vec<_>>(), ["billing"]);
+    assert!(ScopeVocabulary::mine(&repo, 1, 1).unwrap().is_empty());
+}
+
+#[test]
+fn test_resolver_prefers_learned_scopes() {
+    let dir = TempDir::new().unwrap();
+    write(
+        dir.path(),
+        "crates/Parser/Cargo.toml",
+        "[package]\nname = \"Parser\"\n",
+    );
+    let vocabulary = ScopeVocabulary::from_messages(
+        [
+            "feat(parser): a",
+            "fix(parser): b",
+            "feat(auth): c",
+            "fix(auth): d",
+        ],
+        2,
+    );
+    let mut resolver = resolver(dir.path(), &[(&["*.proto"], "proto")]).with_vocabulary(vocabulary);
+
+    // Established spelling of a package name
+    assert_eq!(
+        resolver.resolve("crates/Parser/src/lib.rs"),
+        Some("parser".to_string())
+    );
+    // A known scope deeper in the path wins over an unknown inferred one
+    assert_eq!(
+        resolver.resolve("services/auth/token.rs"),
+        Some("auth".to_string())
+    );
+    // Unknown scopes and rules are kept
+    assert_eq!(
+        resolver.resolve("tools/release.sh"),
+        Some("tools".to_string())
+    );
+    assert_eq!(
+        resolver.resolve("auth/users.proto"),
+        Some("proto".to_string())
+    );
+}
+
+#[test]
+fn test_recorded_aliases_extend_the_config() {
+    let dir = TempDir::new().unwrap();
+    write(
+        dir.path(),
+        ".commit-wizard.toml",
+        "[scope.aliases]\nfrontend = \"ui\"\n",
+    );
+
+    record_alias(dir.path(), "users", "user").unwrap();
+    // Renaming the target again keeps the table free of chains
+    record_alias(dir.path(), "user", "accounts").unwrap();
+    let text = fs::read_to_string(dir.path().join(ALIAS_FILE)).unwrap();
+    assert!(text.contains("users = \"accounts\""), "{}", text);
+    assert!(text.contains("user = \"accounts\""), "{}", text);
+
+    let config = Config::load(dir.path(), None).unwrap();
+    let aliases = &config.scope.aliases;
+    assert_eq!(resolve_alias(aliases, "users"), "accounts");
+    assert_eq!(resolve_alias(aliases, "frontend"), "ui");
+    assert_eq!(resolve_alias(aliases, "api"), "api");
+
+    // Renaming back removes the alias of the new target
+    record_alias(dir.path(), "accounts", "user").unwrap();
+    let config = Config::load(dir.path(), None).unwrap();
+    assert_eq!(resolve_alias(&config.scope.aliases, "users"), "user");
+    assert_eq!(resolve_alias(&config.scope.aliases, "accounts"), "user");
+}
+
+#[test]
+fn test_alias_cycles_terminate() {
+    let aliases = [("a", "b"), ("b", "a")]
+        .iter()
+        .map(|(from, to)| (from.to_string(), to.to_string()))
+        .collect();
+    assert_eq!(resolve_alias(&aliases, "a"), "a");
+}
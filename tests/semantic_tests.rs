@@ -0,0 +1,237 @@
+//! Tests for declaration-level diff summaries.
+
+use std::collections::HashMap;
+
+use git2::Status;
+
+use commit_wizard::semantic::{analyze_patch, describe_file, describe_files, describe_purpose};
+use commit_wizard::types::{ChangedFile, FileDiff};
+
+fn summarize(path: &str, patch: &str) -> Vec<String> {
+    analyze_patch(path, patch)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn test_rust_declarations() {
+    let patch = "\
+@@ -1,12 +1,14 @@
+ use std::fmt;
+
+-pub struct Bar {
++pub struct Baz {
+     id: u32,
+ }
+
+-pub(crate) fn load(path: &str) -> Config {
++pub(crate) fn load(path: &Path) -> Config {
+     todo!()
+ }
++
++pub const MAX_DEPTH: usize = 8;
+
+-#[deprecated]
+-mod foo;
++async fn parse_config(input: &str) {}
+";
+
+    assert_eq!(
+        summarize("src/config.rs", patch),
+        [
+            "rename `Bar` to `Baz`",
+            "update function `load`",
+            "add constant `MAX_DEPTH`",
+            "remove deprecated `foo` module",
+            "add function `parse_config`",
+        ]
+    );
+}
+
+#[test]
+fn test_hunk_header_names_the_changed_function() {
+    let patch = "\
+@@ -20,6 +20,7 @@ pub fn parse_config(input: &str) -> Config {
+     let mut config = Config::default();
+-    config.name = input.to_string();
++    config.name = input.trim().to_string();
++    config.validate();
+     config
+ }
+@@ -40,3 +41,3 @@ impl Config {
+     fn validate(&self) {
+-        assert!(self.name.len() > 0);
++        assert!(!self.name.is_empty());
+     }
+";
+
+    assert_eq!(
+        summarize("src/config.rs", patch),
+        [
+            "update function `parse_config`",
+            "update function `validate`"
+        ]
+    );
+}
+
+#[test]
+fn test_changes_outside_declarations_are_not_attributed() {
+    let patch = "\
+@@ -1,6 +1,6 @@
+ const LIMIT: usize = 3;
+-use std::io;
++use std::io::{self, Write};
+
+ fn run() {}
+";
+
+    assert!(summarize("src/main.rs", patch).is_empty());
+}
+
+#[test]
+fn test_other_languages() {
+    let python = "\
+@@ -1,4 +1,7 @@
+ class Parser:
+-    def parse(self, text):
++    def parse(self, text, strict=False):
+         pass
++
++async def fetch_config(url):
++    pass
+";
+    assert_eq!(
+        summarize("app/parser.py", python),
+        ["update function `parse`", "add function `fetch_config`"]
+    );
+
+    let typescript = "\
+@@ -1,3 +1,4 @@
+-export function oldName() {}
++export function newName() {}
++export const handler = async (event) => event;
+ export interface Options {}
+";
+    assert_eq!(
+        summarize("src/index.ts", typescript),
+        ["rename `oldName` to `newName`", "add function `handler`"]
+    );
+
+    let go = "\
+@@ -1,2 +1,3 @@
+ package main
++func (s *Server) Start() error { return nil }
++type Config struct {}
+";
+    assert_eq!(
+        summarize("server.go", go),
+        ["add function `Start`", "add struct `Config`"]
+    );
+}
+
+#[test]
+fn test_unknown_languages_are_skipped() {
+    assert!(summarize("README.md", "@@ -1 +1 @@\n-fn old() {}\n+fn new() {}\n").is_empty());
+}
+
+#[test]
+fn test_new_deleted_and_renamed_files() {
+    let new = ChangedFile::new("src/parser/mod.rs".to_string(), Status::INDEX_NEW);
+    let changes = describe_file(&new, "+pub fn parse() {}\n");
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].to_string(), "add `parser` module");
+
+    let deleted = ChangedFile::new("lib/legacy.py".to_string(), Status::WT_DELETED);
+    let changes = describe_file(&deleted, "-def old():\n-    pass\n");
+    assert_eq!(changes[0].to_string(), "remove `legacy` module");
+
+    let renamed = ChangedFile::new("src/settings.rs".to_string(), Status::INDEX_RENAMED)
+        .with_old_path(Some("src/config.rs".to_string()));
+    let changes: Vec<String> =
+        describe_file(&renamed, "@@ -1 +1 @@\n-fn load() {}\n+fn read() {}\n")
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+    assert_eq!(
+        changes,
+        ["rename `config` to `settings`", "rename `load` to `read`"]
+    );
+}
+
+#[test]
+fn test_describe_files_skips_binary_diffs() {
+    let mut files = vec![
+        ChangedFile::new("src/lib.rs".to_string(), Status::INDEX_MODIFIED),
+        ChangedFile::new("assets/logo.rs".to_string(), Status::INDEX_MODIFIED),
+    ];
+    let diffs = HashMap::from([
+        (
+            "src/lib.rs".to_string(),
+            FileDiff {
+                patch: "@@ -1 +1,2 @@\n pub mod a;\n+pub mod b;\n".to_string(),
+                ..Default::default()
+            },
+        ),
+        (
+            "assets/logo.rs".to_string(),
+            FileDiff {
+                patch: "+fn binary() {}\n".to_string(),
+                is_binary: true,
+                ..Default::default()
+            },
+        ),
+    ]);
+
+    describe_files(&mut files, &diffs);
+
+    assert_eq!(files[0].symbol_changes[0].to_string(), "add `b` module");
+    assert!(files[1].symbol_changes.is_empty());
+}
+
+#[test]
+fn test_describe_purpose_from_leading_comments() {
+    let python = "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\n\"\"\"Sync users from the\nold LDAP directory. Runs nightly.\n\"\"\"\nimport ldap\n";
+    assert_eq!(
+        describe_purpose("scripts/sync.py", python).as_deref(),
+        Some("Sync users from the old LDAP directory")
+    );
+
+    let javascript = "/**\n * Polyfills for IE 11.\n */\nexport function install() {}\n";
+    assert_eq!(
+        describe_purpose("web/polyfills.js", javascript).as_deref(),
+        Some("Polyfills for IE 11")
+    );
+
+    let markdown = "# Migration guide for v1\n\nSteps.\n";
+    assert_eq!(
+        describe_purpose("docs/migrate.md", markdown).as_deref(),
+        Some("Migration guide for v1")
+    );
+
+    let licensed = "// Copyright 2020 Example\n\n// Helpers for the old API.\nfn helper() {}\n";
+    assert_eq!(
+        describe_purpose("src/old_api.rs", licensed).as_deref(),
+        Some("Helpers for the old API")
+    );
+}
+
+#[test]
+fn test_describe_purpose_without_comment() {
+    // Attributes are code, so the declarations describe the file
+    let rust = "#[derive(Debug)]\npub struct Legacy;\n";
+    assert_eq!(
+        describe_purpose("src/legacy.rs", rust).as_deref(),
+        Some("defined struct `Legacy`")
+    );
+    assert_eq!(describe_purpose("data/values.csv", "a,b\n1,2\n"), None);
+    assert_eq!(describe_purpose("src/empty.rs", ""), None);
+}
+
+#[test]
+fn test_describe_purpose_shortens_long_sentences() {
+    let content = format!("//! {}\n", "word ".repeat(60));
+    let purpose = describe_purpose("src/long.rs", &content).unwrap();
+    assert!(purpose.ends_with("..."));
+    assert!(purpose.chars().count() <= 123);
+}
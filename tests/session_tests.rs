@@ -0,0 +1,146 @@
+//! Tests for saving and resuming unfinished sessions
+
+use std::fs;
+
+use commit_wizard::fingerprint::Snapshot;
+use commit_wizard::session::{remove_session, session_path, SavedSession, SESSION_FILE};
+use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+use git2::{Oid, Status};
+use tempfile::TempDir;
+
+fn changed(path: &str) -> ChangedFile {
+    ChangedFile::new(path.to_string(), Status::WT_MODIFIED)
+}
+
+fn head() -> Option<Oid> {
+    Some(Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap())
+}
+
+/// Plans a committed docs group and an edited feat group in a temporary
+/// working tree.
+fn setup() -> (TempDir, Vec<ChangeGroup>, Snapshot) {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/api.rs"), "fn api() {}\n").unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "mod api;\n").unwrap();
+    let snapshot = Snapshot::capture(dir.path(), ["src/api.rs", "src/lib.rs"]).unwrap();
+
+    let mut docs = ChangeGroup::new(
+        CommitType::Docs,
+        None,
+        vec![changed("README.md")],
+        None,
+        "describe api".to_string(),
+        vec![],
+    );
+    docs.mark_as_committed_as(Oid::from_str("89abcdef0123456789abcdef0123456789abcdef").unwrap());
+    let mut feat = ChangeGroup::new(
+        CommitType::Feat,
+        Some("api".to_string()),
+        vec![changed("src/api.rs"), changed("src/lib.rs")],
+        Some("LU-7".to_string()),
+        "add api".to_string(),
+        vec![],
+    );
+    feat.set_from_commit_text("feat(api): add the users endpoint\n\n- list users");
+    feat.skipped = true;
+    feat.notes = Some("Needed by the mobile app".to_string());
+    (dir, vec![docs, feat], snapshot)
+}
+
+#[test]
+fn test_session_roundtrip_keeps_edits_and_commits() {
+    let (dir, groups, snapshot) = setup();
+    let path = session_path(dir.path());
+    assert!(path.ends_with(SESSION_FILE));
+
+    SavedSession::capture(&groups, &snapshot, "feature/LU-7", head(), 1_700_000_000)
+        .save(&path)
+        .unwrap();
+    let saved = SavedSession::load(&path).unwrap().unwrap();
+    assert_eq!(saved.progress(), (2, 1));
+
+    let current = vec![changed("src/lib.rs"), changed("src/api.rs")];
+    assert!(saved
+        .matches(dir.path(), "feature/LU-7", head(), &current)
+        .unwrap());
+
+    let restored = saved.restore(current);
+    assert_eq!(restored.len(), 2);
+    assert!(restored[0].is_committed());
+    assert_eq!(restored[0].committed_as, groups[0].committed_as);
+    assert_eq!(restored[0].files[0].path, "README.md");
+    assert!(!restored[1].is_committed());
+    assert_eq!(restored[1].description, "add the users endpoint");
+    assert_eq!(restored[1].body_lines, ["list users"]);
+    assert_eq!(restored[1].ticket.as_deref(), Some("LU-7"));
+    assert!(restored[1].skipped);
+    assert_eq!(
+        restored[1].notes.as_deref(),
+        Some("Needed by the mobile app")
+    );
+    assert_eq!(restored[0].notes, None);
+    let paths: Vec<&str> = restored[1].files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, ["src/api.rs", "src/lib.rs"]);
+}
+
+#[test]
+fn test_session_does_not_match_a_changed_working_tree() {
+    let (dir, groups, snapshot) = setup();
+    let saved = SavedSession::capture(&groups, &snapshot, "main", head(), 0);
+    let current = vec![changed("src/api.rs"), changed("src/lib.rs")];
+
+    assert!(!saved
+        .matches(dir.path(), "other", head(), &current)
+        .unwrap());
+    assert!(!saved.matches(dir.path(), "main", None, &current).unwrap());
+
+    let mut more = current.clone();
+    more.push(changed("src/main.rs"));
+    assert!(!saved.matches(dir.path(), "main", head(), &more).unwrap());
+    assert!(!saved
+        .matches(dir.path(), "main", head(), &current[..1])
+        .unwrap());
+
+    fs::write(dir.path().join("src/api.rs"), "fn api() { todo!() }\n").unwrap();
+    assert!(!saved.matches(dir.path(), "main", head(), &current).unwrap());
+}
+
+#[test]
+fn test_session_keeps_left_out_files() {
+    let (dir, groups, snapshot) = setup();
+    let path = session_path(dir.path());
+    SavedSession::capture(&groups, &snapshot, "main", head(), 0)
+        .with_orphaned_files(&[changed("notes.txt")])
+        .save(&path)
+        .unwrap();
+    let saved = SavedSession::load(&path).unwrap().unwrap();
+
+    // The left-out file must still be changed, but its content may differ
+    let grouped = vec![changed("src/api.rs"), changed("src/lib.rs")];
+    assert!(!saved.matches(dir.path(), "main", head(), &grouped).unwrap());
+    let mut current = grouped.clone();
+    current.push(changed("notes.txt"));
+    assert!(saved.matches(dir.path(), "main", head(), &current).unwrap());
+
+    let orphaned = saved.restore_orphaned(&current);
+    assert_eq!(orphaned.len(), 1);
+    assert_eq!(orphaned[0].path, "notes.txt");
+    let restored = saved.restore(current);
+    assert!(restored
+        .iter()
+        .all(|g| g.files.iter().all(|f| f.path != "notes.txt")));
+}
+
+#[test]
+fn test_session_missing_or_removed() {
+    let dir = TempDir::new().unwrap();
+    let path = session_path(dir.path());
+    assert!(SavedSession::load(&path).unwrap().is_none());
+    remove_session(&path).unwrap();
+
+    fs::write(&path, "not json").unwrap();
+    assert!(SavedSession::load(&path).is_err());
+    remove_session(&path).unwrap();
+    assert!(!path.exists());
+}
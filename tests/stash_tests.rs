@@ -0,0 +1,158 @@
+//! Tests for stashing the changes outside the groups for the session.
+
+use std::fs;
+use std::path::Path;
+
+use git2::{Repository, Signature, Status};
+use tempfile::TempDir;
+
+use commit_wizard::config::TimeoutConfig;
+use commit_wizard::stash::{restore_leftover, stash_marker_path, unrelated_paths, SessionStash};
+use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+
+/// Helper to create a repository with committed `a.txt` and `b.txt`
+fn create_test_repo() -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+    fs::write(tmp.path().join("b.txt"), "b\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("a.txt")).unwrap();
+    index.add_path(Path::new("b.txt")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .unwrap();
+
+    tmp
+}
+
+/// Modifies both files and adds an untracked one
+fn change_files(tmp: &TempDir) {
+    fs::write(tmp.path().join("a.txt"), "a changed\n").unwrap();
+    fs::write(tmp.path().join("b.txt"), "b changed\n").unwrap();
+    fs::write(tmp.path().join("notes.txt"), "notes\n").unwrap();
+}
+
+fn group(paths: &[&str]) -> ChangeGroup {
+    let files = paths
+        .iter()
+        .map(|path| ChangedFile::new(path.to_string(), Status::WT_MODIFIED))
+        .collect();
+    ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        files,
+        None,
+        "change a".to_string(),
+        Vec::new(),
+    )
+}
+
+fn read(tmp: &TempDir, path: &str) -> Option<String> {
+    fs::read_to_string(tmp.path().join(path)).ok()
+}
+
+#[test]
+fn test_unrelated_paths() {
+    let tmp = create_test_repo();
+    change_files(&tmp);
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    let groups = vec![group(&["a.txt"])];
+    assert_eq!(
+        unrelated_paths(&repo, &groups).unwrap(),
+        vec!["b.txt".to_string(), "notes.txt".to_string()]
+    );
+
+    // Skipped and committed groups are not committed by the session
+    let mut skipped = group(&["b.txt"]);
+    skipped.skipped = true;
+    let mut committed = group(&["notes.txt"]);
+    committed.mark_as_committed();
+    let groups = vec![group(&["a.txt"]), skipped, committed];
+    assert_eq!(
+        unrelated_paths(&repo, &groups).unwrap(),
+        vec!["b.txt".to_string(), "notes.txt".to_string()]
+    );
+}
+
+#[test]
+fn test_stash_and_restore() {
+    let tmp = create_test_repo();
+    change_files(&tmp);
+    let repo = Repository::open(tmp.path()).unwrap();
+    let marker = stash_marker_path(repo.path());
+
+    let stash = SessionStash::push(tmp.path(), &[group(&["a.txt"])], &TimeoutConfig::default())
+        .unwrap()
+        .expect("changes to stash");
+    assert_eq!(stash.paths(), ["b.txt", "notes.txt"]);
+    assert!(stash.contains("notes.txt"));
+    assert!(!stash.contains("a.txt"));
+    assert!(marker.exists());
+
+    // Only the group's changes are left
+    assert_eq!(read(&tmp, "a.txt").as_deref(), Some("a changed\n"));
+    assert_eq!(read(&tmp, "b.txt").as_deref(), Some("b\n"));
+    assert_eq!(read(&tmp, "notes.txt"), None);
+
+    // A second stash is refused while the first is held
+    assert!(SessionStash::push(tmp.path(), &[], &TimeoutConfig::default()).is_err());
+
+    stash.restore().unwrap();
+    assert_eq!(read(&tmp, "b.txt").as_deref(), Some("b changed\n"));
+    assert_eq!(read(&tmp, "notes.txt").as_deref(), Some("notes\n"));
+    assert!(!marker.exists());
+}
+
+#[test]
+fn test_stash_nothing_unrelated() {
+    let tmp = create_test_repo();
+    fs::write(tmp.path().join("a.txt"), "a changed\n").unwrap();
+
+    assert!(
+        SessionStash::push(tmp.path(), &[group(&["a.txt"])], &TimeoutConfig::default())
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_stash_restored_on_drop() {
+    let tmp = create_test_repo();
+    change_files(&tmp);
+
+    {
+        let _stash =
+            SessionStash::push(tmp.path(), &[group(&["a.txt"])], &TimeoutConfig::default())
+                .unwrap();
+        assert_eq!(read(&tmp, "b.txt").as_deref(), Some("b\n"));
+    }
+
+    assert_eq!(read(&tmp, "b.txt").as_deref(), Some("b changed\n"));
+    assert_eq!(read(&tmp, "notes.txt").as_deref(), Some("notes\n"));
+}
+
+#[test]
+fn test_restore_leftover() {
+    let tmp = create_test_repo();
+    change_files(&tmp);
+    let repo = Repository::open(tmp.path()).unwrap();
+    let timeouts = TimeoutConfig::default();
+    assert!(!restore_leftover(tmp.path(), &timeouts).unwrap());
+
+    // A killed session never restores its stash
+    let stash =
+        SessionStash::push(tmp.path(), &[group(&["a.txt"])], &TimeoutConfig::default()).unwrap();
+    std::mem::forget(stash);
+    assert_eq!(read(&tmp, "notes.txt"), None);
+
+    assert!(restore_leftover(tmp.path(), &timeouts).unwrap());
+    assert_eq!(read(&tmp, "b.txt").as_deref(), Some("b changed\n"));
+    assert_eq!(read(&tmp, "notes.txt").as_deref(), Some("notes\n"));
+    assert!(!stash_marker_path(repo.path()).exists());
+    assert!(!restore_leftover(tmp.path(), &timeouts).unwrap());
+}
@@ -0,0 +1,168 @@
+//! Tests for `--no-subprocess` mode.
+//!
+//! Disabling external programs is process-wide and permanent, so these
+//! tests live in their own test binary and all run with it disabled.
+
+use std::fs;
+use std::path::Path;
+
+use git2::{Repository, Signature};
+use tempfile::TempDir;
+
+use commit_wizard::config::ContextConfig;
+use commit_wizard::context::run_context_command;
+use commit_wizard::copilot::CopilotCliProvider;
+use commit_wizard::git::{
+    collect_changed_files, commit_group, create_tag, get_file_diff, push_branch,
+};
+use commit_wizard::provider::AiProvider;
+use commit_wizard::subprocess;
+use commit_wizard::types::{ChangeGroup, CommitType};
+
+/// Creates a repository with one commit and disables external programs.
+fn create_test_repo() -> TempDir {
+    subprocess::disable();
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Test User").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+
+    fs::write(tmp.path().join("README.md"), "# Test Repo\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("README.md")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .unwrap();
+    tmp
+}
+
+fn group(repo: &Repository, paths: &[&str], description: &str) -> ChangeGroup {
+    let files = collect_changed_files(repo, true)
+        .unwrap()
+        .into_iter()
+        .filter(|f| paths.contains(&f.path.as_str()))
+        .collect();
+    ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        files,
+        None,
+        description.to_string(),
+        vec!["first line".to_string()],
+    )
+}
+
+#[test]
+fn test_commit_uses_only_the_group_paths() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+    fs::write(tmp.path().join("a.txt"), "a\n").unwrap();
+    fs::write(tmp.path().join("b.txt"), "b\n").unwrap();
+    // b.txt is staged but belongs to another group
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("b.txt")).unwrap();
+    index.write().unwrap();
+
+    let output = commit_group(tmp.path(), &group(&repo, &["a.txt"], "add a")).unwrap();
+    assert!(output.contains("feat: add a"), "{}", output);
+
+    let commit = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(commit.message().unwrap(), "feat: add a\n\n- first line\n");
+    assert_eq!(commit.author().name(), Some("Test User"));
+    let tree = commit.tree().unwrap();
+    assert!(tree.get_path(Path::new("a.txt")).is_ok());
+    assert!(tree.get_path(Path::new("b.txt")).is_err());
+
+    // The other group's file stays staged
+    let remaining = collect_changed_files(&repo, true).unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].path, "b.txt");
+}
+
+#[test]
+fn test_commit_stages_deletions_and_renames() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+    fs::rename(tmp.path().join("README.md"), tmp.path().join("GUIDE.md")).unwrap();
+
+    commit_group(tmp.path(), &group(&repo, &["GUIDE.md"], "rename readme")).unwrap();
+
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    assert!(tree.get_path(Path::new("GUIDE.md")).is_ok());
+    assert!(tree.get_path(Path::new("README.md")).is_err());
+    assert!(collect_changed_files(&repo, true).unwrap().is_empty());
+}
+
+#[test]
+fn test_commit_without_changes_fails() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+    let group = ChangeGroup::new(
+        CommitType::Docs,
+        None,
+        vec![commit_wizard::types::ChangedFile::new(
+            "README.md".to_string(),
+            git2::Status::WT_MODIFIED,
+        )],
+        None,
+        "nothing".to_string(),
+        vec![],
+    );
+    let error = commit_group(tmp.path(), &group).unwrap_err();
+    assert!(format!("{:#}", error).contains("nothing to commit"));
+    assert_eq!(
+        repo.head().unwrap().peel_to_commit().unwrap().message(),
+        Some("Initial commit")
+    );
+}
+
+#[test]
+fn test_staged_diff_and_tags_use_libgit2() {
+    let tmp = create_test_repo();
+    let repo = Repository::open(tmp.path()).unwrap();
+    fs::write(tmp.path().join("README.md"), "# Test Repo\nmore\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("README.md")).unwrap();
+    index.write().unwrap();
+
+    let diff = get_file_diff(&repo, "README.md").unwrap();
+    assert!(
+        diff.contains("diff --git a/README.md b/README.md"),
+        "{}",
+        diff
+    );
+    assert!(diff.contains("+more\n"), "{}", diff);
+
+    create_tag(tmp.path(), "v1.0.0", "Release 1.0.0\n", false).unwrap();
+    let tag = repo
+        .find_reference("refs/tags/v1.0.0")
+        .unwrap()
+        .peel_to_tag()
+        .unwrap();
+    assert_eq!(tag.message(), Some("Release 1.0.0\n"));
+
+    let error = create_tag(tmp.path(), "v1.0.1", "signed", true).unwrap_err();
+    assert!(format!("{:#}", error).contains("--no-subprocess"));
+}
+
+#[test]
+fn test_programs_are_not_started() {
+    let tmp = create_test_repo();
+
+    let error = push_branch(tmp.path(), "origin", "master").unwrap_err();
+    assert!(format!("{:#}", error).contains("`git` was not started"));
+
+    let config = ContextConfig {
+        command: Some("echo hi".to_string()),
+        ..ContextConfig::default()
+    };
+    assert!(run_context_command(&config, tmp.path()).is_err());
+
+    let copilot = CopilotCliProvider { timeout: None };
+    assert!(!copilot.is_available());
+    assert!(copilot.unavailable_reason().contains("--no-subprocess"));
+    assert!(copilot.complete("hello").is_err());
+}
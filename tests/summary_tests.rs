@@ -0,0 +1,101 @@
+//! Tests for the end-of-session report
+
+use commit_wizard::summary::{SessionSummary, SummaryFormat};
+use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+use git2::Status;
+
+fn file(path: &str, added: usize, removed: usize) -> ChangedFile {
+    let mut file = ChangedFile::new(path.to_string(), Status::WT_MODIFIED);
+    file.lines_added = Some(added);
+    file.lines_removed = Some(removed);
+    file
+}
+
+fn test_groups() -> Vec<ChangeGroup> {
+    let mut users = ChangeGroup::new(
+        CommitType::Feat,
+        Some("api".to_string()),
+        vec![
+            file("src/api/users.rs", 10, 2),
+            file("src/api/mod.rs", 1, 0),
+        ],
+        None,
+        "add users endpoint".to_string(),
+        vec![],
+    );
+    users.mark_as_committed_as(
+        git2::Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap(),
+    );
+    let readme = ChangeGroup::new(
+        CommitType::Docs,
+        None,
+        vec![file("README.md", 4, 4)],
+        None,
+        "describe users endpoint".to_string(),
+        vec![],
+    );
+    vec![users, readme]
+}
+
+#[test]
+fn test_summary_counts_committed_and_skipped_groups() {
+    let summary = SessionSummary::from_groups(&test_groups());
+
+    assert_eq!(summary.committed.len(), 1);
+    assert_eq!(summary.committed[0].header, "feat(api): add users endpoint");
+    assert_eq!(summary.committed[0].files, 2);
+    assert_eq!(summary.skipped.len(), 1);
+    assert_eq!(summary.skipped[0].commit, None);
+
+    assert_eq!(summary.totals.groups, 2);
+    assert_eq!(summary.totals.files_committed, 2);
+    // Lines of skipped groups are not counted
+    assert_eq!(summary.totals.lines_added, 11);
+    assert_eq!(summary.totals.lines_removed, 2);
+}
+
+#[test]
+fn test_summary_text_report() {
+    let summary = SessionSummary::from_groups(&test_groups());
+
+    assert_eq!(
+        summary.render(SummaryFormat::Text).unwrap(),
+        "Committed 1 of 2 groups:\n  \
+         0123456 feat(api): add users endpoint (2 files)\n\
+         Skipped 1 group:\n  \
+         docs: describe users endpoint (1 file)\n\
+         Total: 2 files committed, +11 -2 lines"
+    );
+}
+
+#[test]
+fn test_summary_text_report_is_empty_without_groups() {
+    let summary = SessionSummary::from_groups(&[]);
+    assert_eq!(summary.render(SummaryFormat::Text).unwrap(), "");
+}
+
+#[test]
+fn test_summary_json_report() {
+    let summary = SessionSummary::from_groups(&test_groups());
+    let json: serde_json::Value =
+        serde_json::from_str(&summary.render(SummaryFormat::Json).unwrap()).unwrap();
+
+    assert_eq!(
+        json["committed"][0]["commit"],
+        "0123456789abcdef0123456789abcdef01234567"
+    );
+    assert_eq!(json["committed"][0]["files"], 2);
+    assert_eq!(
+        json["skipped"][0]["header"],
+        "docs: describe users endpoint"
+    );
+    assert!(json["skipped"][0]["commit"].is_null());
+    assert_eq!(json["totals"]["committed"], 1);
+    assert_eq!(json["totals"]["skipped"], 1);
+
+    // Scripts always get an object, even for an empty session
+    let empty = SessionSummary::from_groups(&[]);
+    let json: serde_json::Value =
+        serde_json::from_str(&empty.render(SummaryFormat::Json).unwrap()).unwrap();
+    assert_eq!(json["totals"]["groups"], 0);
+}
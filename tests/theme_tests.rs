@@ -0,0 +1,84 @@
+//! Tests for the interface themes.
+
+use std::collections::HashMap;
+
+use ratatui::style::Color;
+
+use commit_wizard::config::Config;
+use commit_wizard::palette::Palette;
+use commit_wizard::theme::{Theme, ThemeName, ROLES};
+use commit_wizard::types::CommitType;
+
+#[test]
+fn test_default_theme_is_dark() {
+    let theme = Theme::default();
+    assert_eq!(theme, Theme::builtin(ThemeName::Dark));
+    assert_eq!(theme.border, Color::Cyan);
+    assert_eq!(theme.highlight, Color::Yellow);
+}
+
+#[test]
+fn test_builtin_themes_differ() {
+    let dark = Theme::builtin(ThemeName::Dark);
+    let light = Theme::builtin(ThemeName::Light);
+    let high_contrast = Theme::builtin(ThemeName::HighContrast);
+    assert_ne!(dark, light);
+    assert_ne!(dark, high_contrast);
+    // Text stays readable on the background the theme is meant for
+    assert_eq!(light.text, Color::Black);
+    assert_eq!(high_contrast.muted, Color::Gray);
+}
+
+#[test]
+fn test_theme_from_config() {
+    let config = Config::from_toml_str(
+        "[ui]\ntheme = \"high-contrast\"\n\n[ui.colors]\ndiff_added = \"28\"\nborder = \"#ffffff\"\n",
+    )
+    .unwrap();
+    assert_eq!(config.ui.theme, ThemeName::HighContrast);
+
+    let theme = Theme::from_config(config.ui.theme, &config.ui.colors).unwrap();
+    assert_eq!(theme.diff_added, Color::Indexed(28));
+    assert_eq!(theme.border, Color::Rgb(255, 255, 255));
+    // Roles without an override keep the theme's color
+    assert_eq!(theme.error, Theme::builtin(ThemeName::HighContrast).error);
+}
+
+#[test]
+fn test_theme_overrides_every_role() {
+    for role in ROLES {
+        let overrides = HashMap::from([(role.to_string(), "#010203".to_string())]);
+        let theme = Theme::from_config(ThemeName::Dark, &overrides).unwrap();
+        assert_ne!(theme, Theme::default(), "{} is not applied", role);
+    }
+}
+
+#[test]
+fn test_theme_rejects_unknown_role_and_color() {
+    let unknown = HashMap::from([("accent".to_string(), "green".to_string())]);
+    let err = Theme::from_config(ThemeName::Dark, &unknown).unwrap_err();
+    assert!(err.to_string().contains("Unknown color role 'accent'"));
+    assert!(err.to_string().contains("diff_added"));
+
+    let invalid = HashMap::from([("border".to_string(), "bluish".to_string())]);
+    let err = Theme::from_config(ThemeName::Dark, &invalid).unwrap_err();
+    assert!(err.to_string().contains("Invalid color 'bluish'"));
+
+    assert!(Config::from_toml_str("[ui]\ntheme = \"solarized\"\n").is_err());
+}
+
+#[test]
+fn test_palette_follows_theme() {
+    let light = Palette::for_theme(ThemeName::Light, &HashMap::new()).unwrap();
+    assert_ne!(light.color(CommitType::Perf), Color::LightYellow);
+    assert_eq!(
+        Palette::for_theme(ThemeName::Dark, &HashMap::new()).unwrap(),
+        Palette::default()
+    );
+
+    // [colors] still wins over the theme
+    let overrides = HashMap::from([("feat".to_string(), "green".to_string())]);
+    let palette = Palette::for_theme(ThemeName::HighContrast, &overrides).unwrap();
+    assert_eq!(palette.color(CommitType::Feat), Color::Green);
+    assert_eq!(palette.color(CommitType::Fix), Color::LightRed);
+}
@@ -0,0 +1,81 @@
+//! Tests for ticket detection in branch names.
+
+use commit_wizard::config::TicketConfig;
+use commit_wizard::ticket::TicketExtractor;
+use commit_wizard::types::{AppState, ChangeGroup, CommitType, TicketPlacement};
+
+fn extractor(patterns: &[&str]) -> TicketExtractor {
+    let config = TicketConfig {
+        patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        ..Default::default()
+    };
+    TicketExtractor::from_config(&config).unwrap()
+}
+
+#[test]
+fn test_default_pattern_matches_jira_keys() {
+    let extractor = TicketExtractor::from_config(&TicketConfig::default()).unwrap();
+    assert_eq!(
+        extractor.ticket_for("feature/LU-1234-add-feature"),
+        Some("LU-1234".to_string())
+    );
+    assert_eq!(
+        extractor.ticket_for("fix/LU-1-and-LU-2"),
+        Some("LU-1, LU-2".to_string())
+    );
+    assert_eq!(extractor.ticket_for("main"), None);
+}
+
+#[test]
+fn test_other_trackers() {
+    let extractor = extractor(&[r"AB#\d+", r"#\d+", r"!\d+"]);
+    assert_eq!(extractor.extract("fix/#123-login"), ["#123"]);
+    assert_eq!(extractor.extract("feature/!45-review"), ["!45"]);
+    assert_eq!(extractor.extract("AB#77/#77"), ["AB#77", "#77"]);
+    // Repeated tickets are reported once
+    assert_eq!(extractor.extract("#5-fix-#5"), ["#5"]);
+}
+
+#[test]
+fn test_capture_group_selects_ticket() {
+    let extractor = extractor(&[r"^(\d+)-"]);
+    assert_eq!(extractor.extract("123-fix-login"), ["123"]);
+    assert!(extractor.extract("fix-123").is_empty());
+}
+
+#[test]
+fn test_invalid_pattern_is_reported() {
+    let config = TicketConfig {
+        patterns: vec!["(".to_string()],
+        ..Default::default()
+    };
+    let err = TicketExtractor::from_config(&config).unwrap_err();
+    assert!(
+        err.to_string().contains("Invalid [ticket] pattern"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_configured_placement_applies_to_all_groups() {
+    let group = ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        vec![],
+        Some("LU-1".to_string()),
+        "add login".to_string(),
+        vec![],
+    );
+    let mut config = commit_wizard::config::Config::default();
+    config.ticket.placement = TicketPlacement::Footer;
+
+    let mut app = AppState::new(vec![group.clone()]).with_config(config);
+    assert_eq!(
+        app.groups[0].full_message(),
+        "feat: add login\n\nRefs: LU-1"
+    );
+
+    app.replace_groups(vec![group]);
+    assert_eq!(app.groups[0].ticket_placement, TicketPlacement::Footer);
+}
@@ -0,0 +1,104 @@
+//! Tests for checking pull request titles
+
+use commit_wizard::config::ScopeConfig;
+use commit_wizard::title::{TitleCheck, TitleFormat, TitleRule};
+use commit_wizard::validation::ScopePolicy;
+
+fn policy(config: ScopeConfig) -> ScopePolicy {
+    ScopePolicy::from_config(&config).unwrap()
+}
+
+fn rules(check: &TitleCheck) -> Vec<TitleRule> {
+    check.problems.iter().map(|p| p.rule).collect()
+}
+
+#[test]
+fn test_valid_titles() {
+    let policy = ScopePolicy::default();
+    for title in [
+        "feat: add login",
+        "fix(api): handle timeouts",
+        "refactor(core)!: drop the v1 API",
+        "  docs: describe the daemon  ",
+    ] {
+        let check = TitleCheck::run(title, &policy);
+        assert!(check.valid, "{}: {:?}", title, check.problems);
+        assert!(check.suggestion.is_none());
+    }
+
+    let check = TitleCheck::run("refactor(core)!: drop the v1 API", &policy);
+    assert_eq!(check.commit_type.as_deref(), Some("refactor"));
+    assert_eq!(check.scope.as_deref(), Some("core"));
+    assert!(check.breaking);
+    assert_eq!(check.description.as_deref(), Some("drop the v1 API"));
+}
+
+#[test]
+fn test_format_and_type_problems() {
+    let policy = ScopePolicy::default();
+
+    let check = TitleCheck::run("Update the readme", &policy);
+    assert_eq!(rules(&check), [TitleRule::Format]);
+    assert!(check.commit_type.is_none());
+
+    let check = TitleCheck::run("feature: add login", &policy);
+    assert_eq!(rules(&check), [TitleRule::Type]);
+    assert!(check.suggestion.is_none());
+
+    let check = TitleCheck::run("Fix:handle timeouts", &policy);
+    assert_eq!(rules(&check), [TitleRule::Format, TitleRule::Type]);
+    assert_eq!(check.suggestion.as_deref(), Some("fix: handle timeouts"));
+
+    let check = TitleCheck::run("docs(api): ", &policy);
+    assert_eq!(rules(&check), [TitleRule::Description]);
+}
+
+#[test]
+fn test_scope_policy_applies_to_titles() {
+    let policy = policy(ScopeConfig {
+        required_for: vec!["feat".into()],
+        forbidden_for: vec!["docs".into()],
+        allowed: vec!["auth".into(), "billing".into()],
+        ..Default::default()
+    });
+
+    let check = TitleCheck::run("feat: add login", &policy);
+    assert_eq!(rules(&check), [TitleRule::Scope]);
+    assert!(check.problems[0].message.contains("require a scope"));
+
+    let check = TitleCheck::run("docs(auth): describe login", &policy);
+    assert_eq!(check.suggestion.as_deref(), Some("docs: describe login"));
+
+    let check = TitleCheck::run("fix(biling)!: round totals", &policy);
+    assert_eq!(
+        check.suggestion.as_deref(),
+        Some("fix(billing)!: round totals")
+    );
+
+    assert!(TitleCheck::run("fix(auth): expire sessions", &policy).valid);
+}
+
+#[test]
+fn test_render_formats() {
+    let check = TitleCheck::run("fix:x", &ScopePolicy::default());
+
+    assert_eq!(
+        check.render(TitleFormat::Text).unwrap(),
+        "✗ fix:x\n  • a space must follow the ':'\n  → fix: x"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&check.render(TitleFormat::Json).unwrap()).unwrap();
+    assert_eq!(json["valid"], false);
+    assert_eq!(json["type"], "fix");
+    assert_eq!(json["problems"][0]["rule"], "format");
+    assert_eq!(json["suggestion"], "fix: x");
+
+    // Annotations are one line each
+    assert_eq!(
+        check.render(TitleFormat::Github).unwrap(),
+        "::error title=Pull request title::a space must follow the ':'%0ASuggested title: fix: x"
+    );
+    let valid = TitleCheck::run("fix: x", &ScopePolicy::default());
+    assert_eq!(valid.render(TitleFormat::Github).unwrap(), "✓ fix: x");
+}
@@ -0,0 +1,63 @@
+//! Tests for the first-run tour.
+
+use std::fs;
+
+use tempfile::TempDir;
+
+use commit_wizard::tutorial::{Tutorial, STEPS};
+use commit_wizard::types::{ActivePanel, AppState};
+
+#[test]
+fn test_first_run_detection() {
+    let dir = TempDir::new().unwrap();
+    let marker = dir.path().join("state/commit-wizard/tutorial-seen");
+    let config = dir.path().join("config.toml");
+
+    assert!(Tutorial::detect(Some(marker.clone()), Some(&config)).is_some());
+    // Without a place to record it, the tour would show on every run
+    assert!(Tutorial::detect(None, Some(&config)).is_none());
+
+    fs::write(&config, "[ai]\n").unwrap();
+    assert!(Tutorial::detect(Some(marker), Some(&config)).is_none());
+}
+
+#[test]
+fn test_tour_highlights_panels_and_records_when_finished() {
+    let dir = TempDir::new().unwrap();
+    let marker = dir.path().join("state/commit-wizard/tutorial-seen");
+    let tutorial = Tutorial::detect(Some(marker.clone()), None);
+    let mut app = AppState::new(Vec::new()).with_tutorial(tutorial);
+
+    let mut panels = Vec::new();
+    for _ in 1..STEPS.len() {
+        app.advance_tutorial();
+        panels.push(app.active_panel);
+    }
+    assert_eq!(
+        panels[..3],
+        [
+            ActivePanel::Groups,
+            ActivePanel::CommitMessage,
+            ActivePanel::Files
+        ]
+    );
+
+    app.rewind_tutorial();
+    assert_eq!(app.tutorial.as_ref().unwrap().index(), STEPS.len() - 2);
+    assert!(!marker.exists());
+
+    app.advance_tutorial();
+    app.advance_tutorial();
+    assert!(app.tutorial.is_none());
+    assert_eq!(app.active_panel, ActivePanel::Groups);
+    assert!(marker.exists());
+}
+
+#[test]
+fn test_replay_does_not_need_a_marker() {
+    let mut app = AppState::new(Vec::new()).with_tutorial(Some(Tutorial::replay()));
+    assert_eq!(app.tutorial.as_ref().unwrap().step().title, STEPS[0].title);
+
+    app.close_tutorial();
+    assert!(app.tutorial.is_none());
+}
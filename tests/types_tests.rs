@@ -5,7 +5,7 @@
 use git2::Status;
 
 // Import types from the library
-use commit_wizard::types::{AppState, ChangeGroup, ChangedFile, CommitType};
+use commit_wizard::types::{AppState, ChangeGroup, ChangedFile, CommitType, FileDiff};
 
 #[test]
 fn test_commit_type_as_str() {
@@ -154,6 +154,63 @@ fn test_change_group_full_message_without_body() {
     assert!(!msg.contains('\n')); // No newlines when no body
 }
 
+#[test]
+fn test_ticket_placement() {
+    use commit_wizard::types::TicketPlacement;
+
+    let mut group = ChangeGroup::new(
+        CommitType::Fix,
+        Some("api".to_string()),
+        vec![],
+        Some("#12, AB#34".to_string()),
+        "reject empty names".to_string(),
+        vec!["check length".to_string()],
+    );
+
+    group.ticket_placement = TicketPlacement::Footer;
+    assert_eq!(group.header(), "fix(api): reject empty names");
+    assert_eq!(
+        group.full_message(),
+        "fix(api): reject empty names\n\n- check length\n\nRefs: #12, AB#34"
+    );
+
+    // Editing the footer changes the ticket instead of adding a body line
+    group.set_from_commit_text("fix(api): reject empty names\n\n- check length\n\nRefs: #12");
+    assert_eq!(group.ticket.as_deref(), Some("#12"));
+    assert_eq!(group.body_lines, ["check length"]);
+
+    group.body_lines.clear();
+    assert_eq!(
+        group.full_message(),
+        "fix(api): reject empty names\n\nRefs: #12"
+    );
+
+    group.ticket_placement = TicketPlacement::Omit;
+    assert_eq!(group.full_message(), "fix(api): reject empty names");
+}
+
+#[test]
+fn test_closes_footers() {
+    let mut group = ChangeGroup::new(
+        CommitType::Fix,
+        None,
+        vec![],
+        Some("#12".to_string()),
+        "reject empty names".to_string(),
+        vec!["check length".to_string()],
+    );
+    group.footers = vec!["Closes #12: Empty names are accepted".to_string()];
+    assert_eq!(
+        group.full_message(),
+        "fix: #12: reject empty names\n\n- check length\n\nCloses #12: Empty names are accepted"
+    );
+
+    // Removing the footer in the editor drops it from the group
+    group.set_from_commit_text("fix: #12: reject empty names\n\n- check length");
+    assert!(group.footers.is_empty());
+    assert_eq!(group.body_lines, ["check length"]);
+}
+
 #[test]
 fn test_change_group_set_from_commit_text() {
     let mut group = ChangeGroup::new(
@@ -475,6 +532,45 @@ fn test_app_state_diff_viewer() {
     assert_eq!(app.diff_scroll_offset, 0);
 }
 
+#[test]
+fn test_app_state_diff_viewer_preview() {
+    use commit_wizard::types::DiffView;
+
+    let mut app = AppState::new(vec![]);
+    app.show_diff_with_preview(
+        "src/new.rs".to_string(),
+        "+line one\n+line two\n+line three".to_string(),
+        Some("line one\nline two\nline three".to_string()),
+        DiffView::Preview,
+    );
+    assert_eq!(app.diff_view, DiffView::Preview);
+    assert_eq!(app.diff_viewer_content(), "line one\nline two\nline three");
+
+    app.scroll_diff_down();
+    assert_eq!(app.diff_scroll_offset, 1);
+
+    // Toggling resets the scroll position
+    app.toggle_diff_view();
+    assert_eq!(app.diff_view, DiffView::Diff);
+    assert_eq!(app.diff_scroll_offset, 0);
+    assert!(app.diff_viewer_content().starts_with("+line one"));
+
+    app.close_diff();
+    assert!(app.preview_content.is_none());
+    assert_eq!(app.diff_view, DiffView::Diff);
+
+    // Without a preview the viewer stays on the diff
+    app.show_diff_with_preview(
+        "image.png".to_string(),
+        "Binary files differ".to_string(),
+        None,
+        DiffView::Preview,
+    );
+    assert_eq!(app.diff_view, DiffView::Diff);
+    app.toggle_diff_view();
+    assert_eq!(app.diff_view, DiffView::Diff);
+}
+
 #[test]
 fn test_app_state_diff_viewer_empty_content() {
     let mut app = AppState::new(vec![]);
@@ -691,6 +787,39 @@ fn test_change_group_committed_state() {
 
     group.mark_as_committed();
     assert!(group.is_committed());
+
+    // Undoing the commit restores the group
+    group.mark_as_uncommitted();
+    assert!(!group.is_committed());
+}
+
+#[test]
+fn test_change_group_commit_log_keeps_every_attempt() {
+    let mut group = ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        vec![],
+        None,
+        "test".to_string(),
+        vec![],
+    );
+    assert_eq!(group.commit_log_text(), None);
+
+    group
+        .commit_log
+        .push("✗ pre-commit hook failed".to_string());
+    group
+        .commit_log
+        .push("lint ok\n[main abc1234] feat: test\n 1 file changed\n".to_string());
+    group.mark_as_committed();
+    // Undoing keeps the log of the undone commit
+    group.mark_as_uncommitted();
+
+    assert_eq!(
+        group.commit_log_text().unwrap(),
+        "Attempt 1:\n✗ pre-commit hook failed\n\n\
+         Attempt 2:\nlint ok\n[main abc1234] feat: test\n 1 file changed"
+    );
 }
 
 #[test]
@@ -770,3 +899,737 @@ fn test_changed_file_multiple_status_flags() {
     let file = ChangedFile::new("test.rs".to_string(), status);
     assert!(file.is_modified()); // Should still detect modified
 }
+
+#[test]
+fn test_changed_file_diff_stats() {
+    let mut file = ChangedFile::new("test.rs".to_string(), Status::INDEX_MODIFIED);
+    assert_eq!(file.diff_stats(), None);
+
+    file.set_diff_stats(&FileDiff {
+        lines_added: 42,
+        lines_removed: 7,
+        ..Default::default()
+    });
+    assert_eq!(file.diff_stats(), Some((42, 7)));
+
+    // Binary diffs have no meaningful line counts
+    file.set_diff_stats(&FileDiff {
+        is_binary: true,
+        ..Default::default()
+    });
+    assert_eq!(file.diff_stats(), None);
+}
+
+#[test]
+fn test_change_group_aggregate_diff_stats() {
+    let mut a = ChangedFile::new("a.rs".to_string(), Status::INDEX_MODIFIED);
+    a.lines_added = Some(10);
+    a.lines_removed = Some(2);
+    let mut b = ChangedFile::new("b.rs".to_string(), Status::INDEX_NEW);
+    b.lines_added = Some(5);
+    b.lines_removed = Some(0);
+    let binary = ChangedFile::new("logo.png".to_string(), Status::INDEX_NEW);
+
+    let group = ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        vec![a, b, binary.clone()],
+        None,
+        "add stuff".to_string(),
+        vec![],
+    );
+    assert_eq!(group.diff_stats(), Some((15, 2)));
+
+    let group = ChangeGroup::new(
+        CommitType::Chore,
+        None,
+        vec![binary],
+        None,
+        "add logo".to_string(),
+        vec![],
+    );
+    assert_eq!(group.diff_stats(), None);
+}
+
+#[test]
+fn test_app_state_replace_groups_resets_selection() {
+    let mut app = AppState::new(vec![
+        ChangeGroup::new(
+            CommitType::Feat,
+            None,
+            vec![],
+            None,
+            "first".to_string(),
+            vec![],
+        ),
+        ChangeGroup::new(
+            CommitType::Fix,
+            None,
+            vec![],
+            None,
+            "second".to_string(),
+            vec![],
+        ),
+    ]);
+    app.selected_index = 1;
+    app.session_commits.push((1, git2::Oid::zero()));
+    assert!(!app.is_ai_busy());
+
+    app.replace_groups(vec![ChangeGroup::new(
+        CommitType::Docs,
+        None,
+        vec![],
+        None,
+        "only".to_string(),
+        vec![],
+    )]);
+
+    assert_eq!(app.groups.len(), 1);
+    assert_eq!(app.selected_index, 0);
+    assert_eq!(app.selected_file_index, 0);
+    // The commit's group is gone, so it cannot be undone
+    assert!(app.session_commits.is_empty());
+}
+
+#[test]
+fn test_candidate_picker_cycles() {
+    use commit_wizard::types::CandidatePicker;
+
+    let mut picker = CandidatePicker {
+        group_index: 0,
+        candidates: vec![
+            ("first".to_string(), None),
+            ("second".to_string(), Some("body".to_string())),
+            ("third".to_string(), None),
+        ],
+        selected: 0,
+    };
+
+    picker.select_previous();
+    assert_eq!(picker.selected_candidate().unwrap().0, "third");
+    picker.select_next();
+    picker.select_next();
+    assert_eq!(picker.selected_candidate().unwrap().0, "second");
+
+    // An empty picker stays put
+    let mut empty = CandidatePicker::default();
+    empty.select_next();
+    assert!(empty.selected_candidate().is_none());
+}
+
+fn sort_filter_groups() -> Vec<ChangeGroup> {
+    let file = |path: &str| ChangedFile::new(path.to_string(), Status::INDEX_MODIFIED);
+    let mut docs = ChangeGroup::new(
+        CommitType::Docs,
+        None,
+        vec![file("README.md")],
+        None,
+        "update readme".to_string(),
+        vec![],
+    );
+    docs.mark_as_committed();
+    vec![
+        docs,
+        ChangeGroup::new(
+            CommitType::Fix,
+            None,
+            vec![file("src/a.rs")],
+            None,
+            "fix a".to_string(),
+            vec![],
+        ),
+        ChangeGroup::new(
+            CommitType::Feat,
+            None,
+            vec![file("src/b.rs"), file("src/c.rs")],
+            None,
+            "add b".to_string(),
+            vec![],
+        ),
+        ChangeGroup::new(
+            CommitType::Chore,
+            None,
+            vec![file("Cargo.toml")],
+            None,
+            "bump deps".to_string(),
+            vec![],
+        ),
+    ]
+}
+
+#[test]
+fn test_app_state_group_sort_orders() {
+    use commit_wizard::types::GroupSort;
+
+    let mut app = AppState::new(sort_filter_groups());
+    assert_eq!(app.visible_groups(), vec![0, 1, 2, 3]);
+
+    app.group_sort = GroupSort::Type;
+    assert_eq!(app.visible_groups(), vec![2, 1, 0, 3]);
+
+    app.group_sort = GroupSort::Size;
+    assert_eq!(app.visible_groups(), vec![2, 0, 1, 3]);
+
+    app.group_sort = GroupSort::UncommittedFirst;
+    assert_eq!(app.visible_groups(), vec![1, 2, 3, 0]);
+
+    app.cycle_group_sort();
+    assert_eq!(app.group_sort, GroupSort::Original);
+}
+
+#[test]
+fn test_app_state_group_filter_navigation() {
+    use commit_wizard::types::GroupFilter;
+
+    let mut app = AppState::new(sort_filter_groups());
+
+    // The selected committed group is hidden, so selection moves
+    app.cycle_group_filter();
+    assert_eq!(app.group_filter, GroupFilter::Uncommitted);
+    assert_eq!(app.visible_groups(), vec![1, 2, 3]);
+    assert_eq!(app.selected_index, 1);
+
+    app.select_previous();
+    assert_eq!(app.selected_index, 3);
+    app.select_next();
+    assert_eq!(app.selected_index, 1);
+
+    app.cycle_group_filter();
+    assert_eq!(app.group_filter, GroupFilter::FeatFix);
+    assert_eq!(app.visible_groups(), vec![1, 2]);
+    app.select_next();
+    app.select_next();
+    assert_eq!(app.selected_index, 1);
+}
+
+#[test]
+fn test_app_state_group_filter_skips_empty_filters() {
+    use commit_wizard::types::GroupFilter;
+
+    let mut app = AppState::new(vec![ChangeGroup::new(
+        CommitType::Docs,
+        None,
+        vec![],
+        None,
+        "update docs".to_string(),
+        vec![],
+    )]);
+
+    app.cycle_group_filter();
+    assert_eq!(app.group_filter, GroupFilter::Uncommitted);
+    // No feat/fix groups: back to all
+    app.cycle_group_filter();
+    assert_eq!(app.group_filter, GroupFilter::All);
+}
+
+#[test]
+fn test_app_state_applies_configured_sort_and_filter() {
+    use commit_wizard::config::Config;
+    use commit_wizard::types::{GroupFilter, GroupSort};
+
+    let config =
+        Config::from_toml_str("[ui]\nsort = \"uncommitted-first\"\nfilter = \"feat-fix\"\n")
+            .unwrap();
+    let app = AppState::new(sort_filter_groups()).with_config(config);
+
+    assert_eq!(app.group_sort, GroupSort::UncommittedFirst);
+    assert_eq!(app.group_filter, GroupFilter::FeatFix);
+    assert_eq!(app.selected_index, 1);
+}
+
+#[test]
+fn test_confirmations_are_configurable() {
+    use commit_wizard::config::Config;
+    use commit_wizard::types::Confirmation;
+
+    assert!(Config::default().ui.confirm);
+    let config = Config::from_toml_str("[ui]\nconfirm = false\n").unwrap();
+    assert!(!config.ui.confirm);
+
+    assert_eq!(
+        Confirmation::CommitAll { groups: 4 }.question(),
+        "Commit 4 groups?"
+    );
+    assert_eq!(
+        Confirmation::Quit { uncommitted: 2 }.question(),
+        "Quit with 2 uncommitted groups?"
+    );
+}
+
+#[test]
+fn test_commit_progress_continues_after_failure() {
+    use commit_wizard::types::{CommitProgress, CommitState};
+
+    let group = |description: &str| {
+        ChangeGroup::new(
+            CommitType::Feat,
+            None,
+            vec![],
+            None,
+            description.to_string(),
+            vec![],
+        )
+    };
+    let mut progress = CommitProgress::new(&[
+        (0, group("add a")),
+        (1, group("add b")),
+        (3, group("add c")),
+    ]);
+    assert_eq!(progress.current(), Some(0));
+
+    let commit = git2::Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap();
+    progress.record_committed(0, Some(commit), "[main 0123456] feat: add a\n");
+    assert_eq!(progress.current(), Some(1));
+    progress.record_failure("pre-commit hook failed\n");
+
+    assert_eq!(progress.entries[1].state, CommitState::Failed);
+    assert_eq!(progress.current(), None);
+    assert_eq!(progress.remaining(), vec![3]);
+    assert!(progress.awaits_decision());
+    assert_eq!(
+        progress.lines(),
+        vec![
+            "✓ 0123456 feat: add a",
+            "    [main 0123456] feat: add a",
+            "✗ feat: add b",
+            "    pre-commit hook failed",
+            "· feat: add c",
+        ]
+    );
+
+    // Continuing commits the last group; nothing is left to decide
+    progress.running = true;
+    assert_eq!(progress.current(), Some(3));
+    progress.record_committed(3, None, "");
+    progress.running = false;
+    assert!(!progress.awaits_decision());
+    assert_eq!(progress.failed(), 1);
+}
+
+#[test]
+fn test_rename_scope_in_open_groups() {
+    use commit_wizard::config::Config;
+
+    let group = |scope: &str| {
+        ChangeGroup::new(
+            CommitType::Feat,
+            Some(scope.to_string()),
+            vec![],
+            None,
+            "change".to_string(),
+            vec![],
+        )
+    };
+    let mut app = AppState::new(vec![group("users"), group("users"), group("api")]);
+    app.groups[0].mark_as_committed();
+
+    assert_eq!(
+        app.rename_scope("users", "Accounts"),
+        (Some("accounts".to_string()), 1)
+    );
+    assert_eq!(app.groups[0].scope.as_deref(), Some("users"));
+    assert_eq!(app.groups[1].scope.as_deref(), Some("accounts"));
+    assert_eq!(app.rename_scope("api", " "), (None, 1));
+    assert_eq!(app.groups[2].scope, None);
+
+    // Aliases apply to groups replaced later, e.g. by AI grouping
+    let config = Config::from_toml_str("[scope.aliases]\nusers = \"accounts\"\n").unwrap();
+    let mut app = AppState::new(vec![]).with_config(config);
+    app.replace_groups(vec![group("Users"), group("api")]);
+    assert_eq!(app.groups[0].scope.as_deref(), Some("accounts"));
+    assert_eq!(app.groups[1].scope.as_deref(), Some("api"));
+}
+
+#[test]
+fn test_move_selected_group_changes_the_commit_order() {
+    use commit_wizard::types::{GroupFilter, GroupSort};
+
+    let group = |commit_type, path: &str| {
+        ChangeGroup::new(
+            commit_type,
+            None,
+            vec![ChangedFile::new(path.to_string(), Status::INDEX_MODIFIED)],
+            None,
+            "change".to_string(),
+            vec![],
+        )
+    };
+    let mut app = AppState::new(vec![
+        group(CommitType::Feat, "src/lib.rs"),
+        group(CommitType::Docs, "README.md"),
+        group(CommitType::Build, "Cargo.toml"),
+    ]);
+    app.toggle_file_mark(2, 0).unwrap();
+    app.select_group(2);
+
+    app.move_selected_group(true).unwrap();
+    app.move_selected_group(true).unwrap();
+    let order: Vec<CommitType> = app.groups.iter().map(|g| g.commit_type).collect();
+    assert_eq!(
+        order,
+        [CommitType::Build, CommitType::Feat, CommitType::Docs]
+    );
+    assert_eq!(app.selected_index, 0);
+    // The mark moved with its group
+    assert_eq!(app.marks(), [(0, 0)]);
+    assert!(app.move_selected_group(true).is_err());
+
+    // Hidden groups are skipped, sorted views cannot be reordered
+    app.select_group(1);
+    app.group_filter = GroupFilter::FeatFix;
+    assert!(app.move_selected_group(false).is_err());
+    app.group_filter = GroupFilter::All;
+    app.group_sort = GroupSort::Type;
+    assert!(app.move_selected_group(false).is_err());
+    app.group_sort = GroupSort::Original;
+    app.groups[1].mark_as_committed();
+    assert!(app.move_selected_group(false).is_err());
+}
+
+#[test]
+fn test_move_selected_group_skips_committed_neighbours() {
+    let group = |commit_type| {
+        ChangeGroup::new(
+            commit_type,
+            None,
+            vec![],
+            None,
+            "change".to_string(),
+            vec![],
+        )
+    };
+    let mut app = AppState::new(vec![
+        group(CommitType::Feat),
+        group(CommitType::Docs),
+        group(CommitType::Build),
+    ]);
+    app.groups[1].mark_as_committed();
+    app.select_group(2);
+
+    app.move_selected_group(true).unwrap();
+    let order: Vec<CommitType> = app.groups.iter().map(|g| g.commit_type).collect();
+    assert_eq!(
+        order,
+        [CommitType::Build, CommitType::Docs, CommitType::Feat]
+    );
+    // The committed group keeps its place
+    assert!(app.groups[1].is_committed());
+    assert_eq!(app.selected_index, 0);
+
+    app.groups[2].mark_as_committed();
+    assert!(app.move_selected_group(false).is_err());
+    assert_eq!(app.groups[0].commit_type, CommitType::Build);
+}
+
+#[test]
+fn test_format_elapsed() {
+    use commit_wizard::types::format_elapsed;
+    use std::time::Duration;
+
+    assert_eq!(format_elapsed(Duration::from_secs(0)), "0:00");
+    assert_eq!(format_elapsed(Duration::from_secs(59)), "0:59");
+    assert_eq!(format_elapsed(Duration::from_secs(600)), "10:00");
+    assert_eq!(format_elapsed(Duration::from_secs(3600)), "1:00:00");
+}
+
+#[test]
+fn test_set_from_commit_text_updates_scope() {
+    let mut group = ChangeGroup::new(
+        CommitType::Feat,
+        Some("ui".to_string()),
+        vec![],
+        None,
+        "old".to_string(),
+        vec![],
+    );
+
+    group.set_from_commit_text("feat(User Auth ): add login");
+    assert_eq!(group.scope.as_deref(), Some("User Auth "));
+    assert_eq!(group.description, "add login");
+
+    group.set_from_commit_text("feat!: drop login");
+    assert_eq!(group.scope, None);
+
+    // A header of another type leaves the scope alone
+    group.scope = Some("api".to_string());
+    group.set_from_commit_text("fix(core): something");
+    assert_eq!(group.scope.as_deref(), Some("api"));
+}
+
+#[test]
+fn test_app_state_normalizes_scopes() {
+    use commit_wizard::config::Config;
+
+    let group = |scope: &str| {
+        ChangeGroup::new(
+            CommitType::Feat,
+            Some(scope.to_string()),
+            vec![],
+            None,
+            "change".to_string(),
+            vec![],
+        )
+    };
+    let mut app =
+        AppState::new(vec![group("User Auth "), group(" () ")]).with_config(Config::default());
+    assert_eq!(app.groups[0].scope.as_deref(), Some("user-auth"));
+    assert_eq!(app.groups[0].header(), "feat(user-auth): change");
+    assert_eq!(app.groups[1].scope, None);
+
+    app.replace_groups(vec![group("PaymentGateway")]);
+    assert_eq!(app.groups[0].scope.as_deref(), Some("payment-gateway"));
+}
+
+#[test]
+fn test_committed_groups_keep_their_commit() {
+    let group = |description: &str| {
+        ChangeGroup::new(
+            CommitType::Feat,
+            Some("api".to_string()),
+            vec![],
+            None,
+            description.to_string(),
+            vec![],
+        )
+    };
+    let mut app = AppState::new(vec![
+        group("add users"),
+        group("add posts"),
+        group("add tags"),
+    ]);
+    assert_eq!(app.session_summary().totals.committed, 0);
+
+    let id = git2::Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap();
+    app.groups[0].mark_as_committed_as(id);
+    app.groups[2].mark_as_committed();
+    assert_eq!(app.groups[0].committed_as, Some(id));
+    assert_eq!(app.groups[0].short_commit().as_deref(), Some("0123456"));
+    assert_eq!(app.groups[2].short_commit(), None);
+
+    let summary = app.session_summary();
+    assert_eq!(summary.totals.committed, 2);
+    assert_eq!(
+        summary.committed[0].commit.as_deref(),
+        Some("0123456789abcdef0123456789abcdef01234567")
+    );
+    assert_eq!(summary.committed[1].commit, None);
+
+    app.groups[0].mark_as_uncommitted();
+    assert_eq!(app.groups[0].committed_as, None);
+}
+
+#[test]
+fn test_empty_descriptions_are_generated_or_kept() {
+    let group = |description: &str| {
+        ChangeGroup::new(
+            CommitType::Fix,
+            None,
+            vec![ChangedFile::new(
+                "src/login.rs".to_string(),
+                Status::WT_MODIFIED,
+            )],
+            None,
+            description.to_string(),
+            vec![],
+        )
+    };
+
+    let mut app = AppState::new(vec![group(""), group("handle expired tokens")])
+        .with_config(commit_wizard::config::Config::default());
+    assert_eq!(app.groups[0].header(), "fix: fix login.rs");
+    assert!(app.groups[0].description_generated);
+    assert!(!app.groups[1].description_generated);
+
+    // Reviewing the message in the editor clears the mark
+    app.groups[0].set_from_commit_text("fix: reject expired sessions");
+    assert!(!app.groups[0].description_generated);
+
+    let mut config = commit_wizard::config::Config::default();
+    config.message.empty_description = commit_wizard::validation::EmptyDescription::Block;
+    let app = AppState::new(vec![group(" ")]).with_config(config);
+    assert!(app.groups[0].has_empty_description());
+    assert!(!app.groups[0].description_generated);
+}
+
+#[test]
+fn test_app_state_remove_file_from_group() {
+    let files = vec![
+        ChangedFile::new("src/a.rs".to_string(), Status::WT_MODIFIED),
+        ChangedFile::new("src/b.rs".to_string(), Status::WT_MODIFIED),
+    ];
+    let mut committed = ChangeGroup::new(
+        CommitType::Docs,
+        None,
+        vec![ChangedFile::new(
+            "README.md".to_string(),
+            Status::WT_MODIFIED,
+        )],
+        None,
+        "describe".to_string(),
+        vec![],
+    );
+    committed.mark_as_committed();
+    let feat = ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        files.clone(),
+        None,
+        "add".to_string(),
+        vec![],
+    );
+    let mut app = AppState::new(vec![committed, feat]);
+    app.selected_index = 1;
+    app.selected_file_index = 1;
+
+    assert!(app.remove_file_from_group(0, 0).is_err());
+    assert!(app.remove_file_from_group(1, 5).is_err());
+    assert_eq!(app.remove_file_from_group(1, 1).unwrap().path, "src/b.rs");
+    assert_eq!(app.selected_file_index, 0);
+    assert!(app.is_orphaned("src/b.rs"));
+    assert_eq!(app.groups[1].files.len(), 1);
+
+    // The last file stays; the group can be skipped instead
+    assert!(app.remove_file_from_group(1, 0).is_err());
+
+    // Regrouping keeps left-out files out of the groups
+    app.replace_groups(vec![ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        files,
+        None,
+        "add".to_string(),
+        vec![],
+    )]);
+    assert_eq!(app.groups.len(), 1);
+    assert_eq!(app.groups[0].files.len(), 1);
+    assert_eq!(app.groups[0].files[0].path, "src/a.rs");
+}
+
+#[test]
+fn test_app_state_marked_files() {
+    let file = |path: &str| ChangedFile::new(path.to_string(), Status::WT_MODIFIED);
+    let group = |paths: &[&str]| {
+        ChangeGroup::new(
+            CommitType::Feat,
+            None,
+            paths.iter().map(|p| file(p)).collect(),
+            None,
+            "add".to_string(),
+            vec![],
+        )
+    };
+    let mut app = AppState::new(vec![
+        group(&["a.rs", "b.rs", "c.rs"]),
+        group(&["d.rs"]),
+        group(&["e.rs", "f.rs"]),
+    ]);
+
+    assert!(app.toggle_file_mark(0, 1).unwrap());
+    assert!(app.toggle_file_mark(0, 2).unwrap());
+    assert!(!app.toggle_file_mark(0, 2).unwrap());
+    assert!(app.toggle_file_mark(0, 9).is_err());
+    assert!(app.toggle_file_mark(2, 0).unwrap());
+    assert_eq!(app.marks(), [(0, 1), (2, 0)]);
+    assert_eq!(app.marked_count(0), 1);
+
+    // Moving into the second group
+    assert_eq!(app.move_marked_files(1).unwrap(), 2);
+    let paths = |app: &AppState, g: usize| -> Vec<String> {
+        app.groups[g].files.iter().map(|f| f.path.clone()).collect()
+    };
+    assert_eq!(paths(&app, 0), ["a.rs", "c.rs"]);
+    assert_eq!(paths(&app, 1), ["d.rs", "b.rs", "e.rs"]);
+    assert_eq!(paths(&app, 2), ["f.rs"]);
+    assert!(app.marks().is_empty());
+    assert!(app.move_marked_files(1).is_err());
+
+    // A group cannot be emptied
+    app.toggle_file_mark(2, 0).unwrap();
+    app.toggle_file_mark(1, 0).unwrap();
+    assert!(app.remove_marked_files().is_err());
+    assert_eq!(paths(&app, 2), ["f.rs"]);
+
+    // Leaving out the marked files
+    app.toggle_file_mark(2, 0).unwrap();
+    app.toggle_file_mark(1, 2).unwrap();
+    assert_eq!(app.remove_marked_files().unwrap(), 2);
+    assert_eq!(paths(&app, 1), ["b.rs"]);
+    assert!(app.is_orphaned("d.rs") && app.is_orphaned("e.rs"));
+
+    // Marks follow files when one is taken out before them
+    app.toggle_file_mark(0, 1).unwrap();
+    app.groups[0].files.push(file("g.rs"));
+    app.remove_file_from_group(0, 0).unwrap();
+    assert_eq!(app.marks(), [(0, 0)]);
+    assert_eq!(app.groups[0].files[0].path, "c.rs");
+
+    // Committed groups take no marks
+    app.groups[1].mark_as_committed();
+    assert!(app.toggle_file_mark(1, 0).is_err());
+    assert!(app.move_marked_files(1).is_err());
+}
+
+#[test]
+fn test_app_state_search() {
+    use commit_wizard::types::ActivePanel;
+
+    let group = |description: &str, paths: &[&str]| {
+        ChangeGroup::new(
+            CommitType::Feat,
+            None,
+            paths
+                .iter()
+                .map(|p| ChangedFile::new(p.to_string(), Status::WT_MODIFIED))
+                .collect(),
+            None,
+            description.to_string(),
+            vec![],
+        )
+    };
+    let mut app = AppState::new(vec![
+        group("add login", &["src/auth/login.rs", "README.md"]),
+        group("update docs", &["docs/guide.md"]),
+        group("tune login form", &["web/Login.tsx", "web/form.tsx"]),
+    ]);
+    assert!(app.search_matches().is_empty());
+    assert!(!app.next_match());
+
+    // Typing jumps to the first match
+    app.set_search("LOGIN".to_string());
+    assert_eq!(
+        app.search_matches(),
+        [(0, None), (0, Some(0)), (2, None), (2, Some(0))]
+    );
+    assert_eq!(app.selected_index, 0);
+    assert_eq!(app.active_panel, ActivePanel::Groups);
+
+    assert!(app.next_match());
+    assert_eq!(app.active_panel, ActivePanel::Files);
+    assert_eq!(app.selected_file().unwrap().path, "src/auth/login.rs");
+    app.next_match();
+    assert_eq!(
+        (app.selected_index, app.active_panel),
+        (2, ActivePanel::Groups)
+    );
+    app.next_match();
+    assert_eq!(app.selected_file().unwrap().path, "web/Login.tsx");
+
+    // Wrapping around in both directions
+    app.next_match();
+    assert_eq!(
+        (app.selected_index, app.active_panel),
+        (0, ActivePanel::Groups)
+    );
+    app.previous_match();
+    assert_eq!(app.selected_file().unwrap().path, "web/Login.tsx");
+
+    // Refining the query keeps a selection that still matches
+    app.set_search("login.tsx".to_string());
+    assert_eq!(app.selected_file().unwrap().path, "web/Login.tsx");
+    app.set_search("guide".to_string());
+    assert_eq!(app.selected_file().unwrap().path, "docs/guide.md");
+
+    app.clear_search();
+    assert!(app.search.is_none());
+    assert!(!app.matches_search("docs/guide.md"));
+}
@@ -0,0 +1,110 @@
+//! Tests for the opt-in update check.
+#![cfg(feature = "update-check")]
+
+use std::fs;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use mockito::Server;
+use tempfile::TempDir;
+
+use commit_wizard::config::{NetworkSettings, UpdateConfig};
+use commit_wizard::update::{check_for_update, latest_version, update_hint, CachedRelease};
+
+const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+fn at(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+/// Connects directly, whatever proxy the environment sets
+fn direct() -> NetworkSettings {
+    NetworkSettings {
+        proxy: Some(String::new()),
+        ..NetworkSettings::default()
+    }
+}
+
+#[test]
+fn test_update_hint_compares_versions() {
+    assert!(update_hint("0.1.0", "0.1.1").is_some());
+    assert!(update_hint("0.9.0", "0.10.0").is_some());
+    assert!(update_hint("1.0.0", "v0.9.9").is_none());
+    assert!(update_hint("1.0.0", "1.0.0-rc.1").is_none());
+    assert!(update_hint("1.0.0", "nightly").is_none());
+}
+
+#[test]
+fn test_latest_version_is_fetched_and_cached() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("GET", "/releases/latest")
+        .with_status(200)
+        .with_body(r#"{"tag_name": "v0.3.0", "name": "0.3.0"}"#)
+        .expect(1)
+        .create();
+    let url = format!("{}/releases/latest", server.url());
+    let dir = TempDir::new().unwrap();
+    let cache = dir.path().join("nested").join("latest-release.json");
+
+    assert_eq!(
+        latest_version(&url, Some(&cache), WEEK, at(1_000), &direct()).unwrap(),
+        "0.3.0"
+    );
+    let cached: CachedRelease = serde_json::from_str(&fs::read_to_string(&cache).unwrap()).unwrap();
+    assert_eq!(cached.checked_at, 1_000);
+
+    // Within a week the cache answers
+    assert_eq!(
+        latest_version(&url, Some(&cache), WEEK, at(5_000), &direct()).unwrap(),
+        "0.3.0"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_stale_cache_asks_again() {
+    let mut server = Server::new();
+    let mock = server
+        .mock("GET", "/latest")
+        .with_status(200)
+        .with_body(r#"{"tag_name": "0.4.0"}"#)
+        .create();
+    let dir = TempDir::new().unwrap();
+    let cache = dir.path().join("latest-release.json");
+    fs::write(&cache, r#"{"checked_at": 0, "version": "0.3.0"}"#).unwrap();
+
+    let now = at(WEEK.as_secs() + 1);
+    let url = format!("{}/latest", server.url());
+    assert_eq!(
+        latest_version(&url, Some(&cache), WEEK, now, &direct()).unwrap(),
+        "0.4.0"
+    );
+    mock.assert();
+}
+
+#[test]
+fn test_failed_check_gives_no_hint() {
+    let mut server = Server::new();
+    server.mock("GET", "/latest").with_status(503).create();
+    let url = format!("{}/latest", server.url());
+    assert!(latest_version(&url, None, WEEK, at(0), &direct()).is_err());
+
+    let config = UpdateConfig {
+        check: true,
+        interval_days: 0,
+        url: Some(url),
+    };
+    assert_eq!(check_for_update(&config, &direct()), None);
+}
+
+#[test]
+fn test_disabled_check_sends_nothing() {
+    let mut server = Server::new();
+    let mock = server.mock("GET", "/latest").expect(0).create();
+    let config = UpdateConfig {
+        url: Some(format!("{}/latest", server.url())),
+        ..UpdateConfig::default()
+    };
+    assert_eq!(check_for_update(&config, &direct()), None);
+    mock.assert();
+}
@@ -1,15 +1,17 @@
 //! Tests for commit group validation functions.
 
+use commit_wizard::config::ScopeConfig;
 use commit_wizard::copilot::validate_no_duplicate_files;
 use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+use commit_wizard::validation::{
+    apply_scope_fix, transliterate_ascii, transliterate_header, HeaderCharset, ScopeCasing,
+    ScopeFix, ScopePolicy, ScopeViolationKind,
+};
 use git2::Status;
 
 /// Helper to create a test file
 fn test_file(path: &str) -> ChangedFile {
-    ChangedFile {
-        path: path.to_string(),
-        status: Status::INDEX_MODIFIED,
-    }
+    ChangedFile::new(path.to_string(), Status::INDEX_MODIFIED)
 }
 
 /// Helper to create a test group
@@ -226,3 +228,224 @@ fn test_validate_case_sensitive_paths() {
     // For now, we treat them as different files (no error expected)
     assert!(validate_no_duplicate_files(&groups).is_ok());
 }
+
+/// Helper to create a policy requiring scopes for feat/fix with a kebab-case pattern
+fn test_policy() -> ScopePolicy {
+    ScopePolicy::from_config(&ScopeConfig {
+        required_for: vec!["feat".to_string(), "fix".to_string()],
+        forbidden_for: vec!["docs".to_string()],
+        pattern: Some("^[a-z-]+$".to_string()),
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_scope_policy_empty_allows_everything() {
+    let policy = ScopePolicy::from_config(&ScopeConfig::default()).unwrap();
+    assert!(policy.is_empty());
+    assert!(policy
+        .validate(&test_group(CommitType::Feat, None, vec!["src/main.rs"]))
+        .is_none());
+}
+
+#[test]
+fn test_scope_policy_rejects_unknown_commit_type() {
+    let config = ScopeConfig {
+        required_for: vec!["feature".to_string()],
+        ..Default::default()
+    };
+    assert!(ScopePolicy::from_config(&config).is_err());
+}
+
+#[test]
+fn test_scope_policy_rejects_invalid_pattern() {
+    let config = ScopeConfig {
+        pattern: Some("^[a-z".to_string()),
+        ..Default::default()
+    };
+    assert!(ScopePolicy::from_config(&config).is_err());
+}
+
+#[test]
+fn test_scope_required_suggests_scope_from_paths() {
+    let group = test_group(
+        CommitType::Fix,
+        None,
+        vec!["Web_UI/app.ts", "Web_UI/view.ts", "api/users.rs"],
+    );
+    let violation = test_policy().validate(&group).unwrap();
+    assert_eq!(violation.kind, ScopeViolationKind::Missing);
+    assert_eq!(
+        violation.suggestion,
+        Some(ScopeFix::Set("web-ui".to_string()))
+    );
+
+    // Types without a rule need no scope
+    assert!(test_policy()
+        .validate(&test_group(CommitType::Chore, None, vec!["Cargo.toml"]))
+        .is_none());
+}
+
+#[test]
+fn test_scope_forbidden_suggests_removal() {
+    let mut group = test_group(CommitType::Docs, Some("readme"), vec!["README.md"]);
+    let violation = test_policy().validate(&group).unwrap();
+    assert_eq!(violation.kind, ScopeViolationKind::Forbidden);
+    assert_eq!(violation.suggestion, Some(ScopeFix::Remove));
+
+    apply_scope_fix(&mut group, violation.suggestion.as_ref().unwrap());
+    assert!(group.scope.is_none());
+    assert!(test_policy().validate(&group).is_none());
+}
+
+#[test]
+fn test_scope_pattern_mismatch() {
+    let mut group = test_group(CommitType::Feat, Some("API_v2"), vec!["api/users.rs"]);
+    let violation = test_policy().validate(&group).unwrap();
+    assert!(matches!(
+        violation.kind,
+        ScopeViolationKind::InvalidFormat { .. }
+    ));
+    assert!(violation.to_string().contains("API_v2"));
+
+    apply_scope_fix(&mut group, violation.suggestion.as_ref().unwrap());
+    assert_eq!(group.scope.as_deref(), Some("api"));
+    assert!(test_policy().validate(&group).is_none());
+}
+
+#[test]
+fn test_scope_required_without_derivable_suggestion() {
+    // Root-level files give no directory to derive a scope from
+    let group = test_group(CommitType::Feat, None, vec!["README.md"]);
+    let violation = test_policy().validate(&group).unwrap();
+    assert_eq!(violation.suggestion, None);
+}
+
+#[test]
+fn test_scope_casing_normalizes() {
+    let kebab = |s: &str| ScopeCasing::Kebab.normalize(s);
+    assert_eq!(kebab("User Auth ").as_deref(), Some("user-auth"));
+    assert_eq!(kebab("  api ").as_deref(), Some("api"));
+    assert_eq!(kebab("payment_gateway").as_deref(), Some("payment-gateway"));
+    assert_eq!(kebab("oauth2Client").as_deref(), Some("oauth2-client"));
+    assert_eq!(kebab("core): x (").as_deref(), Some("core-x"));
+    assert_eq!(kebab("   "), None);
+
+    assert_eq!(
+        ScopeCasing::Lower.normalize("Web UI/Forms").as_deref(),
+        Some("web-ui/forms")
+    );
+    assert_eq!(
+        ScopeCasing::Preserve.normalize(" userAuth, v2 ").as_deref(),
+        Some("userAuth-v2")
+    );
+}
+
+#[test]
+fn test_scope_suggestion_uses_configured_casing() {
+    let policy = ScopePolicy::from_config(&ScopeConfig {
+        required_for: vec!["fix".to_string()],
+        casing: ScopeCasing::Lower,
+        ..Default::default()
+    })
+    .unwrap();
+    let group = test_group(CommitType::Fix, None, vec!["Web_UI/app.ts"]);
+
+    let violation = policy.validate(&group).unwrap();
+    assert_eq!(
+        violation.suggestion,
+        Some(ScopeFix::Set("web_ui".to_string()))
+    );
+}
+
+#[test]
+fn test_scope_not_allowed_suggests_closest_allowed_scope() {
+    let policy = ScopePolicy::from_config(&ScopeConfig {
+        allowed: vec!["auth".to_string(), "Billing".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(!policy.is_empty());
+
+    let group = test_group(CommitType::Fix, Some("atuh"), vec!["src/login.rs"]);
+    let violation = policy.validate(&group).unwrap();
+    assert_eq!(
+        violation.kind,
+        ScopeViolationKind::NotAllowed {
+            allowed: vec!["auth".to_string(), "billing".to_string()]
+        }
+    );
+    assert_eq!(
+        violation.suggestion,
+        Some(ScopeFix::Set("auth".to_string()))
+    );
+    assert!(violation.to_string().contains("auth, billing"));
+
+    let group = test_group(CommitType::Fix, Some("billing"), vec!["src/login.rs"]);
+    assert!(policy.validate(&group).is_none());
+    let group = test_group(CommitType::Docs, None, vec!["README.md"]);
+    assert!(policy.validate(&group).is_none());
+}
+
+#[test]
+fn test_scope_not_allowed_falls_back_to_allowed_file_scope() {
+    let policy = ScopePolicy::from_config(&ScopeConfig {
+        required_for: vec!["feat".to_string()],
+        allowed: vec!["api".to_string(), "ui".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let group = test_group(CommitType::Feat, Some("database"), vec!["src/api/users.rs"]);
+    let violation = policy.validate(&group).unwrap();
+    assert_eq!(violation.suggestion, Some(ScopeFix::Set("api".to_string())));
+
+    // A missing scope is only suggested from files when it is allowed
+    let group = test_group(CommitType::Feat, None, vec!["src/models/user.rs"]);
+    let violation = policy.validate(&group).unwrap();
+    assert_eq!(violation.kind, ScopeViolationKind::Missing);
+    assert_eq!(violation.suggestion, None);
+}
+
+#[test]
+fn test_transliterate_ascii() {
+    assert_eq!(transliterate_ascii("‚a‘ – b — c‐d"), "'a' - b - c-d");
+    assert_eq!(
+        transliterate_ascii("«Öl» über Straße"),
+        "\"Oel\" ueber Strasse"
+    );
+    assert_eq!(transliterate_ascii("café crème, Łódź"), "cafe creme, Lodz");
+    assert_eq!(transliterate_ascii("wait\u{a0}for it…"), "wait for it...");
+    assert_eq!(
+        transliterate_ascii("plain `ascii` stays"),
+        "plain `ascii` stays"
+    );
+}
+
+#[test]
+fn test_transliterate_header() {
+    let mut group = test_group(CommitType::Feat, Some("größe"), vec!["src/lib.rs"]);
+    group.description = "add “sizes” 📏 and 🚀".to_string();
+    group.body_lines = vec!["- Größe in “cm”".to_string()];
+
+    let left = transliterate_header(&mut group);
+    assert_eq!(left, ['📏', '🚀']);
+    assert_eq!(group.scope.as_deref(), Some("groesse"));
+    assert_eq!(group.description, "add \"sizes\" 📏 and 🚀");
+    // The body keeps its characters
+    assert_eq!(group.body_lines, ["- Größe in “cm”"]);
+
+    group.description = "add sizes".to_string();
+    assert!(transliterate_header(&mut group).is_empty());
+}
+
+#[test]
+fn test_header_charset_config() {
+    use commit_wizard::config::Config;
+
+    assert_eq!(Config::default().message.charset, HeaderCharset::Unicode);
+    let config = Config::from_toml_str("[message]\ncharset = \"ascii\"\n").unwrap();
+    assert_eq!(config.message.charset, HeaderCharset::Ascii);
+    assert!(Config::from_toml_str("[message]\ncharset = \"latin1\"\n").is_err());
+}
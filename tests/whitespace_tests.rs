@@ -0,0 +1,90 @@
+//! Tests for whitespace and line-ending checks.
+
+use std::fs;
+use std::path::Path;
+
+use git2::{Repository, Signature};
+use tempfile::TempDir;
+
+use commit_wizard::git::collect_changed_files;
+use commit_wizard::whitespace::{check_diff, check_files, fix_issues, WhitespaceIssueKind};
+
+/// Helper to create a repository with one committed file
+fn create_test_repo(initial: &str) -> TempDir {
+    let tmp = TempDir::new().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+
+    fs::write(tmp.path().join("file.txt"), initial).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+
+    let tree_id = index.write_tree().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .unwrap();
+
+    tmp
+}
+
+#[test]
+fn test_check_diff_trailing_whitespace_line_numbers() {
+    let patch = "@@ -1,2 +1,3 @@\n context\n+added  \n-removed \n+clean\n+tab\t\n";
+    let issues = check_diff("a.rs", patch);
+
+    let lines: Vec<_> = issues
+        .iter()
+        .filter(|i| i.kind == WhitespaceIssueKind::TrailingWhitespace)
+        .map(|i| i.line)
+        .collect();
+    assert_eq!(lines, vec![Some(2), Some(4)]);
+}
+
+#[test]
+fn test_check_diff_ignores_removed_and_context_lines() {
+    let patch = "@@ -1,2 +1,2 @@\n context   \n-old   \n+new\n";
+    assert!(check_diff("a.rs", patch).is_empty());
+}
+
+#[test]
+fn test_check_diff_mixed_line_endings() {
+    let patch = "@@ -0,0 +1,2 @@\n+crlf\r\n+lf\n";
+    let issues = check_diff("a.rs", patch);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, WhitespaceIssueKind::MixedLineEndings);
+}
+
+#[test]
+fn test_check_diff_missing_final_newline() {
+    let patch = "@@ -1 +1 @@\n-old\n+new\n\\ No newline at end of file\n";
+    let issues = check_diff("a.rs", patch);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].kind, WhitespaceIssueKind::MissingFinalNewline);
+
+    // Marker after a removed line refers to the old file only
+    let patch = "@@ -1 +1 @@\n-old\n\\ No newline at end of file\n+new\n";
+    assert!(check_diff("a.rs", patch).is_empty());
+}
+
+#[test]
+fn test_check_files_and_fix_roundtrip() {
+    let tmp = create_test_repo("keep me   \n");
+    let repo = Repository::open(tmp.path()).unwrap();
+
+    fs::write(tmp.path().join("file.txt"), "keep me   \nnew line \nlast").unwrap();
+
+    let files = collect_changed_files(&repo, false).unwrap();
+    let issues = check_files(&repo, &files).unwrap();
+    let kinds: Vec<_> = issues.iter().map(|i| i.kind).collect();
+    assert!(kinds.contains(&WhitespaceIssueKind::TrailingWhitespace));
+    assert!(kinds.contains(&WhitespaceIssueKind::MissingFinalNewline));
+
+    let fixed = fix_issues(tmp.path(), &issues).unwrap();
+    assert_eq!(fixed, 1);
+
+    // Pre-existing trailing whitespace on unchanged lines is left alone
+    let content = fs::read_to_string(tmp.path().join("file.txt")).unwrap();
+    assert_eq!(content, "keep me   \nnew line\nlast\n");
+    assert!(check_files(&repo, &files).unwrap().is_empty());
+}
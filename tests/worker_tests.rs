@@ -0,0 +1,216 @@
+//! Tests for background AI workers.
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use git2::Status;
+
+use commit_wizard::provider::{cancel_flag, AiProvider, ChatMessage};
+use commit_wizard::types::{ChangeGroup, ChangedFile, CommitType};
+use commit_wizard::worker::{AiJob, AiOutcome, AiWorker};
+
+/// Provider that answers once the test releases it.
+struct GatedProvider {
+    gate: Mutex<Receiver<Option<String>>>,
+}
+
+impl GatedProvider {
+    fn new() -> (Arc<Self>, Sender<Option<String>>) {
+        let (sender, receiver) = mpsc::channel();
+        let provider = Arc::new(Self {
+            gate: Mutex::new(receiver),
+        });
+        (provider, sender)
+    }
+}
+
+impl AiProvider for GatedProvider {
+    fn name(&self) -> &str {
+        "gated"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn complete(&self, _prompt: &str) -> Result<String> {
+        match self.gate.lock().unwrap().recv()? {
+            Some(response) => Ok(response),
+            None => panic!("provider crashed"),
+        }
+    }
+}
+
+fn wait_for_result(worker: &AiWorker) -> Option<AiOutcome> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        if let Some(outcome) = worker.try_result() {
+            return Some(outcome);
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    None
+}
+
+fn group() -> ChangeGroup {
+    ChangeGroup::new(
+        CommitType::Feat,
+        None,
+        vec![ChangedFile::new(
+            "src/lib.rs".to_string(),
+            Status::INDEX_MODIFIED,
+        )],
+        None,
+        "placeholder".to_string(),
+        vec![],
+    )
+}
+
+#[test]
+fn test_grouping_result_arrives_without_blocking() {
+    let (provider, gate) = GatedProvider::new();
+    let files = vec![ChangedFile::new(
+        "src/lib.rs".to_string(),
+        Status::INDEX_MODIFIED,
+    )];
+
+    let worker = AiWorker::spawn_grouping(provider, files, None, HashMap::new());
+    assert_eq!(worker.job(), AiJob::Grouping);
+    assert_eq!(worker.provider_name(), "gated");
+    assert!(worker.try_result().is_none());
+
+    let response = r#"[{"type": "fix", "scope": "core", "description": "handle empty input", "files": ["src/lib.rs"], "body_lines": []}]"#;
+    gate.send(Some(response.to_string())).unwrap();
+
+    match wait_for_result(&worker) {
+        Some(AiOutcome::Groups(Ok(groups))) => {
+            assert_eq!(groups.len(), 1);
+            assert_eq!(groups[0].commit_type, CommitType::Fix);
+            assert_eq!(groups[0].description, "handle empty input");
+        }
+        other => panic!("unexpected outcome: {:?}", other),
+    }
+}
+
+#[test]
+fn test_message_result_carries_group_index() {
+    let (provider, gate) = GatedProvider::new();
+
+    let worker = AiWorker::spawn_message(provider, 3, group(), None, 1);
+    assert_eq!(worker.job(), AiJob::Message(3));
+
+    gate.send(Some("add parser support".to_string())).unwrap();
+
+    match wait_for_result(&worker) {
+        Some(AiOutcome::Message { index, result }) => {
+            assert_eq!(index, 3);
+            assert_eq!(result.unwrap()[0].0, "add parser support");
+        }
+        other => panic!("unexpected outcome: {:?}", other),
+    }
+}
+
+#[test]
+fn test_cancelled_worker_discards_result() {
+    let (provider, gate) = GatedProvider::new();
+    let observer = provider.clone();
+
+    let worker = AiWorker::spawn_message(provider, 0, group(), None, 1);
+    worker.cancel();
+    gate.send(Some("too late".to_string())).unwrap();
+
+    // The worker thread drops its provider handle once it has finished
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Arc::strong_count(&observer) > 1 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(Arc::strong_count(&observer), 1);
+}
+
+#[test]
+fn test_panicking_provider_reports_error() {
+    let (provider, gate) = GatedProvider::new();
+
+    let worker = AiWorker::spawn_message(provider, 1, group(), None, 1);
+    gate.send(None).unwrap();
+
+    match wait_for_result(&worker) {
+        Some(AiOutcome::Message { index, result }) => {
+            assert_eq!(index, 1);
+            assert!(result.is_err());
+        }
+        other => panic!("unexpected outcome: {:?}", other),
+    }
+}
+
+#[test]
+fn test_refine_returns_conversation() {
+    let (provider, gate) = GatedProvider::new();
+    let conversation = vec![
+        ChatMessage::user("Generate a message"),
+        ChatMessage::assistant("add parser support"),
+        ChatMessage::user("shorter"),
+    ];
+
+    let worker = AiWorker::spawn_refine(provider, 2, conversation.clone());
+    assert_eq!(worker.job(), AiJob::Refine(2));
+
+    gate.send(Some("add parser".to_string())).unwrap();
+
+    match wait_for_result(&worker) {
+        Some(AiOutcome::Refined {
+            index,
+            conversation: returned,
+            result,
+        }) => {
+            assert_eq!(index, 2);
+            assert_eq!(returned, conversation);
+            assert_eq!(result.unwrap().0, "add parser");
+        }
+        other => panic!("unexpected outcome: {:?}", other),
+    }
+}
+
+/// Provider that waits until its request is cancelled, like a CLI that
+/// would otherwise keep running.
+struct CancellableProvider {
+    stopped: Mutex<Sender<bool>>,
+}
+
+impl AiProvider for CancellableProvider {
+    fn name(&self) -> &str {
+        "cancellable"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn complete(&self, _prompt: &str) -> Result<String> {
+        let cancel = cancel_flag();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !cancel.load(Ordering::Relaxed) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let cancelled = cancel.load(Ordering::Relaxed);
+        self.stopped.lock().unwrap().send(cancelled).unwrap();
+        anyhow::bail!("cancelled")
+    }
+}
+
+#[test]
+fn test_cancel_reaches_the_running_provider_call() {
+    let (sender, stopped) = mpsc::channel();
+    let provider = Arc::new(CancellableProvider {
+        stopped: Mutex::new(sender),
+    });
+
+    let worker = AiWorker::spawn_refine(provider, 0, vec![ChatMessage::user("shorter")]);
+    worker.cancel();
+
+    assert!(stopped.recv_timeout(Duration::from_secs(10)).unwrap());
+}